@@ -0,0 +1,282 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Benchmarks for each phase of compilation, run over a corpus of samples and of generated
+//! programs of increasing size, so that a regression can be attributed to the phase that caused it.
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use qsc::{
+    compile::{self, check_runtime_capabilities, lower, parse, resolve, typeck},
+    interpret::Interpreter,
+};
+use qsc_codegen::{qir_base::BaseProfile, target::TargetBackend};
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::{run_default_passes, PackageType};
+use std::fmt::Write;
+
+/// Samples that can be run by the base profile, so that every phase can be measured on them.
+const SAMPLES: &[(&str, &str)] = &[
+    (
+        "BellState",
+        include_str!("../../../samples/algorithms/BellState.qs"),
+    ),
+    ("GHZ", include_str!("../../../samples/algorithms/GHZ.qs")),
+    (
+        "BernsteinVaziraniNISQ",
+        include_str!("../../../samples/algorithms/BernsteinVaziraniNISQ.qs"),
+    ),
+    (
+        "DeutschJozsaNISQ",
+        include_str!("../../../samples/algorithms/DeutschJozsaNISQ.qs"),
+    ),
+    (
+        "HiddenShiftNISQ",
+        include_str!("../../../samples/algorithms/HiddenShiftNISQ.qs"),
+    ),
+];
+
+/// The number of operations in each of the generated programs.
+const GENERATED_SIZES: &[usize] = &[10, 100, 1000];
+
+/// A program made of `size` operations that are all called from the entry point.
+fn generated(size: usize) -> String {
+    let mut program =
+        String::from("namespace Generated {\n    open Microsoft.Quantum.Measurement;\n");
+    for i in 0..size {
+        write!(
+            program,
+            "
+    operation Op{i}(qs : Qubit[]) : Unit {{
+        let angle = {i}.0 * 0.001;
+        for q in qs {{
+            H(q);
+            Rz(angle, q);
+        }}
+        CNOT(qs[{i} % 4], qs[({i} + 1) % 4]);
+    }}
+"
+        )
+        .expect("writing to string should succeed");
+    }
+
+    program.push_str(
+        "
+    @EntryPoint()
+    operation Main() : Result[] {
+        use qs = Qubit[4];
+",
+    );
+    for i in 0..size {
+        writeln!(program, "        Op{i}(qs);").expect("writing to string should succeed");
+    }
+    program.push_str("        MResetEachZ(qs)\n    }\n}\n");
+    program
+}
+
+/// The programs that every phase is measured on, by name.
+fn corpus() -> Vec<(String, String)> {
+    SAMPLES
+        .iter()
+        .map(|&(name, source)| (name.to_string(), source.to_string()))
+        .chain(
+            GENERATED_SIZES
+                .iter()
+                .map(|&size| (format!("Generated{size}"), generated(size))),
+        )
+        .collect()
+}
+
+fn sources(name: &str, source: &str) -> SourceMap {
+    SourceMap::new([(format!("{name}.qs").into(), source.into())], None)
+}
+
+/// A package store with the core and standard libraries, which every program depends on.
+fn store() -> (PackageStore, PackageId) {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    (store, std)
+}
+
+pub fn parse_phase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            let sources = sources(&name, &source);
+            b.iter(|| parse(&sources, RuntimeCapabilityFlags::all()));
+        });
+    }
+    group.finish();
+}
+
+pub fn resolve_phase(c: &mut Criterion) {
+    let (store, std) = store();
+    let mut group = c.benchmark_group("resolve");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            let parsed = parse(&sources(&name, &source), RuntimeCapabilityFlags::all());
+            b.iter(|| resolve(&store, &[std], &parsed));
+        });
+    }
+    group.finish();
+}
+
+pub fn typeck_phase(c: &mut Criterion) {
+    let (store, std) = store();
+    let mut group = c.benchmark_group("typeck");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            let parsed = parse(&sources(&name, &source), RuntimeCapabilityFlags::all());
+            let resolved = resolve(&store, &[std], &parsed);
+            b.iter(|| typeck(&store, &[std], &parsed, &resolved));
+        });
+    }
+    group.finish();
+}
+
+pub fn lower_phase(c: &mut Criterion) {
+    let (store, std) = store();
+    let mut group = c.benchmark_group("lower");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            // Lowering consumes the results of the earlier phases, so they are rerun untimed.
+            b.iter_batched(
+                || {
+                    let sources = sources(&name, &source);
+                    let parsed = parse(&sources, RuntimeCapabilityFlags::all());
+                    let resolved = resolve(&store, &[std], &parsed);
+                    let checked = typeck(&store, &[std], &parsed, &resolved);
+                    (sources, parsed, resolved, checked)
+                },
+                |(sources, parsed, resolved, checked)| {
+                    let unit = lower(sources, parsed, resolved, checked);
+                    assert!(unit.errors.is_empty());
+                    unit
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+pub fn passes(c: &mut Criterion) {
+    let (store, std) = store();
+    let mut group = c.benchmark_group("passes");
+    for (name, source) in corpus() {
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &source, |b, source| {
+            b.iter_batched(
+                || {
+                    qsc_frontend::compile::compile(
+                        &store,
+                        &[std],
+                        sources(&name, source),
+                        RuntimeCapabilityFlags::all(),
+                    )
+                },
+                |mut unit| {
+                    let errors = run_default_passes(
+                        store.core(),
+                        &mut unit,
+                        PackageType::Exe,
+                        RuntimeCapabilityFlags::all(),
+                    );
+                    assert!(errors.is_empty());
+                    unit
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+pub fn capabilities(c: &mut Criterion) {
+    let (store, std) = store();
+    let mut group = c.benchmark_group("capabilities");
+    for (name, source) in corpus() {
+        let (unit, errors) = compile::compile(
+            &store,
+            &[std],
+            sources(&name, &source),
+            PackageType::Exe,
+            RuntimeCapabilityFlags::all(),
+        );
+        assert!(errors.is_empty());
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            b.iter(|| {
+                check_runtime_capabilities(
+                    black_box(&unit.package),
+                    RuntimeCapabilityFlags::empty(),
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+pub fn codegen(c: &mut Criterion) {
+    let (mut store, std) = store();
+    let backend = BaseProfile::default();
+    let mut group = c.benchmark_group("codegen");
+    for (name, source) in corpus() {
+        let (unit, errors) = compile::compile(
+            &store,
+            &[std],
+            sources(&name, &source),
+            PackageType::Exe,
+            RuntimeCapabilityFlags::all(),
+        );
+        assert!(errors.is_empty());
+        let package = store.insert(unit);
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            b.iter(|| {
+                backend
+                    .generate(&store, package)
+                    .expect("program should generate QIR")
+            });
+        });
+    }
+    group.finish();
+}
+
+pub fn eval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eval");
+    for (name, source) in corpus() {
+        let mut interpreter = Interpreter::new(
+            true,
+            sources(&name, &source),
+            PackageType::Exe,
+            RuntimeCapabilityFlags::all(),
+        )
+        .expect("program should compile");
+        group.bench_function(BenchmarkId::from_parameter(&name), |b| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                let mut rec = GenericReceiver::new(&mut out);
+                assert!(interpreter.eval_entry(&mut rec).is_ok());
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    parse_phase,
+    resolve_phase,
+    typeck_phase,
+    lower_phase,
+    passes,
+    capabilities,
+    codegen,
+    eval
+);
+criterion_main!(benches);
@@ -0,0 +1,427 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Control-flow analysis of FIR. A [`Cfg`] is the control-flow graph of a block, such as the body
+//! of a callable, or of an expression, such as the entry of a package, and answers reachability,
+//! dominance and loop queries, so that passes and external analyses don't each derive control
+//! flow on their own.
+//!
+//! The nodes of the graph are the points where statements start and where branches decide: the
+//! conditions of `if` and `while` expressions and the left operands of `and` and `or`. A `return`
+//! or a `fail` goes to the exit. Closures are separate callables, so their bodies are not part of
+//! the graph of the block that creates them.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_fir::fir::{
+    BinOp, BlockId, ExprId, ExprKind, Package, PackageLookup, StmtId, StmtKind, StringComponent,
+};
+use rustc_hash::FxHashSet;
+use std::fmt::{self, Display, Formatter};
+
+/// A node of a control-flow graph.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(usize);
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "n{}", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodeKind {
+    Entry,
+    Exit,
+    /// The start of a statement.
+    Stmt(StmtId),
+    /// A branch on the value of a condition.
+    Branch(ExprId),
+}
+
+impl Display for NodeKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            NodeKind::Entry => write!(f, "entry"),
+            NodeKind::Exit => write!(f, "exit"),
+            NodeKind::Stmt(stmt) => write!(f, "stmt {stmt}"),
+            NodeKind::Branch(expr) => write!(f, "branch {expr}"),
+        }
+    }
+}
+
+/// The control-flow graph of a block or an expression.
+#[derive(Clone, Debug)]
+pub struct Cfg {
+    kinds: Vec<NodeKind>,
+    succs: Vec<Vec<NodeId>>,
+    preds: Vec<Vec<NodeId>>,
+}
+
+impl Cfg {
+    pub const ENTRY: NodeId = NodeId(0);
+    pub const EXIT: NodeId = NodeId(1);
+
+    /// Builds the graph of the block, such as the block of a callable specialization.
+    #[must_use]
+    pub fn block(package: &Package, block: BlockId) -> Self {
+        Builder::new(package).finish(|builder, flow| builder.block(block, flow))
+    }
+
+    /// Builds the graph of the expression, such as the entry expression of a package.
+    #[must_use]
+    pub fn expr(package: &Package, expr: ExprId) -> Self {
+        Builder::new(package).finish(|builder, flow| builder.expr(expr, flow))
+    }
+
+    /// The nodes of the graph, in the order they were built, which is the order of the source.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, NodeKind)> + '_ {
+        self.kinds
+            .iter()
+            .enumerate()
+            .map(|(index, &kind)| (NodeId(index), kind))
+    }
+
+    #[must_use]
+    pub fn kind(&self, node: NodeId) -> NodeKind {
+        self.kinds[node.0]
+    }
+
+    /// The node of the start of the statement, if the statement is part of the graph.
+    #[must_use]
+    pub fn stmt(&self, stmt: StmtId) -> Option<NodeId> {
+        self.nodes()
+            .find(|&(_, kind)| kind == NodeKind::Stmt(stmt))
+            .map(|(node, _)| node)
+    }
+
+    #[must_use]
+    pub fn successors(&self, node: NodeId) -> &[NodeId] {
+        &self.succs[node.0]
+    }
+
+    #[must_use]
+    pub fn predecessors(&self, node: NodeId) -> &[NodeId] {
+        &self.preds[node.0]
+    }
+
+    /// The nodes that can be reached from the entry.
+    #[must_use]
+    pub fn reachable(&self) -> FxHashSet<NodeId> {
+        let mut reached = FxHashSet::default();
+        let mut stack = vec![Self::ENTRY];
+        while let Some(node) = stack.pop() {
+            if reached.insert(node) {
+                stack.extend(self.successors(node));
+            }
+        }
+        reached
+    }
+
+    /// The statements that can't be reached from the entry, such as those after a `return`.
+    #[must_use]
+    pub fn unreachable_stmts(&self) -> Vec<StmtId> {
+        let reachable = self.reachable();
+        self.nodes()
+            .filter_map(|(node, kind)| match kind {
+                NodeKind::Stmt(stmt) if !reachable.contains(&node) => Some(stmt),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The dominator tree of the nodes that can be reached from the entry, found with the
+    /// iterative algorithm of Cooper, Harvey and Kennedy.
+    #[must_use]
+    pub fn dominators(&self) -> Dominators {
+        let order = self.reverse_postorder();
+        let mut position = vec![usize::MAX; self.kinds.len()];
+        for (index, node) in order.iter().enumerate() {
+            position[node.0] = index;
+        }
+
+        let mut idom: Vec<Option<NodeId>> = vec![None; self.kinds.len()];
+        idom[Self::ENTRY.0] = Some(Self::ENTRY);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in &order[1..] {
+                let mut preds = self
+                    .predecessors(node)
+                    .iter()
+                    .copied()
+                    .filter(|pred| idom[pred.0].is_some());
+                let Some(first) = preds.next() else {
+                    continue;
+                };
+                let new_idom = preds.fold(first, |mut lhs, mut rhs| {
+                    while lhs != rhs {
+                        while position[lhs.0] > position[rhs.0] {
+                            lhs = idom[lhs.0].expect("processed node should have a dominator");
+                        }
+                        while position[rhs.0] > position[lhs.0] {
+                            rhs = idom[rhs.0].expect("processed node should have a dominator");
+                        }
+                    }
+                    lhs
+                });
+                if idom[node.0] != Some(new_idom) {
+                    idom[node.0] = Some(new_idom);
+                    changed = true;
+                }
+            }
+        }
+        idom[Self::ENTRY.0] = None;
+        Dominators { idom }
+    }
+
+    /// The natural loops of the graph, one for each node that a back edge goes to, ordered by
+    /// their headers. A back edge goes from a node to one of its dominators.
+    #[must_use]
+    pub fn loops(&self) -> Vec<Loop> {
+        let dominators = self.dominators();
+        let mut loops: Vec<Loop> = Vec::new();
+        for (node, _) in self.nodes() {
+            for &header in self.successors(node) {
+                if !dominators.dominates(header, node) {
+                    continue;
+                }
+                let index = match loops.iter().position(|natural| natural.header == header) {
+                    Some(index) => index,
+                    None => {
+                        loops.push(Loop {
+                            header,
+                            body: vec![header],
+                        });
+                        loops.len() - 1
+                    }
+                };
+                // The body is the header and every node that reaches the back edge without
+                // going through the header.
+                let body = &mut loops[index].body;
+                let mut stack = vec![node];
+                while let Some(node) = stack.pop() {
+                    if dominators.dominates(header, node) && !body.contains(&node) {
+                        body.push(node);
+                        stack.extend(self.predecessors(node));
+                    }
+                }
+            }
+        }
+        for natural in &mut loops {
+            natural.body.sort_unstable();
+        }
+        loops.sort_unstable_by_key(|natural| natural.header);
+        loops
+    }
+
+    fn reverse_postorder(&self) -> Vec<NodeId> {
+        let mut visited = vec![false; self.kinds.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![(Self::ENTRY, 0)];
+        visited[Self::ENTRY.0] = true;
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&succ) = self.successors(node).get(next) {
+                stack.push((node, next + 1));
+                if !visited[succ.0] {
+                    visited[succ.0] = true;
+                    stack.push((succ, 0));
+                }
+            } else {
+                order.push(node);
+            }
+        }
+        order.reverse();
+        order
+    }
+}
+
+impl Display for Cfg {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (node, kind) in self.nodes() {
+            write!(f, "{node} {kind}")?;
+            for (index, succ) in self.successors(node).iter().enumerate() {
+                let separator = if index == 0 { " -> " } else { ", " };
+                write!(f, "{separator}{succ}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The immediate dominator of each node of a control-flow graph. A node dominates another if
+/// every path from the entry to the other node goes through it.
+#[derive(Clone, Debug)]
+pub struct Dominators {
+    idom: Vec<Option<NodeId>>,
+}
+
+impl Dominators {
+    /// The closest node that strictly dominates the node, which is `None` for the entry and for
+    /// nodes that can't be reached.
+    #[must_use]
+    pub fn immediate(&self, node: NodeId) -> Option<NodeId> {
+        self.idom[node.0]
+    }
+
+    /// Whether `lhs` dominates `rhs`. Every reachable node dominates itself.
+    #[must_use]
+    pub fn dominates(&self, lhs: NodeId, rhs: NodeId) -> bool {
+        if lhs == rhs {
+            return rhs == Cfg::ENTRY || self.idom[rhs.0].is_some();
+        }
+        let mut node = rhs;
+        while let Some(idom) = self.idom[node.0] {
+            if idom == lhs {
+                return true;
+            }
+            node = idom;
+        }
+        false
+    }
+}
+
+/// A natural loop: the header that every iteration starts at, and the nodes of the loop,
+/// including the header, in order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Loop {
+    pub header: NodeId,
+    pub body: Vec<NodeId>,
+}
+
+/// Builds a graph by walking the FIR in evaluation order, threading the flow: the nodes that
+/// control reaches the current point from. An empty flow means the current point can't be
+/// reached.
+struct Builder<'a> {
+    package: &'a Package,
+    cfg: Cfg,
+}
+
+impl<'a> Builder<'a> {
+    fn new(package: &'a Package) -> Self {
+        Self {
+            package,
+            cfg: Cfg {
+                kinds: vec![NodeKind::Entry, NodeKind::Exit],
+                succs: vec![Vec::new(), Vec::new()],
+                preds: vec![Vec::new(), Vec::new()],
+            },
+        }
+    }
+
+    fn finish(mut self, build: impl FnOnce(&mut Self, Vec<NodeId>) -> Vec<NodeId>) -> Cfg {
+        for node in build(&mut self, vec![Cfg::ENTRY]) {
+            self.edge(node, Cfg::EXIT);
+        }
+        self.cfg
+    }
+
+    fn node(&mut self, kind: NodeKind, flow: &[NodeId]) -> NodeId {
+        let node = NodeId(self.cfg.kinds.len());
+        self.cfg.kinds.push(kind);
+        self.cfg.succs.push(Vec::new());
+        self.cfg.preds.push(Vec::new());
+        for &pred in flow {
+            self.edge(pred, node);
+        }
+        node
+    }
+
+    fn edge(&mut self, from: NodeId, to: NodeId) {
+        if !self.cfg.succs[from.0].contains(&to) {
+            self.cfg.succs[from.0].push(to);
+            self.cfg.preds[to.0].push(from);
+        }
+    }
+
+    fn block(&mut self, block: BlockId, flow: Vec<NodeId>) -> Vec<NodeId> {
+        let package = self.package;
+        package
+            .get_block(block)
+            .stmts
+            .iter()
+            .fold(flow, |flow, &stmt| self.stmt(stmt, flow))
+    }
+
+    fn stmt(&mut self, stmt: StmtId, flow: Vec<NodeId>) -> Vec<NodeId> {
+        let node = self.node(NodeKind::Stmt(stmt), &flow);
+        match self.package.get_stmt(stmt).kind {
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) | StmtKind::Local(_, _, expr) => {
+                self.expr(expr, vec![node])
+            }
+            StmtKind::Item(_) => vec![node],
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[ExprId], flow: Vec<NodeId>) -> Vec<NodeId> {
+        exprs.iter().fold(flow, |flow, &expr| self.expr(expr, flow))
+    }
+
+    fn expr(&mut self, expr: ExprId, flow: Vec<NodeId>) -> Vec<NodeId> {
+        let package = self.package;
+        match &package.get_expr(expr).kind {
+            ExprKind::If(cond, body, otherwise) => {
+                let flow = self.expr(*cond, flow);
+                let branch = self.node(NodeKind::Branch(*cond), &flow);
+                let mut after = self.expr(*body, vec![branch]);
+                match otherwise {
+                    Some(otherwise) => after.extend(self.expr(*otherwise, vec![branch])),
+                    None => after.push(branch),
+                }
+                after
+            }
+            ExprKind::While(cond, body) => {
+                // The loop starts again at the first node of its condition.
+                let head = NodeId(self.cfg.kinds.len());
+                let flow = self.expr(*cond, flow);
+                let branch = self.node(NodeKind::Branch(*cond), &flow);
+                for node in self.block(*body, vec![branch]) {
+                    self.edge(node, head);
+                }
+                vec![branch]
+            }
+            ExprKind::BinOp(BinOp::AndL | BinOp::OrL, lhs, rhs) => {
+                let flow = self.expr(*lhs, flow);
+                let branch = self.node(NodeKind::Branch(*lhs), &flow);
+                let mut after = self.expr(*rhs, vec![branch]);
+                after.push(branch);
+                after
+            }
+            ExprKind::Return(value) | ExprKind::Fail(value) => {
+                for node in self.expr(*value, flow) {
+                    self.edge(node, Cfg::EXIT);
+                }
+                Vec::new()
+            }
+            ExprKind::Block(block) => self.block(*block, flow),
+            ExprKind::Array(exprs) | ExprKind::Tuple(exprs) => self.exprs(exprs, flow),
+            ExprKind::ArrayRepeat(lhs, rhs)
+            | ExprKind::Assign(lhs, rhs)
+            | ExprKind::AssignOp(_, lhs, rhs)
+            | ExprKind::AssignField(lhs, _, rhs)
+            | ExprKind::BinOp(_, lhs, rhs)
+            | ExprKind::Call(lhs, rhs)
+            | ExprKind::Index(lhs, rhs)
+            | ExprKind::UpdateField(lhs, _, rhs) => self.exprs(&[*lhs, *rhs], flow),
+            ExprKind::AssignIndex(container, index, value)
+            | ExprKind::UpdateIndex(container, index, value) => {
+                self.exprs(&[*container, *index, *value], flow)
+            }
+            ExprKind::Field(expr, _) | ExprKind::UnOp(_, expr) => self.expr(*expr, flow),
+            ExprKind::Range(start, step, end) => {
+                let exprs = [start, step, end].into_iter().flatten().copied();
+                self.exprs(&exprs.collect::<Vec<_>>(), flow)
+            }
+            ExprKind::String(components) => {
+                let exprs = components.iter().filter_map(|component| match component {
+                    StringComponent::Expr(expr) => Some(*expr),
+                    StringComponent::Lit(_) => None,
+                });
+                self.exprs(&exprs.collect::<Vec<_>>(), flow)
+            }
+            ExprKind::Closure(..) | ExprKind::Hole | ExprKind::Lit(_) | ExprKind::Var(..) => flow,
+        }
+    }
+}
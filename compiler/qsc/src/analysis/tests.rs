@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Cfg, NodeId, NodeKind};
+use crate::compile;
+use indoc::indoc;
+use qsc_fir::fir::{CallableImpl, ItemKind, Package, StmtId};
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+/// Compiles the source and builds the graph of the body of its callable `F`.
+fn cfg(source: &str) -> (Package, Cfg) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = qsc_eval::lower::Lowerer::new().lower_package(&unit.package);
+    let block = package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) if &*decl.name.name == "F" => match &decl.implementation {
+                CallableImpl::Spec(spec) => Some(spec.body.block),
+                CallableImpl::Intrinsic => None,
+            },
+            _ => None,
+        })
+        .expect("source should have a callable F");
+    let cfg = Cfg::block(&package, block);
+    (package, cfg)
+}
+
+/// The statement whose source, without its semicolon, is `text`.
+fn stmt(package: &Package, source: &str, text: &str) -> StmtId {
+    package
+        .stmts
+        .values()
+        .find(|stmt| {
+            let span = &source[stmt.span.lo as usize..stmt.span.hi as usize];
+            span.trim_end_matches(';') == text
+        })
+        .map(|stmt| stmt.id)
+        .unwrap_or_else(|| panic!("source should have the statement `{text}`"))
+}
+
+fn node(cfg: &Cfg, package: &Package, source: &str, text: &str) -> NodeId {
+    cfg.stmt(stmt(package, source, text))
+        .expect("statement should be in the graph")
+}
+
+#[test]
+fn statements_after_return_are_unreachable() {
+    let source = indoc! {"
+        namespace Test {
+            function F(x : Int) : Int {
+                if x > 0 {
+                    return 1;
+                }
+                let y = 2;
+                return y;
+                let z = 3;
+                z
+            }
+        }
+    "};
+    let (package, cfg) = cfg(source);
+    assert_eq!(
+        cfg.unreachable_stmts(),
+        vec![
+            stmt(&package, source, "let z = 3"),
+            stmt(&package, source, "z")
+        ]
+    );
+    let returned = node(&cfg, &package, source, "return 1");
+    assert_eq!(cfg.successors(returned), [Cfg::EXIT]);
+    assert!(cfg.reachable().contains(&Cfg::EXIT));
+}
+
+#[test]
+fn branches_dominate_their_arms_but_not_what_follows() {
+    let source = indoc! {"
+        namespace Test {
+            function F(x : Int) : Int {
+                mutable y = 0;
+                if x > 0 {
+                    set y = 1;
+                } else {
+                    set y = 2;
+                }
+                y
+            }
+        }
+    "};
+    let (package, cfg) = cfg(source);
+    let dominators = cfg.dominators();
+    let first = node(&cfg, &package, source, "mutable y = 0");
+    let then = node(&cfg, &package, source, "set y = 1");
+    let last = node(&cfg, &package, source, "y");
+    let branch = cfg
+        .nodes()
+        .find(|(_, kind)| matches!(kind, NodeKind::Branch(_)))
+        .map(|(node, _)| node)
+        .expect("graph should have a branch");
+
+    assert!(dominators.dominates(first, last));
+    assert!(dominators.dominates(branch, then));
+    assert!(!dominators.dominates(then, last));
+    assert_eq!(dominators.immediate(then), Some(branch));
+    assert_eq!(dominators.immediate(last), Some(branch));
+    assert_eq!(dominators.immediate(Cfg::ENTRY), None);
+}
+
+#[test]
+fn nested_while_loops_are_found() {
+    let source = indoc! {"
+        namespace Test {
+            function F() : Int {
+                mutable total = 0;
+                mutable i = 0;
+                while i < 3 {
+                    mutable j = 0;
+                    while j < i {
+                        set total += j;
+                        set j += 1;
+                    }
+                    set i += 1;
+                }
+                total
+            }
+        }
+    "};
+    let (package, cfg) = cfg(source);
+    let loops = cfg.loops();
+    assert_eq!(loops.len(), 2);
+    let (outer, inner) = (&loops[0], &loops[1]);
+    assert!(inner.body.iter().all(|node| outer.body.contains(node)));
+
+    let inner_stmt = node(&cfg, &package, source, "set total += j");
+    let outer_stmt = node(&cfg, &package, source, "set i += 1");
+    let after = node(&cfg, &package, source, "total");
+    assert!(inner.body.contains(&inner_stmt));
+    assert!(!inner.body.contains(&outer_stmt));
+    assert!(outer.body.contains(&outer_stmt));
+    assert!(!outer.body.contains(&after));
+    assert!(cfg.dominators().dominates(outer.header, inner.header));
+}
+
+#[test]
+fn short_circuit_operators_branch() {
+    let source = indoc! {"
+        namespace Test {
+            function F(a : Bool, b : Bool) : Bool {
+                let c = a and b;
+                c
+            }
+        }
+    "};
+    let (package, cfg) = cfg(source);
+    let declared = node(&cfg, &package, source, "let c = a and b");
+    let used = node(&cfg, &package, source, "c");
+    let branch = cfg.successors(declared)[0];
+    assert!(matches!(cfg.kind(branch), NodeKind::Branch(_)));
+    assert!(cfg.successors(branch).contains(&used));
+    assert_eq!(cfg.predecessors(used), [branch]);
+}
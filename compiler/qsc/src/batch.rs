@@ -0,0 +1,90 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compiles many small, independent programs against one standard library, for hosts such as
+//! grading services and documentation builds where compiling the standard library and building
+//! its global tables would otherwise dominate the time spent on each program.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compile::{self, Dependencies, Error, QubitInitialization};
+use qsc_frontend::compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+
+/// A compiler that compiles the core and standard libraries once and reuses them, along with their
+/// global tables, for every program it compiles. The programs don't see each other.
+pub struct BatchCompiler {
+    store: PackageStore,
+    std: PackageId,
+    dependencies: Dependencies,
+    capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+}
+
+impl BatchCompiler {
+    /// Compiles the core and standard libraries for the given target.
+    #[must_use]
+    pub fn new(
+        capabilities: RuntimeCapabilityFlags,
+        qubit_initialization: QubitInitialization,
+    ) -> Self {
+        let mut store = PackageStore::new(compile::core());
+        let std = store.insert(compile::std_with_qubit_initialization(
+            &store,
+            capabilities,
+            qubit_initialization,
+        ));
+        let dependencies = Dependencies::new(&store, &[std]);
+        Self {
+            store,
+            std,
+            dependencies,
+            capabilities,
+            qubit_initialization,
+        }
+    }
+
+    /// The store holding the core and standard libraries.
+    #[must_use]
+    pub fn package_store(&self) -> &PackageStore {
+        &self.store
+    }
+
+    /// The ID of the standard library in the store.
+    #[must_use]
+    pub fn std(&self) -> PackageId {
+        self.std
+    }
+
+    /// Compiles a program. The program isn't added to the store, so it can't be a dependency of
+    /// the programs compiled after it.
+    #[must_use]
+    pub fn compile(
+        &self,
+        sources: SourceMap,
+        package_type: PackageType,
+    ) -> (CompileUnit, Vec<Error>) {
+        compile::compile_with_dependencies(
+            &self.store,
+            &self.dependencies,
+            sources,
+            package_type,
+            self.capabilities,
+            self.qubit_initialization,
+        )
+    }
+
+    /// Compiles each program in turn, returning the errors of each.
+    pub fn check_all(
+        &self,
+        programs: impl IntoIterator<Item = SourceMap>,
+        package_type: PackageType,
+    ) -> Vec<Vec<Error>> {
+        programs
+            .into_iter()
+            .map(|sources| self.compile(sources, package_type).1)
+            .collect()
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::BatchCompiler;
+use crate::compile::QubitInitialization;
+use indoc::indoc;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn sources(name: &str, source: &str) -> SourceMap {
+    SourceMap::new([(name.into(), source.into())], None)
+}
+
+#[test]
+fn programs_compile_independently() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    for name in ["first", "second"] {
+        let source = indoc! {"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    H(q);
+                    M(q)
+                }
+            }
+        "};
+        let (unit, errors) = compiler.compile(sources(name, source), PackageType::Exe);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(unit.package.entry.is_some());
+    }
+}
+
+#[test]
+fn errors_are_reported_per_program() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let errors = compiler.check_all(
+        [
+            sources("valid", "namespace Test { function F() : Int { 1 } }"),
+            sources("invalid", "namespace Test { function F() : Int { 1.0 } }"),
+            sources(
+                "unresolved",
+                "namespace Test { function F() : Int { G() } }",
+            ),
+        ],
+        PackageType::Lib,
+    );
+    assert!(errors[0].is_empty(), "{:?}", errors[0]);
+    assert_eq!(errors[1].len(), 1, "{:?}", errors[1]);
+    assert!(!errors[2].is_empty());
+}
+
+#[test]
+fn programs_are_not_added_to_the_store() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let packages = compiler.package_store().iter().count();
+    let (_, errors) = compiler.compile(
+        sources("test", "namespace Test { function F() : Unit {} }"),
+        PackageType::Lib,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(compiler.package_store().iter().count(), packages);
+    assert!(compiler.package_store().get(compiler.std()).is_some());
+}
+
+#[test]
+fn items_of_a_program_are_not_seen_by_the_next() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let source = "namespace Test { newtype Pair = (Int, Int); function F() : Pair { Pair(1, 2) } }";
+    let errors = compiler.check_all(
+        [sources("first", source), sources("second", source)],
+        PackageType::Lib,
+    );
+    assert!(errors[0].is_empty(), "{:?}", errors[0]);
+    assert!(errors[1].is_empty(), "{:?}", errors[1]);
+}
+
+#[test]
+fn items_that_duplicate_the_standard_library_are_rejected() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let (_, errors) = compiler.compile(
+        sources(
+            "test",
+            "namespace Microsoft.Quantum.Intrinsic { operation H(q : Qubit) : Unit {} }",
+        ),
+        PackageType::Lib,
+    );
+    let errors = format!("{errors:?}");
+    assert!(
+        errors.contains(r#"Duplicate("H", "Microsoft.Quantum.Intrinsic""#),
+        "{errors}"
+    );
+}
@@ -6,22 +6,40 @@
 
 use clap::{crate_version, ArgGroup, Parser, ValueEnum};
 use log::info;
-use miette::{Context, IntoDiagnostic, Report};
-use qsc::compile::compile;
-use qsc_codegen::qir_base;
+use miette::{Context, Diagnostic, IntoDiagnostic, Report};
+use qsc::{
+    compilation_db::{self, CompilationDatabase},
+    compile::{
+        compile_with_max_qubits, compile_with_qubit_initialization, required_capabilities,
+        QubitInitialization,
+    },
+    doc_test,
+    line_column::{Encoding, Position},
+    report::{ColorChoice, Renderer},
+    watch::Watcher,
+};
+use qsc_codegen::{
+    qir_base::{AdaptiveProfile, BaseProfile, CodegenOptions, OutputFormat},
+    target::{BackendRegistry, TargetBackend},
+};
 use qsc_frontend::{
     compile::{PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName},
     error::WithSource,
 };
-use qsc_hir::hir::{Package, PackageId};
+use qsc_hir::{
+    hir::{Package, PackageId},
+    renumber::renumber,
+};
 use qsc_passes::PackageType;
-use qsc_project::{FileSystem, Manifest, StdFs};
+use qsc_project::{FileSystem, Manifest, Overlay, StdFs};
+use serde::Serialize;
 use std::{
-    concat, fs,
+    concat, env, fs,
     io::{self, Read},
     path::{Path, PathBuf},
     process::ExitCode,
     string::String,
+    time::Duration,
 };
 
 #[derive(Debug, Parser)]
@@ -55,77 +73,530 @@ struct Cli {
     /// Path to a Q# manifest for a project
     #[arg(short, long)]
     qsharp_json: Option<PathBuf>,
+
+    /// Watch the sources for changes and rebuild whenever they are saved, reporting
+    /// diagnostics as JSON lines.
+    #[arg(long)]
+    watch: bool,
+
+    /// Name of the backend that generates code when emitting QIR.
+    #[arg(long, default_value = "base")]
+    target: String,
+
+    /// Attach Q# source locations to the generated QIR as LLVM debug metadata.
+    #[arg(short = 'g', long)]
+    debug_info: bool,
+
+    /// How the QIR records the program's results: `tree`, `labeled`, `flat` or `packed`.
+    #[arg(long, default_value = "tree")]
+    output_format: OutputFormat,
+
+    /// Let measurements reuse the result registers of results that are no longer needed.
+    #[arg(long)]
+    reuse_result_registers: bool,
+
+    /// Fail to compile programs whose precision needs more than this many qubits, and to emit QIR
+    /// for programs that need more.
+    #[arg(long, value_name = "QUBITS")]
+    max_qubits: Option<usize>,
+
+    /// The state the target leaves freshly allocated qubits in: `zero`, or `arbitrary` to reset
+    /// each qubit after it is allocated.
+    #[arg(long, default_value = "zero")]
+    qubit_initialization: QubitInitialization,
+
+    /// Run the `qsharp` examples in the doc comments of the sources as tests.
+    #[arg(long)]
+    test: bool,
+
+    /// With `--test`, write the coverage of the sources by the doc tests to FILE in the lcov
+    /// format.
+    #[arg(long, value_name = "FILE", requires = "test")]
+    coverage: Option<PathBuf>,
+
+    /// Use the contents of SOURCE, or standard input if it is `-`, in place of the file at PATH,
+    /// so that editors can check buffers with unsaved changes. PATH doesn't need to exist.
+    #[arg(long, value_name = "PATH=SOURCE", value_parser = parse_overlay)]
+    overlay: Vec<(PathBuf, PathBuf)>,
+
+    /// Print the least capable profile that can run the program, with the capabilities it needs.
+    #[arg(long)]
+    required_profile: bool,
+
+    /// Print the full explanation of a diagnostic code, such as `Qsc.TypeCk.MissingFunctor`,
+    /// and exit.
+    #[arg(long, value_name = "CODE")]
+    explain: Option<String>,
+
+    /// When to color diagnostics: `auto`, `always` or `never`.
+    #[arg(long, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Wrap diagnostics to this many columns instead of the width of the terminal.
+    #[arg(long, value_name = "COLUMNS")]
+    diagnostic_width: Option<usize>,
 }
 
+/// How long watch mode waits for the files to stop changing before it rebuilds.
+const WATCH_QUIET_PERIOD: Duration = Duration::from_millis(100);
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Emit {
     Hir,
     Qir,
+    CompileDb,
 }
 
 fn main() -> miette::Result<ExitCode> {
     env_logger::init();
     let cli = Cli::parse();
+    if let Some(code) = &cli.explain {
+        return explain(code);
+    }
+    if cli.debug_info && !cli.emit.contains(&Emit::Qir) {
+        return Err(Report::msg(
+            "`--debug-info` only applies when emitting QIR with `--emit qir`",
+        ));
+    }
+    if cli.test {
+        return test(&cli);
+    }
+
     let mut store = PackageStore::new(qsc::compile::core());
     let mut dependencies = Vec::new();
+    let mut registry = BackendRegistry::default();
+    let options = CodegenOptions {
+        debug_info: cli.debug_info,
+        output_format: cli.output_format,
+        reuse_result_registers: cli.reuse_result_registers,
+        max_qubits: cli.max_qubits,
+    };
+    registry.register(Box::new(BaseProfile { options }));
+    registry.register(Box::new(AdaptiveProfile { options }));
+    let backend = registry.get(&cli.target).ok_or_else(|| {
+        Report::msg(format!(
+            "unknown target `{}`, expected one of: {}",
+            cli.target,
+            registry.names().join(", ")
+        ))
+    })?;
 
     let (package_type, capabilities) = if cli.emit.contains(&Emit::Qir) {
-        (PackageType::Exe, RuntimeCapabilityFlags::empty())
+        (PackageType::Exe, backend.capabilities())
     } else {
         (PackageType::Lib, RuntimeCapabilityFlags::all())
     };
 
     if !cli.nostdlib {
-        dependencies.push(store.insert(qsc::compile::std(&store, capabilities)));
+        dependencies.push(store.insert(qsc::compile::std_with_qubit_initialization(
+            &store,
+            capabilities,
+            cli.qubit_initialization,
+        )));
+    }
+
+    let mut builder = Builder {
+        cli: &cli,
+        store,
+        dependencies,
+        package_type,
+        capabilities,
+        backend,
+    };
+
+    if cli.watch {
+        return builder.watch();
+    }
+
+    let (sources, manifest) = load_sources(&cli)?;
+    let (errors, warnings) = builder.build(sources, manifest)?;
+    let renderer = Renderer::new(cli.color, cli.diagnostic_width);
+    for warning in warnings {
+        eprintln!("{}", renderer.render(&warning));
+    }
+    if errors.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        for error in errors {
+            eprintln!("{}", renderer.render(&error));
+        }
+
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Prints the explanation of the diagnostic code as Markdown.
+fn explain(code: &str) -> miette::Result<ExitCode> {
+    let explanation = qsc::explanation::explain(code).ok_or_else(|| {
+        Report::msg(format!(
+            "no explanation for `{code}`, expected one of: {}",
+            qsc::explanation::codes().collect::<Vec<_>>().join(", ")
+        ))
+    })?;
+    print!("{explanation}");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Runs the doc tests of the sources, reporting each failure at the location of its example.
+fn test(cli: &Cli) -> miette::Result<ExitCode> {
+    let renderer = Renderer::new(cli.color, cli.diagnostic_width);
+    let (sources, manifest) = load_sources(cli)?;
+    let sources = SourceMap::new(sources, None)
+        .with_features(manifest.features)
+        .with_edition(manifest.edition);
+    let run = match doc_test::run_doc_tests(
+        !cli.nostdlib,
+        &sources,
+        RuntimeCapabilityFlags::all(),
+        cli.coverage.is_some(),
+    ) {
+        Ok(run) => run,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}", renderer.render(&error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let results = run.results;
+
+    let mut failed = 0;
+    for result in &results {
+        if result.passed() {
+            println!("doc test {} ... ok", result.test);
+        } else {
+            failed += 1;
+            println!("doc test {} ... FAILED", result.test);
+            for error in &result.errors {
+                eprintln!("{}", renderer.render(error));
+            }
+        }
+    }
+
+    println!(
+        "\ndoc tests: {} passed, {failed} failed",
+        results.len() - failed
+    );
+    if let (Some(path), Some(lcov)) = (&cli.coverage, run.lcov) {
+        fs::write(path, lcov)
+            .into_diagnostic()
+            .context("could not write coverage")?;
+    }
+    Ok(if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Compiles the user package against dependencies that are compiled only once.
+struct Builder<'a> {
+    cli: &'a Cli,
+    store: PackageStore,
+    dependencies: Vec<PackageId>,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    backend: &'a dyn TargetBackend,
+}
+
+impl Builder<'_> {
+    /// Compiles the sources and writes the requested outputs, returning the compilation errors
+    /// and warnings. The user package is removed from the store afterwards so that it can be built
+    /// again.
+    fn build(
+        &mut self,
+        sources: Vec<(SourceName, SourceContents)>,
+        manifest: Manifest,
+    ) -> miette::Result<(Vec<qsc::compile::Error>, Vec<qsc::compile::Error>)> {
+        let entry = self.cli.entry.clone().unwrap_or_default();
+        let sources = SourceMap::new(sources, Some(entry.into()))
+            .with_features(manifest.features)
+            .with_edition(manifest.edition);
+        let (unit, errors) = match self.cli.max_qubits {
+            Some(max_qubits) => compile_with_max_qubits(
+                &self.store,
+                &self.dependencies,
+                sources,
+                self.package_type,
+                self.capabilities,
+                self.cli.qubit_initialization,
+                max_qubits,
+            ),
+            None => compile_with_qubit_initialization(
+                &self.store,
+                &self.dependencies,
+                sources,
+                self.package_type,
+                self.capabilities,
+                self.cli.qubit_initialization,
+            ),
+        };
+        let warnings = qsc::compile::warnings(&unit);
+        let package_id = self.store.insert(unit);
+        let result = self.emit(package_id, errors.is_empty());
+        self.store.remove(package_id);
+        result.map(|()| (errors, warnings))
     }
 
+    fn emit(&self, package_id: PackageId, succeeded: bool) -> miette::Result<()> {
+        let unit = self
+            .store
+            .get(package_id)
+            .expect("package should be in store");
+        let out_dir = self
+            .cli
+            .out_dir
+            .as_ref()
+            .map_or(".".as_ref(), PathBuf::as_path);
+        for emit in &self.cli.emit {
+            match emit {
+                Emit::Hir => emit_hir(&unit.package, out_dir)?,
+                Emit::Qir => {
+                    if succeeded {
+                        emit_qir(out_dir, self.backend, &self.store, package_id)?;
+                    }
+                }
+                Emit::CompileDb => {
+                    emit_compile_db(out_dir, &self.compilation_db(package_id)?)?;
+                }
+            }
+        }
+        if self.cli.required_profile && succeeded {
+            let required = required_capabilities(&unit.package);
+            let (profile, _) = required.minimal_preset();
+            let capabilities = required
+                .iter_names()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+            if capabilities.is_empty() {
+                println!("{profile}");
+            } else {
+                println!("{profile} ({})", capabilities.join(", "));
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes how the core library, the dependencies, and the user package were compiled.
+    fn compilation_db(&self, package_id: PackageId) -> miette::Result<CompilationDatabase> {
+        let directory = env::current_dir()
+            .into_diagnostic()
+            .context("could not read the current directory")?;
+        let mut db = CompilationDatabase::new(directory, self.capabilities);
+        let sources = |id| {
+            &self
+                .store
+                .get(id)
+                .expect("package should be in store")
+                .sources
+        };
+        db.add_package(
+            PackageId::CORE,
+            "core",
+            PackageType::Lib,
+            sources(PackageId::CORE),
+            &[],
+        );
+        for &dependency in &self.dependencies {
+            db.add_package(
+                dependency,
+                "std",
+                PackageType::Lib,
+                sources(dependency),
+                &[],
+            );
+        }
+        db.add_package(
+            package_id,
+            "user",
+            self.package_type,
+            sources(package_id),
+            &self.dependencies,
+        );
+        if let Some(unit) = self.store.get(package_id) {
+            db.set_entry_parameters(package_id, compilation_db::entry_parameters(&unit.package));
+        }
+        Ok(db)
+    }
+
+    /// Rebuilds whenever one of the watched files changes, reporting the result of each build
+    /// as JSON lines on standard output. Only the user package is recompiled; the standard
+    /// library is compiled once up front.
+    fn watch(&mut self) -> miette::Result<ExitCode> {
+        if self.cli.sources.iter().any(|path| path.as_os_str() == "-") {
+            return Err(Report::msg("standard input can't be watched"));
+        }
+
+        let watcher = if self.cli.sources.is_empty() {
+            let manifest = StdFs
+                .find_manifest(&manifest_search_start(self.cli)?)?
+                .ok_or_else(|| Report::msg("no sources or project to watch"))?;
+            Watcher::project(&manifest.manifest_dir)
+        } else {
+            Watcher::files(&self.cli.sources)
+        }
+        .into_diagnostic()
+        .context("could not watch the sources")?;
+
+        loop {
+            match load_sources(self.cli)
+                .and_then(|(sources, manifest)| self.build(sources, manifest))
+            {
+                Ok((errors, warnings)) => report_build(&errors, &warnings),
+                Err(report) => eprintln!("{report:?}"),
+            }
+
+            let changed = watcher
+                .wait(WATCH_QUIET_PERIOD)
+                .into_diagnostic()
+                .context("could not watch the sources")?;
+            info!("rebuilding after changes to {changed:?}");
+        }
+    }
+}
+
+/// Reads the sources given on the command line, or the sources of the project and its manifest if
+/// there are none. Sources given on the command line come with an empty manifest. Overlaid files
+/// are read from their overlay.
+fn load_sources(cli: &Cli) -> miette::Result<(Vec<(SourceName, SourceContents)>, Manifest)> {
+    let fs = overlay_fs(cli)?;
     let mut sources = cli
         .sources
         .iter()
-        .map(read_source)
+        .map(|path| read_source(&fs, path))
         .collect::<miette::Result<Vec<_>>>()?;
 
+    let mut manifest = Manifest::default();
     if sources.is_empty() {
-        let fs = StdFs;
-        let manifest = Manifest::load(cli.qsharp_json)?;
-        if let Some(manifest) = manifest {
-            let project = fs.load_project(&manifest)?;
+        if let Some(descriptor) = fs.find_manifest(&manifest_search_start(cli)?)? {
+            let project = fs.load_project(&descriptor)?;
             let mut project_sources = project.sources;
 
             sources.append(&mut project_sources);
+            manifest = project.manifest;
         }
     }
 
-    let entry = cli.entry.unwrap_or_default();
-    let sources = SourceMap::new(sources, Some(entry.into()));
-    let (unit, errors) = compile(&store, &dependencies, sources, package_type, capabilities);
-    let package_id = store.insert(unit);
-    let unit = store.get(package_id).expect("package should be in store");
+    Ok((sources, manifest))
+}
 
-    let out_dir = cli.out_dir.as_ref().map_or(".".as_ref(), PathBuf::as_path);
-    for emit in &cli.emit {
-        match emit {
-            Emit::Hir => emit_hir(&unit.package, out_dir)?,
-            Emit::Qir => {
-                if errors.is_empty() {
-                    emit_qir(out_dir, &store, package_id)?;
-                }
-            }
-        }
+fn manifest_search_start(cli: &Cli) -> miette::Result<PathBuf> {
+    match &cli.qsharp_json {
+        Some(path) => absolute(path),
+        None => env::current_dir()
+            .into_diagnostic()
+            .context("could not read the current directory"),
     }
+}
 
-    if errors.is_empty() {
-        Ok(ExitCode::SUCCESS)
+/// The filesystem with the overlays given on the command line. Overlay paths are canonicalized,
+/// so a file is overlaid however the sources or the project spell its path.
+fn overlay_fs(cli: &Cli) -> miette::Result<Overlay<StdFs>> {
+    let mut fs = Overlay::new(StdFs);
+    for (path, source) in &cli.overlay {
+        let (_, contents) = read_source(&StdFs, source)?;
+        fs.insert(path, contents);
+    }
+    Ok(fs)
+}
+
+/// Resolves a relative path against the current directory.
+fn absolute(path: &Path) -> miette::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
     } else {
-        for error in errors {
-            eprintln!("{:?}", Report::new(error));
+        Ok(env::current_dir()
+            .into_diagnostic()
+            .context("could not read the current directory")?
+            .join(path))
+    }
+}
+
+fn parse_overlay(arg: &str) -> Result<(PathBuf, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((path, source)) if !path.is_empty() && !source.is_empty() => {
+            Ok((path.into(), source.into()))
         }
+        _ => Err("expected PATH=SOURCE".to_string()),
+    }
+}
 
-        Ok(ExitCode::FAILURE)
+/// A line of the structured output of `--watch`.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Event<'a> {
+    Build {
+        errors: usize,
+        warnings: usize,
+    },
+    Diagnostic {
+        severity: &'a str,
+        code: String,
+        message: String,
+        #[serde(flatten)]
+        location: Option<Location<'a>>,
+    },
+}
+
+/// Where a diagnostic points. Lines and columns are one-based.
+#[derive(Serialize)]
+struct Location<'a> {
+    file: &'a str,
+    line: u32,
+    column: u32,
+}
+
+/// Prints a `build` line with the numbers of errors and warnings, followed by a `diagnostic` line
+/// for each of them.
+fn report_build(errors: &[qsc::compile::Error], warnings: &[qsc::compile::Error]) {
+    print_event(&Event::Build {
+        errors: errors.len(),
+        warnings: warnings.len(),
+    });
+    for (error, severity) in errors
+        .iter()
+        .map(|error| (error, "error"))
+        .chain(warnings.iter().map(|warning| (warning, "warning")))
+    {
+        let location = error
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .map(|label| {
+                let (source, span) = error.resolve_span(label.inner());
+                let position = Position::from_utf8_byte_offset(
+                    Encoding::Utf8,
+                    &source.contents,
+                    u32::try_from(span.offset()).expect("offset should fit into u32"),
+                );
+                Location {
+                    file: &source.name,
+                    line: position.line + 1,
+                    column: position.column + 1,
+                }
+            });
+        print_event(&Event::Diagnostic {
+            severity,
+            code: error
+                .code()
+                .map(|code| code.to_string())
+                .unwrap_or_default(),
+            message: error.to_string(),
+            location,
+        });
     }
 }
 
-fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
+fn print_event(event: &Event) {
+    println!(
+        "{}",
+        serde_json::to_string(event).expect("event should serialize")
+    );
+}
+
+fn read_source(
+    fs: &impl FileSystem,
+    path: impl AsRef<Path>,
+) -> miette::Result<(SourceName, SourceContents)> {
     let path = path.as_ref();
     if path.as_os_str() == "-" {
         let mut input = String::new();
@@ -136,11 +607,9 @@ fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceCont
 
         Ok(("<stdin>".into(), input.into()))
     } else {
-        let contents = fs::read_to_string(path)
-            .into_diagnostic()
-            .with_context(|| format!("could not read source file `{}`", path.display()))?;
-
-        Ok((path.to_string_lossy().into(), contents.into()))
+        // The source keeps the name it was given with, whatever path it was read from.
+        let (_, contents) = fs.read_file(&absolute(path)?)?;
+        Ok((path.to_string_lossy().into(), contents))
     }
 }
 
@@ -150,14 +619,31 @@ fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
         "Writing hir output file to: {}",
         path.to_str().unwrap_or_default()
     );
-    fs::write(path, package.to_string())
+    // Renumber the package so that the output doesn't change with the order of the sources.
+    fs::write(path, renumber(package).to_string())
         .into_diagnostic()
         .context("could not emit HIR")
 }
 
-fn emit_qir(out_dir: &Path, store: &PackageStore, package_id: PackageId) -> Result<(), Report> {
-    let path = out_dir.join("qir.ll");
-    let result = qir_base::generate_qir(store, package_id);
+fn emit_compile_db(dir: &Path, db: &CompilationDatabase) -> miette::Result<()> {
+    let path = dir.join("compile_db.json");
+    info!(
+        "Writing compilation database to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    fs::write(path, db.to_json())
+        .into_diagnostic()
+        .context("could not emit compilation database")
+}
+
+fn emit_qir(
+    out_dir: &Path,
+    backend: &dyn TargetBackend,
+    store: &PackageStore,
+    package_id: PackageId,
+) -> Result<(), Report> {
+    let path = out_dir.join("qir").with_extension(backend.file_extension());
+    let result = backend.generate(store, package_id);
     match result {
         Ok(qir) => {
             info!(
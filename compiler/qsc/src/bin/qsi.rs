@@ -5,19 +5,24 @@
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
 use clap::{crate_version, Parser};
-use miette::{Context, IntoDiagnostic, Report, Result};
+use miette::{Context, IntoDiagnostic, Result};
 use num_bigint::BigUint;
 use num_complex::Complex64;
-use qsc::interpret::{self, InterpretResult, Interpreter};
+use qsc::{
+    interpret::{self, InterpretResult, Interpreter},
+    report::{ColorChoice, Renderer},
+};
+use qsc_data_structures::{edition::Edition, line_column::Encoding};
 use qsc_eval::{
+    backend::Precision,
     output::{self, Receiver},
     val::Value,
 };
 use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName};
 use qsc_passes::PackageType;
-use qsc_project::{FileSystem, Manifest, StdFs};
+use qsc_project::{FileSystem, StdFs};
 use std::{
-    fs,
+    env,
     io::{self, prelude::BufRead, Write},
     path::{Path, PathBuf},
     process::ExitCode,
@@ -47,6 +52,44 @@ struct Cli {
     /// Path to a Q# manifest for a project
     #[arg(short, long)]
     qsharp_json: Option<PathBuf>,
+
+    /// Warn before running when the program is estimated to allocate more than this many qubits.
+    /// The program still runs after the warning.
+    #[arg(long)]
+    qubit_limit: Option<usize>,
+
+    /// Simulate with single-precision amplitudes, which take a fifth less memory but accumulate
+    /// larger rounding errors.
+    #[arg(long)]
+    single_precision: bool,
+
+    /// Cache the gates of operations that are called repeatedly with the same arguments, and
+    /// replay them instead of evaluating the calls again.
+    #[arg(long)]
+    cache_operations: bool,
+
+    /// With `--exec`, run the entry twice with the same seed and report where the runs differ
+    /// instead of printing their output.
+    #[arg(long)]
+    audit_determinism: bool,
+
+    /// With `--exec`, write a trace of the calls, gates and measurements of the run to the file in
+    /// the Chrome trace event format, which Perfetto opens.
+    #[arg(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    /// With `--exec`, keep the Pauli gates of the run in a classical frame instead of applying them
+    /// to the simulated state, which is faster for programs heavy in Pauli corrections.
+    #[arg(long, conflicts_with = "trace")]
+    pauli_frame: bool,
+
+    /// When to color diagnostics: `auto`, `always` or `never`.
+    #[arg(long, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Wrap diagnostics to this many columns instead of the width of the terminal.
+    #[arg(long, value_name = "COLUMNS")]
+    diagnostic_width: Option<usize>,
 }
 
 struct TerminalReceiver;
@@ -70,71 +113,126 @@ impl Receiver for TerminalReceiver {
         println!("{msg}");
         Ok(())
     }
+
+    fn message_to(&mut self, channel: &str, msg: &str) -> Result<(), output::Error> {
+        if channel == "stderr" {
+            eprintln!("{msg}");
+        } else {
+            println!("{msg}");
+        }
+        Ok(())
+    }
 }
 
 fn main() -> miette::Result<ExitCode> {
     let cli = Cli::parse();
+    let renderer = Renderer::new(cli.color, cli.diagnostic_width);
     let mut sources = cli
         .sources
         .iter()
         .map(read_source)
         .collect::<miette::Result<Vec<_>>>()?;
 
+    let mut features = Vec::new();
+    let mut edition = Edition::default();
     if sources.is_empty() {
         let fs = StdFs;
-        let manifest = Manifest::load(cli.qsharp_json)?;
-        if let Some(manifest) = manifest {
+        let start = match cli.qsharp_json {
+            Some(path) => path,
+            None => env::current_dir()
+                .into_diagnostic()
+                .context("could not read the current directory")?,
+        };
+        if let Some(manifest) = fs.find_manifest(&start)? {
             let project = fs.load_project(&manifest)?;
             let mut project_sources = project.sources;
 
             sources.append(&mut project_sources);
+            features = project.manifest.features;
+            edition = project.manifest.edition;
         }
     }
+    let precision = if cli.single_precision {
+        Precision::Single
+    } else {
+        Precision::Double
+    };
     if cli.exec {
         let mut interpreter = match Interpreter::new(
             !cli.nostdlib,
-            SourceMap::new(sources, cli.entry.map(std::convert::Into::into)),
+            SourceMap::new(sources, cli.entry.map(std::convert::Into::into))
+                .with_features(features)
+                .with_edition(edition),
             PackageType::Exe,
             RuntimeCapabilityFlags::all(),
         ) {
             Ok(interpreter) => interpreter,
             Err(errors) => {
                 for error in errors {
-                    eprintln!("error: {:?}", Report::new(error));
+                    eprintln!("{}", renderer.render(&error));
                 }
                 return Ok(ExitCode::FAILURE);
             }
         };
-        return Ok(print_exec_result(
-            interpreter.eval_entry(&mut TerminalReceiver),
-        ));
+        interpreter.set_qubit_limit(cli.qubit_limit);
+        interpreter.set_precision(precision);
+        interpreter.set_operation_cache(cli.cache_operations);
+        print_warnings(&renderer, &mut interpreter);
+        if let Some(warning) = interpreter.check_qubit_limit() {
+            eprintln!("{}", renderer.render(&warning));
+        }
+        if cli.audit_determinism {
+            let report = interpreter.audit_entry(Encoding::Utf8);
+            println!("{report}");
+            return Ok(if report.is_deterministic() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            });
+        }
+        let result = run_entry(&mut interpreter, cli.trace.as_deref(), cli.pauli_frame)?;
+        if let Some(warning) = interpreter.check_precision() {
+            eprintln!("{}", renderer.render(&warning));
+        }
+        return Ok(print_exec_result(&renderer, result));
     }
 
     let mut interpreter = match Interpreter::new(
         !cli.nostdlib,
-        SourceMap::new(sources, None),
+        SourceMap::new(sources, None)
+            .with_features(features)
+            .with_edition(edition),
         PackageType::Lib,
         RuntimeCapabilityFlags::all(),
     ) {
         Ok(interpreter) => interpreter,
         Err(errors) => {
             for error in errors {
-                eprintln!("error: {:?}", Report::new(error));
+                eprintln!("{}", renderer.render(&error));
             }
             return Ok(ExitCode::FAILURE);
         }
     };
 
+    interpreter.set_precision(precision);
+    interpreter.set_operation_cache(cli.cache_operations);
+    print_warnings(&renderer, &mut interpreter);
     if let Some(entry) = cli.entry {
-        print_interpret_result(interpreter.eval_fragments(&mut TerminalReceiver, &entry));
+        let result = interpreter.eval_fragments(&mut TerminalReceiver, &entry);
+        print_warnings(&renderer, &mut interpreter);
+        print_interpret_result(&renderer, &interpreter, result);
     }
 
-    repl(&mut interpreter, &mut TerminalReceiver).into_diagnostic()?;
+    repl(&mut interpreter, &mut TerminalReceiver, &renderer).into_diagnostic()?;
 
     Ok(ExitCode::SUCCESS)
 }
 
-fn repl(interpreter: &mut Interpreter, receiver: &mut impl Receiver) -> io::Result<()> {
+fn repl(
+    interpreter: &mut Interpreter,
+    receiver: &mut impl Receiver,
+    renderer: &Renderer,
+) -> io::Result<()> {
     print_prompt(false);
 
     let mut lines = io::BufReader::new(io::stdin()).lines();
@@ -153,7 +251,9 @@ fn repl(interpreter: &mut Interpreter, receiver: &mut impl Receiver) -> io::Resu
         }
 
         if !line.trim().is_empty() {
-            print_interpret_result(interpreter.eval_fragments(receiver, &line));
+            let result = interpreter.eval_fragments(receiver, &line);
+            print_warnings(renderer, interpreter);
+            print_interpret_result(renderer, interpreter, result);
         }
 
         print_prompt(false);
@@ -163,13 +263,28 @@ fn repl(interpreter: &mut Interpreter, receiver: &mut impl Receiver) -> io::Resu
     Ok(())
 }
 
-fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
-    let path = path.as_ref();
-    let contents = fs::read_to_string(path)
+/// Runs the entry expression, writing a trace of the run to the file if one is given.
+fn run_entry(
+    interpreter: &mut Interpreter,
+    trace: Option<&Path>,
+    pauli_frame: bool,
+) -> miette::Result<InterpretResult> {
+    let Some(path) = trace else {
+        return Ok(if pauli_frame {
+            interpreter.eval_entry_with_pauli_frame(&mut TerminalReceiver)
+        } else {
+            interpreter.eval_entry(&mut TerminalReceiver)
+        });
+    };
+    let (result, trace) = interpreter.trace_entry(&mut TerminalReceiver);
+    std::fs::write(path, trace)
         .into_diagnostic()
-        .with_context(|| format!("could not read source file `{}`", path.display()))?;
+        .with_context(|| format!("could not write trace to {}", path.display()))?;
+    Ok(result)
+}
 
-    Ok((path.to_string_lossy().into(), contents.into()))
+fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
+    StdFs.read_file(path.as_ref())
 }
 
 fn print_prompt(continuation: bool) {
@@ -182,23 +297,32 @@ fn print_prompt(continuation: bool) {
     io::stdout().flush().expect("standard out should flush");
 }
 
-fn print_interpret_result(result: InterpretResult) {
+/// Prints the warnings of the sources and fragments compiled since they were last printed.
+fn print_warnings(renderer: &Renderer, interpreter: &mut Interpreter) {
+    for warning in interpreter.take_warnings() {
+        eprintln!("{}", renderer.render(&warning));
+    }
+}
+
+fn print_interpret_result(renderer: &Renderer, interpreter: &Interpreter, result: InterpretResult) {
     match result {
         Ok(Value::Tuple(items)) if items.is_empty() => {}
-        Ok(value) => println!("{value}"),
+        Ok(value) => println!("{}", interpreter.display_result(&value)),
         Err(errors) => {
             for error in errors {
                 if let Some(stack_trace) = error.stack_trace() {
                     eprintln!("{stack_trace}");
                 }
-                let report = Report::new(error);
-                eprintln!("error: {report:?}");
+                eprintln!("{}", renderer.render(&error));
             }
         }
     }
 }
 
-fn print_exec_result(result: Result<Value, Vec<interpret::Error>>) -> ExitCode {
+fn print_exec_result(
+    renderer: &Renderer,
+    result: Result<Value, Vec<interpret::Error>>,
+) -> ExitCode {
     match result {
         Ok(value) => {
             println!("{value}");
@@ -209,8 +333,7 @@ fn print_exec_result(result: Result<Value, Vec<interpret::Error>>) -> ExitCode {
                 if let Some(stack_trace) = error.stack_trace() {
                     eprintln!("{stack_trace}");
                 }
-                let report = Report::new(error);
-                eprintln!("error: {report:?}");
+                eprintln!("{}", renderer.render(&error));
             }
             ExitCode::FAILURE
         }
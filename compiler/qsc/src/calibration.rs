@@ -0,0 +1,200 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Imports the calibration data of a device as a noise model, so that noisy simulations reflect
+//! the error rates of that device.
+//!
+//! A calibration is a JSON object with the properties of each physical qubit and each calibrated
+//! gate:
+//!
+//! ```json
+//! {
+//!     "qubits": [{ "t1": 120.0, "t2": 85.0, "readout_error": 0.015 }],
+//!     "gates": [{ "gate": "cx", "qubits": [0, 1], "error": 0.008, "duration": 0.3 }]
+//! }
+//! ```
+//!
+//! Simulated qubits stand for the physical qubits with the same index, which are the qubits in the
+//! order the program allocates them. Each gate applies depolarizing noise with its error rate,
+//! followed by the thermal relaxation of each of its qubits over its duration, which must be given
+//! in the same unit of time as `t1` and `t2`. Measurements suffer the readout error of the qubit.
+//! Gates on qubits that the calibration doesn't cover, and qubits past the device, get the average
+//! of the calibration instead. Intrinsics that the calibration doesn't cover are noiseless.
+//!
+//! Error rates must be probabilities, between 0 and 1.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_eval::noise::{NoiseModel, PauliNoise};
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Error {
+    #[error("the calibration is not valid: {0}")]
+    Json(String),
+    #[error("the calibrated gate `{0}` does not correspond to a Q# intrinsic")]
+    UnknownGate(String),
+    #[error("the {0} is {1}, which is not a probability between 0 and 1")]
+    Rate(String, f64),
+}
+
+#[derive(Deserialize)]
+struct Calibration {
+    #[serde(default)]
+    qubits: Vec<Qubit>,
+    #[serde(default)]
+    gates: Vec<Gate>,
+}
+
+#[derive(Deserialize)]
+struct Qubit {
+    t1: Option<f64>,
+    t2: Option<f64>,
+    #[serde(default)]
+    readout_error: f64,
+}
+
+#[derive(Deserialize)]
+struct Gate {
+    gate: String,
+    #[serde(default)]
+    qubits: Vec<usize>,
+    #[serde(default)]
+    error: f64,
+    #[serde(default)]
+    duration: f64,
+}
+
+/// Calibrated gates that have no counterpart among the gate intrinsics, whose entries are
+/// skipped.
+const SKIPPED: &[&str] = &["barrier", "delay", "id", "measure", "reset"];
+
+/// The intrinsics, by their backend operation names, that a calibrated gate stands for.
+fn intrinsics(gate: &str) -> Option<&'static [&'static str]> {
+    Some(match gate {
+        "x" => &["x"],
+        "y" => &["y"],
+        "z" => &["z"],
+        "h" => &["h"],
+        "s" => &["s", "sadj"],
+        "sdg" => &["sadj"],
+        "t" => &["t", "tadj"],
+        "tdg" => &["tadj"],
+        "rx" | "sx" => &["rx"],
+        "ry" => &["ry"],
+        "rz" | "p" | "u1" => &["rz"],
+        "rxx" => &["rxx"],
+        "ryy" => &["ryy"],
+        "rzz" => &["rzz"],
+        "cx" | "cnot" | "ecr" => &["cx"],
+        "cy" => &["cy"],
+        "cz" => &["cz"],
+        "ccx" | "ccnot" | "toffoli" => &["ccx"],
+        "swap" => &["swap"],
+        _ => return None,
+    })
+}
+
+/// Builds the noise model described by the JSON device calibration `json`.
+///
+/// # Errors
+///
+/// Returns an error if the calibration isn't valid JSON in the expected shape, if it calibrates a
+/// gate that doesn't correspond to any Q# intrinsic, or if an error rate isn't a probability.
+pub fn noise_model(json: &str) -> Result<NoiseModel, Error> {
+    let calibration: Calibration =
+        serde_json::from_str(json).map_err(|e| Error::Json(e.to_string()))?;
+    validate(&calibration)?;
+
+    let qubits = &calibration.qubits;
+    let t1 = mean(qubits.iter().filter_map(|q| q.t1));
+    let t2 = mean(qubits.iter().filter_map(|q| q.t2));
+    let readout = mean(qubits.iter().map(|q| q.readout_error)).unwrap_or_default();
+
+    let mut entries: FxHashMap<&str, Vec<&Gate>> = FxHashMap::default();
+    for gate in &calibration.gates {
+        let name = gate.gate.to_lowercase();
+        if SKIPPED.contains(&name.as_str()) {
+            continue;
+        }
+        let names = intrinsics(&name).ok_or_else(|| Error::UnknownGate(gate.gate.clone()))?;
+        for &name in names {
+            entries.entry(name).or_default().push(gate);
+        }
+    }
+
+    // The noise of a gate with the error and duration on a qubit with the relaxation times.
+    let noise = |error, duration, t1: Option<f64>, t2: Option<f64>| {
+        let noise = PauliNoise::depolarizing(error);
+        match (t1, t2) {
+            (Some(t1), Some(t2)) => noise.then(PauliNoise::relaxation(duration, t1, t2)),
+            _ => noise,
+        }
+    };
+
+    let mut gates = FxHashMap::default();
+    let mut qubit_gates: FxHashMap<String, FxHashMap<Vec<usize>, Vec<PauliNoise>>> =
+        FxHashMap::default();
+    for (name, entries) in entries {
+        let error = mean(entries.iter().map(|g| g.error)).unwrap_or_default();
+        let duration = mean(entries.iter().map(|g| g.duration)).unwrap_or_default();
+        gates.insert(name.to_string(), noise(error, duration, t1, t2));
+
+        for gate in entries.iter().filter(|g| !g.qubits.is_empty()) {
+            // Qubits past the device relax like the average qubit.
+            let per_qubit = gate
+                .qubits
+                .iter()
+                .map(|&q| match qubits.get(q) {
+                    Some(qubit) => noise(gate.error, gate.duration, qubit.t1, qubit.t2),
+                    None => noise(gate.error, gate.duration, t1, t2),
+                })
+                .collect();
+            qubit_gates
+                .entry(name.to_string())
+                .or_default()
+                .insert(gate.qubits.clone(), per_qubit);
+        }
+    }
+
+    Ok(NoiseModel {
+        default: PauliNoise::default(),
+        gates,
+        qubit_gates,
+        readout,
+        qubit_readout: qubits.iter().map(|q| q.readout_error).collect(),
+    })
+}
+
+/// Checks that the error rates of the calibration are probabilities.
+fn validate(calibration: &Calibration) -> Result<(), Error> {
+    let rate = |name: String, value: f64| {
+        if (0.0..=1.0).contains(&value) {
+            Ok(())
+        } else {
+            Err(Error::Rate(name, value))
+        }
+    };
+    for (index, qubit) in calibration.qubits.iter().enumerate() {
+        rate(
+            format!("readout error of qubit {index}"),
+            qubit.readout_error,
+        )?;
+    }
+    for gate in &calibration.gates {
+        rate(
+            format!("error of gate `{}` on qubits {:?}", gate.gate, gate.qubits),
+            gate.error,
+        )?;
+    }
+    Ok(())
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0_u32), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / f64::from(count))
+}
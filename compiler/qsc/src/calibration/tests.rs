@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{noise_model, Error};
+use crate::noise::PauliNoise;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-12
+}
+
+#[test]
+fn gate_errors_become_depolarizing_noise() {
+    let model = noise_model(
+        r#"{ "gates": [
+            { "gate": "cx", "qubits": [0, 1], "error": 0.01 },
+            { "gate": "CX", "qubits": [1, 2], "error": 0.03 },
+            { "gate": "sx", "qubits": [0], "error": 0.003 }
+        ] }"#,
+    )
+    .expect("calibration should be valid");
+    assert_eq!(model.gate("cx"), PauliNoise::depolarizing(0.02));
+    assert_eq!(model.gate("rx"), PauliNoise::depolarizing(0.003));
+    assert_eq!(model.gate("h"), PauliNoise::default());
+    assert!(close(model.readout, 0.0));
+}
+
+#[test]
+fn qubit_properties_set_relaxation_and_readout() {
+    let model = noise_model(
+        r#"{
+            "qubits": [
+                { "t1": 100.0, "t2": 50.0, "readout_error": 0.01 },
+                { "t1": 300.0, "t2": 150.0, "readout_error": 0.03 }
+            ],
+            "gates": [{ "gate": "x", "duration": 2.0 }, { "gate": "measure", "error": 0.5 }]
+        }"#,
+    )
+    .expect("calibration should be valid");
+    assert_eq!(model.gate("x"), PauliNoise::relaxation(2.0, 200.0, 100.0));
+    assert!(close(model.readout, 0.02));
+}
+
+#[test]
+fn phase_gates_cover_their_adjoints() {
+    let model = noise_model(r#"{ "gates": [{ "gate": "t", "error": 0.006 }] }"#)
+        .expect("calibration should be valid");
+    assert_eq!(model.gate("t"), PauliNoise::depolarizing(0.006));
+    assert_eq!(model.gate("tadj"), PauliNoise::depolarizing(0.006));
+}
+
+#[test]
+fn unknown_gates_are_errors() {
+    assert_eq!(
+        noise_model(r#"{ "gates": [{ "gate": "u3", "error": 0.001 }] }"#),
+        Err(Error::UnknownGate("u3".to_string()))
+    );
+}
+
+#[test]
+fn malformed_calibrations_are_errors() {
+    assert!(matches!(
+        noise_model(r#"{ "qubits": [{ "t1": "long" }] }"#),
+        Err(Error::Json(_))
+    ));
+}
+
+#[test]
+fn gates_and_readout_keep_the_rates_of_their_qubits() {
+    let model = noise_model(
+        r#"{
+            "qubits": [
+                { "t1": 100.0, "t2": 50.0, "readout_error": 0.01 },
+                { "t1": 300.0, "t2": 150.0, "readout_error": 0.03 }
+            ],
+            "gates": [
+                { "gate": "cx", "qubits": [0, 1], "error": 0.01, "duration": 2.0 },
+                { "gate": "cx", "qubits": [1, 0], "error": 0.03, "duration": 2.0 }
+            ]
+        }"#,
+    )
+    .expect("calibration should be valid");
+    let depolarizing = PauliNoise::depolarizing(0.01);
+    assert_eq!(
+        model.gate_on("cx", &[0, 1], 0),
+        depolarizing.then(PauliNoise::relaxation(2.0, 100.0, 50.0))
+    );
+    assert_eq!(
+        model.gate_on("cx", &[0, 1], 1),
+        depolarizing.then(PauliNoise::relaxation(2.0, 300.0, 150.0))
+    );
+    assert_eq!(
+        model.gate_on("cx", &[1, 2], 0),
+        PauliNoise::depolarizing(0.02).then(PauliNoise::relaxation(2.0, 200.0, 100.0))
+    );
+    assert!(close(model.readout_of(0), 0.01));
+    assert!(close(model.readout_of(1), 0.03));
+    assert!(close(model.readout_of(2), 0.02));
+}
+
+#[test]
+fn rates_outside_zero_and_one_are_errors() {
+    assert_eq!(
+        noise_model(r#"{ "qubits": [{ "readout_error": 1.5 }] }"#),
+        Err(Error::Rate("readout error of qubit 0".to_string(), 1.5))
+    );
+    assert_eq!(
+        noise_model(r#"{ "gates": [{ "gate": "cx", "qubits": [0, 1], "error": -0.1 }] }"#),
+        Err(Error::Rate(
+            "error of gate `cx` on qubits [0, 1]".to_string(),
+            -0.1
+        ))
+    );
+}
@@ -0,0 +1,581 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A circuit model: a list of qubit wires and the operations applied to them in order. Operations
+//! can be conditioned on measured results, as in programs for the Adaptive profile. Circuits can
+//! come from anywhere, such as an importer of another format or a simulation recorded by a
+//! [`CircuitBuilder`], and can be turned back into Q#
+//! source with [`Circuit::to_qsharp`], exported as OpenQASM 3 with [`Circuit::to_qasm`], saved as
+//! JSON with [`Circuit::to_json`], drawn with [`render_svg`] or typeset for LaTeX with
+//! [`render_latex`]. Operations can be grouped into operations with children, which are
+//! expanded into their children when the circuit is turned into a program. Operations can be
+//! timed with a [`GateDurationModel`] to find the [`Circuit::critical_path`], and grouped into
+//! the [`Circuit::moments`] that the renderers lay out as columns.
+
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+mod tests;
+
+mod builder;
+mod compact;
+mod diff;
+mod latex;
+mod layout;
+mod qasm;
+mod svg;
+mod timing;
+
+pub use builder::{CircuitBuilder, CircuitConfig};
+pub use diff::{diff, Change, CircuitDiff};
+pub use latex::{render_latex, LatexOptions};
+pub use svg::render_svg;
+pub use timing::{CriticalPath, GateDurationModel, GateDurationTable};
+
+use miette::Diagnostic;
+use qsc_data_structures::{message::MessageParams, span::Span};
+use qsc_hir::hir::PackageId;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// The gate of a barrier, an annotation that marks a point of the program across its targets,
+/// such as the start of a phase of an algorithm, and doesn't act on them. Its label, if it has
+/// one, is its display arguments.
+pub const BARRIER: &str = "Barrier";
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Error {
+    #[error("gate {0} has no Q# equivalent")]
+    #[diagnostic(code("Qsc.Circuit.UnsupportedGate"))]
+    UnsupportedGate(String),
+
+    #[error("gate {0} expects {1} target qubit(s) and {2} argument(s)")]
+    #[diagnostic(code("Qsc.Circuit.GateShape"))]
+    GateShape(String, usize, usize),
+
+    #[error("argument {1} of gate {0} is not a number")]
+    #[diagnostic(code("Qsc.Circuit.InvalidArgument"))]
+    InvalidArgument(String, String),
+
+    #[error("gate {0} has no OpenQASM equivalent")]
+    #[diagnostic(code("Qsc.Circuit.UnsupportedQasmGate"))]
+    UnsupportedQasmGate(String),
+
+    #[error("operation {0} refers to qubit {1}, which is not in the circuit")]
+    #[diagnostic(code("Qsc.Circuit.UnknownQubit"))]
+    UnknownQubit(String, usize),
+
+    #[error("operation {0} is conditioned on a result of qubit {1} that has not been measured")]
+    #[diagnostic(code("Qsc.Circuit.UnknownResult"))]
+    UnknownResult(String, usize),
+
+    #[error("the circuit is not valid JSON: {0}")]
+    #[diagnostic(code("Qsc.Circuit.InvalidJson"))]
+    InvalidJson(String),
+
+    #[error("the circuit has version {0} of the JSON format, which is newer than this version")]
+    #[diagnostic(help("update to a newer version of the tools to read the circuit"))]
+    #[diagnostic(code("Qsc.Circuit.UnsupportedVersion"))]
+    UnsupportedVersion(u32),
+
+    #[error("qubit {0} is not in the circuit")]
+    #[diagnostic(code("Qsc.Circuit.RemapUnknownQubit"))]
+    RemapUnknownQubit(usize),
+
+    #[error("more than one qubit would be moved to qubit {0}")]
+    #[diagnostic(help("map each qubit to a different qubit, including those that are not moved"))]
+    #[diagnostic(code("Qsc.Circuit.RemapCollision"))]
+    RemapCollision(usize),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::UnsupportedGate(gate)
+            | Self::UnsupportedQasmGate(gate)
+            | Self::InvalidJson(gate) => vec![gate.clone()],
+            Self::GateShape(gate, targets, args) => {
+                vec![gate.clone(), targets.to_string(), args.to_string()]
+            }
+            Self::InvalidArgument(gate, arg) => vec![arg.clone(), gate.clone()],
+            Self::UnknownQubit(op, qubit) | Self::UnknownResult(op, qubit) => {
+                vec![op.clone(), qubit.to_string()]
+            }
+            Self::UnsupportedVersion(version) => vec![version.to_string()],
+            Self::RemapUnknownQubit(qubit) | Self::RemapCollision(qubit) => vec![qubit.to_string()],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Circuit {
+    pub operations: Vec<Operation>,
+    pub qubits: Vec<Qubit>,
+    /// Named groups of results, such as the results that the program returns, so that viewers can
+    /// label the results with names rather than by the qubits they were measured from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registers: Vec<ClassicalRegister>,
+}
+
+/// A circuit of the start of a program, such as one generated with at most a given number of
+/// operations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartialCircuit {
+    pub circuit: Circuit,
+    /// Whether the program applied operations that were left out of the circuit.
+    pub truncated: bool,
+}
+
+/// A qubit wire, with the number of classical results measured from it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Qubit {
+    pub id: usize,
+    pub num_children: usize,
+    /// The name that the program first bound the qubit to, such as `aux` or `ctls[1]`, which
+    /// diagrams show as the name of the wire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Operation {
+    pub gate: String,
+    /// The classical arguments of the gate, separated by commas, such as the angle of a rotation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_args: Option<String>,
+    pub is_controlled: bool,
+    pub is_adjoint: bool,
+    /// A measurement has the measured qubit as its control and the result as its target.
+    pub is_measurement: bool,
+    pub controls: Vec<Register>,
+    pub targets: Vec<Register>,
+    /// The operation is only applied when the condition holds, such as the result register that
+    /// controls a branch of an Adaptive profile program.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ClassicalExpr>,
+    /// The operations that this one is made of, such as the gates of a call to a user-defined
+    /// operation, which viewers can show in place of it. Gates have none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Operation>,
+    /// How long the operation takes, as set by [`Circuit::set_durations`], for viewers that draw
+    /// the schedule of the circuit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// The Q# code that the operation comes from, for editors to navigate to. Spans are only
+    /// meaningful with the compilation they come from, so they are not serialized.
+    #[serde(skip)]
+    pub source: Option<SourceSpan>,
+}
+
+/// A span of the sources of a package, which
+/// [`Location::from`](crate::location::Location::from) turns into a location in a source file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub package: PackageId,
+    pub span: Span,
+}
+
+/// A qubit wire, or one of the classical results measured from it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Register {
+    pub q_id: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c_id: Option<usize>,
+}
+
+impl Register {
+    #[must_use]
+    pub fn quantum(q_id: usize) -> Self {
+        Self { q_id, c_id: None }
+    }
+
+    #[must_use]
+    pub fn classical(q_id: usize, c_id: usize) -> Self {
+        Self {
+            q_id,
+            c_id: Some(c_id),
+        }
+    }
+}
+
+/// A named group of measured results. A result that is in no group is only known by its
+/// register.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClassicalRegister {
+    pub name: String,
+    pub results: Vec<Register>,
+}
+
+/// A condition on measured results, such as the branch of an Adaptive profile program that an
+/// operation is applied in.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClassicalExpr {
+    /// The result in the register is `One`, or `Zero` if the value is false.
+    Result(Register, bool),
+    Not(Box<ClassicalExpr>),
+    And(Box<ClassicalExpr>, Box<ClassicalExpr>),
+    Or(Box<ClassicalExpr>, Box<ClassicalExpr>),
+}
+
+/// A circuit as it is serialized, after the version of the format.
+#[derive(Deserialize, Serialize)]
+struct Versioned<C> {
+    #[serde(default = "first_version")]
+    version: u32,
+    #[serde(flatten)]
+    circuit: C,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+impl Circuit {
+    /// The version of the JSON format of circuits. It is increased when a field changes meaning
+    /// or is removed, but not when an optional field is added, so viewers can read circuits of any
+    /// version up to the one they were written for.
+    pub const JSON_VERSION: u32 = 1;
+
+    /// The JSON schema of the format, which documents each field for viewers written in other
+    /// languages.
+    pub const JSON_SCHEMA: &'static str = include_str!("circuit/schema.json");
+
+    /// Serializes the circuit as JSON, with the version of the format in a `version` field,
+    /// followed by the fields of the model in camel case, as described by
+    /// [`Circuit::JSON_SCHEMA`]. Fields that are empty or absent are left out, and conditions are
+    /// objects keyed by their kind, such as `{"result": [{"qId": 0, "cId": 0}, true]}`.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&Versioned {
+            version: Self::JSON_VERSION,
+            circuit: self,
+        })
+        .expect("circuit should serialize")
+    }
+
+    /// Parses a circuit serialized by [`Circuit::to_json`]. A circuit without a version is taken
+    /// to be of the first version.
+    /// # Errors
+    /// Returns an error if the JSON doesn't describe a circuit, or is of a newer version of the
+    /// format.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let versioned: Versioned<Self> =
+            serde_json::from_str(json).map_err(|e| Error::InvalidJson(e.to_string()))?;
+        if versioned.version > Self::JSON_VERSION {
+            return Err(Error::UnsupportedVersion(versioned.version));
+        }
+        Ok(versioned.circuit)
+    }
+
+    /// The label of the result in the classical registers of the circuit, which is the name of its
+    /// register, followed by its index if the register holds more than one result, such as `c1[0]`.
+    #[must_use]
+    pub fn result_label(&self, result: Register) -> Option<String> {
+        self.registers.iter().find_map(|register| {
+            let index = register.results.iter().position(|&r| r == result)?;
+            Some(if register.results.len() == 1 {
+                register.name.clone()
+            } else {
+                format!("{}[{index}]", register.name)
+            })
+        })
+    }
+
+    /// Generates a Q# namespace with one operation that applies the circuit. The qubits are
+    /// allocated as one register, indexed by their position in the circuit, and are reset before
+    /// they are released. If the circuit measures any qubits, the operation returns the results in
+    /// the order they were measured. Conditioned operations are applied in `if` statements, and
+    /// a conditioned measurement is `Zero` when its condition doesn't hold.
+    pub fn to_qsharp(&self, namespace: &str, name: &str) -> Result<String, Error> {
+        let mut body = String::new();
+        let mut results = Results::default();
+        for op in &expand(&self.operations) {
+            let condition = results.condition(op, &QSHARP)?;
+            if op.is_measurement {
+                let qubit = op
+                    .controls
+                    .first()
+                    .ok_or_else(|| Error::GateShape(op.gate.clone(), 1, 0))?;
+                let measure = format!("M({})", self.qubit(op, qubit.q_id)?);
+                let result = format!("c{}", results.measure(op)?);
+                let _ = match condition {
+                    Some(condition) => writeln!(
+                        body,
+                        "        let {result} = if {condition} {{ {measure} }} else {{ Zero }};"
+                    ),
+                    None => writeln!(body, "        let {result} = {measure};"),
+                };
+            } else {
+                let call = self.call(op)?;
+                let _ = match condition {
+                    Some(condition) => writeln!(body, "        if {condition} {{ {call}; }}"),
+                    None => writeln!(body, "        {call};"),
+                };
+            }
+        }
+        let results = (0..results.len())
+            .map(|index| format!("c{index}"))
+            .collect::<Vec<_>>();
+
+        let mut qsharp = String::new();
+        let _ = writeln!(qsharp, "namespace {namespace} {{");
+        let output = if results.is_empty() {
+            "Unit"
+        } else {
+            "Result[]"
+        };
+        let _ = writeln!(qsharp, "    operation {name}() : {output} {{");
+        if !self.qubits.is_empty() {
+            let _ = writeln!(qsharp, "        use qs = Qubit[{}];", self.qubits.len());
+        }
+        qsharp.push_str(&body);
+        if !self.qubits.is_empty() {
+            qsharp.push_str("        ResetAll(qs);\n");
+        }
+        if !results.is_empty() {
+            let _ = writeln!(qsharp, "        [{}]", results.join(", "));
+        }
+        qsharp.push_str("    }\n}\n");
+        Ok(qsharp)
+    }
+
+    /// The position of the qubit in the circuit.
+    fn row(&self, op: &Operation, q_id: usize) -> Result<usize, Error> {
+        self.qubits
+            .iter()
+            .position(|qubit| qubit.id == q_id)
+            .ok_or_else(|| Error::UnknownQubit(op.gate.clone(), q_id))
+    }
+
+    fn qubit(&self, op: &Operation, q_id: usize) -> Result<String, Error> {
+        self.row(op, q_id).map(|index| format!("qs[{index}]"))
+    }
+
+    fn call(&self, op: &Operation) -> Result<String, Error> {
+        if op.gate == BARRIER {
+            let qubits = op
+                .targets
+                .iter()
+                .map(|target| self.qubit(op, target.q_id))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(format!(
+                "Microsoft.Quantum.Diagnostics.Barrier([{}], {:?})",
+                qubits.join(", "),
+                op.display_args.as_deref().unwrap_or_default()
+            ));
+        }
+        let (callable, qubits, args) = match op.gate.as_str() {
+            "I" | "H" | "X" | "Y" | "Z" | "S" | "T" | "Reset" => (op.gate.as_str(), 1, 0),
+            "Rx" | "Ry" | "Rz" => (op.gate.as_str(), 1, 1),
+            "Rxx" | "Ryy" | "Rzz" => (op.gate.as_str(), 2, 1),
+            "SWAP" => ("SWAP", 2, 0),
+            // Controlled gates that were flattened into a gate of their own.
+            "CNOT" | "CX" => return self.controlled(op, "X", 1),
+            "CY" => return self.controlled(op, "Y", 1),
+            "CZ" => return self.controlled(op, "Z", 1),
+            "CCNOT" | "CCX" => return self.controlled(op, "X", 2),
+            _ => return Err(Error::UnsupportedGate(op.gate.clone())),
+        };
+
+        let mut targets = op
+            .targets
+            .iter()
+            .map(|target| self.qubit(op, target.q_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut inputs = parse_args(op)?;
+        if targets.len() != qubits || inputs.len() != args {
+            return Err(Error::GateShape(op.gate.clone(), qubits, args));
+        }
+        inputs.append(&mut targets);
+        let input = inputs.join(", ");
+
+        let mut callee = callable.to_string();
+        if op.is_adjoint {
+            callee = format!("Adjoint {callee}");
+        }
+        if op.is_controlled {
+            let controls = op
+                .controls
+                .iter()
+                .map(|control| self.qubit(op, control.q_id))
+                .collect::<Result<Vec<_>, _>>()?;
+            let input = if qubits + args > 1 {
+                format!("({input})")
+            } else {
+                input
+            };
+            Ok(format!(
+                "Controlled {callee}([{}], {input})",
+                controls.join(", ")
+            ))
+        } else {
+            Ok(format!("{callee}({input})"))
+        }
+    }
+
+    /// A gate such as `CNOT`, whose leading targets are the controls of a Pauli gate.
+    fn controlled(&self, op: &Operation, pauli: &str, controls: usize) -> Result<String, Error> {
+        let registers = op.controls.iter().chain(&op.targets).collect::<Vec<_>>();
+        if op.is_controlled || registers.len() != controls + 1 || op.display_args.is_some() {
+            return Err(Error::GateShape(op.gate.clone(), controls + 1, 0));
+        }
+        let qubits = registers
+            .iter()
+            .map(|register| self.qubit(op, register.q_id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (target, controls) = qubits.split_last().expect("gate should have a target");
+        if controls.len() == 1 && pauli == "X" {
+            Ok(format!("CNOT({}, {target})", controls[0]))
+        } else {
+            Ok(format!(
+                "Controlled {pauli}([{}], {target})",
+                controls.join(", ")
+            ))
+        }
+    }
+}
+
+/// How a language spells the conditions on results, given the index of each result in the order
+/// it was measured.
+struct Syntax {
+    result: fn(usize, bool) -> String,
+    not: &'static str,
+    and: &'static str,
+    or: &'static str,
+}
+
+const QSHARP: Syntax = Syntax {
+    result: |index, one| format!("c{index} == {}", if one { "One" } else { "Zero" }),
+    not: "not ",
+    and: " and ",
+    or: " or ",
+};
+
+/// The results measured so far, in the order they were measured.
+#[derive(Default)]
+struct Results(Vec<Register>);
+
+impl Results {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Records the results of the measurements that the operation is made of.
+    fn measure_children(&mut self, op: &Operation) -> Result<(), Error> {
+        for child in &op.children {
+            if child.is_measurement {
+                self.measure(child)?;
+            }
+            self.measure_children(child)?;
+        }
+        Ok(())
+    }
+
+    /// Records the result of the measurement, returning its index.
+    fn measure(&mut self, op: &Operation) -> Result<usize, Error> {
+        let register = op
+            .targets
+            .first()
+            .or(op.controls.first())
+            .ok_or_else(|| Error::GateShape(op.gate.clone(), 1, 0))?;
+        self.0.push(*register);
+        Ok(self.0.len() - 1)
+    }
+
+    /// The condition of the operation, if it has one, in terms of the results measured so far.
+    fn condition(&self, op: &Operation, syntax: &Syntax) -> Result<Option<String>, Error> {
+        op.condition
+            .as_ref()
+            .map(|condition| self.expr(op, condition, syntax))
+            .transpose()
+    }
+
+    fn expr(&self, op: &Operation, expr: &ClassicalExpr, syntax: &Syntax) -> Result<String, Error> {
+        Ok(match expr {
+            ClassicalExpr::Result(register, one) => {
+                // A register that was measured more than once refers to its latest result.
+                let index = self
+                    .0
+                    .iter()
+                    .rposition(|result| result == register)
+                    .ok_or_else(|| Error::UnknownResult(op.gate.clone(), register.q_id))?;
+                (syntax.result)(index, *one)
+            }
+            ClassicalExpr::Not(inner) => {
+                format!("{}({})", syntax.not, self.expr(op, inner, syntax)?)
+            }
+            ClassicalExpr::And(lhs, rhs) => format!(
+                "({}{}{})",
+                self.expr(op, lhs, syntax)?,
+                syntax.and,
+                self.expr(op, rhs, syntax)?
+            ),
+            ClassicalExpr::Or(lhs, rhs) => format!(
+                "({}{}{})",
+                self.expr(op, lhs, syntax)?,
+                syntax.or,
+                self.expr(op, rhs, syntax)?
+            ),
+        })
+    }
+}
+
+/// The operations with each operation that has children replaced by its expanded children, which
+/// are only applied when its condition holds as well as their own.
+fn expand(operations: &[Operation]) -> Vec<Operation> {
+    let mut expanded = Vec::new();
+    for op in operations {
+        if op.children.is_empty() {
+            expanded.push(op.clone());
+            continue;
+        }
+        for mut child in expand(&op.children) {
+            child.condition = match (op.condition.clone(), child.condition) {
+                (Some(parent), Some(own)) => {
+                    Some(ClassicalExpr::And(Box::new(parent), Box::new(own)))
+                }
+                (parent, own) => parent.or(own),
+            };
+            expanded.push(child);
+        }
+    }
+    expanded
+}
+
+/// Parses the arguments of the gate as Q# `Double` literals.
+fn parse_args(op: &Operation) -> Result<Vec<String>, Error> {
+    let Some(args) = &op.display_args else {
+        return Ok(Vec::new());
+    };
+    args.split(',')
+        .map(|arg| {
+            let arg = arg.trim();
+            match arg.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok(format!("{value:?}")),
+                _ => Err(Error::InvalidArgument(op.gate.clone(), arg.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// Replaces each character of the text that `replace` maps to a replacement, as the renderers do
+/// to escape the characters that are special in their formats.
+fn escape_with(text: &str, replace: impl Fn(char) -> Option<&'static str>) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match replace(c) {
+            Some(replacement) => escaped.push_str(replacement),
+            None => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,636 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Generates circuits from simulations. A backend records the gates and measurements that reach
+//! the simulator, with the calls that led to each of them, and the calls are then either expanded
+//! into their gates or drawn as one box, as the [`CircuitConfig`] asks. A box holds the operations
+//! of its call as children, so that viewers can expand it. For programs too long to draw in full,
+//! the builder can stop recording after a number of operations, and the gates can be taken as the
+//! program runs instead of all at the end.
+//!
+//! Measurements return the index of the measurement rather than its outcome, so that the results
+//! that the program returns can be grouped into the classical registers of the circuit. The
+//! builder keeps the outcome that the wrapped backend returned for each index, so programs can
+//! still compare results, and the circuit has the gates of the branches they took.
+//!
+//! Intrinsics that the simulator doesn't know but that act on qubits, such as the native gates of a
+//! device, are recorded as opaque gates named after their callables, with their classical arguments
+//! as display arguments. They have no effect on the simulation. Barriers are recorded as
+//! [`BARRIER`] gates, labelled with their labels.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Circuit, ClassicalRegister, Operation, Qubit, Register, SourceSpan, BARRIER};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{
+    backend::{Backend, BasisPattern},
+    debug::{map_fir_package_to_hir, Frame},
+    val::{self, Value},
+};
+use qsc_fir::fir::Pauli;
+
+/// How far a generated circuit breaks the operations it calls down into their gates.
+#[derive(Clone, Debug, Default)]
+pub struct CircuitConfig {
+    /// The number of levels of calls below the entry that are expanded into the operations they
+    /// call. Operations called deeper than that are drawn as one box, so a depth of zero draws each
+    /// operation that the entry calls as a box. There is no limit if it is `None`.
+    pub max_decomposition_depth: Option<usize>,
+    /// Operations that are drawn as one box wherever they are called, such as `SWAPfromExp`.
+    pub preserve_named_operations: Vec<String>,
+    /// Whether qubits that are first used after another qubit was reset for the last time are
+    /// drawn on the wire of that qubit, with [`Circuit::compact_qubits`]. Otherwise each qubit id
+    /// has a wire of its own.
+    pub compact_qubits: bool,
+    /// Whether the wires of qubits that no operation uses are removed, with
+    /// [`Circuit::remove_unused_qubits`], after qubits are compacted.
+    pub remove_unused_qubits: bool,
+}
+
+/// A measurement, with the register of its result if it was recorded.
+struct Measured {
+    register: Option<Register>,
+    /// The outcome that the wrapped backend returned.
+    outcome: val::Result,
+}
+
+/// An operation that reached the simulator, with the calls that led to it, outermost first.
+struct Recorded {
+    op: Operation,
+    stack: Vec<Frame>,
+}
+
+/// A backend that records the gates and measurements of the backend it wraps as a circuit.
+///
+/// Calls are found from the call stack that the evaluator reports before each intrinsic, so
+/// consecutive calls from the same call site are drawn as one box.
+pub struct CircuitBuilder<'a, B: Backend> {
+    backend: &'a mut B,
+    /// The calls that led to the last intrinsic, outermost first.
+    stack: Vec<Frame>,
+    qubits: Vec<Qubit>,
+    /// Each measurement, by the index that it returned as its result.
+    results: Vec<Measured>,
+    operations: Vec<Recorded>,
+    /// The number of operations recorded, including those already taken as gates.
+    num_recorded: usize,
+    max_operations: Option<usize>,
+    truncated: bool,
+}
+
+impl<'a, B: Backend> CircuitBuilder<'a, B> {
+    pub fn new(backend: &'a mut B) -> Self {
+        Self {
+            backend,
+            stack: Vec::new(),
+            qubits: Vec::new(),
+            results: Vec::new(),
+            operations: Vec::new(),
+            num_recorded: 0,
+            max_operations: None,
+            truncated: false,
+        }
+    }
+
+    /// Stops recording once `max` operations have been recorded. The operations that reach the
+    /// simulator after that are still applied, but left out of the circuit.
+    #[must_use]
+    pub fn with_max_operations(mut self, max: usize) -> Self {
+        self.max_operations = Some(max);
+        self
+    }
+
+    /// Whether operations were left out because the maximum number of operations was reached.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Takes the operations recorded since the last call as gates, without the calls that led to
+    /// them, so that they can be passed on while the program runs rather than kept until it ends.
+    pub fn take_gates(&mut self) -> impl Iterator<Item = Operation> + '_ {
+        self.operations.drain(..).map(|recorded| recorded.op)
+    }
+
+    /// Returns the circuit, with the calls boxed as the config asks. Boxes are labelled with the
+    /// names of their callables, which `name` gives without functors, and hold the operations of
+    /// their calls as children, grouped by call in turn. A call that applied only one operation,
+    /// such as `H`, is drawn as that operation rather than as a box. The results in `output`, the
+    /// value that the program returned, are grouped into classical registers named after where
+    /// they are in it.
+    #[must_use]
+    pub fn finish(
+        self,
+        config: &CircuitConfig,
+        output: &Value,
+        name: impl Fn(&Frame) -> String,
+    ) -> Circuit {
+        let mut operations = Vec::new();
+        let mut rest = self.operations.as_slice();
+        while let Some(first) = rest.first() {
+            let Some(depth) = box_depth(&first.stack, config, &name) else {
+                operations.push(first.op.clone());
+                rest = &rest[1..];
+                continue;
+            };
+            let frames = &first.stack[..=depth];
+            let len = rest
+                .iter()
+                .take_while(|recorded| {
+                    box_depth(&recorded.stack, config, &name)
+                        .is_some_and(|depth| recorded.stack[..=depth] == *frames)
+                })
+                .count();
+            operations.push(call(&rest[..len], depth, &name));
+            rest = &rest[len..];
+        }
+        let mut registers = Vec::new();
+        classical_registers(output, "c", &self.results, &mut registers);
+        let mut circuit = Circuit {
+            operations,
+            qubits: self.qubits,
+            registers,
+        };
+        if config.compact_qubits {
+            circuit.compact_qubits();
+        }
+        if config.remove_unused_qubits {
+            circuit.remove_unused_qubits();
+        }
+        circuit
+    }
+
+    fn qubit(&mut self, q: usize) {
+        if !self.qubits.iter().any(|qubit| qubit.id == q) {
+            self.qubits.push(Qubit {
+                id: q,
+                num_children: 0,
+                label: None,
+            });
+        }
+    }
+
+    /// Whether the maximum number of operations has been recorded. Operations past it are left
+    /// out, which marks the circuit as truncated.
+    fn is_full(&mut self) -> bool {
+        let full = self
+            .max_operations
+            .is_some_and(|max| self.num_recorded >= max);
+        self.truncated |= full;
+        full
+    }
+
+    fn record(&mut self, op: Operation) {
+        if self.is_full() {
+            return;
+        }
+        self.num_recorded += 1;
+
+        // An operation comes from the innermost call made by code of the package of the entry, so
+        // that the gates of library operations point at where the program calls them.
+        let source = self.stack.first().and_then(|entry| {
+            self.stack
+                .iter()
+                .rev()
+                .find(|frame| frame.caller == entry.caller)
+                .map(source_span)
+        });
+        self.operations.push(Recorded {
+            op: Operation { source, ..op },
+            stack: self.stack.clone(),
+        });
+    }
+
+    fn gate(&mut self, gate: &str, qubits: &[usize]) {
+        self.record(Operation {
+            gate: gate.to_string(),
+            targets: qubits.iter().copied().map(Register::quantum).collect(),
+            ..Operation::default()
+        });
+    }
+
+    fn adjoint(&mut self, gate: &str, q: usize) {
+        self.record(Operation {
+            gate: gate.to_string(),
+            is_adjoint: true,
+            targets: vec![Register::quantum(q)],
+            ..Operation::default()
+        });
+    }
+
+    fn rotation(&mut self, gate: &str, theta: f64, qubits: &[usize]) {
+        self.record(Operation {
+            gate: gate.to_string(),
+            display_args: Some(format!("{theta:?}")),
+            targets: qubits.iter().copied().map(Register::quantum).collect(),
+            ..Operation::default()
+        });
+    }
+
+    /// Records a call to an intrinsic as an opaque gate on the qubits in its argument, unless the
+    /// argument has no qubits, returning whether it was recorded.
+    fn opaque_gate(&mut self, name: &str, arg: &Value) -> bool {
+        let mut qubits = Vec::new();
+        let mut args = Vec::new();
+        split_arg(arg, &mut qubits, &mut args);
+        if qubits.is_empty() {
+            return false;
+        }
+        self.record(Operation {
+            gate: name.to_string(),
+            display_args: (!args.is_empty()).then(|| args.join(", ")),
+            targets: qubits.into_iter().map(Register::quantum).collect(),
+            ..Operation::default()
+        });
+        true
+    }
+
+    /// Records a measurement of the qubit with the outcome that the wrapped backend returned,
+    /// returning the index of its result.
+    fn measurement(&mut self, q: usize, outcome: val::Result) -> usize {
+        // A result left out of the circuit has no register and isn't counted on its wire, so that
+        // the wire only has the results that are drawn.
+        if self.is_full() {
+            self.results.push(Measured {
+                register: None,
+                outcome,
+            });
+            return self.results.len() - 1;
+        }
+        let qubit = self
+            .qubits
+            .iter_mut()
+            .find(|qubit| qubit.id == q)
+            .expect("measured qubit should be allocated");
+        let result = Register::classical(q, qubit.num_children);
+        qubit.num_children += 1;
+        self.results.push(Measured {
+            register: Some(result),
+            outcome,
+        });
+        self.record(Operation {
+            gate: "Measure".to_string(),
+            is_measurement: true,
+            controls: vec![Register::quantum(q)],
+            targets: vec![result],
+            ..Operation::default()
+        });
+        self.results.len() - 1
+    }
+}
+
+/// Groups the results in the value into registers named after where they are in it. A result or an
+/// array of results is one register, named `c` if it is the whole value. The items of a tuple or
+/// of an array of other values are named after the name of their container and their index, with
+/// an underscore between them below the top level: the items of `(Result, Result[])` are `c0` and
+/// `c1`, and those of `Result[][]` are `c0`, `c1` and so on, with `c1_0` for an item nested in
+/// `c1`. Results that no measurement returned, such as literals, are left out.
+fn classical_registers(
+    value: &Value,
+    name: &str,
+    results: &[Measured],
+    registers: &mut Vec<ClassicalRegister>,
+) {
+    let result = |value: &Value| match value {
+        Value::Result(val::Result::Id(id)) => results.get(*id).and_then(|result| result.register),
+        _ => None,
+    };
+    let items = match value {
+        Value::Result(_) => {
+            registers.extend(result(value).map(|result| ClassicalRegister {
+                name: name.to_string(),
+                results: vec![result],
+            }));
+            return;
+        }
+        Value::Array(items) if items.iter().all(|item| matches!(item, Value::Result(_))) => {
+            let results = items.iter().filter_map(result).collect::<Vec<_>>();
+            if !results.is_empty() {
+                registers.push(ClassicalRegister {
+                    name: name.to_string(),
+                    results,
+                });
+            }
+            return;
+        }
+        Value::Array(items) => &items[..],
+        Value::Tuple(items) => &items[..],
+        _ => return,
+    };
+    for (index, item) in items.iter().enumerate() {
+        let name = if name == "c" {
+            format!("c{index}")
+        } else {
+            format!("{name}_{index}")
+        };
+        classical_registers(item, &name, results, registers);
+    }
+}
+
+/// Splits the argument of an intrinsic into the qubits it acts on and the text of its classical
+/// arguments, in order.
+fn split_arg(arg: &Value, qubits: &mut Vec<usize>, args: &mut Vec<String>) {
+    match arg {
+        Value::Qubit(q) => qubits.push(q.0),
+        Value::Tuple(items) => {
+            for item in items.iter() {
+                split_arg(item, qubits, args);
+            }
+        }
+        Value::Array(items) if items.iter().any(has_qubits) => {
+            for item in items.iter() {
+                split_arg(item, qubits, args);
+            }
+        }
+        _ => args.push(arg.to_string()),
+    }
+}
+
+fn has_qubits(value: &Value) -> bool {
+    match value {
+        Value::Qubit(_) => true,
+        Value::Array(items) => items.iter().any(has_qubits),
+        Value::Tuple(items) => items.iter().any(has_qubits),
+        _ => false,
+    }
+}
+
+/// The operation of a call, at the depth in the call stacks of the operations that it applied.
+fn call(applied: &[Recorded], depth: usize, name: &impl Fn(&Frame) -> String) -> Operation {
+    if let [only] = applied {
+        return only.op.clone();
+    }
+    let frame = &applied[0].stack[depth];
+    let mut gate = name(frame);
+    for _ in 0..frame.functor.controlled {
+        gate = format!("Controlled {gate}");
+    }
+    let mut targets = Vec::new();
+    for recorded in applied {
+        for register in recorded.op.controls.iter().chain(&recorded.op.targets) {
+            let target = Register::quantum(register.q_id);
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    Operation {
+        gate,
+        is_adjoint: frame.functor.adjoint,
+        targets,
+        children: calls(applied, depth + 1, name),
+        source: Some(source_span(frame)),
+        ..Operation::default()
+    }
+}
+
+/// The span where the evaluator was in the caller when it made the call.
+fn source_span(frame: &Frame) -> SourceSpan {
+    SourceSpan {
+        package: map_fir_package_to_hir(frame.caller),
+        span: frame.span,
+    }
+}
+
+/// The operations applied by the calls at the depth, with the consecutive operations of each call
+/// grouped into one. Intrinsics are their gates.
+fn calls(applied: &[Recorded], depth: usize, name: &impl Fn(&Frame) -> String) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    let mut rest = applied;
+    while let Some(first) = rest.first() {
+        if depth + 1 >= first.stack.len() {
+            operations.push(first.op.clone());
+            rest = &rest[1..];
+            continue;
+        }
+        let frames = &first.stack[..=depth];
+        let len = rest
+            .iter()
+            .take_while(|recorded| {
+                recorded.stack.len() > depth + 1 && recorded.stack[..=depth] == *frames
+            })
+            .count();
+        operations.push(call(&rest[..len], depth, name));
+        rest = &rest[len..];
+    }
+    operations
+}
+
+/// The position in the call stack of the call that the operation is boxed in, if it is in one.
+/// The outermost call is the entry, which is never boxed, and the innermost one is the intrinsic,
+/// which is drawn as its gate.
+fn box_depth(
+    stack: &[Frame],
+    config: &CircuitConfig,
+    name: &impl Fn(&Frame) -> String,
+) -> Option<usize> {
+    let calls = 1..stack.len().saturating_sub(1);
+    let named = calls.clone().find(|&depth| {
+        config
+            .preserve_named_operations
+            .contains(&name(&stack[depth]))
+    });
+    let deep = config
+        .max_decomposition_depth
+        .map(|max| max + 1)
+        .filter(|depth| calls.contains(depth));
+    match (named, deep) {
+        (Some(named), Some(deep)) => Some(named.min(deep)),
+        (named, deep) => named.or(deep),
+    }
+}
+
+impl<'a, B> Backend for CircuitBuilder<'a, B>
+where
+    B: Backend,
+    B::ResultType: Into<val::Result>,
+{
+    type ResultType = usize;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+        self.gate("CCNOT", &[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+        self.gate("CNOT", &[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+        self.gate("CY", &[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+        self.gate("CZ", &[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+        self.gate("H", &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        let outcome = self.backend.m(q).into();
+        self.measurement(q, outcome)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let outcome = self.backend.mresetz(q).into();
+        let result = self.measurement(q, outcome);
+        self.gate("Reset", &[q]);
+        result
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+        self.gate("Reset", &[q]);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+        self.rotation("Rx", theta, &[q]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+        self.rotation("Rxx", theta, &[q0, q1]);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+        self.rotation("Ry", theta, &[q]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+        self.rotation("Ryy", theta, &[q0, q1]);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+        self.rotation("Rz", theta, &[q]);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+        self.rotation("Rzz", theta, &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+        self.adjoint("S", q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+        self.gate("S", &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+        self.gate("SWAP", &[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+        self.adjoint("T", q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+        self.gate("T", &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+        self.gate("X", &[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+        self.gate("Y", &[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+        self.gate("Z", &[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let q = self.backend.qubit_allocate();
+        self.qubit(q);
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+        if let Some(qubit) = self.qubits.iter_mut().find(|qubit| qubit.id == q) {
+            qubit.label.get_or_insert_with(|| label.to_string());
+        }
+    }
+
+    fn labels_qubits(&self) -> bool {
+        true
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        match self.results.get(id)?.outcome {
+            val::Result::Val(outcome) => Some(outcome),
+            val::Result::Id(id) => self.backend.result_outcome(id),
+        }
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match self.backend.custom_intrinsic(name, arg.clone()) {
+            None if self.opaque_gate(name, &arg) => Some(Ok(Value::unit())),
+            result => result,
+        }
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+        self.record(Operation {
+            gate: BARRIER.to_string(),
+            display_args: (!label.is_empty()).then(|| label.to_string()),
+            targets: qubits.iter().copied().map(Register::quantum).collect(),
+            ..Operation::default()
+        });
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.stack = frames.to_vec();
+        self.backend.set_call_stack(frames);
+    }
+}
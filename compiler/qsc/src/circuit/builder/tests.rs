@@ -0,0 +1,682 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Circuit, CircuitConfig, Operation};
+use crate::circuit::{ClassicalRegister, Register, BARRIER};
+use crate::interpret::{Error, GenericReceiver, Interpreter, OperationArg, Value};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_eval::backend::SparseSim;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Measurement;
+        operation Exchange(a : Qubit, b : Qubit) : Unit is Adj {
+            CNOT(a, b);
+            CNOT(b, a);
+            CNOT(a, b);
+        }
+        @EntryPoint()
+        operation Main() : Result {
+            use (a, b) = (Qubit(), Qubit());
+            X(a);
+            Adjoint Exchange(a, b);
+            MResetZ(b)
+        }
+    }
+"};
+
+fn generate(config: &CircuitConfig) -> Circuit {
+    generate_from(SOURCE, config)
+}
+
+fn interpreter(source: &str) -> Interpreter {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created")
+}
+
+fn generate_from(source: &str, config: &CircuitConfig) -> Circuit {
+    let mut interpreter = interpreter(source);
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    interpreter
+        .generate_circuit(config, &mut receiver)
+        .expect("circuit should be generated")
+}
+
+fn check(config: &CircuitConfig, expect: &Expect) {
+    expect.assert_eq(&format_operations(&generate(config).operations));
+}
+
+fn format_operations(operations: &[Operation]) -> String {
+    operations
+        .iter()
+        .map(|op| {
+            let registers = op
+                .controls
+                .iter()
+                .chain(&op.targets)
+                .map(|register| format!("{register:?}"))
+                .collect::<Vec<_>>();
+            let adjoint = if op.is_adjoint { " Adj" } else { "" };
+            format!("{}{adjoint} {}", op.gate, registers.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn calls_are_decomposed_to_intrinsics_by_default() {
+    check(
+        &CircuitConfig::default(),
+        &expect![[r#"
+            X Register { q_id: 0, c_id: None }
+            CNOT Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }
+            CNOT Register { q_id: 1, c_id: None } Register { q_id: 0, c_id: None }
+            CNOT Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }
+            Measure Register { q_id: 1, c_id: None } Register { q_id: 1, c_id: Some(0) }
+            Reset Register { q_id: 1, c_id: None }"#]],
+    );
+}
+
+#[test]
+fn named_operations_are_boxed() {
+    check(
+        &CircuitConfig {
+            max_decomposition_depth: None,
+            preserve_named_operations: vec!["Exchange".to_string()],
+            compact_qubits: false,
+            remove_unused_qubits: false,
+        },
+        &expect![[r#"
+            X Register { q_id: 0, c_id: None }
+            Exchange Adj Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }
+            Measure Register { q_id: 1, c_id: None } Register { q_id: 1, c_id: Some(0) }
+            Reset Register { q_id: 1, c_id: None }"#]],
+    );
+}
+
+#[test]
+fn calls_below_max_depth_are_boxed() {
+    check(
+        &CircuitConfig {
+            max_decomposition_depth: Some(0),
+            preserve_named_operations: Vec::new(),
+            compact_qubits: false,
+            remove_unused_qubits: false,
+        },
+        &expect![[r#"
+            X Register { q_id: 0, c_id: None }
+            Exchange Adj Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }
+            MResetZ Register { q_id: 1, c_id: None }"#]],
+    );
+}
+
+#[test]
+fn boxes_hold_the_operations_of_their_calls() {
+    let circuit = generate(&CircuitConfig {
+        max_decomposition_depth: Some(0),
+        preserve_named_operations: Vec::new(),
+        compact_qubits: false,
+        remove_unused_qubits: false,
+    });
+    expect![[r#"
+        CNOT Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }
+        CNOT Register { q_id: 1, c_id: None } Register { q_id: 0, c_id: None }
+        CNOT Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }"#]]
+    .assert_eq(&format_operations(&circuit.operations[1].children));
+    assert!(circuit.operations[0].children.is_empty());
+    assert_eq!(circuit.qubits[1].num_children, 1);
+}
+
+#[test]
+fn operations_point_at_the_calls_in_the_program() {
+    let circuit = generate(&CircuitConfig {
+        max_decomposition_depth: Some(0),
+        preserve_named_operations: Vec::new(),
+        compact_qubits: false,
+        remove_unused_qubits: false,
+    });
+    let within = |op: &Operation, call: &str| {
+        let source = op.source.expect("operation should have a source");
+        let start = SOURCE.find(call).expect("call should be in the source");
+        let end = start + call.len();
+        let (lo, hi) = (source.span.lo as usize, source.span.hi as usize);
+        assert!(start <= lo && hi <= end, "{lo}..{hi} is not within {call}");
+    };
+    within(&circuit.operations[0], "X(a)");
+    within(&circuit.operations[1], "Adjoint Exchange(a, b)");
+    within(&circuit.operations[1].children[1], "CNOT(b, a)");
+    assert_eq!(
+        circuit.operations[0].source.map(|source| source.package),
+        circuit.operations[1].source.map(|source| source.package)
+    );
+}
+
+const REUSE: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Main() : (Result, Result) {
+            use q = Qubit();
+            H(q);
+            let r = MResetZ(q);
+            use aux = Qubit();
+            X(aux);
+            (r, MResetZ(aux))
+        }
+    }
+"};
+
+#[test]
+fn qubits_allocated_after_a_reset_get_fresh_wires() {
+    let circuit = generate_from(REUSE, &CircuitConfig::default());
+    assert_eq!(circuit.qubits.len(), 2);
+    expect![[r#"
+        H Register { q_id: 0, c_id: None }
+        Measure Register { q_id: 0, c_id: None } Register { q_id: 0, c_id: Some(0) }
+        Reset Register { q_id: 0, c_id: None }
+        X Register { q_id: 1, c_id: None }
+        Measure Register { q_id: 1, c_id: None } Register { q_id: 1, c_id: Some(0) }
+        Reset Register { q_id: 1, c_id: None }"#]]
+    .assert_eq(&format_operations(&circuit.operations));
+}
+
+#[test]
+fn compacted_qubits_reuse_the_wires_of_reset_qubits() {
+    let circuit = generate_from(
+        REUSE,
+        &CircuitConfig {
+            compact_qubits: true,
+            ..CircuitConfig::default()
+        },
+    );
+    assert_eq!(circuit.qubits.len(), 1);
+    assert_eq!(circuit.qubits[0].num_children, 2);
+    expect![[r#"
+        H Register { q_id: 0, c_id: None }
+        Measure Register { q_id: 0, c_id: None } Register { q_id: 0, c_id: Some(0) }
+        Reset Register { q_id: 0, c_id: None }
+        X Register { q_id: 0, c_id: None }
+        Measure Register { q_id: 0, c_id: None } Register { q_id: 0, c_id: Some(1) }
+        Reset Register { q_id: 0, c_id: None }"#]]
+    .assert_eq(&format_operations(&circuit.operations));
+}
+
+#[test]
+fn gates_are_streamed_as_the_program_runs() {
+    let interpreter = interpreter(SOURCE);
+    let mut sim = SparseSim::new();
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    let gates = interpreter
+        .generate_circuit_iter(&mut sim, &mut receiver)
+        .expect("entry expression should be found")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("program should run");
+    assert_eq!(gates, generate(&CircuitConfig::default()).operations);
+}
+
+#[test]
+fn streamed_gates_stop_at_the_maximum() {
+    let interpreter = interpreter(SOURCE);
+    let mut sim = SparseSim::new();
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    let mut gates = interpreter
+        .generate_circuit_iter(&mut sim, &mut receiver)
+        .expect("entry expression should be found")
+        .with_max_operations(2);
+    let taken = gates
+        .by_ref()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("program should run");
+    expect![[r#"
+        X Register { q_id: 0, c_id: None }
+        CNOT Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }"#]]
+    .assert_eq(&format_operations(&taken));
+    assert!(gates.is_truncated());
+}
+
+#[test]
+fn partial_circuits_box_the_operations_before_the_maximum() {
+    let interpreter = interpreter(SOURCE);
+    let config = CircuitConfig {
+        max_decomposition_depth: Some(0),
+        ..CircuitConfig::default()
+    };
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    let partial = interpreter
+        .generate_partial_circuit(&config, 3, &mut receiver)
+        .expect("program should run");
+    assert!(partial.truncated);
+    expect![[r#"
+        X Register { q_id: 0, c_id: None }
+        Exchange Adj Register { q_id: 0, c_id: None } Register { q_id: 1, c_id: None }"#]]
+    .assert_eq(&format_operations(&partial.circuit.operations));
+    assert_eq!(partial.circuit.operations[1].children.len(), 2);
+
+    let full = interpreter
+        .generate_partial_circuit(&config, 100, &mut receiver)
+        .expect("program should run");
+    assert!(!full.truncated);
+    assert_eq!(full.circuit, generate(&config));
+}
+
+#[test]
+fn unused_qubits_can_be_left_out() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : Result {
+                use qs = Qubit[3];
+                H(qs[2]);
+                MResetZ(qs[2])
+            }
+        }
+    "};
+    assert_eq!(
+        generate_from(source, &CircuitConfig::default())
+            .qubits
+            .len(),
+        3
+    );
+    let circuit = generate_from(
+        source,
+        &CircuitConfig {
+            remove_unused_qubits: true,
+            ..CircuitConfig::default()
+        },
+    );
+    assert_eq!(circuit.qubits.len(), 1);
+    assert_eq!(circuit.qubits[0].label.as_deref(), Some("qs[2]"));
+    expect![[r#"
+        H Register { q_id: 0, c_id: None }
+        Measure Register { q_id: 0, c_id: None } Register { q_id: 0, c_id: Some(0) }
+        Reset Register { q_id: 0, c_id: None }"#]]
+    .assert_eq(&format_operations(&circuit.operations));
+}
+
+#[test]
+fn returned_results_are_grouped_into_registers() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : (Result, Result[]) {
+                use q = Qubit();
+                use qs = Qubit[2];
+                H(q);
+                X(qs[1]);
+                let r = MResetZ(q);
+                let rs = [MResetZ(qs[0]), MResetZ(qs[1])];
+                (r, rs)
+            }
+        }
+    "};
+    let circuit = generate_from(source, &CircuitConfig::default());
+    assert_eq!(
+        circuit.registers,
+        vec![
+            ClassicalRegister {
+                name: "c0".to_string(),
+                results: vec![Register::classical(0, 0)],
+            },
+            ClassicalRegister {
+                name: "c1".to_string(),
+                results: vec![Register::classical(1, 0), Register::classical(2, 0)],
+            },
+        ]
+    );
+    assert_eq!(
+        circuit.result_label(Register::classical(2, 0)).as_deref(),
+        Some("c1[1]")
+    );
+}
+
+#[test]
+fn qubits_are_labelled_with_the_names_they_are_allocated_to() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            operation Flip(target : Qubit) : Unit {
+                use aux = Qubit();
+                CNOT(target, aux);
+                let copy = aux;
+                X(copy);
+                Reset(aux);
+            }
+            @EntryPoint()
+            operation Main() : Result {
+                use (a, b) = (Qubit(), Qubit());
+                use ctls = Qubit[2];
+                Flip(a);
+                CCNOT(ctls[0], ctls[1], b);
+                ResetAll(ctls + [a]);
+                MResetZ(b)
+            }
+        }
+    "};
+    let circuit = generate_from(source, &CircuitConfig::default());
+    let labels = circuit
+        .qubits
+        .iter()
+        .map(|qubit| qubit.label.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            Some("a"),
+            Some("b"),
+            Some("ctls[0]"),
+            Some("ctls[1]"),
+            Some("aux")
+        ]
+    );
+}
+
+#[test]
+fn unknown_intrinsics_on_qubits_are_opaque_gates() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            operation SX(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation Rzx(theta : Double, qs : Qubit[]) : Unit {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Result {
+                use (a, b) = (Qubit(), Qubit());
+                SX(a);
+                Rzx(0.5, [a, b]);
+                MResetZ(b)
+            }
+        }
+    "};
+    let circuit = generate_from(source, &CircuitConfig::default());
+    let gates = circuit
+        .operations
+        .iter()
+        .map(|op| {
+            (
+                op.gate.as_str(),
+                op.display_args.as_deref(),
+                op.targets.len(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        gates,
+        [
+            ("SX", None, 1),
+            ("Rzx", Some("0.5"), 2),
+            ("Measure", None, 1),
+            ("Reset", None, 1),
+        ]
+    );
+    assert_eq!(
+        circuit.operations[1].targets,
+        [Register::quantum(0), Register::quantum(1)]
+    );
+}
+
+#[test]
+fn barriers_are_labelled_gates_on_their_qubits() {
+    let source = indoc! {r#"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            open Microsoft.Quantum.Diagnostics;
+            @EntryPoint()
+            operation Main() : Result {
+                use qs = Qubit[2];
+                H(qs[0]);
+                Barrier(qs, "Oracle");
+                CNOT(qs[0], qs[1]);
+                Barrier([qs[1]], "");
+                Reset(qs[0]);
+                MResetZ(qs[1])
+            }
+        }
+    "#};
+    let circuit = generate_from(source, &CircuitConfig::default());
+    let barriers = circuit
+        .operations
+        .iter()
+        .filter(|op| op.gate == BARRIER)
+        .map(|op| (op.display_args.as_deref(), op.targets.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        barriers,
+        [
+            (
+                Some("Oracle"),
+                vec![Register::quantum(0), Register::quantum(1)]
+            ),
+            (None, vec![Register::quantum(1)]),
+        ]
+    );
+    assert_eq!(circuit.operations[1].gate, BARRIER);
+}
+
+#[test]
+fn phase_estimation_iterations_are_drawn_as_separate_boxes() {
+    let source = indoc! {r#"
+        namespace Test {
+            open Microsoft.Quantum.Convert;
+            open Microsoft.Quantum.Measurement;
+            open Microsoft.Quantum.Unstable.PhaseEstimation;
+            operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {
+                R1(0.25 * IntAsDouble(power), qs[0]);
+                R1(0.5 * IntAsDouble(power), qs[1]);
+            }
+            @EntryPoint()
+            operation Main() : Result[] {
+                use target = Qubit[2];
+                use phase = Qubit[2];
+                ApplyQPE(RotatePower, target, phase);
+                ResetAll(target);
+                MResetEachZ(phase)
+            }
+        }
+    "#};
+    let circuit = generate_from(
+        source,
+        &CircuitConfig {
+            max_decomposition_depth: None,
+            preserve_named_operations: vec!["ApplyQPE".to_string()],
+            compact_qubits: false,
+            remove_unused_qubits: false,
+        },
+    );
+    let qpe = circuit
+        .operations
+        .iter()
+        .find(|op| op.gate == "ApplyQPE")
+        .expect("phase estimation should be boxed");
+    let children = qpe
+        .children
+        .iter()
+        .map(|op| (op.gate.as_str(), op.display_args.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        children[1..6],
+        [
+            (BARRIER, Some("U^1")),
+            ("Controlled RotatePower", None),
+            (BARRIER, Some("U^2")),
+            ("Controlled RotatePower", None),
+            (BARRIER, Some("QFT†")),
+        ]
+    );
+}
+
+fn library_interpreter(source: &str) -> Interpreter {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created")
+}
+
+const LIBRARY: &str = indoc! {"
+    namespace Lib {
+        operation Entangle(qs : Qubit[], theta : Double, target : Qubit) : Unit {
+            for q in qs {
+                Rx(theta, q);
+            }
+            CNOT(qs[0], target);
+        }
+    }
+"};
+
+#[test]
+fn operations_are_drawn_without_an_entry_point() {
+    let mut interpreter = library_interpreter(LIBRARY);
+    let mut stdout = std::io::sink();
+    let circuit = interpreter
+        .generate_operation_circuit(
+            "Lib.Entangle",
+            vec![
+                OperationArg::Qubits(2),
+                OperationArg::Value(Value::Double(0.5)),
+                OperationArg::Qubit,
+            ],
+            &CircuitConfig::default(),
+            &mut GenericReceiver::new(&mut stdout),
+        )
+        .expect("circuit should be generated");
+    assert_eq!(circuit.qubits.len(), 3);
+    expect![[r#"
+        Rx Register { q_id: 0, c_id: None }
+        Rx Register { q_id: 1, c_id: None }
+        CNOT Register { q_id: 0, c_id: None } Register { q_id: 2, c_id: None }"#]]
+    .assert_eq(&format_operations(&circuit.operations));
+    assert_eq!(circuit.operations[0].display_args.as_deref(), Some("0.5"));
+}
+
+#[test]
+fn operation_arguments_are_checked_against_the_parameters() {
+    let mut interpreter = library_interpreter(LIBRARY);
+    let mut stdout = std::io::sink();
+    let mut generate = |args| {
+        interpreter
+            .generate_operation_circuit(
+                "Lib.Entangle",
+                args,
+                &CircuitConfig::default(),
+                &mut GenericReceiver::new(&mut stdout),
+            )
+            .expect_err("arguments should be rejected")
+    };
+
+    let errors = generate(vec![OperationArg::Qubits(2)]);
+    assert!(
+        matches!(errors.as_slice(), [Error::OperationArgCount(3, 1)]),
+        "{errors:?}"
+    );
+    let errors = generate(vec![
+        OperationArg::Qubits(2),
+        OperationArg::Value(Value::Int(1)),
+        OperationArg::Qubit,
+    ]);
+    assert!(
+        matches!(errors.as_slice(), [Error::OperationArgType(1, ty)] if ty == "Double"),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn user_defined_type_arguments_are_checked() {
+    let mut interpreter = library_interpreter(indoc! {"
+        namespace Lib {
+            newtype Angle = (Turns : Double, Label : String);
+            operation RotateBy(angle : Angle, q : Qubit) : Unit {
+                Rx(angle::Turns, q);
+            }
+            operation RotateByPair(angles : (Double, Double)) : Unit {
+                use q = Qubit();
+                let (first, second) = angles;
+                Rx(first + second, q);
+                Reset(q);
+            }
+        }
+    "});
+    let mut stdout = std::io::sink();
+    let mut generate = |operation, args| {
+        interpreter.generate_operation_circuit(
+            operation,
+            args,
+            &CircuitConfig::default(),
+            &mut GenericReceiver::new(&mut stdout),
+        )
+    };
+
+    let angle = Value::Tuple(vec![Value::Double(0.5), Value::String("half".into())].into());
+    generate(
+        "Lib.RotateBy",
+        vec![OperationArg::Value(angle), OperationArg::Qubit],
+    )
+    .expect("value of the definition should be accepted");
+    let errors = generate(
+        "Lib.RotateBy",
+        vec![OperationArg::Value(Value::Double(0.5)), OperationArg::Qubit],
+    )
+    .expect_err("value of another type should be rejected");
+    assert!(
+        matches!(errors.as_slice(), [Error::OperationArgType(0, _)]),
+        "{errors:?}"
+    );
+
+    let pair = Value::Tuple(vec![Value::Double(0.5), Value::Double(0.25)].into());
+    generate("Lib.RotateByPair", vec![OperationArg::Value(pair)])
+        .expect("tuple should be accepted as a single argument");
+}
+
+#[test]
+fn compared_results_take_the_branch_of_their_outcome() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                X(q);
+                if M(q) == One {
+                    X(q);
+                } else {
+                    H(q);
+                }
+                MResetZ(q)
+            }
+        }
+    "};
+    let circuit = generate_from(source, &CircuitConfig::default());
+    let gates = circuit
+        .operations
+        .iter()
+        .map(|op| op.gate.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(gates, ["X", "Measure", "X", "Measure", "Reset"]);
+    assert_eq!(
+        circuit.registers,
+        vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(0, 1)],
+        }]
+    );
+}
@@ -0,0 +1,191 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compacts the wires of a circuit. A program that measures and resets qubits and then allocates
+//! new ones while the old ones are still in scope gets a fresh qubit id for each of them, so the
+//! circuit grows a wire for every reuse. Once a qubit has been reset for the last time, its wire is
+//! free to carry a qubit that is first used afterwards. Qubits that are allocated but never used,
+//! such as the spare qubits of an array, can have their wires removed. Qubits can also be moved to
+//! other ids explicitly, such as by layout tools that place them on the qubits of a device.
+
+#[cfg(test)]
+mod tests;
+
+use super::{expand, Circuit, ClassicalExpr, Error, Operation, Qubit, Register};
+use rustc_hash::FxHashMap;
+
+/// The operations that use a qubit, as positions in the circuit.
+struct Lifetime {
+    first: usize,
+    last: usize,
+    /// The last operation applied to the qubit is an unconditioned reset.
+    reset: bool,
+}
+
+/// A wire of the compacted circuit.
+struct Wire {
+    qubit: Qubit,
+    /// The position of the last use of the wire, if the qubit on it ends in a reset.
+    free_after: Option<usize>,
+}
+
+impl Circuit {
+    /// Moves each qubit onto the wire of a qubit that was reset for the last time before the
+    /// first operation on it, if there is one, and numbers its results after those already on that
+    /// wire. Operations with children count as one step, so a qubit can't join a wire that is used
+    /// by the same operation.
+    pub fn compact_qubits(&mut self) {
+        let lifetimes = self.lifetimes();
+        let mut qubits = self.qubits.iter().enumerate().collect::<Vec<_>>();
+        qubits.sort_by_key(|(_, qubit)| lifetimes.get(&qubit.id).map(|lifetime| lifetime.first));
+
+        // Wires are kept in the order of the qubits they started with.
+        let mut wires: Vec<(usize, Wire)> = Vec::new();
+        let mut moves = FxHashMap::default();
+        for (row, qubit) in qubits {
+            let lifetime = lifetimes.get(&qubit.id);
+            let free_after = lifetime.and_then(|l| l.reset.then_some(l.last));
+            let wire = lifetime.and_then(|lifetime| {
+                wires
+                    .iter_mut()
+                    .filter(|(_, wire)| wire.free_after.is_some_and(|last| last < lifetime.first))
+                    .min_by_key(|(row, _)| *row)
+            });
+            if let Some((_, wire)) = wire {
+                moves.insert(qubit.id, (wire.qubit.id, wire.qubit.num_children));
+                wire.qubit.num_children += qubit.num_children;
+                wire.free_after = free_after;
+            } else {
+                wires.push((
+                    row,
+                    Wire {
+                        qubit: qubit.clone(),
+                        free_after,
+                    },
+                ));
+            }
+        }
+
+        if moves.is_empty() {
+            return;
+        }
+        wires.sort_by_key(|(row, _)| *row);
+        self.qubits = wires.into_iter().map(|(_, wire)| wire.qubit).collect();
+        self.apply_moves(&moves);
+    }
+
+    /// Removes the wires of qubits that no operation uses, and numbers the remaining qubits from
+    /// zero in the order they are listed, along with their results and the operations on them.
+    pub fn remove_unused_qubits(&mut self) {
+        let lifetimes = self.lifetimes();
+        self.qubits
+            .retain(|qubit| lifetimes.contains_key(&qubit.id));
+        let mut moves = FxHashMap::default();
+        for (id, qubit) in self.qubits.iter_mut().enumerate() {
+            if qubit.id != id {
+                moves.insert(qubit.id, (id, 0));
+                qubit.id = id;
+            }
+        }
+        self.apply_moves(&moves);
+    }
+
+    /// Moves each qubit in the mapping to the id it is mapped to, along with its results and the
+    /// operations on it. Qubits that aren't in the mapping keep their ids, and the qubits are
+    /// listed in the order of their new ids, so that viewers draw them in that order.
+    /// # Errors
+    /// Returns an error if the mapping moves a qubit that isn't in the circuit, or moves two
+    /// qubits to the same id. The circuit is left as it was.
+    pub fn remap_qubits(&mut self, mapping: &FxHashMap<usize, usize>) -> Result<(), Error> {
+        if let Some(&q_id) = mapping
+            .keys()
+            .filter(|&&q_id| self.qubits.iter().all(|qubit| qubit.id != q_id))
+            .min()
+        {
+            return Err(Error::RemapUnknownQubit(q_id));
+        }
+        let mut ids = self
+            .qubits
+            .iter()
+            .map(|qubit| mapping.get(&qubit.id).copied().unwrap_or(qubit.id))
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        if let Some(ids) = ids.windows(2).find(|ids| ids[0] == ids[1]) {
+            return Err(Error::RemapCollision(ids[0]));
+        }
+
+        let moves = mapping.iter().map(|(&from, &to)| (from, (to, 0))).collect();
+        for qubit in &mut self.qubits {
+            qubit.id = mapping.get(&qubit.id).copied().unwrap_or(qubit.id);
+        }
+        self.qubits.sort_by_key(|qubit| qubit.id);
+        self.apply_moves(&moves);
+        Ok(())
+    }
+
+    /// Moves the registers of the operations and of the classical registers as given by `moves`,
+    /// as in [`remap`].
+    fn apply_moves(&mut self, moves: &FxHashMap<usize, (usize, usize)>) {
+        remap(&mut self.operations, moves);
+        for register in &mut self.registers {
+            for result in &mut register.results {
+                remap_register(result, moves);
+            }
+        }
+    }
+
+    fn lifetimes(&self) -> FxHashMap<usize, Lifetime> {
+        let mut lifetimes = FxHashMap::<usize, Lifetime>::default();
+        for (index, op) in self.operations.iter().enumerate() {
+            for gate in expand(std::slice::from_ref(op)) {
+                let reset = gate.gate == "Reset" && gate.condition.is_none();
+                for register in gate.controls.iter().chain(&gate.targets) {
+                    lifetimes
+                        .entry(register.q_id)
+                        .and_modify(|lifetime| {
+                            lifetime.last = index;
+                            lifetime.reset = reset;
+                        })
+                        .or_insert(Lifetime {
+                            first: index,
+                            last: index,
+                            reset,
+                        });
+                }
+            }
+        }
+        lifetimes
+    }
+}
+
+/// Moves the registers of the operations as given by `moves`, which maps a qubit id to the wire
+/// it moves to and the number of results already on that wire.
+fn remap(operations: &mut [Operation], moves: &FxHashMap<usize, (usize, usize)>) {
+    for op in operations {
+        for register in op.controls.iter_mut().chain(&mut op.targets) {
+            remap_register(register, moves);
+        }
+        if let Some(condition) = &mut op.condition {
+            remap_condition(condition, moves);
+        }
+        remap(&mut op.children, moves);
+    }
+}
+
+fn remap_condition(expr: &mut ClassicalExpr, moves: &FxHashMap<usize, (usize, usize)>) {
+    match expr {
+        ClassicalExpr::Result(register, _) => remap_register(register, moves),
+        ClassicalExpr::Not(inner) => remap_condition(inner, moves),
+        ClassicalExpr::And(lhs, rhs) | ClassicalExpr::Or(lhs, rhs) => {
+            remap_condition(lhs, moves);
+            remap_condition(rhs, moves);
+        }
+    }
+}
+
+fn remap_register(register: &mut Register, moves: &FxHashMap<usize, (usize, usize)>) {
+    if let Some(&(q_id, offset)) = moves.get(&register.q_id) {
+        register.q_id = q_id;
+        register.c_id = register.c_id.map(|c_id| c_id + offset);
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::circuit::{
+    fixtures::{gate, measure, qubit},
+    Circuit, ClassicalExpr, ClassicalRegister, Error, Operation, Register,
+};
+use rustc_hash::FxHashMap;
+
+#[test]
+fn qubits_reuse_the_wires_of_reset_qubits() {
+    let mut circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            measure(0, 0),
+            gate("Reset", &[0]),
+            gate("X", &[1]),
+            measure(1, 0),
+            gate("Reset", &[1]),
+            gate("H", &[2]),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(1, 0), true)),
+                ..gate("Z", &[2])
+            },
+        ],
+        qubits: vec![qubit(0, 1), qubit(1, 1), qubit(2, 0)],
+        registers: vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(0, 0), Register::classical(1, 0)],
+        }],
+    };
+    circuit.compact_qubits();
+    assert_eq!(circuit.qubits, vec![qubit(0, 2)]);
+    assert_eq!(
+        circuit.registers[0].results,
+        vec![Register::classical(0, 0), Register::classical(0, 1)]
+    );
+    assert_eq!(circuit.operations[3], gate("X", &[0]));
+    assert_eq!(circuit.operations[4], measure(0, 1));
+    assert_eq!(circuit.operations[6], gate("H", &[0]));
+    assert_eq!(
+        circuit.operations[7].condition,
+        Some(ClassicalExpr::Result(Register::classical(0, 1), true))
+    );
+}
+
+#[test]
+fn qubits_keep_wires_that_are_not_reset() {
+    let operations = vec![
+        gate("H", &[0]),
+        measure(0, 0),
+        gate("X", &[1]),
+        gate("Reset", &[1]),
+        Operation {
+            condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+            ..gate("Reset", &[0])
+        },
+        gate("H", &[2]),
+    ];
+    let mut circuit = Circuit {
+        operations: operations.clone(),
+        qubits: vec![qubit(0, 1), qubit(1, 0), qubit(2, 0)],
+        ..Circuit::default()
+    };
+    circuit.compact_qubits();
+    assert_eq!(circuit.qubits, vec![qubit(0, 1), qubit(1, 0)]);
+    assert_eq!(circuit.operations[5], gate("H", &[1]));
+}
+
+#[test]
+fn qubits_do_not_join_wires_used_by_the_same_operation() {
+    let mut circuit = Circuit {
+        operations: vec![Operation {
+            children: vec![gate("Reset", &[0]), gate("H", &[1])],
+            ..gate("Prepare", &[0, 1])
+        }],
+        qubits: vec![qubit(0, 0), qubit(1, 0)],
+        ..Circuit::default()
+    };
+    let original = circuit.clone();
+    circuit.compact_qubits();
+    assert_eq!(circuit, original);
+}
+
+#[test]
+fn unused_qubits_are_removed_and_the_rest_renumbered() {
+    let mut circuit = Circuit {
+        operations: vec![
+            gate("H", &[1]),
+            measure(1, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(1, 0), true)),
+                ..gate("X", &[3])
+            },
+        ],
+        qubits: vec![qubit(0, 0), qubit(1, 1), qubit(2, 0), qubit(3, 0)],
+        registers: vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(1, 0)],
+        }],
+    };
+    circuit.remove_unused_qubits();
+    assert_eq!(circuit.qubits, vec![qubit(0, 1), qubit(1, 0)]);
+    assert_eq!(circuit.operations[0], gate("H", &[0]));
+    assert_eq!(circuit.operations[1], measure(0, 0));
+    assert_eq!(
+        circuit.operations[2],
+        Operation {
+            condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+            ..gate("X", &[1])
+        }
+    );
+    assert_eq!(
+        circuit.registers[0].results,
+        vec![Register::classical(0, 0)]
+    );
+}
+
+#[test]
+fn circuits_without_unused_qubits_are_unchanged() {
+    let mut circuit = Circuit {
+        operations: vec![gate("H", &[0]), gate("CNOT", &[0, 1])],
+        qubits: vec![qubit(0, 0), qubit(1, 0)],
+        ..Circuit::default()
+    };
+    let original = circuit.clone();
+    circuit.remove_unused_qubits();
+    assert_eq!(circuit, original);
+}
+
+fn mapping(pairs: &[(usize, usize)]) -> FxHashMap<usize, usize> {
+    pairs.iter().copied().collect()
+}
+
+#[test]
+fn remapped_qubits_take_their_operations_and_results() {
+    let mut circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                children: vec![gate("X", &[1])],
+                ..gate("Correct", &[1])
+            },
+            gate("CNOT", &[1, 2]),
+        ],
+        qubits: vec![qubit(0, 1), qubit(1, 0), qubit(2, 0)],
+        registers: vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(0, 0)],
+        }],
+    };
+    circuit
+        .remap_qubits(&mapping(&[(0, 5), (1, 2), (2, 1)]))
+        .expect("qubits should be remapped");
+    assert_eq!(circuit.qubits, vec![qubit(1, 0), qubit(2, 0), qubit(5, 1)]);
+    assert_eq!(circuit.operations[0], gate("H", &[5]));
+    assert_eq!(circuit.operations[1], measure(5, 0));
+    assert_eq!(
+        circuit.operations[2].condition,
+        Some(ClassicalExpr::Result(Register::classical(5, 0), true))
+    );
+    assert_eq!(circuit.operations[2].children, vec![gate("X", &[2])]);
+    assert_eq!(circuit.operations[3], gate("CNOT", &[2, 1]));
+    assert_eq!(
+        circuit.registers[0].results,
+        vec![Register::classical(5, 0)]
+    );
+}
+
+#[test]
+fn remapping_checks_the_mapping_before_moving_qubits() {
+    let original = Circuit {
+        operations: vec![gate("CNOT", &[0, 1])],
+        qubits: vec![qubit(0, 0), qubit(1, 0)],
+        ..Circuit::default()
+    };
+    let mut circuit = original.clone();
+    assert_eq!(
+        circuit.remap_qubits(&mapping(&[(0, 1)])),
+        Err(Error::RemapCollision(1))
+    );
+    assert_eq!(
+        circuit.remap_qubits(&mapping(&[(0, 3), (4, 2)])),
+        Err(Error::RemapUnknownQubit(4))
+    );
+    assert_eq!(circuit, original);
+}
@@ -0,0 +1,203 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Compares two circuits, such as the circuits that two versions of the compiler generate for the
+//! same program, so that tests can detect and report when a change alters a circuit. Operations
+//! are matched by a longest common subsequence, ignoring the source spans they come from, and
+//! unmatched operations of the same gate at the same place in both circuits are reported as
+//! changed rather than as removed and added.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Circuit, ClassicalExpr, Operation, Register};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// The differences between two circuits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitDiff {
+    /// The number of qubits of the old and the new circuit.
+    pub qubits: (usize, usize),
+    /// The changes to the operations, in the order they appear in the circuits.
+    pub changes: Vec<Change>,
+}
+
+/// A change to the top-level operations of a circuit. Indices are positions in the operations of
+/// the old or the new circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Removed(usize, Operation),
+    Added(usize, Operation),
+    /// An operation with the same gate but different arguments, qubits, condition or children.
+    Changed(usize, usize, Operation, Operation),
+}
+
+impl CircuitDiff {
+    /// Whether the circuits apply the same operations to the same number of qubits.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.qubits.0 == self.qubits.1 && self.changes.is_empty()
+    }
+}
+
+impl Display for CircuitDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "circuits are the same");
+        }
+        let (old, new) = self.qubits;
+        if old != new {
+            writeln!(f, "qubits: {old} -> {new}")?;
+        }
+        for change in &self.changes {
+            match change {
+                Change::Removed(index, op) => writeln!(f, "- {index}: {}", describe(op))?,
+                Change::Added(index, op) => writeln!(f, "+ {index}: {}", describe(op))?,
+                Change::Changed(old_index, new_index, old, new) => writeln!(
+                    f,
+                    "~ {old_index} -> {new_index}: {} => {}",
+                    describe(old),
+                    describe(new)
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the top-level operations and the qubit counts of two circuits.
+#[must_use]
+pub fn diff(old: &Circuit, new: &Circuit) -> CircuitDiff {
+    let (old_ops, new_ops) = (&old.operations, &new.operations);
+
+    // The length of the longest common subsequence of the operations from each pair of positions.
+    let mut lengths = vec![vec![0; new_ops.len() + 1]; old_ops.len() + 1];
+    for i in (0..old_ops.len()).rev() {
+        for j in (0..new_ops.len()).rev() {
+            lengths[i][j] = if same(&old_ops[i], &new_ops[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old_ops.len() || j < new_ops.len() {
+        if i < old_ops.len() && j < new_ops.len() && same(&old_ops[i], &new_ops[j]) {
+            pair(&mut changes, &mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j == new_ops.len()
+            || (i < old_ops.len() && lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            removed.push((i, &old_ops[i]));
+            i += 1;
+        } else {
+            added.push((j, &new_ops[j]));
+            j += 1;
+        }
+    }
+    pair(&mut changes, &mut removed, &mut added);
+
+    CircuitDiff {
+        qubits: (old.qubits.len(), new.qubits.len()),
+        changes,
+    }
+}
+
+/// Reports a run of removed operations and the run of added operations that replaced them,
+/// pairing the operations at the same place in both runs that have the same gate as changes.
+fn pair(
+    changes: &mut Vec<Change>,
+    removed: &mut Vec<(usize, &Operation)>,
+    added: &mut Vec<(usize, &Operation)>,
+) {
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+    loop {
+        match (removed.next(), added.next()) {
+            (None, None) => break,
+            (Some((i, old)), Some((j, new))) if old.gate == new.gate => {
+                changes.push(Change::Changed(i, j, old.clone(), new.clone()));
+            }
+            (old, new) => {
+                if let Some((i, old)) = old {
+                    changes.push(Change::Removed(i, old.clone()));
+                }
+                if let Some((j, new)) = new {
+                    changes.push(Change::Added(j, new.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Whether the operations are the same, apart from the source spans they come from.
+fn same(lhs: &Operation, rhs: &Operation) -> bool {
+    lhs.gate == rhs.gate
+        && lhs.display_args == rhs.display_args
+        && lhs.is_controlled == rhs.is_controlled
+        && lhs.is_adjoint == rhs.is_adjoint
+        && lhs.is_measurement == rhs.is_measurement
+        && lhs.controls == rhs.controls
+        && lhs.targets == rhs.targets
+        && lhs.condition == rhs.condition
+        && lhs.children.len() == rhs.children.len()
+        && lhs
+            .children
+            .iter()
+            .zip(&rhs.children)
+            .all(|(l, r)| same(l, r))
+}
+
+/// A one-line description of an operation, such as `Rx(0.5) q0` or `X q1 if q0.c0 == One`.
+fn describe(op: &Operation) -> String {
+    let mut text = op.gate.clone();
+    if op.is_adjoint {
+        text.push('†');
+    }
+    if let Some(args) = &op.display_args {
+        let _ = write!(text, "({args})");
+    }
+    let registers = op
+        .controls
+        .iter()
+        .chain(&op.targets)
+        .map(register)
+        .collect::<Vec<_>>();
+    if !registers.is_empty() {
+        let _ = write!(text, " {}", registers.join(", "));
+    }
+    if let Some(condition) = &op.condition {
+        let _ = write!(text, " if {}", expr(condition));
+    }
+    if !op.children.is_empty() {
+        let _ = write!(text, " with {} children", op.children.len());
+    }
+    text
+}
+
+fn register(register: &Register) -> String {
+    match register.c_id {
+        Some(c_id) => format!("q{}.c{c_id}", register.q_id),
+        None => format!("q{}", register.q_id),
+    }
+}
+
+fn expr(condition: &ClassicalExpr) -> String {
+    match condition {
+        ClassicalExpr::Result(result, one) => {
+            format!(
+                "{} == {}",
+                register(result),
+                if *one { "One" } else { "Zero" }
+            )
+        }
+        ClassicalExpr::Not(inner) => format!("not ({})", expr(inner)),
+        ClassicalExpr::And(lhs, rhs) => format!("({} and {})", expr(lhs), expr(rhs)),
+        ClassicalExpr::Or(lhs, rhs) => format!("({} or {})", expr(lhs), expr(rhs)),
+    }
+}
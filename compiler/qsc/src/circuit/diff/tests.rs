@@ -0,0 +1,107 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::circuit::{
+    diff,
+    fixtures::{gate, qubits},
+    Change, Circuit, ClassicalExpr, Operation, Register,
+};
+use expect_test::expect;
+
+fn rx(theta: &str, q_id: usize) -> Operation {
+    Operation {
+        display_args: Some(theta.to_string()),
+        ..gate("Rx", &[q_id])
+    }
+}
+
+#[test]
+fn same_circuits_have_no_differences() {
+    let circuit = Circuit {
+        operations: vec![gate("H", &[0]), gate("CNOT", &[0, 1])],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let diff = diff(&circuit, &circuit.clone());
+    assert!(diff.is_empty());
+    expect![[r#"
+        circuits are the same
+    "#]]
+    .assert_eq(&diff.to_string());
+}
+
+#[test]
+fn source_spans_are_ignored() {
+    let old = Circuit {
+        operations: vec![gate("H", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    let mut new = old.clone();
+    new.operations[0].source = Some(crate::circuit::SourceSpan {
+        package: 2.into(),
+        span: qsc_data_structures::span::Span { lo: 4, hi: 8 },
+    });
+    assert!(diff(&old, &new).is_empty());
+}
+
+#[test]
+fn added_removed_and_changed_operations_are_reported() {
+    let old = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            rx("0.5", 0),
+            gate("CNOT", &[0, 1]),
+            gate("X", &[1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let new = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            rx("0.25", 0),
+            gate("CZ", &[0, 1]),
+            gate("X", &[1]),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                ..gate("Z", &[2])
+            },
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    let diff = diff(&old, &new);
+    assert_eq!(diff.qubits, (2, 3));
+    assert_eq!(
+        diff.changes[0],
+        Change::Changed(1, 1, rx("0.5", 0), rx("0.25", 0))
+    );
+    expect![[r#"
+        qubits: 2 -> 3
+        ~ 1 -> 1: Rx(0.5) q0 => Rx(0.25) q0
+        - 2: CNOT q0, q1
+        + 2: CZ q0, q1
+        + 4: Z q2 if q0.c0 == One
+    "#]]
+    .assert_eq(&diff.to_string());
+}
+
+#[test]
+fn moved_operations_are_matched_in_order() {
+    let old = Circuit {
+        operations: vec![gate("X", &[0]), gate("Y", &[0]), gate("Z", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    let new = Circuit {
+        operations: vec![gate("Y", &[0]), gate("Z", &[0]), gate("X", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        - 0: X q0
+        + 2: X q0
+    "#]]
+    .assert_eq(&diff(&old, &new).to_string());
+}
@@ -0,0 +1,36 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Builders for the qubits and operations of circuits in tests.
+
+use super::{Operation, Qubit, Register};
+
+pub(super) fn qubit(id: usize, num_children: usize) -> Qubit {
+    Qubit {
+        id,
+        num_children,
+        label: None,
+    }
+}
+
+pub(super) fn qubits(count: usize) -> Vec<Qubit> {
+    (0..count).map(|id| qubit(id, 0)).collect()
+}
+
+pub(super) fn gate(name: &str, targets: &[usize]) -> Operation {
+    Operation {
+        gate: name.to_string(),
+        targets: targets.iter().copied().map(Register::quantum).collect(),
+        ..Operation::default()
+    }
+}
+
+pub(super) fn measure(q_id: usize, c_id: usize) -> Operation {
+    Operation {
+        gate: "Measure".to_string(),
+        is_measurement: true,
+        controls: vec![Register::quantum(q_id)],
+        targets: vec![Register::classical(q_id, c_id)],
+        ..Operation::default()
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Renders a circuit as LaTeX source for the `quantikz` package, for including it in papers. Each
+//! qubit is a row of the `quantikz` environment and each operation takes a column, or shares one
+//! with other operations when columns are packed. Conditioned operations are framed in a gate
+//! group labelled with their condition.
+
+#[cfg(test)]
+mod tests;
+
+use super::{escape_with, layout::Placement, Circuit, Error, Operation, Results, Syntax, BARRIER};
+use std::fmt::Write;
+
+/// How [`render_latex`] lays out a circuit.
+#[derive(Clone, Debug, Default)]
+pub struct LatexOptions {
+    /// Places each operation in the first column where the wires it spans are free, as in the SVG
+    /// diagram, rather than each operation in a column of its own.
+    pub pack_columns: bool,
+    /// The labels of the wires, in the order of the qubits of the circuit, which are written in
+    /// math mode as they are. Other wires are labelled with the labels of their qubits, or with
+    /// their qubits, as in `q_{0}`, if they have none.
+    pub wire_labels: Vec<String>,
+}
+
+const LATEX: Syntax = Syntax {
+    result: |index, one| format!("c_{{{index}}} = {}", u8::from(one)),
+    not: "\\lnot ",
+    and: " \\land ",
+    or: " \\lor ",
+};
+
+const WIRE: &str = "\\qw";
+
+/// Renders the circuit as a `quantikz` environment. Operations with children are drawn as one
+/// box, and operations that act on no qubits are left out.
+/// # Errors
+/// Returns an error if an operation refers to a qubit that isn't in the circuit, or is
+/// conditioned on a result that hasn't been measured.
+pub fn render_latex(circuit: &Circuit, options: &LatexOptions) -> Result<String, Error> {
+    let mut results = Results::default();
+    let mut rows = vec![Vec::new(); circuit.qubits.len()];
+    let columns = if options.pack_columns {
+        Some(circuit.columns()?)
+    } else {
+        None
+    };
+    let mut num_columns = 0;
+    for (index, op) in circuit.operations.iter().enumerate() {
+        let condition = results.condition(op, &LATEX)?;
+        if op.is_measurement {
+            results.measure(op)?;
+        }
+        results.measure_children(op)?;
+
+        let placement = circuit.placement(op)?;
+        let column = match &columns {
+            Some(columns) => columns[index],
+            None => placement.span().map(|_| num_columns),
+        };
+        let Some(column) = column else {
+            continue;
+        };
+        num_columns = num_columns.max(column + 1);
+        for row in &mut rows {
+            row.resize(num_columns, WIRE.to_string());
+        }
+        draw(&mut rows, op, &placement, column);
+        if let (Some(condition), Some((top, bottom))) = (condition, placement.span()) {
+            let _ = write!(
+                rows[top][column],
+                " \\gategroup[wires={}, steps=1, style={{dashed}}]{{if ${condition}$}}",
+                bottom - top + 1
+            );
+        }
+    }
+
+    let mut latex = String::from("\\begin{quantikz}\n");
+    for (index, (qubit, cells)) in circuit.qubits.iter().zip(&rows).enumerate() {
+        match options.wire_labels.get(index) {
+            Some(label) => {
+                let _ = write!(latex, "\\lstick{{${label}$}}");
+            }
+            None => match &qubit.label {
+                Some(label) => {
+                    let _ = write!(latex, "\\lstick{{$\\mathit{{{}}}$}}", escape(label));
+                }
+                None => {
+                    let _ = write!(latex, "\\lstick{{$q_{{{}}}$}}", qubit.id);
+                }
+            },
+        }
+        for cell in cells {
+            let _ = write!(latex, " & {cell}");
+        }
+        let _ = write!(latex, " & {WIRE}");
+        if index + 1 < rows.len() {
+            latex.push_str(" \\\\");
+        }
+        latex.push('\n');
+    }
+    latex.push_str("\\end{quantikz}\n");
+    Ok(latex)
+}
+
+/// Fills the cells of the operation in the column: control dots linked to the first target, and
+/// a meter, a ⊕, a swap or a gate box on the targets.
+fn draw(rows: &mut [Vec<String>], op: &Operation, placement: &Placement, column: usize) {
+    let first = placement.targets.iter().min().copied();
+    let last = placement.targets.iter().max().copied();
+    if op.gate == BARRIER {
+        // A slice is a dashed line across the wires, with its label above it.
+        if let Some(first) = first {
+            let label = op.display_args.as_deref().unwrap_or_default();
+            rows[first][column] = format!("{WIRE} \\slice{{{}}}", escape_text(label));
+        }
+        return;
+    }
+    for &row in &placement.controls {
+        rows[row][column] = match first {
+            Some(target) if target >= row => format!("\\ctrl{{{}}}", target - row),
+            Some(target) => format!("\\ctrl{{-{}}}", row - target),
+            None => "\\control{}".to_string(),
+        };
+    }
+
+    if op.is_measurement {
+        for &row in &placement.targets {
+            rows[row][column] = "\\meter{}".to_string();
+        }
+    } else if placement.is_not {
+        for &row in &placement.targets {
+            rows[row][column] = "\\targ{}".to_string();
+        }
+    } else if let (Some(first), Some(last)) = (first, last) {
+        if op.gate == "SWAP" && placement.targets.len() == 2 {
+            rows[first][column] = format!("\\swap{{{}}}", last - first);
+            rows[last][column] = "\\targX{}".to_string();
+        } else if first == last {
+            rows[first][column] = format!("\\gate{{{}}}", label(op));
+        } else {
+            // Gates on several qubits are one box that covers the rows between them.
+            rows[first][column] = format!("\\gate[wires={}]{{{}}}", last - first + 1, label(op));
+        }
+    }
+}
+
+/// The math-mode text of a gate box: the gate, upright if its name is longer than one letter,
+/// marked with a dagger if it is adjoint, and its classical arguments.
+fn label(op: &Operation) -> String {
+    let gate = match op.gate.as_str() {
+        // The controls of a flattened controlled gate are drawn as dots.
+        "CY" => "Y",
+        "CZ" => "Z",
+        gate => gate,
+    };
+    let mut label = if gate.chars().count() == 1 {
+        escape(gate)
+    } else {
+        format!("\\mathrm{{{}}}", escape(gate))
+    };
+    if op.is_adjoint {
+        label.push_str("^\\dagger");
+    }
+    if let Some(args) = &op.display_args {
+        let _ = write!(label, "({})", escape(args));
+    }
+    label
+}
+
+/// Escapes the characters that are special in text mode.
+fn escape_text(text: &str) -> String {
+    escape_with(text, |c| match c {
+        '\\' => Some("\\textbackslash{}"),
+        '^' => Some("\\textasciicircum{}"),
+        '~' => Some("\\textasciitilde{}"),
+        _ => escape_reserved(c),
+    })
+}
+
+/// Escapes the characters that are special in math mode.
+fn escape(text: &str) -> String {
+    escape_with(text, |c| match c {
+        '\\' => Some("\\backslash "),
+        'π' => Some("\\pi "),
+        _ => escape_reserved(c),
+    })
+}
+
+/// The escaped form of a character that is reserved in both text and math mode.
+fn escape_reserved(c: char) -> Option<&'static str> {
+    match c {
+        '#' => Some("\\#"),
+        '$' => Some("\\$"),
+        '%' => Some("\\%"),
+        '&' => Some("\\&"),
+        '_' => Some("\\_"),
+        '{' => Some("\\{"),
+        '}' => Some("\\}"),
+        _ => None,
+    }
+}
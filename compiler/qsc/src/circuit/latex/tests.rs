@@ -0,0 +1,169 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{render_latex, LatexOptions};
+use crate::circuit::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, Error, Operation, Register, BARRIER,
+};
+use expect_test::expect;
+
+fn bell_pair() -> Circuit {
+    Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("CNOT", &[0, 1]),
+            measure(0, 0),
+            measure(1, 0),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    }
+}
+
+#[test]
+fn bell_pair_takes_a_column_per_operation() {
+    let latex =
+        render_latex(&bell_pair(), &LatexOptions::default()).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \gate{H} & \ctrl{1} & \meter{} & \qw & \qw \\
+        \lstick{$q_{1}$} & \qw & \targ{} & \qw & \meter{} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
+
+#[test]
+fn packed_columns_share_free_wires() {
+    let options = LatexOptions {
+        pack_columns: true,
+        wire_labels: vec!["a".to_string(), "\\ket{b}".to_string()],
+    };
+    let latex = render_latex(&bell_pair(), &options).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$a$} & \gate{H} & \ctrl{1} & \meter{} & \qw \\
+        \lstick{$\ket{b}$} & \qw & \targ{} & \meter{} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
+
+#[test]
+fn qubit_labels_name_the_wires_without_a_label_option() {
+    let mut circuit = bell_pair();
+    circuit.qubits[0].label = Some("ctl_a".to_string());
+    circuit.qubits[1].label = Some("targets[1]".to_string());
+    let options = LatexOptions {
+        pack_columns: true,
+        wire_labels: vec!["a".to_string()],
+    };
+    let latex = render_latex(&circuit, &options).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$a$} & \gate{H} & \ctrl{1} & \meter{} & \qw \\
+        \lstick{$\mathit{targets[1]}$} & \qw & \targ{} & \meter{} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
+
+#[test]
+fn gates_with_arguments_and_several_qubits() {
+    let mut rzz = gate("Rzz", &[0, 2]);
+    rzz.display_args = Some("π/2".to_string());
+    let mut t = gate("T", &[1]);
+    t.is_adjoint = true;
+    let mut cy = gate("Y", &[0]);
+    cy.is_controlled = true;
+    cy.controls = vec![Register::quantum(2)];
+    let circuit = Circuit {
+        operations: vec![rzz, t, gate("SWAP", &[0, 1]), cy],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    let latex = render_latex(&circuit, &LatexOptions::default()).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \gate[wires=3]{\mathrm{Rzz}(\pi /2)} & \qw & \swap{1} & \gate{Y} & \qw \\
+        \lstick{$q_{1}$} & \qw & \gate{T^\dagger} & \targX{} & \qw & \qw \\
+        \lstick{$q_{2}$} & \qw & \qw & \qw & \ctrl{-2} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
+
+#[test]
+fn gate_names_are_escaped() {
+    let circuit = Circuit {
+        operations: vec![gate("My_Gate", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    let latex = render_latex(&circuit, &LatexOptions::default()).expect("circuit should render");
+    assert!(latex.contains(r"\gate{\mathrm{My\_Gate}}"), "{latex}");
+}
+
+#[test]
+fn conditioned_gate_is_framed_with_its_condition() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Not(Box::new(ClassicalExpr::Result(
+                    Register::classical(0, 0),
+                    true,
+                )))),
+                ..gate("X", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let latex = render_latex(&circuit, &LatexOptions::default()).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \meter{} & \qw & \qw \\
+        \lstick{$q_{1}$} & \qw & \gate{X} \gategroup[wires=1, steps=1, style={dashed}]{if $\lnot (c_{0} = 1)$} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
+
+#[test]
+fn unknown_qubit_is_an_error() {
+    let circuit = Circuit {
+        operations: vec![gate("H", &[3])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        render_latex(&circuit, &LatexOptions::default()),
+        Err(Error::UnknownQubit("H".to_string(), 3))
+    );
+}
+
+#[test]
+fn barrier_is_a_labelled_slice() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            Operation {
+                display_args: Some("Phase_1".to_string()),
+                ..gate(BARRIER, &[0, 1])
+            },
+            gate("X", &[1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let latex = render_latex(&circuit, &LatexOptions::default()).expect("circuit should render");
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \gate{H} & \qw \slice{Phase\_1} & \qw & \qw \\
+        \lstick{$q_{1}$} & \qw & \qw & \gate{X} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&latex);
+}
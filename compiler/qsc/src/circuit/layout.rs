@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Layout of a circuit into columns, shared by the renderers. Each operation is drawn across the
+//! rows from its first qubit to its last, and goes in the first column where those rows are free,
+//! so the columns of a circuit are its moments: groups of operations on disjoint wires that could
+//! run at the same time.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Circuit, ClassicalExpr, Error, Operation, Register};
+use rustc_hash::FxHashMap;
+
+/// The rows of the qubits that an operation acts on.
+pub(super) struct Placement {
+    pub(super) controls: Vec<usize>,
+    pub(super) targets: Vec<usize>,
+    /// Draws the targets as the ⊕ of a controlled X rather than as a box.
+    pub(super) is_not: bool,
+}
+
+impl Placement {
+    fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.controls.iter().chain(&self.targets).copied()
+    }
+
+    /// The first and last rows that the operation spans, if it acts on any qubits.
+    pub(super) fn span(&self) -> Option<(usize, usize)> {
+        Some((self.rows().min()?, self.rows().max()?))
+    }
+
+    /// The placement with each row replaced by the row it is drawn in.
+    pub(super) fn in_slots(&self, slot: impl Fn(usize) -> usize) -> Self {
+        Self {
+            controls: self.controls.iter().map(|&row| slot(row)).collect(),
+            targets: self.targets.iter().map(|&row| slot(row)).collect(),
+            is_not: self.is_not,
+        }
+    }
+}
+
+/// Places an operation in the first column where the wires it spans are free, returning that
+/// column, or `None` if it acts on no qubits.
+pub(super) fn place(free: &mut [usize], placement: &Placement) -> Option<usize> {
+    place_from(free, placement, 0)
+}
+
+/// Places an operation in the first column from `earliest` on where the wires it spans are free.
+fn place_from(free: &mut [usize], placement: &Placement, earliest: usize) -> Option<usize> {
+    placement.span().map(|(top, bottom)| {
+        let column = free[top..=bottom]
+            .iter()
+            .copied()
+            .fold(earliest, usize::max);
+        free[top..=bottom].fill(column + 1);
+        column
+    })
+}
+
+impl Circuit {
+    /// The moments of the circuit: the positions of its operations grouped into columns, where
+    /// the operations of a column act on disjoint wires. Each operation goes in the first column
+    /// after the operations before it on the wires it spans, and after the measurements of the
+    /// results it is conditioned on. Operations that act on no qubits are left out.
+    /// # Errors
+    /// Returns an error if an operation refers to a qubit that isn't in the circuit.
+    pub fn moments(&self) -> Result<Vec<Vec<usize>>, Error> {
+        let mut moments = Vec::<Vec<usize>>::new();
+        for (index, column) in self.columns()?.into_iter().enumerate() {
+            if let Some(column) = column {
+                if moments.len() <= column {
+                    moments.resize_with(column + 1, Vec::new);
+                }
+                moments[column].push(index);
+            }
+        }
+        Ok(moments)
+    }
+
+    /// The column of each operation in the moments of the circuit, or `None` for operations that
+    /// act on no qubits.
+    pub(super) fn columns(&self) -> Result<Vec<Option<usize>>, Error> {
+        let mut free = vec![0; self.qubits.len()];
+        // The column after the measurement of each result.
+        let mut measured = FxHashMap::<(usize, Option<usize>), usize>::default();
+        let mut columns = Vec::with_capacity(self.operations.len());
+        for op in &self.operations {
+            let placement = self.placement(op)?;
+            let mut results = Vec::new();
+            if let Some(condition) = &op.condition {
+                condition_results(condition, &mut results);
+            }
+            let earliest = results
+                .iter()
+                .filter_map(|result| measured.get(result))
+                .copied()
+                .max()
+                .unwrap_or_default();
+            let column = place_from(&mut free, &placement, earliest);
+            if let Some(column) = column {
+                for register in op.targets.iter().chain(&op.controls) {
+                    if register.c_id.is_some() {
+                        measured.insert((register.q_id, register.c_id), column + 1);
+                    }
+                }
+            }
+            columns.push(column);
+        }
+        Ok(columns)
+    }
+
+    pub(super) fn placement(&self, op: &Operation) -> Result<Placement, Error> {
+        let rows = |registers: &[Register]| {
+            registers
+                .iter()
+                .map(|register| self.row(op, register.q_id))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let mut controls = rows(&op.controls)?;
+        let mut targets = rows(&op.targets)?;
+        if op.is_measurement {
+            // The measured qubit is the control, and the targets are classical results.
+            return Ok(Placement {
+                controls: Vec::new(),
+                targets: controls,
+                is_not: false,
+            });
+        }
+
+        // Controlled gates that were flattened into a gate of their own have their controls as
+        // the leading targets when the controls aren't given separately.
+        let flattened = match op.gate.as_str() {
+            "CNOT" | "CX" | "CY" | "CZ" => Some(1),
+            "CCNOT" | "CCX" => Some(2),
+            _ => None,
+        };
+        if let Some(count) = flattened {
+            if controls.is_empty() && targets.len() > count {
+                controls = targets.drain(..count).collect();
+            }
+        }
+        let is_not = !controls.is_empty()
+            && matches!(op.gate.as_str(), "X" | "CNOT" | "CX" | "CCNOT" | "CCX");
+        Ok(Placement {
+            controls,
+            targets,
+            is_not,
+        })
+    }
+}
+
+/// The results that the condition reads, by their qubit and result ids.
+fn condition_results(expr: &ClassicalExpr, results: &mut Vec<(usize, Option<usize>)>) {
+    match expr {
+        ClassicalExpr::Result(register, _) => results.push((register.q_id, register.c_id)),
+        ClassicalExpr::Not(inner) => condition_results(inner, results),
+        ClassicalExpr::And(lhs, rhs) | ClassicalExpr::Or(lhs, rhs) => {
+            condition_results(lhs, results);
+            condition_results(rhs, results);
+        }
+    }
+}
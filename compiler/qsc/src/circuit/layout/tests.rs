@@ -0,0 +1,64 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::circuit::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, Error, Operation, Register,
+};
+
+#[test]
+fn gates_on_disjoint_wires_share_a_moment() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("H", &[1]),
+            gate("CNOT", &[0, 1]),
+            gate("X", &[2]),
+            gate("Y", &[0]),
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    assert_eq!(circuit.moments(), Ok(vec![vec![0, 1, 3], vec![2], vec![4]]));
+}
+
+#[test]
+fn gates_take_the_wires_between_their_qubits() {
+    let circuit = Circuit {
+        operations: vec![gate("CNOT", &[0, 2]), gate("H", &[1]), gate("H", &[3])],
+        qubits: qubits(4),
+        ..Circuit::default()
+    };
+    assert_eq!(circuit.moments(), Ok(vec![vec![0, 2], vec![1]]));
+}
+
+#[test]
+fn conditioned_gates_come_after_the_measurements_they_read() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                ..gate("X", &[1])
+            },
+            gate("Z", &[2]),
+            gate("Barrier", &[]),
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    assert_eq!(circuit.moments(), Ok(vec![vec![0, 2], vec![1]]));
+}
+
+#[test]
+fn unknown_qubits_are_errors() {
+    let circuit = Circuit {
+        operations: vec![gate("H", &[1])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.moments(),
+        Err(Error::UnknownQubit("H".to_string(), 1))
+    );
+}
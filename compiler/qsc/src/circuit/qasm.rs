@@ -0,0 +1,170 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Exports a circuit as an OpenQASM 3 program, so that it can be run by toolchains that consume
+//! OpenQASM. The qubits are one register `q`, indexed by their position in the circuit, and the
+//! measurement results are one register `c`, in the order they were measured. Conditioned
+//! operations are applied in `if` statements, and operations with children are expanded.
+
+#[cfg(test)]
+mod tests;
+
+use super::{expand, parse_args, Circuit, Error, Operation, Results, Syntax, BARRIER};
+use std::fmt::Write;
+
+/// Definitions of the two-qubit rotations, which are not in the standard gate library.
+const RXX: &str = "gate rxx(theta) a, b { h a; h b; cx a, b; rz(theta) b; cx a, b; h a; h b; }";
+const RYY: &str = "gate ryy(theta) a, b { rx(pi / 2) a; rx(pi / 2) b; cx a, b; rz(theta) b; \
+                   cx a, b; rx(-pi / 2) a; rx(-pi / 2) b; }";
+const RZZ: &str = "gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }";
+
+const QASM: Syntax = Syntax {
+    result: |index, one| format!("c[{index}] == {}", u8::from(one)),
+    not: "!",
+    and: " && ",
+    or: " || ",
+};
+
+impl Circuit {
+    /// Generates an OpenQASM 3 program that applies the circuit. Adjoint and controlled
+    /// operations use the `inv` and `ctrl` gate modifiers.
+    pub fn to_qasm(&self) -> Result<String, Error> {
+        let mut body = String::new();
+        let mut definitions = Vec::new();
+        let mut results = Results::default();
+        for op in &expand(&self.operations) {
+            let condition = results.condition(op, &QASM)?;
+            let statement = if op.is_measurement {
+                let qubit = op
+                    .controls
+                    .first()
+                    .ok_or_else(|| Error::GateShape(op.gate.clone(), 1, 0))?;
+                let row = self.row(op, qubit.q_id)?;
+                format!("c[{}] = measure q[{row}]", results.measure(op)?)
+            } else if op.gate == BARRIER {
+                // The label of a barrier has no place in the statement, so it is a comment.
+                if let Some(label) = &op.display_args {
+                    let _ = writeln!(body, "// {}", label.replace('\n', " "));
+                }
+                let operands = op
+                    .targets
+                    .iter()
+                    .map(|target| Ok(format!("q[{}]", self.row(op, target.q_id)?)))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                format!("barrier {}", operands.join(", "))
+            } else {
+                self.qasm_statement(op, &mut definitions)?
+            };
+            let _ = match condition {
+                Some(condition) => writeln!(body, "if ({condition}) {{ {statement}; }}"),
+                None => writeln!(body, "{statement};"),
+            };
+        }
+
+        let mut qasm = String::from("OPENQASM 3.0;\ninclude \"stdgates.inc\";\n");
+        for definition in definitions {
+            qasm.push_str(definition);
+            qasm.push('\n');
+        }
+        if !self.qubits.is_empty() {
+            let _ = writeln!(qasm, "qubit[{}] q;", self.qubits.len());
+        }
+        if !results.is_empty() {
+            let _ = writeln!(qasm, "bit[{}] c;", results.len());
+        }
+        qasm.push_str(&body);
+        Ok(qasm)
+    }
+
+    /// The statement that applies the operation, without its semicolon. The definitions of the
+    /// gates it uses that are not in the standard gate library are added to the definitions.
+    fn qasm_statement(
+        &self,
+        op: &Operation,
+        definitions: &mut Vec<&'static str>,
+    ) -> Result<String, Error> {
+        let (gate, qubits, args, definition) = match op.gate.as_str() {
+            "I" => ("id", 1, 0, None),
+            "H" => ("h", 1, 0, None),
+            "X" => ("x", 1, 0, None),
+            "Y" => ("y", 1, 0, None),
+            "Z" => ("z", 1, 0, None),
+            "S" => ("s", 1, 0, None),
+            "T" => ("t", 1, 0, None),
+            "Reset" => ("reset", 1, 0, None),
+            "Rx" => ("rx", 1, 1, None),
+            "Ry" => ("ry", 1, 1, None),
+            "Rz" => ("rz", 1, 1, None),
+            "Rxx" => ("rxx", 2, 1, Some(RXX)),
+            "Ryy" => ("ryy", 2, 1, Some(RYY)),
+            "Rzz" => ("rzz", 2, 1, Some(RZZ)),
+            "SWAP" => ("swap", 2, 0, None),
+            // Controlled gates that were flattened into a gate of their own.
+            "CNOT" | "CX" => return self.qasm_controlled(op, "cx", 1),
+            "CY" => return self.qasm_controlled(op, "cy", 1),
+            "CZ" => return self.qasm_controlled(op, "cz", 1),
+            "CCNOT" | "CCX" => return self.qasm_controlled(op, "ccx", 2),
+            _ => return Err(Error::UnsupportedQasmGate(op.gate.clone())),
+        };
+
+        let inputs = parse_args(op)?;
+        if op.targets.len() != qubits || inputs.len() != args {
+            return Err(Error::GateShape(op.gate.clone(), qubits, args));
+        }
+        // A reset is not a gate, so it can't take modifiers.
+        if gate == "reset" && (op.is_adjoint || op.is_controlled) {
+            return Err(Error::UnsupportedQasmGate(op.gate.clone()));
+        }
+        if let Some(definition) = definition {
+            if !definitions.contains(&definition) {
+                definitions.push(definition);
+            }
+        }
+
+        let mut statement = String::new();
+        if op.is_adjoint {
+            statement.push_str("inv @ ");
+        }
+        let controls = if op.is_controlled {
+            op.controls.as_slice()
+        } else {
+            &[]
+        };
+        match controls.len() {
+            0 => {}
+            1 => statement.push_str("ctrl @ "),
+            count => {
+                let _ = write!(statement, "ctrl({count}) @ ");
+            }
+        }
+        statement.push_str(gate);
+        if !inputs.is_empty() {
+            let _ = write!(statement, "({})", inputs.join(", "));
+        }
+        let operands = controls
+            .iter()
+            .chain(&op.targets)
+            .map(|register| Ok(format!("q[{}]", self.row(op, register.q_id)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let _ = write!(statement, " {}", operands.join(", "));
+        Ok(statement)
+    }
+
+    /// A gate such as `CNOT`, whose leading targets are the controls of a Pauli gate.
+    fn qasm_controlled(
+        &self,
+        op: &Operation,
+        gate: &str,
+        controls: usize,
+    ) -> Result<String, Error> {
+        let registers = op.controls.iter().chain(&op.targets).collect::<Vec<_>>();
+        if op.is_controlled || registers.len() != controls + 1 || op.display_args.is_some() {
+            return Err(Error::GateShape(op.gate.clone(), controls + 1, 0));
+        }
+        let operands = registers
+            .iter()
+            .map(|register| Ok(format!("q[{}]", self.row(op, register.q_id)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(format!("{gate} {}", operands.join(", ")))
+    }
+}
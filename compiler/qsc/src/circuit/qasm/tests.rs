@@ -0,0 +1,181 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::circuit::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, Error, Operation, Register, BARRIER,
+};
+use expect_test::expect;
+
+#[test]
+fn bell_pair_is_exported() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("CNOT", &[0, 1]),
+            measure(0, 0),
+            measure(1, 0),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        bit[2] c;
+        h q[0];
+        cx q[0], q[1];
+        c[0] = measure q[0];
+        c[1] = measure q[1];
+    "#]]
+    .assert_eq(&circuit.to_qasm().expect("circuit should export"));
+}
+
+#[test]
+fn modifiers_and_arguments_are_exported() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                is_adjoint: true,
+                ..gate("T", &[0])
+            },
+            Operation {
+                display_args: Some("0.5".to_string()),
+                ..gate("Rx", &[1])
+            },
+            Operation {
+                is_controlled: true,
+                controls: vec![Register::quantum(0), Register::quantum(1)],
+                ..gate("Z", &[2])
+            },
+            Operation {
+                is_adjoint: true,
+                is_controlled: true,
+                controls: vec![Register::quantum(2)],
+                ..gate("S", &[0])
+            },
+            gate("CCNOT", &[0, 1, 2]),
+            gate("Reset", &[2]),
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[3] q;
+        inv @ t q[0];
+        rx(0.5) q[1];
+        ctrl(2) @ z q[0], q[1], q[2];
+        inv @ ctrl @ s q[2], q[0];
+        ccx q[0], q[1], q[2];
+        reset q[2];
+    "#]]
+    .assert_eq(&circuit.to_qasm().expect("circuit should export"));
+}
+
+#[test]
+fn two_qubit_rotations_are_defined_once() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                display_args: Some("1".to_string()),
+                ..gate("Rzz", &[0, 1])
+            },
+            Operation {
+                display_args: Some("2".to_string()),
+                ..gate("Rzz", &[1, 0])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }
+        qubit[2] q;
+        rzz(1.0) q[0], q[1];
+        rzz(2.0) q[1], q[0];
+    "#]]
+    .assert_eq(&circuit.to_qasm().expect("circuit should export"));
+}
+
+#[test]
+fn unsupported_gate_is_an_error() {
+    let circuit = Circuit {
+        operations: vec![gate("Foo", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.to_qasm(),
+        Err(Error::UnsupportedQasmGate("Foo".to_string()))
+    );
+}
+
+#[test]
+fn conditioned_operations_are_exported() {
+    let one = ClassicalExpr::Result(Register::classical(0, 0), true);
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(one.clone()),
+                ..gate("X", &[1])
+            },
+            Operation {
+                condition: Some(ClassicalExpr::Not(Box::new(one.clone()))),
+                ..measure(1, 0)
+            },
+            Operation {
+                condition: Some(ClassicalExpr::Or(
+                    Box::new(one),
+                    Box::new(ClassicalExpr::Result(Register::classical(1, 0), false)),
+                )),
+                is_adjoint: true,
+                ..gate("S", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        bit[2] c;
+        c[0] = measure q[0];
+        if (c[0] == 1) { x q[1]; }
+        if (!(c[0] == 1)) { c[1] = measure q[1]; }
+        if ((c[0] == 1 || c[1] == 0)) { inv @ s q[1]; }
+    "#]]
+    .assert_eq(&circuit.to_qasm().expect("circuit should export"));
+}
+
+#[test]
+fn barriers_are_exported_with_their_labels_as_comments() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            Operation {
+                display_args: Some("Oracle".to_string()),
+                ..gate(BARRIER, &[0, 1])
+            },
+            gate(BARRIER, &[1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        h q[0];
+        // Oracle
+        barrier q[0], q[1];
+        barrier q[1];
+    "#]]
+    .assert_eq(&circuit.to_qasm().expect("circuit should export"));
+}
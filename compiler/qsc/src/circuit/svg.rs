@@ -0,0 +1,419 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Renders a circuit as a standalone SVG diagram. Each qubit is a horizontal wire, and operations
+//! are laid out in columns from left to right, each in the first column where the wires it spans
+//! are free. Conditioned operations are drawn dashed, in lanes labelled with their condition, and
+//! other operations with children are drawn collapsed, as one box.
+//! Coordinates are whole pixels so that the output is stable across platforms.
+
+#[cfg(test)]
+mod tests;
+
+use super::{
+    escape_with,
+    layout::{place, Placement},
+    Circuit, ClassicalExpr, Error, Operation, Results, BARRIER, QSHARP,
+};
+use std::fmt::Write;
+
+/// The width of the margin on the left of the wires, which holds the qubit labels.
+const LABEL_WIDTH: usize = 60;
+const COLUMN_WIDTH: usize = 60;
+const ROW_HEIGHT: usize = 50;
+const GATE_SIZE: usize = 40;
+const MARGIN: usize = 20;
+const CONTROL_RADIUS: usize = 5;
+const TARGET_RADIUS: usize = 12;
+
+/// An operation, or a group of conditioned operations that are drawn in lanes of their own.
+enum Item<'a> {
+    Op(&'a Operation, Placement, Option<usize>),
+    Branches(Branches<'a>),
+}
+
+/// Conditioned operations that are drawn side by side, in parallel lanes that span the same
+/// columns, such as the two branches of an `if`-`else` on a measured result.
+struct Branches<'a> {
+    /// The condition of each lane and the operations drawn in it, with their placements and
+    /// columns.
+    lanes: Vec<(String, Vec<(&'a Operation, Placement, usize)>)>,
+    /// The first and last rows that the lanes span.
+    rows: (usize, usize),
+    /// The first column of the lanes and the number of columns they span.
+    columns: (usize, usize),
+}
+
+/// Renders the circuit as an SVG document with qubit wires, gate boxes, control dots and
+/// measurement symbols. Classical results are not drawn as wires of their own, but the results
+/// in the classical registers of the circuit are labelled with their names.
+///
+/// Conditioned operations are drawn dashed, in a lane labelled with their condition, and an
+/// operation with children that is conditioned is drawn with its children in the lane. When an
+/// operation is followed by one with the opposite condition, as in an `if`-`else` on a result,
+/// the second is drawn in a parallel lane on a copy of the wires below the circuit.
+/// # Errors
+/// Returns an error if an operation refers to a qubit that isn't in the circuit, or is
+/// conditioned on a result that hasn't been measured.
+pub fn render_svg(circuit: &Circuit) -> Result<String, Error> {
+    let mut results = Results::default();
+    let mut conditions = Vec::with_capacity(circuit.operations.len());
+    for op in &circuit.operations {
+        conditions.push(results.condition(op, &QSHARP)?);
+        if op.is_measurement {
+            results.measure(op)?;
+        }
+        results.measure_children(op)?;
+    }
+
+    // Each wire is free from the column after the last operation that spans it.
+    let mut free = vec![0; circuit.qubits.len()];
+    let mut items = Vec::new();
+    let mut index = 0;
+    while let Some(op) = circuit.operations.get(index) {
+        let Some(condition) = conditions[index].take() else {
+            let placement = circuit.placement(op)?;
+            let column = place(&mut free, &placement);
+            items.push(Item::Op(op, placement, column));
+            index += 1;
+            continue;
+        };
+        let mut lanes = vec![(condition, op)];
+        if let Some(next) = circuit.operations.get(index + 1) {
+            if let (Some(own), Some(other)) = (&op.condition, &next.condition) {
+                if is_opposite(own, other) {
+                    let condition = conditions[index + 1]
+                        .take()
+                        .expect("condition should be set");
+                    lanes.push((condition, next));
+                }
+            }
+        }
+        index += lanes.len();
+        if let Some(branches) = circuit.branches(&mut free, lanes)? {
+            items.push(Item::Branches(branches));
+        }
+    }
+    let num_columns = free.iter().copied().max().unwrap_or(0);
+
+    // Lanes after the first are drawn on copies of the wires below the circuit.
+    let num_lanes = items
+        .iter()
+        .map(|item| match item {
+            Item::Op(..) => 1,
+            Item::Branches(branches) => branches.lanes.len(),
+        })
+        .max()
+        .unwrap_or(1);
+    let num_qubits = circuit.qubits.len();
+
+    let width = LABEL_WIDTH + num_columns * COLUMN_WIDTH + MARGIN;
+    let height = num_lanes * num_qubits * ROW_HEIGHT + 2 * MARGIN;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    svg.push_str(concat!(
+        r#"<rect width="100%" height="100%" fill="white"/>"#,
+        "\n",
+        r#"<g font-family="sans-serif" font-size="14" text-anchor="middle" dominant-baseline="middle" stroke="black" stroke-width="1">"#,
+        "\n",
+    ));
+
+    for (row, qubit) in circuit.qubits.iter().enumerate() {
+        let y = wire_y(row);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{LABEL_WIDTH}" y1="{y}" x2="{}" y2="{y}"/>"#,
+            width - MARGIN
+        );
+        let label = qubit
+            .label
+            .as_deref()
+            .map_or_else(|| format!("q{}", qubit.id), escape);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{y}" stroke="none">{label}</text>"#,
+            LABEL_WIDTH / 2,
+        );
+    }
+
+    for item in &items {
+        match item {
+            Item::Op(_, _, None) => {}
+            Item::Op(op, placement, Some(column)) => {
+                draw(&mut svg, circuit, op, placement, *column);
+            }
+            Item::Branches(branches) => draw_branches(&mut svg, circuit, branches),
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    Ok(svg)
+}
+
+/// Draws each lane of the branches dashed, in a frame labelled with its condition. Lanes after
+/// the first are drawn on copies of the wires they span, which are as many rows further down for
+/// each lane as the circuit has qubits.
+fn draw_branches(svg: &mut String, circuit: &Circuit, branches: &Branches) {
+    let num_qubits = circuit.qubits.len();
+    let (top, bottom) = branches.rows;
+    let (first, count) = branches.columns;
+    let left = LABEL_WIDTH + first * COLUMN_WIDTH;
+    let right = left + count * COLUMN_WIDTH;
+    for (lane, (condition, ops)) in branches.lanes.iter().enumerate() {
+        let slot = |row: usize| lane * num_qubits + row;
+        if lane > 0 {
+            for row in top..=bottom {
+                let y = wire_y(slot(row));
+                let _ = writeln!(svg, r#"<line x1="{left}" y1="{y}" x2="{right}" y2="{y}"/>"#);
+            }
+        }
+        svg.push_str("<g stroke-dasharray=\"4 2\">\n");
+        let _ = writeln!(svg, "<title>if {}</title>", escape(condition));
+        for (op, placement, column) in ops {
+            draw(svg, circuit, op, &placement.in_slots(slot), *column);
+        }
+        let frame_top = wire_y(slot(top)) - ROW_HEIGHT / 2;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{frame_top}" width="{}" height="{}" fill="none"/>"#,
+            left + 2,
+            count * COLUMN_WIDTH - 4,
+            (bottom - top + 1) * ROW_HEIGHT
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="10" text-anchor="start" stroke="none">{}</text>"#,
+            left + 4,
+            frame_top + 6,
+            escape(condition)
+        );
+        svg.push_str("</g>\n");
+    }
+}
+
+/// Whether one condition holds exactly when the other doesn't.
+fn is_opposite(lhs: &ClassicalExpr, rhs: &ClassicalExpr) -> bool {
+    match (lhs, rhs) {
+        (ClassicalExpr::Result(lhs, lhs_one), ClassicalExpr::Result(rhs, rhs_one)) => {
+            lhs == rhs && lhs_one != rhs_one
+        }
+        (ClassicalExpr::Not(inner), other) | (other, ClassicalExpr::Not(inner)) => {
+            **inner == *other
+        }
+        _ => false,
+    }
+}
+
+impl Circuit {
+    /// Lays out conditioned operations in parallel lanes, from the first column where all the
+    /// wires that any lane spans are free. An operation with children is drawn as its children.
+    fn branches<'a>(
+        &self,
+        free: &mut [usize],
+        lanes: Vec<(String, &'a Operation)>,
+    ) -> Result<Option<Branches<'a>>, Error> {
+        let mut placed = Vec::with_capacity(lanes.len());
+        for (condition, op) in lanes {
+            let ops = if op.children.is_empty() {
+                std::slice::from_ref(op)
+            } else {
+                op.children.as_slice()
+            };
+            let ops = ops
+                .iter()
+                .map(|op| Ok((op, self.placement(op)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+            placed.push((condition, ops));
+        }
+
+        let spans = placed
+            .iter()
+            .flat_map(|(_, ops)| ops.iter().filter_map(|(_, placement)| placement.span()));
+        let Some(rows) = spans.reduce(|(top, bottom), (lo, hi)| (top.min(lo), bottom.max(hi)))
+        else {
+            return Ok(None);
+        };
+        let first = free[rows.0..=rows.1].iter().copied().max().unwrap_or(0);
+
+        let mut end = first + 1;
+        let lanes = placed
+            .into_iter()
+            .map(|(condition, ops)| {
+                let mut lane_free = vec![first; free.len()];
+                let ops = ops
+                    .into_iter()
+                    .filter_map(|(op, placement)| {
+                        let column = place(&mut lane_free, &placement)?;
+                        Some((op, placement, column))
+                    })
+                    .collect();
+                end = end.max(lane_free.iter().copied().max().unwrap_or(first));
+                (condition, ops)
+            })
+            .collect();
+        free[rows.0..=rows.1].fill(end);
+        Ok(Some(Branches {
+            lanes,
+            rows,
+            columns: (first, end - first),
+        }))
+    }
+}
+
+fn wire_y(row: usize) -> usize {
+    MARGIN + row * ROW_HEIGHT + ROW_HEIGHT / 2
+}
+
+fn draw(svg: &mut String, circuit: &Circuit, op: &Operation, placement: &Placement, column: usize) {
+    let x = LABEL_WIDTH + column * COLUMN_WIDTH + COLUMN_WIDTH / 2;
+    let (top, bottom) = placement.span().expect("operation should act on qubits");
+    if op.gate == BARRIER {
+        draw_barrier(svg, op, x, (top, bottom));
+        return;
+    }
+    if top != bottom {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}"/>"#,
+            wire_y(top),
+            wire_y(bottom)
+        );
+    }
+
+    for &row in &placement.controls {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{x}" cy="{}" r="{CONTROL_RADIUS}" fill="black"/>"#,
+            wire_y(row)
+        );
+    }
+
+    if op.is_measurement {
+        for &row in &placement.targets {
+            draw_meter(svg, x, wire_y(row));
+        }
+        // A result in a classical register of the circuit is labelled with its name.
+        let label = op
+            .targets
+            .first()
+            .and_then(|&result| circuit.result_label(result));
+        if let (Some(label), Some(&row)) = (label, placement.targets.first()) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x}" y="{}" font-size="10" stroke="none">{}</text>"#,
+                wire_y(row) + GATE_SIZE / 2 + 6,
+                escape(&label)
+            );
+        }
+    } else if placement.is_not {
+        for &row in &placement.targets {
+            let y = wire_y(row);
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{x}" cy="{y}" r="{TARGET_RADIUS}" fill="white"/>"#
+            );
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}"/>"#,
+                x - TARGET_RADIUS,
+                x + TARGET_RADIUS
+            );
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}"/>"#,
+                y - TARGET_RADIUS,
+                y + TARGET_RADIUS
+            );
+        }
+    } else if let (Some(&first), Some(&last)) = (
+        placement.targets.iter().min(),
+        placement.targets.iter().max(),
+    ) {
+        // Gates on several qubits are one box that covers the rows between them.
+        let box_top = wire_y(first) - GATE_SIZE / 2;
+        let box_height = wire_y(last) - wire_y(first) + GATE_SIZE;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{box_top}" width="{GATE_SIZE}" height="{box_height}" fill="white"/>"#,
+            x - GATE_SIZE / 2
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x}" y="{}" stroke="none">{}</text>"#,
+            box_top + box_height / 2,
+            escape(&label(op))
+        );
+    }
+}
+
+/// Draws a barrier as a dashed line across the rows it spans, with its label above it.
+fn draw_barrier(svg: &mut String, op: &Operation, x: usize, (top, bottom): (usize, usize)) {
+    let y1 = wire_y(top) - ROW_HEIGHT / 2 + 10;
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{x}" y1="{y1}" x2="{x}" y2="{}" stroke-dasharray="4 2"/>"#,
+        wire_y(bottom) + ROW_HEIGHT / 2
+    );
+    if let Some(label) = &op.display_args {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{x}" y="{}" font-size="10" stroke="none">{}</text>"#,
+            y1 - 5,
+            escape(label)
+        );
+    }
+}
+
+/// Draws a measurement box with a meter: an arc and a needle.
+fn draw_meter(svg: &mut String, x: usize, y: usize) {
+    let left = x - GATE_SIZE / 2;
+    let top = y - GATE_SIZE / 2;
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{left}" y="{top}" width="{GATE_SIZE}" height="{GATE_SIZE}" fill="white"/>"#
+    );
+    let radius = GATE_SIZE / 3;
+    let base = y + GATE_SIZE / 4;
+    let _ = writeln!(
+        svg,
+        r#"<path d="M {} {base} A {radius} {radius} 0 0 1 {} {base}" fill="none"/>"#,
+        x - radius,
+        x + radius
+    );
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{x}" y1="{base}" x2="{}" y2="{}"/>"#,
+        x + radius / 2,
+        top + GATE_SIZE / 4
+    );
+}
+
+/// The text of a gate box: the gate, marked if it is adjoint, and its classical arguments.
+fn label(op: &Operation) -> String {
+    let mut label = match op.gate.as_str() {
+        // The controls of a flattened controlled gate are drawn as dots.
+        "CY" => "Y".to_string(),
+        "CZ" => "Z".to_string(),
+        gate => gate.to_string(),
+    };
+    if op.is_adjoint {
+        label.push('†');
+    }
+    if let Some(args) = &op.display_args {
+        let _ = write!(label, "({args})");
+    }
+    label
+}
+
+fn escape(text: &str) -> String {
+    escape_with(text, |c| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '"' => Some("&quot;"),
+        _ => None,
+    })
+}
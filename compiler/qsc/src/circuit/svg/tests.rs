@@ -0,0 +1,240 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::render_svg;
+use crate::circuit::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, ClassicalRegister, Error, Operation, Register, BARRIER,
+};
+
+#[test]
+fn bell_pair_is_drawn_in_columns() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("CNOT", &[0, 1]),
+            measure(0, 0),
+            measure(1, 0),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+
+    // Three columns: the H, the CNOT, and both measurements side by side.
+    assert!(svg.starts_with(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="260" height="140" viewBox="0 0 260 140">"#
+    ));
+    assert!(svg.ends_with("</g>\n</svg>\n"));
+    assert!(svg.contains(r#"<text x="30" y="45" stroke="none">q0</text>"#));
+    assert!(svg.contains(r#"<text x="30" y="95" stroke="none">q1</text>"#));
+    assert!(svg.contains(r#"<rect x="70" y="25" width="40" height="40" fill="white"/>"#));
+    assert!(svg.contains(r#"<text x="90" y="45" stroke="none">H</text>"#));
+    assert!(svg.contains(r#"<line x1="150" y1="45" x2="150" y2="95"/>"#));
+    assert!(svg.contains(r#"<circle cx="150" cy="45" r="5" fill="black"/>"#));
+    assert!(svg.contains(r#"<circle cx="150" cy="95" r="12" fill="white"/>"#));
+    assert!(svg.contains(r#"<rect x="190" y="25" width="40" height="40" fill="white"/>"#));
+    assert!(svg.contains(r#"<rect x="190" y="75" width="40" height="40" fill="white"/>"#));
+    assert_eq!(svg.matches("<path").count(), 2);
+}
+
+#[test]
+fn multi_qubit_gate_covers_the_wires_between_its_targets() {
+    let mut rzz = gate("Rzz", &[0, 2]);
+    rzz.display_args = Some("0.5".to_string());
+    let circuit = Circuit {
+        operations: vec![rzz, gate("X", &[1])],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+
+    assert!(svg.contains(r#"<rect x="70" y="25" width="40" height="140" fill="white"/>"#));
+    assert!(svg.contains(r#"<text x="90" y="95" stroke="none">Rzz(0.5)</text>"#));
+    // The middle wire is busy while the box covers it, so the X goes in the next column.
+    assert!(svg.contains(r#"<text x="150" y="95" stroke="none">X</text>"#));
+}
+
+#[test]
+fn controlled_gate_has_control_dots_and_labelled_target() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                gate: "S".to_string(),
+                is_adjoint: true,
+                is_controlled: true,
+                controls: vec![Register::quantum(0)],
+                targets: vec![Register::quantum(1)],
+                ..Operation::default()
+            },
+            gate("CZ", &[1, 0]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+
+    assert!(svg.contains(r#"<circle cx="90" cy="45" r="5" fill="black"/>"#));
+    assert!(svg.contains(r#"<text x="90" y="95" stroke="none">S†</text>"#));
+    assert!(svg.contains(r#"<circle cx="150" cy="95" r="5" fill="black"/>"#));
+    assert!(svg.contains(r#"<text x="150" y="45" stroke="none">Z</text>"#));
+}
+
+#[test]
+fn gate_names_are_escaped() {
+    let circuit = Circuit {
+        operations: vec![gate("<U&V>", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+    assert!(svg.contains(">&lt;U&amp;V&gt;</text>"));
+}
+
+#[test]
+fn unknown_qubit_is_an_error() {
+    let circuit = Circuit {
+        operations: vec![gate("H", &[3])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        render_svg(&circuit),
+        Err(Error::UnknownQubit("H".to_string(), 3))
+    );
+}
+
+#[test]
+fn conditioned_gate_is_dashed_with_its_condition() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                ..gate("X", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+    assert!(svg.contains(concat!(
+        "<g stroke-dasharray=\"4 2\">\n",
+        "<title>if c0 == One</title>\n",
+        r#"<rect x="70" y="75" width="40" height="40" fill="white"/>"#,
+    )));
+}
+
+#[test]
+fn else_branch_is_drawn_in_a_parallel_lane() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                ..gate("X", &[1])
+            },
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), false)),
+                ..gate("Z", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+
+    // The lanes are below each other, so the circuit is twice as tall.
+    assert!(svg.starts_with(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="240" viewBox="0 0 200 240">"#
+    ));
+    assert!(svg.contains(concat!(
+        "<g stroke-dasharray=\"4 2\">\n",
+        "<title>if c0 == One</title>\n",
+        r#"<rect x="70" y="75" width="40" height="40" fill="white"/>"#,
+        "\n",
+        r#"<text x="90" y="95" stroke="none">X</text>"#,
+        "\n",
+        r#"<rect x="62" y="70" width="56" height="50" fill="none"/>"#,
+        "\n",
+        r#"<text x="64" y="76" font-size="10" text-anchor="start" stroke="none">c0 == One</text>"#,
+    )));
+    assert!(svg.contains(concat!(
+        r#"<line x1="60" y1="195" x2="120" y2="195"/>"#,
+        "\n",
+        "<g stroke-dasharray=\"4 2\">\n",
+        "<title>if c0 == Zero</title>\n",
+        r#"<rect x="70" y="175" width="40" height="40" fill="white"/>"#,
+    )));
+    assert!(svg.contains(">c0 == Zero</text>"));
+}
+
+#[test]
+fn conditioned_operation_is_drawn_as_its_children() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                children: vec![gate("H", &[1]), gate("X", &[1])],
+                ..gate("Prepare", &[1])
+            },
+            gate("Y", &[1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+
+    assert!(!svg.contains("Prepare"));
+    assert!(svg.contains(r#"<text x="90" y="95" stroke="none">H</text>"#));
+    assert!(svg.contains(r#"<text x="150" y="95" stroke="none">X</text>"#));
+    assert!(svg.contains(r#"<text x="210" y="95" stroke="none">Y</text>"#));
+    assert!(svg.contains(r#"<rect x="62" y="70" width="116" height="50" fill="none"/>"#));
+}
+
+#[test]
+fn results_in_registers_are_labelled() {
+    let circuit = Circuit {
+        operations: vec![measure(0, 0), measure(1, 0)],
+        qubits: qubits(2),
+        registers: vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(1, 0)],
+        }],
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+    assert!(svg.contains(r#"<text x="90" y="121" font-size="10" stroke="none">c</text>"#));
+    assert_eq!(svg.matches(r#"font-size="10""#).count(), 1);
+}
+
+#[test]
+fn barrier_is_a_labelled_dashed_line() {
+    let circuit = Circuit {
+        operations: vec![Operation {
+            display_args: Some("Oracle & more".to_string()),
+            ..gate(BARRIER, &[0, 1])
+        }],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let svg = render_svg(&circuit).expect("circuit should render");
+    assert!(svg.contains(r#"<line x1="90" y1="30" x2="90" y2="120" stroke-dasharray="4 2"/>"#));
+    assert!(svg
+        .contains(r#"<text x="90" y="25" font-size="10" stroke="none">Oracle &amp; more</text>"#));
+    assert!(!svg.contains("<rect x="));
+}
+
+#[test]
+fn wires_are_named_after_qubit_labels() {
+    let mut circuit = Circuit {
+        operations: vec![gate("CNOT", &[0, 1])],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    circuit.qubits[1].label = Some("ctls[1]".to_string());
+    let svg = render_svg(&circuit).expect("circuit should render");
+    assert!(svg.contains(r#"<text x="30" y="45" stroke="none">q0</text>"#));
+    assert!(svg.contains(r#"<text x="30" y="95" stroke="none">ctls[1]</text>"#));
+}
@@ -0,0 +1,422 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, ClassicalRegister, Error, Operation, Qubit, Register, BARRIER,
+};
+use crate::compile;
+use expect_test::expect;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn check_compiles(source: &str) {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("circuit.qs".into(), source.into())], None);
+    let (_, errors) = compile::compile(&store, &[std], sources, PackageType::Lib, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn bell_pair_is_synthesized() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("CNOT", &[0, 1]),
+            measure(0, 0),
+            measure(1, 0),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let qsharp = circuit
+        .to_qsharp("Imported", "Bell")
+        .expect("circuit should synthesize");
+    expect![[r#"
+        namespace Imported {
+            operation Bell() : Result[] {
+                use qs = Qubit[2];
+                H(qs[0]);
+                CNOT(qs[0], qs[1]);
+                let c0 = M(qs[0]);
+                let c1 = M(qs[1]);
+                ResetAll(qs);
+                [c0, c1]
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    check_compiles(&qsharp);
+}
+
+#[test]
+fn functors_and_arguments_are_synthesized() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                is_adjoint: true,
+                ..gate("T", &[1])
+            },
+            Operation {
+                display_args: Some("1.5".to_string()),
+                is_controlled: true,
+                controls: vec![Register::quantum(0)],
+                ..gate("Rx", &[1])
+            },
+            Operation {
+                display_args: Some("0.25".to_string()),
+                ..gate("Rzz", &[0, 1])
+            },
+            gate("CCX", &[0, 1, 2]),
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    let qsharp = circuit
+        .to_qsharp("Imported", "Rotations")
+        .expect("circuit should synthesize");
+    expect![[r#"
+        namespace Imported {
+            operation Rotations() : Unit {
+                use qs = Qubit[3];
+                Adjoint T(qs[1]);
+                Controlled Rx([qs[0]], (1.5, qs[1]));
+                Rzz(0.25, qs[0], qs[1]);
+                Controlled X([qs[0], qs[1]], qs[2]);
+                ResetAll(qs);
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    check_compiles(&qsharp);
+}
+
+#[test]
+fn unknown_gate_is_error() {
+    let circuit = Circuit {
+        operations: vec![gate("U3", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.to_qsharp("Imported", "Main"),
+        Err(Error::UnsupportedGate("U3".to_string()))
+    );
+}
+
+#[test]
+fn missing_rotation_angle_is_error() {
+    let circuit = Circuit {
+        operations: vec![gate("Ry", &[0])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.to_qsharp("Imported", "Main"),
+        Err(Error::GateShape("Ry".to_string(), 1, 1))
+    );
+}
+
+#[test]
+fn unknown_qubit_is_error() {
+    let circuit = Circuit {
+        operations: vec![gate("X", &[3])],
+        qubits: qubits(1),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.to_qsharp("Imported", "Main"),
+        Err(Error::UnknownQubit("X".to_string(), 3))
+    );
+}
+
+fn if_one(q_id: usize, c_id: usize) -> Option<ClassicalExpr> {
+    Some(ClassicalExpr::Result(Register::classical(q_id, c_id), true))
+}
+
+#[test]
+fn conditioned_operations_are_synthesized() {
+    let circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            measure(0, 0),
+            Operation {
+                condition: if_one(0, 0),
+                ..gate("X", &[1])
+            },
+            Operation {
+                condition: Some(ClassicalExpr::Not(Box::new(ClassicalExpr::Result(
+                    Register::classical(0, 0),
+                    false,
+                )))),
+                ..measure(1, 0)
+            },
+            Operation {
+                condition: Some(ClassicalExpr::And(
+                    Box::new(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                    Box::new(ClassicalExpr::Result(Register::classical(1, 0), false)),
+                )),
+                ..gate("CNOT", &[0, 1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let qsharp = circuit
+        .to_qsharp("Imported", "Adaptive")
+        .expect("circuit should synthesize");
+    expect![[r#"
+        namespace Imported {
+            operation Adaptive() : Result[] {
+                use qs = Qubit[2];
+                H(qs[0]);
+                let c0 = M(qs[0]);
+                if c0 == One { X(qs[1]); }
+                let c1 = if not (c0 == Zero) { M(qs[1]) } else { Zero };
+                if (c0 == One and c1 == Zero) { CNOT(qs[0], qs[1]); }
+                ResetAll(qs);
+                [c0, c1]
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    check_compiles(&qsharp);
+}
+
+#[test]
+fn condition_on_unmeasured_result_is_an_error() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                condition: if_one(0, 0),
+                ..gate("X", &[1])
+            },
+            measure(0, 0),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    assert_eq!(
+        circuit.to_qsharp("Imported", "Adaptive"),
+        Err(Error::UnknownResult("X".to_string(), 0))
+    );
+}
+
+#[test]
+fn operations_with_children_are_expanded() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: if_one(0, 0),
+                children: vec![
+                    gate("H", &[1]),
+                    Operation {
+                        condition: if_one(0, 0),
+                        ..gate("X", &[1])
+                    },
+                ],
+                ..gate("Prepare", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let qsharp = circuit
+        .to_qsharp("Imported", "Grouped")
+        .expect("circuit should synthesize");
+    expect![[r#"
+        namespace Imported {
+            operation Grouped() : Result[] {
+                use qs = Qubit[2];
+                let c0 = M(qs[0]);
+                if c0 == One { H(qs[1]); }
+                if (c0 == One and c0 == One) { X(qs[1]); }
+                ResetAll(qs);
+                [c0]
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    check_compiles(&qsharp);
+}
+
+#[test]
+fn branches_round_trip_through_json() {
+    let circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: if_one(0, 0),
+                children: vec![gate("X", &[1])],
+                ..gate("Correct", &[1])
+            },
+            Operation {
+                condition: Some(ClassicalExpr::Not(Box::new(ClassicalExpr::Result(
+                    Register::classical(0, 0),
+                    true,
+                )))),
+                ..gate("Z", &[1])
+            },
+        ],
+        qubits: vec![
+            Qubit {
+                id: 0,
+                num_children: 1,
+                label: None,
+            },
+            Qubit {
+                id: 1,
+                num_children: 0,
+                label: None,
+            },
+        ],
+        ..Circuit::default()
+    };
+    let json = circuit.to_json();
+    expect![[r#"{"version":1,"operations":[{"gate":"Measure","isControlled":false,"isAdjoint":false,"isMeasurement":true,"controls":[{"qId":0}],"targets":[{"qId":0,"cId":0}]},{"gate":"Correct","isControlled":false,"isAdjoint":false,"isMeasurement":false,"controls":[],"targets":[{"qId":1}],"condition":{"result":[{"qId":0,"cId":0},true]},"children":[{"gate":"X","isControlled":false,"isAdjoint":false,"isMeasurement":false,"controls":[],"targets":[{"qId":1}]}]},{"gate":"Z","isControlled":false,"isAdjoint":false,"isMeasurement":false,"controls":[],"targets":[{"qId":1}],"condition":{"not":{"result":[{"qId":0,"cId":0},true]}}}],"qubits":[{"id":0,"numChildren":1},{"id":1,"numChildren":0}]}"#]]
+    .assert_eq(&json);
+    assert_eq!(Circuit::from_json(&json), Ok(circuit));
+}
+
+#[test]
+fn json_fields_are_optional() {
+    let circuit =
+        Circuit::from_json(r#"{"operations":[{"gate":"H","targets":[{"qId":0}]}],"qubits":[{"id":0,"numChildren":0}]}"#)
+            .expect("circuit should parse");
+    assert_eq!(circuit.operations, vec![gate("H", &[0])]);
+    assert!(matches!(
+        Circuit::from_json(r#"{"operations":[{"gate":1}]}"#),
+        Err(Error::InvalidJson(_))
+    ));
+}
+
+#[test]
+fn json_version_is_checked() {
+    let circuit = Circuit::from_json(r#"{"version":1,"operations":[],"qubits":[]}"#)
+        .expect("circuit should parse");
+    assert_eq!(circuit, Circuit::default());
+    assert_eq!(
+        Circuit::from_json(r#"{"version":2,"operations":[],"qubits":[]}"#),
+        Err(Error::UnsupportedVersion(2))
+    );
+}
+
+#[test]
+fn json_schema_describes_serialized_fields() {
+    let schema: serde_json::Value =
+        serde_json::from_str(Circuit::JSON_SCHEMA).expect("schema should be valid JSON");
+    let properties = |path: &str| {
+        schema
+            .pointer(path)
+            .and_then(serde_json::Value::as_object)
+            .unwrap_or_else(|| panic!("schema should have {path}"))
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let fields = |value: &serde_json::Value| {
+        value
+            .as_object()
+            .expect("value should be an object")
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let circuit = Circuit {
+        operations: vec![Operation {
+            display_args: Some("0.5".to_string()),
+            condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+            children: vec![gate("X", &[0])],
+            duration: Some(1.0),
+            ..measure(0, 0)
+        }],
+        qubits: vec![Qubit {
+            id: 0,
+            num_children: 1,
+            label: Some("q".to_string()),
+        }],
+        registers: vec![ClassicalRegister {
+            name: "c".to_string(),
+            results: vec![Register::classical(0, 0)],
+        }],
+    };
+    let json: serde_json::Value =
+        serde_json::from_str(&circuit.to_json()).expect("circuit should serialize");
+    let mut serialized = fields(&json);
+    let mut described = properties("/properties");
+    serialized.sort();
+    described.sort();
+    assert_eq!(serialized, described);
+    for (value, path) in [
+        (&json["operations"][0], "/$defs/operation/properties"),
+        (&json["qubits"][0], "/$defs/qubit/properties"),
+        (
+            &json["operations"][0]["targets"][0],
+            "/$defs/register/properties",
+        ),
+        (&json["registers"][0], "/$defs/classicalRegister/properties"),
+    ] {
+        let mut serialized = fields(value);
+        let mut described = properties(path);
+        serialized.sort();
+        described.sort();
+        assert_eq!(serialized, described, "{path}");
+    }
+}
+
+#[test]
+fn results_are_labelled_by_their_registers() {
+    let circuit = Circuit {
+        operations: vec![measure(0, 0), measure(1, 0), measure(1, 1)],
+        qubits: qubits(2),
+        registers: vec![
+            ClassicalRegister {
+                name: "c0".to_string(),
+                results: vec![Register::classical(0, 0)],
+            },
+            ClassicalRegister {
+                name: "c1".to_string(),
+                results: vec![Register::classical(1, 1), Register::classical(1, 0)],
+            },
+        ],
+    };
+    let label = |q_id, c_id| circuit.result_label(Register::classical(q_id, c_id));
+    assert_eq!(label(0, 0).as_deref(), Some("c0"));
+    assert_eq!(label(1, 0).as_deref(), Some("c1[1]"));
+    assert_eq!(label(1, 1).as_deref(), Some("c1[0]"));
+    assert_eq!(label(0, 1), None);
+}
+
+#[test]
+fn barriers_are_synthesized_with_their_labels() {
+    let circuit = Circuit {
+        operations: vec![
+            Operation {
+                display_args: Some("Oracle".to_string()),
+                ..gate(BARRIER, &[0, 1])
+            },
+            gate(BARRIER, &[1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    let qsharp = circuit
+        .to_qsharp("Imported", "Phases")
+        .expect("circuit should synthesize");
+    expect![[r#"
+        namespace Imported {
+            operation Phases() : Unit {
+                use qs = Qubit[2];
+                Microsoft.Quantum.Diagnostics.Barrier([qs[0], qs[1]], "Oracle");
+                Microsoft.Quantum.Diagnostics.Barrier([qs[1]], "");
+                ResetAll(qs);
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    check_compiles(&qsharp);
+}
@@ -0,0 +1,151 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Durations of the operations of a circuit, for drawing schedules and finding the latency of a
+//! program on hardware. Durations come from a [`GateDurationModel`], which is asked about each
+//! gate, and operations with children take as long as the schedule of their children. Operations
+//! are scheduled as soon as the registers they use are free, so the longest chain of operations
+//! that wait on each other is the critical path of the circuit.
+
+#[cfg(test)]
+mod tests;
+
+use super::{expand, Circuit, ClassicalExpr, Operation, Register, BARRIER};
+use rustc_hash::FxHashMap;
+
+/// How long gates take. Durations can be in any unit, such as nanoseconds or clock cycles, as long
+/// as the model uses the same one for every gate.
+pub trait GateDurationModel {
+    /// The duration of the gate, or `None` if the model doesn't know it.
+    fn duration(&self, op: &Operation) -> Option<f64>;
+}
+
+/// A table of the durations of gates by name, such as `H` or `Measure`, with the duration of
+/// gates that aren't in it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GateDurationTable {
+    pub durations: FxHashMap<String, f64>,
+    pub default: Option<f64>,
+}
+
+impl GateDurationModel for GateDurationTable {
+    fn duration(&self, op: &Operation) -> Option<f64> {
+        self.durations.get(&op.gate).copied().or(self.default)
+    }
+}
+
+/// The longest chain of operations of a circuit that wait on each other.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CriticalPath {
+    /// The time from the start of the first operation to the end of the last, which is the
+    /// duration of the whole circuit.
+    pub duration: f64,
+    /// The positions of the operations on the path in the circuit, in order.
+    pub operations: Vec<usize>,
+}
+
+impl Circuit {
+    /// Sets the duration of each operation from the model. Barriers take no time, operations with
+    /// children take as long as their children take when scheduled on their own, and gates that
+    /// the model doesn't know have no duration.
+    pub fn set_durations(&mut self, model: &impl GateDurationModel) {
+        set_durations(&mut self.operations, model);
+    }
+
+    /// The critical path of the circuit, with each operation started as soon as the qubits and
+    /// results it uses are done with the operations before it. Operations without a duration take
+    /// no time.
+    #[must_use]
+    pub fn critical_path(&self) -> CriticalPath {
+        critical_path(&self.operations)
+    }
+}
+
+fn set_durations(operations: &mut [Operation], model: &impl GateDurationModel) {
+    for op in operations {
+        op.duration = if op.gate == BARRIER {
+            Some(0.0)
+        } else if op.children.is_empty() {
+            model.duration(op)
+        } else {
+            set_durations(&mut op.children, model);
+            Some(critical_path(&op.children).duration)
+        };
+    }
+}
+
+fn critical_path(operations: &[Operation]) -> CriticalPath {
+    // The time each register is free from, and the operation that used it last.
+    let mut free = FxHashMap::<(usize, Option<usize>), (f64, usize)>::default();
+    // The end of each operation, and the operation it waited on.
+    let mut ends = Vec::<(f64, Option<usize>)>::with_capacity(operations.len());
+    for (index, op) in operations.iter().enumerate() {
+        let registers = registers(op);
+        let waits_on = registers
+            .iter()
+            .filter_map(|register| free.get(register))
+            .copied()
+            .fold(
+                None,
+                |latest: Option<(f64, usize)>, (time, user)| match latest {
+                    Some((latest_time, _)) if latest_time >= time => latest,
+                    _ => Some((time, user)),
+                },
+            );
+        let start = waits_on.map_or(0.0, |(time, _)| time);
+        let end = start + op.duration.unwrap_or_default();
+        for register in registers {
+            free.insert(register, (end, index));
+        }
+        ends.push((end, waits_on.map(|(_, op)| op)));
+    }
+
+    let Some(last) = (0..ends.len()).reduce(|last, index| {
+        if ends[index].0 > ends[last].0 {
+            index
+        } else {
+            last
+        }
+    }) else {
+        return CriticalPath::default();
+    };
+    let mut path = vec![last];
+    while let Some(previous) = ends[path[path.len() - 1]].1 {
+        path.push(previous);
+    }
+    path.reverse();
+    CriticalPath {
+        duration: ends[last].0,
+        operations: path,
+    }
+}
+
+/// The qubits and results that the operation and its children use, including the results it is
+/// conditioned on.
+fn registers(op: &Operation) -> Vec<(usize, Option<usize>)> {
+    let mut registers = Vec::new();
+    let mut add = |register: &Register| {
+        let register = (register.q_id, register.c_id);
+        if !registers.contains(&register) {
+            registers.push(register);
+        }
+    };
+    for gate in expand(std::slice::from_ref(op)) {
+        gate.controls.iter().chain(&gate.targets).for_each(&mut add);
+        if let Some(condition) = &gate.condition {
+            condition_registers(condition, &mut add);
+        }
+    }
+    registers
+}
+
+fn condition_registers(expr: &ClassicalExpr, add: &mut impl FnMut(&Register)) {
+    match expr {
+        ClassicalExpr::Result(register, _) => add(register),
+        ClassicalExpr::Not(inner) => condition_registers(inner, add),
+        ClassicalExpr::And(lhs, rhs) | ClassicalExpr::Or(lhs, rhs) => {
+            condition_registers(lhs, add);
+            condition_registers(rhs, add);
+        }
+    }
+}
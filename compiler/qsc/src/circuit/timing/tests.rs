@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{CriticalPath, GateDurationModel, GateDurationTable};
+use crate::circuit::{
+    fixtures::{gate, measure, qubits},
+    Circuit, ClassicalExpr, Operation, Register, BARRIER,
+};
+
+fn table() -> GateDurationTable {
+    GateDurationTable {
+        durations: [("H", 20.0), ("CNOT", 100.0), ("Measure", 500.0)]
+            .into_iter()
+            .map(|(gate, duration)| (gate.to_string(), duration))
+            .collect(),
+        default: Some(10.0),
+    }
+}
+
+#[test]
+fn durations_come_from_the_table() {
+    let mut circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("CNOT", &[0, 1]),
+            gate("T", &[1]),
+            gate(BARRIER, &[0, 1]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    circuit.set_durations(&table());
+    let durations = circuit
+        .operations
+        .iter()
+        .map(|op| op.duration)
+        .collect::<Vec<_>>();
+    assert_eq!(durations, [Some(20.0), Some(100.0), Some(10.0), Some(0.0)]);
+
+    circuit.set_durations(&GateDurationTable::default());
+    assert_eq!(circuit.operations[0].duration, None);
+}
+
+#[test]
+fn critical_path_follows_the_longest_chain() {
+    let mut circuit = Circuit {
+        operations: vec![
+            gate("H", &[0]),
+            gate("X", &[1]),
+            gate("X", &[1]),
+            gate("X", &[1]),
+            gate("CNOT", &[0, 1]),
+            gate("H", &[2]),
+            measure(1, 0),
+        ],
+        qubits: qubits(3),
+        ..Circuit::default()
+    };
+    circuit.set_durations(&table());
+    assert_eq!(
+        circuit.critical_path(),
+        CriticalPath {
+            duration: 630.0,
+            operations: vec![1, 2, 3, 4, 6],
+        }
+    );
+}
+
+#[test]
+fn conditioned_operations_wait_for_their_results() {
+    let mut circuit = Circuit {
+        operations: vec![
+            measure(0, 0),
+            Operation {
+                condition: Some(ClassicalExpr::Result(Register::classical(0, 0), true)),
+                ..gate("X", &[1])
+            },
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    circuit.set_durations(&table());
+    assert_eq!(
+        circuit.critical_path(),
+        CriticalPath {
+            duration: 510.0,
+            operations: vec![0, 1],
+        }
+    );
+}
+
+#[test]
+fn operations_with_children_take_as_long_as_their_schedule() {
+    struct Uniform;
+    impl GateDurationModel for Uniform {
+        fn duration(&self, _op: &Operation) -> Option<f64> {
+            Some(1.0)
+        }
+    }
+
+    let mut circuit = Circuit {
+        operations: vec![
+            Operation {
+                children: vec![gate("H", &[0]), gate("H", &[1]), gate("CNOT", &[0, 1])],
+                ..gate("Prepare", &[0, 1])
+            },
+            gate("X", &[0]),
+        ],
+        qubits: qubits(2),
+        ..Circuit::default()
+    };
+    circuit.set_durations(&Uniform);
+    assert_eq!(circuit.operations[0].duration, Some(2.0));
+    assert_eq!(
+        circuit.critical_path(),
+        CriticalPath {
+            duration: 3.0,
+            operations: vec![0, 1],
+        }
+    );
+}
+
+#[test]
+fn empty_circuit_has_an_empty_critical_path() {
+    assert_eq!(Circuit::default().critical_path(), CriticalPath::default());
+}
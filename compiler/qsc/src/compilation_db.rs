@@ -0,0 +1,218 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A compilation database describes how each package in a build was compiled, so that external
+//! build systems can reproduce or orchestrate the compilation. It is similar in spirit to the
+//! `compile_commands.json` files used by C and C++ tooling.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{graph::serialize_package_id, target::Profile};
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::{self, Attr, ExprKind, ItemKind, Lit, PackageId, PatKind, StringComponent};
+use qsc_passes::PackageType;
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// How a single package was compiled.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PackageEntry {
+    #[serde(serialize_with = "serialize_package_id")]
+    pub id: PackageId,
+    pub name: Arc<str>,
+    #[serde(rename = "type", serialize_with = "serialize_package_type")]
+    pub package_type: PackageType,
+    /// The names of the package's source files, in compilation order.
+    pub sources: Vec<Arc<str>>,
+    /// The packages that this package was compiled against, not including the core library.
+    #[serde(serialize_with = "serialize_package_ids")]
+    pub dependencies: Vec<PackageId>,
+    /// The entry expression, if one was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<Arc<str>>,
+    /// The parameters of the entry point callable, so that tools submitting the program can
+    /// offer their default values.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entry_parameters: Vec<EntryParameter>,
+}
+
+/// A parameter of an entry point callable.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EntryParameter {
+    pub name: Arc<str>,
+    /// The type of the parameter, written as in Q#.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The default value of the parameter from its `@Default` attribute, evaluated at compile time
+    /// and written as a Q# literal.
+    pub default: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompilationDatabase {
+    /// The directory that relative source paths are resolved against.
+    pub directory: PathBuf,
+    /// The runtime capabilities that the packages were compiled for. These determine which
+    /// items are included by `@Config` attributes.
+    pub capabilities: RuntimeCapabilityFlags,
+    pub packages: Vec<PackageEntry>,
+}
+
+impl CompilationDatabase {
+    #[must_use]
+    pub fn new(directory: impl AsRef<Path>, capabilities: RuntimeCapabilityFlags) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            capabilities,
+            packages: Vec::new(),
+        }
+    }
+
+    /// Records a package compiled from the given sources.
+    pub fn add_package(
+        &mut self,
+        id: PackageId,
+        name: &str,
+        package_type: PackageType,
+        sources: &SourceMap,
+        dependencies: &[PackageId],
+    ) {
+        self.packages.push(PackageEntry {
+            id,
+            name: name.into(),
+            package_type,
+            sources: sources.iter().map(|s| s.name.as_ref().into()).collect(),
+            dependencies: dependencies.to_vec(),
+            entry: sources.entry().map(|s| s.contents.as_ref().into()),
+            entry_parameters: Vec::new(),
+        });
+    }
+
+    /// Records the parameters of the entry point of a package that was already added.
+    pub fn set_entry_parameters(&mut self, id: PackageId, parameters: Vec<EntryParameter>) {
+        if let Some(package) = self.packages.iter_mut().find(|package| package.id == id) {
+            package.entry_parameters = parameters;
+        }
+    }
+
+    /// The target profile that the capabilities correspond to, if any.
+    #[must_use]
+    pub fn target_profile(&self) -> Option<Profile> {
+        [Profile::Unrestricted, Profile::Base]
+            .into_iter()
+            .find(|&profile| RuntimeCapabilityFlags::from(profile) == self.capabilities)
+    }
+
+    /// Renders the database as a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("compilation database should serialize")
+    }
+}
+
+impl Serialize for CompilationDatabase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let defines = self
+            .capabilities
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        let mut database = serializer.serialize_struct("CompilationDatabase", 4)?;
+        database.serialize_field("directory", &self.directory.to_string_lossy())?;
+        database.serialize_field("target_profile", &self.target_profile().map(|p| p.to_str()))?;
+        database.serialize_field("defines", &defines)?;
+        database.serialize_field("packages", &self.packages)?;
+        database.end()
+    }
+}
+
+// Serde passes the field by reference.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_package_type<S: Serializer>(
+    package_type: &PackageType,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(match package_type {
+        PackageType::Exe => "exe",
+        PackageType::Lib => "lib",
+    })
+}
+
+fn serialize_package_ids<S: Serializer>(
+    ids: &[PackageId],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(ids.iter().map(|&id| usize::from(id)))
+}
+
+/// Finds the parameters of the `@EntryPoint()` callable of a package that have default values.
+#[must_use]
+pub fn entry_parameters(package: &hir::Package) -> Vec<EntryParameter> {
+    let Some((decl, attrs)) = package.items.values().find_map(|item| match &item.kind {
+        ItemKind::Callable(decl) if item.attrs.contains(&Attr::EntryPoint) => {
+            Some((decl, &item.attrs))
+        }
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+
+    let params = if let PatKind::Tuple(params) = &decl.input.kind {
+        params.iter().collect()
+    } else {
+        vec![&decl.input]
+    };
+    params
+        .into_iter()
+        .filter_map(|param| {
+            let PatKind::Bind(name) = &param.kind else {
+                return None;
+            };
+            attrs.iter().find_map(|attr| match attr {
+                Attr::Default(default_name, value) if *default_name == name.name => {
+                    Some(EntryParameter {
+                        name: Arc::clone(&name.name),
+                        ty: param.ty.display(),
+                        default: constant_to_qsharp(value),
+                    })
+                }
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn constant_to_qsharp(expr: &hir::Expr) -> String {
+    let join = |items: &[hir::Expr]| {
+        items
+            .iter()
+            .map(constant_to_qsharp)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match &expr.kind {
+        ExprKind::Lit(Lit::BigInt(value)) => format!("{value}L"),
+        ExprKind::Lit(Lit::Bool(value)) => value.to_string(),
+        ExprKind::Lit(Lit::Double(value)) => format!("{value:?}"),
+        ExprKind::Lit(Lit::Int(value)) => value.to_string(),
+        ExprKind::Lit(Lit::Pauli(pauli)) => format!("Pauli{pauli:?}"),
+        ExprKind::Lit(Lit::Result(result)) => format!("{result:?}"),
+        ExprKind::String(components) => {
+            let mut string = String::new();
+            for component in components {
+                if let StringComponent::Lit(lit) = component {
+                    string.push_str(lit);
+                }
+            }
+            format!("{string:?}")
+        }
+        ExprKind::Array(items) => format!("[{}]", join(items)),
+        ExprKind::Tuple(items) if items.len() == 1 => format!("({},)", join(items)),
+        ExprKind::Tuple(items) => format!("({})", join(items)),
+        _ => String::new(),
+    }
+}
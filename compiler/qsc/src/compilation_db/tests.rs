@@ -0,0 +1,88 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{entry_parameters, CompilationDatabase};
+use crate::{compile, target::Profile};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+
+#[test]
+fn packages_are_listed_with_sources_and_dependencies() {
+    let mut db = CompilationDatabase::new("/work", RuntimeCapabilityFlags::empty());
+    let std = PackageId::CORE.successor();
+    let user = std.successor();
+    db.add_package(
+        std,
+        "std",
+        PackageType::Lib,
+        &SourceMap::new([("arrays.qs".into(), "".into())], None),
+        &[],
+    );
+    db.add_package(
+        user,
+        "main",
+        PackageType::Exe,
+        &SourceMap::new(
+            [
+                ("src/Main.qs".into(), "".into()),
+                ("src/Say \"Hi\".qs".into(), "".into()),
+            ],
+            Some("Main.Run()".into()),
+        ),
+        &[std],
+    );
+
+    expect![[r#"{"directory":"/work","target_profile":"Base","defines":[],"packages":[{"id":1,"name":"std","type":"lib","sources":["arrays.qs"],"dependencies":[]},{"id":2,"name":"main","type":"exe","sources":["src/Main.qs","src/Say \"Hi\".qs"],"dependencies":[1],"entry":"Main.Run()"}]}"#]]
+    .assert_eq(&db.to_json());
+}
+
+#[test]
+fn capabilities_are_listed_as_defines() {
+    let db = CompilationDatabase::new(
+        "/work",
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::IntegerComputations,
+    );
+    assert_eq!(db.target_profile(), None);
+    expect![[r#"{"directory":"/work","target_profile":null,"defines":["ForwardBranching","IntegerComputations"],"packages":[]}"#]]
+    .assert_eq(&db.to_json());
+}
+
+#[test]
+fn all_capabilities_are_unrestricted_profile() {
+    let db = CompilationDatabase::new("/work", RuntimeCapabilityFlags::all());
+    assert_eq!(db.target_profile(), Some(Profile::Unrestricted));
+}
+
+#[test]
+fn entry_parameters_are_listed_with_defaults() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            @Default(qubits, 2 + 3)
+            @Default(angle, 0.5)
+            @Default(bases, [PauliX, PauliZ])
+            operation Main(qubits : Int, angle : Double, bases : Pauli[]) : Unit {}
+        }
+    "};
+    let mut store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    let unit = store.get(package).expect("package should be in store");
+
+    let mut db = CompilationDatabase::new("/work", RuntimeCapabilityFlags::empty());
+    db.add_package(package, "main", PackageType::Exe, &unit.sources, &[]);
+    db.set_entry_parameters(package, entry_parameters(&unit.package));
+    expect![[r#"{"directory":"/work","target_profile":"Base","defines":[],"packages":[{"id":1,"name":"main","type":"exe","sources":["test.qs"],"dependencies":[],"entry_parameters":[{"name":"qubits","type":"Int","default":"5"},{"name":"angle","type":"Double","default":"0.5"},{"name":"bases","type":"Pauli[]","default":"[PauliX, PauliZ]"}]}]}"#]]
+    .assert_eq(&db.to_json());
+}
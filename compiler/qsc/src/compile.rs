@@ -1,15 +1,29 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use crate::index_overflow;
 use miette::{Diagnostic, Report};
+use qsc_data_structures::message::MessageParams;
 use qsc_frontend::{
     compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap},
     error::WithSource,
 };
-use qsc_hir::hir::PackageId;
-use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use qsc_hir::{global, hir::PackageId};
+use qsc_passes::{
+    qubit_sized_callables, result_literal_callables, run_core_passes, PackageType, PassContext,
+};
 use thiserror::Error;
 
+pub use qsc_frontend::compile::{
+    generate::{Request, SourceGenerator},
+    lower, parse, resolve, typeck, CheckedPackage, Dependencies, ParsedPackage, ResolvedPackage,
+};
+pub use qsc_passes::{
+    capability_delta, check_runtime_capabilities, required_capabilities, suggest_fix,
+    ArithmeticBudget, CapabilityDelta, QubitInitialization, Substitution, SubstitutionTable,
+    Suggestion,
+};
+
 pub type Error = WithSource<ErrorKind>;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -18,6 +32,17 @@ pub type Error = WithSource<ErrorKind>;
 pub enum ErrorKind {
     Frontend(#[from] qsc_frontend::compile::Error),
     Pass(#[from] qsc_passes::Error),
+    Lint(#[from] index_overflow::Lint),
+}
+
+impl MessageParams for ErrorKind {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            ErrorKind::Frontend(error) => error.message_params(),
+            ErrorKind::Pass(error) => error.message_params(),
+            ErrorKind::Lint(error) => error.message_params(),
+        }
+    }
 }
 
 #[must_use]
@@ -28,14 +53,153 @@ pub fn compile(
     package_type: PackageType,
     capabilities: RuntimeCapabilityFlags,
 ) -> (CompileUnit, Vec<Error>) {
-    let mut unit = qsc_frontend::compile::compile(store, dependencies, sources, capabilities);
+    compile_with_qubit_initialization(
+        store,
+        dependencies,
+        sources,
+        package_type,
+        capabilities,
+        QubitInitialization::Zero,
+    )
+}
+
+/// Compiles the sources like [`compile`] for a target that leaves freshly allocated qubits in the
+/// given state.
+#[must_use]
+pub fn compile_with_qubit_initialization(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+) -> (CompileUnit, Vec<Error>) {
+    compile_with_dependencies(
+        store,
+        &Dependencies::new(store, dependencies),
+        sources,
+        package_type,
+        capabilities,
+        qubit_initialization,
+    )
+}
+
+/// Compiles the sources like [`compile_with_qubit_initialization`] against dependencies whose
+/// global tables are already built, which saves rebuilding them when compiling many packages
+/// against the same dependencies.
+#[must_use]
+pub fn compile_with_dependencies(
+    store: &PackageStore,
+    dependencies: &Dependencies,
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+) -> (CompileUnit, Vec<Error>) {
+    let unit =
+        qsc_frontend::compile::compile_with_dependencies(dependencies, sources, capabilities);
+    run_passes(
+        store,
+        unit,
+        package_type,
+        PassContext::new(capabilities).with_qubit_initialization(qubit_initialization),
+    )
+}
+
+/// Compiles the sources like [`compile_with_qubit_initialization`] for a target with at most
+/// `max_qubits` qubits, reporting the calls whose precision is known at compile time and needs
+/// more of them.
+#[must_use]
+pub fn compile_with_max_qubits(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+    max_qubits: usize,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile(store, dependencies, sources, capabilities);
+    let context = PassContext::new(capabilities)
+        .with_qubit_initialization(qubit_initialization)
+        .with_max_qubits(max_qubits)
+        .with_qubit_sized_callables(qubit_sized_callables(store));
+    run_passes(store, unit, package_type, context)
+}
+
+/// Compiles the sources like [`compile`], calling the substitutes that the table gives for the
+/// target in place of the callables they replace.
+#[must_use]
+pub fn compile_with_substitutions(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    substitutions: &SubstitutionTable,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile(store, dependencies, sources, capabilities);
+    let globals: global::Table = std::iter::once(PackageId::CORE)
+        .chain(dependencies.iter().copied())
+        .filter_map(|id| store.get(id).map(|dependency| (id, dependency)))
+        .flat_map(|(id, dependency)| global::iter_package(Some(id), &dependency.package))
+        .chain(global::iter_package(None, &unit.package))
+        .collect();
+    let (substitutions, substitution_errors) = substitutions.resolve(capabilities, &globals);
+
+    let (unit, mut errors) = run_passes(
+        store,
+        unit,
+        package_type,
+        PassContext::new(capabilities).with_substitutions(substitutions),
+    );
+    for error in substitution_errors {
+        let error = qsc_passes::Error::Substitution(error);
+        errors.push(WithSource::from_map(&unit.sources, error.into()));
+    }
+    (unit, errors)
+}
+
+/// Compiles the sources like [`compile`], first adding the sources that the generators produce for
+/// the items with their attributes.
+#[must_use]
+pub fn compile_with_generators(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    generators: &[&dyn SourceGenerator],
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile_with_generators(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        generators,
+    );
+    run_passes(store, unit, package_type, PassContext::new(capabilities))
+}
+
+fn run_passes(
+    store: &PackageStore,
+    mut unit: CompileUnit,
+    package_type: PackageType,
+    context: PassContext,
+) -> (CompileUnit, Vec<Error>) {
+    let mut context = context.with_result_literal_callables(result_literal_callables(store));
     let mut errors = Vec::new();
     for error in unit.errors.drain(..) {
         errors.push(WithSource::from_map(&unit.sources, error.into()));
     }
 
     if errors.is_empty() {
-        for error in run_default_passes(store.core(), &mut unit, package_type, capabilities) {
+        for error in context.run_default_passes(
+            &mut unit.package,
+            &mut unit.assigner,
+            store.core(),
+            package_type,
+        ) {
             errors.push(WithSource::from_map(&unit.sources, error.into()));
         }
     }
@@ -43,6 +207,19 @@ pub fn compile(
     (unit, errors)
 }
 
+/// Returns the warnings of the compile unit, which don't stop it from compiling: uses of deprecated
+/// aliases and the lints of its code, such as unchecked arithmetic in qubit indices.
+#[must_use]
+pub fn warnings(unit: &CompileUnit) -> Vec<Error> {
+    let lints = index_overflow::unchecked_index_arithmetic(&unit.package);
+    unit.warnings
+        .iter()
+        .map(|warning| ErrorKind::from(warning.clone()))
+        .chain(lints.into_iter().map(ErrorKind::from))
+        .map(|warning| WithSource::from_map(&unit.sources, warning))
+        .collect()
+}
+
 /// Compiles the core library.
 ///
 /// # Panics
@@ -71,8 +248,30 @@ pub fn core() -> CompileUnit {
 /// Panics if the standard library does not compile without errors.
 #[must_use]
 pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> CompileUnit {
+    std_with_qubit_initialization(store, capabilities, QubitInitialization::Zero)
+}
+
+/// Compiles the standard library for a target that leaves freshly allocated qubits in the given
+/// state.
+///
+/// # Panics
+///
+/// Panics if the standard library does not compile without errors.
+#[must_use]
+pub fn std_with_qubit_initialization(
+    store: &PackageStore,
+    capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+) -> CompileUnit {
     let mut unit = qsc_frontend::compile::std(store, capabilities);
-    let pass_errors = run_default_passes(store.core(), &mut unit, PackageType::Lib, capabilities);
+    let pass_errors = PassContext::new(capabilities)
+        .with_qubit_initialization(qubit_initialization)
+        .run_default_passes(
+            &mut unit.package,
+            &mut unit.assigner,
+            store.core(),
+            PackageType::Lib,
+        );
     if pass_errors.is_empty() {
         unit
     } else {
@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Reports test coverage in the lcov format, which coverage viewers and CI services read. Each
+//! source file of a package is one record with the lines of its statements, the two branches of
+//! each of its `if` expressions and each of its callable specializations as a function, with the
+//! number of times each ran.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_data_structures::{
+    line_column::{Encoding, Position},
+    span::Span,
+};
+use qsc_fir::fir::{CallableImpl, ExprKind, ItemKind, Package, PackageId};
+use qsc_frontend::compile::SourceMap;
+use std::{collections::BTreeMap, fmt::Write};
+
+pub use qsc_eval::coverage::Coverage;
+
+/// The coverage of one source file. Lines are one-based, as in lcov.
+#[derive(Default)]
+struct FileCoverage {
+    /// The line, name and number of calls of each function.
+    functions: Vec<(u32, String, u64)>,
+    /// The line and the times each branch was taken of each `if`.
+    branches: Vec<(u32, [u64; 2])>,
+    /// The times the statements that start on each line ran.
+    lines: BTreeMap<u32, u64>,
+}
+
+/// Formats the coverage of the package with the given ID as lcov tracefile records, one for each
+/// of its source files. Code without a location in the sources, such as generated code, is left
+/// out.
+#[must_use]
+pub fn lcov(coverage: &Coverage, id: PackageId, package: &Package, sources: &SourceMap) -> String {
+    let mut files = sources
+        .iter()
+        .map(|_| FileCoverage::default())
+        .collect::<Vec<_>>();
+
+    for item in package.items.values() {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+            continue;
+        };
+        let namespace = item
+            .parent
+            .and_then(|parent| match &package.items.get(parent)?.kind {
+                ItemKind::Namespace(namespace, _) => Some(&namespace.name),
+                _ => None,
+            });
+        let name = match namespace {
+            Some(namespace) => format!("{namespace}.{}", decl.name.name),
+            None => decl.name.name.to_string(),
+        };
+        let specs = [
+            ("", Some(&spec_impl.body)),
+            ("Adjoint ", spec_impl.adj.as_ref()),
+            ("Controlled ", spec_impl.ctl.as_ref()),
+            ("Controlled Adjoint ", spec_impl.ctl_adj.as_ref()),
+        ];
+        for (functor, spec) in specs {
+            let Some(spec) = spec else {
+                continue;
+            };
+            // Generated specializations have no span of their own.
+            let span = if spec.span == Span::default() {
+                decl.name.span
+            } else {
+                spec.span
+            };
+            if let Some((file, line)) = locate(sources, span) {
+                let hits = coverage.spec(id, spec.block);
+                files[file]
+                    .functions
+                    .push((line, format!("{functor}{name}"), hits));
+            }
+        }
+    }
+
+    for expr in package.exprs.values() {
+        if let ExprKind::If(_, then_expr, _) = &expr.kind {
+            if let Some((file, line)) = locate(sources, expr.span) {
+                let hits = coverage.branch(id, *then_expr);
+                files[file].branches.push((line, hits));
+            }
+        }
+    }
+
+    for stmt in package.stmts.values() {
+        if let Some((file, line)) = locate(sources, stmt.span) {
+            let hits = files[file].lines.entry(line).or_default();
+            *hits = (*hits).max(coverage.stmt(id, stmt.id));
+        }
+    }
+
+    let mut lcov = String::new();
+    for (source, mut file) in sources.iter().zip(files) {
+        file.functions.sort_by_key(|&(line, _, _)| line);
+        file.branches.sort_by_key(|&(line, _)| line);
+        let _ = writeln!(lcov, "SF:{}", source.name);
+        for (line, name, _) in &file.functions {
+            let _ = writeln!(lcov, "FN:{line},{name}");
+        }
+        for (_, name, hits) in &file.functions {
+            let _ = writeln!(lcov, "FNDA:{hits},{name}");
+        }
+        let _ = writeln!(lcov, "FNF:{}", file.functions.len());
+        let _ = writeln!(
+            lcov,
+            "FNH:{}",
+            file.functions
+                .iter()
+                .filter(|(_, _, hits)| *hits > 0)
+                .count()
+        );
+
+        let mut branches_hit = 0;
+        for (block, (line, hits)) in file.branches.iter().enumerate() {
+            let reached = hits.iter().any(|&hits| hits > 0);
+            for (branch, &taken) in hits.iter().enumerate() {
+                // A branch of an `if` that never ran is marked as not taken rather than zero.
+                if reached {
+                    let _ = writeln!(lcov, "BRDA:{line},{block},{branch},{taken}");
+                } else {
+                    let _ = writeln!(lcov, "BRDA:{line},{block},{branch},-");
+                }
+                branches_hit += usize::from(taken > 0);
+            }
+        }
+        let _ = writeln!(lcov, "BRF:{}", 2 * file.branches.len());
+        let _ = writeln!(lcov, "BRH:{branches_hit}");
+
+        for (line, hits) in &file.lines {
+            let _ = writeln!(lcov, "DA:{line},{hits}");
+        }
+        let _ = writeln!(lcov, "LF:{}", file.lines.len());
+        let _ = writeln!(
+            lcov,
+            "LH:{}",
+            file.lines.values().filter(|&&hits| hits > 0).count()
+        );
+        lcov.push_str("end_of_record\n");
+    }
+    lcov
+}
+
+/// The index of the source file that the span starts in and the one-based line it starts on.
+fn locate(sources: &SourceMap, span: Span) -> Option<(usize, u32)> {
+    if span == Span::default() {
+        return None;
+    }
+    let (file, source) = sources
+        .iter()
+        .enumerate()
+        .filter(|(_, source)| span.lo >= source.offset)
+        .last()?;
+    let position =
+        Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, span.lo - source.offset);
+    Some((file, position.line + 1))
+}
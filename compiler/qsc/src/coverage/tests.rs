@@ -0,0 +1,87 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::interpret::{GenericReceiver, Interpreter};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn interpreter() -> Interpreter {
+    let source = indoc! {"
+        namespace Test {
+            function Choose(flag : Bool) : Int {
+                if flag {
+                    return 1;
+                }
+                0
+            }
+            function Unused() : Int {
+                2
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created")
+}
+
+#[test]
+fn coverage_accumulates_across_runs() {
+    let mut interpreter = interpreter();
+    interpreter.set_coverage(true);
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    for expr in ["Test.Choose(true)", "Test.Choose(false)"] {
+        let result = interpreter
+            .run(&mut receiver, expr)
+            .expect("expression should compile");
+        assert!(result.is_ok(), "{result:?}");
+    }
+    expect![[r#"
+        SF:test.qs
+        FN:2,Test.Choose
+        FN:8,Test.Unused
+        FNDA:2,Test.Choose
+        FNDA:0,Test.Unused
+        FNF:2
+        FNH:1
+        BRDA:3,0,0,1
+        BRDA:3,0,1,1
+        BRF:2
+        BRH:2
+        DA:3,2
+        DA:4,1
+        DA:6,1
+        DA:9,0
+        LF:4
+        LH:3
+        end_of_record
+    "#]]
+    .assert_eq(
+        &interpreter
+            .coverage_lcov()
+            .expect("coverage should be enabled"),
+    );
+}
+
+#[test]
+fn branches_of_unreached_if_are_not_taken() {
+    let mut interpreter = interpreter();
+    interpreter.set_coverage(true);
+    let lcov = interpreter
+        .coverage_lcov()
+        .expect("coverage should be enabled");
+    assert!(lcov.contains("BRDA:3,0,0,-\nBRDA:3,0,1,-\nBRF:2\nBRH:0\n"));
+    assert!(lcov.contains("LH:0\n"));
+}
+
+#[test]
+fn coverage_is_off_by_default() {
+    assert!(interpreter().coverage_lcov().is_none());
+}
@@ -0,0 +1,258 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Audits simulations for nondeterminism. The entry expression is run twice with the same seed,
+//! and everything the runs observably did is compared: the messages and states they output, the
+//! calls, gates and measurements of their traces, and the values they returned. Two runs with the
+//! same seed should agree exactly, so any difference means that something other than the seed
+//! decided how the program ran, such as the iteration order of a hash map or the scheduling of
+//! threads. Each difference is reported with the call that the first run was in when it happened,
+//! which is where to start looking for the source.
+
+#[cfg(test)]
+mod tests;
+
+use crate::interpret::{Frame, StackFrame};
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::{
+    output::{self, fmt_complex, format_state_id, Receiver},
+    trace::TraceEvent,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+/// An output of a run that a repeated run with the same seed should output too.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Observation {
+    /// A message, with the channel it was sent to, if any.
+    Message(Option<String>, String),
+    /// A state dump, as a hash of its amplitudes at the precision that dumps are shown with, so
+    /// that checkpoints of large states are cheap to keep and compare.
+    State(u64),
+    /// The value that the run returned, or the errors that it failed with.
+    Result(String),
+}
+
+impl Display for Observation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Message(None, msg) => write!(f, "message `{msg}`"),
+            Self::Message(Some(channel), msg) => write!(f, "message `{msg}` to `{channel}`"),
+            Self::State(hash) => write!(f, "state with hash {hash:016x}"),
+            Self::Result(result) => write!(f, "result `{result}`"),
+        }
+    }
+}
+
+/// A receiver that keeps the outputs of a run as observations.
+#[derive(Debug, Default)]
+pub struct ObservingReceiver {
+    observations: Vec<Observation>,
+}
+
+impl ObservingReceiver {
+    /// Adds the result of the run and returns all the observations of the run.
+    #[must_use]
+    pub fn finish(mut self, result: String) -> Vec<Observation> {
+        self.observations.push(Observation::Result(result));
+        self.observations
+    }
+}
+
+impl Receiver for ObservingReceiver {
+    fn state(
+        &mut self,
+        state: Vec<(BigUint, Complex64)>,
+        qubit_count: usize,
+    ) -> Result<(), output::Error> {
+        let mut hasher = DefaultHasher::new();
+        qubit_count.hash(&mut hasher);
+        for (id, amplitude) in &state {
+            format_state_id(id, qubit_count).hash(&mut hasher);
+            fmt_complex(amplitude).hash(&mut hasher);
+        }
+        self.observations.push(Observation::State(hasher.finish()));
+        Ok(())
+    }
+
+    fn message(&mut self, msg: &str) -> Result<(), output::Error> {
+        self.observations
+            .push(Observation::Message(None, msg.to_string()));
+        Ok(())
+    }
+
+    fn message_to(&mut self, channel: &str, msg: &str) -> Result<(), output::Error> {
+        self.observations.push(Observation::Message(
+            Some(channel.to_string()),
+            msg.to_string(),
+        ));
+        Ok(())
+    }
+}
+
+/// What a run observably did.
+#[derive(Debug, Default)]
+pub struct Run {
+    pub observations: Vec<Observation>,
+    pub trace: Vec<TraceEvent>,
+}
+
+/// What differs between two runs with the same seed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DivergenceKind {
+    /// The outputs or results of the runs.
+    Output,
+    /// The calls, gates or measurements of the runs.
+    Trace,
+}
+
+/// The first place where two runs with the same seed differ.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub kind: DivergenceKind,
+    /// The index of the first observation or trace event that differs.
+    pub index: usize,
+    /// What the first run observed there, or `None` if it had already finished.
+    pub first: Option<String>,
+    /// What the second run observed there, or `None` if it had already finished.
+    pub second: Option<String>,
+    /// The innermost call that the first run was in when the runs differed. Outputs aren't traced,
+    /// so only trace divergences have one.
+    pub call: Option<StackFrame>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            DivergenceKind::Output => "output",
+            DivergenceKind::Trace => "trace event",
+        };
+        write!(f, "{kind} {}", self.index)?;
+        if let Some(call) = &self.call {
+            write!(
+                f,
+                " in {} ({}:{}:{})",
+                call.name,
+                call.path,
+                call.range.start.line + 1,
+                call.range.start.column + 1
+            )?;
+        }
+        let observed = |observed: &Option<String>| {
+            observed
+                .clone()
+                .unwrap_or_else(|| "nothing more".to_string())
+        };
+        write!(
+            f,
+            ": the first run observed {}, the second run observed {}",
+            observed(&self.first),
+            observed(&self.second)
+        )
+    }
+}
+
+/// The outcome of auditing a program for nondeterminism.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeterminismReport {
+    /// The seed that both runs were given.
+    pub seed: u64,
+    pub divergences: Vec<Divergence>,
+}
+
+impl DeterminismReport {
+    /// Whether both runs observably did the same.
+    #[must_use]
+    pub fn is_deterministic(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl Display for DeterminismReport {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_deterministic() {
+            return write!(f, "runs with seed {} were identical", self.seed);
+        }
+        write!(f, "runs with seed {} diverged:", self.seed)?;
+        for divergence in &self.divergences {
+            write!(f, "\n  {divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two runs with the same seed, finding where their outputs and their traces first
+/// differ. `describe` names the callable of a frame, and `locate` finds the source of a call.
+pub fn compare(
+    seed: u64,
+    first: &Run,
+    second: &Run,
+    describe: impl Fn(&Frame) -> String,
+    locate: impl Fn(&Frame) -> StackFrame,
+) -> DeterminismReport {
+    let mut divergences = Vec::new();
+    if let Some(index) = first_difference(&first.observations, &second.observations) {
+        divergences.push(Divergence {
+            kind: DivergenceKind::Output,
+            index,
+            first: first.observations.get(index).map(ToString::to_string),
+            second: second.observations.get(index).map(ToString::to_string),
+            call: None,
+        });
+    }
+    if let Some(index) = first_difference(&first.trace, &second.trace) {
+        let event = |run: &Run| {
+            run.trace
+                .get(index)
+                .map(|event| describe_event(event, &describe))
+        };
+        divergences.push(Divergence {
+            kind: DivergenceKind::Trace,
+            index,
+            first: event(first),
+            second: event(second),
+            call: innermost_call(&first.trace[..index]).map(locate),
+        });
+    }
+    DeterminismReport { seed, divergences }
+}
+
+fn first_difference<T: PartialEq>(first: &[T], second: &[T]) -> Option<usize> {
+    first
+        .iter()
+        .zip(second)
+        .position(|(a, b)| a != b)
+        .or_else(|| (first.len() != second.len()).then_some(first.len().min(second.len())))
+}
+
+/// The call that is still in progress at the end of the events and was entered last.
+fn innermost_call(events: &[TraceEvent]) -> Option<&Frame> {
+    let mut stack = Vec::new();
+    for event in events {
+        match event {
+            TraceEvent::Enter { frame, .. } => stack.push(frame),
+            TraceEvent::Exit { .. } => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    stack.pop()
+}
+
+fn describe_event(event: &TraceEvent, describe: impl Fn(&Frame) -> String) -> String {
+    match event {
+        TraceEvent::Enter { frame, .. } => format!("a call to {}", describe(frame)),
+        TraceEvent::Exit { frame, .. } => format!("a return from {}", describe(frame)),
+        TraceEvent::Gate { name, qubits, .. } => format!("gate {name} on qubits {qubits:?}"),
+        TraceEvent::Measurement { qubit, reset, .. } => {
+            let name = if *reset { "MResetZ" } else { "M" };
+            format!("{name} of qubit {qubit}")
+        }
+        TraceEvent::Reset { qubit, .. } => format!("reset of qubit {qubit}"),
+    }
+}
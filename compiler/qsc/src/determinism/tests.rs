@@ -0,0 +1,125 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{compare, Observation, Run};
+use crate::interpret::{Interpreter, StackFrame};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::{
+    line_column::{Encoding, Position, Range},
+    span::Span,
+};
+use qsc_eval::{debug::Frame, trace::TraceEvent, val::FunctorApp};
+use qsc_fir::fir::{PackageId, StoreItemId};
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+#[test]
+fn runs_with_the_same_seed_are_identical() {
+    let source = indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            @EntryPoint()
+            operation Main() : (Result, Int) {
+                use qs = Qubit[2];
+                H(qs[0]);
+                CNOT(qs[0], qs[1]);
+                Microsoft.Quantum.Diagnostics.DumpMachine();
+                let n = Microsoft.Quantum.Random.DrawRandomInt(0, 100);
+                Message($\"drew {n}\");
+                Reset(qs[1]);
+                (MResetZ(qs[0]), n)
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_quantum_seed(Some(7));
+    let report = interpreter.audit_entry(Encoding::Utf8);
+    assert!(report.is_deterministic(), "{report}");
+    expect!["runs with seed 7 were identical"].assert_eq(&report.to_string());
+}
+
+#[test]
+fn first_divergences_are_reported_with_their_calls() {
+    let frame = Frame {
+        span: Span { lo: 10, hi: 20 },
+        id: StoreItemId {
+            package: PackageId::from(2),
+            item: 0.into(),
+        },
+        caller: PackageId::from(2),
+        functor: FunctorApp::default(),
+    };
+    let gate = |name| TraceEvent::Gate {
+        time: 0,
+        name,
+        qubits: vec![0],
+    };
+    let first = Run {
+        observations: vec![
+            Observation::Message(None, "start".into()),
+            Observation::State(1),
+            Observation::Result("Zero".into()),
+        ],
+        trace: vec![TraceEvent::Enter { time: 0, frame }, gate("H")],
+    };
+    let second = Run {
+        observations: vec![
+            Observation::Message(None, "start".into()),
+            Observation::State(2),
+            Observation::Result("Zero".into()),
+        ],
+        trace: vec![TraceEvent::Enter { time: 0, frame }, gate("X")],
+    };
+    let report = compare(
+        3,
+        &first,
+        &second,
+        |_| "Prepare".into(),
+        |_| StackFrame {
+            name: "Prepare".into(),
+            functor: String::new(),
+            path: "test".into(),
+            range: Range {
+                start: Position { line: 2, column: 8 },
+                end: Position {
+                    line: 2,
+                    column: 18,
+                },
+            },
+        },
+    );
+    expect![[r#"
+        runs with seed 3 diverged:
+          output 1: the first run observed state with hash 0000000000000001, the second run observed state with hash 0000000000000002
+          trace event 1 in Prepare (test:3:9): the first run observed gate H on qubits [0], the second run observed gate X on qubits [0]"#]]
+    .assert_eq(&report.to_string());
+}
+
+#[test]
+fn runs_that_stop_early_diverge_where_they_stop() {
+    let first = Run {
+        observations: vec![Observation::Result("()".into())],
+        trace: Vec::new(),
+    };
+    let second = Run {
+        observations: vec![
+            Observation::Message(Some("stderr".into()), "late".into()),
+            Observation::Result("()".into()),
+        ],
+        trace: vec![TraceEvent::Reset { time: 0, qubit: 0 }],
+    };
+    let report = compare(0, &first, &second, |_| String::new(), |_| unreachable!());
+    expect![[r#"
+        runs with seed 0 diverged:
+          output 0: the first run observed result `()`, the second run observed message `late` to `stderr`
+          trace event 0: the first run observed nothing more, the second run observed reset of qubit 0"#]]
+    .assert_eq(&report.to_string());
+}
@@ -0,0 +1,196 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Doc tests: the `qsharp` code blocks in the doc comments of a package's items are run as
+//! examples against the package, so that the documentation fails loudly when it rots. Each example
+//! is compiled on top of the documented package with the namespace of the documented item opened,
+//! and passes if it compiles and runs to completion. Each example runs in an interpreter of its
+//! own, so that one example can't change what another sees.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    coverage::Coverage,
+    graph::namespace_of,
+    interpret::{self, GenericReceiver, Interpreter},
+};
+use qsc_data_structures::line_column::{Encoding, Position};
+use qsc_frontend::compile::{CompileUnit, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::ItemKind;
+use qsc_passes::PackageType;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+
+const FENCE: &str = "```";
+
+/// An example embedded in the doc comment of an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocTest {
+    /// The fully qualified name of the documented item.
+    pub item: String,
+    /// The namespace that is opened when the example is run.
+    pub namespace: Arc<str>,
+    /// The source file that contains the doc comment.
+    pub source: Arc<str>,
+    /// The one-based line of the doc comment where the example's code starts.
+    pub line: u32,
+    /// The code of the example.
+    pub code: String,
+}
+
+impl DocTest {
+    /// The entry expression that runs the example.
+    #[must_use]
+    pub fn entry_expr(&self) -> String {
+        format!("{{\nopen {};\n{}\n}}", self.namespace, self.code)
+    }
+}
+
+impl Display for DocTest {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} ({}:{})", self.item, self.source, self.line)
+    }
+}
+
+/// The outcome of running a doc test.
+#[derive(Debug)]
+pub struct DocTestResult {
+    pub test: DocTest,
+    /// The errors that the example failed with, which are empty if it passed.
+    pub errors: Vec<interpret::Error>,
+}
+
+impl DocTestResult {
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The outcomes of the doc tests of a package.
+#[derive(Debug)]
+pub struct DocTestRun {
+    /// The outcome of each doc test, in source order.
+    pub results: Vec<DocTestResult>,
+    /// The coverage of the package by all of the doc tests as lcov tracefile records, if coverage
+    /// was requested.
+    pub lcov: Option<String>,
+}
+
+/// Finds the examples in the doc comments of the items in the compilation unit, in source order.
+/// Code blocks are examples when their info string is `qsharp`, unless it is followed by
+/// `ignore`, as in `qsharp,ignore`.
+#[must_use]
+pub fn extract_doc_tests(unit: &CompileUnit) -> Vec<DocTest> {
+    let mut tests = Vec::new();
+    for item in unit.package.items.values() {
+        let name = match &item.kind {
+            ItemKind::Callable(decl) => &decl.name.name,
+            ItemKind::Ty(name, _) => &name.name,
+            ItemKind::Namespace(..) => continue,
+        };
+        if item.doc.is_empty() {
+            continue;
+        }
+        let Some(source) = unit.sources.find_by_offset(item.span.lo) else {
+            continue;
+        };
+
+        // Doc comments are the first thing in the item, one line of the doc per line of source.
+        let first_line = Position::from_utf8_byte_offset(
+            Encoding::Utf8,
+            &source.contents,
+            item.span.lo - source.offset,
+        )
+        .line
+            + 1;
+        let namespace = namespace_of(&unit.package, item.parent);
+        for (doc_line, code) in code_blocks(&item.doc) {
+            tests.push(DocTest {
+                item: format!("{namespace}.{name}"),
+                namespace: Arc::clone(&namespace),
+                source: Arc::clone(&source.name),
+                line: first_line + doc_line,
+                code,
+            });
+        }
+    }
+
+    tests.sort_by(|a, b| (&a.source, a.line).cmp(&(&b.source, b.line)));
+    tests
+}
+
+/// The examples in a doc comment, with the zero-based line of the doc where each one's code
+/// starts.
+fn code_blocks(doc: &str) -> Vec<(u32, String)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(u32, bool, Vec<&str>)> = None;
+    for (line, text) in (0..).zip(doc.lines()) {
+        let trimmed = text.trim_start();
+        match &mut open {
+            Some((start, is_example, code)) => {
+                if trimmed.starts_with(FENCE) {
+                    if *is_example {
+                        blocks.push((*start, code.join("\n")));
+                    }
+                    open = None;
+                } else {
+                    code.push(text);
+                }
+            }
+            None => {
+                if let Some(info) = trimmed.strip_prefix(FENCE) {
+                    let mut tags = info.split(',').map(str::trim);
+                    let is_example =
+                        tags.next() == Some("qsharp") && tags.all(|tag| tag != "ignore");
+                    open = Some((line + 1, is_example, Vec::new()));
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// Compiles the sources and runs the examples in their doc comments against them, each in a
+/// fresh interpreter. When `coverage` is set, the coverage of the sources by all of the examples
+/// is recorded.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources don't compile.
+pub fn run_doc_tests(
+    std: bool,
+    sources: &SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+    coverage: bool,
+) -> Result<DocTestRun, Vec<interpret::Error>> {
+    let mut documented = Interpreter::new(std, sources.clone(), PackageType::Lib, capabilities)?;
+    let tests = extract_doc_tests(documented.source_package());
+
+    let mut recorded = coverage.then(Coverage::new);
+    let mut results = Vec::with_capacity(tests.len());
+    for test in tests {
+        let mut interpreter =
+            Interpreter::new(std, sources.clone(), PackageType::Lib, capabilities)?;
+        if let Some(coverage) = recorded.take() {
+            interpreter.resume_coverage(coverage);
+        }
+        let mut out = Vec::new();
+        let errors = match interpreter.run(&mut GenericReceiver::new(&mut out), &test.entry_expr())
+        {
+            Ok(Ok(_)) => Vec::new(),
+            Ok(Err(errors)) | Err(errors) => errors,
+        };
+        recorded = interpreter.take_coverage();
+        results.push(DocTestResult { test, errors });
+    }
+
+    let lcov = recorded.and_then(|coverage| {
+        documented.resume_coverage(coverage);
+        documented.coverage_lcov()
+    });
+    Ok(DocTestRun { results, lcov })
+}
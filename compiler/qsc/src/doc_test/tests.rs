@@ -0,0 +1,97 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{extract_doc_tests, run_doc_tests};
+use crate::compile;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {r#"
+    namespace Test {
+        /// Adds one.
+        ///
+        /// # Example
+        /// ```qsharp
+        /// if AddOne(1) != 2 { fail "AddOne should add one"; }
+        /// ```
+        function AddOne(x : Int) : Int {
+            x + 1
+        }
+
+        /// Subtracts one.
+        /// ```qsharp
+        /// if SubtractOne(1) != 1 { fail "SubtractOne should return its input"; }
+        /// ```
+        ///
+        /// ```qsharp,ignore
+        /// This is not Q#.
+        /// ```
+        ///
+        /// ```text
+        /// Neither is this.
+        /// ```
+        function SubtractOne(x : Int) : Int {
+            x - 1
+        }
+    }
+"#};
+
+fn sources() -> SourceMap {
+    SourceMap::new([("test.qs".into(), SOURCE.into())], None)
+}
+
+#[test]
+fn examples_are_extracted_with_their_locations() {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let (unit, errors) =
+        compile::compile(&store, &[std], sources(), PackageType::Lib, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let tests = extract_doc_tests(&unit);
+    let summary = tests.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec!["Test.AddOne (test.qs:6)", "Test.SubtractOne (test.qs:14)"]
+    );
+    assert_eq!(
+        tests[1].code,
+        r#"if SubtractOne(1) != 1 { fail "SubtractOne should return its input"; }"#
+    );
+    assert_eq!(
+        tests[1].entry_expr(),
+        indoc! {r#"
+            {
+            open Test;
+            if SubtractOne(1) != 1 { fail "SubtractOne should return its input"; }
+            }"#}
+    );
+}
+
+#[test]
+fn failing_examples_are_reported() {
+    let run = run_doc_tests(true, &sources(), RuntimeCapabilityFlags::all(), false)
+        .unwrap_or_else(|errors| panic!("sources should compile: {errors:?}"));
+    let results = run.results;
+    let outcomes = results
+        .iter()
+        .map(|result| (result.test.item.as_str(), result.passed()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        vec![("Test.AddOne", true), ("Test.SubtractOne", false)]
+    );
+    assert!(results[1].errors[0].to_string().contains("runtime error"));
+    assert!(run.lcov.is_none());
+}
+
+#[test]
+fn coverage_is_recorded_across_examples() {
+    let run = run_doc_tests(true, &sources(), RuntimeCapabilityFlags::all(), true)
+        .unwrap_or_else(|errors| panic!("sources should compile: {errors:?}"));
+    let lcov = run.lcov.expect("coverage should be recorded");
+    assert!(lcov.contains("FNDA:1,Test.AddOne\n"), "{lcov}");
+    assert!(lcov.contains("FNDA:1,Test.SubtractOne\n"), "{lcov}");
+}
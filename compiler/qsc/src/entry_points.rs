@@ -0,0 +1,252 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Entry point candidates: the operations of a package that could be run as its entry point, so
+//! that launch UIs can offer a picker of runnable operations instead of only the one marked with
+//! `@EntryPoint()`.
+//!
+//! An operation is a candidate if it has a body and no other specializations, isn't generic, and
+//! takes and returns only classical values, which are the rules that the entry point of a program
+//! follows. Each candidate comes with the runtime capabilities it needs, found by the same checks
+//! that reject programs for a target, counting the callables of the package that it refers to.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation_db::constant_to_qsharp,
+    graph::{namespace_of, References},
+    kernels::{is_classical, is_results},
+};
+use qsc_data_structures::span::Span;
+use qsc_doc_gen::display::parse_doc_for_summary;
+use qsc_frontend::compile::{ConfigAttr, PackageStore, RuntimeCapabilityFlags};
+use qsc_hir::{
+    hir::{
+        Attr, CallableDecl, CallableKind, Item, ItemKind, LocalItemId, Package, PackageId, PatKind,
+        SpecBody,
+    },
+    ty::{Prim, Ty},
+    visit::Visitor,
+};
+use qsc_passes::capability_delta;
+use rustc_hash::FxHashSet;
+use std::sync::Arc;
+
+/// An operation that could be run as the entry point of its package.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryPointCandidate {
+    pub id: LocalItemId,
+    /// The fully qualified name of the operation.
+    pub name: String,
+    /// The declaration of the operation without its body, as in
+    /// `operation Main(shots : Int) : Result[]`.
+    pub signature: String,
+    pub parameters: Vec<CandidateParameter>,
+    /// The output type of the operation, written as in Q#.
+    pub output: String,
+    /// The `# Summary` section of the doc comment, or the whole doc comment if it has no such
+    /// section.
+    pub summary: String,
+    /// Whether the operation is the one marked with `@EntryPoint()`.
+    pub is_entry_point: bool,
+    /// The names of the operation's other attributes, such as `MaxIterations`. Default values of
+    /// parameters are given with the parameters instead.
+    pub attributes: Vec<&'static str>,
+    /// The runtime capabilities that running the operation needs.
+    pub capabilities: RuntimeCapabilityFlags,
+    /// The least capable profile that can run the operation.
+    pub minimum_profile: ConfigAttr,
+    pub span: Span,
+}
+
+/// A parameter of an entry point candidate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CandidateParameter {
+    /// The name of the parameter, or `_` if it isn't bound to a name.
+    pub name: Arc<str>,
+    /// The type of the parameter, written as in Q#.
+    pub ty: String,
+    /// The default value of the parameter from its `@Default` attribute, written as a Q# literal.
+    pub default: Option<String>,
+}
+
+/// Finds the operations of the package that could be run as its entry point, in source order.
+///
+/// # Panics
+///
+/// Panics if the package is not in the store.
+#[must_use]
+pub fn entry_point_candidates(
+    store: &PackageStore,
+    package: PackageId,
+) -> Vec<EntryPointCandidate> {
+    let unit = store.get(package).expect("package should be in store");
+    let uses = capability_delta(&unit.package, RuntimeCapabilityFlags::empty()).sources;
+
+    let mut candidates = Vec::new();
+    for item in unit.package.items.values() {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        if !is_candidate(item, decl) {
+            continue;
+        }
+
+        let mut capabilities = output_capabilities(&decl.output);
+        let callables = reachable_callables(&unit.package, package, item.id);
+        for (capability, spans) in &uses {
+            if spans.iter().any(|span| {
+                callables.iter().any(|&id| {
+                    unit.package
+                        .items
+                        .get(id)
+                        .is_some_and(|callable| contains(callable.span, *span))
+                })
+            }) {
+                capabilities |= *capability;
+            }
+        }
+        let minimum_profile = [ConfigAttr::Base, ConfigAttr::Adaptive]
+            .into_iter()
+            .find(|&profile| RuntimeCapabilityFlags::from(profile).contains(capabilities))
+            .unwrap_or(ConfigAttr::Unrestricted);
+
+        let namespace = namespace_of(&unit.package, item.parent);
+        let parameters = parameters(item, decl);
+        let output = decl.output.display();
+        let signature = format!(
+            "operation {}({}) : {output}",
+            decl.name.name,
+            parameters
+                .iter()
+                .map(|param| format!("{} : {}", param.name, param.ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        candidates.push(EntryPointCandidate {
+            id: item.id,
+            name: if namespace.is_empty() {
+                decl.name.name.to_string()
+            } else {
+                format!("{namespace}.{}", decl.name.name)
+            },
+            signature,
+            parameters,
+            output,
+            summary: parse_doc_for_summary(&item.doc),
+            is_entry_point: item.attrs.contains(&Attr::EntryPoint),
+            attributes: item.attrs.iter().filter_map(attr_name).collect(),
+            capabilities,
+            minimum_profile,
+            span: decl.name.span,
+        });
+    }
+
+    candidates.sort_by_key(|candidate| candidate.span.lo);
+    candidates
+}
+
+fn is_candidate(item: &Item, decl: &CallableDecl) -> bool {
+    decl.kind == CallableKind::Operation
+        && decl.generics.is_empty()
+        && matches!(decl.body.body, SpecBody::Impl(..))
+        && decl.adj.is_none()
+        && decl.ctl.is_none()
+        && decl.ctl_adj.is_none()
+        && !item.attrs.contains(&Attr::Unimplemented)
+        && is_classical(&decl.input.ty)
+        && is_classical(&decl.output)
+}
+
+fn parameters(item: &Item, decl: &CallableDecl) -> Vec<CandidateParameter> {
+    let params = match &decl.input.kind {
+        PatKind::Tuple(params) => params.iter().collect(),
+        _ => vec![&decl.input],
+    };
+    params
+        .into_iter()
+        .map(|param| {
+            let name: Arc<str> = match &param.kind {
+                PatKind::Bind(name) => Arc::clone(&name.name),
+                _ => "_".into(),
+            };
+            let default = item.attrs.iter().find_map(|attr| match attr {
+                Attr::Default(default_name, value) if *default_name == name => {
+                    Some(constant_to_qsharp(value))
+                }
+                _ => None,
+            });
+            CandidateParameter {
+                name,
+                ty: param.ty.display(),
+                default,
+            }
+        })
+        .collect()
+}
+
+fn attr_name(attr: &Attr) -> Option<&'static str> {
+    match attr {
+        Attr::Alias(_) => Some("Alias"),
+        Attr::Config => Some("Config"),
+        Attr::MaxIterations => Some("MaxIterations"),
+        Attr::Default(..) | Attr::EntryPoint | Attr::Unimplemented => None,
+    }
+}
+
+/// The capabilities needed to give the output of a program of the type to the host, which follow
+/// the check of the entry expression's output.
+fn output_capabilities(ty: &Ty) -> RuntimeCapabilityFlags {
+    if is_results(ty) && *ty != Ty::UNIT {
+        RuntimeCapabilityFlags::empty()
+    } else if is_results_or_ints(ty) {
+        RuntimeCapabilityFlags::IntegerComputations
+    } else {
+        RuntimeCapabilityFlags::HigherLevelConstructs
+    }
+}
+
+fn is_results_or_ints(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_results_or_ints(item),
+        Ty::Prim(Prim::Result | Prim::Int) => true,
+        Ty::Tuple(items) => !items.is_empty() && items.iter().all(is_results_or_ints),
+        _ => false,
+    }
+}
+
+/// The callables of the package that the callable refers to, directly or through other callables,
+/// including the callable itself.
+fn reachable_callables(
+    package: &Package,
+    package_id: PackageId,
+    id: LocalItemId,
+) -> FxHashSet<LocalItemId> {
+    let mut reached = FxHashSet::default();
+    let mut stack = vec![id];
+    while let Some(id) = stack.pop() {
+        if !reached.insert(id) {
+            continue;
+        }
+        if let Some(Item {
+            kind: ItemKind::Callable(decl),
+            ..
+        }) = package.items.get(id)
+        {
+            let mut refs = References::new(package_id);
+            refs.visit_callable_decl(decl);
+            stack.extend(
+                refs.refs
+                    .into_iter()
+                    .filter(|(item, _)| item.package == Some(package_id))
+                    .map(|(item, _)| item.item),
+            );
+        }
+    }
+    reached
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.lo <= inner.lo && inner.hi <= outer.hi
+}
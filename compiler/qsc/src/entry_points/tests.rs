@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{entry_point_candidates, CandidateParameter, EntryPointCandidate};
+use crate::compile;
+use indoc::indoc;
+use qsc_frontend::compile::{ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+static SOURCE: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Measurement;
+        /// # Summary
+        /// Prepares a Bell pair and measures it.
+        /// # Output
+        /// The two measurement results.
+        @EntryPoint()
+        operation Main() : Result[] {
+            use qs = Qubit[2];
+            H(qs[0]);
+            CNOT(qs[0], qs[1]);
+            MResetEachZ(qs)
+        }
+
+        /// Measures a qubit until it gives `One`.
+        @MaxIterations(10)
+        operation MeasureOne() : Result {
+            use q = Qubit();
+            while M(q) != One {
+                H(q);
+            }
+            MResetZ(q)
+        }
+
+        @Default(rounds, 3)
+        operation Rounds(rounds : Int) : Int {
+            for _ in 1..rounds {
+                let _ = MeasureOne();
+            }
+            rounds
+        }
+
+        operation MeasureZero() : Result {
+            use q = Qubit();
+            while M(q) != Zero {
+                X(q);
+            }
+            MResetZ(q)
+        }
+
+        operation Apply(q : Qubit) : Unit {
+            H(q);
+        }
+
+        operation Flip() : Unit is Adj {
+            use q = Qubit();
+            X(q);
+        }
+
+        operation Identity<'T>(value : 'T) : 'T {
+            value
+        }
+
+        function Double(x : Int) : Int {
+            2 * x
+        }
+    }
+"};
+
+fn candidates() -> Vec<EntryPointCandidate> {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("test.qs".into(), SOURCE.into())], None);
+    let (unit, errors) = compile::compile(&store, &[std], sources, PackageType::Lib, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    entry_point_candidates(&store, package)
+}
+
+fn candidate(name: &str) -> EntryPointCandidate {
+    candidates()
+        .into_iter()
+        .find(|candidate| candidate.name == name)
+        .expect("operation should be a candidate")
+}
+
+#[test]
+fn candidates_are_classical_operations_with_only_a_body() {
+    let names = candidates()
+        .into_iter()
+        .map(|candidate| candidate.name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "Test.Main",
+            "Test.MeasureOne",
+            "Test.Rounds",
+            "Test.MeasureZero"
+        ]
+    );
+}
+
+#[test]
+fn entry_point_has_signature_and_summary() {
+    let main = candidate("Test.Main");
+    assert_eq!(main.signature, "operation Main() : Result[]");
+    assert_eq!(main.output, "Result[]");
+    assert_eq!(main.summary, "Prepares a Bell pair and measures it.");
+    assert!(main.is_entry_point);
+    assert!(main.parameters.is_empty());
+    assert!(main.attributes.is_empty());
+    assert_eq!(main.capabilities, RuntimeCapabilityFlags::empty());
+    assert_eq!(main.minimum_profile, ConfigAttr::Base);
+}
+
+#[test]
+fn parameters_have_their_default_values() {
+    let rounds = candidate("Test.Rounds");
+    assert_eq!(rounds.signature, "operation Rounds(rounds : Int) : Int");
+    assert_eq!(
+        rounds.parameters,
+        [CandidateParameter {
+            name: "rounds".into(),
+            ty: "Int".to_string(),
+            default: Some("3".to_string()),
+        }]
+    );
+    assert!(rounds.summary.is_empty());
+    assert!(!rounds.is_entry_point);
+}
+
+#[test]
+fn attributes_and_doc_without_summary_section() {
+    let measure = candidate("Test.MeasureOne");
+    assert_eq!(measure.summary, "Measures a qubit until it gives `One`.");
+    assert_eq!(measure.attributes, ["MaxIterations"]);
+}
+
+#[test]
+fn capabilities_include_those_of_called_operations() {
+    let measure = candidate("Test.MeasureOne");
+    assert_eq!(
+        measure.capabilities,
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::BackwardsBranching
+    );
+    assert_eq!(measure.minimum_profile, ConfigAttr::Unrestricted);
+
+    let rounds = candidate("Test.Rounds");
+    assert_eq!(
+        rounds.capabilities,
+        RuntimeCapabilityFlags::ForwardBranching
+            | RuntimeCapabilityFlags::IntegerComputations
+            | RuntimeCapabilityFlags::BackwardsBranching
+    );
+    assert_eq!(rounds.minimum_profile, ConfigAttr::Unrestricted);
+
+    let measure = candidate("Test.MeasureZero");
+    assert_eq!(
+        measure.capabilities,
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::BackwardsBranching
+    );
+    assert_eq!(measure.minimum_profile, ConfigAttr::Unrestricted);
+}
@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 use miette::Diagnostic;
+use qsc_data_structures::message::MessageParams;
 use qsc_frontend::compile::PackageStore;
 use std::fmt::{self, Debug, Display, Formatter};
 use thiserror::Error;
@@ -34,6 +35,12 @@ impl<E: Display> Display for WithStack<E> {
     }
 }
 
+impl<E: MessageParams> MessageParams for WithStack<E> {
+    fn message_params(&self) -> Vec<String> {
+        self.error.message_params()
+    }
+}
+
 // #[diagnostic(transparent)] does not seem to work with generics
 impl<E: Diagnostic> Diagnostic for WithStack<E> {
     fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
@@ -0,0 +1,843 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use std::fmt::{self, Display, Formatter};
+
+/// The long-form explanation of a diagnostic code, for users who want more than the one-line
+/// message of the diagnostic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    /// The code of the diagnostic, such as `Qsc.TypeCk.MissingFunctor`.
+    pub code: &'static str,
+    /// What the diagnostic means.
+    pub meaning: &'static str,
+    /// The common causes of the diagnostic, and how to resolve each of them.
+    pub causes: &'static [&'static str],
+    /// Q# code that reports the diagnostic.
+    pub example: &'static str,
+    /// Links to documentation about the diagnostic.
+    pub links: &'static [&'static str],
+}
+
+const QIR: &str = "https://aka.ms/qdk.qir";
+const DOCUMENTATION: &str = "https://aka.ms/AQ/Documentation";
+const CONJUGATIONS: &str =
+    "https://learn.microsoft.com/en-us/azure/quantum/user-guide/language/statements/conjugations";
+
+/// The explanations, sorted by code.
+const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "Qsc.AdjGen.MissingAdjFunctor",
+        meaning: "The compiler generates the adjoint of an operation declared with `is Adj` or \
+            `adjoint auto` by inverting its body, which needs the adjoint of every operation the \
+            body calls. One of the operations it calls doesn't support the adjoint functor.",
+        causes: &[
+            "The called operation is missing `is Adj` in its signature. Add it if the operation \
+            can be inverted.",
+            "The called operation measures or resets qubits, which can't be inverted. Move the \
+            call out of the operation, or remove `is Adj` from it.",
+        ],
+        example: "operation Measured(q : Qubit) : Unit {\n    \
+            let _ = M(q);\n\
+            }\n\n\
+            operation Main(q : Qubit) : Unit is Adj {\n    \
+            Measured(q);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.BaseProfCk.ResultComparison",
+        meaning: "The base profile runs a fixed sequence of gates and measurements, and reports \
+            the results only when the program ends. A program for it can't compare measurement \
+            results, since it can't choose what to do next based on them.",
+        causes: &[
+            "An `if` or `while` depends on the result of a measurement. Target the adaptive \
+            profile, or return the result and process it after the program has run.",
+            "A result is compared to compute a value that is returned. Return the result itself \
+            instead.",
+        ],
+        example: "operation Main() : Result {\n    \
+            use q = Qubit();\n    \
+            if M(q) == One {\n        \
+            X(q);\n    \
+            }\n    \
+            MResetZ(q)\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.BaseProfCk.ResultLiteral",
+        meaning: "The base profile has no way to express a result that no measurement returned, \
+            so a program for it can't use the literals `One` and `Zero`.",
+        causes: &[
+            "A literal result is returned from the entry point. Return the result of a \
+            measurement, or return nothing for it.",
+            "A result is compared to a literal. Comparing results isn't supported by the base \
+            profile either; see `Qsc.BaseProfCk.ResultComparison`.",
+        ],
+        example: "operation Main() : Result {\n    \
+            Zero\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.BaseProfCk.ResultLoop",
+        meaning: "Code generation for the adaptive profile can branch on a measurement result, \
+            but it can't loop on one. A `while` loop whose condition depends on a measurement \
+            result is rejected even when `@MaxIterations` bounds it, since the loop isn't \
+            unrolled.",
+        causes: &[
+            "A repeat-until-success loop checks a measurement result. Use a `for` loop over a \
+            fixed number of attempts with an `if` on the result instead.",
+        ],
+        example: "operation Main() : Unit {\n    \
+            use q = Qubit();\n    \
+            while MResetZ(q) == Zero {\n        \
+            H(q);\n    \
+            }\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.BaseProfCk.ReturnNonResult",
+        meaning: "The base profile can only report measurement results, so the entry point of a \
+            program for it must return a result, or a tuple or array of results.",
+        causes: &[
+            "The entry point returns a classical value computed by the program, such as an \
+            `Int`. Return the results it is computed from, and compute it after the program has \
+            run.",
+            "The entry point returns a `Bool` made from a result. Return the result itself.",
+        ],
+        example: "operation Main() : Int {\n    \
+            use q = Qubit();\n    \
+            let _ = MResetZ(q);\n    \
+            1\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.BaseProfCk.UnsupportedIntrinsic",
+        meaning: "The base profile can only call intrinsic operations that return a result or \
+            nothing, since it has no way to pass any other value back from the hardware.",
+        causes: &[
+            "The program declares its own intrinsic that returns a classical value. Return a \
+            result from it, or implement it in Q#.",
+        ],
+        example: "operation Sample(q : Qubit) : Int {\n    \
+            body intrinsic;\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.ConjugateInvert.ApplyAssign",
+        meaning: "The compiler runs the `within` block of a conjugation again, inverted, after \
+            the `apply` block. Assigning a variable in the `apply` block that the `within` block \
+            uses would make the inverse differ from the original, so it isn't allowed.",
+        causes: &[
+            "A mutable variable that controls the `within` block is updated in the `apply` block. \
+            Copy it into an immutable variable before the conjugation, and use the copy in the \
+            `within` block.",
+        ],
+        example: "operation Main(q : Qubit) : Unit {\n    \
+            mutable angle = 1.0;\n    \
+            within {\n        \
+            Rx(angle, q);\n    \
+            } apply {\n        \
+            set angle = 2.0;\n    \
+            }\n\
+            }",
+        links: &[CONJUGATIONS],
+    },
+    Explanation {
+        code: "Qsc.CtlGen.MissingCtlFunctor",
+        meaning: "The compiler generates the controlled version of an operation declared with \
+            `is Ctl` or `controlled auto` by controlling each operation its body calls. One of the \
+            operations it calls doesn't support the controlled functor.",
+        causes: &[
+            "The called operation is missing `is Ctl` in its signature. Add it if the operation \
+            can be controlled.",
+            "The called operation measures or resets qubits, which can't be controlled. Move the \
+            call out of the operation.",
+        ],
+        example: "operation Measured(q : Qubit) : Unit {\n    \
+            let _ = M(q);\n\
+            }\n\n\
+            operation Main(q : Qubit) : Unit is Ctl {\n    \
+            Measured(q);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Eval.QubitBudgetExceeded",
+        meaning: "Code generation traces the program to count the qubits it needs, including the \
+            fresh qubits that stand in for measured and reset ones, and checks the count against \
+            the qubit budget of the target. The program needs more qubits than the target has.",
+        causes: &[
+            "A precision parameter that is only known when the program runs, such as the number \
+            of bits of a phase estimate computed from other values, sizes a register beyond what \
+            the target can hold. Lower the precision. Precisions given as literals are reported \
+            when the program is compiled, as `Qsc.QubitBudget.PrecisionExceedsBudget`.",
+            "Qubits are measured or reset and then used again. Each reuse takes a fresh qubit, so \
+            the program needs more qubits than it holds at once. Move measurements toward the end \
+            of the program where the algorithm allows it.",
+        ],
+        example: "operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {\n    \
+            R1(IntAsDouble(power), qs[0]);\n\
+            }\n\
+            operation Main() : Result[] {\n    \
+            use target = Qubit();\n    \
+            X(target);\n    \
+            let precision = 4 * 5;\n    \
+            let bits = Microsoft.Quantum.Unstable.PhaseEstimation.EstimatePhase(\n        \
+            precision,\n        \
+            RotatePower,\n        \
+            [target]\n    \
+            );\n    \
+            Reset(target);\n    \
+            bits\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.Eval.ReleasedQubitNotZero",
+        meaning: "Qubits are allocated in the |0⟩ state, and must be returned to it before they \
+            are released, so that the next allocation can rely on it. A qubit was released in \
+            another state.",
+        causes: &[
+            "A qubit was measured but not reset. Use `MResetZ` in place of `M`, or call `Reset` \
+            after measuring.",
+            "A qubit was left entangled or in superposition. Uncompute the gates applied to it, \
+            for instance with a conjugation, or call `Reset` before it goes out of scope.",
+        ],
+        example: "operation Main() : Unit {\n    \
+            use q = Qubit();\n    \
+            X(q);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Interpret.UnsupportedEntryCapabilities",
+        meaning: "An entry expression submitted for code generation needs runtime capabilities \
+            that the target profile doesn't have. The expression is checked when it is compiled, \
+            and the errors that follow this one show where each capability is needed.",
+        causes: &[
+            "The expression returns a value other than results, such as an integer or a double, \
+            which the base profile can't record. Return the measurement results instead.",
+            "The expression branches on measurement results. Target the adaptive profile, or \
+            restructure the expression.",
+        ],
+        example: "{\n    \
+            use q = Qubit();\n    \
+            if M(q) == One {\n        \
+            X(q);\n    \
+            }\n    \
+            MResetZ(q)\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.Interpret.UnsupportedRuntimeCapabilities",
+        meaning: "Code can only be generated for a program that the target profile supports. The \
+            program uses a capability, such as branching on measurement results, that the target \
+            doesn't have.",
+        causes: &[
+            "The target profile is the base profile but the program branches on measurement \
+            results. Target the adaptive profile, or restructure the program.",
+        ],
+        example: "operation Main() : Result {\n    \
+            use q = Qubit();\n    \
+            if M(q) == One {\n        \
+            X(q);\n    \
+            }\n    \
+            MResetZ(q)\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.Lint.UncheckedIndexArithmetic",
+        meaning: "The result of integer arithmetic is used, directly or through local variables, \
+            to index an array of qubits. Integer arithmetic wraps around when it overflows, so an \
+            overflow there silently selects the wrong qubit instead of failing.",
+        causes: &[
+            "An index is computed from values that may be large, such as sizes or offsets given by \
+            the caller. Use `AddIChecked`, `SubtractIChecked` or `MultiplyIChecked` from \
+            `Microsoft.Quantum.Math`, which also return whether the result overflowed.",
+        ],
+        example: "operation Apply(qs : Qubit[], offset : Int) : Unit {\n    \
+            X(qs[offset + 1]);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.QubitBudget.PrecisionExceedsBudget",
+        meaning: "A library operation that allocates a register for each bit of precision, such \
+            as `EstimatePhase` or `EstimateAmplitude`, was called with a precision known at \
+            compile time that needs more qubits than the target has. Only the registers that \
+            the precision and size parameters allocate are counted, so the program needs at \
+            least that many qubits.",
+        causes: &[
+            "The precision is higher than the target can hold. Lower it, or generate code for a \
+            target with more qubits.",
+            "The precision and the number of qubits of an amplitude estimation together need \
+            more qubits than the target has. Lower either of them.",
+        ],
+        example: "operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {\n    \
+            R1(IntAsDouble(power), qs[0]);\n\
+            }\n\
+            operation Main() : Result[] {\n    \
+            use target = Qubit();\n    \
+            X(target);\n    \
+            let bits = Microsoft.Quantum.Unstable.PhaseEstimation.EstimatePhase(\n        \
+            20,\n        \
+            RotatePower,\n        \
+            [target]\n    \
+            );\n    \
+            Reset(target);\n    \
+            bits\n\
+            }",
+        links: &[QIR],
+    },
+    Explanation {
+        code: "Qsc.Resolve.Ambiguous",
+        meaning: "A name was used that two opened namespaces both declare, so the compiler can't \
+            tell which item it refers to.",
+        causes: &[
+            "Two `open` statements bring in namespaces that declare items with the same name. \
+            Qualify the name with its namespace, or open one of the namespaces under an alias, as \
+            in `open Foo as F;`.",
+        ],
+        example: "namespace Foo {\n    \
+            function Sample() : Unit {}\n\
+            }\n\n\
+            namespace Bar {\n    \
+            function Sample() : Unit {}\n\
+            }\n\n\
+            namespace Test {\n    \
+            open Foo;\n    \
+            open Bar;\n\n    \
+            function Main() : Unit {\n        \
+            Sample();\n    \
+            }\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.AmbiguousPrelude",
+        meaning: "The namespaces `Microsoft.Quantum.Core`, `Microsoft.Quantum.Canon` and \
+            `Microsoft.Quantum.Intrinsic` are opened in every namespace. A name was used that more \
+            than one of them declares.",
+        causes: &[
+            "A package declares an item in one of these namespaces with the same name as an item \
+            in another of them. Qualify the name with its namespace, or rename the item.",
+        ],
+        example: "namespace Microsoft.Quantum.Canon {\n    \
+            function Sample() : Unit {}\n\
+            }\n\n\
+            namespace Microsoft.Quantum.Core {\n    \
+            function Sample() : Unit {}\n\
+            }\n\n\
+            namespace Test {\n    \
+            function Main() : Unit {\n        \
+            Sample();\n    \
+            }\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.DeprecatedAlias",
+        meaning: "An item was used by a name that it had before it moved. The old name still \
+            works, but it may be removed in a future release.",
+        causes: &[
+            "The code was written against an older version of a library. Use the name that the \
+            warning gives instead.",
+        ],
+        example: "namespace Current {\n    \
+            @Alias(\"Legacy.Old\")\n    \
+            function New() : Unit {}\n\
+            }\n\n\
+            namespace Test {\n    \
+            function Main() : Unit {\n        \
+            Legacy.Old();\n    \
+            }\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.Duplicate",
+        meaning: "Two items in the same namespace have the same name.",
+        causes: &[
+            "Two callables were given the same name to overload them. Q# has no overloading, so \
+            rename one of them.",
+            "The namespace is split across files that each declare the item. Remove one of the \
+            declarations.",
+        ],
+        example: "function Add(a : Int, b : Int) : Int {\n    \
+            a + b\n\
+            }\n\n\
+            function Add(a : Double, b : Double) : Double {\n    \
+            a + b\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.DuplicateBinding",
+        meaning: "A pattern binds the same name more than once. Each name that a pattern binds \
+            must be distinct.",
+        causes: &[
+            "A tuple is destructured into names that repeat. Rename one of them, or use `_` for \
+            the items that aren't needed.",
+            "A callable has two parameters with the same name. Rename one of them.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let (a, a) = (1, 2);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.DuplicateIntrinsic",
+        meaning: "The target implements intrinsic callables, and finds them by name alone. Two \
+            callables declared with `body intrinsic` have the same name, even if they are in \
+            different namespaces.",
+        causes: &[
+            "A package declares an intrinsic with the same name as one in another namespace or in \
+            the standard library. Rename one of them.",
+        ],
+        example: "namespace First {\n    \
+            operation Apply(q : Qubit) : Unit {\n        \
+            body intrinsic;\n    \
+            }\n\
+            }\n\n\
+            namespace Second {\n    \
+            operation Apply(q : Qubit) : Unit {\n        \
+            body intrinsic;\n    \
+            }\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.NotAvailable",
+        meaning: "A name was used that only refers to an item in other compilation configurations. \
+            The item has a `@Config` attribute that excludes the current target profile, so it \
+            isn't compiled.",
+        causes: &[
+            "The item is only declared for another profile, such as the base profile. Declare a \
+            version of it for the current profile, or remove the `@Config` attribute.",
+            "The program is compiled for a profile it wasn't written for. Choose the profile that \
+            the item is configured for.",
+        ],
+        example: "@Config(Base)\n\
+            function BaseOnly() : Unit {}\n\n\
+            function Main() : Unit {\n    \
+            BaseOnly();\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.NotFound",
+        meaning: "A name was used that doesn't refer to any local variable or item in scope.",
+        causes: &[
+            "The name is misspelled. Check its spelling and capitalization.",
+            "The item is in a namespace that isn't opened. Add an `open` statement for the \
+            namespace, or qualify the name with it.",
+            "A local variable is used outside the block that declares it. Declare it in the \
+            enclosing block.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let n = Lenght([1, 2, 3]);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.Resolve.Unimplemented",
+        meaning: "An item with the `@Unimplemented()` attribute was used. The attribute marks \
+            items that are declared for compatibility, but that this compiler doesn't implement.",
+        causes: &[
+            "The code was written for another Q# implementation that provides the item. Replace it \
+            with an equivalent from the standard library, or implement it in the package.",
+        ],
+        example: "@Unimplemented()\n\
+            function Legacy() : Unit {}\n\n\
+            function Main() : Unit {\n    \
+            Legacy();\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.AmbiguousTy",
+        meaning: "The type of an expression can't be inferred from how it is used, and nothing \
+            else decides it.",
+        causes: &[
+            "A generic callable is called without an argument or a use of its output that fixes \
+            its type parameters. Give the type arguments explicitly, as in `Empty<Int>()`.",
+        ],
+        example: "function Empty<'T>() : 'T[] {\n    \
+            []\n\
+            }\n\n\
+            function Main() : Unit {\n    \
+            let xs = Empty();\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.CallableMismatch",
+        meaning: "A function was used where an operation is expected, or an operation where a \
+            function is expected.",
+        causes: &[
+            "An operation was passed to a parameter of function type. Pass a function, or change \
+            the parameter to an operation type, as in `op : (Qubit => Unit)`.",
+            "A mutable variable that holds a function is assigned an operation. Declare the \
+            variable with an operation instead.",
+        ],
+        example: "operation Flip(q : Qubit) : Unit {\n    \
+            X(q);\n\
+            }\n\n\
+            operation ApplyTo(f : (Qubit -> Unit), q : Qubit) : Unit {\n    \
+            f(q);\n\
+            }\n\n\
+            operation Main() : Unit {\n    \
+            use q = Qubit();\n    \
+            ApplyTo(Flip, q);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.Edition",
+        meaning: "The code uses a language feature from a newer edition of Q# than the one the \
+            package is compiled in. Packages that don't name an edition in `qsharp.json` are \
+            compiled in the first edition.",
+        causes: &[
+            "The manifest of the package doesn't name an edition. Set `\"edition\"` in \
+            `qsharp.json` to the edition that the message gives, after checking that the rest of \
+            the package compiles in it.",
+        ],
+        example: "newtype Meters = Double;\n\n\
+            function Same(a : Meters, b : Meters) : Bool {\n    \
+            a == b\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.FunctorMismatch",
+        meaning: "A callable was used where a callable with exactly a given set of functors is \
+            expected, and its functors differ.",
+        causes: &[
+            "The items of an array of operations have different functors. The array takes the \
+            functors of its first item, so put the operation with the fewest functors first, or \
+            wrap the others in operations that share its functors.",
+            "A mutable variable of operation type is assigned an operation with other functors. \
+            Declare the variable with an operation that has the functors that all the operations \
+            assigned to it share.",
+        ],
+        example: "operation Plain(q : Qubit) : Unit {}\n\n\
+            operation Main() : Unit {\n    \
+            let ops = [Plain, H];\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.IndexByQubits",
+        meaning: "An array was indexed by a qubit register. Looking up classical data by the \
+            value of a register is a quantum operation, which writes the entry into a target \
+            register, so it can't be an index expression.",
+        causes: &[
+            "The lookup was meant as a QROM. Call `Microsoft.Quantum.Unstable.TableLookup.Lookup` \
+            with the data, the address register and a zero-initialized target register, which \
+            is lowered to the lookup that suits the target.",
+        ],
+        example: "operation Main() : Unit {\n    \
+            use address = Qubit[1];\n    \
+            let entry = [[true], [false]][address];\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassAdd",
+        meaning: "`+` was applied to values of a type that can't be added. Numbers, strings and \
+            arrays support `+`.",
+        causes: &[
+            "Booleans were combined with `+`. Use `or` for the disjunction of booleans.",
+            "Values of a user-defined type were added. Unwrap them with `!` and add the values \
+            they wrap.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let either = true + false;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassAdj",
+        meaning: "The `Adjoint` functor can only be applied to operations, and it was applied to \
+            a value of another type.",
+        causes: &[
+            "A local variable shadows the operation that was meant to be inverted. Rename the \
+            variable.",
+            "`Adjoint` was applied to the result of calling an operation rather than to the \
+            operation. Write `Adjoint Op(q)`, which applies the adjoint of `Op` to `q`.",
+        ],
+        example: "operation Main() : Unit {\n    \
+            let angle = 1.0;\n    \
+            let inverted = Adjoint angle;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassCall",
+        meaning: "A value that isn't a function or an operation was called.",
+        causes: &[
+            "A local variable shadows the callable that was meant to be called. Rename the \
+            variable.",
+            "An array was indexed with parentheses. Use square brackets, as in `xs[0]`.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let count = 3;\n    \
+            let value = count(1);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassCtl",
+        meaning: "The `Controlled` functor can only be applied to operations, and it was applied \
+            to a value of another type.",
+        causes: &[
+            "A local variable shadows the operation that was meant to be controlled. Rename the \
+            variable.",
+            "`Controlled` was applied to the result of calling an operation rather than to the \
+            operation. Write `Controlled Op([c], q)`, which applies `Op` to `q` controlled on \
+            `c`.",
+        ],
+        example: "operation Main() : Unit {\n    \
+            let angle = 1.0;\n    \
+            let applied = Controlled angle;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassEq",
+        meaning: "`==` or `!=` was applied to values of a type that can't be compared, such as \
+            callables.",
+        causes: &[
+            "Two callables were compared. Compare values that identify them, such as indices into \
+            an array of the callables, instead.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let same = Main == Main;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassExp",
+        meaning: "`^` was applied to values of a type that can't be raised to a power. Only `Int`, \
+            `BigInt` and `Double` support `^`.",
+        causes: &[
+            "`^` was meant as exclusive or. Use `^^^` for the bitwise exclusive or of integers, or \
+            `!=` for booleans.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let flipped = true ^ false;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassHasField",
+        meaning: "A field was accessed with `::` on a value that has no field with that name. Only \
+            user-defined types with named items have fields.",
+        causes: &[
+            "The name of the field is misspelled. Check it against the declaration of the type.",
+            "The value is a tuple rather than a user-defined type. Destructure it with `let (a, b) \
+            = ...` instead.",
+        ],
+        example: "newtype Complex = (Real : Double, Imag : Double);\n\n\
+            function Main() : Unit {\n    \
+            let c = Complex(1.0, 0.0);\n    \
+            let r = c::Re;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassHasIndex",
+        meaning: "A value that isn't an array was indexed, or an array was indexed by a value \
+            other than an `Int` or a `Range`.",
+        causes: &[
+            "The index is a `Double`. Convert it to an `Int`, for instance with `Round` or \
+            `Truncate`.",
+            "The indexed value is a tuple or a user-defined type. Destructure or unwrap it \
+            instead.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let xs = [1, 2, 3];\n    \
+            let x = xs[1.0];\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassInteger",
+        meaning: "A bitwise or shift operator, such as `&&&` or `<<<`, was applied to values that \
+            aren't integers. Only `Int` and `BigInt` support them.",
+        causes: &[
+            "Booleans were combined with `&&&` or `|||`. Use `and` or `or` for booleans.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let both = true &&& false;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassIterable",
+        meaning: "A `for` loop can only iterate over an array or a range, and it was given a value \
+            of another type.",
+        causes: &[
+            "The loop iterates over a count. Iterate over the range `0..n - 1` instead.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let n = 3;\n    \
+            for i in n {}\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassNum",
+        meaning: "An arithmetic operator, such as `*` or `-`, was applied to values that aren't \
+            numbers. Only `Int`, `BigInt` and `Double` support them.",
+        causes: &[
+            "Booleans were combined with an arithmetic operator. Use `and`, `or` and `not` for \
+            booleans.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let both = true * false;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassShow",
+        meaning: "A value was interpolated into a string whose type has no string representation, \
+            such as a callable.",
+        causes: &[
+            "A callable was interpolated, often because the arguments of a call were left out. \
+            Call it, and interpolate the value it returns.",
+        ],
+        example: "function Main() : Unit {\n    \
+            Message($\"{Main}\");\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingClassUnwrap",
+        meaning: "The unwrap operator `!` was applied to a value that isn't of a user-defined \
+            type.",
+        causes: &[
+            "The value already has the underlying type. Remove the `!`.",
+        ],
+        example: "function Main() : Unit {\n    \
+            let x = 1;\n    \
+            let y = x!;\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingFunctor",
+        meaning: "A functor was applied to an operation that doesn't support it, or an operation \
+            was passed where an operation with at least a given set of functors is expected, and \
+            it lacks some of them.",
+        causes: &[
+            "The operation is missing `is Adj`, `is Ctl` or `is Adj + Ctl` in its signature. Add \
+            the functors it needs, and the compiler generates them from its body.",
+            "The operation was passed in as a parameter whose type doesn't list the functor. Add \
+            the functor to the type of the parameter, as in `op : (Qubit => Unit is Adj)`.",
+        ],
+        example: "operation Plain(q : Qubit) : Unit {}\n\n\
+            operation ApplyAdjoint(op : (Qubit => Unit is Adj), q : Qubit) : Unit {\n    \
+            Adjoint op(q);\n\
+            }\n\n\
+            operation Main(q : Qubit) : Unit {\n    \
+            ApplyAdjoint(Plain, q);\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.MissingItemTy",
+        meaning: "Types are inferred within callables, but not in the signatures of items, which \
+            need a type for every parameter.",
+        causes: &[
+            "A parameter of a callable is missing `: Type`. Add its type.",
+        ],
+        example: "function Twice(x) : Int {\n    \
+            2 * x\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.TyHole",
+        meaning: "An expression was left as a hole, `_`, which stands for code that hasn't been \
+            written yet. The message gives the type that the missing expression must have.",
+        causes: &[
+            "The hole was left while writing the code. Replace it with an expression of the given \
+            type.",
+        ],
+        example: "function Main() : Int {\n    \
+            _\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+    Explanation {
+        code: "Qsc.TypeCk.TyMismatch",
+        meaning: "An expression has a different type from the one its context needs, such as an \
+            argument whose type differs from the type of its parameter.",
+        causes: &[
+            "An `Int` was used where a `Double` is expected, or the other way around. Q# doesn't \
+            convert numbers implicitly, so write `1.0` rather than `1`, or convert with \
+            `IntAsDouble`.",
+            "A callable was called with too few or too many arguments, so the tuple of arguments \
+            has a different type from its input.",
+        ],
+        example: "function Main() : Double {\n    \
+            1\n\
+            }",
+        links: &[DOCUMENTATION],
+    },
+];
+
+/// Returns the explanation of the diagnostic code, if there is one.
+#[must_use]
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS
+        .binary_search_by(|explanation| explanation.code.cmp(code))
+        .ok()
+        .map(|index| &EXPLANATIONS[index])
+}
+
+/// Returns the explanation of the code of the diagnostic, if it has a code with an explanation.
+#[must_use]
+pub fn explain_diagnostic(diagnostic: &dyn Diagnostic) -> Option<&'static Explanation> {
+    explain(&diagnostic.code()?.to_string())
+}
+
+/// The codes that have explanations, in order.
+pub fn codes() -> impl Iterator<Item = &'static str> {
+    EXPLANATIONS.iter().map(|explanation| explanation.code)
+}
+
+impl Display for Explanation {
+    /// Formats the explanation as Markdown.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "# {}\n\n{}\n\n## Common causes\n",
+            self.code, self.meaning
+        )?;
+        for cause in self.causes {
+            writeln!(f, "- {cause}")?;
+        }
+        writeln!(f, "\n## Example\n\n```qsharp\n{}\n```", self.example)?;
+        if !self.links.is_empty() {
+            writeln!(f, "\n## Learn more\n")?;
+            for link in self.links {
+                writeln!(f, "- <{link}>")?;
+            }
+        }
+        Ok(())
+    }
+}
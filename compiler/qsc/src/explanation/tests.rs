@@ -0,0 +1,150 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{codes, explain, explain_diagnostic, EXPLANATIONS};
+use crate::{
+    compile::{self, Error},
+    message_catalog::MessageCatalog,
+};
+use expect_test::expect;
+use miette::Diagnostic;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+/// Compiles the source, returning its errors followed by its warnings.
+fn compile_errors(source: &str) -> Vec<Error> {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let (unit, mut errors) = compile::compile(
+        &store,
+        &[std],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    errors.extend(compile::warnings(&unit));
+    errors
+}
+
+#[test]
+fn codes_are_sorted_and_unique() {
+    let codes = codes().collect::<Vec<_>>();
+    assert!(codes.windows(2).all(|pair| pair[0] < pair[1]), "{codes:?}");
+}
+
+#[test]
+fn explain_finds_codes() {
+    for code in codes() {
+        assert_eq!(
+            explain(code).map(|explanation| explanation.code),
+            Some(code)
+        );
+    }
+    assert_eq!(explain("Qsc.TypeCk"), None);
+    assert_eq!(explain("Qsc.Parse.Token"), None);
+}
+
+#[test]
+fn explained_codes_are_in_the_catalog() {
+    let catalog = codes_in_catalog();
+    for code in codes() {
+        assert!(
+            catalog.contains(&code),
+            "{code} is not in the message catalog"
+        );
+    }
+}
+
+#[test]
+fn codes_of_explained_diagnostics_are_all_explained() {
+    let explained = [
+        "Qsc.Lint.",
+        "Qsc.QubitBudget.",
+        "Qsc.Resolve.",
+        "Qsc.TypeCk.",
+    ];
+    let unexplained = codes_in_catalog()
+        .into_iter()
+        .filter(|code| explained.iter().any(|&p| code.starts_with(p)) && explain(code).is_none())
+        .collect::<Vec<_>>();
+    assert!(unexplained.is_empty(), "{unexplained:?}");
+}
+
+fn codes_in_catalog() -> Vec<&'static str> {
+    MessageCatalog::default().codes().collect()
+}
+
+#[test]
+fn compile_time_examples_report_their_codes() {
+    let compiled = [
+        "Qsc.AdjGen.",
+        "Qsc.ConjugateInvert.",
+        "Qsc.CtlGen.",
+        "Qsc.Lint.",
+        "Qsc.Resolve.",
+        "Qsc.TypeCk.",
+    ];
+    for explanation in EXPLANATIONS
+        .iter()
+        .filter(|explanation| compiled.iter().any(|&p| explanation.code.starts_with(p)))
+    {
+        let source = if explanation.example.starts_with("namespace ") {
+            explanation.example.to_string()
+        } else {
+            format!("namespace Test {{\n{}\n}}", explanation.example)
+        };
+        let codes = compile_errors(&source)
+            .iter()
+            .filter_map(|error| error.code().map(|code| code.to_string()))
+            .collect::<Vec<_>>();
+        assert!(
+            codes.iter().any(|code| code == explanation.code),
+            "example of {} reports {codes:?}",
+            explanation.code
+        );
+    }
+}
+
+#[test]
+fn diagnostics_are_explained_by_their_codes() {
+    let errors = compile_errors(
+        "namespace Test { operation Plain(q : Qubit) : Unit {} operation Main(q : Qubit) : Unit \
+        { Adjoint Plain(q); } }",
+    );
+    let explained = errors
+        .iter()
+        .filter_map(|error| explain_diagnostic(error))
+        .map(|explanation| explanation.code)
+        .collect::<Vec<_>>();
+    assert_eq!(explained, ["Qsc.TypeCk.MissingFunctor"]);
+}
+
+#[test]
+fn explanation_is_markdown() {
+    let explanation = explain("Qsc.TypeCk.MissingClassCtl").expect("code should be explained");
+    expect![[r#"
+        # Qsc.TypeCk.MissingClassCtl
+
+        The `Controlled` functor can only be applied to operations, and it was applied to a value of another type.
+
+        ## Common causes
+
+        - A local variable shadows the operation that was meant to be controlled. Rename the variable.
+        - `Controlled` was applied to the result of calling an operation rather than to the operation. Write `Controlled Op([c], q)`, which applies `Op` to `q` controlled on `c`.
+
+        ## Example
+
+        ```qsharp
+        operation Main() : Unit {
+            let angle = 1.0;
+            let applied = Controlled angle;
+        }
+        ```
+
+        ## Learn more
+
+        - <https://aka.ms/AQ/Documentation>
+    "#]]
+    .assert_eq(&explanation.to_string());
+}
@@ -0,0 +1,856 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Static gate-count estimation. Counts are computed from the syntax of each callable without
+//! running a simulation: loops over ranges and arrays with constant bounds multiply the counts of
+//! their body, branches widen the counts to cover every arm, and anything whose cost can't be known
+//! statically, such as a `while` loop or a recursive call, leaves the affected counts unbounded.
+//!
+//! Specializations that are generated rather than written out are counted from the block they are
+//! generated from, with their functors applied to the calls in it, so that the adjoint of an
+//! operation that applies `T` counts the gates of the adjoint of `T`.
+//!
+//! The analysis works on the AST rather than the HIR because the default passes lower `for`
+//! loops into `while` loops, which loses the iteration counts.
+
+#[cfg(test)]
+mod tests;
+
+use crate::graph::namespace_of;
+use qsc_ast::{
+    ast::{
+        self, BinOp, Block, CallableBody, CallableDecl, CallableKind, Expr, ExprKind, Functor,
+        FunctorExpr, FunctorExprKind, Lit, QubitInit, QubitInitKind, SetOp, Spec, SpecBody,
+        SpecGen, Stmt, StmtKind, StringComponent, UnOp,
+    },
+    visit::{self, Visitor},
+};
+use qsc_frontend::{
+    compile::{CompileUnit, PackageStore},
+    resolve::Res,
+};
+use qsc_hir::hir::{self, ItemKind, LocalItemId, PackageId};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+    ops::{Add, Mul},
+};
+
+/// A count that is known to lie between a minimum and an optional maximum. A missing maximum
+/// means the count has no static upper bound.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bound {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Default for Bound {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Bound {
+    pub const ZERO: Self = Self::exact(0);
+
+    #[must_use]
+    pub const fn exact(count: u64) -> Self {
+        Self {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    #[must_use]
+    pub const fn at_least(min: u64) -> Self {
+        Self { min, max: None }
+    }
+
+    #[must_use]
+    pub fn is_exact(self) -> bool {
+        self.max == Some(self.min)
+    }
+
+    /// The bound of a value that is either `self` or `other`.
+    fn either(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+
+    /// The bound of the larger of `self` and `other`.
+    fn peak(self, other: Self) -> Self {
+        Self {
+            min: self.min.max(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+}
+
+impl Add for Bound {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            min: self.min.saturating_add(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.saturating_add(b)),
+        }
+    }
+}
+
+impl Mul for Bound {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let max = match (self.max, other.max) {
+            (Some(0), _) | (_, Some(0)) => Some(0),
+            (Some(a), Some(b)) => Some(a.saturating_mul(b)),
+            _ => None,
+        };
+        Self {
+            min: self.min.saturating_mul(other.min),
+            max,
+        }
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{}..{max}", self.min),
+            None => write!(f, "{}..", self.min),
+        }
+    }
+}
+
+/// The estimated cost of a callable or expression.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Estimate {
+    /// Calls to intrinsic operations, keyed by the name of the operation prefixed by any functors
+    /// applied to it, such as `Adjoint t__body`. The `__quantum__qis__` prefix shared by the
+    /// standard intrinsics is left out.
+    pub gates: BTreeMap<String, Bound>,
+    /// The largest number of qubits allocated at once.
+    pub qubits: Bound,
+    /// Calls whose target can't be determined statically, such as calls to callable values or
+    /// recursive calls. When there may be any, the gate and qubit counts have no upper bound.
+    pub unresolved_calls: Bound,
+}
+
+impl Estimate {
+    fn gate(name: String) -> Self {
+        Self {
+            gates: [(name, Bound::exact(1))].into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    fn unresolved() -> Self {
+        Self {
+            unresolved_calls: Bound::exact(1),
+            ..Self::default()
+        }
+    }
+
+    /// The total number of gates.
+    #[must_use]
+    pub fn total_gates(&self) -> Bound {
+        self.gates.values().fold(Bound::ZERO, |total, &b| total + b)
+    }
+
+    /// The cost of running `self` followed by `other`.
+    fn then(&mut self, other: Self) {
+        for (name, count) in other.gates {
+            let entry = self.gates.entry(name).or_insert(Bound::ZERO);
+            *entry = *entry + count;
+        }
+        self.qubits = self.qubits.peak(other.qubits);
+        self.unresolved_calls = self.unresolved_calls + other.unresolved_calls;
+    }
+
+    /// The cost of running `self` the given number of times.
+    fn repeat(mut self, times: Bound) -> Self {
+        for count in self.gates.values_mut() {
+            *count = *count * times;
+        }
+        self.unresolved_calls = self.unresolved_calls * times;
+        if times.max == Some(0) {
+            self.qubits = Bound::ZERO;
+        } else if times.min == 0 {
+            self.qubits = self.qubits.either(Bound::ZERO);
+        }
+        self
+    }
+
+    /// The cost of running either `self` or `other`.
+    fn either(mut self, mut other: Self) -> Self {
+        for name in other.gates.keys() {
+            self.gates.entry(name.clone()).or_insert(Bound::ZERO);
+        }
+        for (name, count) in &mut self.gates {
+            *count = count.either(other.gates.remove(name).unwrap_or(Bound::ZERO));
+        }
+        self.qubits = self.qubits.either(other.qubits);
+        self.unresolved_calls = self.unresolved_calls.either(other.unresolved_calls);
+        self
+    }
+
+    /// Removes the upper bounds that unresolved calls make unknowable.
+    fn seal(&mut self) {
+        if self.unresolved_calls.max != Some(0) {
+            for count in self.gates.values_mut() {
+                count.max = None;
+            }
+            self.qubits.max = None;
+        }
+    }
+}
+
+/// The estimates of the specializations computed by a [`GateCounter`]. They outlive the counter,
+/// so that a later counter for the same package store starts from them instead of estimating the
+/// callables again.
+#[derive(Debug, Default)]
+pub struct EstimateCache(FxHashMap<(PackageId, LocalItemId, Spec), Estimate>);
+
+/// Computes and caches gate-count estimates for the callables in a package store.
+pub struct GateCounter<'a> {
+    store: &'a PackageStore,
+    decls: FxHashMap<PackageId, FxHashMap<LocalItemId, &'a CallableDecl>>,
+    estimates: EstimateCache,
+    active: FxHashSet<(PackageId, LocalItemId, Spec)>,
+}
+
+impl<'a> GateCounter<'a> {
+    #[must_use]
+    pub fn new(store: &'a PackageStore) -> Self {
+        Self::with_cache(store, EstimateCache::default())
+    }
+
+    /// A counter that starts from the estimates in the cache, which must have been computed for
+    /// the same package store.
+    #[must_use]
+    pub fn with_cache(store: &'a PackageStore, cache: EstimateCache) -> Self {
+        Self {
+            store,
+            decls: FxHashMap::default(),
+            estimates: cache,
+            active: FxHashSet::default(),
+        }
+    }
+
+    /// The estimates computed so far, including the ones the counter started from.
+    #[must_use]
+    pub fn into_cache(self) -> EstimateCache {
+        self.estimates
+    }
+
+    /// Estimates the body of the callable item, or returns `None` if the item isn't a callable.
+    pub fn callable(&mut self, package: PackageId, item: LocalItemId) -> Option<Estimate> {
+        self.decl(package, item)?;
+        Some(self.spec(package, item, Spec::Body))
+    }
+
+    /// Estimates every operation declared in the package, keyed by fully qualified name.
+    pub fn package(&mut self, package: PackageId) -> BTreeMap<String, Estimate> {
+        let mut table = BTreeMap::new();
+        let Some(unit) = self.store.get(package) else {
+            return table;
+        };
+        for item in unit.package.items.values() {
+            if let ItemKind::Callable(decl) = &item.kind {
+                if decl.kind == hir::CallableKind::Operation {
+                    let namespace = namespace_of(&unit.package, item.parent);
+                    let name = if namespace.is_empty() {
+                        decl.name.name.to_string()
+                    } else {
+                        format!("{namespace}.{}", decl.name.name)
+                    };
+                    if let Some(estimate) = self.callable(package, item.id) {
+                        table.insert(name, estimate);
+                    }
+                }
+            }
+        }
+        table
+    }
+
+    /// Estimates the whole program: the entry point callable of the package if it has one, or
+    /// otherwise its entry expression.
+    pub fn program(&mut self, package: PackageId) -> Option<Estimate> {
+        let unit = self.store.get(package)?;
+        let entry_point = unit.package.items.values().find(|item| {
+            matches!(item.kind, ItemKind::Callable(_))
+                && item.attrs.contains(&hir::Attr::EntryPoint)
+        });
+        match (entry_point, &unit.ast.package.entry) {
+            (Some(item), _) => self.callable(package, item.id),
+            (None, Some(entry)) => {
+                let mut estimate = Walker::new(self, package).expr(entry);
+                estimate.seal();
+                Some(estimate)
+            }
+            (None, None) => None,
+        }
+    }
+
+    fn decl(&mut self, package: PackageId, item: LocalItemId) -> Option<&'a CallableDecl> {
+        let store = self.store;
+        let decls = self.decls.entry(package).or_insert_with(|| {
+            let mut collector = DeclCollector {
+                package: store.get(package),
+                decls: FxHashMap::default(),
+            };
+            if let Some(unit) = store.get(package) {
+                collector.visit_package(&unit.ast.package);
+            }
+            collector.decls
+        });
+        decls.get(&item).copied()
+    }
+
+    fn spec(&mut self, package: PackageId, item: LocalItemId, spec: Spec) -> Estimate {
+        let key = (package, item, spec);
+        if let Some(estimate) = self.estimates.0.get(&key) {
+            return estimate.clone();
+        }
+        if !self.active.insert(key) {
+            return Estimate::unresolved();
+        }
+
+        let mut estimate = Estimate::default();
+        if let Some(decl) = self.decl(package, item) {
+            if decl.kind == CallableKind::Operation {
+                if let Some((block, functors)) = spec_impl(decl, spec) {
+                    let mut walker = Walker::new(self, package);
+                    walker.functors = functors;
+                    estimate = walker.callable_block(block);
+                }
+            }
+        }
+        estimate.seal();
+
+        self.active.remove(&key);
+        self.estimates.0.insert(key, estimate.clone());
+        estimate
+    }
+}
+
+/// Formats estimates as a table with a row for each gate of each callable.
+#[must_use]
+pub fn format_table(estimates: &BTreeMap<String, Estimate>) -> String {
+    let mut rows = vec![[
+        "Callable".to_string(),
+        "Gate".to_string(),
+        "Count".to_string(),
+    ]];
+    for (name, estimate) in estimates {
+        rows.push([
+            name.clone(),
+            "(qubits)".to_string(),
+            estimate.qubits.to_string(),
+        ]);
+        for (gate, count) in &estimate.gates {
+            rows.push([String::new(), gate.clone(), count.to_string()]);
+        }
+        if estimate.unresolved_calls != Bound::ZERO {
+            rows.push([
+                String::new(),
+                "(unresolved calls)".to_string(),
+                estimate.unresolved_calls.to_string(),
+            ]);
+        }
+    }
+
+    let widths = [0, 1].map(|column| {
+        rows.iter()
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or_default()
+    });
+    let mut table = String::new();
+    for row in rows {
+        let _ = writeln!(
+            table,
+            "{:w0$}  {:w1$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            w0 = widths[0],
+            w1 = widths[1]
+        );
+    }
+    table
+}
+
+/// The functors applied to the calls in a block.
+#[derive(Clone, Copy, Debug, Default)]
+struct Functors {
+    adjoint: bool,
+    controlled: bool,
+}
+
+/// Walks the syntax of a single callable or expression, accumulating its cost.
+struct Walker<'a, 'b> {
+    counter: &'b mut GateCounter<'a>,
+    package: PackageId,
+    functors: Functors,
+    may_return: bool,
+    /// The number of items of the immutable array and range locals whose lengths are constant,
+    /// such as qubit arrays allocated with a constant size, keyed by the ID of their bindings.
+    lengths: FxHashMap<ast::NodeId, u64>,
+}
+
+impl<'a, 'b> Walker<'a, 'b> {
+    fn new(counter: &'b mut GateCounter<'a>, package: PackageId) -> Self {
+        Self {
+            counter,
+            package,
+            functors: Functors::default(),
+            may_return: false,
+            lengths: FxHashMap::default(),
+        }
+    }
+
+    fn callable_block(mut self, block: &Block) -> Estimate {
+        // A return at the very end of the callable doesn't skip anything.
+        let (last, rest) = match block.stmts.split_last() {
+            Some((last, rest)) => match &*last.kind {
+                StmtKind::Expr(expr) | StmtKind::Semi(expr) => match &*expr.kind {
+                    ExprKind::Return(value) => (Some(value), rest),
+                    _ => (None, &*block.stmts),
+                },
+                _ => (None, &*block.stmts),
+            },
+            None => (None, &*block.stmts),
+        };
+
+        let mut estimate = self.stmts(rest);
+        if let Some(value) = last {
+            estimate.then(self.expr(value));
+        }
+        if self.may_return {
+            // An early return may skip any of the gates.
+            for count in estimate.gates.values_mut() {
+                count.min = 0;
+            }
+            estimate.unresolved_calls.min = 0;
+        }
+        estimate
+    }
+
+    fn block(&mut self, block: &Block) -> Estimate {
+        self.stmts(&block.stmts)
+    }
+
+    fn stmts(&mut self, stmts: &[Box<Stmt>]) -> Estimate {
+        let mut estimate = Estimate::default();
+        let mut held = Bound::ZERO;
+        for stmt in stmts {
+            match &*stmt.kind {
+                StmtKind::Qubit(_, pat, init, scope) => {
+                    let (count, mut cost) = self.qubit_init(init);
+                    if let (QubitInitKind::Array(_), Some(len)) = (&*init.kind, count.max) {
+                        self.bind_length(pat, len);
+                    }
+                    match scope {
+                        Some(scope) => {
+                            let mut inner = self.block(scope);
+                            inner.qubits = held + count + inner.qubits;
+                            cost.then(inner);
+                        }
+                        None => {
+                            held = held + count;
+                            cost.qubits = cost.qubits.peak(held);
+                        }
+                    }
+                    estimate.then(cost);
+                }
+                StmtKind::Local(mutability, pat, expr) => {
+                    if *mutability == ast::Mutability::Immutable {
+                        if let Some(len) = self.iterations(expr).max {
+                            self.bind_length(pat, len);
+                        }
+                    }
+                    let mut cost = self.expr(expr);
+                    cost.qubits = held + cost.qubits;
+                    estimate.then(cost);
+                }
+                StmtKind::Expr(expr) | StmtKind::Semi(expr) => {
+                    let mut cost = self.expr(expr);
+                    cost.qubits = held + cost.qubits;
+                    estimate.then(cost);
+                }
+                StmtKind::Empty | StmtKind::Item(_) | StmtKind::Err => {}
+            }
+        }
+        estimate
+    }
+
+    fn qubit_init(&mut self, init: &QubitInit) -> (Bound, Estimate) {
+        match &*init.kind {
+            QubitInitKind::Array(size) => {
+                let count = const_int(size)
+                    .and_then(|size| u64::try_from(size).ok())
+                    .map_or(Bound::at_least(0), Bound::exact);
+                (count, self.expr(size))
+            }
+            QubitInitKind::Paren(init) => self.qubit_init(init),
+            QubitInitKind::Single => (Bound::exact(1), Estimate::default()),
+            QubitInitKind::Tuple(inits) => {
+                let mut total = (Bound::ZERO, Estimate::default());
+                for init in inits.iter() {
+                    let (count, cost) = self.qubit_init(init);
+                    total.0 = total.0 + count;
+                    total.1.then(cost);
+                }
+                total
+            }
+            QubitInitKind::Err => (Bound::ZERO, Estimate::default()),
+        }
+    }
+
+    /// Records the length of the array or range bound by the pattern, if it binds a single name.
+    fn bind_length(&mut self, pat: &ast::Pat, len: u64) {
+        match &*pat.kind {
+            ast::PatKind::Bind(ident, _) => {
+                self.lengths.insert(ident.id, len);
+            }
+            ast::PatKind::Paren(pat) => self.bind_length(pat, len),
+            _ => {}
+        }
+    }
+
+    /// The number of iterations of a `for` loop over the given expression, which is also the
+    /// length of the expression if it is an array.
+    fn iterations(&self, iter: &Expr) -> Bound {
+        match &*iter.kind {
+            ExprKind::Paren(inner) => self.iterations(inner),
+            ExprKind::Array(items) => Bound::exact(items.len() as u64),
+            ExprKind::ArrayRepeat(_, size) => const_int(size)
+                .and_then(|size| u64::try_from(size).ok())
+                .map_or(Bound::at_least(0), Bound::exact),
+            ExprKind::Range(Some(start), step, Some(end)) => {
+                let step = step.as_ref().map_or(Some(1), |step| const_int(step));
+                match (const_int(start), step, const_int(end)) {
+                    (Some(start), Some(step), Some(end)) => {
+                        Bound::exact(range_len(start, step, end))
+                    }
+                    _ => Bound::at_least(0),
+                }
+            }
+            ExprKind::Path(path) => self
+                .counter
+                .store
+                .get(self.package)
+                .and_then(|unit| match unit.ast.names.get(path.id) {
+                    Some(Res::Local(id)) => self.lengths.get(id).copied(),
+                    _ => None,
+                })
+                .map_or(Bound::at_least(0), Bound::exact),
+            _ => Bound::at_least(0),
+        }
+    }
+
+    fn exprs<'e>(&mut self, exprs: impl IntoIterator<Item = &'e Expr>) -> Estimate {
+        let mut estimate = Estimate::default();
+        for expr in exprs {
+            estimate.then(self.expr(expr));
+        }
+        estimate
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Estimate {
+        match &*expr.kind {
+            ExprKind::Call(callee, arg) => {
+                let mut estimate = self.exprs([&**callee, &**arg]);
+                estimate.then(self.call(callee));
+                estimate
+            }
+            ExprKind::Conjugate(within, apply) => {
+                // The within block is applied, and then its adjoint, without the functors of the
+                // conjugation, which only apply to the apply block.
+                let functors = self.functors;
+                self.functors = Functors::default();
+                let mut estimate = self.block(within);
+                self.functors.adjoint = true;
+                let undo = self.block(within);
+                self.functors = functors;
+                estimate.then(self.block(apply));
+                estimate.then(undo);
+                estimate
+            }
+            ExprKind::For(_, iter, body) => {
+                let mut estimate = self.expr(iter);
+                let iterations = self.iterations(iter);
+                estimate.then(self.block(body).repeat(iterations));
+                estimate
+            }
+            ExprKind::If(cond, then, otherwise) => {
+                let mut estimate = self.expr(cond);
+                let otherwise = otherwise
+                    .as_ref()
+                    .map_or_else(Estimate::default, |e| self.expr(e));
+                estimate.then(self.block(then).either(otherwise));
+                estimate
+            }
+            ExprKind::Repeat(body, until, fixup) => {
+                let mut iteration = self.block(body);
+                iteration.then(self.expr(until));
+                let mut estimate = iteration.repeat(Bound::at_least(1));
+                if let Some(fixup) = fixup {
+                    estimate.then(self.block(fixup).repeat(Bound::at_least(0)));
+                }
+                estimate
+            }
+            ExprKind::While(cond, body) => {
+                let mut estimate = self.expr(cond);
+                let mut iteration = self.block(body);
+                iteration.then(self.expr(cond));
+                estimate.then(iteration.repeat(Bound::at_least(0)));
+                estimate
+            }
+            ExprKind::Return(value) => {
+                self.may_return = true;
+                self.expr(value)
+            }
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                self.exprs(items.iter().map(|e| &**e))
+            }
+            ExprKind::ArrayRepeat(item, size) => {
+                let mut estimate = self.expr(size);
+                let times = const_int(size)
+                    .and_then(|size| u64::try_from(size).ok())
+                    .map_or(Bound::at_least(0), Bound::exact);
+                estimate.then(self.expr(item).repeat(times));
+                estimate
+            }
+            ExprKind::Assign(a, b)
+            | ExprKind::AssignOp(_, a, b)
+            | ExprKind::BinOp(_, a, b)
+            | ExprKind::Index(a, b) => self.exprs([&**a, &**b]),
+            ExprKind::AssignUpdate(a, b, c) => self.exprs([&**a, &**b, &**c]),
+            ExprKind::TernOp(ast::TernOp::Cond, cond, a, b) => {
+                let mut estimate = self.expr(cond);
+                let a = self.expr(a);
+                estimate.then(a.either(self.expr(b)));
+                estimate
+            }
+            ExprKind::TernOp(ast::TernOp::Update, a, b, c) => self.exprs([&**a, &**b, &**c]),
+            ExprKind::Fail(e)
+            | ExprKind::Field(e, _)
+            | ExprKind::Paren(e)
+            | ExprKind::UnOp(_, e) => self.expr(e),
+            ExprKind::Interpolate(components) => {
+                self.exprs(components.iter().filter_map(|c| match c {
+                    StringComponent::Expr(e) => Some(&**e),
+                    StringComponent::Lit(_) => None,
+                }))
+            }
+            ExprKind::Range(start, step, end) => {
+                self.exprs([start, step, end].into_iter().flatten().map(|e| &**e))
+            }
+            ExprKind::Err
+            | ExprKind::Hole
+            | ExprKind::Lambda(..)
+            | ExprKind::Lit(_)
+            | ExprKind::Path(_) => Estimate::default(),
+        }
+    }
+
+    /// The cost of the callee itself, not including the evaluation of the callee expression.
+    fn call(&mut self, callee: &Expr) -> Estimate {
+        let Functors {
+            mut adjoint,
+            mut controlled,
+        } = self.functors;
+        let mut callee = callee;
+        let path = loop {
+            match &*callee.kind {
+                ExprKind::Paren(inner) => callee = inner,
+                ExprKind::UnOp(UnOp::Functor(Functor::Adj), inner) => {
+                    adjoint = !adjoint;
+                    callee = inner;
+                }
+                ExprKind::UnOp(UnOp::Functor(Functor::Ctl), inner) => {
+                    controlled = true;
+                    callee = inner;
+                }
+                ExprKind::Path(path) => break path,
+                _ => return Estimate::unresolved(),
+            }
+        };
+
+        let store = self.counter.store;
+        let Some(unit) = store.get(self.package) else {
+            return Estimate::unresolved();
+        };
+        let Some(Res::Item(id, _)) = unit.ast.names.get(path.id) else {
+            return Estimate::unresolved();
+        };
+        let package = id.package.unwrap_or(self.package);
+        let Some(decl) = self.counter.decl(package, id.item) else {
+            // Calls to items other than callables, such as type constructors, don't cost anything.
+            let is_callable = store
+                .get(package)
+                .and_then(|unit| unit.package.items.get(id.item))
+                .map_or(true, |item| matches!(item.kind, ItemKind::Callable(_)));
+            return if is_callable {
+                Estimate::unresolved()
+            } else {
+                Estimate::default()
+            };
+        };
+
+        if decl.kind == CallableKind::Function {
+            Estimate::default()
+        } else if is_intrinsic(decl) {
+            // Intrinsics that don't support a functor are their own adjoint, such as `h__body`.
+            let mut name = String::new();
+            if controlled && supports(decl, Functor::Ctl) {
+                name.push_str("Controlled ");
+            }
+            if adjoint && supports(decl, Functor::Adj) {
+                name.push_str("Adjoint ");
+            }
+            let base: &str = &decl.name.name;
+            name.push_str(base.strip_prefix("__quantum__qis__").unwrap_or(base));
+            Estimate::gate(name)
+        } else {
+            let spec = match (adjoint, controlled) {
+                (false, false) => Spec::Body,
+                (true, false) => Spec::Adj,
+                (false, true) => Spec::Ctl,
+                (true, true) => Spec::CtlAdj,
+            };
+            self.counter.spec(package, id.item, spec)
+        }
+    }
+}
+
+/// Collects the callable declarations in a package, keyed by item ID.
+struct DeclCollector<'a> {
+    package: Option<&'a CompileUnit>,
+    decls: FxHashMap<LocalItemId, &'a CallableDecl>,
+}
+
+impl<'a> Visitor<'a> for DeclCollector<'a> {
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        if let Some(Res::Item(id, _)) = self
+            .package
+            .and_then(|unit| unit.ast.names.get(decl.name.id))
+        {
+            if id.package.is_none() {
+                self.decls.insert(id.item, decl);
+            }
+        }
+        visit::walk_callable_decl(self, decl);
+    }
+}
+
+/// The block that a specialization runs and the functors it applies to the calls in the block.
+/// A generated specialization runs the block of the specialization it is generated from.
+fn spec_impl(decl: &CallableDecl, spec: Spec) -> Option<(&Block, Functors)> {
+    let body = match &*decl.body {
+        CallableBody::Block(block) if spec == Spec::Body => {
+            return Some((&**block, Functors::default()))
+        }
+        CallableBody::Block(_) => None,
+        CallableBody::Specs(specs) => specs.iter().find(|s| s.spec == spec).map(|s| &s.body),
+    };
+    match body {
+        Some(SpecBody::Impl(_, block)) => Some((&**block, Functors::default())),
+        Some(SpecBody::Gen(SpecGen::Intrinsic)) => None,
+        Some(SpecBody::Gen(gen)) => generated_impl(decl, spec, Some(*gen)),
+        None => generated_impl(decl, spec, None),
+    }
+}
+
+/// The block that a generated specialization runs, from the generator given for it, if any.
+fn generated_impl(
+    decl: &CallableDecl,
+    spec: Spec,
+    gen: Option<SpecGen>,
+) -> Option<(&Block, Functors)> {
+    let (from, adjoint, controlled) = match (spec, gen) {
+        (Spec::Body, _) => return None,
+        (Spec::Adj, Some(SpecGen::Slf)) => (Spec::Body, false, false),
+        (Spec::Adj, _) => (Spec::Body, true, false),
+        (Spec::Ctl, _) => (Spec::Body, false, true),
+        (Spec::CtlAdj, Some(SpecGen::Slf)) => (Spec::Ctl, false, false),
+        (Spec::CtlAdj, Some(SpecGen::Invert)) => (Spec::Ctl, true, false),
+        (Spec::CtlAdj, _) => (Spec::Adj, false, true),
+    };
+    let (block, functors) = spec_impl(decl, from)?;
+    Some((
+        block,
+        Functors {
+            adjoint: functors.adjoint != adjoint,
+            controlled: functors.controlled || controlled,
+        },
+    ))
+}
+
+/// Whether the callable declares that it supports the functor.
+fn supports(decl: &CallableDecl, functor: Functor) -> bool {
+    fn contains(expr: &FunctorExpr, functor: Functor) -> bool {
+        match &*expr.kind {
+            FunctorExprKind::BinOp(SetOp::Union, a, b) => {
+                contains(a, functor) || contains(b, functor)
+            }
+            FunctorExprKind::BinOp(SetOp::Intersect, a, b) => {
+                contains(a, functor) && contains(b, functor)
+            }
+            FunctorExprKind::Lit(lit) => *lit == functor,
+            FunctorExprKind::Paren(inner) => contains(inner, functor),
+        }
+    }
+    decl.functors
+        .as_ref()
+        .is_some_and(|functors| contains(functors, functor))
+}
+
+fn is_intrinsic(decl: &CallableDecl) -> bool {
+    match &*decl.body {
+        CallableBody::Block(_) => false,
+        CallableBody::Specs(specs) => specs
+            .iter()
+            .any(|s| s.spec == Spec::Body && s.body == SpecBody::Gen(SpecGen::Intrinsic)),
+    }
+}
+
+fn range_len(start: i64, step: i64, end: i64) -> u64 {
+    let (distance, step) = match step.cmp(&0) {
+        std::cmp::Ordering::Greater if end >= start => (end.abs_diff(start), step.unsigned_abs()),
+        std::cmp::Ordering::Less if end <= start => (start.abs_diff(end), step.unsigned_abs()),
+        _ => return 0,
+    };
+    distance / step + 1
+}
+
+/// Evaluates an integer expression made only of literals and arithmetic.
+fn const_int(expr: &Expr) -> Option<i64> {
+    match &*expr.kind {
+        ExprKind::Lit(lit) => match **lit {
+            Lit::Int(value) => Some(value),
+            _ => None,
+        },
+        ExprKind::Paren(inner) | ExprKind::UnOp(UnOp::Pos, inner) => const_int(inner),
+        ExprKind::UnOp(UnOp::Neg, inner) => const_int(inner)?.checked_neg(),
+        ExprKind::BinOp(op, a, b) => {
+            let (a, b) = (const_int(a)?, const_int(b)?);
+            match op {
+                BinOp::Add => a.checked_add(b),
+                BinOp::Sub => a.checked_sub(b),
+                BinOp::Mul => a.checked_mul(b),
+                BinOp::Div => a.checked_div(b),
+                BinOp::Mod => a.checked_rem(b),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{format_table, Bound, GateCounter};
+use crate::compile;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+
+fn compile_package(source: &str) -> (PackageStore, PackageId) {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(&store, &[std], sources, PackageType::Lib, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    (store, package)
+}
+
+fn check_table(source: &str, expect: &Expect) {
+    let (store, package) = compile_package(source);
+    let table = GateCounter::new(&store).package(package);
+    expect.assert_eq(&format_table(&table));
+}
+
+#[test]
+fn bound_display() {
+    assert_eq!(Bound::exact(3).to_string(), "3");
+    assert_eq!(
+        Bound {
+            min: 2,
+            max: Some(5)
+        }
+        .to_string(),
+        "2..5"
+    );
+    assert_eq!(Bound::at_least(1).to_string(), "1..");
+}
+
+#[test]
+fn straight_line_calls_are_exact() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Bell(q0 : Qubit, q1 : Qubit) : Unit {
+                    H(q0);
+                    CNOT(q0, q1);
+                }
+                operation Main() : Unit {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    Bell(q0, q1);
+                    Bell(q0, q1);
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate      Count
+            Test.Bell  (qubits)  0
+                       cx__body  1
+                       h__body   1
+            Test.Main  (qubits)  2
+                       cx__body  2
+                       h__body   2
+        "#]],
+    );
+}
+
+#[test]
+fn constant_loops_multiply_counts() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use qs = Qubit[2 * 3];
+                    for i in 0..2..9 {
+                        T(qs[0]);
+                    }
+                    for q in [qs[0], qs[1]] {
+                        Adjoint T(q);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate      Count
+            Test.Main  (qubits)  6
+                       t__adj    2
+                       t__body   5
+        "#]],
+    );
+}
+
+#[test]
+fn loops_over_constant_length_locals_multiply_counts() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use qs = Qubit[3];
+                    for q in qs {
+                        H(q);
+                    }
+                    let angles = [0.1, 0.2];
+                    for angle in angles {
+                        Rx(angle, qs[0]);
+                    }
+                    mutable flips = [1];
+                    set flips += [2];
+                    for _ in flips {
+                        X(qs[0]);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate      Count
+            Test.Main  (qubits)  3
+                       h__body   3
+                       rx__body  2
+                       x__body   0..
+        "#]],
+    );
+}
+
+#[test]
+fn dynamic_loops_and_branches_are_bounded() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Main(n : Int) : Unit {
+                    use q = Qubit();
+                    if n > 0 {
+                        X(q);
+                        X(q);
+                    } else {
+                        X(q);
+                    }
+                    for _ in 1..n {
+                        H(q);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate      Count
+            Test.Main  (qubits)  1
+                       h__body   0..
+                       x__body   1..2
+        "#]],
+    );
+}
+
+#[test]
+fn conjugation_counts_within_block_twice() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use q = Qubit();
+                    within {
+                        H(q);
+                    } apply {
+                        Z(q);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate      Count
+            Test.Main  (qubits)  1
+                       h__body   2
+                       z__body   1
+        "#]],
+    );
+}
+
+#[test]
+fn generated_specializations_apply_their_functors() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Prepare(q : Qubit) : Unit is Adj + Ctl {
+                    H(q);
+                    T(q);
+                }
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Adjoint Prepare(q);
+                    within {
+                        S(q);
+                    } apply {
+                        Prepare(q);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable      Gate      Count
+            Test.Main     (qubits)  1
+                          h__body   2
+                          s__adj    1
+                          s__body   1
+                          t__adj    1
+                          t__body   1
+            Test.Prepare  (qubits)  0
+                          h__body   1
+                          t__body   1
+        "#]],
+    );
+}
+
+#[test]
+fn recursion_is_unbounded() {
+    check_table(
+        indoc! {"
+            namespace Test {
+                operation Down(q : Qubit, n : Int) : Unit {
+                    if n > 0 {
+                        X(q);
+                        Down(q, n - 1);
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            Callable   Gate                Count
+            Test.Down  (qubits)            0..
+                       x__body             0..
+                       (unresolved calls)  0..1
+        "#]],
+    );
+}
+
+#[test]
+fn program_uses_entry_point() {
+    let (store, package) = compile_package(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                M(q)
+            }
+        }
+    "});
+    let estimate = GateCounter::new(&store)
+        .program(package)
+        .expect("package should have an entry point");
+    assert_eq!(estimate.qubits, Bound::exact(1));
+    assert_eq!(estimate.unresolved_calls, Bound::ZERO);
+    assert!(estimate.total_gates().is_exact());
+    assert_eq!(estimate.gates.get("h__body"), Some(&Bound::exact(1)));
+}
+
+#[test]
+fn cached_estimates_are_reused_by_a_new_counter() {
+    let (store, package) = compile_package(indoc! {"
+        namespace Test {
+            operation Prepare(q : Qubit) : Unit {
+                H(q);
+            }
+            operation Main() : Unit {
+                use q = Qubit();
+                Prepare(q);
+                Prepare(q);
+            }
+        }
+    "});
+    let mut counter = GateCounter::new(&store);
+    let estimates = counter.package(package);
+    let cache = counter.into_cache();
+    let cached = cache.0.len();
+    assert!(cached > 0);
+
+    let mut counter = GateCounter::with_cache(&store, cache);
+    assert_eq!(counter.package(package), estimates);
+    assert_eq!(counter.into_cache().0.len(), cached);
+}
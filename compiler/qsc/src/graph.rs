@@ -0,0 +1,408 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    hir::{
+        CallableDecl, Expr, ExprKind, ItemId, ItemKind, LocalItemId, Package, PackageId, Pat, Res,
+        UnOp,
+    },
+    ty::Ty,
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Serialize, Serializer};
+use std::{fmt::Write, sync::Arc};
+
+/// The kind of dependency that an edge represents.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    /// A callable calls, or refers to, another callable.
+    Call,
+    /// A callable applies a functor to another callable.
+    Functor,
+    /// A callable uses a user-defined type.
+    Udt,
+    /// A package refers to items in another package.
+    Package,
+}
+
+impl EdgeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::Call => "call",
+            EdgeKind::Functor => "functor",
+            EdgeKind::Udt => "udt",
+            EdgeKind::Package => "package",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    Callable,
+    Udt,
+    Package,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Node {
+    pub kind: NodeKind,
+    #[serde(serialize_with = "serialize_package_id")]
+    pub package: PackageId,
+    /// The namespace of the item, or empty for packages and items declared outside of a namespace.
+    pub namespace: Arc<str>,
+    pub name: Arc<str>,
+}
+
+impl Node {
+    fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.to_string()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+}
+
+/// An edge between the nodes at the given indices.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    /// Renders the graph in the Graphviz DOT language.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph {\n".to_string();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(
+                dot,
+                "    n{index} [label=\"{}\"];",
+                escape_dot(&node.qualified_name())
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"];",
+                edge.from,
+                edge.to,
+                edge.kind.as_str()
+            );
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Renders the graph as a JSON object with `nodes` and `edges` arrays. Edges refer to nodes by
+    /// their index in the `nodes` array.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("graph should serialize")
+    }
+}
+
+/// Escapes a string for use in a DOT string literal.
+fn escape_dot(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serializes a package ID as its number.
+// Serde passes the field by reference.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub(crate) fn serialize_package_id<S: Serializer>(
+    id: &PackageId,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    usize::from(*id).serialize(serializer)
+}
+
+/// Selects the parts of a call graph to include.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    /// The namespaces whose items are included, along with the namespaces nested in them.
+    /// Items from every namespace are included if this is empty.
+    pub namespaces: Vec<String>,
+    pub calls: bool,
+    pub functors: bool,
+    pub udts: bool,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            namespaces: Vec::new(),
+            calls: true,
+            functors: true,
+            udts: true,
+        }
+    }
+}
+
+impl Filter {
+    fn includes_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.is_empty()
+            || self.namespaces.iter().any(|filter| {
+                namespace
+                    .strip_prefix(filter.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+
+    fn includes_edge(&self, kind: EdgeKind) -> bool {
+        match kind {
+            EdgeKind::Call => self.calls,
+            EdgeKind::Functor => self.functors,
+            EdgeKind::Udt => self.udts,
+            EdgeKind::Package => true,
+        }
+    }
+}
+
+/// Builds the call graph of the callables and user-defined types declared in a package. Items
+/// from other packages are included when the package's callables refer to them.
+///
+/// # Panics
+///
+/// Panics if the package is not in the store.
+#[must_use]
+pub fn call_graph(store: &PackageStore, package: PackageId, filter: &Filter) -> Graph {
+    let unit = store.get(package).expect("package should be in store");
+    let mut builder = Builder {
+        store,
+        filter,
+        graph: Graph::default(),
+        nodes: FxHashMap::default(),
+        edges: FxHashSet::default(),
+    };
+
+    for item in unit.package.items.values() {
+        let id = ItemId {
+            package: Some(package),
+            item: item.id,
+        };
+        match &item.kind {
+            ItemKind::Callable(decl) => {
+                if let Some(from) = builder.item_node(id) {
+                    let mut refs = References::new(package);
+                    refs.visit_callable_decl(decl);
+                    for (to, kind) in refs.refs {
+                        builder.item_edge(from, to, kind);
+                    }
+                }
+            }
+            ItemKind::Ty(..) => {
+                builder.item_node(id);
+            }
+            ItemKind::Namespace(..) => {}
+        }
+    }
+
+    builder.graph
+}
+
+/// Builds the graph of dependencies between the packages in the store.
+#[must_use]
+pub fn package_graph(store: &PackageStore) -> Graph {
+    let mut graph = Graph::default();
+    let mut nodes = FxHashMap::default();
+    for (id, _) in store {
+        nodes.insert(id, graph.nodes.len());
+        graph.nodes.push(Node {
+            kind: NodeKind::Package,
+            package: id,
+            namespace: "".into(),
+            name: format!("package {id}").into(),
+        });
+    }
+
+    let mut edges = FxHashSet::default();
+    for (id, unit) in store {
+        let mut refs = References::new(id);
+        refs.visit_package(&unit.package);
+        for (item, _) in refs.refs {
+            let dependency = item.package.expect("reference should have a package");
+            if dependency == id {
+                continue;
+            }
+            if let Some(&to) = nodes.get(&dependency) {
+                let edge = Edge {
+                    from: nodes[&id],
+                    to,
+                    kind: EdgeKind::Package,
+                };
+                if edges.insert(edge) {
+                    graph.edges.push(edge);
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+struct Builder<'a> {
+    store: &'a PackageStore,
+    filter: &'a Filter,
+    graph: Graph,
+    nodes: FxHashMap<ItemId, usize>,
+    edges: FxHashSet<Edge>,
+}
+
+impl Builder<'_> {
+    /// Returns the node for the item, adding it to the graph if needed, or `None` if the item is
+    /// excluded by the filter.
+    fn item_node(&mut self, id: ItemId) -> Option<usize> {
+        if let Some(&node) = self.nodes.get(&id) {
+            return Some(node);
+        }
+
+        let store = self.store;
+        let package = id.package.expect("item id should have a package");
+        let items = &store.get(package)?.package;
+        let item = items.items.get(id.item)?;
+        let (kind, name) = match &item.kind {
+            ItemKind::Callable(decl) => (NodeKind::Callable, &decl.name.name),
+            ItemKind::Ty(name, _) => (NodeKind::Udt, &name.name),
+            ItemKind::Namespace(..) => return None,
+        };
+
+        let namespace = namespace_of(items, item.parent);
+        if !self.filter.includes_namespace(&namespace) {
+            return None;
+        }
+
+        let node = self.graph.nodes.len();
+        self.graph.nodes.push(Node {
+            kind,
+            package,
+            namespace,
+            name: Arc::clone(name),
+        });
+        self.nodes.insert(id, node);
+        Some(node)
+    }
+
+    fn item_edge(&mut self, from: usize, to: ItemId, kind: EdgeKind) {
+        let Some(to) = self.item_node(to) else {
+            return;
+        };
+
+        // References to a newtype's constructor are uses of the type.
+        let kind = if self.graph.nodes[to].kind == NodeKind::Udt {
+            EdgeKind::Udt
+        } else {
+            kind
+        };
+
+        let edge = Edge { from, to, kind };
+        if self.filter.includes_edge(kind) && self.edges.insert(edge) {
+            self.graph.edges.push(edge);
+        }
+    }
+}
+
+pub(crate) fn namespace_of(package: &Package, mut parent: Option<LocalItemId>) -> Arc<str> {
+    while let Some(item) = parent.and_then(|id| package.items.get(id)) {
+        if let ItemKind::Namespace(name, _) = &item.kind {
+            return Arc::clone(&name.name);
+        }
+        parent = item.parent;
+    }
+    "".into()
+}
+
+/// Collects the items referred to by HIR nodes, along with the kind of each reference.
+pub(crate) struct References {
+    package: PackageId,
+    pub(crate) refs: Vec<(ItemId, EdgeKind)>,
+}
+
+impl References {
+    pub(crate) fn new(package: PackageId) -> Self {
+        Self {
+            package,
+            refs: Vec::new(),
+        }
+    }
+
+    fn res(&mut self, res: &Res, kind: EdgeKind) {
+        if let Res::Item(id) = res {
+            self.refs.push((
+                ItemId {
+                    package: Some(id.package.unwrap_or(self.package)),
+                    item: id.item,
+                },
+                kind,
+            ));
+        }
+    }
+
+    fn ty(&mut self, ty: &Ty) {
+        match ty {
+            Ty::Array(item) => self.ty(item),
+            Ty::Arrow(arrow) => {
+                self.ty(&arrow.input);
+                self.ty(&arrow.output);
+            }
+            Ty::Tuple(items) => items.iter().for_each(|item| self.ty(item)),
+            Ty::Udt(_, res) => self.res(res, EdgeKind::Udt),
+            Ty::Infer(_) | Ty::Param(..) | Ty::Prim(_) | Ty::Err => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for References {
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        self.ty(&decl.output);
+        visit::walk_callable_decl(self, decl);
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        self.ty(&pat.ty);
+        visit::walk_pat(self, pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.ty(&expr.ty);
+        match &expr.kind {
+            ExprKind::UnOp(UnOp::Functor(_), operand) => {
+                let mut operand = operand;
+                while let ExprKind::UnOp(UnOp::Functor(_), inner) = &operand.kind {
+                    operand = inner;
+                }
+                if let ExprKind::Var(res, _) = &operand.kind {
+                    self.res(res, EdgeKind::Functor);
+                } else {
+                    visit::walk_expr(self, expr);
+                }
+            }
+            ExprKind::Var(res, _) => self.res(res, EdgeKind::Call),
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
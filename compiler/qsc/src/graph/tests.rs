@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{call_graph, package_graph, Edge, EdgeKind, Filter, Graph, Node, NodeKind};
+use crate::compile;
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {"
+    namespace Test.Inner {
+        newtype Pair = (First : Int, Second : Int);
+        function Sum(p : Pair) : Int { p::First + p::Second }
+    }
+    namespace Test {
+        open Test.Inner;
+        operation Apply(q : Qubit) : Unit is Adj { X(q); }
+        operation Main() : Int {
+            use q = Qubit();
+            Apply(q);
+            Adjoint Apply(q);
+            Sum(Pair(1, 2))
+        }
+    }
+"};
+
+fn compile_package() -> (PackageStore, PackageId, PackageId) {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("test.qs".into(), SOURCE.into())], None);
+    let (unit, errors) = compile::compile(&store, &[std], sources, PackageType::Lib, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    (store, std, package)
+}
+
+/// Lists the edges of the graph by node name, sorted so that the
+/// output doesn't depend on the order of items in the package.
+fn edges(graph: &Graph) -> String {
+    let mut edges = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            format!(
+                "{} -{}-> {}",
+                graph.nodes[edge.from].qualified_name(),
+                edge.kind.as_str(),
+                graph.nodes[edge.to].qualified_name()
+            )
+        })
+        .collect::<Vec<_>>();
+    edges.sort();
+    edges.join("\n")
+}
+
+#[test]
+fn call_graph_filtered_by_namespace() {
+    let (store, _, package) = compile_package();
+    let filter = Filter {
+        namespaces: vec!["Test".to_string()],
+        ..Filter::default()
+    };
+    let graph = call_graph(&store, package, &filter);
+    expect![[r#"
+        Test.Inner.Sum -udt-> Test.Inner.Pair
+        Test.Main -call-> Test.Apply
+        Test.Main -call-> Test.Inner.Sum
+        Test.Main -functor-> Test.Apply
+        Test.Main -udt-> Test.Inner.Pair"#]]
+    .assert_eq(&edges(&graph));
+}
+
+#[test]
+fn call_graph_filtered_by_edge_kind() {
+    let (store, _, package) = compile_package();
+    let filter = Filter {
+        namespaces: vec!["Test".to_string()],
+        functors: false,
+        udts: false,
+        ..Filter::default()
+    };
+    let graph = call_graph(&store, package, &filter);
+    expect![[r#"
+        Test.Main -call-> Test.Apply
+        Test.Main -call-> Test.Inner.Sum"#]]
+    .assert_eq(&edges(&graph));
+}
+
+#[test]
+fn call_graph_nested_namespace_filter_excludes_parent() {
+    let (store, _, package) = compile_package();
+    let filter = Filter {
+        namespaces: vec!["Test.Inner".to_string()],
+        ..Filter::default()
+    };
+    let graph = call_graph(&store, package, &filter);
+    expect!["Test.Inner.Sum -udt-> Test.Inner.Pair"].assert_eq(&edges(&graph));
+}
+
+#[test]
+fn call_graph_includes_other_packages() {
+    let (store, std, package) = compile_package();
+    let graph = call_graph(&store, package, &Filter::default());
+    assert!(graph
+        .nodes
+        .iter()
+        .any(|node| node.package == std && node.name.as_ref() == "X"));
+}
+
+#[test]
+fn package_graph_has_dependencies() {
+    let (store, std, package) = compile_package();
+    let graph = package_graph(&store);
+    let node = |id| {
+        graph
+            .nodes
+            .iter()
+            .position(|node| node.package == id)
+            .expect("package should have a node")
+    };
+    let has_edge = |from, to| {
+        graph.edges.contains(&Edge {
+            from: node(from),
+            to: node(to),
+            kind: EdgeKind::Package,
+        })
+    };
+    assert!(has_edge(std, PackageId::CORE));
+    assert!(has_edge(package, std));
+    assert!(!has_edge(std, package));
+    assert!(!has_edge(PackageId::CORE, std));
+}
+
+#[test]
+fn dot_and_json_output() {
+    let graph = Graph {
+        nodes: vec![
+            Node {
+                kind: NodeKind::Callable,
+                package: PackageId::CORE,
+                namespace: "A".into(),
+                name: "Foo".into(),
+            },
+            Node {
+                kind: NodeKind::Udt,
+                package: PackageId::CORE,
+                namespace: "".into(),
+                name: "Q\"uote".into(),
+            },
+        ],
+        edges: vec![Edge {
+            from: 0,
+            to: 1,
+            kind: EdgeKind::Udt,
+        }],
+    };
+
+    expect![[r#"
+        digraph {
+            n0 [label="A.Foo"];
+            n1 [label="Q\"uote"];
+            n0 -> n1 [label="udt"];
+        }"#]]
+    .assert_eq(&graph.to_dot());
+    expect![[r#"{"nodes":[{"kind":"callable","package":0,"namespace":"A","name":"Foo"},{"kind":"udt","package":0,"namespace":"","name":"Q\"uote"}],"edges":[{"from":0,"to":1,"kind":"udt"}]}"#]]
+    .assert_eq(&graph.to_json());
+}
+
+#[test]
+fn json_strings_escape_control_characters() {
+    let text = "tab\tbell\u{7}\"quote\"\\";
+    let graph = Graph {
+        nodes: vec![Node {
+            kind: NodeKind::Callable,
+            package: PackageId::CORE,
+            namespace: "".into(),
+            name: text.into(),
+        }],
+        edges: Vec::new(),
+    };
+    let json = graph.to_json();
+    assert!(json.contains(r#""name":"tab\tbell\u0007\"quote\"\\""#));
+    let parsed: serde_json::Value =
+        serde_json::from_str(&json).expect("graph should be valid JSON");
+    assert_eq!(parsed["nodes"][0]["name"], text);
+}
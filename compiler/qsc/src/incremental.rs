@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::compile::{self, compile, core, std};
+use crate::compile::{self, capability_delta, compile, core, std, warnings, CapabilityDelta};
 use miette::Diagnostic;
 use qsc_frontend::{
     compile::{OpenPackageStore, PackageStore, RuntimeCapabilityFlags, SourceMap},
@@ -9,7 +9,7 @@ use qsc_frontend::{
     incremental::Increment,
 };
 use qsc_hir::hir::PackageId;
-use qsc_passes::{PackageType, PassContext};
+use qsc_passes::{result_literal_callables, PackageType, PassContext};
 
 /// An incremental Q# compiler.
 pub struct Compiler {
@@ -23,11 +23,25 @@ pub struct Compiler {
     passes: PassContext,
     /// The frontend incremental compiler.
     frontend: qsc_frontend::incremental::Compiler,
+    /// The runtime capabilities of the target.
+    capabilities: RuntimeCapabilityFlags,
+    /// The warnings of the source package that haven't been taken yet.
+    warnings: Errors,
 }
 
 /// An incremental compiler error.
 pub type Errors = Vec<compile::Error>;
 
+/// An error from compiling an entry expression with [`Compiler::compile_entry_expr`].
+#[derive(Debug)]
+pub enum EntryExprError {
+    /// The expression failed to compile.
+    Compile(Errors),
+    /// The expression needs runtime capabilities that the target doesn't have. The errors are
+    /// the ones that the checks of those capabilities report.
+    Capabilities(CapabilityDelta, Errors),
+}
+
 impl Compiler {
     /// Creates a new incremental compiler, compiling the passed in sources.
     /// # Errors
@@ -47,22 +61,29 @@ impl Compiler {
             dependencies.push(id);
         }
 
+        let edition = sources.edition();
         let (unit, errors) = compile(&store, &dependencies, sources, package_type, capabilities);
         if !errors.is_empty() {
             return Err(errors);
         }
 
+        let warnings = warnings(&unit);
         let source_package_id = store.insert(unit);
         dependencies.push(source_package_id);
 
-        let frontend = qsc_frontend::incremental::Compiler::new(&store, dependencies, capabilities);
+        let frontend =
+            qsc_frontend::incremental::Compiler::new(&store, dependencies, capabilities, edition);
+        let passes = PassContext::new(capabilities)
+            .with_result_literal_callables(result_literal_callables(&store));
         let store = store.open();
 
         Ok(Self {
             store,
             source_package_id,
             frontend,
-            passes: PassContext::new(capabilities),
+            passes,
+            capabilities,
+            warnings,
         })
     }
 
@@ -136,12 +157,25 @@ impl Compiler {
     /// It is then the caller's responsibility to merge
     /// these packages into the current `CompileUnit` using the `update()` method.
     pub fn compile_expr(&mut self, expr: &str) -> Result<Increment, Errors> {
+        self.compile_entry_expr(expr).map_err(|error| match error {
+            EntryExprError::Compile(errors) | EntryExprError::Capabilities(_, errors) => errors,
+        })
+    }
+
+    /// Compiles an entry expression like [`Self::compile_expr`], checking it against the runtime
+    /// capabilities of the target as the entry expression of a program. An expression that the
+    /// target can't run comes with the capabilities it needs, so that hosts can reject it when it
+    /// is submitted rather than when code is generated for it.
+    /// # Errors
+    /// Returns the capabilities that the expression needs beyond those of the target, or the
+    /// compiler errors if it fails to compile for another reason.
+    pub fn compile_entry_expr(&mut self, expr: &str) -> Result<Increment, EntryExprError> {
         let (core, unit) = self.store.get_open_mut();
 
         let mut increment = self
             .frontend
             .compile_expr(unit, "<entry>", expr)
-            .map_err(into_errors)?;
+            .map_err(|errors| EntryExprError::Compile(into_errors(errors)))?;
 
         let pass_errors = self.passes.run_default_passes(
             &mut increment.hir,
@@ -151,7 +185,13 @@ impl Compiler {
         );
 
         if !pass_errors.is_empty() {
-            return Err(into_errors_with_source(pass_errors, &unit.sources));
+            let errors = into_errors_with_source(pass_errors, &unit.sources);
+            let delta = capability_delta(&increment.hir, self.capabilities);
+            return Err(if delta.is_empty() {
+                EntryExprError::Compile(errors)
+            } else {
+                EntryExprError::Capabilities(delta, errors)
+            });
         }
 
         Ok(increment)
@@ -165,6 +205,19 @@ impl Compiler {
         self.frontend.update(unit, new);
     }
 
+    /// Takes the warnings of the compilations so far, such as uses of deprecated aliases, which
+    /// don't stop the sources from compiling. Each warning is only returned once.
+    pub fn take_warnings(&mut self) -> Errors {
+        let (_, unit) = self.store.get_open_mut();
+        let mut warnings = self.warnings.drain(..).collect::<Errors>();
+        warnings.extend(
+            unit.warnings
+                .drain(..)
+                .map(|warning| WithSource::from_map(&unit.sources, warning.into())),
+        );
+        warnings
+    }
+
     /// Returns a reference to the underlying package store.
     #[must_use]
     pub fn package_store(&self) -> &PackageStore {
@@ -0,0 +1,184 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Finds integer arithmetic whose result is used to index an array of qubits. Integer arithmetic
+//! wraps around on overflow, so an overflow there silently selects the wrong qubit instead of
+//! failing, and the checked functions of `Microsoft.Quantum.Math` should be used instead.
+//!
+//! The analysis works on the HIR after the default passes. A local carries the arithmetic of every
+//! expression that is ever assigned to it, regardless of the order of the statements, so that
+//! updates made in one iteration of a loop are seen by the next. Locals generated by the passes are
+//! ignored, which keeps the counters of `for` loops from being reported.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::{message::MessageParams, span::Span};
+use qsc_hir::{
+    hir::{BinOp, Expr, ExprKind, NodeId, Package, Pat, PatKind, Res, Stmt, StmtKind},
+    ty::{Prim, Ty},
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Lint {
+    #[error("integer arithmetic used to index qubits wraps around on overflow")]
+    #[diagnostic(help("use {0} to detect the overflow"))]
+    #[diagnostic(code("Qsc.Lint.UncheckedIndexArithmetic"), severity(Warning))]
+    UncheckedIndexArithmetic(&'static str, #[label] Span),
+}
+
+impl MessageParams for Lint {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::UncheckedIndexArithmetic(..) => Vec::new(),
+        }
+    }
+}
+
+/// Finds the arithmetic that feeds an index into an array of qubits in the package, in source
+/// order. The lints are reported among the warnings of [`crate::compile::warnings`].
+#[must_use]
+pub fn unchecked_index_arithmetic(package: &Package) -> Vec<Lint> {
+    let mut collector = Collector::default();
+    collector.visit_package(package);
+
+    // Spread the arithmetic through the assignments until no local gains any more.
+    let mut locals: FxHashMap<NodeId, FxHashSet<(Span, BinOp)>> = FxHashMap::default();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (local, expr) in &collector.assignments {
+            let sources = sources(expr, &locals);
+            let entry = locals.entry(*local).or_default();
+            let len = entry.len();
+            entry.extend(sources);
+            changed |= entry.len() > len;
+        }
+    }
+
+    let mut found = collector
+        .indices
+        .iter()
+        .flat_map(|index| sources(index, &locals))
+        .collect::<Vec<_>>();
+    found.sort_by_key(|(span, _)| (span.lo, span.hi));
+    found.dedup();
+    found
+        .into_iter()
+        .map(|(span, op)| {
+            let checked = match op {
+                BinOp::Add => "AddIChecked",
+                BinOp::Sub => "SubtractIChecked",
+                _ => "MultiplyIChecked",
+            };
+            Lint::UncheckedIndexArithmetic(checked, span)
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Collector<'a> {
+    /// The expressions assigned to each local that isn't generated by the passes.
+    assignments: Vec<(NodeId, &'a Expr)>,
+    /// The locals generated by the passes.
+    generated: FxHashSet<NodeId>,
+    /// The index expressions of arrays of qubits.
+    indices: Vec<&'a Expr>,
+}
+
+impl<'a> Collector<'a> {
+    fn bind(&mut self, pat: &'a Pat, expr: &'a Expr) {
+        match &pat.kind {
+            PatKind::Bind(ident) if ident.name.starts_with('@') => {
+                self.generated.insert(ident.id);
+            }
+            PatKind::Bind(ident) => self.assignments.push((ident.id, expr)),
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.bind(pat, expr);
+                }
+            }
+            PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    fn assign(&mut self, lhs: &'a Expr, expr: &'a Expr) {
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(local), _) if !self.generated.contains(local) => {
+                self.assignments.push((*local, expr));
+            }
+            ExprKind::Tuple(items) => {
+                for item in items {
+                    self.assign(item, expr);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Local(_, pat, expr) = &stmt.kind {
+            self.bind(pat, expr);
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Assign(lhs, rhs) => self.assign(lhs, rhs),
+            // The whole expression is recorded so that its own arithmetic is included.
+            ExprKind::AssignOp(_, lhs, _) => self.assign(lhs, expr),
+            ExprKind::Index(array, index) if is_qubit_array(&array.ty) => self.indices.push(index),
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn is_qubit_array(ty: &Ty) -> bool {
+    matches!(ty, Ty::Array(item) if matches!(item.as_ref(), Ty::Prim(Prim::Qubit)))
+}
+
+/// The integer arithmetic in the expression and in the locals it reads.
+fn sources(
+    expr: &Expr,
+    locals: &FxHashMap<NodeId, FxHashSet<(Span, BinOp)>>,
+) -> FxHashSet<(Span, BinOp)> {
+    struct Sources<'b> {
+        locals: &'b FxHashMap<NodeId, FxHashSet<(Span, BinOp)>>,
+        found: FxHashSet<(Span, BinOp)>,
+    }
+
+    impl<'c> Visitor<'c> for Sources<'_> {
+        fn visit_expr(&mut self, expr: &'c Expr) {
+            match &expr.kind {
+                ExprKind::BinOp(op @ (BinOp::Add | BinOp::Sub | BinOp::Mul), lhs, _)
+                | ExprKind::AssignOp(op @ (BinOp::Add | BinOp::Sub | BinOp::Mul), lhs, _)
+                    if lhs.ty == Ty::Prim(Prim::Int) =>
+                {
+                    self.found.insert((expr.span, *op));
+                }
+                ExprKind::Var(Res::Local(local), _) => {
+                    if let Some(sources) = self.locals.get(local) {
+                        self.found.extend(sources.iter().copied());
+                    }
+                }
+                _ => {}
+            }
+            visit::walk_expr(self, expr);
+        }
+    }
+
+    let mut finder = Sources {
+        locals,
+        found: FxHashSet::default(),
+    };
+    finder.visit_expr(expr);
+    finder.found
+}
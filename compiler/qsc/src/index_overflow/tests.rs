@@ -0,0 +1,111 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{unchecked_index_arithmetic, Lint};
+use crate::compile;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn lints(source: &str) -> Vec<(&'static str, String)> {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(
+        &store,
+        &[std],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    unchecked_index_arithmetic(&unit.package)
+        .into_iter()
+        .map(|Lint::UncheckedIndexArithmetic(checked, span)| {
+            (
+                checked,
+                source[span.lo as usize..span.hi as usize].to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn arithmetic_in_index_is_reported() {
+    let source = indoc! {"
+        namespace Test {
+            operation Foo(qs : Qubit[], i : Int) : Unit {
+                X(qs[i + 1]);
+            }
+        }
+    "};
+    assert_eq!(lints(source), [("AddIChecked", "i + 1".to_string())]);
+}
+
+#[test]
+fn arithmetic_through_locals_is_reported() {
+    let source = indoc! {"
+        namespace Test {
+            operation Foo(qs : Qubit[], i : Int) : Unit {
+                let j = i * 2;
+                mutable k = j;
+                set k -= 1;
+                X(qs[k]);
+            }
+        }
+    "};
+    assert_eq!(
+        lints(source),
+        [
+            ("MultiplyIChecked", "i * 2".to_string()),
+            ("SubtractIChecked", "set k -= 1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn arithmetic_not_used_as_qubit_index_is_not_reported() {
+    let source = indoc! {"
+        namespace Test {
+            operation Foo(qs : Qubit[], values : Int[], i : Int) : Unit {
+                let n = i + 1;
+                let value = values[n];
+                let m = 2;
+                X(qs[m]);
+                for q in 0..Length(qs) - 1 {
+                    X(qs[q]);
+                }
+            }
+        }
+    "};
+    assert!(lints(source).is_empty());
+}
+
+#[test]
+fn lint_is_reported_among_the_warnings() {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let source = indoc! {"
+        namespace Test {
+            operation Foo(qs : Qubit[], i : Int) : Unit {
+                X(qs[i + 1]);
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, _) = compile::compile(
+        &store,
+        &[std],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    let warnings = compile::warnings(&unit);
+    assert!(
+        matches!(
+            warnings.as_slice(),
+            [warning] if matches!(warning.error(), compile::ErrorKind::Lint(_))
+        ),
+        "{warnings:?}"
+    );
+}
@@ -0,0 +1,288 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Values that hosts, such as the Python and JavaScript bindings, can pass to Q# programs and read
+//! back from them. Ranges, Paulis and user-defined types have representations of their own rather
+//! than their Q# text, so hosts can build arguments from them and take results apart. Values that
+//! only make sense inside a simulation, such as qubits and callables, have no host
+//! representation.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use num_bigint::BigInt;
+use qsc_data_structures::message::MessageParams;
+use qsc_eval::val::{self, Value};
+use qsc_fir::fir::Pauli;
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    hir::{ItemId, ItemKind, PackageId, Res},
+    ty::{Ty, Udt, UdtDef, UdtDefKind},
+};
+use std::rc::Rc;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Error {
+    #[error("a value of type {0} has no host representation")]
+    #[diagnostic(help("return classical values, such as measurement results, to the host"))]
+    #[diagnostic(code("Qsc.Interop.Unsupported"))]
+    Unsupported(&'static str),
+
+    #[error("the result has not been measured")]
+    #[diagnostic(code("Qsc.Interop.UnmeasuredResult"))]
+    UnmeasuredResult,
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Unsupported(ty) => vec![(*ty).to_string()],
+            Self::UnmeasuredResult => Vec::new(),
+        }
+    }
+}
+
+/// A Q# value as a host sees it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HostValue {
+    Array(Vec<HostValue>),
+    BigInt(BigInt),
+    Bool(bool),
+    Double(f64),
+    Int(i64),
+    Pauli(Pauli),
+    Range(HostRange),
+    /// A measurement result, which is `true` for `One`.
+    Result(bool),
+    String(String),
+    /// A tuple, which is unit when it is empty.
+    Tuple(Vec<HostValue>),
+    Udt(HostUdt),
+}
+
+/// A range of integers, such as `0..2..10`. A range without a start or an end is open on that side,
+/// as in the array slice `xs[...2]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HostRange {
+    pub start: Option<i64>,
+    pub step: i64,
+    /// The last value of the range, if it is reached by the steps, which is included in it.
+    pub end: Option<i64>,
+}
+
+/// A value of a user-defined type, with the items of its definition. A type defined as a single
+/// item, such as `newtype Angle = Double;`, has one item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostUdt {
+    pub name: String,
+    /// The items of the value, with their names if they are named.
+    pub items: Vec<(Option<String>, HostValue)>,
+}
+
+impl HostValue {
+    /// The unit value, `()`.
+    #[must_use]
+    pub fn unit() -> Self {
+        Self::Tuple(Vec::new())
+    }
+
+    /// Converts the value, using its type to recover the user-defined types in it, which are
+    /// otherwise seen as tuples. Types are looked up in the store, and items of the package
+    /// `package` are the ones that the type refers to without a package.
+    /// # Errors
+    /// Returns an error if the value contains a value without a host representation.
+    pub fn from_typed(
+        value: &Value,
+        ty: &Ty,
+        store: &PackageStore,
+        package: PackageId,
+    ) -> Result<Self, Error> {
+        match (ty, value) {
+            (Ty::Udt(name, res), _) => match udt(store, package, res) {
+                Some((udt, package)) => Ok(Self::Udt(HostUdt {
+                    name: name.to_string(),
+                    items: udt_items(value, &udt.definition, store, package)?,
+                })),
+                None => Self::try_from(value),
+            },
+            (Ty::Array(item), Value::Array(values)) => values
+                .iter()
+                .map(|value| Self::from_typed(value, item, store, package))
+                .collect::<Result<_, _>>()
+                .map(Self::Array),
+            (Ty::Tuple(items), Value::Tuple(values)) if items.len() == values.len() => values
+                .iter()
+                .zip(items)
+                .map(|(value, ty)| Self::from_typed(value, ty, store, package))
+                .collect::<Result<_, _>>()
+                .map(Self::Tuple),
+            _ => Self::try_from(value),
+        }
+    }
+}
+
+impl HostRange {
+    /// The values of the range in order, or `None` if it is open.
+    #[must_use]
+    pub fn values(&self) -> Option<Vec<i64>> {
+        let (start, end) = (self.start?, self.end?);
+        let mut values = Vec::new();
+        let mut value = start;
+        while (self.step > 0 && value <= end) || (self.step < 0 && value >= end) {
+            values.push(value);
+            match value.checked_add(self.step) {
+                Some(next) => value = next,
+                None => break,
+            }
+        }
+        Some(values)
+    }
+}
+
+impl HostUdt {
+    /// The item with the name, if the type has one.
+    #[must_use]
+    pub fn item(&self, name: &str) -> Option<&HostValue> {
+        self.items
+            .iter()
+            .find(|(item, _)| item.as_deref() == Some(name))
+            .map(|(_, value)| value)
+    }
+}
+
+impl TryFrom<&Value> for HostValue {
+    type Error = Error;
+
+    /// Converts the value without its type, so user-defined types are seen as tuples of their
+    /// items, or as their item if they have one.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Array(values) => Self::Array(
+                values
+                    .iter()
+                    .map(Self::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::BigInt(value) => Self::BigInt(value.clone()),
+            Value::Bool(value) => Self::Bool(*value),
+            Value::Double(value) => Self::Double(*value),
+            Value::Int(value) => Self::Int(*value),
+            Value::Pauli(pauli) => Self::Pauli(*pauli),
+            Value::Range(start, step, end) => Self::Range(HostRange {
+                start: *start,
+                step: *step,
+                end: *end,
+            }),
+            Value::Result(val::Result::Val(one)) => Self::Result(*one),
+            Value::Result(val::Result::Id(_)) => return Err(Error::UnmeasuredResult),
+            Value::String(value) => Self::String(value.to_string()),
+            Value::Tuple(values) => Self::Tuple(
+                values
+                    .iter()
+                    .map(Self::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Closure(..) | Value::Global(..) | Value::Qubit(_) => {
+                return Err(Error::Unsupported(value.type_name()))
+            }
+        })
+    }
+}
+
+impl From<HostValue> for Value {
+    /// Converts the value into the Q# value it stands for. A user-defined type becomes the tuple of
+    /// its items, or its item if it has one, which is how the evaluator represents it.
+    fn from(value: HostValue) -> Self {
+        match value {
+            HostValue::Array(values) => {
+                Value::Array(Rc::new(values.into_iter().map(Value::from).collect()))
+            }
+            HostValue::BigInt(value) => Value::BigInt(value),
+            HostValue::Bool(value) => Value::Bool(value),
+            HostValue::Double(value) => Value::Double(value),
+            HostValue::Int(value) => Value::Int(value),
+            HostValue::Pauli(pauli) => Value::Pauli(pauli),
+            HostValue::Range(range) => Value::Range(range.start, range.step, range.end),
+            HostValue::Result(one) => Value::Result(val::Result::Val(one)),
+            HostValue::String(value) => Value::String(value.into()),
+            HostValue::Tuple(values) => Value::Tuple(values.into_iter().map(Value::from).collect()),
+            HostValue::Udt(mut udt) => {
+                if udt.items.len() == 1 {
+                    let (_, item) = udt.items.remove(0);
+                    Value::from(item)
+                } else {
+                    Value::Tuple(
+                        udt.items
+                            .into_iter()
+                            .map(|(_, item)| Value::from(item))
+                            .collect(),
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// The definition of the user-defined type that the resolution refers to, with the package it is
+/// declared in.
+fn udt<'a>(store: &'a PackageStore, package: PackageId, res: &Res) -> Option<(&'a Udt, PackageId)> {
+    let Res::Item(ItemId {
+        package: item_package,
+        item,
+    }) = res
+    else {
+        return None;
+    };
+    let package = item_package.unwrap_or(package);
+    match &store.get(package)?.package.items.get(*item)?.kind {
+        ItemKind::Ty(_, udt) => Some((udt, package)),
+        _ => None,
+    }
+}
+
+/// The items of a value of a user-defined type with the definition, which are the items of the
+/// tuple it is defined as, or the value itself if it is defined as one item.
+fn udt_items(
+    value: &Value,
+    def: &UdtDef,
+    store: &PackageStore,
+    package: PackageId,
+) -> Result<Vec<(Option<String>, HostValue)>, Error> {
+    match (&def.kind, value) {
+        (UdtDefKind::Tuple(defs), Value::Tuple(values)) if defs.len() == values.len() => defs
+            .iter()
+            .zip(values.iter())
+            .map(|(def, value)| udt_item(value, def, store, package))
+            .collect(),
+        _ => Ok(vec![udt_item(value, def, store, package)?]),
+    }
+}
+
+fn udt_item(
+    value: &Value,
+    def: &UdtDef,
+    store: &PackageStore,
+    package: PackageId,
+) -> Result<(Option<String>, HostValue), Error> {
+    match &def.kind {
+        UdtDefKind::Field(field) => Ok((
+            field.name.as_deref().map(str::to_string),
+            HostValue::from_typed(value, &field.ty, store, package)?,
+        )),
+        // A nested tuple of items is one item of the value, which is a tuple.
+        UdtDefKind::Tuple(defs) => match value {
+            Value::Tuple(values) if defs.len() == values.len() => Ok((
+                None,
+                HostValue::Tuple(
+                    defs.iter()
+                        .zip(values.iter())
+                        .map(|(def, value)| Ok(udt_item(value, def, store, package)?.1))
+                        .collect::<Result<_, Error>>()?,
+                ),
+            )),
+            _ => Ok((None, HostValue::try_from(value)?)),
+        },
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Error, HostRange, HostUdt, HostValue};
+use crate::interpret::Interpreter;
+use indoc::indoc;
+use qsc_eval::{
+    output::GenericReceiver,
+    val::{self, Value},
+};
+use qsc_fir::fir::Pauli;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::rc::Rc;
+
+fn entry(source: &str) -> (Interpreter, Value) {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created");
+    let mut out = std::io::sink();
+    let value = interpreter
+        .eval_entry(&mut GenericReceiver::new(&mut out))
+        .expect("entry should evaluate");
+    (interpreter, value)
+}
+
+#[test]
+fn values_convert_without_types() {
+    let value = Value::Tuple(Rc::from([
+        Value::Range(Some(0), 2, Some(6)),
+        Value::Pauli(Pauli::Y),
+        Value::Result(val::Result::Val(true)),
+        Value::Array(Rc::new(vec![Value::Int(1), Value::Double(0.5)])),
+        Value::unit(),
+    ]));
+    let host = HostValue::try_from(&value).expect("value should convert");
+    assert_eq!(
+        host,
+        HostValue::Tuple(vec![
+            HostValue::Range(HostRange {
+                start: Some(0),
+                step: 2,
+                end: Some(6),
+            }),
+            HostValue::Pauli(Pauli::Y),
+            HostValue::Result(true),
+            HostValue::Array(vec![HostValue::Int(1), HostValue::Double(0.5)]),
+            HostValue::unit(),
+        ])
+    );
+    assert_eq!(Value::from(host), value);
+}
+
+#[test]
+fn simulation_values_have_no_host_representation() {
+    let (_, value) = entry(indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : (Int, Result) {
+                use q = Qubit();
+                (1, M(q))
+            }
+        }
+    "});
+    assert!(HostValue::try_from(&value).is_ok());
+    assert_eq!(
+        HostValue::try_from(&Value::Result(val::Result::Id(0))),
+        Err(Error::UnmeasuredResult)
+    );
+    assert_eq!(
+        HostValue::try_from(&Value::Tuple(Rc::from([Value::Qubit(val::Qubit(0))]))),
+        Err(Error::Unsupported("Qubit"))
+    );
+}
+
+#[test]
+fn user_defined_types_convert_with_their_items() {
+    let (interpreter, value) = entry(indoc! {r#"
+        namespace Test {
+            newtype Pulse = (Axis : Pauli, (Steps : Range, Repeat : Int), String);
+            newtype Angle = Double;
+
+            @EntryPoint()
+            function Main() : (Pulse[], Angle) {
+                ([Pulse(PauliX, (1..3, 2), "x")], Angle(0.5))
+            }
+        }
+    "#});
+    let host = interpreter
+        .entry_host_value(&value)
+        .expect("value should convert");
+    let pulse = HostUdt {
+        name: "Pulse".to_string(),
+        items: vec![
+            (Some("Axis".to_string()), HostValue::Pauli(Pauli::X)),
+            (
+                None,
+                HostValue::Tuple(vec![
+                    HostValue::Range(HostRange {
+                        start: Some(1),
+                        step: 1,
+                        end: Some(3),
+                    }),
+                    HostValue::Int(2),
+                ]),
+            ),
+            (None, HostValue::String("x".to_string())),
+        ],
+    };
+    let angle = HostUdt {
+        name: "Angle".to_string(),
+        items: vec![(None, HostValue::Double(0.5))],
+    };
+    assert_eq!(
+        host,
+        HostValue::Tuple(vec![
+            HostValue::Array(vec![HostValue::Udt(pulse.clone())]),
+            HostValue::Udt(angle),
+        ])
+    );
+    assert_eq!(pulse.item("Axis"), Some(&HostValue::Pauli(Pauli::X)));
+    assert_eq!(pulse.item("Steps"), None);
+    assert_eq!(Value::from(host), value);
+}
+
+#[test]
+fn range_values_follow_the_steps() {
+    let range = |start, step, end| HostRange { start, step, end };
+    assert_eq!(range(Some(0), 2, Some(7)).values(), Some(vec![0, 2, 4, 6]));
+    assert_eq!(range(Some(5), -2, Some(0)).values(), Some(vec![5, 3, 1]));
+    assert_eq!(range(Some(3), 1, Some(2)).values(), Some(Vec::new()));
+    assert_eq!(range(None, 1, Some(2)).values(), None);
+}
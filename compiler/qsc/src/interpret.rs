@@ -10,35 +10,56 @@ mod tests;
 mod debugger_tests;
 
 pub use qsc_eval::{
-    debug::Frame,
+    backend::{BasisPattern, ParseBasisPatternError},
+    debug::{BreakpointCondition, Frame, HitCondition},
+    format::{Ellipsis, ValueFormat},
     output::{self, GenericReceiver},
     val::Value,
     StepAction, StepResult,
 };
 
 use crate::{
+    circuit::{Circuit, CircuitBuilder, CircuitConfig, Operation, PartialCircuit},
+    compile::CapabilityDelta,
+    coverage::{self, Coverage},
+    determinism::{self, DeterminismReport, ObservingReceiver, Run},
+    entry_points::{self, EntryPointCandidate},
     error::{self, WithStack},
-    incremental::Compiler,
+    gate_counts::GateCounter,
+    incremental::{Compiler, EntryExprError},
+    interop::{self, HostValue},
+    kernels::{self, KernelProgram},
+    memory::{CompilationStats, MemoryStats, SimulationStats},
+    telemetry::{self, Counter, TelemetrySink},
+    tomography::{self, ChoiMatrix},
+    trace::{self, TracingBackend},
 };
-use debug::format_call_stack;
+use debug::{format_call_stack, frame_eval_source, mutable_frame_locals};
 use miette::Diagnostic;
 use num_bigint::BigUint;
 use num_complex::Complex;
 use qsc_codegen::qir_base::BaseProfSim;
 use qsc_data_structures::{
     line_column::{Encoding, Range},
+    message::MessageParams,
     span::Span,
 };
 use qsc_eval::{
-    backend::{Backend, SparseSim},
+    backend::{Backend, Precision, SparseSim},
+    cache::OperationCache,
     debug::{map_fir_package_to_hir, map_hir_package_to_fir},
+    noise::{NoisyBackend, PauliNoise},
     output::Receiver,
+    pauli_frame::PauliFrameBackend,
+    progress::{CountingBackend, ProgressTracker},
+    replay::{MeasurementLog, RecordingBackend},
     val::{self},
     Env, EvalId, State, VariableInfo,
 };
-use qsc_fir::fir::{self, Global, PackageStoreLookup};
+use qsc_fir::fir::{self, Global, PackageStoreLookup, StorePatId};
 use qsc_fir::{
     fir::{Block, BlockId, Expr, ExprId, Package, PackageId, Pat, PatId, Stmt, StmtId},
+    ty::{Prim, Ty},
     visit::{self, Visitor},
 };
 use qsc_frontend::{
@@ -47,6 +68,13 @@ use qsc_frontend::{
 };
 use qsc_passes::PackageType;
 use rustc_hash::FxHashSet;
+use std::{
+    collections::hash_map::RandomState,
+    collections::VecDeque,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::Duration,
+};
 use thiserror::Error;
 
 impl Error {
@@ -76,6 +104,90 @@ pub enum Error {
     #[error("unsupported runtime capabilities for code generation")]
     #[diagnostic(code("Qsc.Interpret.UnsupportedRuntimeCapabilities"))]
     UnsupportedRuntimeCapabilities,
+    #[error(
+        "entry expression needs runtime capabilities that the target doesn't support: {}",
+        capability_names(.0.missing)
+    )]
+    #[diagnostic(help(
+        "the errors that follow show where the capabilities are needed; use a profile that supports them"
+    ))]
+    #[diagnostic(code("Qsc.Interpret.UnsupportedEntryCapabilities"))]
+    UnsupportedEntryCapabilities(CapabilityDelta),
+    #[error("expression does not evaluate to a callable")]
+    #[diagnostic(code("Qsc.Interpret.NotCallable"))]
+    NotCallable,
+    #[error("the operation takes {0} argument(s), but {1} were given")]
+    #[diagnostic(code("Qsc.Interpret.OperationArgCount"))]
+    OperationArgCount(usize, usize),
+    #[error("argument {0} of the operation should have type {1}")]
+    #[diagnostic(code("Qsc.Interpret.OperationArgType"))]
+    OperationArgType(usize, String),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Kernel(#[from] WithSource<kernels::Error>),
+    #[error("program allocates at least {0} qubits, more than the limit of {1}")]
+    #[diagnostic(help(
+        "the limit only applies to this estimate and the program still runs, but simulating this many qubits may run out of memory; allocate fewer qubits or raise the limit"
+    ))]
+    #[diagnostic(code("Qsc.Interpret.QubitLimit"), severity(Warning))]
+    QubitLimit(u64, usize),
+    #[error("rounding errors changed the norm of the simulated state by {0:e}")]
+    #[diagnostic(help(
+        "results may be skewed by rounding errors; simulate at double precision for accurate probabilities"
+    ))]
+    #[diagnostic(code("Qsc.Interpret.PrecisionLoss"), severity(Warning))]
+    PrecisionLoss(f64),
+    #[error("the Choi matrix of {0} qubits is too large to find, the limit is {1} qubits")]
+    #[diagnostic(help(
+        "the Choi matrix of n qubits has 16^n entries; characterize the operation on fewer qubits at a time"
+    ))]
+    #[diagnostic(code("Qsc.Interpret.TomographyQubits"))]
+    TomographyQubits(usize, usize),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Error::Compile(error) => error.message_params(),
+            Error::Pass(error) => error.message_params(),
+            Error::Eval(error) => error.message_params(),
+            Error::Kernel(error) => error.message_params(),
+            Error::NoEntryPoint | Error::UnsupportedRuntimeCapabilities | Error::NotCallable => {
+                Vec::new()
+            }
+            Error::UnsupportedEntryCapabilities(delta) => vec![capability_names(delta.missing)],
+            Error::OperationArgCount(expected, given) => {
+                vec![expected.to_string(), given.to_string()]
+            }
+            Error::OperationArgType(index, ty) => vec![index.to_string(), ty.clone()],
+            Error::QubitLimit(needed, limit) => vec![needed.to_string(), limit.to_string()],
+            Error::PrecisionLoss(error) => vec![format!("{error:e}")],
+            Error::TomographyQubits(qubits, limit) => vec![qubits.to_string(), limit.to_string()],
+        }
+    }
+}
+
+/// The error in the norm of a simulated state above which [`Interpreter::check_precision`] warns.
+const NORM_ERROR_LIMIT: f64 = 1e-4;
+
+/// What a host loop does after a shot.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShotControl {
+    /// Runs another shot with the given argument.
+    Continue(Value),
+    /// Stops the loop.
+    Stop,
+}
+
+/// An argument of an operation that a circuit is generated for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OperationArg {
+    /// A new qubit, for a `Qubit` parameter.
+    Qubit,
+    /// An array of this many new qubits, for a `Qubit[]` parameter.
+    Qubits(usize),
+    /// A classical value, such as an angle or an integer.
+    Value(Value),
 }
 
 /// A Q# interpreter.
@@ -109,6 +221,18 @@ pub struct Interpreter {
     classical_seed: Option<u64>,
     /// The evaluator environment.
     env: Env,
+    /// The last statement evaluated with `eval_fragments`, whose value the REPL echoes.
+    last_fragment: Option<StmtId>,
+    /// The cache of the gates of repeated operation calls, if caching is enabled.
+    operation_cache: Option<OperationCache>,
+    /// The parts of the program reached by runs, if coverage is enabled.
+    coverage: Option<Coverage>,
+    /// The largest number of qubits that programs are expected to allocate, if any.
+    qubit_limit: Option<usize>,
+    /// The precision of the amplitudes of simulated state vectors.
+    precision: Precision,
+    /// Where usage counters go, if the host opted in to telemetry.
+    telemetry: Option<Box<dyn TelemetrySink>>,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -147,14 +271,71 @@ impl Interpreter {
             fir_store,
             lowerer,
             env: Env::default(),
+            last_fragment: None,
             sim: SparseSim::new(),
             quantum_seed: None,
             classical_seed: None,
+            operation_cache: None,
+            coverage: None,
+            qubit_limit: None,
+            precision: Precision::default(),
+            telemetry: None,
             package: map_hir_package_to_fir(package_id),
             source_package: map_hir_package_to_fir(source_package_id),
         })
     }
 
+    /// The compilation unit of the sources that the interpreter was created with.
+    #[must_use]
+    pub fn source_package(&self) -> &CompileUnit {
+        self.compiler
+            .package_store()
+            .get(map_fir_package_to_hir(self.source_package))
+            .expect("Could not load package")
+    }
+
+    /// Sends usage counters to the sink, or stops sending them if it is `None`. The compilation of
+    /// the sources that the interpreter was created with is reported to a new sink right away.
+    pub fn set_telemetry_sink(&mut self, mut sink: Option<Box<dyn TelemetrySink>>) {
+        if let Some(sink) = &mut sink {
+            telemetry::report_compilation(sink.as_mut(), self.source_package(), self.capabilities);
+        }
+        self.telemetry = sink;
+    }
+
+    /// The size of everything the interpreter has compiled and the qubits held by its simulator.
+    #[must_use]
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut compilation = CompilationStats::default();
+        for (id, unit) in self.compiler.package_store() {
+            let fir = self
+                .fir_store
+                .get(map_hir_package_to_fir(id))
+                .expect("package should be lowered to FIR");
+            compilation += CompilationStats::new(&unit.package, fir);
+        }
+
+        MemoryStats {
+            compilation,
+            simulation: SimulationStats {
+                qubits: self.sim.qubits(),
+                peak_qubits: self.sim.peak_qubits(),
+                precision: self.precision,
+            },
+        }
+    }
+
+    /// Takes the warnings of the sources and the fragments compiled so far, such as uses of
+    /// deprecated aliases. Each warning is only returned once, so hosts can report the warnings of
+    /// each fragment after it is evaluated.
+    pub fn take_warnings(&mut self) -> Vec<Error> {
+        self.compiler
+            .take_warnings()
+            .into_iter()
+            .map(Error::Compile)
+            .collect()
+    }
+
     pub fn set_quantum_seed(&mut self, seed: Option<u64>) {
         self.quantum_seed = seed;
         self.sim.set_seed(seed);
@@ -163,6 +344,132 @@ impl Interpreter {
     pub fn set_classical_seed(&mut self, seed: Option<u64>) {
         self.classical_seed = seed;
     }
+
+    /// Enables or disables caching the gates of operations that are called repeatedly with the
+    /// same arguments, which speeds up structured algorithms such as phase estimation. The cache
+    /// is kept across runs until caching is disabled.
+    pub fn set_operation_cache(&mut self, enabled: bool) {
+        if !enabled {
+            self.operation_cache = None;
+        } else if self.operation_cache.is_none() {
+            self.operation_cache = Some(OperationCache::new());
+        }
+    }
+
+    /// The operation cache, if caching is enabled.
+    #[must_use]
+    pub fn operation_cache(&self) -> Option<&OperationCache> {
+        self.operation_cache.as_ref()
+    }
+
+    /// Enables or disables recording which statements, branches and specializations runs reach.
+    /// Coverage accumulates across runs, such as the tests of a suite, until it is disabled.
+    pub fn set_coverage(&mut self, enabled: bool) {
+        if !enabled {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some(Coverage::new());
+        }
+    }
+
+    /// Takes the coverage recorded so far, if coverage is enabled, and disables coverage.
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    /// Enables coverage, adding to coverage taken from another interpreter. Compiling the same
+    /// sources gives the same IDs, so coverage can be carried between interpreters of the same
+    /// sources, such as the fresh interpreters that run each test of a suite.
+    pub fn resume_coverage(&mut self, coverage: Coverage) {
+        self.coverage = Some(coverage);
+    }
+
+    /// The coverage of the sources that the interpreter was created with as lcov tracefile
+    /// records, if coverage is enabled.
+    #[must_use]
+    pub fn coverage_lcov(&self) -> Option<String> {
+        let coverage = self.coverage.as_ref()?;
+        let package = self
+            .fir_store
+            .get(self.source_package)
+            .expect("source package should be lowered to FIR");
+        Some(coverage::lcov(
+            coverage,
+            self.source_package,
+            package,
+            &self.source_package().sources,
+        ))
+    }
+
+    /// Sets the largest number of qubits that programs are expected to allocate, which should be
+    /// what the backend can simulate. The limit is only checked by [`Self::check_qubit_limit`],
+    /// against an estimate, and doesn't stop programs from allocating more qubits.
+    pub fn set_qubit_limit(&mut self, limit: Option<usize>) {
+        self.qubit_limit = limit;
+    }
+
+    /// Estimates the qubits allocated by the entry of the program without running it, and returns
+    /// a warning with the estimate if the program needs more qubits than the limit. Hosts can call
+    /// this before a simulation that would otherwise only fail once it runs out of qubits.
+    #[must_use]
+    pub fn check_qubit_limit(&self) -> Option<Error> {
+        let limit = self.qubit_limit?;
+        let estimate = GateCounter::new(self.compiler.package_store())
+            .program(map_fir_package_to_hir(self.source_package))?;
+        let needed = estimate.qubits.min;
+        (usize::try_from(needed).map_or(true, |needed| needed > limit))
+            .then_some(Error::QubitLimit(needed, limit))
+    }
+
+    /// Sets the precision of the amplitudes of the state vectors that the interpreter simulates.
+    /// Single precision saves 8 of the 40 bytes of each entry of a state vector, at the cost of
+    /// larger rounding errors. Setting the precision replaces the simulator, so qubits allocated
+    /// by earlier evaluations are released.
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+        self.sim = SparseSim::with_precision(precision);
+        self.sim.set_seed(self.quantum_seed);
+    }
+
+    /// Sets how values are displayed: in interpolated strings, when the REPL echoes them and in
+    /// the variables view of the debugger.
+    pub fn set_value_format(&mut self, format: ValueFormat) {
+        self.env.set_value_format(format);
+    }
+
+    /// Displays a value returned by [`Interpreter::eval_fragments`] the way the REPL echoes it,
+    /// as the type of the last fragment that was evaluated.
+    #[must_use]
+    pub fn display_result(&self, value: &Value) -> String {
+        let format = self.env.value_format();
+        let Some(stmt) = self.last_fragment else {
+            return format.display(value);
+        };
+        match self.fir_store.get_stmt((self.package, stmt).into()).kind {
+            fir::StmtKind::Expr(expr) => {
+                let ty = &self.fir_store.get_expr((self.package, expr).into()).ty;
+                format.display_typed(&self.fir_store, self.package, value, ty)
+            }
+            _ => format.display(value),
+        }
+    }
+
+    /// An environment for a new evaluation, which displays values the same way as this one.
+    fn new_env(&self) -> Env {
+        let mut env = Env::default();
+        env.set_value_format(self.env.value_format().clone());
+        env
+    }
+
+    /// Returns a warning if rounding errors have changed the norm of the state of the simulator
+    /// used for evaluation enough to skew the probabilities of results. Errors only grow that
+    /// large at single precision, so hosts that set it can call this after running a program.
+    #[must_use]
+    pub fn check_precision(&self) -> Option<Error> {
+        let error = self.sim.norm_error();
+        (error > NORM_ERROR_LIMIT).then_some(Error::PrecisionLoss(error))
+    }
+
     /// Executes the entry expression until the end of execution.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -174,12 +481,31 @@ impl Interpreter {
             expr.into(),
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.new_env(),
             &mut self.sim,
             receiver,
+            self.operation_cache.as_mut(),
+            self.coverage.as_mut(),
         )
     }
 
+    /// Converts a value returned by the entry expression into a host value, using the type of the
+    /// entry expression to recover the user-defined types in it.
+    /// # Errors
+    /// Returns an error if the value contains a value without a host representation, such as a
+    /// qubit.
+    pub fn entry_host_value(&self, value: &Value) -> Result<HostValue, interop::Error> {
+        match &self.source_package().package.entry {
+            Some(entry) => HostValue::from_typed(
+                value,
+                &entry.ty,
+                self.compiler.package_store(),
+                map_fir_package_to_hir(self.source_package),
+            ),
+            None => HostValue::try_from(value),
+        }
+    }
+
     /// Executes the entry expression until the end of execution, using the given simulator backend
     /// and a new instance of the environment.
     pub fn eval_entry_with_sim(
@@ -197,12 +523,604 @@ impl Interpreter {
             expr.into(),
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.new_env(),
             sim,
             receiver,
+            self.operation_cache.as_mut(),
+            self.coverage.as_mut(),
         )
     }
 
+    /// Executes the entry expression once per shot, each on a new simulator, and reports the
+    /// progress to the receiver after every shot. The host supplies `now`, a monotonic clock used to
+    /// time the shots, because WASM has no clock that the interpreter could read. If the receiver
+    /// fails to accept the progress, the remaining shots are cancelled. When the quantum or
+    /// classical seed is set, each shot is seeded with it plus the number of the shot, so that the
+    /// shots are repeatable without all having the same outcome.
+    pub fn run_shots(
+        &mut self,
+        receiver: &mut impl Receiver,
+        shots: u32,
+        mut now: impl FnMut() -> Duration,
+    ) -> Vec<Result<Value, Vec<Error>>> {
+        let (quantum_seed, classical_seed) = (self.quantum_seed, self.classical_seed);
+        let mut tracker = ProgressTracker::new(shots);
+        let mut results = Vec::new();
+        for shot in 0..shots {
+            self.quantum_seed = quantum_seed.map(|seed| seed.wrapping_add(shot.into()));
+            self.classical_seed = classical_seed.map(|seed| seed.wrapping_add(shot.into()));
+            let start = now();
+            let mut sim = SparseSim::with_precision(self.precision);
+            let mut counter = CountingBackend::new(&mut sim);
+            results.push(self.eval_entry_with_sim(&mut counter, receiver));
+            let progress = tracker.shot_finished(now().saturating_sub(start), counter.gates());
+            if receiver.progress(&progress).is_err() {
+                break;
+            }
+        }
+        self.quantum_seed = quantum_seed;
+        self.classical_seed = classical_seed;
+        if let Some(sink) = &mut self.telemetry {
+            sink.add(Counter::Shots, results.len() as u64);
+        }
+        results
+    }
+
+    /// Executes the entry expression on a new simulator while tracing its calls, gates and
+    /// measurements, and returns the result with the trace in the Chrome trace event format.
+    pub fn trace_entry(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> (Result<Value, Vec<Error>>, String) {
+        let mut sim = SparseSim::with_precision(self.precision);
+        let mut tracing = TracingBackend::new(&mut sim);
+        let result = self.eval_entry_with_sim(&mut tracing, receiver);
+        let trace = trace::chrome_trace(&tracing.finish(), |frame| self.frame_name(frame));
+        (result, trace)
+    }
+
+    /// Executes the entry expression on a new simulator that keeps the Pauli gates of the program
+    /// in a classical frame instead of applying them to the state, which runs programs heavy in
+    /// Pauli corrections, such as teleportation and error correction, faster. Dumped states may
+    /// differ from those of [`Self::eval_entry`] by a global phase.
+    pub fn eval_entry_with_pauli_frame(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> Result<Value, Vec<Error>> {
+        let mut sim = SparseSim::with_precision(self.precision);
+        self.eval_entry_with_sim(&mut PauliFrameBackend::new(&mut sim), receiver)
+    }
+
+    /// Runs the entry expression twice with the same seed and reports where the runs observably
+    /// differ: in their outputs, in the calls, gates and measurements of their traces, or in their
+    /// results. The runs are seeded with the quantum seed if one is set, and with a random seed
+    /// otherwise. The operation cache is not used, since the second run would hit what the first
+    /// one cached.
+    pub fn audit_entry(&mut self, position_encoding: Encoding) -> DeterminismReport {
+        let seed = self
+            .quantum_seed
+            .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        let operation_cache = self.operation_cache.take();
+        let first = self.audit_run(seed);
+        let second = self.audit_run(seed);
+        self.operation_cache = operation_cache;
+        determinism::compare(
+            seed,
+            &first,
+            &second,
+            |frame| self.frame_name(frame),
+            |frame| self.stack_frame(frame, position_encoding),
+        )
+    }
+
+    /// Executes the entry expression on a new simulator with the seed, observing its outputs and
+    /// tracing it.
+    fn audit_run(&mut self, seed: u64) -> Run {
+        let mut sim = SparseSim::with_precision(self.precision);
+        sim.set_seed(Some(seed));
+        let mut tracing = TracingBackend::new(&mut sim);
+        let mut receiver = ObservingReceiver::default();
+        let quantum_seed = self.quantum_seed.take();
+        let classical_seed = self.classical_seed.replace(seed);
+        let result = self.eval_entry_with_sim(&mut tracing, &mut receiver);
+        self.quantum_seed = quantum_seed;
+        self.classical_seed = classical_seed;
+        let result = match result {
+            Ok(value) => value.to_string(),
+            Err(errors) => errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        };
+        Run {
+            observations: receiver.finish(result),
+            trace: tracing.finish(),
+        }
+    }
+
+    /// The name of the callable of the frame, after its functors.
+    fn frame_name(&self, frame: &Frame) -> String {
+        let name = self.callable_name(frame);
+        let functor = frame.functor.to_string();
+        if functor.is_empty() {
+            name
+        } else {
+            format!("{functor} {name}")
+        }
+    }
+
+    fn callable_name(&self, frame: &Frame) -> String {
+        match self
+            .fir_store
+            .get_global(frame.id)
+            .expect("frame should exist")
+        {
+            Global::Callable(decl) => decl.name.name.to_string(),
+            Global::Udt(_) => "udt".into(),
+        }
+    }
+
+    fn stack_frame(&self, frame: &Frame, position_encoding: Encoding) -> StackFrame {
+        let hir_package = self
+            .compiler
+            .package_store()
+            .get(map_fir_package_to_hir(frame.id.package))
+            .expect("package should exist");
+        let source = hir_package
+            .sources
+            .find_by_offset(frame.span.lo)
+            .expect("frame should have a source");
+        StackFrame {
+            name: self.callable_name(frame),
+            functor: frame.functor.to_string(),
+            path: source.name.to_string(),
+            range: Range::from_span(
+                position_encoding,
+                &source.contents,
+                &(frame.span - source.offset),
+            ),
+        }
+    }
+
+    /// Executes the entry expression on a new simulator and returns the circuit of the gates and
+    /// measurements it applied, with its calls expanded or boxed as the config asks, and the
+    /// results it returned grouped into classical registers. The operation cache is not used,
+    /// since cached operations don't report the calls that led to them.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails.
+    pub fn generate_circuit(
+        &mut self,
+        config: &CircuitConfig,
+        receiver: &mut impl Receiver,
+    ) -> Result<Circuit, Vec<Error>> {
+        let mut sim = SparseSim::with_precision(self.precision);
+        let mut builder = CircuitBuilder::new(&mut sim);
+        let cache = self.operation_cache.take();
+        let result = self.eval_entry_with_sim(&mut builder, receiver);
+        self.operation_cache = cache;
+        let output = result?;
+        Ok(builder.finish(config, &output, |frame| {
+            match self
+                .fir_store
+                .get_global(frame.id)
+                .expect("frame should exist")
+            {
+                Global::Callable(decl) => decl.name.name.to_string(),
+                Global::Udt(_) => "udt".into(),
+            }
+        }))
+    }
+
+    /// Executes the entry expression on a new simulator until the circuit of the gates and
+    /// measurements it applied has `max_operations` operations, and returns that circuit, with its
+    /// calls expanded or boxed as the config asks. Long programs can be drawn this way without
+    /// running them to the end; a call that was cut short is boxed with the operations it applied
+    /// before then.
+    /// # Errors
+    /// Returns a vector of errors if evaluating the entry point fails before the circuit is full.
+    pub fn generate_partial_circuit(
+        &self,
+        config: &CircuitConfig,
+        max_operations: usize,
+        receiver: &mut impl Receiver,
+    ) -> Result<PartialCircuit, Vec<Error>> {
+        let mut sim = SparseSim::with_precision(self.precision);
+        let mut gates = self
+            .generate_circuit_iter(&mut sim, receiver)?
+            .with_max_operations(max_operations);
+        let mut output = None;
+        while !gates.is_truncated() && output.is_none() {
+            output = gates.step()?;
+        }
+        let truncated = gates.is_truncated();
+        let output = output.unwrap_or_else(Value::unit);
+        let circuit = gates.builder.finish(config, &output, |frame| {
+            match self
+                .fir_store
+                .get_global(frame.id)
+                .expect("frame should exist")
+            {
+                Global::Callable(decl) => decl.name.name.to_string(),
+                Global::Udt(_) => "udt".into(),
+            }
+        });
+        Ok(PartialCircuit { circuit, truncated })
+    }
+
+    /// Starts executing the entry expression on the given simulator, returning an iterator over
+    /// the gates and measurements it applies, without the calls that led to them. The expression
+    /// is only evaluated as far as needed for the next gate, so the gates of programs too long to
+    /// hold in memory can be passed on one at a time, and the caller can stop at any point. The
+    /// operation cache is not used.
+    /// # Errors
+    /// Returns a vector of errors if the entry expression can't be found.
+    pub fn generate_circuit_iter<'a, B, R>(
+        &'a self,
+        sim: &'a mut B,
+        receiver: &'a mut R,
+    ) -> Result<CircuitGates<'a, B, R>, Vec<Error>>
+    where
+        B: Backend,
+        B::ResultType: Into<val::Result>,
+        R: Receiver,
+    {
+        let expr = self.get_entry_expr()?;
+        if self.quantum_seed.is_some() {
+            sim.set_seed(self.quantum_seed);
+        }
+        let mut state = State::new(self.source_package, self.classical_seed);
+        qsc_eval::eval_push_expr(&mut state, expr);
+        Ok(CircuitGates {
+            package_store: self.compiler.package_store(),
+            fir_store: &self.fir_store,
+            state,
+            env: Env::default(),
+            builder: CircuitBuilder::new(sim),
+            receiver,
+            gates: VecDeque::new(),
+            finished: false,
+        })
+    }
+
+    /// Generates the circuit of one call to the operation that `operation` evaluates to, such as
+    /// the fully qualified name of an operation in a library, as [`Self::generate_circuit`] does
+    /// for the entry expression. There is one argument for each parameter of the operation, and
+    /// the qubits passed to it are allocated for the call, so no program is needed to call it.
+    /// # Errors
+    /// Returns an error if `operation` fails to compile or evaluate, doesn't evaluate to a
+    /// callable, if the arguments don't match the parameters of the operation, or if the call
+    /// fails.
+    pub fn generate_operation_circuit(
+        &mut self,
+        operation: &str,
+        args: Vec<OperationArg>,
+        config: &CircuitConfig,
+        receiver: &mut impl Receiver,
+    ) -> Result<Circuit, Vec<Error>> {
+        let callable = self.eval_callable(receiver, operation)?;
+        if let Value::Global(id, _) = &callable {
+            if let Some(Global::Callable(decl)) = self.fir_store.get_global(*id) {
+                check_operation_args(&self.fir_store, id.package, decl.input, &args)
+                    .map_err(|error| vec![error])?;
+            }
+        }
+
+        let mut sim = SparseSim::with_precision(self.precision);
+        let mut builder = CircuitBuilder::new(&mut sim);
+        let mut allocate = || Value::Qubit(val::Qubit(builder.qubit_allocate()));
+        let mut values = args
+            .into_iter()
+            .map(|arg| match arg {
+                OperationArg::Qubit => allocate(),
+                OperationArg::Qubits(len) => {
+                    Value::Array((0..len).map(|_| allocate()).collect::<Vec<_>>().into())
+                }
+                OperationArg::Value(value) => value,
+            })
+            .collect::<Vec<_>>();
+        let arg = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Tuple(values.into())
+        };
+        let output = self.invoke(&mut builder, receiver, callable, arg)?;
+        Ok(builder.finish(config, &output, |frame| {
+            match self
+                .fir_store
+                .get_global(frame.id)
+                .expect("frame should exist")
+            {
+                Global::Callable(decl) => decl.name.name.to_string(),
+                Global::Udt(_) => "udt".into(),
+            }
+        }))
+    }
+
+    /// Executes the entry expression on a new simulator and records the outcomes of its
+    /// measurements, so that the run can be reproduced with [`Self::replay_entry`]. The run is
+    /// seeded with the quantum seed if one is set, and with a random seed otherwise, which is used
+    /// for both the simulator and the classical random numbers.
+    pub fn record_entry(
+        &mut self,
+        receiver: &mut impl Receiver,
+    ) -> (Result<Value, Vec<Error>>, MeasurementLog) {
+        let seed = self
+            .quantum_seed
+            .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        let mut sim = SparseSim::with_precision(self.precision);
+        let (result, outcomes) = self.run_seeded(&mut sim, seed, Vec::new(), receiver);
+        (result, MeasurementLog { seed, outcomes })
+    }
+
+    /// Executes the entry expression so that its measurements have the outcomes in the log, to
+    /// reproduce a rare run after the program has changed, such as by added `DumpMachine` calls.
+    /// Each measurement is forced to have its logged outcome, and the classical random numbers
+    /// are seeded with the seed of the log. Returns whether the run followed the log, which it
+    /// doesn't if it made a different number of measurements or a logged outcome became
+    /// impossible.
+    pub fn replay_entry(
+        &mut self,
+        log: &MeasurementLog,
+        receiver: &mut impl Receiver,
+    ) -> (Result<Value, Vec<Error>>, bool) {
+        let mut sim = SparseSim::for_replay(self.precision);
+        let (result, outcomes) =
+            self.run_seeded(&mut sim, log.seed, log.outcomes.clone(), receiver);
+        (result, log.matches(&outcomes))
+    }
+
+    /// Executes the entry expression on the simulator with the seed, forcing the outcomes of the
+    /// log, and returns the outcomes of its measurements.
+    fn run_seeded(
+        &mut self,
+        sim: &mut SparseSim,
+        seed: u64,
+        log: Vec<bool>,
+        receiver: &mut impl Receiver,
+    ) -> (Result<Value, Vec<Error>>, Vec<bool>) {
+        sim.set_seed(Some(seed));
+        let mut recording = RecordingBackend::replaying(sim, log);
+        let quantum_seed = self.quantum_seed.take();
+        let classical_seed = self.classical_seed.replace(seed);
+        let result = self.eval_entry_with_sim(&mut recording, receiver);
+        self.quantum_seed = quantum_seed;
+        self.classical_seed = classical_seed;
+        (result, recording.finish())
+    }
+
+    /// Runs a host loop: the callable that `callable` evaluates to is called once per shot, each
+    /// on a new simulator, starting with `arg` as its argument. After each shot `on_shot` receives
+    /// the shot's index and result and decides the argument for the next shot, or stops the loop,
+    /// which stops after `max_shots` shots in any case. The callable is compiled once up front, so
+    /// adjusting the argument between shots costs no recompilation. Each argument is checked
+    /// against the input type of the callable before its shot runs.
+    /// # Errors
+    /// Returns an error if `callable` fails to compile or evaluate, doesn't evaluate to a
+    /// callable, or if an argument doesn't match the parameters of the callable.
+    pub fn run_host_loop(
+        &mut self,
+        receiver: &mut impl Receiver,
+        callable: &str,
+        mut arg: Value,
+        max_shots: u32,
+        mut on_shot: impl FnMut(u32, InterpretResult) -> ShotControl,
+    ) -> Result<(), Vec<Error>> {
+        let callable = self.eval_callable(receiver, callable)?;
+        for shot in 0..max_shots {
+            self.check_callable_arg(&callable, &arg)
+                .map_err(|error| vec![error])?;
+            let mut sim = SparseSim::with_precision(self.precision);
+            let result = self.invoke(&mut sim, receiver, callable.clone(), arg);
+            match on_shot(shot, result) {
+                ShotControl::Continue(next) => arg = next,
+                ShotControl::Stop => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the argument can be passed to the callable, which is only possible for global
+    /// callables, since the input types of closures aren't kept.
+    fn check_callable_arg(&self, callable: &Value, arg: &Value) -> Result<(), Error> {
+        let Value::Global(id, _) = callable else {
+            return Ok(());
+        };
+        let Some(Global::Callable(decl)) = self.fir_store.get_global(*id) else {
+            return Ok(());
+        };
+        let input = self.fir_store.get_pat(StorePatId {
+            package: id.package,
+            pat: decl.input,
+        });
+        let args = match (&input.kind, arg) {
+            (fir::PatKind::Tuple(_), Value::Tuple(values)) => values
+                .iter()
+                .map(|value| OperationArg::Value(value.clone()))
+                .collect::<Vec<_>>(),
+            _ => vec![OperationArg::Value(arg.clone())],
+        };
+        check_operation_args(&self.fir_store, id.package, decl.input, &args)
+    }
+
+    /// Finds the operations of the sources that could be run as the entry point.
+    #[must_use]
+    pub fn entry_point_candidates(&self) -> Vec<EntryPointCandidate> {
+        entry_points::entry_point_candidates(
+            self.compiler.package_store(),
+            map_fir_package_to_hir(self.source_package),
+        )
+    }
+
+    /// Splits the entry point of the sources into quantum kernels and a classical driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for each operation called by the driver that isn't a kernel.
+    pub fn extract_kernels(&self) -> Result<KernelProgram, Vec<Error>> {
+        let unit = self.source_package();
+        kernels::extract_kernels(
+            self.compiler.package_store(),
+            map_fir_package_to_hir(self.source_package),
+        )
+        .map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| Error::Kernel(WithSource::from_map(&unit.sources, error)))
+                .collect()
+        })
+    }
+
+    /// Performs QIR codegen for a single call of the kernel with the given fully qualified name,
+    /// such as the name of a [`kernels::Kernel`], with the given classical argument. The kernel is
+    /// looked up in the sources rather than evaluated, since the base profile doesn't allow an
+    /// entry expression that returns a callable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interpreter doesn't target the base profile, if the sources have no
+    /// callable with the name or if the call fails.
+    pub fn kernel_qir(&mut self, kernel: &str, arg: Value) -> Result<String, Vec<Error>> {
+        if self.capabilities != RuntimeCapabilityFlags::empty() {
+            return Err(vec![Error::UnsupportedRuntimeCapabilities]);
+        }
+
+        let mut stdout = std::io::sink();
+        let mut out = GenericReceiver::new(&mut stdout);
+        let callable = self
+            .source_callable(kernel)
+            .ok_or_else(|| vec![Error::NotCallable])?;
+        let mut sim = BaseProfSim::new();
+        let val = self.invoke(&mut sim, &mut out, callable, arg)?;
+
+        Ok(sim
+            .finish(&val)
+            .expect("program traced without debug info should finish"))
+    }
+
+    /// Finds the Choi matrix of the operation that `operation` evaluates to, which must take an
+    /// array of `qubits` qubits. Each shot runs on a new simulator, with the noise model, if any,
+    /// applied to the gates of the operation, and the matrix is averaged over the shots. The
+    /// operation must release any other qubits it allocates.
+    /// # Errors
+    /// Returns an error if `qubits` is more than [`tomography::MAX_QUBITS`], if `operation` fails
+    /// to compile or evaluate, doesn't evaluate to a callable, or if a call fails.
+    pub fn choi_matrix(
+        &mut self,
+        receiver: &mut impl Receiver,
+        operation: &str,
+        qubits: usize,
+        noise: Option<PauliNoise>,
+        shots: u32,
+    ) -> Result<ChoiMatrix, Vec<Error>> {
+        if qubits > tomography::MAX_QUBITS {
+            return Err(vec![Error::TomographyQubits(
+                qubits,
+                tomography::MAX_QUBITS,
+            )]);
+        }
+        let callable = self.eval_callable(receiver, operation)?;
+        let mut choi = ChoiMatrix::new(qubits);
+        for shot in 0..shots {
+            let mut sim = SparseSim::with_precision(self.precision);
+            let ids = (0..2 * qubits)
+                .map(|_| sim.qubit_allocate())
+                .collect::<Vec<_>>();
+            let (reference, target) = ids.split_at(qubits);
+            for (&r, &t) in reference.iter().zip(target) {
+                sim.h(r);
+                sim.cx(r, t);
+            }
+
+            let arg = Value::Array(
+                target
+                    .iter()
+                    .map(|&q| Value::Qubit(val::Qubit(q)))
+                    .collect::<Vec<_>>()
+                    .into(),
+            );
+            if let Some(noise) = noise {
+                let seed = self.quantum_seed.map(|seed| seed.wrapping_add(shot.into()));
+                let mut noisy = NoisyBackend::new(&mut sim, noise, seed);
+                self.invoke(&mut noisy, receiver, callable.clone(), arg)?;
+            } else {
+                self.invoke(&mut sim, receiver, callable.clone(), arg)?;
+            }
+
+            let (state, count) = sim.capture_quantum_state();
+            choi.add_state(&state, count, 1.0 / f64::from(shots));
+        }
+
+        Ok(choi)
+    }
+
+    fn eval_callable(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+    ) -> Result<Value, Vec<Error>> {
+        let callable = self.run(receiver, expr)??;
+        if matches!(callable, Value::Closure(..) | Value::Global(..)) {
+            Ok(callable)
+        } else {
+            Err(vec![Error::NotCallable])
+        }
+    }
+
+    /// Finds the callable of the sources with the given fully qualified name.
+    fn source_callable(&self, name: &str) -> Option<Value> {
+        let package = self
+            .fir_store
+            .get(self.source_package)
+            .expect("source package should be lowered to FIR");
+        package.items.iter().find_map(|(id, item)| {
+            let fir::ItemKind::Callable(decl) = &item.kind else {
+                return None;
+            };
+            let namespace = namespace_of(package, item)?;
+            let item = fir::StoreItemId {
+                package: self.source_package,
+                item: id,
+            };
+            (format!("{namespace}.{}", decl.name.name) == name)
+                .then(|| Value::Global(item, val::FunctorApp::default()))
+        })
+    }
+
+    /// Calls the callable with the argument on the given simulator with a new instance of the
+    /// environment.
+    fn invoke(
+        &self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        receiver: &mut impl Receiver,
+        callable: Value,
+        arg: Value,
+    ) -> InterpretResult {
+        if self.quantum_seed.is_some() {
+            sim.set_seed(self.quantum_seed);
+        }
+
+        qsc_eval::invoke(
+            self.package,
+            self.classical_seed,
+            callable,
+            arg,
+            &self.fir_store,
+            &mut self.new_env(),
+            sim,
+            receiver,
+        )
+        .map_err(|(error, call_stack)| {
+            eval_error(
+                self.compiler.package_store(),
+                &self.fir_store,
+                call_stack,
+                error,
+            )
+        })
+    }
+
     fn get_entry_expr(&self) -> Result<ExprId, Vec<Error>> {
         let unit = self
             .fir_store
@@ -243,6 +1161,7 @@ impl Interpreter {
         let mut result = Value::unit();
 
         for stmt_id in stmts {
+            self.last_fragment = Some(stmt_id);
             result = eval(
                 self.package,
                 self.classical_seed,
@@ -252,6 +1171,8 @@ impl Interpreter {
                 &mut self.env,
                 &mut self.sim,
                 receiver,
+                self.operation_cache.as_mut(),
+                self.coverage.as_mut(),
             )?;
         }
 
@@ -265,7 +1186,11 @@ impl Interpreter {
         receiver: &mut impl Receiver,
         expr: &str,
     ) -> Result<InterpretResult, Vec<Error>> {
-        self.run_with_sim(&mut SparseSim::new(), receiver, expr)
+        self.run_with_sim(
+            &mut SparseSim::with_precision(self.precision),
+            receiver,
+            expr,
+        )
     }
 
     /// Gets the current quantum state of the simulator.
@@ -273,20 +1198,52 @@ impl Interpreter {
         self.sim.capture_quantum_state()
     }
 
+    /// Gets the amplitudes of the basis states of the simulator that match the pattern, which
+    /// avoids collecting the whole state of a large simulation.
+    pub fn get_matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.sim
+            .matching_amplitudes(pattern)
+            .expect("sparse simulator should inspect the state")
+    }
+
     /// Performs QIR codegen using the given entry expression on a new instance of the environment
     /// and simulator but using the current compilation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interpreter doesn't target the base profile, or if the expression
+    /// fails to compile or to run. An expression that needs capabilities beyond the base profile
+    /// is rejected with an [`Error::UnsupportedEntryCapabilities`] before it runs.
     pub fn qirgen(&mut self, expr: &str) -> Result<String, Vec<Error>> {
         if self.capabilities != RuntimeCapabilityFlags::empty() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
 
+        let increment = self
+            .compiler
+            .compile_entry_expr(expr)
+            .map_err(|error| match error {
+                EntryExprError::Compile(errors) => into_errors(errors),
+                EntryExprError::Capabilities(delta, errors) => {
+                    let mut errors = into_errors(errors);
+                    errors.insert(0, Error::UnsupportedEntryCapabilities(delta));
+                    errors
+                }
+            })?;
+        let stmt_id = self.lower_expr(increment);
+
         let mut sim = BaseProfSim::new();
         let mut stdout = std::io::sink();
         let mut out = GenericReceiver::new(&mut stdout);
 
-        let val = self.run_with_sim(&mut sim, &mut out, expr)??;
+        let val = self.run_stmt_with_sim(&mut sim, &mut out, stmt_id)?;
 
-        Ok(sim.finish(&val))
+        Ok(sim
+            .finish(&val)
+            .expect("program traced without debug info should finish"))
     }
 
     /// Runs the given entry expression on the given simulator with a new instance of the environment
@@ -298,25 +1255,39 @@ impl Interpreter {
         expr: &str,
     ) -> Result<InterpretResult, Vec<Error>> {
         let stmt_id = self.compile_expr_to_stmt(expr)?;
+        Ok(self.run_stmt_with_sim(sim, receiver, stmt_id))
+    }
+
+    fn run_stmt_with_sim(
+        &mut self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        receiver: &mut impl Receiver,
+        stmt_id: StmtId,
+    ) -> InterpretResult {
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
         }
 
-        Ok(eval(
+        eval(
             self.package,
             self.classical_seed,
             stmt_id.into(),
             self.compiler.package_store(),
             &self.fir_store,
-            &mut Env::default(),
+            &mut self.new_env(),
             sim,
             receiver,
-        ))
+            self.operation_cache.as_mut(),
+            self.coverage.as_mut(),
+        )
     }
 
     fn compile_expr_to_stmt(&mut self, expr: &str) -> Result<StmtId, Vec<Error>> {
         let increment = self.compiler.compile_expr(expr).map_err(into_errors)?;
+        Ok(self.lower_expr(increment))
+    }
 
+    fn lower_expr(&mut self, increment: qsc_frontend::incremental::Increment) -> StmtId {
         let stmts = self.lower(&increment);
 
         // Updating the compiler state with the new AST/HIR nodes
@@ -328,9 +1299,7 @@ impl Interpreter {
         self.compiler.update(increment);
 
         assert!(stmts.len() == 1, "expected exactly one statement");
-        let stmt_id = stmts.first().expect("expected exactly one statement");
-
-        Ok(*stmt_id)
+        *stmts.first().expect("expected exactly one statement")
     }
 
     fn lower(&mut self, unit_addition: &qsc_frontend::incremental::Increment) -> Vec<StmtId> {
@@ -349,6 +1318,175 @@ impl Interpreter {
     }
 }
 
+/// The gates and measurements that an entry expression applies, evaluated as they are needed.
+/// See [`Interpreter::generate_circuit_iter`].
+pub struct CircuitGates<'a, B: Backend, R: Receiver> {
+    package_store: &'a PackageStore,
+    fir_store: &'a fir::PackageStore,
+    state: State,
+    env: Env,
+    builder: CircuitBuilder<'a, B>,
+    receiver: &'a mut R,
+    /// The gates applied by the last step that haven't been returned yet.
+    gates: VecDeque<Operation>,
+    finished: bool,
+}
+
+impl<'a, B, R> CircuitGates<'a, B, R>
+where
+    B: Backend,
+    B::ResultType: Into<val::Result>,
+    R: Receiver,
+{
+    /// Stops after `max` gates and measurements. Whether the program applied more is then
+    /// reported by [`Self::is_truncated`].
+    #[must_use]
+    pub fn with_max_operations(mut self, max: usize) -> Self {
+        self.builder = self.builder.with_max_operations(max);
+        self
+    }
+
+    /// Whether the program applied gates past the maximum, which were left out.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.builder.is_truncated()
+    }
+
+    /// Evaluates the entry expression up to its next statement, returning its value once it has
+    /// finished.
+    fn step(&mut self) -> Result<Option<Value>, Vec<Error>> {
+        let result = self
+            .state
+            .eval(
+                self.fir_store,
+                &mut self.env,
+                &mut self.builder,
+                self.receiver,
+                &[],
+                StepAction::In,
+            )
+            .map_err(|(error, call_stack)| {
+                eval_error(self.package_store, self.fir_store, call_stack, error)
+            })?;
+        Ok(match result {
+            StepResult::Return(value) => Some(value),
+            _ => None,
+        })
+    }
+}
+
+impl<'a, B, R> Iterator for CircuitGates<'a, B, R>
+where
+    B: Backend,
+    B::ResultType: Into<val::Result>,
+    R: Receiver,
+{
+    type Item = Result<Operation, Vec<Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(gate) = self.gates.pop_front() {
+                return Some(Ok(gate));
+            }
+            if self.finished || self.builder.is_truncated() {
+                return None;
+            }
+            match self.step() {
+                Ok(output) => self.finished = output.is_some(),
+                Err(errors) => {
+                    self.finished = true;
+                    return Some(Err(errors));
+                }
+            }
+            self.gates.extend(self.builder.take_gates());
+        }
+    }
+}
+
+/// Checks the arguments against the parameters of the callable with the input pattern, which has
+/// one parameter for each item if it is a tuple pattern. Values of user-defined types are checked
+/// against their definitions, and generic parameters can't be given values by a host.
+fn check_operation_args(
+    store: &fir::PackageStore,
+    package: PackageId,
+    input: PatId,
+    args: &[OperationArg],
+) -> Result<(), Error> {
+    let input = store.get_pat(StorePatId {
+        package,
+        pat: input,
+    });
+    let params = match &input.kind {
+        fir::PatKind::Tuple(items) => items
+            .iter()
+            .map(|&pat| &store.get_pat(StorePatId { package, pat }).ty)
+            .collect(),
+        fir::PatKind::Bind(_) | fir::PatKind::Discard => vec![&input.ty],
+    };
+    if params.len() != args.len() {
+        return Err(Error::OperationArgCount(params.len(), args.len()));
+    }
+    for (index, (param, arg)) in params.into_iter().zip(args).enumerate() {
+        let matches = match (arg, param) {
+            (OperationArg::Qubit, Ty::Prim(Prim::Qubit)) => true,
+            (OperationArg::Qubits(_), Ty::Array(item)) => **item == Ty::Prim(Prim::Qubit),
+            (OperationArg::Value(value), ty) => value_has_type(store, package, value, ty),
+            _ => false,
+        };
+        if !matches {
+            return Err(Error::OperationArgType(index, param.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the value has the type, where the type is from the package with the ID.
+fn value_has_type(store: &fir::PackageStore, package: PackageId, value: &Value, ty: &Ty) -> bool {
+    match (value, ty) {
+        (Value::Array(values), Ty::Array(item)) => values
+            .iter()
+            .all(|value| value_has_type(store, package, value, item)),
+        (Value::Tuple(values), Ty::Tuple(items)) => {
+            values.len() == items.len()
+                && values
+                    .iter()
+                    .zip(items)
+                    .all(|(value, ty)| value_has_type(store, package, value, ty))
+        }
+        // Values of user-defined types are the values of their definitions.
+        (value, Ty::Udt(fir::Res::Item(item))) => {
+            let package = item.package.unwrap_or(package);
+            match store.get_global(fir::StoreItemId {
+                package,
+                item: item.item,
+            }) {
+                Some(Global::Udt(udt)) => value_has_type(store, package, value, &udt.get_pure_ty()),
+                _ => false,
+            }
+        }
+        (Value::BigInt(_), Ty::Prim(Prim::BigInt))
+        | (Value::Bool(_), Ty::Prim(Prim::Bool))
+        | (Value::Double(_), Ty::Prim(Prim::Double))
+        | (Value::Int(_), Ty::Prim(Prim::Int))
+        | (Value::Pauli(_), Ty::Prim(Prim::Pauli))
+        | (
+            Value::Range(..),
+            Ty::Prim(Prim::Range | Prim::RangeFrom | Prim::RangeTo | Prim::RangeFull),
+        )
+        | (Value::Result(_), Ty::Prim(Prim::Result))
+        | (Value::String(_), Ty::Prim(Prim::String)) => true,
+        _ => false,
+    }
+}
+
+/// The name of the namespace that declares the item, if it is declared in one.
+fn namespace_of(package: &Package, item: &fir::Item) -> Option<Arc<str>> {
+    match &package.items.get(item.parent?)?.kind {
+        fir::ItemKind::Namespace(namespace, _) => Some(Arc::clone(&namespace.name)),
+        _ => None,
+    }
+}
+
 /// A debugger that enables step-by-step evaluation of code
 /// and inspecting state in the interpreter.
 pub struct Debugger {
@@ -414,53 +1552,38 @@ impl Debugger {
             })
     }
 
+    /// Attaches a hit condition or log message to the breakpoint on the given statement.
+    pub fn set_breakpoint_condition(&mut self, id: StmtId, condition: BreakpointCondition) {
+        self.state.set_breakpoint_condition(id, condition);
+    }
+
+    /// Removes the hit conditions and log messages from all breakpoints.
+    pub fn clear_breakpoint_conditions(&mut self) {
+        self.state.clear_breakpoint_conditions();
+    }
+
     #[must_use]
     pub fn get_stack_frames(&self) -> Vec<StackFrame> {
         let frames = self.state.get_stack_frames();
-        let stack_frames = frames
+        frames
             .iter()
-            .map(|frame| {
-                let callable = self
-                    .interpreter
-                    .fir_store
-                    .get_global(frame.id)
-                    .expect("frame should exist");
-                let functor = format!("{}", frame.functor);
-                let name = match callable {
-                    Global::Callable(decl) => decl.name.name.to_string(),
-                    Global::Udt => "udt".into(),
-                };
-
-                let hir_package = self
-                    .interpreter
-                    .compiler
-                    .package_store()
-                    .get(map_fir_package_to_hir(frame.id.package))
-                    .expect("package should exist");
-                let source = hir_package
-                    .sources
-                    .find_by_offset(frame.span.lo)
-                    .expect("frame should have a source");
-                let path = source.name.to_string();
-                StackFrame {
-                    name,
-                    functor,
-                    path,
-                    range: Range::from_span(
-                        self.position_encoding,
-                        &source.contents,
-                        &(frame.span - source.offset),
-                    ),
-                }
-            })
-            .collect();
-        stack_frames
+            .map(|frame| self.interpreter.stack_frame(frame, self.position_encoding))
+            .collect()
     }
 
     pub fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
         self.interpreter.sim.capture_quantum_state()
     }
 
+    /// Captures the amplitudes of the basis states that match the pattern, such as `1011??` for
+    /// the states where the first four qubits are `1011`.
+    pub fn capture_matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.interpreter.get_matching_amplitudes(pattern)
+    }
+
     #[must_use]
     pub fn get_breakpoints(&self, path: &str) -> Vec<BreakpointSpan> {
         let unit = self.source_package();
@@ -505,12 +1628,89 @@ impl Debugger {
             .collect()
     }
 
-    fn source_package(&self) -> &CompileUnit {
+    /// Sets how values are displayed: in interpolated strings, in logpoint messages and by
+    /// [`Debugger::display_local`].
+    pub fn set_value_format(&mut self, format: ValueFormat) {
+        self.interpreter.set_value_format(format);
+    }
+
+    /// Displays the value of a local as its declared type, the way the variables view shows it.
+    #[must_use]
+    pub fn display_local(&self, local: &VariableInfo) -> String {
         self.interpreter
-            .compiler
-            .package_store()
-            .get(map_fir_package_to_hir(self.interpreter.source_package))
-            .expect("Could not load package")
+            .env
+            .display_variable(&self.interpreter.fir_store, local)
+    }
+
+    /// Evaluates an expression in the context of the current stack frame.
+    /// The frame's locals are rebound to their current values before the expression
+    /// is evaluated, so the expression can refer to them and call any callable in scope.
+    /// Locals whose values have no literal representation, such as qubits and callables,
+    /// are not visible to the expression.
+    ///
+    /// By default evaluation is read-only: it runs against a fresh simulator and leaves the
+    /// frame untouched. When `allow_mutation` is set, the expression runs against the
+    /// debugger's simulator and updates to mutable locals are written back to the frame.
+    /// # Errors
+    /// Returns a vector of errors if compiling or evaluating the expression fails.
+    pub fn eval_in_frame(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        allow_mutation: bool,
+    ) -> InterpretResult {
+        let locals = self.get_locals();
+        let namespace = self.state.get_stack_frames().last().and_then(|frame| {
+            let package = self.interpreter.fir_store.get(frame.id.package)?;
+            namespace_of(package, package.items.get(frame.id.item)?)
+        });
+        let source = frame_eval_source(namespace.as_deref(), &locals, expr, allow_mutation);
+        let stmt_id = self.interpreter.compile_expr_to_stmt(&source)?;
+
+        if !allow_mutation {
+            return eval(
+                self.interpreter.package,
+                self.interpreter.classical_seed,
+                stmt_id.into(),
+                self.interpreter.compiler.package_store(),
+                &self.interpreter.fir_store,
+                &mut self.interpreter.new_env(),
+                &mut SparseSim::with_precision(self.interpreter.precision),
+                receiver,
+                None,
+                None,
+            );
+        }
+
+        let value = eval(
+            self.interpreter.package,
+            self.interpreter.classical_seed,
+            stmt_id.into(),
+            self.interpreter.compiler.package_store(),
+            &self.interpreter.fir_store,
+            &mut self.interpreter.new_env(),
+            &mut self.interpreter.sim,
+            receiver,
+            None,
+            None,
+        )?;
+        let (result, updates) = match &*value.unwrap_tuple() {
+            [result, updates] => (result.clone(), updates.clone().unwrap_tuple()),
+            _ => panic!("frame evaluation should produce a result and updated locals"),
+        };
+        for (local, update) in mutable_frame_locals(&locals)
+            .into_iter()
+            .zip(updates.iter())
+        {
+            self.interpreter
+                .env
+                .update_variable_in_top_frame(&local.name, update.clone());
+        }
+        Ok(result)
+    }
+
+    fn source_package(&self) -> &CompileUnit {
+        self.interpreter.source_package()
     }
 }
 
@@ -525,12 +1725,25 @@ fn eval(
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
+    cache: Option<&mut OperationCache>,
+    coverage: Option<&mut Coverage>,
 ) -> InterpretResult {
-    qsc_eval::eval(package, classical_seed, id, fir_store, env, sim, receiver)
-        .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
+    qsc_eval::eval(
+        package,
+        classical_seed,
+        id,
+        fir_store,
+        env,
+        sim,
+        receiver,
+        cache,
+        coverage,
+    )
+    .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
 }
 
 /// Represents a stack frame for debugging.
+#[derive(Clone, Debug, PartialEq)]
 pub struct StackFrame {
     /// The name of the callable.
     pub name: String,
@@ -655,6 +1868,15 @@ fn eval_error(
     vec![error::from_eval(error, package_store, stack_trace).into()]
 }
 
+/// The names of the capabilities, as in `ForwardBranching, IntegerComputations`.
+fn capability_names(capabilities: RuntimeCapabilityFlags) -> String {
+    capabilities
+        .iter_names()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn into_errors(errors: Vec<crate::compile::Error>) -> Vec<Error> {
     errors
         .into_iter()
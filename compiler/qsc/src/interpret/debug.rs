@@ -4,11 +4,16 @@
 #[cfg(test)]
 mod tests;
 
-use qsc_eval::debug::{map_fir_package_to_hir, Frame};
-use qsc_fir::fir::{Global, PackageStoreLookup, StoreItemId};
+use qsc_eval::{
+    debug::{map_fir_package_to_hir, Frame},
+    val::Value,
+    VariableInfo,
+};
+use qsc_fir::fir::{Global, Mutability, PackageStoreLookup, Pauli, StoreItemId};
 use qsc_frontend::compile::PackageStore;
 use qsc_hir::hir;
 use qsc_hir::hir::{Item, ItemKind};
+use std::fmt::Write;
 
 #[must_use]
 pub(crate) fn format_call_stack(
@@ -88,3 +93,128 @@ fn get_ns_name(item: &Item) -> Option<String> {
         None
     }
 }
+
+/// The name used to hold the result of an expression evaluated in a stack frame.
+const FRAME_EVAL_RESULT: &str = "__frame_eval_result";
+
+/// Builds the source of a block expression that opens the namespace of the frame's callable and
+/// rebinds the given locals to their current values before evaluating `expr`. When
+/// `allow_mutation` is set, the block evaluates to a tuple of the expression result and the final
+/// values of the mutable locals, in the order returned by [`mutable_frame_locals`].
+#[must_use]
+pub(crate) fn frame_eval_source(
+    namespace: Option<&str>,
+    locals: &[VariableInfo],
+    expr: &str,
+    allow_mutation: bool,
+) -> String {
+    let mut source = String::from("{ ");
+    if let Some(namespace) = namespace {
+        write!(source, "open {namespace}; ").expect("string should be writable");
+    }
+    for local in bindable_locals(locals) {
+        let literal = value_to_literal(&local.value).expect("local should be bindable");
+        let keyword = if allow_mutation && local.mutability == Mutability::Mutable {
+            "mutable"
+        } else {
+            "let"
+        };
+        write!(source, "{keyword} {} = {literal}; ", local.name)
+            .expect("string should be writable");
+    }
+
+    if allow_mutation {
+        let mut mutables = String::new();
+        for local in mutable_frame_locals(locals) {
+            write!(mutables, "{}, ", local.name).expect("string should be writable");
+        }
+        write!(
+            source,
+            "let {FRAME_EVAL_RESULT} = ({expr}); ({FRAME_EVAL_RESULT}, ({mutables})) }}"
+        )
+        .expect("string should be writable");
+    } else {
+        write!(source, "({expr}) }}").expect("string should be writable");
+    }
+
+    source
+}
+
+/// Returns the mutable locals that are rebound by [`frame_eval_source`].
+#[must_use]
+pub(crate) fn mutable_frame_locals(locals: &[VariableInfo]) -> Vec<&VariableInfo> {
+    bindable_locals(locals)
+        .into_iter()
+        .filter(|local| local.mutability == Mutability::Mutable)
+        .collect()
+}
+
+/// Returns the locals that can be rebound by name, keeping only the innermost binding
+/// when a name is declared more than once in the frame.
+fn bindable_locals(locals: &[VariableInfo]) -> Vec<&VariableInfo> {
+    let mut bindable: Vec<&VariableInfo> = Vec::new();
+    for local in locals.iter().rev() {
+        if value_to_literal(&local.value).is_some()
+            && !bindable.iter().any(|other| other.name == local.name)
+        {
+            bindable.push(local);
+        }
+    }
+    bindable.reverse();
+    bindable
+}
+
+/// Renders a value as Q# source that evaluates to the same value.
+/// Returns `None` for values that have no literal representation, such as qubits, callables,
+/// empty arrays (whose item type cannot be inferred) and open-ended ranges.
+fn value_to_literal(value: &Value) -> Option<String> {
+    match value {
+        Value::Array(arr) if arr.is_empty() => None,
+        Value::Array(arr) => {
+            let items = arr
+                .iter()
+                .map(value_to_literal)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", items.join(", ")))
+        }
+        Value::BigInt(v) => Some(format!("{v}L")),
+        Value::Bool(v) => Some(v.to_string()),
+        Value::Double(v) if v.is_finite() => Some(format!("{v:?}")),
+        Value::Int(v) if *v != i64::MIN => Some(v.to_string()),
+        Value::Pauli(p) => Some(
+            match p {
+                Pauli::I => "PauliI",
+                Pauli::X => "PauliX",
+                Pauli::Y => "PauliY",
+                Pauli::Z => "PauliZ",
+            }
+            .to_string(),
+        ),
+        Value::Range(Some(start), step, Some(end)) => Some(format!("{start}..{step}..{end}")),
+        Value::Result(qsc_eval::val::Result::Val(v)) => {
+            Some(if *v { "One" } else { "Zero" }.to_string())
+        }
+        Value::String(v) => Some(format!(
+            "\"{}\"",
+            v.replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+        Value::Tuple(tup) => {
+            let items = tup
+                .iter()
+                .map(value_to_literal)
+                .collect::<Option<Vec<_>>>()?;
+            if items.len() == 1 {
+                Some(format!("({},)", items[0]))
+            } else {
+                Some(format!("({})", items.join(", ")))
+            }
+        }
+        Value::Closure(..)
+        | Value::Double(_)
+        | Value::Global(..)
+        | Value::Int(_)
+        | Value::Qubit(_)
+        | Value::Range(..)
+        | Value::Result(_) => None,
+    }
+}
@@ -3,7 +3,7 @@
 
 #![allow(clippy::needless_raw_string_hashes)]
 
-use crate::interpret::Debugger;
+use crate::interpret::{BreakpointCondition, Debugger, HitCondition};
 use crate::line_column::Encoding;
 use qsc_eval::{output::CursorReceiver, StepAction, StepResult};
 use qsc_fir::fir::StmtId;
@@ -69,6 +69,15 @@ fn step(
     )
 }
 
+fn expect_continue_return(debugger: &mut Debugger, expected: &str) {
+    let r = step(debugger, &[], StepAction::Continue);
+    match r.0 {
+        Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), expected),
+        Ok(v) => panic!("Expected Return, got {v:?}"),
+        Err(e) => panic!("Expected Return, got {e:?}"),
+    }
+}
+
 fn expect_next(debugger: &mut Debugger) {
     let result = step_next(debugger, &[]);
     match result.0 {
@@ -204,4 +213,189 @@ mod given_debugger {
             Ok(())
         }
     }
+
+    static FRAME_EVAL_SOURCE: &str = r#"
+        namespace Test {
+            function Square(x : Int) : Int {
+                x * x
+            }
+            @EntryPoint()
+            operation Main() : Int {
+                mutable count = 3;
+                let name = "q";
+                set count += 1;
+                count
+            }
+        }"#;
+
+    fn break_at_line(debugger: &mut Debugger, line: u32) {
+        let bp = debugger
+            .get_breakpoints("test")
+            .into_iter()
+            .find(|bp| bp.range.start.line == line)
+            .expect("breakpoint should exist on line");
+        let id = StmtId::from(bp.id);
+        expect_bp(debugger, &[id], id);
+    }
+
+    fn eval_in_frame(
+        debugger: &mut Debugger,
+        expr: &str,
+        allow_mutation: bool,
+    ) -> Result<String, Vec<crate::interpret::Error>> {
+        let mut cursor = Cursor::new(Vec::<u8>::new());
+        let mut receiver = CursorReceiver::new(&mut cursor);
+        debugger
+            .eval_in_frame(&mut receiver, expr, allow_mutation)
+            .map(|value| value.to_string())
+    }
+
+    #[cfg(test)]
+    mod eval_in_frame {
+        use super::*;
+
+        #[test]
+        fn expression_can_use_locals_and_call_functions() -> Result<(), Vec<crate::interpret::Error>>
+        {
+            let sources = SourceMap::new([("test".into(), FRAME_EVAL_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            break_at_line(&mut debugger, 9);
+            assert_eq!(
+                eval_in_frame(&mut debugger, "Square(count) + 1", false)?,
+                "10"
+            );
+            assert_eq!(
+                eval_in_frame(&mut debugger, "$\"{name}{count}\"", false)?,
+                "q3"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn read_only_evaluation_cannot_update_locals() -> Result<(), Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), FRAME_EVAL_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            break_at_line(&mut debugger, 9);
+            assert!(eval_in_frame(&mut debugger, "set count = 10", false).is_err());
+            expect_continue_return(&mut debugger, "4");
+            Ok(())
+        }
+
+        #[test]
+        fn mutation_updates_frame_locals() -> Result<(), Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), FRAME_EVAL_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            break_at_line(&mut debugger, 9);
+            assert_eq!(eval_in_frame(&mut debugger, "set count = 10", true)?, "()");
+            expect_continue_return(&mut debugger, "11");
+            Ok(())
+        }
+
+        #[test]
+        fn immutable_locals_cannot_be_updated_with_mutation_allowed(
+        ) -> Result<(), Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), FRAME_EVAL_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            break_at_line(&mut debugger, 9);
+            assert!(eval_in_frame(&mut debugger, "set name = \"r\"", true).is_err());
+            Ok(())
+        }
+    }
+
+    static LOOP_SOURCE: &str = r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                mutable total = 0;
+                for i in 0..4 {
+                    set total += i;
+                }
+                total
+            }
+        }"#;
+
+    fn breakpoint_on_line(debugger: &Debugger, line: u32) -> StmtId {
+        let bp = debugger
+            .get_breakpoints("test")
+            .into_iter()
+            .find(|bp| bp.range.start.line == line)
+            .expect("breakpoint should exist on line");
+        StmtId::from(bp.id)
+    }
+
+    #[cfg(test)]
+    mod breakpoint_conditions {
+        use super::*;
+
+        #[test]
+        fn hit_count_stops_only_on_matching_hit() -> Result<(), Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), LOOP_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            let id = breakpoint_on_line(&debugger, 6);
+            debugger.set_breakpoint_condition(
+                id,
+                BreakpointCondition {
+                    hit_condition: Some(HitCondition::Equal(3)),
+                    log_message: None,
+                },
+            );
+            expect_bp(&mut debugger, &[id], id);
+            let total = debugger
+                .get_locals()
+                .into_iter()
+                .find(|local| &*local.name == "total")
+                .expect("total should be a local");
+            assert_eq!(total.value.to_string(), "1");
+            let (result, _) = step(&mut debugger, &[id], StepAction::Continue);
+            match result {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), "10"),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn logpoint_emits_message_without_stopping() -> Result<(), Vec<crate::interpret::Error>> {
+            let sources = SourceMap::new([("test".into(), LOOP_SOURCE.into())], None);
+            let mut debugger =
+                Debugger::new(sources, RuntimeCapabilityFlags::all(), Encoding::Utf8)?;
+            debugger.set_entry()?;
+            let id = breakpoint_on_line(&debugger, 6);
+            debugger.set_breakpoint_condition(
+                id,
+                BreakpointCondition {
+                    hit_condition: Some(HitCondition::AtLeast(4)),
+                    log_message: Some("{{total}} = {total}, i = {i}".into()),
+                },
+            );
+            let (result, output) = step(&mut debugger, &[id], StepAction::Continue);
+            match result {
+                Ok(StepResult::Return(value)) => assert_eq!(value.to_string(), "10"),
+                Ok(v) => panic!("Expected Return, got {v:?}"),
+                Err(e) => panic!("Expected Return, got {e:?}"),
+            }
+            assert_eq!(output, "{total} = 3, i = 3\n{total} = 6, i = 4");
+            Ok(())
+        }
+
+        #[test]
+        fn hit_conditions_parse() {
+            assert_eq!("3".parse(), Ok(HitCondition::Equal(3)));
+            assert_eq!("== 3".parse(), Ok(HitCondition::Equal(3)));
+            assert_eq!(">= 2".parse(), Ok(HitCondition::AtLeast(2)));
+            assert_eq!("% 5".parse(), Ok(HitCondition::Multiple(5)));
+            assert_eq!("< 5".parse::<HitCondition>(), Err(()));
+        }
+    }
 }
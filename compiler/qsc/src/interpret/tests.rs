@@ -41,7 +41,7 @@ mod given_interpreter {
     mod without_sources {
         use expect_test::expect;
         use indoc::indoc;
-        use qsc_frontend::compile::RuntimeCapabilityFlags;
+        use qsc_frontend::compile::{ConfigAttr, RuntimeCapabilityFlags};
 
         use super::*;
 
@@ -947,12 +947,35 @@ mod given_interpreter {
             is_error(
                 &res,
                 &expect![[r#"
+                entry expression needs runtime capabilities that the target doesn't support: IntegerComputations
                 non-Result return type in entry expression
                    [<entry>] [1]
             "#]],
             );
         }
 
+        #[test]
+        fn qirgen_entry_expr_capabilities_are_checked_before_running() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+            )
+            .expect("interpreter should be created");
+            let res = interpreter
+                .qirgen("{ use q = Qubit(); if M(q) == One { X(q); } let r = M(q); Reset(q); r }")
+                .expect_err("expected qirgen to fail");
+            let Some(Error::UnsupportedEntryCapabilities(delta)) = res.first() else {
+                panic!("expected unsupported capabilities, got {res:?}");
+            };
+            assert_eq!(delta.missing, RuntimeCapabilityFlags::ForwardBranching);
+            assert_eq!(delta.minimum_profile, ConfigAttr::Adaptive);
+            assert!(res[1..]
+                .iter()
+                .all(|error| matches!(error, Error::Compile(_))));
+        }
+
         #[test]
         fn run_with_shots() {
             let mut interpreter = get_interpreter();
@@ -1035,6 +1058,67 @@ mod given_interpreter {
                 );
             }
         }
+        #[test]
+        fn matching_amplitudes_are_filtered_by_pattern() {
+            use crate::interpret::BasisPattern;
+            use num_bigint::BigUint;
+
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "use qs = Qubit[3]; X(qs[0]); H(qs[2]);");
+            is_only_value(&result, &output, &Value::unit());
+
+            let pattern = "1?1".parse::<BasisPattern>().expect("pattern should parse");
+            let (state, qubits) = interpreter.get_matching_amplitudes(&pattern);
+            assert_eq!(qubits, 3);
+            assert_eq!(
+                state.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>(),
+                [BigUint::from(0b101_u32)]
+            );
+            assert!((state[0].1.re - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-10);
+
+            let pattern = BasisPattern::default().fix(1, true);
+            let (state, _) = interpreter.get_matching_amplitudes(&pattern);
+            assert!(state.is_empty());
+            let error = "10x"
+                .parse::<BasisPattern>()
+                .expect_err("pattern should not parse");
+            assert_eq!((error.position, error.found), (2, 'x'));
+        }
+
+        #[test]
+        fn values_are_displayed_with_the_value_format() {
+            use crate::interpret::{Ellipsis, ValueFormat};
+
+            let mut interpreter = get_interpreter();
+            interpreter.set_value_format(
+                ValueFormat::default()
+                    .with_max_items(3)
+                    .with_ellipsis(Ellipsis::Counted)
+                    .with_formatter("Point", |value| match value {
+                        Value::Tuple(items) => format!("<{}, {}>", items[0], items[1]),
+                        _ => panic!("point should be a tuple"),
+                    }),
+            );
+            let (result, output) = line(&mut interpreter, "newtype Point = (X : Int, Y : Int);");
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = line(&mut interpreter, "let p = Point(1, 2);");
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = line(
+                &mut interpreter,
+                r#"Message($"{p} and {[p, p]} in {[1, 2, 3, 4, 5]}")"#,
+            );
+            is_unit_with_output(
+                &result,
+                &output,
+                "<1, 2> and [<1, 2>, <1, 2>] in [1, 2, 3, ...2 more]",
+            );
+            let (result, _) = line(&mut interpreter, "(p, [1, 2, 3, 4])");
+            let value = result.expect("line should evaluate");
+            assert_eq!(
+                interpreter.display_result(&value),
+                "(<1, 2>, [1, 2, 3, ...1 more])"
+            );
+        }
     }
 
     fn get_interpreter() -> Interpreter {
@@ -1129,6 +1213,7 @@ mod given_interpreter {
         use super::*;
         use crate::interpret::Debugger;
         use crate::line_column::Encoding;
+        use crate::replay::MeasurementLog;
         use expect_test::expect;
         use indoc::indoc;
         use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
@@ -1157,6 +1242,35 @@ mod given_interpreter {
             is_unit_with_output_eval_entry(&result, &output, "hello there...");
         }
 
+        #[test]
+        fn entry_point_is_called_with_default_values() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                @Default(count, 2 * 3)
+                @Default(scale, -0.5)
+                operation Main(count : Int, scale : Double) : (Int, Double) {
+                    (count, scale)
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let (result, output) = entry(&mut interpreter);
+            is_only_value(
+                &result,
+                &output,
+                &Value::Tuple(vec![Value::Int(6), Value::Double(-0.5)].into()),
+            );
+        }
+
         #[test]
         fn stdlib_members_can_be_accessed_from_sources() {
             let source = indoc! { r#"
@@ -1307,5 +1421,552 @@ mod given_interpreter {
                 "#]],
             );
         }
+
+        #[test]
+        fn host_io_is_recorded_and_replayed() {
+            use crate::host_io::{HostIo, HostIoBackend, Recorder, ReplayLog, Replayer};
+            use qsc_eval::backend::SparseSim;
+
+            struct Host;
+
+            impl HostIo for Host {
+                fn read_parameter(&mut self, name: &str) -> Result<f64, String> {
+                    Ok(if name == "scale" { 2.0 } else { 0.0 })
+                }
+
+                fn fetch_dataset_row(&mut self, _: &str, row: i64) -> Result<Vec<f64>, String> {
+                    Ok(vec![1.5; usize::try_from(row).unwrap_or_default()])
+                }
+            }
+
+            let source = indoc! { r#"
+            namespace Test {
+                open Microsoft.Quantum.Host;
+                @EntryPoint()
+                operation Main() : Double {
+                    let scale = ReadParameter("scale");
+                    mutable total = 0.0;
+                    for value in FetchDatasetRow("data", 2) {
+                        set total += scale * value;
+                    }
+                    total
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let mut recorder = Recorder::new(Host);
+            let result = interpreter.eval_entry_with_sim(
+                &mut HostIoBackend::new(&mut SparseSim::new(), &mut recorder),
+                &mut receiver,
+            );
+            assert_eq!(result.expect("entry should succeed"), Value::Double(6.0));
+
+            let log = recorder
+                .into_log()
+                .to_string()
+                .parse::<ReplayLog>()
+                .expect("log should parse");
+            let mut replayer = Replayer::new(log);
+            let result = interpreter.eval_entry_with_sim(
+                &mut HostIoBackend::new(&mut SparseSim::new(), &mut replayer),
+                &mut receiver,
+            );
+            assert_eq!(result.expect("replay should succeed"), Value::Double(6.0));
+        }
+
+        #[test]
+        fn run_shots_reports_progress_and_can_be_cancelled() {
+            use crate::progress::Progress;
+            use num_bigint::BigUint;
+            use num_complex::Complex64;
+            use qsc_eval::output::{self, Receiver};
+            use std::time::Duration;
+
+            struct ProgressReceiver {
+                progress: Vec<Progress>,
+                cancel_after: u32,
+            }
+
+            impl Receiver for ProgressReceiver {
+                fn state(
+                    &mut self,
+                    _: Vec<(BigUint, Complex64)>,
+                    _: usize,
+                ) -> Result<(), output::Error> {
+                    Ok(())
+                }
+
+                fn message(&mut self, _: &str) -> Result<(), output::Error> {
+                    Ok(())
+                }
+
+                fn progress(&mut self, progress: &Progress) -> Result<(), output::Error> {
+                    self.progress.push(*progress);
+                    if progress.shots_completed == self.cancel_after {
+                        Err(output::Error)
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+
+            let source = indoc! { r#"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    H(q);
+                    X(q);
+                    MResetZ(q)
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut receiver = ProgressReceiver {
+                progress: Vec::new(),
+                cancel_after: 3,
+            };
+            let mut clock = Duration::ZERO;
+            let results = interpreter.run_shots(&mut receiver, 5, || {
+                clock += Duration::from_millis(5);
+                clock
+            });
+
+            assert_eq!(results.len(), 3);
+            assert!(results.iter().all(Result::is_ok));
+            assert_eq!(
+                receiver.progress.last(),
+                Some(&Progress {
+                    shots_completed: 3,
+                    total_shots: 5,
+                    gates_applied: 9,
+                    eta: Some(Duration::from_millis(10)),
+                })
+            );
+        }
+
+        #[test]
+        fn seeded_shots_are_repeatable_and_differ() {
+            use crate::interpret::GenericReceiver;
+            use std::time::Duration;
+
+            let source = indoc! { r#"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    H(q);
+                    MResetZ(q)
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_quantum_seed(Some(42));
+
+            let mut run = || {
+                let mut out = Vec::new();
+                interpreter
+                    .run_shots(&mut GenericReceiver::new(&mut out), 16, || Duration::ZERO)
+                    .into_iter()
+                    .map(|result| result.expect("shot should succeed"))
+                    .collect::<Vec<_>>()
+            };
+            let results = run();
+            assert_eq!(results, run());
+            assert!(
+                results.contains(&Value::RESULT_ZERO) && results.contains(&Value::RESULT_ONE),
+                "{results:?}"
+            );
+        }
+
+        #[test]
+        fn pauli_frame_gives_the_same_results() {
+            let source = indoc! { r#"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use qs = Qubit[3];
+                    X(qs[0]);
+                    CNOT(qs[0], qs[1]);
+                    Z(qs[1]);
+                    H(qs[2]);
+                    Z(qs[2]);
+                    H(qs[2]);
+                    S(qs[1]);
+                    Y(qs[0]);
+                    T(qs[2]);
+                    [MResetZ(qs[0]), MResetZ(qs[1]), MResetZ(qs[2])]
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let (expected, _) = entry(&mut interpreter);
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let result = interpreter.eval_entry_with_pauli_frame(&mut receiver);
+            assert_eq!(
+                result.expect("entry should run"),
+                expected.expect("entry should run")
+            );
+        }
+
+        #[test]
+        fn repeated_operation_calls_are_replayed_from_cache() {
+            let source = indoc! { r#"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                operation Flip(qs : Qubit[]) : Unit {
+                    for q in qs {
+                        X(q);
+                    }
+                }
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use qs = Qubit[2];
+                    for _ in 1..5 {
+                        Flip(qs);
+                    }
+                    [MResetZ(qs[0]), MResetZ(qs[1])]
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_operation_cache(true);
+
+            let expected = Value::Array(vec![Value::RESULT_ONE, Value::RESULT_ONE].into());
+            let (result, _) = entry(&mut interpreter);
+            assert_eq!(result.expect("run should succeed"), expected);
+            let hits = interpreter
+                .operation_cache()
+                .expect("cache should be enabled")
+                .hits();
+            assert!(hits >= 3, "{hits}");
+
+            // The cache is kept for the next run, where every call to `Flip` is replayed.
+            let (result, _) = entry(&mut interpreter);
+            assert_eq!(result.expect("run should succeed"), expected);
+            let cache = interpreter
+                .operation_cache()
+                .expect("cache should be enabled");
+            assert!(cache.hits() >= hits + 5, "{}", cache.hits());
+        }
+
+        #[test]
+        fn deprecated_aliases_are_taken_as_warnings_once() {
+            let source = indoc! { r#"
+            namespace Current {
+                @Alias("Legacy.Old")
+                function New() : Int { 1 }
+            }
+            namespace Test {
+                function Main() : Int {
+                    Legacy.Old()
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            is_error(
+                &interpreter.take_warnings(),
+                &expect![[r#"
+                    name error: `Legacy.Old` is a deprecated alias of `Current.New`
+                       [test] [Legacy.Old]
+                "#]],
+            );
+            assert!(interpreter.take_warnings().is_empty());
+
+            let (result, output) = line(&mut interpreter, "Legacy.Old() + Test.Main()");
+            is_only_value(&result, &output, &Value::Int(2));
+            is_error(
+                &interpreter.take_warnings(),
+                &expect![[r#"
+                    name error: `Legacy.Old` is a deprecated alias of `Current.New`
+                       [line_0] [Legacy.Old]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn qubit_limit_warns_with_estimate_before_running() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use qs = Qubit[10];
+                    use q = Qubit();
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+            assert!(interpreter.check_qubit_limit().is_none());
+
+            interpreter.set_qubit_limit(Some(11));
+            assert!(interpreter.check_qubit_limit().is_none());
+
+            interpreter.set_qubit_limit(Some(8));
+            let warning = interpreter
+                .check_qubit_limit()
+                .expect("limit should be exceeded");
+            assert_eq!(warning.severity(), Some(miette::Severity::Warning));
+            expect!["program allocates at least 11 qubits, more than the limit of 8"]
+                .assert_eq(&warning.to_string());
+        }
+
+        #[test]
+        fn host_loop_passes_each_shot_result_to_the_host() {
+            use crate::interpret::ShotControl;
+
+            let source = indoc! { r#"
+            namespace Test {
+                operation Double(n : Int) : Int {
+                    use q = Qubit();
+                    X(q);
+                    Reset(q);
+                    2 * n
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let mut shots = Vec::new();
+            interpreter
+                .run_host_loop(
+                    &mut receiver,
+                    "Test.Double",
+                    Value::Int(1),
+                    100,
+                    |shot, result| {
+                        let value = result.expect("shot should succeed");
+                        shots.push((shot, value.clone()));
+                        match value {
+                            Value::Int(n) if n < 16 => ShotControl::Continue(value),
+                            _ => ShotControl::Stop,
+                        }
+                    },
+                )
+                .expect("host loop should run");
+
+            assert_eq!(
+                shots,
+                vec![
+                    (0, Value::Int(2)),
+                    (1, Value::Int(4)),
+                    (2, Value::Int(8)),
+                    (3, Value::Int(16)),
+                ]
+            );
+        }
+
+        #[test]
+        fn host_loop_stops_after_max_shots() {
+            use crate::interpret::ShotControl;
+
+            let sources = SourceMap::new([("test".into(), "".into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let mut shots = 0;
+            interpreter
+                .run_host_loop(
+                    &mut receiver,
+                    "Microsoft.Quantum.Math.AbsI",
+                    Value::Int(-1),
+                    3,
+                    |_, result| {
+                        shots += 1;
+                        ShotControl::Continue(result.expect("shot should succeed"))
+                    },
+                )
+                .expect("host loop should run");
+            assert_eq!(shots, 3);
+        }
+
+        #[test]
+        fn host_loop_rejects_arguments_that_do_not_match() {
+            use crate::interpret::ShotControl;
+
+            let sources = SourceMap::new([("test".into(), "".into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let mut shots = 0;
+            let errors = interpreter
+                .run_host_loop(
+                    &mut receiver,
+                    "Microsoft.Quantum.Math.AbsI",
+                    Value::Int(-1),
+                    100,
+                    |_, _| {
+                        shots += 1;
+                        ShotControl::Continue(Value::Bool(true))
+                    },
+                )
+                .expect_err("host loop should fail");
+            assert_eq!(shots, 1);
+            assert!(
+                matches!(errors.as_slice(), [Error::OperationArgType(0, ty)] if ty == "Int"),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn host_loop_rejects_values_that_are_not_callable() {
+            let sources = SourceMap::new([("test".into(), "".into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+            )
+            .expect("interpreter should be created");
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let errors = interpreter
+                .run_host_loop(&mut receiver, "42", Value::unit(), 100, |_, _| {
+                    panic!("no shots should run")
+                })
+                .expect_err("host loop should fail");
+            assert!(
+                matches!(errors.as_slice(), [Error::NotCallable]),
+                "{errors:?}"
+            );
+        }
+
+        #[test]
+        fn recorded_outcomes_are_replayed_after_the_program_changes() {
+            let program = |extra: &str| {
+                format!(
+                    "namespace Test {{
+                        @EntryPoint()
+                        operation Main() : Result[] {{
+                            use qs = Qubit[8];
+                            ApplyToEach(H, qs);
+                            {extra}
+                            Microsoft.Quantum.Measurement.MResetEachZ(qs)
+                        }}
+                    }}"
+                )
+            };
+            let interpreter = |source: String| {
+                Interpreter::new(
+                    true,
+                    SourceMap::new([("test".into(), source.into())], None),
+                    PackageType::Exe,
+                    RuntimeCapabilityFlags::all(),
+                )
+                .expect("interpreter should be created")
+            };
+
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let (recorded, log) = interpreter(program("")).record_entry(&mut receiver);
+            let recorded = recorded.expect("entry should succeed");
+            assert_eq!(log.outcomes.len(), 8);
+            let log = log.to_string().parse().expect("log should parse");
+
+            let (replayed, followed) =
+                interpreter(program(r#"Message("replaying");"#)).replay_entry(&log, &mut receiver);
+            assert!(followed, "replay should follow the log");
+            assert_eq!(replayed.expect("entry should succeed"), recorded);
+            assert_eq!(receiver.dump(), "replaying");
+
+            // Any possible outcomes are replayed, not just those that the seed of the log gives.
+            let ones = MeasurementLog {
+                seed: log.seed,
+                outcomes: vec![true; 8],
+            };
+            let (replayed, followed) = interpreter(program("")).replay_entry(&ones, &mut receiver);
+            assert!(followed, "replay should follow the log");
+            assert_eq!(
+                replayed.expect("entry should succeed"),
+                Value::Array(vec![Value::RESULT_ONE; 8].into())
+            );
+
+            // A run that measures more qubits than the logged one doesn't follow the log.
+            let (_, followed) =
+                interpreter(program("let _ = M(qs[0]);")).replay_entry(&log, &mut receiver);
+            assert!(!followed);
+        }
     }
 }
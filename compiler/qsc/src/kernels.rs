@@ -0,0 +1,443 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Quantum kernel extraction, which splits a hybrid program into quantum kernels and a classical
+//! driver so that it can run on a target that only accepts base profile programs.
+//!
+//! A kernel is an operation that takes only classical arguments, allocates and measures its own
+//! qubits and never inspects a measurement result, so each call to it can be generated as a base
+//! profile program of its own. The driver is the rest of the entry point, which runs on the host
+//! and calls the kernels. For each kernel call in the driver, the extracted program records which
+//! earlier calls produced the results that feed its arguments or decide whether it runs.
+
+#[cfg(test)]
+mod tests;
+
+use crate::graph::namespace_of;
+use miette::Diagnostic;
+use qsc_data_structures::{message::MessageParams, span::Span};
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    hir::{
+        BinOp, CallableDecl, CallableKind, Expr, ExprKind, ItemId, ItemKind, NodeId, PackageId,
+        Pat, PatKind, Res, SpecBody, Stmt, StmtKind,
+    },
+    ty::{Prim, Ty},
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    mem::{replace, take},
+};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("operation called from the classical driver is not a quantum kernel")]
+    #[diagnostic(help(
+        "kernels take only classical arguments, return only measurement results and never compare them; move the quantum operations of the entry point into such an operation"
+    ))]
+    #[diagnostic(code("Qsc.Kernels.NotAKernel"))]
+    NotAKernel(#[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::NotAKernel(_) => Vec::new(),
+        }
+    }
+}
+
+/// An operation that can be generated as a base profile program on its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    pub id: ItemId,
+    /// The fully qualified name of the operation.
+    pub name: String,
+    pub input: Ty,
+    pub output: Ty,
+}
+
+/// A call to a kernel made by the driver.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KernelCall {
+    /// The index of the kernel that is called.
+    pub kernel: usize,
+    pub span: Span,
+    /// The indices of the earlier calls whose results feed the arguments of this call or decide
+    /// whether it is made.
+    pub depends_on: Vec<usize>,
+}
+
+/// A hybrid program split into quantum kernels and the calls that the classical driver makes to
+/// them, in source order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KernelProgram {
+    pub kernels: Vec<Kernel>,
+    pub calls: Vec<KernelCall>,
+}
+
+impl Display for KernelProgram {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, kernel) in self.kernels.iter().enumerate() {
+            writeln!(
+                f,
+                "kernel {index}: {} : {} => {}",
+                kernel.name,
+                kernel.input.display(),
+                kernel.output.display()
+            )?;
+        }
+        for (index, call) in self.calls.iter().enumerate() {
+            write!(f, "call {index}: kernel {}", call.kernel)?;
+            if !call.depends_on.is_empty() {
+                let calls = call
+                    .depends_on
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                write!(f, " after calls {}", calls.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits the entry point of the package into kernels and a classical driver. If the entry point
+/// calls a callable that isn't a kernel, the body of that callable is the driver, otherwise the
+/// entry expression itself is.
+///
+/// # Errors
+///
+/// Returns an error for each operation that the driver calls which can't be extracted as a kernel,
+/// including the allocation of qubits outside of a kernel.
+pub fn extract_kernels(
+    store: &PackageStore,
+    package: PackageId,
+) -> Result<KernelProgram, Vec<Error>> {
+    let Some(entry) = store
+        .get(package)
+        .and_then(|unit| unit.package.entry.as_ref())
+    else {
+        return Ok(KernelProgram::default());
+    };
+
+    let mut driver = Driver {
+        kernels: Kernels {
+            store,
+            inspects_results: FxHashMap::default(),
+        },
+        package,
+        kernel_ids: FxHashMap::default(),
+        program: KernelProgram::default(),
+        errors: Vec::new(),
+        locals: FxHashMap::default(),
+        deps: BTreeSet::new(),
+        control: BTreeSet::new(),
+    };
+
+    // The results that reach each local are found flow-insensitively, so the driver is walked until
+    // they stop changing in order to follow results around loops. Only the last walk's calls and
+    // errors are kept.
+    loop {
+        let locals = driver.locals.clone();
+        driver.program.calls.clear();
+        driver.errors.clear();
+        driver.visit_entry(entry);
+        if driver.locals == locals {
+            break;
+        }
+    }
+
+    if driver.errors.is_empty() {
+        Ok(driver.program)
+    } else {
+        Err(driver.errors)
+    }
+}
+
+/// Decides which callables are kernels.
+struct Kernels<'a> {
+    store: &'a PackageStore,
+    /// Whether each callable that has been checked compares measurement results, either itself or
+    /// through the callables it calls.
+    inspects_results: FxHashMap<ItemId, bool>,
+}
+
+impl<'a> Kernels<'a> {
+    fn decl(&self, id: ItemId) -> Option<&'a CallableDecl> {
+        let package = id
+            .package
+            .expect("item ID should be qualified with its package");
+        let item = self.store.get(package)?.package.items.get(id.item)?;
+        match &item.kind {
+            ItemKind::Callable(decl) => Some(decl),
+            _ => None,
+        }
+    }
+
+    fn is_kernel(&mut self, id: ItemId) -> bool {
+        let Some(decl) = self.decl(id) else {
+            return false;
+        };
+        decl.kind == CallableKind::Operation
+            && matches!(decl.body.body, SpecBody::Impl(..))
+            && is_classical(&decl.input.ty)
+            && is_results(&decl.output)
+            && !self.inspects_results(id)
+    }
+
+    fn inspects_results(&mut self, id: ItemId) -> bool {
+        if let Some(&inspects) = self.inspects_results.get(&id) {
+            return inspects;
+        }
+
+        // Recursive callables are assumed not to inspect results until shown otherwise.
+        self.inspects_results.insert(id, false);
+        let mut finder = ResultInspection {
+            package: id
+                .package
+                .expect("item ID should be qualified with its package"),
+            found: false,
+            callees: FxHashSet::default(),
+        };
+        if let Some(decl) = self.decl(id) {
+            finder.visit_callable_decl(decl);
+        }
+        let inspects = finder.found
+            || finder
+                .callees
+                .into_iter()
+                .any(|callee| self.inspects_results(callee));
+        self.inspects_results.insert(id, inspects);
+        inspects
+    }
+}
+
+/// Whether a value of the type holds no qubits or callables. User-defined types are rejected, since
+/// their items aren't checked.
+pub(crate) fn is_classical(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_classical(item),
+        Ty::Prim(prim) => *prim != Prim::Qubit,
+        Ty::Tuple(items) => items.iter().all(is_classical),
+        Ty::Arrow(_) | Ty::Udt(..) | Ty::Infer(_) | Ty::Param(..) | Ty::Err => false,
+    }
+}
+
+/// Whether a value of the type is made up only of measurement results.
+pub(crate) fn is_results(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_results(item),
+        Ty::Prim(Prim::Result) => true,
+        Ty::Tuple(items) => items.iter().all(is_results),
+        _ => false,
+    }
+}
+
+/// Finds comparisons of measurement results in a callable, and the callables it refers to.
+struct ResultInspection {
+    package: PackageId,
+    found: bool,
+    callees: FxHashSet<ItemId>,
+}
+
+impl Visitor<'_> for ResultInspection {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::BinOp(BinOp::Eq | BinOp::Neq, lhs, _) if lhs.ty == Ty::Prim(Prim::Result) => {
+                self.found = true;
+            }
+            ExprKind::Var(Res::Item(id), _) => {
+                self.callees.insert(qualify(*id, self.package));
+            }
+            ExprKind::Closure(_, item) => {
+                self.callees.insert(ItemId {
+                    package: Some(self.package),
+                    item: *item,
+                });
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn qualify(id: ItemId, package: PackageId) -> ItemId {
+    ItemId {
+        package: Some(id.package.unwrap_or(package)),
+        item: id.item,
+    }
+}
+
+/// Walks the driver, recording its calls to kernels and the results that flow into them.
+struct Driver<'a> {
+    kernels: Kernels<'a>,
+    /// The package of the code being walked.
+    package: PackageId,
+    /// The index in the program of each kernel that has been called.
+    kernel_ids: FxHashMap<ItemId, usize>,
+    program: KernelProgram,
+    errors: Vec<Error>,
+    /// The calls whose results may reach each local.
+    locals: FxHashMap<NodeId, BTreeSet<usize>>,
+    /// The calls whose results may reach the value of the expression being walked.
+    deps: BTreeSet<usize>,
+    /// The calls whose results decide whether the code being walked runs.
+    control: BTreeSet<usize>,
+}
+
+impl Driver<'_> {
+    fn visit_entry(&mut self, entry: &Expr) {
+        if let ExprKind::Call(callee, _) = &entry.kind {
+            if let ExprKind::Var(Res::Item(id), _) = &callee.kind {
+                let id = qualify(*id, self.package);
+                if !self.kernels.is_kernel(id) {
+                    if let Some(SpecBody::Impl(_, block)) =
+                        self.kernels.decl(id).map(|decl| &decl.body.body)
+                    {
+                        let package = self.package;
+                        self.package = id.package.expect("item ID should be qualified");
+                        self.visit_block(block);
+                        self.package = package;
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.visit_expr(entry);
+    }
+
+    /// The calls whose results may reach the value of the expression.
+    fn deps_of(&mut self, expr: &Expr) -> BTreeSet<usize> {
+        let outer = take(&mut self.deps);
+        self.visit_expr(expr);
+        replace(&mut self.deps, outer)
+    }
+
+    fn call_kernel(&mut self, id: ItemId, arg: &Expr, span: Span) {
+        let mut depends_on = self.deps_of(arg);
+        depends_on.extend(&self.control);
+        let next_kernel = self.program.kernels.len();
+        let kernel = *self.kernel_ids.entry(id).or_insert(next_kernel);
+        if kernel == next_kernel {
+            let decl = self.kernels.decl(id).expect("kernel should be a callable");
+            let unit = self
+                .kernels
+                .store
+                .get(id.package.expect("item ID should be qualified"))
+                .expect("kernel's package should be in store");
+            let parent = unit.package.items.get(id.item).and_then(|item| item.parent);
+            let namespace = namespace_of(&unit.package, parent);
+            self.program.kernels.push(Kernel {
+                id,
+                name: format!("{namespace}.{}", decl.name.name),
+                input: decl.input.ty.clone(),
+                output: decl.output.clone(),
+            });
+        }
+
+        self.deps.insert(self.program.calls.len());
+        self.program.calls.push(KernelCall {
+            kernel,
+            span,
+            depends_on: depends_on.into_iter().collect(),
+        });
+    }
+
+    fn bind(&mut self, pat: &Pat, deps: &BTreeSet<usize>) {
+        match &pat.kind {
+            PatKind::Bind(ident) => self.locals.entry(ident.id).or_default().extend(deps),
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind(pat, deps)),
+            PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    fn assign(&mut self, lhs: &Expr, mut deps: BTreeSet<usize>) {
+        deps.extend(&self.control);
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(id), _) => self.locals.entry(*id).or_default().extend(deps),
+            ExprKind::Tuple(items) => items
+                .iter()
+                .for_each(|item| self.assign(item, deps.clone())),
+            _ => {}
+        }
+    }
+
+    fn controlled_by(&mut self, cond: BTreeSet<usize>, body: impl FnOnce(&mut Self)) {
+        let outer = self.control.clone();
+        self.control.extend(&cond);
+        self.deps.extend(cond);
+        body(self);
+        self.control = outer;
+    }
+}
+
+impl<'a> Visitor<'a> for Driver<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Local(_, pat, value) = &stmt.kind {
+            let mut deps = self.deps_of(value);
+            deps.extend(&self.control);
+            self.bind(pat, &deps);
+        } else {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Call(callee, arg) => {
+                if let ExprKind::Var(Res::Item(id), _) = &callee.kind {
+                    let id = qualify(*id, self.package);
+                    if self.kernels.is_kernel(id) {
+                        self.call_kernel(id, arg, expr.span);
+                        return;
+                    }
+                }
+                if let Ty::Arrow(arrow) = &callee.ty {
+                    if arrow.kind == CallableKind::Operation {
+                        self.errors.push(Error::NotAKernel(expr.span));
+                    }
+                }
+                visit::walk_expr(self, expr);
+            }
+            ExprKind::Var(Res::Local(id), _) => {
+                if let Some(deps) = self.locals.get(id) {
+                    self.deps.extend(deps);
+                }
+            }
+            ExprKind::Assign(lhs, rhs) | ExprKind::AssignOp(_, lhs, rhs) => {
+                let deps = self.deps_of(rhs);
+                self.assign(lhs, deps);
+            }
+            ExprKind::AssignField(lhs, _, value) => {
+                let deps = self.deps_of(value);
+                self.assign(lhs, deps);
+            }
+            ExprKind::AssignIndex(lhs, index, value) => {
+                let mut deps = self.deps_of(index);
+                deps.extend(self.deps_of(value));
+                self.assign(lhs, deps);
+            }
+            ExprKind::If(cond, then, otherwise) => {
+                let cond = self.deps_of(cond);
+                self.controlled_by(cond, |driver| {
+                    driver.visit_expr(then);
+                    if let Some(otherwise) = otherwise {
+                        driver.visit_expr(otherwise);
+                    }
+                });
+            }
+            ExprKind::While(cond, block) => {
+                let cond = self.deps_of(cond);
+                self.controlled_by(cond, |driver| driver.visit_block(block));
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
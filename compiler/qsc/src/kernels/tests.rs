@@ -0,0 +1,191 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{extract_kernels, Error, KernelProgram};
+use crate::{
+    compile,
+    interpret::{Interpreter, Value},
+};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn extract(source: &str) -> Result<KernelProgram, Vec<Error>> {
+    let mut store = PackageStore::new(compile::core());
+    let capabilities = RuntimeCapabilityFlags::all();
+    let std = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(&store, &[std], sources, PackageType::Exe, capabilities);
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    extract_kernels(&store, package)
+}
+
+fn check(source: &str, expect: &Expect) {
+    let program = extract(source).expect("kernels should be extracted");
+    expect.assert_eq(&program.to_string());
+}
+
+#[test]
+fn quantum_entry_point_is_a_single_kernel() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    H(q);
+                    M(q)
+                }
+            }
+        "},
+        &expect![[r#"
+            kernel 0: Test.Main : Unit => Result
+            call 0: kernel 0
+        "#]],
+    );
+}
+
+#[test]
+fn driver_records_which_results_feed_each_call() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                operation Prepare() : Result[] {
+                    use qs = Qubit[2];
+                    H(qs[0]);
+                    CNOT(qs[0], qs[1]);
+                    MResetEachZ(qs)
+                }
+
+                operation Rotate(theta : Double) : Result {
+                    use q = Qubit();
+                    Rx(theta, q);
+                    MResetZ(q)
+                }
+
+                @EntryPoint()
+                operation Main() : Result[] {
+                    let first = Prepare();
+                    let independent = Rotate(1.0);
+                    mutable theta = 0.5;
+                    if first[0] == first[1] {
+                        set theta = 1.5;
+                    }
+                    let dependent = Rotate(theta);
+                    [independent, dependent]
+                }
+            }
+        "},
+        &expect![[r#"
+            kernel 0: Test.Prepare : Unit => Result[]
+            kernel 1: Test.Rotate : Double => Result
+            call 0: kernel 0
+            call 1: kernel 1
+            call 2: kernel 1 after calls 0
+        "#]],
+    );
+}
+
+#[test]
+fn results_flow_around_loops() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                operation Flip(angle : Double) : Result {
+                    use q = Qubit();
+                    Ry(angle, q);
+                    MResetZ(q)
+                }
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    mutable angle = 0.0;
+                    for _ in 0..2 {
+                        let r = Flip(angle);
+                        set angle = r == One ? 1.0 | 2.0;
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            kernel 0: Test.Flip : Double => Result
+            call 0: kernel 0 after calls 0
+        "#]],
+    );
+}
+
+#[test]
+fn operation_that_inspects_results_is_not_a_kernel() {
+    let errors = extract(indoc! {"
+        namespace Test {
+            open Microsoft.Quantum.Measurement;
+            operation Correct() : Result {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+                MResetZ(q)
+            }
+
+            operation Check() : Result {
+                Correct()
+            }
+
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                let r = Check();
+                Reset(q);
+                r
+            }
+        }
+    "})
+    .expect_err("driver should use qubits directly");
+    assert!(
+        errors
+            .iter()
+            .all(|error| matches!(error, Error::NotAKernel(_))),
+        "{errors:?}"
+    );
+    assert!(errors.len() >= 3, "{errors:?}");
+}
+
+#[test]
+fn kernel_qir_traces_a_single_call() {
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            indoc! {"
+                namespace Test {
+                    open Microsoft.Quantum.Measurement;
+                    operation Rotate(theta : Double) : Result {
+                        use q = Qubit();
+                        Rx(theta, q);
+                        MResetZ(q)
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::empty(),
+    )
+    .expect("interpreter should be created");
+    let qir = interpreter
+        .kernel_qir("Test.Rotate", Value::Double(0.25))
+        .expect("kernel should generate QIR");
+    assert!(
+        qir.contains("call void @__quantum__qis__rx__body(double 0.25"),
+        "{qir}"
+    );
+}
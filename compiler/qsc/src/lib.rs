@@ -4,15 +4,42 @@
 #![warn(clippy::mod_module_files, clippy::pedantic, clippy::unwrap_used)]
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
+pub mod analysis;
+pub mod batch;
+pub mod calibration;
+pub mod circuit;
+pub mod compilation_db;
 pub mod compile;
+pub mod coverage;
+pub mod determinism;
+pub mod doc_test;
+pub mod entry_points;
 pub mod error;
+pub mod explanation;
+pub mod gate_counts;
+pub mod graph;
 pub mod incremental;
+pub mod index_overflow;
+pub mod interop;
 pub mod interpret;
+pub mod kernels;
 pub mod location;
+pub mod memory;
+pub mod message_catalog;
+pub mod metrics;
+pub mod mutation;
+pub mod report;
+pub mod signature_search;
 pub mod target;
+pub mod telemetry;
+pub mod tomography;
+pub mod trace;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
 
 pub use qsc_frontend::compile::{
-    CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName,
+    CompileUnit, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap,
+    SourceName,
 };
 
 pub mod resolve {
@@ -31,12 +58,45 @@ pub mod ast {
     pub use qsc_ast::{ast::*, *};
 }
 
+pub mod cache {
+    pub use qsc_eval::cache::OperationCache;
+}
+
+pub mod host_io {
+    pub use qsc_eval::host_io::{
+        AsyncHostIo, BlockingHost, HostIo, HostIoBackend, Recorder, ReplayEntry, ReplayLog,
+        Replayer,
+    };
+}
+
+pub mod noise {
+    pub use qsc_eval::noise::{NoiseModel, NoisyBackend, PauliNoise};
+}
+
+pub mod pauli_frame {
+    pub use qsc_eval::pauli_frame::PauliFrameBackend;
+}
+
+pub mod progress {
+    pub use qsc_eval::progress::{CountingBackend, Progress, ProgressTracker};
+}
+
+pub mod replay {
+    pub use qsc_eval::replay::{MeasurementLog, RecordingBackend};
+}
+
 pub mod project {
-    pub use qsc_project::{DirEntry, EntryType, FileSystem, Manifest, ManifestDescriptor};
+    pub use qsc_project::{
+        DirEntry, EntryType, FileSystem, Manifest, ManifestDescriptor, MemEntry, MemFs,
+    };
 }
 
 pub use qsc_data_structures::span::Span;
 
+pub mod edition {
+    pub use qsc_data_structures::edition::{Edition, LanguageFeature};
+}
+
 pub use qsc_passes::{PackageType, PassContext};
 
 pub mod line_column {
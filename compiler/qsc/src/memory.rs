@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Memory usage accounting for compilations and simulations, so that hosts with a fixed memory
+//! budget, such as WASM, can display how much of it a program uses and warn before it runs out.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_eval::backend::Precision;
+use qsc_fir::fir;
+use qsc_hir::{
+    hir::{Block, Expr, Item, Package, Pat, Stmt},
+    visit::{self, Visitor},
+};
+use std::{
+    mem::size_of,
+    ops::{Add, AddAssign},
+};
+
+/// The number of nodes of each kind in a package.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NodeCounts {
+    pub items: usize,
+    pub blocks: usize,
+    pub exprs: usize,
+    pub pats: usize,
+    pub stmts: usize,
+}
+
+impl NodeCounts {
+    /// Counts the nodes in a HIR package.
+    #[must_use]
+    pub fn hir(package: &Package) -> Self {
+        let mut counter = HirCounter::default();
+        counter.visit_package(package);
+        counter.0
+    }
+
+    /// Counts the nodes in the arenas of a FIR package.
+    #[must_use]
+    pub fn fir(package: &fir::Package) -> Self {
+        Self {
+            items: package.items.values().count(),
+            blocks: package.blocks.values().count(),
+            exprs: package.exprs.values().count(),
+            pats: package.pats.values().count(),
+            stmts: package.stmts.values().count(),
+        }
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.items + self.blocks + self.exprs + self.pats + self.stmts
+    }
+}
+
+impl Add for NodeCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            items: self.items + other.items,
+            blocks: self.blocks + other.blocks,
+            exprs: self.exprs + other.exprs,
+            pats: self.pats + other.pats,
+            stmts: self.stmts + other.stmts,
+        }
+    }
+}
+
+impl AddAssign for NodeCounts {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+#[derive(Default)]
+struct HirCounter(NodeCounts);
+
+impl Visitor<'_> for HirCounter {
+    fn visit_item(&mut self, item: &Item) {
+        self.0.items += 1;
+        visit::walk_item(self, item);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.0.blocks += 1;
+        visit::walk_block(self, block);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0.exprs += 1;
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &Pat) {
+        self.0.pats += 1;
+        visit::walk_pat(self, pat);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.0.stmts += 1;
+        visit::walk_stmt(self, stmt);
+    }
+}
+
+/// The size of the compiled representation of one or more packages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CompilationStats {
+    pub hir: NodeCounts,
+    pub fir: NodeCounts,
+    /// The bytes taken up by the nodes in the FIR arenas, not counting the heap data that the
+    /// nodes own.
+    pub fir_arena_bytes: usize,
+}
+
+impl CompilationStats {
+    #[must_use]
+    pub fn new(hir: &Package, fir: &fir::Package) -> Self {
+        let fir_counts = NodeCounts::fir(fir);
+        Self {
+            hir: NodeCounts::hir(hir),
+            fir: fir_counts,
+            fir_arena_bytes: fir_counts.items * size_of::<fir::Item>()
+                + fir_counts.blocks * size_of::<fir::Block>()
+                + fir_counts.exprs * size_of::<fir::Expr>()
+                + fir_counts.pats * size_of::<fir::Pat>()
+                + fir_counts.stmts * size_of::<fir::Stmt>(),
+        }
+    }
+}
+
+impl Add for CompilationStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            hir: self.hir + other.hir,
+            fir: self.fir + other.fir,
+            fir_arena_bytes: self.fir_arena_bytes + other.fir_arena_bytes,
+        }
+    }
+}
+
+impl AddAssign for CompilationStats {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// The qubits held by a simulator.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SimulationStats {
+    /// The number of qubits that are currently allocated.
+    pub qubits: usize,
+    /// The largest number of qubits that were allocated at once.
+    pub peak_qubits: usize,
+    /// The precision of the amplitudes of the state vector.
+    pub precision: Precision,
+}
+
+impl SimulationStats {
+    /// The bytes of a sparse state vector with an entry of the precision for every basis state of
+    /// `qubits` qubits, saturating at `usize::MAX`. The sparse simulator only stores the
+    /// amplitudes that are nonzero, so this is an upper bound on the memory of its entries.
+    #[must_use]
+    pub fn state_vector_bytes(qubits: usize, precision: Precision) -> usize {
+        u32::try_from(qubits)
+            .ok()
+            .and_then(|qubits| 1usize.checked_shl(qubits))
+            .and_then(|amplitudes| amplitudes.checked_mul(precision.entry_bytes()))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The upper bound on the bytes of the state vector at its largest.
+    #[must_use]
+    pub fn peak_state_vector_bytes(&self) -> usize {
+        Self::state_vector_bytes(self.peak_qubits, self.precision)
+    }
+}
+
+/// The memory statistics of an interpreter.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryStats {
+    /// The size of every package the interpreter has compiled, including the libraries.
+    pub compilation: CompilationStats,
+    pub simulation: SimulationStats,
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{NodeCounts, SimulationStats};
+use crate::{
+    compile,
+    interpret::{GenericReceiver, Interpreter},
+};
+use indoc::indoc;
+use qsc_eval::backend::Precision;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+#[test]
+fn hir_and_fir_node_counts_agree() {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            indoc! {"
+                namespace Test {
+                    function A() : Int { 1 }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let (unit, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let hir = NodeCounts::hir(&unit.package);
+    assert_eq!(
+        hir,
+        NodeCounts {
+            items: 2,
+            blocks: 1,
+            exprs: 1,
+            pats: 1,
+            stmts: 1,
+        }
+    );
+    let fir = qsc_eval::lower::Lowerer::new().lower_package(&unit.package);
+    assert_eq!(NodeCounts::fir(&fir), hir);
+}
+
+#[test]
+fn state_vector_bytes_saturate() {
+    assert_eq!(
+        SimulationStats::state_vector_bytes(0, Precision::Double),
+        40
+    );
+    assert_eq!(
+        SimulationStats::state_vector_bytes(3, Precision::Double),
+        320
+    );
+    assert_eq!(
+        SimulationStats::state_vector_bytes(3, Precision::Single),
+        256
+    );
+    assert_eq!(
+        SimulationStats::state_vector_bytes(200, Precision::Double),
+        usize::MAX
+    );
+}
+
+#[test]
+fn interpreter_tracks_peak_qubits() {
+    let sources = SourceMap::new(
+        [(
+            "test.qs".into(),
+            indoc! {"
+                namespace Test {
+                    @EntryPoint()
+                    operation Main() : Unit {
+                        {
+                            use qs = Qubit[3];
+                        }
+                        use q = Qubit();
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created");
+    let before = interpreter.memory_stats();
+    assert_eq!(before.simulation.peak_qubits, 0);
+    assert!(before.compilation.hir.total() > 0);
+    assert!(before.compilation.fir_arena_bytes > 0);
+
+    let mut out = Vec::new();
+    interpreter
+        .eval_entry(&mut GenericReceiver::new(&mut out))
+        .expect("entry should run");
+    let after = interpreter.memory_stats();
+    assert_eq!(after.simulation.qubits, 0);
+    assert_eq!(after.simulation.peak_qubits, 3);
+    assert_eq!(after.simulation.peak_state_vector_bytes(), 320);
+    assert_eq!(after.compilation, before.compilation);
+
+    // At single precision, only the amplitude of each entry shrinks.
+    interpreter.set_precision(Precision::Single);
+    interpreter
+        .eval_entry(&mut GenericReceiver::new(&mut out))
+        .expect("entry should run");
+    let single = interpreter.memory_stats();
+    assert_eq!(single.simulation.peak_qubits, 3);
+    assert_eq!(single.simulation.peak_state_vector_bytes(), 256);
+    assert!(interpreter.check_precision().is_none());
+}
@@ -0,0 +1,222 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::message::MessageParams;
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+/// The English messages of the compiler diagnostics, written as a message bundle.
+const ENGLISH: &str = include_str!("message_catalog/en.txt");
+
+#[derive(Clone, Debug, Diagnostic, Eq, Error, PartialEq)]
+pub enum BundleError {
+    #[error("line {0} of the message bundle is not of the form `code = message`")]
+    #[diagnostic(code("Qsc.MessageCatalog.Syntax"))]
+    Syntax(usize),
+
+    #[error("line {0} of the message bundle refers to unknown error code `{1}`")]
+    #[diagnostic(code("Qsc.MessageCatalog.UnknownCode"))]
+    UnknownCode(usize, String),
+
+    #[error("line {0} of the message bundle uses parameter `{{{1}}}`, but `{2}` has only {3}")]
+    #[diagnostic(code("Qsc.MessageCatalog.UnknownParameter"))]
+    UnknownParameter(usize, usize, String, usize),
+}
+
+impl MessageParams for BundleError {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Syntax(line) => vec![line.to_string()],
+            Self::UnknownCode(line, code) => vec![line.to_string(), code.clone()],
+            Self::UnknownParameter(line, param, code, count) => vec![
+                line.to_string(),
+                param.to_string(),
+                code.clone(),
+                count.to_string(),
+            ],
+        }
+    }
+}
+
+/// A catalog of user-facing diagnostic messages keyed by error code.
+///
+/// Every compiler diagnostic with a code is rendered from the template for its code, filled in
+/// with the parameters of the diagnostic. Hosts can load a localization bundle to translate the
+/// templates. Codes that the bundle doesn't cover are rendered from their English templates, and
+/// diagnostics without a code in the catalog keep their own messages.
+#[derive(Clone, Debug)]
+pub struct MessageCatalog {
+    english: FxHashMap<&'static str, Template>,
+    localized: FxHashMap<String, Template>,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self {
+            english: messages(ENGLISH)
+                .map(|entry| {
+                    let (_, code, template) = entry.expect("English messages should parse");
+                    (code, template)
+                })
+                .collect(),
+            localized: FxHashMap::default(),
+        }
+    }
+}
+
+impl MessageCatalog {
+    /// Loads a localization bundle, replacing any messages loaded before for the same codes.
+    ///
+    /// A bundle has one message per line, written as `code = message`. Blank lines and lines
+    /// starting with `#` are ignored. Messages refer to the parameters of the English message by
+    /// the order they appear in it, e.g. `{0}` for the first, and `{{` and `}}` stand for literal
+    /// braces.
+    pub fn load_bundle(&mut self, bundle: &str) -> Result<(), BundleError> {
+        let mut localized = Vec::new();
+        for entry in messages(bundle) {
+            let (line_number, code, template) = entry?;
+            let english = self
+                .english
+                .get(code)
+                .ok_or_else(|| BundleError::UnknownCode(line_number, code.to_string()))?;
+            if let Some(param) = template.params().find(|&p| p >= english.param_count()) {
+                return Err(BundleError::UnknownParameter(
+                    line_number,
+                    param,
+                    code.to_string(),
+                    english.param_count(),
+                ));
+            }
+            localized.push((code.to_string(), template));
+        }
+
+        self.localized.extend(localized);
+        Ok(())
+    }
+
+    /// Returns the codes of the diagnostics in the catalog, sorted, which a bundle can translate.
+    pub fn codes(&self) -> impl Iterator<Item = &'static str> {
+        let mut codes = self.english.keys().copied().collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.into_iter()
+    }
+
+    /// Returns the message for the diagnostic, rendered from the template for its code with its
+    /// parameters. The template is translated if a localized message is loaded for the code.
+    #[must_use]
+    pub fn message<D: Diagnostic + MessageParams + ?Sized>(&self, diagnostic: &D) -> String {
+        let params = diagnostic.message_params();
+        diagnostic
+            .code()
+            .map(|code| code.to_string())
+            .and_then(|code| {
+                self.localized
+                    .get(&code)
+                    .or_else(|| self.english.get(code.as_str()))
+            })
+            .filter(|template| template.param_count() <= params.len())
+            .map_or_else(
+                || diagnostic.to_string(),
+                |template| template.render(&params),
+            )
+    }
+}
+
+/// Parses the messages of a bundle, with the line numbers they are on.
+fn messages(bundle: &str) -> impl Iterator<Item = Result<(usize, &str, Template), BundleError>> {
+    bundle
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let (code, message) = line
+                .split_once('=')
+                .ok_or(BundleError::Syntax(line_number))?;
+            Ok((line_number, code.trim(), Template::parse(message.trim())))
+        })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Param(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Template(Vec<Segment>);
+
+impl Template {
+    fn parse(message: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = message.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut digits = String::new();
+                    while let Some(&d) = chars.peek() {
+                        if !d.is_ascii_digit() {
+                            break;
+                        }
+                        digits.push(d);
+                        chars.next();
+                    }
+                    match (digits.parse(), chars.peek()) {
+                        (Ok(param), Some('}')) => {
+                            chars.next();
+                            if !text.is_empty() {
+                                segments.push(Segment::Text(std::mem::take(&mut text)));
+                            }
+                            segments.push(Segment::Param(param));
+                        }
+                        _ => {
+                            text.push('{');
+                            text.push_str(&digits);
+                        }
+                    }
+                }
+                _ => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Self(segments)
+    }
+
+    fn params(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().filter_map(|segment| match segment {
+            Segment::Param(param) => Some(*param),
+            Segment::Text(_) => None,
+        })
+    }
+
+    fn param_count(&self) -> usize {
+        self.params().max().map_or(0, |max| max + 1)
+    }
+
+    fn render(&self, params: &[String]) -> String {
+        let mut message = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Text(text) => message.push_str(text),
+                Segment::Param(param) => message.push_str(&params[*param]),
+            }
+        }
+        message
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{BundleError, MessageCatalog, ENGLISH};
+use crate::{
+    circuit,
+    compile::{self, Error},
+};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::fmt::Write;
+
+fn compile_errors(source: &str) -> Vec<Error> {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let (_, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    errors
+}
+
+/// The sources that declare diagnostics with codes.
+const SOURCES: &[(&str, &str)] = &[
+    ("qsc/src/circuit.rs", include_str!("../circuit.rs")),
+    (
+        "qsc/src/index_overflow.rs",
+        include_str!("../index_overflow.rs"),
+    ),
+    ("qsc/src/interop.rs", include_str!("../interop.rs")),
+    ("qsc/src/interpret.rs", include_str!("../interpret.rs")),
+    ("qsc/src/kernels.rs", include_str!("../kernels.rs")),
+    (
+        "qsc/src/message_catalog.rs",
+        include_str!("../message_catalog.rs"),
+    ),
+    (
+        "qsc/src/signature_search.rs",
+        include_str!("../signature_search.rs"),
+    ),
+    (
+        "qsc_codegen/src/qir_base.rs",
+        include_str!("../../../qsc_codegen/src/qir_base.rs"),
+    ),
+    (
+        "qsc_eval/src/lib.rs",
+        include_str!("../../../qsc_eval/src/lib.rs"),
+    ),
+    (
+        "qsc_frontend/src/compile/generate.rs",
+        include_str!("../../../qsc_frontend/src/compile/generate.rs"),
+    ),
+    (
+        "qsc_frontend/src/lower.rs",
+        include_str!("../../../qsc_frontend/src/lower.rs"),
+    ),
+    (
+        "qsc_frontend/src/resolve.rs",
+        include_str!("../../../qsc_frontend/src/resolve.rs"),
+    ),
+    (
+        "qsc_frontend/src/typeck.rs",
+        include_str!("../../../qsc_frontend/src/typeck.rs"),
+    ),
+    (
+        "qsc_parse/src/lex/cooked.rs",
+        include_str!("../../../qsc_parse/src/lex/cooked.rs"),
+    ),
+    (
+        "qsc_parse/src/lib.rs",
+        include_str!("../../../qsc_parse/src/lib.rs"),
+    ),
+    (
+        "qsc_passes/src/baseprofck.rs",
+        include_str!("../../../qsc_passes/src/baseprofck.rs"),
+    ),
+    (
+        "qsc_passes/src/borrowck.rs",
+        include_str!("../../../qsc_passes/src/borrowck.rs"),
+    ),
+    (
+        "qsc_passes/src/callable_limits.rs",
+        include_str!("../../../qsc_passes/src/callable_limits.rs"),
+    ),
+    (
+        "qsc_passes/src/compute_properties.rs",
+        include_str!("../../../qsc_passes/src/compute_properties.rs"),
+    ),
+    (
+        "qsc_passes/src/conjugate_invert.rs",
+        include_str!("../../../qsc_passes/src/conjugate_invert.rs"),
+    ),
+    (
+        "qsc_passes/src/entry_point.rs",
+        include_str!("../../../qsc_passes/src/entry_point.rs"),
+    ),
+    (
+        "qsc_passes/src/logic_sep.rs",
+        include_str!("../../../qsc_passes/src/logic_sep.rs"),
+    ),
+    (
+        "qsc_passes/src/qubit_budget.rs",
+        include_str!("../../../qsc_passes/src/qubit_budget.rs"),
+    ),
+    (
+        "qsc_passes/src/recursion.rs",
+        include_str!("../../../qsc_passes/src/recursion.rs"),
+    ),
+    (
+        "qsc_passes/src/spec_gen.rs",
+        include_str!("../../../qsc_passes/src/spec_gen.rs"),
+    ),
+    (
+        "qsc_passes/src/spec_gen/adj_gen.rs",
+        include_str!("../../../qsc_passes/src/spec_gen/adj_gen.rs"),
+    ),
+    (
+        "qsc_passes/src/spec_gen/ctl_gen.rs",
+        include_str!("../../../qsc_passes/src/spec_gen/ctl_gen.rs"),
+    ),
+    (
+        "qsc_passes/src/substitution.rs",
+        include_str!("../../../qsc_passes/src/substitution.rs"),
+    ),
+];
+
+/// Finds the code and the `#[error]` message of each diagnostic declared in the source, with
+/// the parameters of the message numbered in the order they appear.
+fn declared_messages(source: &str) -> Vec<(String, String)> {
+    let mut messages = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("#[error(") {
+        rest = rest[start + "#[error(".len()..].trim_start();
+        let next = rest.find("#[error(").unwrap_or(rest.len());
+        let Some(literal) = rest.strip_prefix('"') else {
+            continue;
+        };
+        let literal = &literal[..literal.find('"').expect("message should end")];
+        if let Some(code) = rest[..next].find("code(\"").map(|i| &rest[i + 6..]) {
+            let code = &code[..code.find('"').expect("code should end")];
+            messages.push((code.to_string(), number_params(literal)));
+        }
+    }
+    messages
+}
+
+/// Replaces the parameters of a format string, which may be positional, named or implicit, with
+/// the order they first appear in, and drops their format specs.
+fn number_params(format: &str) -> String {
+    let mut params = Vec::new();
+    let mut message = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '{' || chars.peek() == Some(&'{') {
+            message.push(c);
+            if c == '{' {
+                message.push(chars.next().expect("brace should follow"));
+            }
+            continue;
+        }
+
+        let param = chars.by_ref().take_while(|&c| c != '}').collect::<String>();
+        let param = param.split(':').next().unwrap_or_default().to_string();
+        let position = match params.iter().position(|p| *p == param) {
+            Some(position) if !param.is_empty() => position,
+            _ => {
+                params.push(param);
+                params.len() - 1
+            }
+        };
+        write!(message, "{{{position}}}").expect("string should be writable");
+    }
+    message
+}
+
+fn messages(catalog: &MessageCatalog, errors: &[Error]) -> String {
+    errors
+        .iter()
+        .map(|error| catalog.message(error))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        function Foo() : Int {
+            Bar()
+        }
+        function Baz() : Int {
+            true
+        }
+    }
+"};
+
+#[test]
+fn default_catalog_keeps_english_messages() {
+    let errors = compile_errors(SOURCE);
+    expect![[r#"
+        `Bar` not found
+        expected Int, found Bool"#]]
+    .assert_eq(&messages(&MessageCatalog::default(), &errors));
+}
+
+#[test]
+fn bundle_translates_messages_with_parameters() {
+    let mut catalog = MessageCatalog::default();
+    catalog
+        .load_bundle(indoc! {"
+            # French
+            Qsc.Resolve.NotFound = `{0}` introuvable
+            Qsc.TypeCk.TyMismatch = type {1} trouvé, {0} attendu
+        "})
+        .expect("bundle should load");
+
+    let errors = compile_errors(SOURCE);
+    expect![[r#"
+        `Bar` introuvable
+        type Bool trouvé, Int attendu"#]]
+    .assert_eq(&messages(&catalog, &errors));
+}
+
+#[test]
+fn uncovered_codes_stay_in_english() {
+    let mut catalog = MessageCatalog::default();
+    catalog
+        .load_bundle("Qsc.TypeCk.TyMismatch = {{{0}}} attendu")
+        .expect("bundle should load");
+
+    let errors = compile_errors(SOURCE);
+    expect![[r#"
+        `Bar` not found
+        {Int} attendu"#]]
+    .assert_eq(&messages(&catalog, &errors));
+}
+
+#[test]
+fn bundle_errors() {
+    let mut catalog = MessageCatalog::default();
+    assert_eq!(
+        catalog.load_bundle("\nQsc.Resolve.NotFound"),
+        Err(BundleError::Syntax(2))
+    );
+    assert_eq!(
+        catalog.load_bundle("Qsc.Nope = nope"),
+        Err(BundleError::UnknownCode(1, "Qsc.Nope".to_string()))
+    );
+    assert_eq!(
+        catalog.load_bundle("Qsc.Resolve.NotFound = {0} {1}"),
+        Err(BundleError::UnknownParameter(
+            1,
+            1,
+            "Qsc.Resolve.NotFound".to_string(),
+            1
+        ))
+    );
+}
+
+#[test]
+fn english_messages_match_the_declared_diagnostics() {
+    let mut declared = SOURCES
+        .iter()
+        .flat_map(|(_, source)| declared_messages(source))
+        .collect::<Vec<_>>();
+    declared.sort();
+    let english = ENGLISH
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (code, message) = line.split_once(" = ").expect("line should have a message");
+            (code.to_string(), message.to_string())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(english, declared);
+}
+
+#[test]
+fn declared_codes_are_unique() {
+    for &(path, source) in SOURCES {
+        let mut codes = declared_messages(source)
+            .into_iter()
+            .map(|(code, _)| code)
+            .collect::<Vec<_>>();
+        codes.sort();
+        let len = codes.len();
+        codes.dedup();
+        assert_eq!(codes.len(), len, "{path} declares a code twice");
+    }
+}
+
+#[test]
+fn every_code_can_be_translated() {
+    let mut catalog = MessageCatalog::default();
+    let bundle = catalog
+        .codes()
+        .map(|code| format!("{code} = traduit"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(catalog.load_bundle(&bundle), Ok(()));
+}
+
+#[test]
+fn bundle_translates_parse_errors() {
+    let mut catalog = MessageCatalog::default();
+    catalog
+        .load_bundle("Qsc.Parse.Rule = {1} trouvé, {0} attendu")
+        .expect("bundle should load");
+
+    let errors = compile_errors("namespace Test { function Foo() : Int { let x = ; } }");
+    expect!["`;` trouvé, expression attendu"].assert_eq(&messages(&catalog, &errors));
+}
+
+#[test]
+fn parameters_are_not_recovered_from_the_english_message() {
+    let mut catalog = MessageCatalog::default();
+    catalog
+        .load_bundle(
+            "Qsc.Circuit.InvalidArgument = l'argument {0} de la porte {1} n'est pas un nombre",
+        )
+        .expect("bundle should load");
+
+    let error = circuit::Error::InvalidArgument("Rx".to_string(), "θ of gate H".to_string());
+    assert_eq!(
+        error.to_string(),
+        "argument θ of gate H of gate Rx is not a number"
+    );
+    expect!["l'argument θ of gate H de la porte Rx n'est pas un nombre"]
+        .assert_eq(&catalog.message(&error));
+}
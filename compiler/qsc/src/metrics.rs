@@ -0,0 +1,194 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Measures the namespaces of a compiled package, so that the health of a code base can be tracked
+//! as it grows. The metrics are structured data that serializes to JSON.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compile;
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::{CompileUnit, SourceMap};
+use qsc_hir::{
+    hir::{CallableKind, Expr, ExprKind, Item, ItemId, ItemKind, Package, Res},
+    visit::{self, Visitor},
+};
+use rustc_hash::FxHashSet;
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// The metrics of a namespace, summed over all of its declarations.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceMetrics {
+    /// The name of the namespace.
+    pub name: String,
+    /// The number of operations declared in the namespace.
+    pub operations: usize,
+    /// The number of functions declared in the namespace.
+    pub functions: usize,
+    /// The number of newtypes declared in the namespace.
+    pub types: usize,
+    /// The number of lines of the namespace that aren't blank.
+    pub lines: usize,
+    /// The number of distinct instantiations of generic callables that the namespace uses, where
+    /// each instantiation is a callable with its generic arguments.
+    pub generic_instantiations: usize,
+    /// The average number of lines that aren't blank of the callables declared in the namespace,
+    /// or zero if it declares none.
+    pub average_callable_lines: f64,
+    /// The number of uses of features that the target doesn't support.
+    pub unsupported_features: usize,
+    /// The number of uses of features that the target doesn't support per thousand lines.
+    pub unsupported_feature_density: f64,
+}
+
+/// Measures each namespace of the compiled unit, in order of name. The errors of the capability
+/// checks among `errors`, the errors of compiling the unit, are counted as uses of unsupported
+/// features.
+#[must_use]
+pub fn namespace_metrics(unit: &CompileUnit, errors: &[compile::Error]) -> Vec<NamespaceMetrics> {
+    let package = &unit.package;
+    let mut namespaces: BTreeMap<Arc<str>, Measured> = BTreeMap::new();
+    let mut spans = Vec::new();
+    for item in package.items.values() {
+        if let ItemKind::Namespace(name, _) = &item.kind {
+            let measured = namespaces.entry(Arc::clone(&name.name)).or_default();
+            measured.metrics.lines += lines(&unit.sources, item.span);
+            spans.push((item.span, Arc::clone(&name.name)));
+        }
+    }
+
+    for item in package.items.values() {
+        let Some(namespace) = namespace(package, item) else {
+            continue;
+        };
+        let measured = namespaces
+            .get_mut(namespace)
+            .expect("namespace should be measured");
+        // Items declared in callables are measured as part of their callables.
+        let declared = item
+            .parent
+            .and_then(|parent| package.items.get(parent))
+            .is_some_and(|parent| matches!(parent.kind, ItemKind::Namespace(..)));
+        match &item.kind {
+            ItemKind::Callable(decl) => {
+                if declared {
+                    match decl.kind {
+                        CallableKind::Function => measured.metrics.functions += 1,
+                        CallableKind::Operation => measured.metrics.operations += 1,
+                    }
+                    measured
+                        .callable_lines
+                        .push(lines(&unit.sources, decl.span));
+                }
+                measured.instantiations.visit_callable_decl(decl);
+            }
+            ItemKind::Ty(..) if declared => measured.metrics.types += 1,
+            ItemKind::Namespace(..) | ItemKind::Ty(..) => {}
+        }
+    }
+
+    for error in errors {
+        if !error
+            .code()
+            .is_some_and(|code| code.to_string().starts_with("Qsc.BaseProfCk."))
+        {
+            continue;
+        }
+        let Some(offset) = error
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .and_then(|label| u32::try_from(label.offset()).ok())
+        else {
+            continue;
+        };
+        if let Some((_, name)) = spans
+            .iter()
+            .find(|(span, _)| span.lo <= offset && offset < span.hi)
+        {
+            if let Some(measured) = namespaces.get_mut(name) {
+                measured.metrics.unsupported_features += 1;
+            }
+        }
+    }
+
+    namespaces
+        .into_iter()
+        .map(|(name, measured)| measured.finish(&name))
+        .collect()
+}
+
+/// The metrics of a namespace as they are gathered.
+#[derive(Default)]
+struct Measured {
+    metrics: NamespaceMetrics,
+    callable_lines: Vec<usize>,
+    instantiations: Instantiations,
+}
+
+impl Measured {
+    #[allow(clippy::cast_precision_loss)]
+    fn finish(self, name: &str) -> NamespaceMetrics {
+        let mut metrics = self.metrics;
+        metrics.name = name.to_string();
+        metrics.generic_instantiations = self.instantiations.0.len();
+        if !self.callable_lines.is_empty() {
+            metrics.average_callable_lines =
+                self.callable_lines.iter().sum::<usize>() as f64 / self.callable_lines.len() as f64;
+        }
+        if metrics.lines > 0 {
+            metrics.unsupported_feature_density =
+                metrics.unsupported_features as f64 * 1000.0 / metrics.lines as f64;
+        }
+        metrics
+    }
+}
+
+/// The generic callables used with their arguments, which are kept as text since types can't be
+/// hashed.
+#[derive(Default)]
+struct Instantiations(FxHashSet<(ItemId, String)>);
+
+impl<'a> Visitor<'a> for Instantiations {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Var(Res::Item(id), args) = &expr.kind {
+            if !args.is_empty() {
+                let args = args
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.0.insert((*id, args));
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// The name of the namespace that the item is in, following the parents of local items up to it.
+fn namespace<'a>(package: &'a Package, item: &'a Item) -> Option<&'a Arc<str>> {
+    let mut parent = item.parent;
+    while let Some(id) = parent {
+        let item = package.items.get(id)?;
+        if let ItemKind::Namespace(name, _) = &item.kind {
+            return Some(&name.name);
+        }
+        parent = item.parent;
+    }
+    None
+}
+
+/// The number of lines of the span that aren't blank.
+fn lines(sources: &SourceMap, span: Span) -> usize {
+    let Some(source) = sources.find_by_offset(span.lo) else {
+        return 0;
+    };
+    let lo = (span.lo - source.offset) as usize;
+    let hi = (span.hi - source.offset) as usize;
+    source.contents.get(lo..hi).map_or(0, |text| {
+        text.lines().filter(|line| !line.trim().is_empty()).count()
+    })
+}
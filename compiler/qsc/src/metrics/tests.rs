@@ -0,0 +1,121 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{namespace_metrics, NamespaceMetrics};
+use crate::compile;
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {"
+    namespace A {
+        newtype Pair = (Int, Int);
+
+        function Identity<'T>(x : 'T) : 'T {
+            x
+        }
+
+        operation Flip(q : Qubit) : Bool {
+            let pair = Identity(Pair(Identity(1), 2));
+            M(q) == One
+        }
+    }
+
+    namespace B {
+        function Twice(x : Int) : Int {
+            A.Identity(x) * 2
+        }
+    }
+"};
+
+fn metrics(source: &str, capabilities: RuntimeCapabilityFlags) -> Vec<NamespaceMetrics> {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (unit, errors) = compile::compile(&store, &[std], sources, PackageType::Lib, capabilities);
+    namespace_metrics(&unit, &errors)
+}
+
+#[test]
+fn namespaces_are_measured() {
+    expect![[r#"
+        [
+            NamespaceMetrics {
+                name: "A",
+                operations: 1,
+                functions: 1,
+                types: 1,
+                lines: 10,
+                generic_instantiations: 2,
+                average_callable_lines: 3.5,
+                unsupported_features: 0,
+                unsupported_feature_density: 0.0,
+            },
+            NamespaceMetrics {
+                name: "B",
+                operations: 0,
+                functions: 1,
+                types: 0,
+                lines: 5,
+                generic_instantiations: 1,
+                average_callable_lines: 3.0,
+                unsupported_features: 0,
+                unsupported_feature_density: 0.0,
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&metrics(SOURCE, RuntimeCapabilityFlags::all()));
+}
+
+#[test]
+fn unsupported_features_are_counted_where_they_are_used() {
+    let metrics = metrics(SOURCE, RuntimeCapabilityFlags::empty());
+    let counts = metrics
+        .iter()
+        .map(|metrics| (metrics.name.as_str(), metrics.unsupported_features))
+        .collect::<Vec<_>>();
+    // The comparison and the `One` literal it compares to.
+    assert_eq!(counts, [("A", 2), ("B", 0)]);
+    assert!((metrics[0].unsupported_feature_density - 200.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn declarations_of_a_namespace_are_summed() {
+    let metrics = metrics(
+        indoc! {"
+            namespace A {
+                function F() : Unit {}
+            }
+            namespace A {
+                function G() : Unit {
+                    F();
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::all(),
+    );
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].functions, 2);
+    assert_eq!(metrics[0].lines, 8);
+    assert!((metrics[0].average_callable_lines - 2.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn metrics_serialize_to_json() {
+    let metrics = metrics(SOURCE, RuntimeCapabilityFlags::all());
+    let json = serde_json::to_string_pretty(&metrics[1]).expect("metrics should serialize");
+    expect![[r#"
+        {
+          "name": "B",
+          "operations": 0,
+          "functions": 1,
+          "types": 0,
+          "lines": 5,
+          "genericInstantiations": 1,
+          "averageCallableLines": 3.0,
+          "unsupportedFeatures": 0,
+          "unsupportedFeatureDensity": 0.0
+        }"#]]
+    .assert_eq(&json);
+}
@@ -0,0 +1,378 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Mutation testing: small, systematic edits are made to the sources of a program, one at a time,
+//! and its tests are run against each edited program, called a mutant. A mutant that passes every
+//! test survives, which points at behavior that the tests don't check.
+//!
+//! Gates are swapped for their adjoints, the conditions of `if` expressions are negated and the
+//! bounds of ranges are moved by one. Only calls to operations that are adjointable are swapped,
+//! and self-adjoint gates such as `H` aren't, since their mutants would always survive. Mutants that
+//! don't compile anyway are stillborn and aren't run.
+//!
+//! A mutant can loop for much longer than the program, such as when the bound of a loop moves, so
+//! each test of a mutant only runs for a budget of statements. A mutant that runs out of it is
+//! killed by timing out.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{batch::BatchCompiler, compile};
+use qsc_ast::{
+    ast::{Expr, ExprKind, Functor, UnOp},
+    visit::{self, Visitor},
+};
+use qsc_data_structures::span::Span;
+use qsc_eval::{
+    backend::SparseSim, debug::map_hir_package_to_fir, eval_push_expr, lower::Lowerer,
+    output::GenericReceiver, Env, State, StepAction, StepResult,
+};
+use qsc_fir::fir;
+use qsc_frontend::{
+    compile::{CompileUnit, SourceContents, SourceMap, SourceName},
+    typeck,
+};
+use qsc_hir::{
+    hir::{self, CallableKind, PackageId},
+    ty::{Arrow, FunctorSet, FunctorSetValue, Ty},
+};
+use qsc_passes::PackageType;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Gates that are their own adjoint.
+const SELF_ADJOINT: &[&str] = &[
+    "I", "H", "X", "Y", "Z", "CNOT", "CCNOT", "CX", "CY", "CZ", "SWAP",
+];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("the program does not compile with test {0}")]
+    Compile(String, Vec<compile::Error>),
+
+    #[error("test {0} fails before any mutation: {1}")]
+    TestFailed(String, String),
+
+    #[error("test {0} does not finish within {1} statements before any mutation")]
+    OutOfSteps(String, u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// A gate is swapped for its adjoint, or an adjoint gate for the gate.
+    Adjoint,
+    /// The condition of an `if` expression is negated.
+    FlipCondition,
+    /// A bound of a range is moved by one.
+    OffByOne,
+}
+
+/// An edit of one span of a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mutant {
+    pub kind: Edit,
+    /// The name of the source that is edited.
+    pub source: SourceName,
+    /// The span of the source that is replaced, relative to the start of the source.
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl Mutant {
+    /// The sources with the edit applied.
+    #[must_use]
+    pub fn apply(
+        &self,
+        sources: &[(SourceName, SourceContents)],
+    ) -> Vec<(SourceName, SourceContents)> {
+        sources
+            .iter()
+            .map(|(name, contents)| {
+                if *name == self.source {
+                    let lo = self.span.lo as usize;
+                    let hi = self.span.hi as usize;
+                    let mutated =
+                        format!("{}{}{}", &contents[..lo], self.replacement, &contents[hi..]);
+                    (Arc::clone(name), mutated.into())
+                } else {
+                    (Arc::clone(name), Arc::clone(contents))
+                }
+            })
+            .collect()
+    }
+}
+
+impl Display for Mutant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            Edit::Adjoint => "adjoint",
+            Edit::FlipCondition => "flipped condition",
+            Edit::OffByOne => "off by one",
+        };
+        write!(
+            f,
+            "{} [{}-{}]: {kind}: `{}`",
+            self.source, self.span.lo, self.span.hi, self.replacement
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The test with the given entry expression failed.
+    Killed(String),
+    /// The test with the given entry expression ran out of its budget of statements.
+    TimedOut(String),
+    /// Every test passed.
+    Survived,
+    /// The mutant doesn't compile.
+    Stillborn,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MutantResult {
+    pub mutant: Mutant,
+    pub outcome: Outcome,
+}
+
+impl MutantResult {
+    #[must_use]
+    pub fn survived(&self) -> bool {
+        self.outcome == Outcome::Survived
+    }
+}
+
+/// Finds the mutants of the sources of the compilation unit, in source order.
+#[must_use]
+pub fn mutants(unit: &CompileUnit) -> Vec<Mutant> {
+    let mut finder = Finder {
+        sources: &unit.sources,
+        tys: &unit.ast.tys,
+        mutants: Vec::new(),
+    };
+    finder.visit_package(&unit.ast.package);
+    let mut mutants = finder.mutants;
+    mutants.sort_by(|a, b| (&a.source, a.span.lo).cmp(&(&b.source, b.span.lo)));
+    mutants
+}
+
+/// Runs the tests against the sources and then against each of their mutants, stopping at the
+/// first test that kills a mutant. Each test is an entry expression, such as `Test.TestAdd()`,
+/// that passes if it runs to completion within `step_budget` statements.
+///
+/// # Errors
+///
+/// Returns an error if the sources don't compile with a test, or a test fails or runs out of
+/// statements before any mutation.
+pub fn run_mutants(
+    compiler: &BatchCompiler,
+    sources: &[(SourceName, SourceContents)],
+    tests: &[String],
+    step_budget: u64,
+) -> Result<Vec<MutantResult>, Error> {
+    let mut runner = Runner::new(compiler, step_budget);
+    for test in tests {
+        match runner.run(sources, test) {
+            Ok(Run::Passed) => {}
+            Ok(Run::Failed(message)) => return Err(Error::TestFailed(test.clone(), message)),
+            Ok(Run::OutOfSteps) => return Err(Error::OutOfSteps(test.clone(), step_budget)),
+            Err(errors) => return Err(Error::Compile(test.clone(), errors)),
+        }
+    }
+
+    let (unit, _) = compiler.compile(
+        SourceMap::new(sources.iter().cloned(), None),
+        PackageType::Lib,
+    );
+    let mut results = Vec::new();
+    for mutant in mutants(&unit) {
+        let mutated = mutant.apply(sources);
+        let mut outcome = Outcome::Survived;
+        for test in tests {
+            match runner.run(&mutated, test) {
+                Ok(Run::Passed) => {}
+                Ok(Run::Failed(_)) => outcome = Outcome::Killed(test.clone()),
+                Ok(Run::OutOfSteps) => outcome = Outcome::TimedOut(test.clone()),
+                Err(_) => outcome = Outcome::Stillborn,
+            }
+            if outcome != Outcome::Survived {
+                break;
+            }
+        }
+        results.push(MutantResult { mutant, outcome });
+    }
+    Ok(results)
+}
+
+/// How a test run ended.
+enum Run {
+    Passed,
+    /// The test failed with the error message.
+    Failed(String),
+    /// The test didn't finish within the budget of statements.
+    OutOfSteps,
+}
+
+/// Runs tests on the libraries of a batch compiler, which are lowered once.
+struct Runner<'a> {
+    compiler: &'a BatchCompiler,
+    fir_store: fir::PackageStore,
+    /// The ID that the program under test is lowered into, after the libraries.
+    package: PackageId,
+    /// The number of statements that a test can run.
+    step_budget: u64,
+}
+
+impl<'a> Runner<'a> {
+    fn new(compiler: &'a BatchCompiler, step_budget: u64) -> Self {
+        let mut lowerer = Lowerer::new();
+        let mut fir_store = fir::PackageStore::new();
+        let mut count = 0;
+        for (id, unit) in compiler.package_store() {
+            fir_store.insert(
+                map_hir_package_to_fir(id),
+                lowerer.lower_package(&unit.package),
+            );
+            count += 1;
+        }
+        Self {
+            compiler,
+            fir_store,
+            package: PackageId::from(count),
+            step_budget,
+        }
+    }
+
+    /// Compiles the sources with the test as their entry expression and runs it one statement at
+    /// a time, until it finishes or runs out of its budget.
+    fn run(
+        &mut self,
+        sources: &[(SourceName, SourceContents)],
+        test: &str,
+    ) -> Result<Run, Vec<compile::Error>> {
+        let (unit, errors) = self.compiler.compile(
+            SourceMap::new(sources.iter().cloned(), Some(test.into())),
+            PackageType::Exe,
+        );
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let package = map_hir_package_to_fir(self.package);
+        let fir_package = Lowerer::new().lower_package(&unit.package);
+        let entry = fir_package.entry.expect("package should have an entry");
+        self.fir_store.insert(package, fir_package);
+
+        let mut out = Vec::new();
+        let mut receiver = GenericReceiver::new(&mut out);
+        let mut env = Env::default();
+        let mut sim = SparseSim::new();
+        let mut state = State::new(package, None);
+        eval_push_expr(&mut state, entry);
+        for _ in 0..=self.step_budget {
+            match state.eval(
+                &self.fir_store,
+                &mut env,
+                &mut sim,
+                &mut receiver,
+                &[],
+                StepAction::In,
+            ) {
+                Ok(StepResult::Return(_)) => return Ok(Run::Passed),
+                Ok(_) => {}
+                Err((error, _)) => return Ok(Run::Failed(error.to_string())),
+            }
+        }
+        Ok(Run::OutOfSteps)
+    }
+}
+
+struct Finder<'a> {
+    sources: &'a SourceMap,
+    tys: &'a typeck::Table,
+    mutants: Vec<Mutant>,
+}
+
+impl Finder<'_> {
+    /// Replaces the expression with the replacement made from its source text, unless it comes
+    /// from the entry expression rather than a source.
+    fn mutate(&mut self, kind: Edit, expr: &Expr, replace: impl FnOnce(&str) -> String) {
+        let Some(source) = self.sources.find_by_offset(expr.span.lo) else {
+            return;
+        };
+        if self
+            .sources
+            .entry()
+            .is_some_and(|entry| entry.name == source.name)
+        {
+            return;
+        }
+        let span = Span {
+            lo: expr.span.lo - source.offset,
+            hi: expr.span.hi - source.offset,
+        };
+        let replacement = replace(&source.contents[span.lo as usize..span.hi as usize]);
+        self.mutants.push(Mutant {
+            kind,
+            source: Arc::clone(&source.name),
+            span,
+            replacement,
+        });
+    }
+
+    fn text(&self, expr: &Expr) -> Option<String> {
+        let source = self.sources.find_by_offset(expr.span.lo)?;
+        let lo = (expr.span.lo - source.offset) as usize;
+        let hi = (expr.span.hi - source.offset) as usize;
+        Some(source.contents[lo..hi].to_string())
+    }
+
+    /// Whether the expression is an operation that supports the adjoint functor.
+    fn is_adjointable(&self, expr: &Expr) -> bool {
+        match self.tys.terms.get(expr.id) {
+            Some(Ty::Arrow(arrow)) => {
+                let Arrow { kind, functors, .. } = &**arrow;
+                let functors = match functors {
+                    FunctorSet::Value(value) | FunctorSet::Param(_, value) => *value,
+                    FunctorSet::Infer(_) => FunctorSetValue::Empty,
+                };
+                *kind == CallableKind::Operation && functors.contains(&hir::Functor::Adj)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Finder<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::Call(callee, _) => match &*callee.kind {
+                ExprKind::Path(path)
+                    if !SELF_ADJOINT.contains(&&*path.name.name) && self.is_adjointable(callee) =>
+                {
+                    self.mutate(Edit::Adjoint, callee, |text| format!("Adjoint {text}"));
+                }
+                ExprKind::UnOp(UnOp::Functor(Functor::Adj), inner) => {
+                    if let Some(text) = self.text(inner) {
+                        self.mutate(Edit::Adjoint, callee, |_| text);
+                    }
+                }
+                _ => {}
+            },
+            ExprKind::If(cond, ..) => {
+                self.mutate(Edit::FlipCondition, cond, |text| format!("not ({text})"));
+            }
+            ExprKind::Range(start, _, end) => {
+                for bound in [start, end].into_iter().flatten() {
+                    for delta in ["+ 1", "- 1"] {
+                        self.mutate(Edit::OffByOne, bound, |text| format!("({text}) {delta}"));
+                    }
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
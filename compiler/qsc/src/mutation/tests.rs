@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{mutants, run_mutants, Error, Outcome};
+use crate::{batch::BatchCompiler, compile::QubitInitialization};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        operation Prepare(q : Qubit) : Unit is Adj {
+            H(q);
+            S(q);
+        }
+
+        function Sum(n : Int) : Int {
+            mutable total = 0;
+            for i in 1..n {
+                set total += i;
+            }
+            total
+        }
+
+        function Sign(x : Int) : Int {
+            if x < 0 { -1 } else { 1 }
+        }
+    }
+"};
+
+fn tests() -> Vec<String> {
+    vec![
+        r#"Microsoft.Quantum.Diagnostics.Fact(Test.Sum(3) == 6, "sum")"#.to_string(),
+        r#"Microsoft.Quantum.Diagnostics.Fact(Test.Sign(-2) == -1, "sign")"#.to_string(),
+    ]
+}
+
+#[test]
+fn mutants_are_found_in_source_order() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let (unit, errors) = compiler.compile(
+        SourceMap::new([("test".into(), SOURCE.into())], None),
+        PackageType::Lib,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let found = mutants(&unit)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    expect![[r#"
+        test [88-89]: adjoint: `Adjoint S`
+        test [179-180]: off by one: `(1) + 1`
+        test [179-180]: off by one: `(1) - 1`
+        test [182-183]: off by one: `(n) + 1`
+        test [182-183]: off by one: `(n) - 1`
+        test [291-296]: flipped condition: `not (x < 0)`"#]]
+    .assert_eq(&found.join("\n"));
+}
+
+#[test]
+fn mutants_that_pass_the_tests_survive() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let results = run_mutants(&compiler, &[("test".into(), SOURCE.into())], &tests(), 1000)
+        .expect("tests should pass before mutation");
+    let outcomes = results
+        .iter()
+        .map(|result| format!("{}: {:?}", result.mutant.replacement, result.outcome))
+        .collect::<Vec<_>>();
+    expect![[r#"
+        Adjoint S: Survived
+        (1) + 1: Killed("Microsoft.Quantum.Diagnostics.Fact(Test.Sum(3) == 6, \"sum\")")
+        (1) - 1: Survived
+        (n) + 1: Killed("Microsoft.Quantum.Diagnostics.Fact(Test.Sum(3) == 6, \"sum\")")
+        (n) - 1: Killed("Microsoft.Quantum.Diagnostics.Fact(Test.Sum(3) == 6, \"sum\")")
+        not (x < 0): Killed("Microsoft.Quantum.Diagnostics.Fact(Test.Sign(-2) == -1, \"sign\")")"#]]
+    .assert_eq(&outcomes.join("\n"));
+    assert_eq!(results.iter().filter(|result| result.survived()).count(), 2);
+}
+
+#[test]
+fn only_adjointable_operations_are_swapped_for_their_adjoints() {
+    let source = indoc! {"
+        namespace Test {
+            operation Flip(q : Qubit) : Unit {
+                Rx(1.0, q);
+            }
+            operation Turn(q : Qubit) : Unit is Adj {
+                Rx(1.0, q);
+            }
+            function Double(x : Int) : Int {
+                2 * x
+            }
+            operation Apply() : Unit {
+                use q = Qubit();
+                Flip(q);
+                Turn(q);
+                let _ = Double(1);
+                Reset(q);
+            }
+        }
+    "};
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let (unit, errors) = compiler.compile(
+        SourceMap::new([("test".into(), source.into())], None),
+        PackageType::Lib,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let found = mutants(&unit)
+        .iter()
+        .map(|mutant| mutant.replacement.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(found, ["Adjoint Rx", "Adjoint Rx", "Adjoint Turn"]);
+}
+
+#[test]
+fn mutant_that_loops_forever_times_out() {
+    let source = indoc! {"
+        namespace Test {
+            function Count() : Int {
+                mutable i = 0;
+                while i < 10 {
+                    if i >= 0 {
+                        set i += 1;
+                    }
+                }
+                i
+            }
+        }
+    "};
+    let test = r#"Microsoft.Quantum.Diagnostics.Fact(Test.Count() == 10, "count")"#.to_string();
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let results = run_mutants(
+        &compiler,
+        &[("test".into(), source.into())],
+        std::slice::from_ref(&test),
+        1000,
+    )
+    .expect("tests should pass before mutation");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].outcome, Outcome::TimedOut(test));
+}
+
+#[test]
+fn test_that_runs_out_of_steps_is_an_error() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let error = run_mutants(&compiler, &[("test".into(), SOURCE.into())], &tests(), 2)
+        .expect_err("test should run out of steps");
+    assert!(matches!(error, Error::OutOfSteps(_, 2)), "{error:?}");
+}
+
+#[test]
+fn failing_test_is_an_error() {
+    let compiler = BatchCompiler::new(RuntimeCapabilityFlags::all(), QubitInitialization::Zero);
+    let error = run_mutants(
+        &compiler,
+        &[("test".into(), SOURCE.into())],
+        &[r#"Microsoft.Quantum.Diagnostics.Fact(Test.Sum(3) == 5, "sum")"#.to_string()],
+        1000,
+    )
+    .expect_err("test should fail");
+    assert!(matches!(error, Error::TestFailed(..)), "{error:?}");
+}
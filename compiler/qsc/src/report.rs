@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Renders diagnostics for terminals in the style of `rustc`: the message and code, an excerpt of
+//! the source with each label underlined, and the help and related diagnostics below it.
+
+#[cfg(test)]
+mod tests;
+
+use miette::{Diagnostic, GraphicalReportHandler, GraphicalTheme};
+use std::{
+    env,
+    io::{self, IsTerminal},
+    str::FromStr,
+};
+
+/// The width that diagnostics are wrapped to when the terminal doesn't say how wide it is.
+const DEFAULT_WIDTH: usize = 100;
+
+/// Whether rendered diagnostics use ANSI colors.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorChoice {
+    /// Colors when standard error is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn use_color(self) -> bool {
+        match self {
+            Self::Auto => {
+                io::stderr().is_terminal()
+                    && env::var_os("NO_COLOR").filter(|v| !v.is_empty()).is_none()
+            }
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!(
+                "unknown color choice `{s}`, expected one of: auto, always, never"
+            )),
+        }
+    }
+}
+
+/// Renders diagnostics with their source excerpts.
+pub struct Renderer {
+    handler: GraphicalReportHandler,
+}
+
+impl Renderer {
+    /// Creates a renderer that colors its output as chosen and wraps it to the width, or to the
+    /// width in the `COLUMNS` environment variable if none is given.
+    #[must_use]
+    pub fn new(color: ColorChoice, width: Option<usize>) -> Self {
+        let theme = if color.use_color() {
+            GraphicalTheme::unicode()
+        } else {
+            GraphicalTheme::unicode_nocolor()
+        };
+        let width = width
+            .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH);
+        Self {
+            handler: GraphicalReportHandler::new_themed(theme).with_width(width),
+        }
+    }
+
+    /// Renders the diagnostic, without a trailing newline.
+    #[must_use]
+    pub fn render(&self, diagnostic: &dyn Diagnostic) -> String {
+        let mut output = String::new();
+        self.handler
+            .render_report(&mut output, diagnostic)
+            .expect("writing to a string should succeed");
+        output.truncate(output.trim_end().len());
+        output
+    }
+}
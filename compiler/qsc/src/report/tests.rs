@@ -0,0 +1,56 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{ColorChoice, Renderer};
+use crate::compile;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn error() -> compile::Error {
+    let store = PackageStore::new(compile::core());
+    let source = indoc! {"
+        namespace Test {
+            function Main() : Int {
+                Missing()
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test.qs".into(), source.into())], None);
+    let (_, errors) = compile::compile(
+        &store,
+        &[],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    errors
+        .into_iter()
+        .next()
+        .expect("source should have an error")
+}
+
+#[test]
+fn rendering_shows_the_source_excerpt() {
+    let output = Renderer::new(ColorChoice::Never, Some(80)).render(&error());
+    assert!(output.contains("Qsc.Resolve.NotFound"), "{output}");
+    assert!(output.contains("`Missing` not found"), "{output}");
+    assert!(output.contains("[test.qs:2:1]"), "{output}");
+    assert!(output.contains("│         Missing()"), "{output}");
+    assert!(output.contains("·         ───────"), "{output}");
+    assert!(!output.contains('\u{1b}'), "{output}");
+    assert!(!output.ends_with('\n'));
+}
+
+#[test]
+fn colors_can_be_forced() {
+    let output = Renderer::new(ColorChoice::Always, Some(80)).render(&error());
+    assert!(output.contains("\u{1b}["), "{output}");
+}
+
+#[test]
+fn color_choices_parse() {
+    assert_eq!("never".parse(), Ok(ColorChoice::Never));
+    assert_eq!("always".parse(), Ok(ColorChoice::Always));
+    assert!("sometimes".parse::<ColorChoice>().is_err());
+}
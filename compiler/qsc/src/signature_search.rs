@@ -0,0 +1,354 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Finds callables by their signatures, for discovering what the standard library and the other
+//! packages offer without knowing the names to look for. A query is a callable type written as in
+//! Q#, such as `(Qubit[], Double) => Unit is Adj`, in which `_` and type parameters stand for any
+//! type.
+//!
+//! Matches are approximate. A callable matches if it takes the items of the input in any order,
+//! takes any type where it is generic, and supports at least the functors of the query. Each match
+//! comes with a distance that counts how loosely it matches, so that exact matches come first.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::message::MessageParams;
+use qsc_doc_gen::display::parse_doc_for_summary;
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    global::{self, Kind},
+    hir::{CallableKind, ItemId, ItemStatus, PackageId, Visibility},
+    ty::{Arrow, FunctorSet, FunctorSetValue, Prim, Ty},
+};
+use std::{str::FromStr, sync::Arc};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq)]
+pub enum Error {
+    #[error("expected {0} at offset {1} of the signature")]
+    #[diagnostic(code("Qsc.SignatureSearch.Syntax"))]
+    Syntax(&'static str, usize),
+    #[error("the signature is not a function or operation type")]
+    #[diagnostic(help("write the type of a callable, such as `Qubit => Unit`"))]
+    #[diagnostic(code("Qsc.SignatureSearch.NotCallable"))]
+    NotCallable,
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Syntax(expected, offset) => vec![(*expected).to_string(), offset.to_string()],
+            Self::NotCallable => Vec::new(),
+        }
+    }
+}
+
+/// A signature to search for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query(ArrowPattern);
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let pattern = parser.ty()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(Error::Syntax("the end of the signature", parser.pos));
+        }
+        match pattern {
+            Pattern::Arrow(arrow) => Ok(Self(*arrow)),
+            _ => Err(Error::NotCallable),
+        }
+    }
+}
+
+/// A callable whose signature matches a query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureMatch {
+    pub id: ItemId,
+    /// The fully qualified name of the callable.
+    pub name: String,
+    /// The type of the callable, as it is written in Q#.
+    pub signature: String,
+    /// The summary from the documentation of the callable.
+    pub summary: String,
+    /// How loosely the callable matches, where 0 is an exact match.
+    pub distance: u32,
+}
+
+/// Finds the callables that match the query among those that the package can use: its own, and
+/// the public ones of the other packages in the store. The closest matches come first.
+#[must_use]
+pub fn search(store: &PackageStore, package: PackageId, query: &Query) -> Vec<SignatureMatch> {
+    let mut matches = Vec::new();
+    for (id, unit) in store {
+        for global in global::iter_package(Some(id), &unit.package) {
+            let Kind::Term(term) = global.kind else {
+                continue;
+            };
+            if global.status != ItemStatus::Available
+                || (global.visibility != Visibility::Public && id != package)
+            {
+                continue;
+            }
+            let arrow = term.scheme.ty();
+            let Some(distance) = arrow_distance(&query.0, arrow) else {
+                continue;
+            };
+            let signature = Ty::Arrow(Box::new(arrow.clone())).display();
+            let summary = unit
+                .package
+                .items
+                .get(term.id.item)
+                .map(|item| parse_doc_for_summary(&item.doc))
+                .unwrap_or_default();
+            matches.push(SignatureMatch {
+                id: term.id,
+                name: format!("{}.{}", global.namespace, global.name),
+                signature: signature[1..signature.len() - 1].to_string(),
+                summary,
+                distance,
+            });
+        }
+    }
+    matches.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    matches
+}
+
+/// A type in a query.
+#[derive(Clone, Debug, PartialEq)]
+enum Pattern {
+    /// Any type, written as `_` or as a type parameter.
+    Any,
+    Array(Box<Pattern>),
+    Arrow(Box<ArrowPattern>),
+    /// A type that isn't primitive, matched by its name.
+    Named(Arc<str>),
+    Prim(Prim),
+    Tuple(Vec<Pattern>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ArrowPattern {
+    kind: CallableKind,
+    input: Pattern,
+    output: Pattern,
+    functors: FunctorSetValue,
+}
+
+fn distance(pattern: &Pattern, ty: &Ty) -> Option<u32> {
+    match (pattern, ty) {
+        (Pattern::Any, _) => Some(0),
+        (_, Ty::Param(..)) => Some(1),
+        (Pattern::Array(item), Ty::Array(ty_item)) => distance(item, ty_item),
+        (Pattern::Arrow(arrow), Ty::Arrow(ty_arrow)) => arrow_distance(arrow, ty_arrow),
+        (Pattern::Named(name), Ty::Udt(ty_name, _)) => (name == ty_name).then_some(0),
+        (Pattern::Prim(prim), Ty::Prim(ty_prim)) => (prim == ty_prim).then_some(0),
+        (Pattern::Tuple(items), Ty::Tuple(ty_items)) => tuple_distance(items, ty_items),
+        _ => None,
+    }
+}
+
+fn arrow_distance(pattern: &ArrowPattern, arrow: &Arrow) -> Option<u32> {
+    let functors = match arrow.functors {
+        FunctorSet::Value(functors) | FunctorSet::Param(_, functors) => functors,
+        FunctorSet::Infer(_) => FunctorSetValue::Empty,
+    };
+    if pattern.kind != arrow.kind || pattern.functors.union(&functors) != functors {
+        return None;
+    }
+    Some(distance(&pattern.input, &arrow.input)? + distance(&pattern.output, &arrow.output)?)
+}
+
+/// The distance between tuples, whose items can match in any order. Taking the items in another
+/// order makes the match one looser.
+fn tuple_distance(items: &[Pattern], ty_items: &[Ty]) -> Option<u32> {
+    if items.len() != ty_items.len() {
+        return None;
+    }
+    let in_order = items
+        .iter()
+        .zip(ty_items)
+        .map(|(item, ty)| distance(item, ty))
+        .sum::<Option<u32>>();
+    if in_order.is_some() {
+        return in_order;
+    }
+
+    // Match the most specific items first, so that those that match anything take what is left.
+    let mut order: Vec<_> = items.iter().collect();
+    order.sort_by_key(|item| matches!(item, Pattern::Any));
+    let mut unused: Vec<_> = ty_items.iter().collect();
+    let mut total = 1;
+    for item in order {
+        let (index, item_distance) = unused
+            .iter()
+            .enumerate()
+            .filter_map(|(index, ty)| Some((index, distance(item, ty)?)))
+            .min_by_key(|&(_, item_distance)| item_distance)?;
+        unused.swap_remove(index);
+        total += item_distance;
+    }
+    Some(total)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn ty(&mut self) -> Result<Pattern, Error> {
+        let mut ty = self.atom()?;
+        while self.eat("[") {
+            self.expect("]", "`]`")?;
+            ty = Pattern::Array(Box::new(ty));
+        }
+
+        let kind = if self.eat("->") {
+            CallableKind::Function
+        } else if self.eat("=>") {
+            CallableKind::Operation
+        } else {
+            return Ok(ty);
+        };
+        let output = self.ty()?;
+        let functors = if self.eat_keyword("is") {
+            self.functors()?
+        } else {
+            FunctorSetValue::Empty
+        };
+        Ok(Pattern::Arrow(Box::new(ArrowPattern {
+            kind,
+            input: ty,
+            output,
+            functors,
+        })))
+    }
+
+    fn atom(&mut self) -> Result<Pattern, Error> {
+        if self.eat("(") {
+            let mut items = Vec::new();
+            let mut trailing_comma = false;
+            while !self.eat(")") {
+                items.push(self.ty()?);
+                trailing_comma = self.eat(",");
+                if !trailing_comma {
+                    self.expect(")", "`,` or `)`")?;
+                    break;
+                }
+            }
+            return Ok(if items.len() == 1 && !trailing_comma {
+                items.pop().expect("tuple should have one item")
+            } else {
+                Pattern::Tuple(items)
+            });
+        }
+
+        if self.eat("'") {
+            self.ident()
+                .ok_or(Error::Syntax("a type parameter name", self.pos))?;
+            return Ok(Pattern::Any);
+        }
+
+        let mut name = self.ident().ok_or(Error::Syntax("a type", self.pos))?;
+        while self.eat(".") {
+            name = self.ident().ok_or(Error::Syntax("a name", self.pos))?;
+        }
+        Ok(match name {
+            "_" => Pattern::Any,
+            "Unit" => Pattern::Tuple(Vec::new()),
+            "BigInt" => Pattern::Prim(Prim::BigInt),
+            "Bool" => Pattern::Prim(Prim::Bool),
+            "Double" => Pattern::Prim(Prim::Double),
+            "Int" => Pattern::Prim(Prim::Int),
+            "Pauli" => Pattern::Prim(Prim::Pauli),
+            "Qubit" => Pattern::Prim(Prim::Qubit),
+            "Range" => Pattern::Prim(Prim::Range),
+            "Result" => Pattern::Prim(Prim::Result),
+            "String" => Pattern::Prim(Prim::String),
+            _ => Pattern::Named(name.into()),
+        })
+    }
+
+    fn functors(&mut self) -> Result<FunctorSetValue, Error> {
+        let mut functors = self.functor()?;
+        loop {
+            if self.eat("+") {
+                functors = functors.union(&self.functor()?);
+            } else if self.eat("*") {
+                functors = functors.intersect(&self.functor()?);
+            } else {
+                return Ok(functors);
+            }
+        }
+    }
+
+    fn functor(&mut self) -> Result<FunctorSetValue, Error> {
+        if self.eat("(") {
+            let functors = self.functors()?;
+            self.expect(")", "`)`")?;
+            return Ok(functors);
+        }
+        let pos = self.pos;
+        match self.ident() {
+            Some("Adj") => Ok(FunctorSetValue::Adj),
+            Some("Ctl") => Ok(FunctorSetValue::Ctl),
+            _ => Err(Error::Syntax("`Adj` or `Ctl`", pos)),
+        }
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let pos = self.pos;
+        if self.ident() == Some(keyword) {
+            true
+        } else {
+            self.pos = pos;
+            false
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str, expected: &'static str) -> Result<(), Error> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(Error::Syntax(expected, self.pos))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{search, Error, Query, SignatureMatch};
+use crate::compile;
+use indoc::indoc;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+fn search_source(source: &str, query: &str) -> Vec<SignatureMatch> {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let (unit, errors) = compile::compile(
+        &store,
+        &[std],
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+    let package = store.insert(unit);
+    let query = query.parse().expect("query should parse");
+    search(&store, package, &query)
+}
+
+fn distance_of(matches: &[SignatureMatch], name: &str) -> Option<u32> {
+    matches.iter().find(|m| m.name == name).map(|m| m.distance)
+}
+
+#[test]
+fn queries_must_be_callable_types() {
+    assert_eq!("Qubit[]".parse::<Query>(), Err(Error::NotCallable));
+    assert_eq!(
+        "(Qubit => Unit".parse::<Query>(),
+        Err(Error::Syntax("`,` or `)`", 14))
+    );
+    assert_eq!(
+        "Qubit => Unit is Adj +".parse::<Query>(),
+        Err(Error::Syntax("`Adj` or `Ctl`", 22))
+    );
+    assert!("(Qubit[], Double) => Unit is Adj + Ctl"
+        .parse::<Query>()
+        .is_ok());
+    assert!("('T[], Int) -> 'T".parse::<Query>().is_ok());
+}
+
+#[test]
+fn standard_operations_are_found_by_signature() {
+    let matches = search_source("", "(Qubit, Qubit) => Unit is Adj + Ctl");
+    assert_eq!(
+        distance_of(&matches, "Microsoft.Quantum.Intrinsic.CNOT"),
+        Some(0)
+    );
+    assert_eq!(
+        distance_of(&matches, "Microsoft.Quantum.Intrinsic.SWAP"),
+        Some(0)
+    );
+    assert_eq!(distance_of(&matches, "Microsoft.Quantum.Canon.CZ"), Some(0));
+    assert_eq!(distance_of(&matches, "Microsoft.Quantum.Intrinsic.H"), None);
+    let cnot = matches
+        .iter()
+        .find(|m| m.name == "Microsoft.Quantum.Intrinsic.CNOT")
+        .expect("CNOT should match");
+    assert_eq!(cnot.signature, "(Qubit, Qubit) => Unit is Adj + Ctl");
+    assert!(!cnot.summary.is_empty());
+}
+
+#[test]
+fn matches_are_approximate_and_ordered_by_distance() {
+    let source = indoc! {"
+        namespace Test {
+            operation Exact(qs : Qubit[], angle : Double) : Unit is Adj {}
+            operation Swapped(angle : Double, qs : Qubit[]) : Unit is Adj + Ctl {}
+            operation Generic<'T>(qs : Qubit[], value : 'T) : Unit is Adj {}
+            operation NotAdjointable(qs : Qubit[], angle : Double) : Unit {}
+            function NotAnOperation(qs : Qubit[], angle : Double) : Unit {}
+        }
+    "};
+    let matches = search_source(source, "(Qubit[], Double) => Unit is Adj");
+    let names: Vec<_> = matches
+        .iter()
+        .filter(|m| m.name.starts_with("Test."))
+        .map(|m| (m.name.as_str(), m.distance))
+        .collect();
+    assert_eq!(
+        names,
+        [("Test.Exact", 0), ("Test.Generic", 1), ("Test.Swapped", 1)]
+    );
+}
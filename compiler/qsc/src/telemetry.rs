@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Anonymous usage counters for products that embed the compiler and want to know how it is used.
+//! Nothing is collected unless the host supplies a [`TelemetrySink`], and the counters never carry
+//! names, sources or values from the programs, only which language constructs they use, which
+//! profile they were compiled for and how many shots were run. Where the counters go, and whether
+//! they are aggregated or sent anywhere, is up to the host.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_ast::{
+    ast::{Expr, ExprKind, Item, ItemKind, Stmt, StmtKind, UnOp},
+    visit::{self, Visitor},
+};
+use qsc_frontend::compile::{CompileUnit, RuntimeCapabilityFlags};
+use std::collections::BTreeSet;
+
+/// A usage counter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Counter {
+    /// A compiled package uses the language construct, such as `lambda` or `repeat`.
+    Feature(&'static str),
+    /// A package was compiled for the profile, named as in [`RuntimeCapabilityFlags::presets`].
+    Profile(&'static str),
+    /// Shots of an entry expression were run.
+    Shots,
+}
+
+/// Receives usage counters from the compiler and the interpreter.
+pub trait TelemetrySink {
+    /// Adds to the counter.
+    fn add(&mut self, counter: Counter, count: u64);
+}
+
+/// Reports the compilation of a package for the capabilities: each language construct that its
+/// sources use, once however often they use it, and the smallest profile that has the
+/// capabilities. Constructs are found in the syntax tree, since passes rewrite some of them.
+pub fn report_compilation(
+    sink: &mut dyn TelemetrySink,
+    unit: &CompileUnit,
+    capabilities: RuntimeCapabilityFlags,
+) {
+    let mut features = Features::default();
+    features.visit_package(&unit.ast.package);
+    for feature in features.used {
+        sink.add(Counter::Feature(feature), 1);
+    }
+    sink.add(Counter::Profile(capabilities.minimal_preset().0), 1);
+}
+
+/// The language constructs that a package uses.
+#[derive(Default)]
+struct Features {
+    used: BTreeSet<&'static str>,
+}
+
+impl<'a> Visitor<'a> for Features {
+    fn visit_item(&mut self, item: &'a Item) {
+        if let ItemKind::Ty(..) = &*item.kind {
+            self.used.insert("newtype");
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Qubit(..) = &*stmt.kind {
+            self.used.insert("use");
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        let feature = match &*expr.kind {
+            ExprKind::Conjugate(..) => Some("within"),
+            ExprKind::Fail(_) => Some("fail"),
+            ExprKind::For(..) => Some("for"),
+            ExprKind::Hole => Some("partial application"),
+            ExprKind::Interpolate(_) => Some("interpolation"),
+            ExprKind::Lambda(..) => Some("lambda"),
+            ExprKind::Range(..) => Some("range"),
+            ExprKind::Repeat(..) => Some("repeat"),
+            ExprKind::UnOp(UnOp::Functor(_), _) => Some("functor"),
+            ExprKind::While(..) => Some("while"),
+            _ => None,
+        };
+        self.used.extend(feature);
+        visit::walk_expr(self, expr);
+    }
+}
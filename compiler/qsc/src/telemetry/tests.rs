@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Counter, TelemetrySink};
+use crate::interpret::Interpreter;
+use indoc::indoc;
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use std::{cell::RefCell, io, rc::Rc, time::Duration};
+
+#[derive(Clone, Default)]
+struct Recorder(Rc<RefCell<Vec<(Counter, u64)>>>);
+
+impl TelemetrySink for Recorder {
+    fn add(&mut self, counter: Counter, count: u64) {
+        self.0.borrow_mut().push((counter, count));
+    }
+}
+
+fn interpreter(source: &str, capabilities: RuntimeCapabilityFlags) -> Interpreter {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    Interpreter::new(true, sources, PackageType::Exe, capabilities)
+        .expect("interpreter should be created")
+}
+
+#[test]
+fn nothing_is_collected_without_a_sink() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {}
+        }
+    "};
+    let mut interpreter = interpreter(source, RuntimeCapabilityFlags::all());
+    let recorder = Recorder::default();
+    interpreter.set_telemetry_sink(Some(Box::new(recorder.clone())));
+    interpreter.set_telemetry_sink(None);
+    let mut out = io::sink();
+    interpreter.run_shots(&mut GenericReceiver::new(&mut out), 2, || Duration::ZERO);
+    assert_eq!(
+        *recorder.0.borrow(),
+        [(Counter::Profile("Unrestricted"), 1)]
+    );
+}
+
+#[test]
+fn compilations_and_shots_are_counted() {
+    let source = indoc! {"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                use q = Qubit();
+                let add = (x, y) -> x + y;
+                mutable n = 0;
+                for i in 0..2 {
+                    set n = add(n, i);
+                    set n = add(n, i);
+                }
+                repeat {
+                    set n += 1;
+                } until n > 5;
+                n
+            }
+        }
+    "};
+    let mut interpreter = interpreter(source, RuntimeCapabilityFlags::all());
+    let recorder = Recorder::default();
+    interpreter.set_telemetry_sink(Some(Box::new(recorder.clone())));
+    let mut out = io::sink();
+    let results = interpreter.run_shots(&mut GenericReceiver::new(&mut out), 3, || Duration::ZERO);
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(
+        *recorder.0.borrow(),
+        [
+            (Counter::Feature("for"), 1),
+            (Counter::Feature("lambda"), 1),
+            (Counter::Feature("range"), 1),
+            (Counter::Feature("repeat"), 1),
+            (Counter::Feature("use"), 1),
+            (Counter::Profile("Unrestricted"), 1),
+            (Counter::Shots, 3),
+        ]
+    );
+}
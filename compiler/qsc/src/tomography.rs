@@ -0,0 +1,220 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Process tomography of operations on the simulator.
+//!
+//! The Choi matrix of an operation on `n` qubits is found by preparing `n` Bell pairs, applying the
+//! operation to one half of each pair and reading the state of all `2n` qubits. It is normalized
+//! to a trace of one, so it is the density matrix of that state, averaged over shots.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use rustc_hash::FxHashMap;
+
+/// The number of Jacobi sweeps after which the eigenvalues are used even if they haven't converged.
+const MAX_SWEEPS: usize = 100;
+
+/// The off-diagonal weight below which a matrix is treated as diagonal.
+const EPSILON: f64 = 1e-24;
+
+/// The most qubits that a Choi matrix can be found for. The matrix of `n` qubits has `16^n`
+/// entries, and comparing two of them finds the eigenvalues of a matrix four times as large, so
+/// each qubit beyond this multiplies the memory and time by 16.
+pub const MAX_QUBITS: usize = 5;
+
+/// The normalized Choi matrix of an operation, indexed by the basis state of the reference qubits
+/// followed by the basis state of the qubits the operation acted on. As in `DumpMachine`, the
+/// first qubit of each register is the most significant bit of its basis state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChoiMatrix {
+    qubits: usize,
+    entries: Vec<Complex<f64>>,
+}
+
+/// How closely a Choi matrix matches a reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChoiComparison {
+    /// The overlap between the two Choi matrices, which is the process fidelity when the reference
+    /// is a unitary operation.
+    pub process_fidelity: f64,
+    /// The fidelity averaged over pure input states, derived from the process fidelity.
+    pub average_gate_fidelity: f64,
+    /// The trace distance between the two Choi matrices.
+    pub trace_distance: f64,
+    /// A lower bound on the diamond distance, which is half the diamond norm of the difference
+    /// between the operations. It is the trace distance between the Choi matrices.
+    pub diamond_distance_lower: f64,
+    /// An upper bound on the diamond distance, which is the trace distance scaled by the
+    /// dimension of the qubits the operations act on.
+    pub diamond_distance_upper: f64,
+}
+
+impl ChoiMatrix {
+    pub(crate) fn new(qubits: usize) -> Self {
+        let dimension = 1 << (2 * qubits);
+        Self {
+            qubits,
+            entries: vec![Complex::default(); dimension * dimension],
+        }
+    }
+
+    /// The number of qubits the operation acts on.
+    #[must_use]
+    pub fn qubits(&self) -> usize {
+        self.qubits
+    }
+
+    /// The number of rows and columns in the matrix, which is `4^n` for `n` qubits.
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        1 << (2 * self.qubits)
+    }
+
+    /// The entry at the given row and column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is out of range.
+    #[must_use]
+    pub fn entry(&self, row: usize, col: usize) -> Complex<f64> {
+        assert!(col < self.dimension(), "column should be in range");
+        self.entries[row * self.dimension() + col]
+    }
+
+    /// Adds a weighted state of the simulator, where the reference qubits are the first `n` qubits
+    /// and the operation acted on the next `n`. Any other qubits still allocated are traced out.
+    pub(crate) fn add_state(
+        &mut self,
+        state: &[(BigUint, Complex<f64>)],
+        count: usize,
+        weight: f64,
+    ) {
+        let count = count as u64;
+        let own = 2 * self.qubits as u64;
+        let mut groups: FxHashMap<BigUint, Vec<(usize, Complex<f64>)>> = FxHashMap::default();
+        for (index, amplitude) in state {
+            let mut row = 0;
+            for qubit in 0..own {
+                row = row << 1 | usize::from(index.bit(count - 1 - qubit));
+            }
+            let mut rest = BigUint::default();
+            for qubit in own..count {
+                if index.bit(count - 1 - qubit) {
+                    rest.set_bit(qubit - own, true);
+                }
+            }
+            groups.entry(rest).or_default().push((row, *amplitude));
+        }
+
+        let dimension = self.dimension();
+        for amplitudes in groups.values() {
+            for &(row, a) in amplitudes {
+                for &(col, b) in amplitudes {
+                    self.entries[row * dimension + col] += a * b.conj() * weight;
+                }
+            }
+        }
+    }
+
+    /// Compares the matrix with the Choi matrix of a reference operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operations act on different numbers of qubits.
+    #[must_use]
+    pub fn compare(&self, reference: &ChoiMatrix) -> ChoiComparison {
+        assert_eq!(
+            self.qubits, reference.qubits,
+            "operations should act on the same number of qubits"
+        );
+        let dimension = self.dimension();
+        let mut overlap = 0.0;
+        for row in 0..dimension {
+            for col in 0..dimension {
+                overlap += (reference.entry(row, col) * self.entry(col, row)).re;
+            }
+        }
+
+        let difference = self
+            .entries
+            .iter()
+            .zip(&reference.entries)
+            .map(|(a, b)| a - b)
+            .collect::<Vec<_>>();
+        let trace_distance = trace_norm(&difference, dimension) / 2.0;
+        #[allow(clippy::cast_precision_loss)]
+        let d = (1usize << self.qubits) as f64;
+        ChoiComparison {
+            process_fidelity: overlap,
+            average_gate_fidelity: (d * overlap + 1.0) / (d + 1.0),
+            trace_distance,
+            diamond_distance_lower: trace_distance,
+            diamond_distance_upper: (d * trace_distance).min(1.0),
+        }
+    }
+}
+
+/// The sum of the absolute eigenvalues of a Hermitian matrix. The matrix `A + iB` has the same
+/// eigenvalues as the real symmetric matrix `[[A, -B], [B, A]]`, each of them twice, so the
+/// eigenvalues are found with the Jacobi method on that matrix.
+fn trace_norm(matrix: &[Complex<f64>], dimension: usize) -> f64 {
+    let n = 2 * dimension;
+    let mut real = vec![0.0; n * n];
+    for row in 0..dimension {
+        for col in 0..dimension {
+            let entry = matrix[row * dimension + col];
+            real[row * n + col] = entry.re;
+            real[(row + dimension) * n + col + dimension] = entry.re;
+            real[row * n + col + dimension] = -entry.im;
+            real[(row + dimension) * n + col] = entry.im;
+        }
+    }
+
+    symmetric_eigenvalues(real, n)
+        .iter()
+        .map(|eigenvalue| eigenvalue.abs())
+        .sum::<f64>()
+        / 2.0
+}
+
+fn symmetric_eigenvalues(mut matrix: Vec<f64>, n: usize) -> Vec<f64> {
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+            .map(|(p, q)| matrix[p * n + q] * matrix[p * n + q])
+            .sum();
+        if off_diagonal < EPSILON {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = matrix[p * n + q];
+                if apq.abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                // Rotate rows and columns `p` and `q` so that the entry at `(p, q)` becomes zero.
+                let theta = (matrix[q * n + q] - matrix[p * n + p]) / (2.0 * apq);
+                let tan = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let cos = 1.0 / (tan * tan + 1.0).sqrt();
+                let sin = tan * cos;
+                for k in 0..n {
+                    let (akp, akq) = (matrix[k * n + p], matrix[k * n + q]);
+                    matrix[k * n + p] = cos * akp - sin * akq;
+                    matrix[k * n + q] = sin * akp + cos * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (matrix[p * n + k], matrix[q * n + k]);
+                    matrix[p * n + k] = cos * apk - sin * aqk;
+                    matrix[q * n + k] = sin * apk + cos * aqk;
+                }
+            }
+        }
+    }
+
+    (0..n).map(|i| matrix[i * n + i]).collect()
+}
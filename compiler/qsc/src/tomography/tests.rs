@@ -0,0 +1,129 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{trace_norm, ChoiMatrix, MAX_QUBITS};
+use crate::{
+    interpret::{Error, Interpreter},
+    noise::PauliNoise,
+};
+use indoc::indoc;
+use num_complex::Complex;
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+const SOURCE: &str = indoc! {"
+    namespace Test {
+        operation Identity(qs : Qubit[]) : Unit {}
+
+        operation Flip(qs : Qubit[]) : Unit {
+            ApplyToEach(X, qs);
+        }
+
+        operation Entangle(qs : Qubit[]) : Unit {
+            use ancilla = Qubit();
+            CNOT(qs[0], ancilla);
+            CNOT(qs[0], ancilla);
+            CNOT(qs[0], qs[1]);
+        }
+    }
+"};
+
+fn interpreter() -> Interpreter {
+    let sources = SourceMap::new([("test.qs".into(), SOURCE.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created");
+    interpreter.set_quantum_seed(Some(0));
+    interpreter
+}
+
+fn choi(operation: &str, qubits: usize, noise: Option<PauliNoise>, shots: u32) -> ChoiMatrix {
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter()
+        .choi_matrix(&mut out, operation, qubits, noise, shots)
+        .expect("choi matrix should be found")
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+#[test]
+fn identity_is_a_bell_state() {
+    let choi = choi("Test.Identity", 1, None, 1);
+    assert_eq!(choi.dimension(), 4);
+    for (row, col) in [(0, 0), (0, 3), (3, 0), (3, 3)] {
+        assert_close(choi.entry(row, col).re, 0.5);
+    }
+    assert_close(choi.entry(1, 1).re, 0.0);
+}
+
+#[test]
+fn operation_matches_itself() {
+    let choi = choi("Test.Entangle", 2, None, 1);
+    let comparison = choi.compare(&choi);
+    assert_close(comparison.process_fidelity, 1.0);
+    assert_close(comparison.average_gate_fidelity, 1.0);
+    assert_close(comparison.trace_distance, 0.0);
+    assert_close(comparison.diamond_distance_upper, 0.0);
+}
+
+#[test]
+fn orthogonal_operations_are_perfectly_distinguishable() {
+    let comparison = choi("Test.Flip", 1, None, 1).compare(&choi("Test.Identity", 1, None, 1));
+    assert_close(comparison.process_fidelity, 0.0);
+    assert_close(comparison.average_gate_fidelity, 1.0 / 3.0);
+    assert_close(comparison.trace_distance, 1.0);
+    assert_close(comparison.diamond_distance_lower, 1.0);
+    assert_close(comparison.diamond_distance_upper, 1.0);
+}
+
+#[test]
+fn noise_lowers_fidelity() {
+    let ideal = choi("Test.Flip", 1, None, 1);
+    let noisy = choi("Test.Flip", 1, Some(PauliNoise::depolarizing(0.5)), 200);
+    let comparison = noisy.compare(&ideal);
+    assert!(comparison.process_fidelity < 0.9, "{comparison:?}");
+    assert!(comparison.process_fidelity > 0.2, "{comparison:?}");
+    assert!(comparison.diamond_distance_lower <= comparison.diamond_distance_upper);
+
+    let depolarized = choi("Test.Flip", 1, Some(PauliNoise::depolarizing(1.0)), 20);
+    assert_close(depolarized.compare(&ideal).process_fidelity, 0.0);
+}
+
+#[test]
+fn too_many_qubits_are_rejected() {
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    let errors = interpreter()
+        .choi_matrix(&mut out, "Test.Identity", MAX_QUBITS + 1, None, 1)
+        .expect_err("choi matrix should be too large");
+    assert!(
+        matches!(errors[..], [Error::TomographyQubits(qubits, MAX_QUBITS)] if qubits == MAX_QUBITS + 1),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn trace_norm_of_hermitian_matrix_sums_absolute_eigenvalues() {
+    let y = [
+        Complex::new(0.0, 0.0),
+        Complex::new(0.0, -1.0),
+        Complex::new(0.0, 1.0),
+        Complex::new(0.0, 0.0),
+    ];
+    assert_close(trace_norm(&y, 2), 2.0);
+    let diagonal = [
+        Complex::new(3.0, 0.0),
+        Complex::new(0.0, 0.0),
+        Complex::new(0.0, 0.0),
+        Complex::new(-1.0, 0.0),
+    ];
+    assert_close(trace_norm(&diagonal, 2), 4.0);
+}
@@ -0,0 +1,150 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Exports traces of simulations in the Chrome trace event format, which can be opened in Perfetto
+//! or `chrome://tracing`. Calls are shown as nested slices on the first track, and each qubit has
+//! its own track with the gates and measurements applied to it. A gate on several qubits appears
+//! on the track of each of them. Each unit of logical time is shown as one microsecond.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_eval::debug::Frame;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+pub use qsc_eval::trace::{TraceEvent, TracingBackend};
+
+/// The track of the calls, which comes before the tracks of the qubits.
+const CALLS_TRACK: usize = 0;
+
+/// A trace in the Chrome trace event format.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: Vec<Event<'a>>,
+}
+
+/// An event in the Chrome trace event format, with the fields that the phase `ph` doesn't use left
+/// out.
+#[derive(Serialize)]
+struct Event<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<&'static str>,
+    ph: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u64>,
+    pid: u32,
+    tid: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Args<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Args<'a> {
+    Gate { qubits: &'a [usize] },
+    Track { name: String },
+    None {},
+}
+
+impl Event<'_> {
+    fn call(ph: &'static str, time: u64, name: Option<String>) -> Self {
+        Self {
+            name,
+            cat: None,
+            ph,
+            ts: Some(time),
+            dur: None,
+            pid: 1,
+            tid: CALLS_TRACK,
+            args: None,
+        }
+    }
+}
+
+/// Formats the trace as Chrome trace event JSON, naming each call with `name`.
+#[must_use]
+pub fn chrome_trace(events: &[TraceEvent], name: impl Fn(&Frame) -> String) -> String {
+    let mut qubits = BTreeSet::new();
+    let mut slices = Vec::new();
+    for event in events {
+        match event {
+            TraceEvent::Enter { time, frame } => {
+                slices.push(Event::call("B", *time, Some(name(frame))));
+            }
+            TraceEvent::Exit { time, .. } => slices.push(Event::call("E", *time, None)),
+            TraceEvent::Gate {
+                time,
+                name,
+                qubits: targets,
+            } => {
+                for &qubit in targets {
+                    qubits.insert(qubit);
+                    slices.push(slice(
+                        name,
+                        "gate",
+                        *time,
+                        qubit,
+                        Args::Gate { qubits: targets },
+                    ));
+                }
+            }
+            TraceEvent::Measurement { time, qubit, reset } => {
+                qubits.insert(*qubit);
+                let name = if *reset { "MResetZ" } else { "M" };
+                slices.push(slice(name, "measurement", *time, *qubit, Args::None {}));
+            }
+            TraceEvent::Reset { time, qubit } => {
+                qubits.insert(*qubit);
+                slices.push(slice("Reset", "reset", *time, *qubit, Args::None {}));
+            }
+        }
+    }
+
+    let mut trace_events = vec![track_name(CALLS_TRACK, "calls".to_string())];
+    trace_events.extend(
+        qubits
+            .into_iter()
+            .map(|qubit| track_name(qubit + 1, format!("qubit {qubit}"))),
+    );
+    trace_events.extend(slices);
+    serde_json::to_string(&Trace { trace_events }).expect("trace should serialize")
+}
+
+/// A slice of one unit of time on the track of the qubit.
+fn slice<'a>(
+    name: &str,
+    category: &'static str,
+    time: u64,
+    qubit: usize,
+    args: Args<'a>,
+) -> Event<'a> {
+    Event {
+        name: Some(name.to_string()),
+        cat: Some(category),
+        ph: "X",
+        ts: Some(time),
+        dur: Some(1),
+        pid: 1,
+        tid: qubit + 1,
+        args: Some(args),
+    }
+}
+
+fn track_name(track: usize, name: String) -> Event<'static> {
+    Event {
+        name: Some("thread_name".to_string()),
+        cat: None,
+        ph: "M",
+        ts: None,
+        dur: None,
+        pid: 1,
+        tid: track,
+        args: Some(Args::Track { name }),
+    }
+}
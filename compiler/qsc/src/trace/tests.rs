@@ -0,0 +1,80 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{chrome_trace, TraceEvent};
+use crate::interpret::{GenericReceiver, Interpreter};
+use expect_test::expect;
+use indoc::indoc;
+use qsc_data_structures::span::Span;
+use qsc_eval::{debug::Frame, val::FunctorApp};
+use qsc_fir::fir::{PackageId, StoreItemId};
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+
+#[test]
+fn calls_and_qubits_have_their_own_tracks() {
+    let frame = Frame {
+        span: Span::default(),
+        id: StoreItemId {
+            package: PackageId::from(2),
+            item: 0.into(),
+        },
+        caller: PackageId::from(2),
+        functor: FunctorApp::default(),
+    };
+    let events = [
+        TraceEvent::Enter { time: 0, frame },
+        TraceEvent::Gate {
+            time: 0,
+            name: "CX",
+            qubits: vec![1, 0],
+        },
+        TraceEvent::Measurement {
+            time: 1,
+            qubit: 1,
+            reset: true,
+        },
+        TraceEvent::Exit { time: 2, frame },
+    ];
+    expect![[r#"{"traceEvents":[{"name":"thread_name","ph":"M","pid":1,"tid":0,"args":{"name":"calls"}},{"name":"thread_name","ph":"M","pid":1,"tid":1,"args":{"name":"qubit 0"}},{"name":"thread_name","ph":"M","pid":1,"tid":2,"args":{"name":"qubit 1"}},{"name":"Say \"Hi\"","ph":"B","ts":0,"pid":1,"tid":0},{"name":"CX","cat":"gate","ph":"X","ts":0,"dur":1,"pid":1,"tid":2,"args":{"qubits":[1,0]}},{"name":"CX","cat":"gate","ph":"X","ts":0,"dur":1,"pid":1,"tid":1,"args":{"qubits":[1,0]}},{"name":"MResetZ","cat":"measurement","ph":"X","ts":1,"dur":1,"pid":1,"tid":2,"args":{}},{"ph":"E","ts":2,"pid":1,"tid":0}]}"#]]
+    .assert_eq(&chrome_trace(&events, |_| "Say \"Hi\"".to_string()));
+}
+
+#[test]
+fn entry_is_traced_with_callable_names() {
+    let source = indoc! {"
+        namespace Test {
+            operation Prepare(q : Qubit) : Unit is Adj {
+                H(q);
+            }
+            function Twice(x : Int) : Int {
+                2 * x
+            }
+            @EntryPoint()
+            operation Main() : Result {
+                use q = Qubit();
+                let _ = Twice(1);
+                Adjoint Prepare(q);
+                Microsoft.Quantum.Measurement.MResetZ(q)
+            }
+        }
+    "};
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+    )
+    .expect("interpreter should be created");
+    let mut stdout = std::io::sink();
+    let mut receiver = GenericReceiver::new(&mut stdout);
+    let (result, trace) = interpreter.trace_entry(&mut receiver);
+    assert!(result.is_ok(), "{result:?}");
+    assert!(trace.contains(r#"{"name":"Main","ph":"B","ts":0,"pid":1,"tid":0}"#));
+    assert!(trace.contains(r#"{"name":"Adjoint Prepare","ph":"B","ts":0,"pid":1,"tid":0}"#));
+    // Calls are traced even if they don't reach an intrinsic.
+    assert!(trace.contains(r#"{"name":"Twice","ph":"B","ts":0,"pid":1,"tid":0}"#));
+    assert!(trace.contains(r#"{"name":"H","cat":"gate","ph":"X","ts":0,"dur":1,"pid":1,"tid":1,"#));
+    assert!(trace.contains(r#"{"name":"MResetZ","cat":"measurement","ph":"X","ts":1,"dur":1,"#));
+}
@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Watching the files of a program for changes, so that it can be rebuilt whenever they are saved.
+//! Changes are reported by the file system, so nothing is read until a file actually changes.
+
+#[cfg(test)]
+mod tests;
+
+use notify::{
+    event::ModifyKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _,
+};
+use qsc_project::MANIFEST_FILE_NAME;
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+pub use notify::{Error, Result};
+
+/// Watches source files, or the files of a project, for changes.
+pub struct Watcher {
+    /// The watcher stops when it is dropped, so it is kept alongside its events.
+    _handle: RecommendedWatcher,
+    events: Receiver<Result<Event>>,
+    /// Whether a change to the file with the path is reported.
+    wanted: Box<dyn Fn(&Path) -> bool + Send>,
+}
+
+impl Watcher {
+    /// Watches the files. Their directories are watched rather than the files themselves, since
+    /// editors often save a file by replacing it with a new one.
+    pub fn files(paths: &[PathBuf]) -> Result<Self> {
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for path in paths {
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            let dir = dir.canonicalize()?;
+            let name = path
+                .file_name()
+                .ok_or_else(|| Error::generic("watched path should name a file"))?;
+            files.push(dir.join(name));
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+
+        Self::new(&dirs, RecursiveMode::NonRecursive, move |path| {
+            files.iter().any(|file| file == path)
+        })
+    }
+
+    /// Watches the manifest and the Q# sources of the project in the directory, including
+    /// sources that are added after watching starts.
+    pub fn project(dir: &Path) -> Result<Self> {
+        let dir = dir.canonicalize()?;
+        let manifest = dir.join(MANIFEST_FILE_NAME);
+        Self::new(&[dir], RecursiveMode::Recursive, move |path| {
+            path == manifest || path.extension().is_some_and(|ext| ext == "qs")
+        })
+    }
+
+    fn new(
+        dirs: &[PathBuf],
+        mode: RecursiveMode,
+        wanted: impl Fn(&Path) -> bool + Send + 'static,
+    ) -> Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        for dir in dirs {
+            watcher.watch(dir, mode)?;
+        }
+        Ok(Self {
+            _handle: watcher,
+            events,
+            wanted: Box::new(wanted),
+        })
+    }
+
+    /// Blocks until a watched file is created, changed or removed, and returns the paths of the
+    /// files that changed. Once a file changes, the changes are collected until none have been
+    /// made for the quiet period, so that a save that writes a file in several steps, or saves
+    /// several files at once, is reported once.
+    pub fn wait(&self, quiet: Duration) -> Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+        loop {
+            let event = if changed.is_empty() {
+                self.events
+                    .recv()
+                    .map_err(|_| Error::generic("watcher stopped"))?
+            } else {
+                match self.events.recv_timeout(quiet) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => return Ok(changed),
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(Error::generic("watcher stopped"))
+                    }
+                }
+            };
+
+            let event = event?;
+            if !is_change(event.kind) {
+                continue;
+            }
+            for path in event.paths {
+                if (self.wanted)(&path) && !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the event changes the contents of a file. Files are opened and read while building,
+/// which mustn't be taken as changes.
+fn is_change(kind: EventKind) -> bool {
+    match kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Modify(kind) => !matches!(kind, ModifyKind::Metadata(_)),
+        EventKind::Any | EventKind::Access(_) | EventKind::Other => false,
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{is_change, Watcher};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const QUIET: Duration = Duration::from_millis(100);
+
+/// An empty directory for the test, which is removed when it is dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("qsc-watch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("directory should be created");
+        Self(dir.canonicalize().expect("directory should exist"))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn saved_file_is_reported() {
+    let dir = TempDir::new("file");
+    let file = dir.path().join("Main.qs");
+    fs::write(&file, "namespace A {}").expect("file should be written");
+    fs::write(dir.path().join("Other.qs"), "").expect("file should be written");
+
+    let watcher = Watcher::files(std::slice::from_ref(&file)).expect("watcher should start");
+    fs::write(dir.path().join("Other.qs"), "namespace B {}").expect("file should be written");
+    fs::write(&file, "namespace C {}").expect("file should be written");
+
+    let changed = watcher
+        .wait(QUIET)
+        .expect("watcher should report the change");
+    assert_eq!(changed, [file]);
+}
+
+#[test]
+fn reading_a_file_is_not_a_change() {
+    let dir = TempDir::new("read");
+    let file = dir.path().join("Main.qs");
+    fs::write(&file, "namespace A {}").expect("file should be written");
+
+    let watcher = Watcher::files(std::slice::from_ref(&file)).expect("watcher should start");
+    fs::read_to_string(&file).expect("file should be read");
+    fs::write(&file, "namespace B {}").expect("file should be written");
+
+    let changed = watcher
+        .wait(QUIET)
+        .expect("watcher should report the change");
+    assert_eq!(changed, std::slice::from_ref(&file));
+    fs::read_to_string(&file).expect("file should be read");
+    while let Ok(event) = watcher.events.recv_timeout(QUIET) {
+        let kind = event.expect("event should be received").kind;
+        assert!(
+            !is_change(kind),
+            "reading should not change the file: {kind:?}"
+        );
+    }
+}
+
+#[test]
+fn source_added_to_project_is_reported() {
+    let dir = TempDir::new("project");
+    fs::write(dir.path().join("qsharp.json"), "{}").expect("manifest should be written");
+    fs::create_dir(dir.path().join("src")).expect("directory should be created");
+
+    let watcher = Watcher::project(dir.path()).expect("watcher should start");
+    fs::write(dir.path().join("notes.txt"), "").expect("file should be written");
+    let source = dir.path().join("src").join("Main.qs");
+    fs::write(&source, "namespace A {}").expect("file should be written");
+
+    let changed = watcher
+        .wait(QUIET)
+        .expect("watcher should report the change");
+    assert_eq!(changed, [source]);
+}
@@ -5,3 +5,4 @@
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
 pub mod qir_base;
+pub mod target;
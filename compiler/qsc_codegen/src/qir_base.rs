@@ -4,22 +4,32 @@
 #[cfg(test)]
 mod tests;
 
+mod body;
+mod debug_info;
+mod result_alloc;
+
+use crate::target::{trace_entry, TargetBackend};
+use body::Body;
+use debug_info::{Dbg, DebugInfo, Metadata};
+use miette::Diagnostic;
 use num_bigint::BigUint;
 use num_complex::Complex;
-use qsc_data_structures::index_map::IndexMap;
+use qsc_data_structures::{index_map::IndexMap, message::MessageParams};
 use qsc_eval::{
     backend::Backend,
-    debug::{map_hir_package_to_fir, Frame},
-    eval,
-    output::GenericReceiver,
-    val::Value,
-    Env, Error,
+    debug::{map_fir_package_to_hir, Frame},
+    val::{self, Value},
+    Error, PackageSpan,
 };
-use qsc_fir::fir;
-use qsc_frontend::compile::PackageStore;
+use qsc_frontend::compile::{ConfigAttr, PackageStore, RuntimeCapabilityFlags};
 use qsc_hir::hir::{self};
 use rustc_hash::FxHashSet;
-use std::fmt::{Display, Write};
+use std::{
+    fmt::{Display, Write},
+    mem::take,
+    str::FromStr,
+};
+use thiserror::Error;
 
 /// # Errors
 ///
@@ -31,34 +41,205 @@ pub fn generate_qir(
     store: &PackageStore,
     package: hir::PackageId,
 ) -> std::result::Result<String, (Error, Vec<Frame>)> {
-    let mut fir_lowerer = qsc_eval::lower::Lowerer::new();
-    let mut fir_store = fir::PackageStore::new();
-    for (id, unit) in store {
-        fir_store.insert(
-            map_hir_package_to_fir(id),
-            fir_lowerer.lower_package(&unit.package),
-        );
+    generate_qir_with_options(store, package, CodegenOptions::default())
+}
+
+/// Generates QIR for the entry expression of the package, shaped by the given options.
+///
+/// # Errors
+///
+/// This function will return an error if execution was unable to complete, if the results can't
+/// be recorded in the requested output format, or if the program needs more qubits than the
+/// target has.
+pub fn generate_qir_with_options(
+    store: &PackageStore,
+    package: hir::PackageId,
+    options: CodegenOptions,
+) -> std::result::Result<String, (Error, Vec<Frame>)> {
+    generate_qir_with_sim(store, package, BaseProfSim::with_options(options))
+}
+
+fn generate_qir_with_sim(
+    store: &PackageStore,
+    package: hir::PackageId,
+    mut sim: BaseProfSim,
+) -> std::result::Result<String, (Error, Vec<Frame>)> {
+    let val = trace_entry(store, package, &mut sim)?;
+    let entry_span = || {
+        let span = store
+            .get(package)
+            .and_then(|unit| unit.package.entry.as_ref())
+            .map(|entry| entry.span)
+            .unwrap_or_default();
+        PackageSpan { package, span }
+    };
+    if let (Some(max), Some(frames)) = (sim.max_qubits, sim.budget_exceeded_at.take()) {
+        // Point at the innermost call made by the program itself, such as the allocation of a
+        // register whose size comes from a precision parameter.
+        let span = frames
+            .iter()
+            .rev()
+            .find(|frame| map_fir_package_to_hir(frame.caller) == package)
+            .map_or_else(entry_span, |frame| PackageSpan {
+                package,
+                span: frame.span,
+            });
+        let needed = sim.next_qubit_hardware_id.0;
+        return Err((Error::QubitBudgetExceeded(needed, max, span), frames));
+    }
+    if !sim.can_record(&val) {
+        let format = sim.output_format.to_string();
+        return Err((Error::UnrecordableOutput(format, entry_span()), Vec::new()));
+    }
+    Ok(sim.finish_with_debug_info(&val, store))
+}
+
+/// An error from finishing a program traced by [`BaseProfSim`].
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum FinishError {
+    #[error("debug info for the program was not resolved")]
+    #[diagnostic(help(
+        "programs traced with debug info must be finished with `finish_with_debug_info`"
+    ))]
+    #[diagnostic(code("Qsc.Codegen.UnresolvedDebugInfo"))]
+    UnresolvedDebugInfo,
+}
+
+impl MessageParams for FinishError {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::UnresolvedDebugInfo => Vec::new(),
+        }
+    }
+}
+
+/// Options that control the shape of the generated QIR.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// Whether to attach Q# source locations to the generated instructions as LLVM debug
+    /// metadata, with the calls that led to each instruction recorded as inlined call sites.
+    pub debug_info: bool,
+    /// How the value returned by the entry point is recorded as program output.
+    pub output_format: OutputFormat,
+    /// Whether measurements whose results are no longer needed may write to the registers of
+    /// earlier results, reducing the number of results the program requires.
+    pub reuse_result_registers: bool,
+    /// The most qubits the target has. Code generation fails for programs that need more,
+    /// counting the fresh qubits that stand in for measured and reset ones. Precisions known at
+    /// compile time are checked against the same budget by the passes, before code generation.
+    pub max_qubits: Option<usize>,
+}
+
+/// How the value returned by the entry point is recorded as program output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Records the tuples and arrays of the return value as they are nested, without labels.
+    #[default]
+    Tree,
+    /// Records the same tree as [`OutputFormat::Tree`], labeling each record with its sequence
+    /// number and its path from the root, such as `2_t1r` for the second element of a tuple.
+    LabeledTree,
+    /// Records every result in the return value in a single array, in the order they appear.
+    FlatArray,
+    /// Records the results in the return value as a single integer, with the first result in the
+    /// least significant bit. Reading results back requires integer computations, so the program
+    /// is marked for the adaptive profile.
+    PackedInt,
+}
+
+/// The most results that can be packed into a single output integer.
+pub const MAX_PACKED_RESULTS: usize = 64;
+
+/// Integers computed from results by the target are traced as handles spaced far enough apart
+/// that arithmetic on one is unlikely to produce another, so that only integers returned
+/// unchanged are recorded.
+const INT_OUTPUT_TAG: i64 = 0x5153_0000_0000_0000;
+const INT_OUTPUT_SHIFT: u32 = 24;
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(Self::Tree),
+            "labeled" => Ok(Self::LabeledTree),
+            "flat" => Ok(Self::FlatArray),
+            "packed" => Ok(Self::PackedInt),
+            _ => Err(format!(
+                "unknown output format `{s}`, expected one of: tree, labeled, flat, packed"
+            )),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Tree => "tree",
+            Self::LabeledTree => "labeled",
+            Self::FlatArray => "flat",
+            Self::PackedInt => "packed",
+        })
+    }
+}
+
+/// The QIR Base Profile backend.
+#[derive(Default)]
+pub struct BaseProfile {
+    pub options: CodegenOptions,
+}
+
+impl TargetBackend for BaseProfile {
+    fn name(&self) -> &str {
+        "base"
+    }
+
+    fn capabilities(&self) -> RuntimeCapabilityFlags {
+        RuntimeCapabilityFlags::empty()
+    }
+
+    fn file_extension(&self) -> &str {
+        "ll"
     }
 
-    let package = map_hir_package_to_fir(package);
-    let unit = fir_store.get(package).expect("store should have package");
-    let entry_expr = unit.entry.expect("package should have entry");
-
-    let mut sim = BaseProfSim::default();
-    let mut stdout = std::io::sink();
-    let mut out = GenericReceiver::new(&mut stdout);
-    let result = eval(
-        package,
-        None,
-        entry_expr.into(),
-        &fir_store,
-        &mut Env::default(),
-        &mut sim,
-        &mut out,
-    );
-    match result {
-        Ok(val) => Ok(sim.finish(&val)),
-        Err((err, stack)) => Err((err, stack)),
+    fn generate(
+        &self,
+        store: &PackageStore,
+        package: hir::PackageId,
+    ) -> std::result::Result<String, (Error, Vec<Frame>)> {
+        generate_qir_with_options(store, package, self.options)
+    }
+}
+
+/// The QIR Adaptive Profile backend, which supports integer outputs computed from results and
+/// forward branches on comparisons of results. Values can't flow out of a branch, since the
+/// generated code has no phi nodes, and integers can't be computed from results inside the
+/// program.
+#[derive(Default)]
+pub struct AdaptiveProfile {
+    pub options: CodegenOptions,
+}
+
+impl TargetBackend for AdaptiveProfile {
+    fn name(&self) -> &str {
+        "adaptive"
+    }
+
+    fn capabilities(&self) -> RuntimeCapabilityFlags {
+        ConfigAttr::Adaptive.into()
+    }
+
+    fn file_extension(&self) -> &str {
+        "ll"
+    }
+
+    fn generate(
+        &self,
+        store: &PackageStore,
+        package: hir::PackageId,
+    ) -> std::result::Result<String, (Error, Vec<Frame>)> {
+        let sim = BaseProfSim::with_options(self.options).with_result_branches();
+        generate_qir_with_sim(store, package, sim)
     }
 }
 
@@ -70,10 +251,22 @@ pub struct BaseProfSim {
     next_qubit_id: usize,
     next_qubit_hardware_id: HardwareId,
     qubit_map: IndexMap<usize, HardwareId>,
-    instrs: String,
-    measurements: String,
+    instrs: Body,
+    measurements: Body,
     decls: String,
     decl_names: FxHashSet<String>,
+    debug_info: Option<DebugInfo>,
+    output_format: OutputFormat,
+    reuse_result_registers: bool,
+    labels: Vec<String>,
+    int_outputs: Vec<Vec<usize>>,
+    result_branches: bool,
+    next_branch_id: usize,
+    open_branches: Vec<usize>,
+    max_qubits: Option<usize>,
+    /// The call stack of the latest intrinsic, kept to report where the qubit budget runs out.
+    call_stack: Vec<Frame>,
+    budget_exceeded_at: Option<Vec<Frame>>,
 }
 
 impl Default for BaseProfSim {
@@ -85,34 +278,126 @@ impl Default for BaseProfSim {
 impl BaseProfSim {
     #[must_use]
     pub fn new() -> Self {
-        let mut sim = BaseProfSim {
+        BaseProfSim {
             next_meas_id: 0,
             next_qubit_id: 0,
             next_qubit_hardware_id: HardwareId::default(),
             qubit_map: IndexMap::new(),
-            instrs: String::new(),
-            measurements: String::new(),
+            instrs: Body::default(),
+            measurements: Body::default(),
             decls: String::new(),
             decl_names: FxHashSet::default(),
-        };
-        sim.instrs.push_str(include_str!("./qir_base/prefix.ll"));
-        sim
+            debug_info: None,
+            output_format: OutputFormat::default(),
+            reuse_result_registers: false,
+            labels: Vec::new(),
+            int_outputs: Vec::new(),
+            result_branches: false,
+            next_branch_id: 0,
+            open_branches: Vec::new(),
+            max_qubits: None,
+            call_stack: Vec::new(),
+            budget_exceeded_at: None,
+        }
+    }
+
+    /// Creates a simulator that generates QIR shaped by the given options. If debug info is
+    /// requested, the program must be finished with [`BaseProfSim::finish_with_debug_info`].
+    #[must_use]
+    pub fn with_options(options: CodegenOptions) -> Self {
+        BaseProfSim {
+            debug_info: options.debug_info.then(DebugInfo::new),
+            output_format: options.output_format,
+            reuse_result_registers: options.reuse_result_registers,
+            max_qubits: options.max_qubits,
+            ..Self::new()
+        }
+    }
+
+    /// Lets the program branch on results when it runs, which requires the adaptive profile. Inside
+    /// a branch, measured and reset qubits are reset in place rather than replaced by fresh qubits,
+    /// so that both sides leave each qubit on the same hardware qubit.
+    #[must_use]
+    pub fn with_result_branches(mut self) -> Self {
+        self.result_branches = true;
+        self
+    }
+
+    /// Finishes the program, recording the value as its output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if debug info was requested, since it can only be resolved against the
+    /// sources by [`BaseProfSim::finish_with_debug_info`].
+    pub fn finish(self, val: &Value) -> std::result::Result<String, FinishError> {
+        if self.debug_info.is_some() {
+            return Err(FinishError::UnresolvedDebugInfo);
+        }
+        Ok(self.write_qir(val, Metadata::default()))
     }
 
+    /// Finishes the program, resolving the recorded instruction locations against the sources
+    /// in the store.
     #[must_use]
-    pub fn finish(mut self, val: &Value) -> String {
-        self.instrs.push_str(&self.measurements);
-        self.write_output_recording(val)
+    pub fn finish_with_debug_info(mut self, val: &Value, store: &PackageStore) -> String {
+        let metadata = self
+            .debug_info
+            .take()
+            .map(|debug_info| debug_info.resolve(store))
+            .unwrap_or_default();
+        self.write_qir(val, metadata)
+    }
+
+    fn write_qir(mut self, val: &Value, metadata: Metadata) -> String {
+        self.flush_measurements();
+        self.write_output(val)
             .expect("writing to string should succeed");
+        let (registers, num_results) = if self.reuse_result_registers {
+            result_alloc::reuse_result_registers(&self.instrs, self.next_meas_id)
+        } else {
+            ((0..self.next_meas_id).collect(), self.next_meas_id)
+        };
 
+        let mut qir = String::new();
         write!(
-            self.instrs,
+            qir,
+            include_str!("./qir_base/prefix.ll"),
+            self.label_globals(),
+            metadata.entry_attachment()
+        )
+        .expect("writing to string should succeed");
+        qir.push_str(&self.instrs.render(|id| registers[id]));
+
+        let profile = if self.output_format == OutputFormat::PackedInt
+            || !self.int_outputs.is_empty()
+            || self.next_branch_id > 0
+        {
+            "adaptive_profile"
+        } else {
+            "base_profile"
+        };
+        write!(
+            qir,
             include_str!("./qir_base/postfix.ll"),
-            self.decls, self.next_qubit_hardware_id.0, self.next_meas_id
+            self.decls,
+            profile,
+            self.next_qubit_hardware_id.0,
+            num_results,
+            metadata.flags,
+            metadata.flag_nodes,
+            metadata.nodes
         )
         .expect("writing to string should succeed");
 
-        self.instrs
+        qir
+    }
+
+    /// The location of the instruction being generated, if debug info is being recorded.
+    fn dbg(&mut self) -> Dbg {
+        self.debug_info
+            .as_mut()
+            .map(DebugInfo::current_location)
+            .unwrap_or_default()
     }
 
     #[must_use]
@@ -129,52 +414,248 @@ impl BaseProfSim {
             let mapped = self.next_qubit_hardware_id;
             self.next_qubit_hardware_id.0 += 1;
             self.qubit_map.insert(qubit, mapped);
+            if self.budget_exceeded_at.is_none()
+                && self
+                    .max_qubits
+                    .is_some_and(|max| self.next_qubit_hardware_id.0 > max)
+            {
+                self.budget_exceeded_at = Some(take(&mut self.call_stack));
+            }
             mapped
         }
     }
 
-    fn write_output_recording(&mut self, val: &Value) -> std::fmt::Result {
+    /// Whether the value can be recorded in the output format.
+    fn can_record(&self, val: &Value) -> bool {
+        match self.output_format {
+            OutputFormat::Tree | OutputFormat::LabeledTree => self.only_recordable_leaves(val),
+            OutputFormat::FlatArray => only_results(val),
+            OutputFormat::PackedInt => {
+                only_results(val) && collect_results(val).len() <= MAX_PACKED_RESULTS
+            }
+        }
+    }
+
+    fn only_recordable_leaves(&self, val: &Value) -> bool {
         match val {
-            Value::Array(arr) => {
-                self.write_array_recording(arr.len())?;
-                for val in arr.iter() {
-                    self.write_output_recording(val)?;
+            Value::Array(items) => items.iter().all(|val| self.only_recordable_leaves(val)),
+            Value::Tuple(items) => items.iter().all(|val| self.only_recordable_leaves(val)),
+            Value::Result(_) => true,
+            Value::Int(handle) => self.int_output(*handle).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Traces the target computing an integer from the results, returning a handle to it.
+    fn trace_result_array_as_int(&mut self, arg: &Value) -> std::result::Result<Value, String> {
+        let results = collect_results(arg);
+        if results.len() >= MAX_PACKED_RESULTS {
+            return Err(format!(
+                "`Length(results)` must be less than {MAX_PACKED_RESULTS}, but was {}",
+                results.len()
+            ));
+        }
+        let index = i64::try_from(self.int_outputs.len()).expect("handle should fit in i64");
+        self.int_outputs.push(results);
+        Ok(Value::Int(INT_OUTPUT_TAG + (index << INT_OUTPUT_SHIFT)))
+    }
+
+    /// The index of the integer output with the given handle.
+    fn int_output(&self, handle: i64) -> Option<usize> {
+        let offset = handle.checked_sub(INT_OUTPUT_TAG)?;
+        if offset < 0 || offset & ((1 << INT_OUTPUT_SHIFT) - 1) != 0 {
+            return None;
+        }
+        let index = usize::try_from(offset >> INT_OUTPUT_SHIFT).ok()?;
+        (index < self.int_outputs.len()).then_some(index)
+    }
+
+    fn write_output(&mut self, val: &Value) -> std::fmt::Result {
+        match self.output_format {
+            OutputFormat::Tree => self.write_output_recording(val, None),
+            OutputFormat::LabeledTree => self.write_output_recording(val, Some("")),
+            OutputFormat::FlatArray => {
+                let results = collect_results(val);
+                self.write_array_recording(results.len(), Label(None))?;
+                for res in results {
+                    self.write_result_recording(res, Label(None));
                 }
+                Ok(())
             }
+            OutputFormat::PackedInt => self.write_packed_recording(&collect_results(val)),
+        }
+    }
+
+    /// Records the value as a tree of tuples and arrays. When `path` is given, each record is
+    /// labeled with its path from the root.
+    fn write_output_recording(&mut self, val: &Value, path: Option<&str>) -> std::fmt::Result {
+        let (kind, items): (_, &[Value]) = match val {
+            Value::Array(arr) => ('a', &arr[..]),
             Value::Result(r) => {
-                self.write_result_recording(r.unwrap_id());
+                let path = path.map(|path| format!("{path}r"));
+                let label = self.label(path.as_deref());
+                self.write_result_recording(r.unwrap_id(), label);
+                return Ok(());
             }
-            Value::Tuple(tup) => {
-                self.write_tuple_recording(tup.len())?;
-                for val in tup.iter() {
-                    self.write_output_recording(val)?;
-                }
+            Value::Tuple(tup) => ('t', &tup[..]),
+            Value::Int(handle) => {
+                let index = self
+                    .int_output(*handle)
+                    .expect("integer output should be computed from results");
+                let results = self.int_outputs[index].clone();
+                let value = self.write_packed_int(&results, &format!("int{index}_"))?;
+                let path = path.map(|path| format!("{path}i"));
+                let label = self.label(path.as_deref());
+                return writeln!(
+                    self.instrs,
+                    "  call void @__quantum__rt__int_record_output(i64 {value}, {label})"
+                );
             }
             _ => panic!("unexpected value type: {val:?}"),
+        };
+
+        let path = path.map(|path| format!("{path}{kind}"));
+        let label = self.label(path.as_deref());
+        if kind == 'a' {
+            self.write_array_recording(items.len(), label)?;
+        } else {
+            self.write_tuple_recording(items.len(), label)?;
+        }
+        for (index, val) in items.iter().enumerate() {
+            let path = path.as_ref().map(|path| format!("{path}{index}"));
+            self.write_output_recording(val, path.as_deref())?;
         }
         Ok(())
     }
 
-    fn write_result_recording(&mut self, res: usize) {
+    /// Reads each result back and records them packed into a single integer.
+    fn write_packed_recording(&mut self, results: &[usize]) -> std::fmt::Result {
+        let packed = self.write_packed_int(results, "")?;
         writeln!(
             self.instrs,
-            "  call void @__quantum__rt__result_record_output({}, i8* null)",
-            Result(res),
+            "  call void @__quantum__rt__int_record_output(i64 {packed}, i8* null)"
+        )
+    }
+
+    /// Reads each result back and accumulates them into an integer, with the first result in the
+    /// least significant bit, returning the operand that holds the integer. The names of the
+    /// registers used are given the prefix.
+    fn write_packed_int(
+        &mut self,
+        results: &[usize],
+        prefix: &str,
+    ) -> std::result::Result<String, std::fmt::Error> {
+        self.write_raw_decl(
+            "__quantum__qis__read_result__body",
+            "declare i1 @__quantum__qis__read_result__body(%Result*)",
+        );
+        self.write_raw_decl(
+            "__quantum__rt__int_record_output",
+            "declare void @__quantum__rt__int_record_output(i64, i8*)",
+        );
+
+        let mut packed = "0".to_string();
+        for (bit, &res) in results.iter().enumerate() {
+            write!(
+                self.instrs,
+                "  %{prefix}bit{bit} = call i1 @__quantum__qis__read_result__body("
+            )?;
+            self.instrs.result(res);
+            writeln!(self.instrs, ")")?;
+            writeln!(
+                self.instrs,
+                "  %{prefix}ext{bit} = zext i1 %{prefix}bit{bit} to i64"
+            )?;
+            writeln!(
+                self.instrs,
+                "  %{prefix}shl{bit} = shl i64 %{prefix}ext{bit}, {bit}"
+            )?;
+            writeln!(
+                self.instrs,
+                "  %{prefix}packed{bit} = or i64 {packed}, %{prefix}shl{bit}"
+            )?;
+            packed = format!("%{prefix}packed{bit}");
+        }
+        Ok(packed)
+    }
+
+    /// Moves the deferred measurements into the instructions, so that their results can be read
+    /// by the instructions that follow.
+    fn flush_measurements(&mut self) {
+        let mut measurements = take(&mut self.measurements);
+        self.instrs.append(&mut measurements);
+    }
+
+    /// Reads the result back into the register with the given name.
+    fn write_read_result(&mut self, name: &str, res: usize) {
+        self.write_raw_decl(
+            "__quantum__qis__read_result__body",
+            "declare i1 @__quantum__qis__read_result__body(%Result*)",
+        );
+        write!(
+            self.instrs,
+            "  %{name} = call i1 @__quantum__qis__read_result__body("
         )
         .expect("writing to string should succeed");
+        self.instrs.result(res);
+        writeln!(self.instrs, ")").expect("writing to string should succeed");
+    }
+
+    fn write_raw_decl(&mut self, name: &str, decl: &str) {
+        if self.decl_names.insert(name.to_string()) {
+            writeln!(self.decls, "{decl}").expect("writing to string should succeed");
+        }
     }
 
-    fn write_tuple_recording(&mut self, size: usize) -> std::fmt::Result {
+    /// Allocates a label for the record at the given path, if records are labeled.
+    fn label(&mut self, path: Option<&str>) -> Label {
+        Label(path.map(|path| {
+            let index = self.labels.len();
+            let label = format!("{index}_{path}");
+            let len = label.len() + 1;
+            self.labels.push(label);
+            (index, len)
+        }))
+    }
+
+    /// The global string constants that hold the output labels.
+    fn label_globals(&self) -> String {
+        let mut globals = String::new();
+        for (index, label) in self.labels.iter().enumerate() {
+            writeln!(
+                globals,
+                "@{index} = internal constant [{} x i8] c\"{label}\\00\"",
+                label.len() + 1
+            )
+            .expect("writing to string should succeed");
+        }
+        if !globals.is_empty() {
+            globals.push('\n');
+        }
+        globals
+    }
+
+    fn write_result_recording(&mut self, res: usize, label: Label) {
+        write!(
+            self.instrs,
+            "  call void @__quantum__rt__result_record_output("
+        )
+        .expect("writing to string should succeed");
+        self.instrs.result(res);
+        writeln!(self.instrs, ", {label})").expect("writing to string should succeed");
+    }
+
+    fn write_tuple_recording(&mut self, size: usize, label: Label) -> std::fmt::Result {
         writeln!(
             self.instrs,
-            "  call void @__quantum__rt__tuple_record_output(i64 {size}, i8* null)"
+            "  call void @__quantum__rt__tuple_record_output(i64 {size}, {label})"
         )
     }
 
-    fn write_array_recording(&mut self, size: usize) -> std::fmt::Result {
+    fn write_array_recording(&mut self, size: usize, label: Label) -> std::fmt::Result {
         writeln!(
             self.instrs,
-            "  call void @__quantum__rt__array_record_output(i64 {size}, i8* null)"
+            "  call void @__quantum__rt__array_record_output(i64 {size}, {label})"
         )
     }
 
@@ -233,9 +714,10 @@ impl Backend for BaseProfSim {
         let ctl0 = self.map(ctl0);
         let ctl1 = self.map(ctl1);
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__ccx__body({}, {}, {})",
+            "  call void @__quantum__qis__ccx__body({}, {}, {}){dbg}",
             Qubit(ctl0),
             Qubit(ctl1),
             Qubit(q)
@@ -246,9 +728,10 @@ impl Backend for BaseProfSim {
     fn cx(&mut self, ctl: usize, q: usize) {
         let ctl = self.map(ctl);
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__cx__body({}, {})",
+            "  call void @__quantum__qis__cx__body({}, {}){dbg}",
             Qubit(ctl),
             Qubit(q),
         )
@@ -258,9 +741,10 @@ impl Backend for BaseProfSim {
     fn cy(&mut self, ctl: usize, q: usize) {
         let ctl = self.map(ctl);
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__cy__body({}, {})",
+            "  call void @__quantum__qis__cy__body({}, {}){dbg}",
             Qubit(ctl),
             Qubit(q),
         )
@@ -270,9 +754,10 @@ impl Backend for BaseProfSim {
     fn cz(&mut self, ctl: usize, q: usize) {
         let ctl = self.map(ctl);
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__cz__body({}, {})",
+            "  call void @__quantum__qis__cz__body({}, {}){dbg}",
             Qubit(ctl),
             Qubit(q),
         )
@@ -281,9 +766,10 @@ impl Backend for BaseProfSim {
 
     fn h(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__h__body({})",
+            "  call void @__quantum__qis__h__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -293,14 +779,22 @@ impl Backend for BaseProfSim {
         let mapped_q = self.map(q);
         let id = self.get_meas_id();
         // Measurements are tracked separately from instructions, so that they can be
-        // deferred until the end of the program.
-        writeln!(
-            self.measurements,
-            "  call void @__quantum__qis__mz__body({}, {}) #1",
-            Qubit(mapped_q),
-            Result(id),
+        // deferred until the end of the program, except inside branches on results where
+        // the qubit is reset in place afterwards.
+        let dbg = self.dbg();
+        let buffer = if self.open_branches.is_empty() {
+            &mut self.measurements
+        } else {
+            &mut self.instrs
+        };
+        write!(
+            buffer,
+            "  call void @__quantum__qis__mz__body({}, ",
+            Qubit(mapped_q)
         )
         .expect("writing to string should succeed");
+        buffer.result(id);
+        writeln!(buffer, ") #1{dbg}").expect("writing to string should succeed");
         self.reset(q);
         id
     }
@@ -310,6 +804,23 @@ impl Backend for BaseProfSim {
     }
 
     fn reset(&mut self, q: usize) {
+        if !self.open_branches.is_empty() {
+            // A fresh qubit would only replace the qubit on one side of the branch, so the qubit
+            // is reset in place instead.
+            let q = self.map(q);
+            let dbg = self.dbg();
+            self.write_raw_decl(
+                "__quantum__qis__reset__body",
+                "declare void @__quantum__qis__reset__body(%Qubit*) #1",
+            );
+            writeln!(
+                self.instrs,
+                "  call void @__quantum__qis__reset__body({}){dbg}",
+                Qubit(q),
+            )
+            .expect("writing to string should succeed");
+            return;
+        }
         // Reset is a no-op in Base Profile, but does force qubit remapping so that future
         // operations on the given qubit id are performed on a fresh qubit. Clear the entry in the map
         // so it is known to require remapping on next use.
@@ -318,9 +829,10 @@ impl Backend for BaseProfSim {
 
     fn rx(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__rx__body({}, {})",
+            "  call void @__quantum__qis__rx__body({}, {}){dbg}",
             Double(theta),
             Qubit(q),
         )
@@ -330,9 +842,10 @@ impl Backend for BaseProfSim {
     fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__rxx__body({}, {}, {})",
+            "  call void @__quantum__qis__rxx__body({}, {}, {}){dbg}",
             Double(theta),
             Qubit(q0),
             Qubit(q1),
@@ -342,9 +855,10 @@ impl Backend for BaseProfSim {
 
     fn ry(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__ry__body({}, {})",
+            "  call void @__quantum__qis__ry__body({}, {}){dbg}",
             Double(theta),
             Qubit(q),
         )
@@ -354,9 +868,10 @@ impl Backend for BaseProfSim {
     fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__ryy__body({}, {}, {})",
+            "  call void @__quantum__qis__ryy__body({}, {}, {}){dbg}",
             Double(theta),
             Qubit(q0),
             Qubit(q1),
@@ -366,9 +881,10 @@ impl Backend for BaseProfSim {
 
     fn rz(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__rz__body({}, {})",
+            "  call void @__quantum__qis__rz__body({}, {}){dbg}",
             Double(theta),
             Qubit(q),
         )
@@ -378,9 +894,10 @@ impl Backend for BaseProfSim {
     fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__rzz__body({}, {}, {})",
+            "  call void @__quantum__qis__rzz__body({}, {}, {}){dbg}",
             Double(theta),
             Qubit(q0),
             Qubit(q1),
@@ -390,9 +907,10 @@ impl Backend for BaseProfSim {
 
     fn sadj(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__s__adj({})",
+            "  call void @__quantum__qis__s__adj({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -400,9 +918,10 @@ impl Backend for BaseProfSim {
 
     fn s(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__s__body({})",
+            "  call void @__quantum__qis__s__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -411,9 +930,10 @@ impl Backend for BaseProfSim {
     fn swap(&mut self, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__swap__body({}, {})",
+            "  call void @__quantum__qis__swap__body({}, {}){dbg}",
             Qubit(q0),
             Qubit(q1),
         )
@@ -422,9 +942,10 @@ impl Backend for BaseProfSim {
 
     fn tadj(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__t__adj({})",
+            "  call void @__quantum__qis__t__adj({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -432,9 +953,10 @@ impl Backend for BaseProfSim {
 
     fn t(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__t__body({})",
+            "  call void @__quantum__qis__t__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -442,9 +964,10 @@ impl Backend for BaseProfSim {
 
     fn x(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__x__body({})",
+            "  call void @__quantum__qis__x__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -452,9 +975,10 @@ impl Backend for BaseProfSim {
 
     fn y(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__y__body({})",
+            "  call void @__quantum__qis__y__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -462,9 +986,10 @@ impl Backend for BaseProfSim {
 
     fn z(&mut self, q: usize) {
         let q = self.map(q);
+        let dbg = self.dbg();
         writeln!(
             self.instrs,
-            "  call void @__quantum__qis__z__body({})",
+            "  call void @__quantum__qis__z__body({}){dbg}",
             Qubit(q),
         )
         .expect("writing to string should succeed");
@@ -496,6 +1021,10 @@ impl Backend for BaseProfSim {
         name: &str,
         arg: Value,
     ) -> Option<std::result::Result<Value, String>> {
+        if name == "ResultArrayAsInt" {
+            return Some(self.trace_result_array_as_int(&arg));
+        }
+
         match self.write_decl(name, &arg) {
             Ok(()) => {}
             Err(e) => return Some(Err(e)),
@@ -523,24 +1052,124 @@ impl Backend for BaseProfSim {
             }
         }
 
-        writeln!(self.instrs, ")").expect("writing to string should succeed");
+        let dbg = self.dbg();
+        writeln!(self.instrs, "){dbg}").expect("writing to string should succeed");
         Some(Ok(Value::unit()))
     }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.set_call_stack(frames);
+        }
+        if self.max_qubits.is_some() && self.budget_exceeded_at.is_none() {
+            self.call_stack = frames.to_vec();
+        }
+    }
+
+    fn result_branches(&self) -> bool {
+        self.result_branches
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        let id = self.next_branch_id;
+        self.next_branch_id += 1;
+        self.open_branches.push(id);
+        self.flush_measurements();
+
+        // The branch is taken when the condition is true, or when it is false for a comparison
+        // with a result whose value is known to differ.
+        let taken = match (lhs, rhs) {
+            (val::Result::Id(lhs), val::Result::Id(rhs)) => {
+                self.write_read_result(&format!("cond{id}_lhs"), lhs);
+                self.write_read_result(&format!("cond{id}_rhs"), rhs);
+                let op = if eq { "eq" } else { "ne" };
+                writeln!(
+                    self.instrs,
+                    "  %cond{id} = icmp {op} i1 %cond{id}_lhs, %cond{id}_rhs"
+                )
+                .expect("writing to string should succeed");
+                true
+            }
+            (val::Result::Id(res), val::Result::Val(val))
+            | (val::Result::Val(val), val::Result::Id(res)) => {
+                self.write_read_result(&format!("cond{id}"), res);
+                val == eq
+            }
+            (val::Result::Val(_), val::Result::Val(_)) => {
+                unreachable!("results with known values should not be branched on")
+            }
+        };
+        let (when_true, when_false) = if taken {
+            ("then", "else")
+        } else {
+            ("else", "then")
+        };
+        writeln!(
+            self.instrs,
+            "  br i1 %cond{id}, label %{when_true}{id}, label %{when_false}{id}\nthen{id}:"
+        )
+        .expect("writing to string should succeed");
+    }
+
+    fn else_result_branch(&mut self) {
+        let id = *self
+            .open_branches
+            .last()
+            .expect("result branch should have begun");
+        writeln!(self.instrs, "  br label %continue{id}\nelse{id}:")
+            .expect("writing to string should succeed");
+    }
+
+    fn end_result_branch(&mut self) {
+        let id = self
+            .open_branches
+            .pop()
+            .expect("result branch should have begun");
+        writeln!(self.instrs, "  br label %continue{id}\ncontinue{id}:")
+            .expect("writing to string should succeed");
+    }
 }
 
-struct Qubit(HardwareId);
+fn only_results(val: &Value) -> bool {
+    match val {
+        Value::Array(items) => items.iter().all(only_results),
+        Value::Tuple(items) => items.iter().all(only_results),
+        Value::Result(_) => true,
+        _ => false,
+    }
+}
 
-impl Display for Qubit {
+/// The results in the value, in the order they appear.
+fn collect_results(val: &Value) -> Vec<usize> {
+    match val {
+        Value::Array(items) => items.iter().flat_map(collect_results).collect(),
+        Value::Tuple(items) => items.iter().flat_map(collect_results).collect(),
+        Value::Result(r) => vec![r.unwrap_id()],
+        _ => panic!("unexpected value type: {val:?}"),
+    }
+}
+
+/// The label passed to an output recording call, given as the index and length of the global
+/// string that holds it.
+struct Label(Option<(usize, usize)>);
+
+impl Display for Label {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "%Qubit* inttoptr (i64 {} to %Qubit*)", self.0 .0)
+        match self.0 {
+            Some((index, len)) => write!(
+                f,
+                "i8* getelementptr inbounds ([{len} x i8], [{len} x i8]* @{index}, i64 0, i64 0)"
+            ),
+            None => write!(f, "i8* null"),
+        }
     }
 }
 
-struct Result(usize);
+struct Qubit(HardwareId);
 
-impl Display for Result {
+impl Display for Qubit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "%Result* inttoptr (i64 {} to %Result*)", self.0)
+        write!(f, "%Qubit* inttoptr (i64 {} to %Qubit*)", self.0 .0)
     }
 }
 
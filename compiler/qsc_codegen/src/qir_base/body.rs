@@ -0,0 +1,65 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::fmt::{self, Write};
+
+/// The instructions of the entry point. The results that instructions refer to are kept apart
+/// from the text around them, so that the register of each result can be chosen once the whole
+/// program is known.
+#[derive(Default)]
+pub(super) struct Body {
+    parts: Vec<Part>,
+}
+
+enum Part {
+    Text(String),
+    /// A `%Result*` operand for the result with the ID.
+    Result(usize),
+}
+
+impl Body {
+    /// Appends a `%Result*` operand for the result with the ID.
+    pub(super) fn result(&mut self, id: usize) {
+        self.parts.push(Part::Result(id));
+    }
+
+    /// Moves the instructions of `other` to the end of these instructions.
+    pub(super) fn append(&mut self, other: &mut Self) {
+        self.parts.append(&mut other.parts);
+    }
+
+    /// The IDs of the results that the instructions refer to, in order.
+    pub(super) fn results(&self) -> impl Iterator<Item = usize> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Text(_) => None,
+            Part::Result(id) => Some(*id),
+        })
+    }
+
+    /// Writes the instructions, with each result in the register given for its ID.
+    pub(super) fn render(&self, register: impl Fn(usize) -> usize) -> String {
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(part) => text.push_str(part),
+                Part::Result(id) => write!(
+                    text,
+                    "%Result* inttoptr (i64 {} to %Result*)",
+                    register(*id)
+                )
+                .expect("writing to string should succeed"),
+            }
+        }
+        text
+    }
+}
+
+impl Write for Body {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.parts.last_mut() {
+            Some(Part::Text(text)) => text.push_str(s),
+            _ => self.parts.push(Part::Text(s.to_string())),
+        }
+        Ok(())
+    }
+}
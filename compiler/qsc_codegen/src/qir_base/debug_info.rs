@@ -0,0 +1,279 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use qsc_data_structures::{
+    line_column::{Encoding, Position},
+    span::Span,
+};
+use qsc_eval::debug::{map_fir_package_to_hir, Frame};
+use qsc_fir::fir::{LocalItemId, PackageId, StoreItemId};
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::hir::{self, ItemKind};
+use rustc_hash::FxHashMap;
+use std::fmt::{Display, Write};
+
+/// The metadata IDs `!0` through `!3` are taken by the QIR module flags.
+const DEBUG_INFO_VERSION_ID: usize = 4;
+const DWARF_VERSION_ID: usize = 5;
+const FIRST_NODE_ID: usize = 6;
+
+/// Collects the call stacks that instructions were generated from, so that each instruction can
+/// be given a `!dbg` location that is resolved to Q# source when the program is finished.
+pub(super) struct DebugInfo {
+    next_id: usize,
+    call_stack: Vec<Frame>,
+    locations: Vec<Location>,
+    location_indices: FxHashMap<(Option<usize>, FrameKey), usize>,
+}
+
+/// A call site, inlined at the call site of its parent. The outermost call sites have no parent
+/// and are scoped to the entry point.
+struct Location {
+    id: usize,
+    frame: Frame,
+    parent: Option<usize>,
+}
+
+type FrameKey = (PackageId, Span, PackageId, LocalItemId);
+
+fn frame_key(frame: &Frame) -> FrameKey {
+    (frame.caller, frame.span, frame.id.package, frame.id.item)
+}
+
+/// A `!dbg` attachment, which is empty for instructions that have no location.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Dbg(pub(super) Option<usize>);
+
+impl Display for Dbg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(id) => write!(f, ", !dbg !{id}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The resolved debug metadata to splice into the generated module.
+#[derive(Default)]
+pub(super) struct Metadata {
+    /// The attachment for the entry point function.
+    pub(super) entry: Option<usize>,
+    /// Extra entries for the module flags list.
+    pub(super) flags: String,
+    /// The nodes of the extra module flags, which follow those of the QIR module flags.
+    pub(super) flag_nodes: String,
+    /// The debug info section, with its numbered nodes in order.
+    pub(super) nodes: String,
+}
+
+impl Metadata {
+    /// The attachment for the entry point function, which unlike those of instructions isn't
+    /// separated from the function attributes by a comma.
+    pub(super) fn entry_attachment(&self) -> String {
+        self.entry
+            .map(|id| format!(" !dbg !{id}"))
+            .unwrap_or_default()
+    }
+}
+
+impl DebugInfo {
+    pub(super) fn new() -> Self {
+        Self {
+            next_id: FIRST_NODE_ID,
+            call_stack: Vec::new(),
+            locations: Vec::new(),
+            location_indices: FxHashMap::default(),
+        }
+    }
+
+    pub(super) fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.call_stack.clear();
+        self.call_stack.extend_from_slice(frames);
+    }
+
+    /// The location of the intrinsic call that is currently being generated.
+    pub(super) fn current_location(&mut self) -> Dbg {
+        let mut parent = None;
+        for frame in &self.call_stack {
+            let index = *self
+                .location_indices
+                .entry((parent, frame_key(frame)))
+                .or_insert_with(|| {
+                    self.locations.push(Location {
+                        id: self.next_id,
+                        frame: *frame,
+                        parent,
+                    });
+                    self.next_id += 1;
+                    self.locations.len() - 1
+                });
+            parent = Some(index);
+        }
+        Dbg(parent.map(|index| self.locations[index].id))
+    }
+
+    /// Resolves the collected locations against the sources in the store. The metadata is empty if
+    /// no instruction was given a location.
+    pub(super) fn resolve(self, store: &PackageStore) -> Metadata {
+        let Some(root) = self.locations.iter().find(|l| l.parent.is_none()) else {
+            return Metadata::default();
+        };
+
+        let mut nodes = Nodes {
+            store,
+            next_id: self.next_id,
+            text: String::new(),
+            files: FxHashMap::default(),
+            subprograms: FxHashMap::default(),
+        };
+        let (file, line, _) = nodes.position(root.frame.caller, root.frame.span);
+        let file = nodes.file(&file);
+        let cu = nodes.add(format!(
+            "distinct !DICompileUnit(language: DW_LANG_C, file: !{file}, producer: \"qsc\", isOptimized: false, runtimeVersion: 0, emissionKind: LineTablesOnly)"
+        ));
+        let ty = nodes.add("!DISubroutineType(types: !{})".to_string());
+        let entry = nodes.add(format!(
+            "distinct !DISubprogram(name: \"ENTRYPOINT__main\", scope: !{file}, file: !{file}, line: {line}, type: !{ty}, scopeLine: {line}, spFlags: DISPFlagDefinition, unit: !{cu})"
+        ));
+
+        let mut lines = Vec::with_capacity(self.locations.len());
+        for location in &self.locations {
+            let scope = match location.parent {
+                Some(parent) => nodes.subprogram(self.locations[parent].frame.id, file, ty, cu),
+                None => entry,
+            };
+            let (_, line, column) = nodes.position(location.frame.caller, location.frame.span);
+            let inlined_at = location
+                .parent
+                .map(|parent| format!(", inlinedAt: !{}", self.locations[parent].id))
+                .unwrap_or_default();
+            lines.push(format!(
+                "!{} = !DILocation(line: {line}, column: {column}, scope: !{scope}{inlined_at})",
+                location.id
+            ));
+        }
+
+        // Locations are numbered as they are found while tracing, before the nodes that they
+        // refer to are resolved.
+        let mut text = format!("\n; debug info\n\n!llvm.dbg.cu = !{{!{cu}}}\n\n");
+        for line in lines {
+            writeln!(text, "{line}").expect("writing to string should succeed");
+        }
+        text.push_str(&nodes.text);
+
+        Metadata {
+            entry: Some(entry),
+            flags: format!(", !{DEBUG_INFO_VERSION_ID}, !{DWARF_VERSION_ID}"),
+            flag_nodes: format!(
+                "!{DEBUG_INFO_VERSION_ID} = !{{i32 2, !\"Debug Info Version\", i32 3}}\n!{DWARF_VERSION_ID} = !{{i32 7, !\"Dwarf Version\", i32 4}}\n"
+            ),
+            nodes: text,
+        }
+    }
+}
+
+/// Numbered metadata nodes, with files and subprograms deduplicated.
+struct Nodes<'a> {
+    store: &'a PackageStore,
+    next_id: usize,
+    text: String,
+    files: FxHashMap<String, usize>,
+    subprograms: FxHashMap<(PackageId, LocalItemId), usize>,
+}
+
+impl Nodes<'_> {
+    fn add(&mut self, node: String) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        writeln!(self.text, "!{id} = {node}").expect("writing to string should succeed");
+        id
+    }
+
+    fn file(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.files.get(name) {
+            return id;
+        }
+        let id = self.add(format!(
+            "!DIFile(filename: \"{}\", directory: \"\")",
+            escape(name)
+        ));
+        self.files.insert(name.to_string(), id);
+        id
+    }
+
+    fn subprogram(
+        &mut self,
+        item: StoreItemId,
+        fallback_file: usize,
+        ty: usize,
+        cu: usize,
+    ) -> usize {
+        let key = (item.package, item.item);
+        if let Some(&id) = self.subprograms.get(&key) {
+            return id;
+        }
+
+        let unit = self
+            .store
+            .get(map_fir_package_to_hir(item.package))
+            .expect("package should be in store");
+        let decl = unit
+            .package
+            .items
+            .get(hir::LocalItemId::from(usize::from(item.item)))
+            .expect("item should be in package");
+        let name = match &decl.kind {
+            ItemKind::Callable(callable) => callable.name.name.to_string(),
+            _ => String::new(),
+        };
+        let (file, line) = match unit.sources.find_by_offset(decl.span.lo) {
+            Some(source) => {
+                let pos = Position::from_utf8_byte_offset(
+                    Encoding::Utf8,
+                    &source.contents,
+                    decl.span.lo - source.offset,
+                );
+                (self.file(&source.name), pos.line + 1)
+            }
+            None => (fallback_file, 0),
+        };
+        let id = self.add(format!(
+            "distinct !DISubprogram(name: \"{}\", scope: !{file}, file: !{file}, line: {line}, type: !{ty}, scopeLine: {line}, spFlags: DISPFlagDefinition, unit: !{cu})",
+            escape(&name)
+        ));
+        self.subprograms.insert(key, id);
+        id
+    }
+
+    /// The file name and the one-based line and column of the span in the given package.
+    fn position(&self, package: PackageId, span: Span) -> (String, u32, u32) {
+        let unit = self
+            .store
+            .get(map_fir_package_to_hir(package))
+            .expect("package should be in store");
+        match unit.sources.find_by_offset(span.lo) {
+            Some(source) => {
+                let pos = Position::from_utf8_byte_offset(
+                    Encoding::Utf8,
+                    &source.contents,
+                    span.lo - source.offset,
+                );
+                (source.name.to_string(), pos.line + 1, pos.column + 1)
+            }
+            None => (String::new(), 0, 0),
+        }
+    }
+}
+
+/// Escapes a string for use in an LLVM metadata string literal.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte == b'"' || byte == b'\\' || !(0x20..0x7f).contains(&byte) {
+            write!(escaped, "\\{byte:02X}").expect("writing to string should succeed");
+        } else {
+            escaped.push(char::from(byte));
+        }
+    }
+    escaped
+}
@@ -25,14 +25,15 @@ declare void @__quantum__rt__result_record_output(%Result*, i8*)
 declare void @__quantum__rt__array_record_output(i64, i8*)
 declare void @__quantum__rt__tuple_record_output(i64, i8*)
 {}
-attributes #0 = {{ "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="{}" "required_num_results"="{}" }}
+attributes #0 = {{ "entry_point" "output_labeling_schema" "qir_profiles"="{}" "required_num_qubits"="{}" "required_num_results"="{}" }}
 attributes #1 = {{ "irreversible" }}
 
 ; module flags
 
-!llvm.module.flags = !{{!0, !1, !2, !3}}
+!llvm.module.flags = !{{!0, !1, !2, !3{}}}
 
 !0 = !{{i32 1, !"qir_major_version", i32 1}}
 !1 = !{{i32 7, !"qir_minor_version", i32 0}}
 !2 = !{{i32 1, !"dynamic_qubit_management", i1 false}}
 !3 = !{{i32 1, !"dynamic_result_management", i1 false}}
+{}{}
\ No newline at end of file
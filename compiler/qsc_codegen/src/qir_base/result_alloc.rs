@@ -0,0 +1,84 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Body;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// The references to a result that it is live across, from the first reference to the result in
+/// the instructions up to and including the last, numbered by their order among the references
+/// to all results.
+#[derive(Clone, Copy)]
+struct Lifetime {
+    first: usize,
+    last: usize,
+}
+
+/// Chooses the register of each result of the body so that results share a register once the
+/// earlier result is dead, returning the register of each result by its ID and the number of
+/// registers used. A result is dead after the last instruction that refers to it, so a result
+/// that is measured but never read back or recorded only occupies its register for the
+/// measurement itself. Results that no instruction refers to are given register 0.
+pub(super) fn reuse_result_registers(body: &Body, num_results: usize) -> (Vec<usize>, usize) {
+    let mut lifetimes: Vec<Option<Lifetime>> = vec![None; num_results];
+    for (reference, id) in body.results().enumerate() {
+        let lifetime = lifetimes[id].get_or_insert(Lifetime {
+            first: reference,
+            last: reference,
+        });
+        lifetime.last = reference;
+    }
+
+    let registers = allocate(&lifetimes);
+    let num_registers = registers
+        .iter()
+        .flatten()
+        .map(|register| register + 1)
+        .max()
+        .unwrap_or_default();
+    let registers = registers
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect();
+    (registers, num_registers)
+}
+
+/// Assigns each result the lowest numbered register that is free for its whole lifetime, in the
+/// order the results become live.
+fn allocate(lifetimes: &[Option<Lifetime>]) -> Vec<Option<usize>> {
+    let mut order = lifetimes
+        .iter()
+        .enumerate()
+        .filter_map(|(id, lifetime)| lifetime.map(|lifetime| (lifetime.first, id)))
+        .collect::<Vec<_>>();
+    order.sort_unstable();
+
+    let mut registers = vec![None; lifetimes.len()];
+    let mut free = BinaryHeap::new();
+    let mut active: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+    let mut next_register = 0;
+    for (first, id) in order {
+        while let Some(&Reverse((last, register))) = active.peek() {
+            if last >= first {
+                break;
+            }
+            active.pop();
+            free.push(Reverse(register));
+        }
+
+        let register = free.pop().map_or_else(
+            || {
+                next_register += 1;
+                next_register - 1
+            },
+            |Reverse(register)| register,
+        );
+        let lifetime = lifetimes[id].expect("ordered result should have a lifetime");
+        active.push(Reverse((lifetime.last, register)));
+        registers[id] = Some(register);
+    }
+
+    registers
+}
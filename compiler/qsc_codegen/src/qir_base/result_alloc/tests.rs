@@ -0,0 +1,58 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{reuse_result_registers, Body};
+use expect_test::expect;
+use std::fmt::Write;
+
+/// A body that measures each qubit into the result with the same ID, then records the results.
+fn measure_and_record(qubits: usize, recorded: &[usize]) -> Body {
+    let mut body = Body::default();
+    for q in 0..qubits {
+        write!(
+            body,
+            "call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 {q} to %Qubit*), "
+        )
+        .expect("writing to body should succeed");
+        body.result(q);
+        writeln!(body, ") #1").expect("writing to body should succeed");
+    }
+    for &res in recorded {
+        write!(body, "call void @__quantum__rt__result_record_output(")
+            .expect("writing to body should succeed");
+        body.result(res);
+        writeln!(body, ", i8* null)").expect("writing to body should succeed");
+    }
+    body
+}
+
+#[test]
+fn dead_results_share_a_register() {
+    let body = measure_and_record(3, &[2]);
+    let (registers, num_registers) = reuse_result_registers(&body, 3);
+    assert_eq!(num_registers, 1);
+    expect![[r#"
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+        call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+    "#]]
+    .assert_eq(&body.render(|id| registers[id]));
+}
+
+#[test]
+fn live_results_keep_distinct_registers() {
+    let body = measure_and_record(3, &[0, 2]);
+    let (registers, num_registers) = reuse_result_registers(&body, 3);
+    assert_eq!(num_registers, 2);
+    expect![[r#"
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+        call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+        call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+        call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+    "#]]
+    .assert_eq(&body.render(|id| registers[id]));
+}
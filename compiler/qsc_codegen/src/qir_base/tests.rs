@@ -8,44 +8,64 @@ use std::sync::Arc;
 
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
-use qsc_passes::{run_core_passes, run_default_passes, PackageType};
-
-use crate::qir_base::generate_qir;
+use qsc_eval::{val::Value, Error};
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::hir::PackageId;
+use qsc_passes::{
+    result_literal_callables, run_core_passes, run_default_passes, PackageType, PassContext,
+};
+
+use crate::{
+    qir_base::{
+        generate_qir, generate_qir_with_options, AdaptiveProfile, BaseProfSim, CodegenOptions,
+        FinishError, OutputFormat,
+    },
+    target::TargetBackend,
+};
 
 fn check(program: &str, expr: Option<&str>, expect: &Expect) {
+    let (store, package) = build(program, expr);
+    let qir = generate_qir(&store, package);
+    match qir {
+        Ok(qir) => expect.assert_eq(&qir),
+        Err((err, _)) => expect.assert_debug_eq(&err),
+    }
+}
+
+fn build(program: &str, expr: Option<&str>) -> (PackageStore, PackageId) {
+    build_with_capabilities(program, expr, RuntimeCapabilityFlags::empty())
+}
+
+fn build_with_capabilities(
+    program: &str,
+    expr: Option<&str>,
+    capabilities: RuntimeCapabilityFlags,
+) -> (PackageStore, PackageId) {
     let mut core = compile::core();
     assert!(run_core_passes(&mut core).is_empty());
     let mut store = PackageStore::new(core);
-    let mut std = compile::std(&store, RuntimeCapabilityFlags::empty());
-    assert!(run_default_passes(
-        store.core(),
-        &mut std,
-        PackageType::Lib,
-        RuntimeCapabilityFlags::empty()
-    )
-    .is_empty());
+    let mut std = compile::std(&store, capabilities);
+    assert!(run_default_passes(store.core(), &mut std, PackageType::Lib, capabilities).is_empty());
     let std = store.insert(std);
 
     let expr_as_arc: Option<Arc<str>> = expr.map(|s| Arc::from(s.to_string()));
     let sources = SourceMap::new([("test".into(), program.into())], expr_as_arc);
 
-    let mut unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::empty());
+    let mut unit = compile(&store, &[std], sources, capabilities);
     assert!(unit.errors.is_empty(), "{:?}", unit.errors);
-    assert!(run_default_passes(
-        store.core(),
-        &mut unit,
-        PackageType::Exe,
-        RuntimeCapabilityFlags::empty()
-    )
-    .is_empty());
+    let pass_errors = PassContext::new(capabilities)
+        .with_result_literal_callables(result_literal_callables(&store))
+        .run_default_passes(
+            &mut unit.package,
+            &mut unit.assigner,
+            store.core(),
+            PackageType::Exe,
+        );
+    assert!(pass_errors.is_empty(), "{pass_errors:?}");
     let package = store.insert(unit);
-
-    let qir = generate_qir(&store, package);
-    match qir {
-        Ok(qir) => expect.assert_eq(&qir),
-        Err((err, _)) => expect.assert_debug_eq(&err),
-    }
+    (store, package)
 }
 
 #[test]
@@ -489,6 +509,30 @@ fn qubit_allocation_allows_reuse_of_unmeasured_qubits() {
     );
 }
 
+#[test]
+fn assert_measurement_probability_is_skipped() {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Diagnostics;
+
+        @EntryPoint()
+        operation Entry() : Result
+        {
+            use q = Qubit();
+            H(q);
+            AssertMeasurementProbability([PauliZ], [q], One, 0.5, 1e-5, "should be balanced");
+            M(q)
+        }
+    }
+        "#},
+        None,
+    );
+    let qir = generate_qir(&store, package).expect("QIR should be generated");
+    assert!(!qir.contains("AssertMeasurementProbability"), "{qir}");
+    assert!(qir.contains("@__quantum__qis__h__body"), "{qir}");
+}
+
 #[test]
 fn verify_all_intrinsics() {
     check(
@@ -1599,3 +1643,474 @@ fn custom_intrinsic_fail_on_non_unit_return() {
         "#]],
     );
 }
+
+#[test]
+fn debug_info_locates_instructions_in_source() {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        @EntryPoint()
+        operation Entry() : Result {
+            use q = Qubit();
+            H(q);
+            M(q)
+        }
+    }
+    "#},
+        None,
+    );
+
+    let options = CodegenOptions {
+        debug_info: true,
+        ..CodegenOptions::default()
+    };
+    let qir = generate_qir_with_options(&store, package, options)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    assert!(qir.contains("define void @ENTRYPOINT__main() #0 !dbg !"));
+    let h = qir
+        .lines()
+        .find(|line| line.contains("@__quantum__qis__h__body(%Qubit*"))
+        .expect("program should apply H");
+    assert!(h.contains(", !dbg !"), "{h}");
+    let mz = qir
+        .lines()
+        .find(|line| line.contains("@__quantum__qis__mz__body(%Qubit*"))
+        .expect("program should measure");
+    assert!(mz.contains(" #1, !dbg !"), "{mz}");
+    assert!(qir.contains("!DILocation(line: 5,"), "{qir}");
+    assert!(qir.contains("!DILocation(line: 6,"), "{qir}");
+    assert!(qir.contains("!DIFile(filename: \"test\", directory: \"\")"));
+    assert!(qir.contains("distinct !DISubprogram(name: \"Entry\""));
+    assert!(qir.contains("distinct !DISubprogram(name: \"H\""));
+    assert!(qir.contains("!llvm.module.flags = !{!0, !1, !2, !3, !4, !5}"));
+    assert!(qir.contains(
+        "!3 = !{i32 1, !\"dynamic_result_management\", i1 false}\n!4 = !{i32 2, !\"Debug Info Version\", i32 3}"
+    ));
+}
+
+#[test]
+fn finishing_without_resolving_debug_info_fails() {
+    let sim = BaseProfSim::with_options(CodegenOptions {
+        debug_info: true,
+        ..CodegenOptions::default()
+    });
+    assert!(matches!(
+        sim.finish(&Value::unit()),
+        Err(FinishError::UnresolvedDebugInfo)
+    ));
+}
+
+#[test]
+fn debug_info_is_omitted_by_default() {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        @EntryPoint()
+        operation Entry() : Result {
+            use q = Qubit();
+            H(q);
+            M(q)
+        }
+    }
+    "#},
+        None,
+    );
+
+    let qir = generate_qir(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    assert!(!qir.contains("!dbg"));
+    assert!(!qir.contains("llvm.dbg.cu"));
+}
+
+fn check_output_format(output_format: OutputFormat, expect: &Expect) {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        @EntryPoint()
+        operation Entry() : (Result, Result[]) {
+            use q = Qubit();
+            use qs = Qubit[2];
+            (M(q), [M(qs[0]), M(qs[1])])
+        }
+    }
+    "#},
+        None,
+    );
+
+    let options = CodegenOptions {
+        output_format,
+        ..CodegenOptions::default()
+    };
+    let qir = generate_qir_with_options(&store, package, options)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    expect.assert_eq(&output_lines(&qir));
+}
+
+/// The lines of the QIR that compute and record the output.
+fn output_lines(qir: &str) -> String {
+    qir.lines()
+        .filter(|line| {
+            line.starts_with('@')
+                || line.starts_with("  %")
+                || (line.starts_with("  call") && line.contains("record_output("))
+                || line.contains("qir_profiles")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn output_format_tree() {
+    check_output_format(
+        OutputFormat::Tree,
+        &expect![[r#"
+            call void @__quantum__rt__tuple_record_output(i64 2, i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+            call void @__quantum__rt__array_record_output(i64 2, i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+          attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="6" "required_num_results"="3" }"#]],
+    );
+}
+
+#[test]
+fn output_format_labeled_tree() {
+    check_output_format(
+        OutputFormat::LabeledTree,
+        &expect![[r#"
+            @0 = internal constant [4 x i8] c"0_t\00"
+            @1 = internal constant [6 x i8] c"1_t0r\00"
+            @2 = internal constant [6 x i8] c"2_t1a\00"
+            @3 = internal constant [8 x i8] c"3_t1a0r\00"
+            @4 = internal constant [8 x i8] c"4_t1a1r\00"
+              call void @__quantum__rt__tuple_record_output(i64 2, i8* getelementptr inbounds ([4 x i8], [4 x i8]* @0, i64 0, i64 0))
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* getelementptr inbounds ([6 x i8], [6 x i8]* @1, i64 0, i64 0))
+              call void @__quantum__rt__array_record_output(i64 2, i8* getelementptr inbounds ([6 x i8], [6 x i8]* @2, i64 0, i64 0))
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* getelementptr inbounds ([8 x i8], [8 x i8]* @3, i64 0, i64 0))
+              call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* getelementptr inbounds ([8 x i8], [8 x i8]* @4, i64 0, i64 0))
+            attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="6" "required_num_results"="3" }"#]],
+    );
+}
+
+#[test]
+fn output_format_flat_array() {
+    check_output_format(
+        OutputFormat::FlatArray,
+        &expect![[r#"
+            call void @__quantum__rt__array_record_output(i64 3, i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+            call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+          attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="6" "required_num_results"="3" }"#]],
+    );
+}
+
+#[test]
+fn output_format_packed_int() {
+    check_output_format(
+        OutputFormat::PackedInt,
+        &expect![[r#"
+            %bit0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+            %ext0 = zext i1 %bit0 to i64
+            %shl0 = shl i64 %ext0, 0
+            %packed0 = or i64 0, %shl0
+            %bit1 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+            %ext1 = zext i1 %bit1 to i64
+            %shl1 = shl i64 %ext1, 1
+            %packed1 = or i64 %packed0, %shl1
+            %bit2 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 2 to %Result*))
+            %ext2 = zext i1 %bit2 to i64
+            %shl2 = shl i64 %ext2, 2
+            %packed2 = or i64 %packed1, %shl2
+            call void @__quantum__rt__int_record_output(i64 %packed2, i8* null)
+          attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="6" "required_num_results"="3" }"#]],
+    );
+}
+
+#[test]
+fn output_format_parses_from_name() {
+    assert_eq!("tree".parse(), Ok(OutputFormat::Tree));
+    assert_eq!("labeled".parse(), Ok(OutputFormat::LabeledTree));
+    assert_eq!("flat".parse(), Ok(OutputFormat::FlatArray));
+    assert_eq!("packed".parse(), Ok(OutputFormat::PackedInt));
+    assert!("bits".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn int_output_computed_from_results_on_adaptive_target() {
+    let (store, package) = build_with_capabilities(
+        indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Convert;
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Entry() : Int {
+            use qs = Qubit[2];
+            X(qs[0]);
+            ResultArrayAsInt([MResetZ(qs[0]), MResetZ(qs[1])])
+        }
+    }
+    "#},
+        None,
+        ConfigAttr::Adaptive.into(),
+    );
+
+    let qir = generate_qir(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    expect![[r#"
+          %int0_bit0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          %int0_ext0 = zext i1 %int0_bit0 to i64
+          %int0_shl0 = shl i64 %int0_ext0, 0
+          %int0_packed0 = or i64 0, %int0_shl0
+          %int0_bit1 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+          %int0_ext1 = zext i1 %int0_bit1 to i64
+          %int0_shl1 = shl i64 %int0_ext1, 1
+          %int0_packed1 = or i64 %int0_packed0, %int0_shl1
+          call void @__quantum__rt__int_record_output(i64 %int0_packed1, i8* null)
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="2" "required_num_results"="2" }"#]]
+    .assert_eq(&output_lines(&qir));
+    assert!(qir.contains("declare i1 @__quantum__qis__read_result__body(%Result*)"));
+    assert!(qir.contains("declare void @__quantum__rt__int_record_output(i64, i8*)"));
+}
+
+#[test]
+fn int_output_changed_after_computation_is_rejected() {
+    let (store, package) = build_with_capabilities(
+        indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Convert;
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Entry() : Int {
+            use qs = Qubit[2];
+            ResultArrayAsInt([MResetZ(qs[0]), MResetZ(qs[1])]) + 1
+        }
+    }
+    "#},
+        None,
+        ConfigAttr::Adaptive.into(),
+    );
+
+    match generate_qir(&store, package) {
+        Err((Error::UnrecordableOutput(format, _), _)) => assert_eq!(format, "tree"),
+        Err((e, _)) => panic!("expected unrecordable output, got {e}"),
+        Ok(qir) => panic!("expected unrecordable output, got {qir}"),
+    }
+}
+
+#[test]
+fn int_output_in_flat_format_is_rejected() {
+    let (store, package) = build_with_capabilities(
+        indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Convert;
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Entry() : Int {
+            use qs = Qubit[2];
+            ResultArrayAsInt([MResetZ(qs[0]), MResetZ(qs[1])])
+        }
+    }
+    "#},
+        None,
+        ConfigAttr::Adaptive.into(),
+    );
+
+    let options = CodegenOptions {
+        output_format: OutputFormat::FlatArray,
+        ..CodegenOptions::default()
+    };
+    match generate_qir_with_options(&store, package, options) {
+        Err((Error::UnrecordableOutput(format, _), _)) => assert_eq!(format, "flat"),
+        Err((e, _)) => panic!("expected unrecordable output, got {e}"),
+        Ok(qir) => panic!("expected unrecordable output, got {qir}"),
+    }
+}
+
+/// The instructions of the entry point, including the labels of its blocks.
+fn entry_lines(qir: &str) -> String {
+    qir.lines()
+        .skip_while(|line| !line.starts_with("define"))
+        .skip(1)
+        .take_while(|line| *line != "}")
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn branch_on_result_on_adaptive_target() {
+    let (store, package) = build_with_capabilities(
+        indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Entry() : Result {
+            use q0 = Qubit();
+            use q1 = Qubit();
+            H(q0);
+            if MResetZ(q0) == One {
+                X(q1);
+            } else {
+                Z(q1);
+            }
+            if MResetZ(q1) != One {
+                H(q0);
+                Reset(q0);
+            }
+            MResetZ(q0)
+        }
+    }
+    "#},
+        None,
+        ConfigAttr::Adaptive.into(),
+    );
+
+    let qir = AdaptiveProfile::default()
+        .generate(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    expect![[r#"
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+          %cond0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          br i1 %cond0, label %then0, label %else0
+        then0:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+          br label %continue0
+        else0:
+          call void @__quantum__qis__z__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+          br label %continue0
+        continue0:
+          call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*)) #1
+          %cond1 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+          br i1 %cond1, label %else1, label %then1
+        then1:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 2 to %Qubit*))
+          call void @__quantum__qis__reset__body(%Qubit* inttoptr (i64 2 to %Qubit*))
+          br label %continue1
+        else1:
+          br label %continue1
+        continue1:
+          call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 2 to %Qubit*), %Result* inttoptr (i64 2 to %Result*)) #1
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+          ret void"#]]
+    .assert_eq(&entry_lines(&qir));
+    assert!(qir.contains(r#""qir_profiles"="adaptive_profile""#));
+    assert!(qir.contains("declare void @__quantum__qis__reset__body(%Qubit*) #1"));
+}
+
+#[test]
+fn dead_results_reuse_registers_when_enabled() {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        @EntryPoint()
+        operation Entry() : Result {
+            use qs = Qubit[3];
+            let _ = M(qs[0]);
+            let _ = M(qs[1]);
+            M(qs[2])
+        }
+    }
+    "#},
+        None,
+    );
+
+    let options = CodegenOptions {
+        reuse_result_registers: true,
+        ..CodegenOptions::default()
+    };
+    let qir = generate_qir_with_options(&store, package, options)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    let measurements = qir
+        .lines()
+        .filter(|line| line.contains("call void @__quantum__qis__mz__body("))
+        .collect::<Vec<_>>();
+    assert_eq!(measurements.len(), 3);
+    assert!(measurements
+        .iter()
+        .all(|line| line.contains("%Result* inttoptr (i64 0 to %Result*)")));
+    assert!(qir.contains(
+        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)"
+    ));
+    assert!(qir.contains(r#""required_num_qubits"="6" "required_num_results"="1""#));
+}
+
+#[test]
+fn results_keep_their_registers_by_default() {
+    let (store, package) = build(
+        indoc! {r#"
+    namespace Sample {
+        @EntryPoint()
+        operation Entry() : Result {
+            use qs = Qubit[3];
+            let _ = M(qs[0]);
+            let _ = M(qs[1]);
+            M(qs[2])
+        }
+    }
+    "#},
+        None,
+    );
+
+    let qir = generate_qir(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    assert!(qir.contains(r#""required_num_qubits"="6" "required_num_results"="3""#));
+}
+
+const PHASE_ESTIMATION: &str = indoc! {r#"
+    namespace Sample {
+        open Microsoft.Quantum.Convert;
+        open Microsoft.Quantum.Unstable.PhaseEstimation;
+
+        operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {
+            R1(0.25 * IntAsDouble(power), qs[0]);
+        }
+
+        @EntryPoint()
+        operation Entry() : Result[] {
+            use target = Qubit();
+            X(target);
+            let bits = EstimatePhase(4, RotatePower, [target]);
+            Reset(target);
+            bits
+        }
+    }
+"#};
+
+#[test]
+fn program_within_qubit_budget_is_generated() {
+    let (store, package) = build(PHASE_ESTIMATION, None);
+    let options = CodegenOptions {
+        max_qubits: Some(5),
+        ..CodegenOptions::default()
+    };
+    let qir = generate_qir_with_options(&store, package, options)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    assert!(qir.contains(r#""required_num_qubits"="5" "required_num_results"="4""#));
+}
+
+#[test]
+fn program_over_qubit_budget_is_rejected_where_it_runs_out() {
+    let (store, package) = build(PHASE_ESTIMATION, None);
+    let options = CodegenOptions {
+        max_qubits: Some(4),
+        ..CodegenOptions::default()
+    };
+    match generate_qir_with_options(&store, package, options) {
+        Err((Error::QubitBudgetExceeded(needed, max, span), frames)) => {
+            assert_eq!((needed, max), (5, 4));
+            assert_eq!(span.package, package);
+            let call = PHASE_ESTIMATION
+                .find("EstimatePhase(4")
+                .expect("program should call `EstimatePhase`");
+            let call_end = call + PHASE_ESTIMATION[call..].find(';').expect("call should end");
+            assert!(
+                call <= span.span.lo as usize && span.span.hi as usize <= call_end,
+                "{span:?}"
+            );
+            assert!(!frames.is_empty());
+        }
+        Err((e, _)) => panic!("expected qubit budget failure, got {e}"),
+        Ok(qir) => panic!("expected qubit budget failure, got {qir}"),
+    }
+}
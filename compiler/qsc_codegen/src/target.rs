@@ -0,0 +1,118 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::qir_base::{AdaptiveProfile, BaseProfile};
+use qsc_eval::{
+    backend::Backend,
+    debug::{map_hir_package_to_fir, Frame},
+    eval,
+    output::GenericReceiver,
+    val::Value,
+    Env, Error,
+};
+use qsc_fir::fir;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags};
+use qsc_hir::hir;
+use rustc_hash::FxHashMap;
+
+/// An output format that code can be generated for, such as a QIR profile or a vendor dialect.
+pub trait TargetBackend {
+    /// The name the backend is registered under.
+    fn name(&self) -> &str;
+
+    /// The runtime capabilities that programs must be compiled with to be accepted by this backend.
+    fn capabilities(&self) -> RuntimeCapabilityFlags;
+
+    /// The extension of the files that the generated code is written to.
+    fn file_extension(&self) -> &str;
+
+    /// Generates code for the entry expression of the given package.
+    fn generate(
+        &self,
+        store: &PackageStore,
+        package: hir::PackageId,
+    ) -> Result<String, (Error, Vec<Frame>)>;
+}
+
+/// A set of target backends keyed by name.
+pub struct BackendRegistry {
+    backends: FxHashMap<String, Box<dyn TargetBackend>>,
+}
+
+impl Default for BackendRegistry {
+    /// A registry that contains the backends built into this crate.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(BaseProfile::default()));
+        registry.register(Box::new(AdaptiveProfile::default()));
+        registry
+    }
+}
+
+impl BackendRegistry {
+    /// A registry with no backends.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            backends: FxHashMap::default(),
+        }
+    }
+
+    /// Adds a backend to the registry, returning the backend it replaces if one was already
+    /// registered under the same name.
+    pub fn register(&mut self, backend: Box<dyn TargetBackend>) -> Option<Box<dyn TargetBackend>> {
+        self.backends.insert(backend.name().to_string(), backend)
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn TargetBackend> {
+        self.backends.get(name).map(AsRef::as_ref)
+    }
+
+    /// The names of the registered backends in sorted order.
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.backends.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Lowers the packages in the store to FIR and evaluates the entry expression of the given
+/// package against `backend`, returning the value of the entry expression. This is the shared
+/// driver for backends that generate code by tracing the gates a program applies.
+pub fn trace_entry(
+    store: &PackageStore,
+    package: hir::PackageId,
+    backend: &mut impl Backend<ResultType = usize>,
+) -> Result<Value, (Error, Vec<Frame>)> {
+    let mut fir_lowerer = qsc_eval::lower::Lowerer::new();
+    let mut fir_store = fir::PackageStore::new();
+    for (id, unit) in store {
+        fir_store.insert(
+            map_hir_package_to_fir(id),
+            fir_lowerer.lower_package(&unit.package),
+        );
+    }
+
+    let package = map_hir_package_to_fir(package);
+    let unit = fir_store.get(package).expect("store should have package");
+    let entry_expr = unit.entry.expect("package should have entry");
+
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    eval(
+        package,
+        None,
+        entry_expr.into(),
+        &fir_store,
+        &mut Env::default(),
+        backend,
+        &mut out,
+        None,
+        None,
+    )
+}
@@ -0,0 +1,301 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{trace_entry, BackendRegistry, TargetBackend};
+use crate::qir_base::generate_qir;
+use expect_test::expect;
+use indoc::indoc;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{backend::Backend, debug::Frame, val::Value, Error};
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use std::fmt::Write;
+
+/// A backend that lives outside of this crate and emits a plain listing of the gates a program
+/// applies, one per line.
+struct GateList;
+
+impl TargetBackend for GateList {
+    fn name(&self) -> &str {
+        "gate-list"
+    }
+
+    fn capabilities(&self) -> RuntimeCapabilityFlags {
+        RuntimeCapabilityFlags::empty()
+    }
+
+    fn file_extension(&self) -> &str {
+        "txt"
+    }
+
+    fn generate(
+        &self,
+        store: &PackageStore,
+        package: PackageId,
+    ) -> Result<String, (Error, Vec<Frame>)> {
+        let mut listing = GateListing::default();
+        let val = trace_entry(store, package, &mut listing)?;
+        writeln!(listing.lines, "output {}", output(&val))
+            .expect("writing to string should succeed");
+        Ok(listing.lines)
+    }
+}
+
+/// Formats the entry expression value, naming results by the measurement that produced them.
+fn output(val: &Value) -> String {
+    match val {
+        Value::Result(r) => format!("r{}", r.unwrap_id()),
+        Value::Tuple(items) => {
+            let items = items.iter().map(output).collect::<Vec<_>>();
+            format!("({})", items.join(", "))
+        }
+        _ => val.to_string(),
+    }
+}
+
+#[derive(Default)]
+struct GateListing {
+    lines: String,
+    next_qubit: usize,
+    next_result: usize,
+}
+
+impl GateListing {
+    fn gate(&mut self, name: &str, qubits: &[usize]) {
+        write!(self.lines, "{name}").expect("writing to string should succeed");
+        for q in qubits {
+            write!(self.lines, " q{q}").expect("writing to string should succeed");
+        }
+        writeln!(self.lines).expect("writing to string should succeed");
+    }
+
+    fn rotation(&mut self, name: &str, theta: f64, qubits: &[usize]) {
+        self.gate(&format!("{name}({theta})"), qubits);
+    }
+}
+
+impl Backend for GateListing {
+    type ResultType = usize;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.gate("ccx", &[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.gate("cx", &[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.gate("cy", &[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.gate("cz", &[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.gate("h", &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        let id = self.next_result;
+        self.next_result += 1;
+        self.gate(&format!("m -> r{id}"), &[q]);
+        id
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let id = self.m(q);
+        self.reset(q);
+        id
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.gate("reset", &[q]);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.rotation("rx", theta, &[q]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation("rxx", theta, &[q0, q1]);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.rotation("ry", theta, &[q]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation("ryy", theta, &[q0, q1]);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.rotation("rz", theta, &[q]);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.rotation("rzz", theta, &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.gate("sadj", &[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.gate("s", &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.gate("swap", &[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.gate("tadj", &[q]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.gate("t", &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.gate("x", &[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.gate("y", &[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.gate("z", &[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let id = self.next_qubit;
+        self.next_qubit += 1;
+        id
+    }
+
+    fn qubit_release(&mut self, _q: usize) {
+        self.next_qubit -= 1;
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        (Vec::new(), 0)
+    }
+
+    fn qubit_is_zero(&mut self, _q: usize) -> bool {
+        true
+    }
+}
+
+fn compile_program(program: &str) -> (PackageStore, PackageId) {
+    let mut core = compile::core();
+    assert!(run_core_passes(&mut core).is_empty());
+    let mut store = PackageStore::new(core);
+    let mut std = compile::std(&store, RuntimeCapabilityFlags::empty());
+    assert!(run_default_passes(
+        store.core(),
+        &mut std,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::empty()
+    )
+    .is_empty());
+    let std = store.insert(std);
+
+    let sources = SourceMap::new([("test".into(), program.into())], None);
+    let mut unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::empty());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert!(run_default_passes(
+        store.core(),
+        &mut unit,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::empty()
+    )
+    .is_empty());
+    let package = store.insert(unit);
+    (store, package)
+}
+
+const BELL: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Measurement;
+        @EntryPoint()
+        operation Main() : (Result, Result) {
+            use (q0, q1) = (Qubit(), Qubit());
+            H(q0);
+            CNOT(q0, q1);
+            (MResetZ(q0), MResetZ(q1))
+        }
+    }
+"};
+
+#[test]
+fn default_registry_contains_qir_profiles() {
+    let registry = BackendRegistry::default();
+    assert_eq!(registry.names(), vec!["adaptive", "base"]);
+    let base = registry
+        .get("base")
+        .expect("base profile should be registered");
+    assert_eq!(base.file_extension(), "ll");
+    assert_eq!(base.capabilities(), RuntimeCapabilityFlags::empty());
+}
+
+#[test]
+fn unknown_target_is_not_found() {
+    let registry = BackendRegistry::default();
+    assert!(registry.get("gate-list").is_none());
+    assert!(BackendRegistry::empty().get("base").is_none());
+}
+
+#[test]
+fn base_profile_backend_matches_generate_qir() {
+    let (store, package) = compile_program(BELL);
+    let registry = BackendRegistry::default();
+    let base = registry
+        .get("base")
+        .expect("base profile should be registered");
+    let qir = base
+        .generate(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    let expected = generate_qir(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    assert_eq!(qir, expected);
+}
+
+#[test]
+fn out_of_tree_backend_generates_through_registry() {
+    let (store, package) = compile_program(BELL);
+    let mut registry = BackendRegistry::default();
+    assert!(registry.register(Box::new(GateList)).is_none());
+    assert_eq!(registry.names(), vec!["adaptive", "base", "gate-list"]);
+
+    let listing = registry
+        .get("gate-list")
+        .expect("gate list should be registered")
+        .generate(&store, package)
+        .unwrap_or_else(|(e, _)| panic!("code generation failed: {e}"));
+    expect![[r#"
+        h q0
+        cx q0 q1
+        m -> r0 q0
+        reset q0
+        m -> r1 q1
+        reset q1
+        output (r0, r1)
+    "#]]
+    .assert_eq(&listing);
+}
+
+#[test]
+fn registering_same_name_replaces_backend() {
+    let mut registry = BackendRegistry::empty();
+    assert!(registry.register(Box::new(GateList)).is_none());
+    let replaced = registry
+        .register(Box::new(GateList))
+        .expect("previous backend should be returned");
+    assert_eq!(replaced.name(), "gate-list");
+    assert_eq!(registry.names(), vec!["gate-list"]);
+}
@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Editions of the Q# language. New syntax and typing rules that could change the meaning of
+//! existing code arrive in a new edition, and a project opts into them by naming the edition in
+//! its manifest. Projects that don't name one keep compiling in the first edition.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// An edition of the language, which decides the language features a package can use.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Edition {
+    /// The first edition, which is the language without any of the [`LanguageFeature`]s.
+    #[default]
+    Q2023,
+    /// Callables without an output type, and equality of user-defined types.
+    Q2024,
+}
+
+impl Edition {
+    /// The newest edition.
+    pub const LATEST: Self = Self::Q2024;
+
+    /// Whether the feature is part of the edition.
+    #[must_use]
+    pub fn supports(self, feature: LanguageFeature) -> bool {
+        self >= feature.edition()
+    }
+}
+
+impl Display for Edition {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Q2023 => "2023",
+            Self::Q2024 => "2024",
+        })
+    }
+}
+
+impl FromStr for Edition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2023" => Ok(Self::Q2023),
+            "2024" => Ok(Self::Q2024),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A language feature that only some editions have.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LanguageFeature {
+    /// Declaring a callable without `: Type` after its input, which then returns `Unit`.
+    OmittedOutputTy,
+    /// Comparing values of user-defined types with `==` and `!=`, which compares the values they
+    /// wrap.
+    UdtEquality,
+}
+
+impl LanguageFeature {
+    /// The first edition with the feature.
+    #[must_use]
+    pub fn edition(self) -> Edition {
+        match self {
+            Self::OmittedOutputTy | Self::UdtEquality => Edition::Q2024,
+        }
+    }
+}
+
+impl Display for LanguageFeature {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::OmittedOutputTy => "omitting the output type of a callable",
+            Self::UdtEquality => "comparing user-defined types for equality",
+        })
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Edition, LanguageFeature};
+
+#[test]
+fn editions_round_trip_through_strings() {
+    for edition in [Edition::Q2023, Edition::Q2024] {
+        assert_eq!(edition.to_string().parse(), Ok(edition));
+    }
+    assert_eq!("2022".parse::<Edition>(), Err(()));
+    assert_eq!(Edition::default(), Edition::Q2023);
+}
+
+#[test]
+fn later_editions_keep_the_features_of_earlier_ones() {
+    let feature = LanguageFeature::OmittedOutputTy;
+    assert!(!Edition::Q2023.supports(feature));
+    assert!(Edition::Q2024.supports(feature));
+    assert!(Edition::LATEST.supports(LanguageFeature::UdtEquality));
+}
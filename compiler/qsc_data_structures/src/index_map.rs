@@ -158,6 +158,14 @@ impl<K: Into<usize>, V> FromIterator<(K, V)> for IndexMap<K, V> {
     }
 }
 
+impl<K: Into<usize>, V> Extend<(K, V)> for IndexMap<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
 pub struct Iter<'a, K, V> {
     _keys: PhantomData<K>,
     base: Enumerate<slice::Iter<'a, Option<V>>>,
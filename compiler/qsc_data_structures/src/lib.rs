@@ -4,6 +4,8 @@
 #![warn(clippy::mod_module_files, clippy::pedantic, clippy::unwrap_used)]
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
+pub mod edition;
 pub mod index_map;
 pub mod line_column;
+pub mod message;
 pub mod span;
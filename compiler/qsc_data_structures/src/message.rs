@@ -0,0 +1,18 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The parameters of diagnostic messages. A message catalog renders a diagnostic from the
+//! template for its code, such as a translation, filled in with these parameters.
+
+/// A diagnostic whose message is rendered from a template keyed by its code.
+pub trait MessageParams {
+    /// The values of the parameters of the message, in the order they appear in the English
+    /// message.
+    fn message_params(&self) -> Vec<String>;
+}
+
+impl<T: MessageParams + ?Sized> MessageParams for Box<T> {
+    fn message_params(&self) -> Vec<String> {
+        (**self).message_params()
+    }
+}
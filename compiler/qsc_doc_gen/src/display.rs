@@ -10,7 +10,7 @@ use qsc_hir::{
 use regex_lite::Regex;
 use std::{
     fmt::{Display, Formatter, Result},
-    rc::Rc,
+    sync::Arc,
 };
 
 /// Trait describing a struct capable of resolving various ids found in the AST and HIR.
@@ -350,7 +350,7 @@ impl<'a> Display for HirUdt<'a> {
 }
 
 struct UdtDef<'a> {
-    name: Option<Rc<str>>,
+    name: Option<Arc<str>>,
     kind: UdtDefKind<'a>,
 }
 
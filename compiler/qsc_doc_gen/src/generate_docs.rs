@@ -13,7 +13,6 @@ use qsc_hir::hir::{CallableKind, Item, ItemKind, Package, PackageId, Visibility}
 use qsc_hir::{hir, ty};
 use rustc_hash::FxHashMap;
 use std::fmt::{Display, Formatter, Result};
-use std::rc::Rc;
 use std::sync::Arc;
 
 /// Represents an immutable compilation state.
@@ -108,7 +107,7 @@ pub fn generate_docs() -> FxHashMap<Arc<str>, Arc<str>> {
         compilation: &compilation,
     };
 
-    let mut toc: FxHashMap<Rc<str>, Vec<String>> = FxHashMap::default();
+    let mut toc: FxHashMap<Arc<str>, Vec<String>> = FxHashMap::default();
     for (_, unit) in &compilation.package_store {
         let package = &unit.package;
         for (_, item) in &package.items {
@@ -128,7 +127,7 @@ fn generate_doc_for_item<'a>(
     item: &'a Item,
     display: &'a CodeDisplay,
     file_map: &mut FxHashMap<Arc<str>, Arc<str>>,
-) -> Option<(Rc<str>, String)> {
+) -> Option<(Arc<str>, String)> {
     // Filter items
     if item.visibility == Visibility::Internal || matches!(item.kind, ItemKind::Namespace(_, _)) {
         return None;
@@ -150,7 +149,7 @@ fn generate_doc_for_item<'a>(
     Some((ns.clone(), line))
 }
 
-fn get_namespace(package: &Package, item: &Item) -> Option<Rc<str>> {
+fn get_namespace(package: &Package, item: &Item) -> Option<Arc<str>> {
     match item.parent {
         Some(local_id) => {
             let parent = package
@@ -172,7 +171,7 @@ fn get_namespace(package: &Package, item: &Item) -> Option<Rc<str>> {
     }
 }
 
-fn generate_file(ns: &Rc<str>, item: &Item, display: &CodeDisplay) -> Option<(Rc<str>, String)> {
+fn generate_file(ns: &Arc<str>, item: &Item, display: &CodeDisplay) -> Option<(Arc<str>, String)> {
     let metadata = get_metadata(ns.clone(), item, display)?;
 
     let doc = increase_header_level(&item.doc);
@@ -206,8 +205,8 @@ struct Metadata {
     title: String,
     topic: String,
     kind: MetadataKind,
-    namespace: Rc<str>,
-    name: Rc<str>,
+    namespace: Arc<str>,
+    name: Arc<str>,
     summary: String,
     signature: String,
 }
@@ -242,7 +241,7 @@ enum MetadataKind {
     Udt,
 }
 
-fn get_metadata(ns: Rc<str>, item: &Item, display: &CodeDisplay) -> Option<Metadata> {
+fn get_metadata(ns: Arc<str>, item: &Item, display: &CodeDisplay) -> Option<Metadata> {
     let (name, signature, kind) = match &item.kind {
         ItemKind::Callable(decl) => Some((
             decl.name.name.clone(),
@@ -278,7 +277,7 @@ fn get_metadata(ns: Rc<str>, item: &Item, display: &CodeDisplay) -> Option<Metad
 
 /// Generates the Table of Contents file, toc.yml
 fn generate_toc(
-    map: &FxHashMap<Rc<str>, Vec<String>>,
+    map: &FxHashMap<Arc<str>, Vec<String>>,
     file_map: &mut FxHashMap<Arc<str>, Arc<str>>,
 ) {
     let header = "
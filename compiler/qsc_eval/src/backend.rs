@@ -3,10 +3,19 @@
 
 use num_bigint::BigUint;
 use num_complex::Complex;
+use qsc_fir::fir::Pauli;
 use quantum_sparse_sim::QuantumSim;
 use rand::RngCore;
+use std::{mem::size_of, str::FromStr};
+use thiserror::Error;
 
-use crate::val::Value;
+use crate::{
+    debug::Frame,
+    val::{self, Value},
+};
+use single::{SingleStateVector, SparseStateVector};
+
+mod single;
 
 /// The trait that must be implemented by a quantum backend, whose functions will be invoked when
 /// quantum intrinsics are called.
@@ -40,16 +49,319 @@ pub trait Backend {
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize);
     fn qubit_is_zero(&mut self, q: usize) -> bool;
 
+    /// The probability that measuring each qubit in its Pauli basis gives results with a parity of
+    /// `One`, computed without disturbing the state. Backends that can't inspect the state return
+    /// `None`, which skips assertions about it.
+    fn measurement_probability(&mut self, _bases: &[(Pauli, usize)]) -> Option<f64> {
+        None
+    }
+
+    /// Measures the qubit with the outcome `one`, collapsing the state as if the measurement had
+    /// found it, so that recorded runs can be replayed. Returns `None` without measuring if the
+    /// backend can't choose outcomes or the outcome has a probability of zero.
+    fn forced_m(&mut self, _q: usize, _one: bool) -> Option<Self::ResultType> {
+        None
+    }
+
+    /// The amplitudes of the basis states that match the pattern, in the same form as the state
+    /// captured by `capture_quantum_state`, without collecting the states that don't match.
+    /// Backends that can't inspect the state return `None`.
+    fn matching_amplitudes(
+        &mut self,
+        _pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        None
+    }
+
     fn custom_intrinsic(&mut self, _name: &str, _arg: Value) -> Option<Result<Value, String>> {
         None
     }
 
+    /// Marks a point of the program across the qubits, with a label that may be empty. A barrier
+    /// doesn't act on the qubits, so only backends that record the program, such as circuit
+    /// builders, do anything with it.
+    fn barrier(&mut self, _qubits: &[usize], _label: &str) {}
+
+    /// Called when a statement binds a qubit to a name, with the name, such as `aux`, or the name
+    /// and index of an array of qubits, such as `ctls[1]`. Qubits are bound as soon as they are
+    /// allocated, and may be bound again to other names later.
+    fn qubit_label(&mut self, _q: usize, _label: &str) {}
+
+    /// Whether the backend uses the names of qubits, with [`Backend::qubit_label`]. Finding the
+    /// names of the qubits that statements bind isn't free, so the evaluator only does it for
+    /// backends that opt in.
+    fn labels_qubits(&self) -> bool {
+        false
+    }
+
     fn set_seed(&mut self, _seed: Option<u64>) {}
+
+    /// Called before each intrinsic with the calls that led to it, outermost first. The span of
+    /// each frame is the call site in its caller, so the last frame is the call to the intrinsic.
+    fn set_call_stack(&mut self, _frames: &[Frame]) {}
+
+    /// Called when a call to a callable starts, including calls to intrinsics and calls replayed
+    /// from the operation cache.
+    fn enter_call(&mut self, _frame: &Frame) {}
+
+    /// Called when the call that started last finishes. Calls that an error interrupts aren't
+    /// finished.
+    fn exit_call(&mut self, _frame: &Frame) {}
+
+    /// Whether the backend can branch on results that are only known to the target, with
+    /// [`Backend::begin_result_branch`]. Comparing such results is an error for backends that
+    /// can't, and other backends don't pay for looking for branches on results.
+    fn result_branches(&self) -> bool {
+        false
+    }
+
+    /// The outcome of the measurement that returned the result with the id, for backends whose
+    /// measurements return ids but that know their outcomes. Results with known outcomes can be
+    /// compared like results that hold their values.
+    fn result_outcome(&self, _id: usize) -> Option<bool> {
+        None
+    }
+
+    /// Starts a branch that the target takes when the program runs if the results are equal, or
+    /// unequal when `eq` is false, where at least one of the results is only known to the target.
+    /// The side that runs when the branch is taken is traced first, then the other side after
+    /// [`Backend::else_result_branch`], until [`Backend::end_result_branch`]. Only called when
+    /// [`Backend::result_branches`] is true. No values flow out of the branch, so its sides join
+    /// without any computed from the branch taken.
+    fn begin_result_branch(&mut self, _lhs: val::Result, _rhs: val::Result, _eq: bool) {}
+
+    /// Switches from tracing the side of the innermost branch on results that runs when it is
+    /// taken to the side that runs when it isn't.
+    fn else_result_branch(&mut self) {}
+
+    /// Ends the innermost branch on results, joining both of its sides.
+    fn end_result_branch(&mut self) {}
+}
+
+/// A set of basis states in which some qubits have fixed values and the others can have any value.
+/// Qubits are identified by their position in the labels of dumped states, with the first
+/// allocated qubit on the left. Positions past the allocated qubits are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BasisPattern {
+    fixed: Vec<(usize, bool)>,
+}
+
+impl BasisPattern {
+    /// Restricts the pattern to the basis states where the qubit at the position is `one`.
+    #[must_use]
+    pub fn fix(mut self, position: usize, one: bool) -> Self {
+        self.fixed.retain(|&(fixed, _)| fixed != position);
+        self.fixed.push((position, one));
+        self
+    }
+
+    /// Whether the basis state of the qubits matches the pattern, where `bit` gives the value of
+    /// the qubit at a position.
+    fn matches(&self, qubits: usize, bit: impl Fn(usize) -> bool) -> bool {
+        self.fixed
+            .iter()
+            .all(|&(position, one)| position >= qubits || bit(position) == one)
+    }
+}
+
+/// A character of a basis pattern that is neither `0`, `1` nor `?`.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("expected `0`, `1` or `?` at position {position} of the basis pattern, found `{found}`")]
+pub struct ParseBasisPatternError {
+    pub position: usize,
+    pub found: char,
+}
+
+impl FromStr for BasisPattern {
+    type Err = ParseBasisPatternError;
+
+    /// Parses a pattern written like a basis state label, such as `1011??`, where `?` stands for
+    /// either value. Qubits after the end of the pattern can have any value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .chars()
+            .enumerate()
+            .try_fold(Self::default(), |pattern, (position, c)| match c {
+                '0' => Ok(pattern.fix(position, false)),
+                '1' => Ok(pattern.fix(position, true)),
+                '?' => Ok(pattern),
+                found => Err(ParseBasisPatternError { position, found }),
+            })
+    }
+}
+
+/// The floating-point precision of the amplitudes of a simulated state vector.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Precision {
+    /// Amplitudes of two `f64`s.
+    #[default]
+    Double,
+    /// Amplitudes of two `f32`s. The sparse state vector stores the index of each basis state
+    /// beside its amplitude, so an entry only shrinks by a fifth. Rounding errors grow faster with
+    /// the number of gates than at double precision.
+    Single,
+}
+
+impl Precision {
+    /// The bytes of one entry of a sparse state vector, which is the index of a basis state and
+    /// its amplitude. The digits of nonzero indices are allocated separately at either precision
+    /// and aren't counted.
+    #[must_use]
+    pub fn entry_bytes(self) -> usize {
+        match self {
+            Self::Double => size_of::<(BigUint, Complex<f64>)>(),
+            Self::Single => size_of::<(BigUint, Complex<f32>)>(),
+        }
+    }
+}
+
+/// The operations of the sparse simulator that [`SparseSim`] is built on, which a state vector at
+/// either precision supports.
+trait StateVector {
+    fn allocate(&mut self) -> usize;
+    fn release(&mut self, q: usize);
+    /// The amplitudes of the basis states that match the pattern, and the number of allocated
+    /// qubits. Bit `i` of each index is the value of the qubit at position `i`.
+    fn matching_state(&mut self, pattern: &BasisPattern) -> (Vec<(BigUint, Complex<f64>)>, usize);
+    fn h(&mut self, q: usize);
+    fn joint_probability(&mut self, ids: &[usize]) -> f64;
+    fn mcx(&mut self, ctls: &[usize], q: usize);
+    fn mcy(&mut self, ctls: &[usize], q: usize);
+    fn mcz(&mut self, ctls: &[usize], q: usize);
+    fn measure(&mut self, q: usize) -> bool;
+    /// Measures the qubit with the outcome if it has a nonzero probability, returning whether it
+    /// did.
+    fn measure_as(&mut self, q: usize, one: bool) -> bool;
+    fn qubit_is_zero(&mut self, q: usize) -> bool;
+    fn rx(&mut self, theta: f64, q: usize);
+    fn ry(&mut self, theta: f64, q: usize);
+    fn rz(&mut self, theta: f64, q: usize);
+    fn s(&mut self, q: usize);
+    fn sadj(&mut self, q: usize);
+    fn set_rng_seed(&mut self, seed: u64);
+    fn swap_qubit_ids(&mut self, q0: usize, q1: usize);
+    fn t(&mut self, q: usize);
+    fn tadj(&mut self, q: usize);
+    fn x(&mut self, q: usize);
+    fn y(&mut self, q: usize);
+    fn z(&mut self, q: usize);
+}
+
+impl StateVector for QuantumSim {
+    fn allocate(&mut self) -> usize {
+        QuantumSim::allocate(self)
+    }
+
+    fn release(&mut self, q: usize) {
+        QuantumSim::release(self, q);
+    }
+
+    fn matching_state(&mut self, pattern: &BasisPattern) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        // The simulator only hands out its whole state, so the state is filtered as it is taken.
+        let (state, count) = QuantumSim::get_state(self);
+        let state = state
+            .into_iter()
+            .filter(|(index, _)| pattern.matches(count, |position| index.bit(position as u64)))
+            .collect();
+        (state, count)
+    }
+
+    fn h(&mut self, q: usize) {
+        QuantumSim::h(self, q);
+    }
+
+    fn joint_probability(&mut self, ids: &[usize]) -> f64 {
+        QuantumSim::joint_probability(self, ids)
+    }
+
+    fn mcx(&mut self, ctls: &[usize], q: usize) {
+        QuantumSim::mcx(self, ctls, q);
+    }
+
+    fn mcy(&mut self, ctls: &[usize], q: usize) {
+        QuantumSim::mcy(self, ctls, q);
+    }
+
+    fn mcz(&mut self, ctls: &[usize], q: usize) {
+        QuantumSim::mcz(self, ctls, q);
+    }
+
+    fn measure(&mut self, q: usize) -> bool {
+        QuantumSim::measure(self, q)
+    }
+
+    fn measure_as(&mut self, _q: usize, _one: bool) -> bool {
+        // The simulator only draws outcomes from its own generator.
+        false
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        QuantumSim::qubit_is_zero(self, q)
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        QuantumSim::rx(self, theta, q);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        QuantumSim::ry(self, theta, q);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        QuantumSim::rz(self, theta, q);
+    }
+
+    fn s(&mut self, q: usize) {
+        QuantumSim::s(self, q);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        QuantumSim::sadj(self, q);
+    }
+
+    fn set_rng_seed(&mut self, seed: u64) {
+        QuantumSim::set_rng_seed(self, seed);
+    }
+
+    fn swap_qubit_ids(&mut self, q0: usize, q1: usize) {
+        QuantumSim::swap_qubit_ids(self, q0, q1);
+    }
+
+    fn t(&mut self, q: usize) {
+        QuantumSim::t(self, q);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        QuantumSim::tadj(self, q);
+    }
+
+    fn x(&mut self, q: usize) {
+        QuantumSim::x(self, q);
+    }
+
+    fn y(&mut self, q: usize) {
+        QuantumSim::y(self, q);
+    }
+
+    fn z(&mut self, q: usize) {
+        QuantumSim::z(self, q);
+    }
+}
+
+enum State {
+    Double(QuantumSim),
+    Single(SingleStateVector),
+    /// A state vector at double precision that can measure chosen outcomes.
+    Replay(SparseStateVector<f64>),
 }
 
 /// Default backend used when targeting sparse simulation.
 pub struct SparseSim {
-    sim: QuantumSim,
+    state: State,
+    /// The number of qubits that are currently allocated.
+    qubits: usize,
+    /// The largest number of qubits that were allocated at once.
+    peak_qubits: usize,
 }
 
 impl Default for SparseSim {
@@ -61,8 +373,77 @@ impl Default for SparseSim {
 impl SparseSim {
     #[must_use]
     pub fn new() -> Self {
+        Self::with_precision(Precision::Double)
+    }
+
+    /// A simulator whose state vector has amplitudes of the precision.
+    #[must_use]
+    pub fn with_precision(precision: Precision) -> Self {
+        Self {
+            state: match precision {
+                Precision::Double => State::Double(QuantumSim::new()),
+                Precision::Single => State::Single(SingleStateVector::new()),
+            },
+            qubits: 0,
+            peak_qubits: 0,
+        }
+    }
+
+    /// A simulator whose measurements can be given their outcomes with [`Backend::forced_m`], to
+    /// replay recorded runs. The simulator at double precision only draws outcomes at random, so
+    /// replays at double precision use the state vector of single precision with `f64`
+    /// amplitudes instead, which is slower.
+    #[must_use]
+    pub fn for_replay(precision: Precision) -> Self {
         Self {
-            sim: QuantumSim::new(),
+            state: match precision {
+                Precision::Double => State::Replay(SparseStateVector::new()),
+                Precision::Single => State::Single(SingleStateVector::new()),
+            },
+            qubits: 0,
+            peak_qubits: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn precision(&self) -> Precision {
+        match self.state {
+            State::Double(_) | State::Replay(_) => Precision::Double,
+            State::Single(_) => Precision::Single,
+        }
+    }
+
+    /// The number of qubits that are currently allocated.
+    #[must_use]
+    pub fn qubits(&self) -> usize {
+        self.qubits
+    }
+
+    /// The largest number of qubits that were allocated at once since the simulator was created,
+    /// which bounds the size that the state vector reached.
+    #[must_use]
+    pub fn peak_qubits(&self) -> usize {
+        self.peak_qubits
+    }
+
+    /// The largest difference from one that measurements found in the norm of the state, which is
+    /// the error that rounding the amplitudes accumulated. It can grow large enough to skew the
+    /// probabilities of results at single precision. The simulator at double precision doesn't
+    /// track it, so it is zero there.
+    #[must_use]
+    pub fn norm_error(&self) -> f64 {
+        match &self.state {
+            State::Double(_) => 0.0,
+            State::Single(state) => state.norm_error(),
+            State::Replay(state) => state.norm_error(),
+        }
+    }
+
+    fn sim(&mut self) -> &mut dyn StateVector {
+        match &mut self.state {
+            State::Double(sim) => sim,
+            State::Single(state) => state,
+            State::Replay(state) => state,
         }
     }
 }
@@ -71,43 +452,47 @@ impl Backend for SparseSim {
     type ResultType = bool;
 
     fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
-        self.sim.mcx(&[ctl0, ctl1], q);
+        self.sim().mcx(&[ctl0, ctl1], q);
     }
 
     fn cx(&mut self, ctl: usize, q: usize) {
-        self.sim.mcx(&[ctl], q);
+        self.sim().mcx(&[ctl], q);
     }
 
     fn cy(&mut self, ctl: usize, q: usize) {
-        self.sim.mcy(&[ctl], q);
+        self.sim().mcy(&[ctl], q);
     }
 
     fn cz(&mut self, ctl: usize, q: usize) {
-        self.sim.mcz(&[ctl], q);
+        self.sim().mcz(&[ctl], q);
     }
 
     fn h(&mut self, q: usize) {
-        self.sim.h(q);
+        self.sim().h(q);
     }
 
     fn m(&mut self, q: usize) -> Self::ResultType {
-        self.sim.measure(q)
+        self.sim().measure(q)
     }
 
     fn mresetz(&mut self, q: usize) -> Self::ResultType {
-        let res = self.sim.measure(q);
+        let res = self.sim().measure(q);
         if res {
-            self.sim.x(q);
+            self.sim().x(q);
         }
         res
     }
 
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        self.sim().measure_as(q, one).then_some(one)
+    }
+
     fn reset(&mut self, q: usize) {
         self.mresetz(q);
     }
 
     fn rx(&mut self, theta: f64, q: usize) {
-        self.sim.rx(theta, q);
+        self.sim().rx(theta, q);
     }
 
     fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
@@ -119,7 +504,7 @@ impl Backend for SparseSim {
     }
 
     fn ry(&mut self, theta: f64, q: usize) {
-        self.sim.ry(theta, q);
+        self.sim().ry(theta, q);
     }
 
     fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
@@ -139,7 +524,7 @@ impl Backend for SparseSim {
     }
 
     fn rz(&mut self, theta: f64, q: usize) {
-        self.sim.rz(theta, q);
+        self.sim().rz(theta, q);
     }
 
     fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
@@ -149,47 +534,58 @@ impl Backend for SparseSim {
     }
 
     fn sadj(&mut self, q: usize) {
-        self.sim.sadj(q);
+        self.sim().sadj(q);
     }
 
     fn s(&mut self, q: usize) {
-        self.sim.s(q);
+        self.sim().s(q);
     }
 
     fn swap(&mut self, q0: usize, q1: usize) {
-        self.sim.swap_qubit_ids(q0, q1);
+        self.sim().swap_qubit_ids(q0, q1);
     }
 
     fn tadj(&mut self, q: usize) {
-        self.sim.tadj(q);
+        self.sim().tadj(q);
     }
 
     fn t(&mut self, q: usize) {
-        self.sim.t(q);
+        self.sim().t(q);
     }
 
     fn x(&mut self, q: usize) {
-        self.sim.x(q);
+        self.sim().x(q);
     }
 
     fn y(&mut self, q: usize) {
-        self.sim.y(q);
+        self.sim().y(q);
     }
 
     fn z(&mut self, q: usize) {
-        self.sim.z(q);
+        self.sim().z(q);
     }
 
     fn qubit_allocate(&mut self) -> usize {
-        self.sim.allocate()
+        self.qubits += 1;
+        self.peak_qubits = self.peak_qubits.max(self.qubits);
+        self.sim().allocate()
     }
 
     fn qubit_release(&mut self, q: usize) {
-        self.sim.release(q);
+        self.qubits = self.qubits.saturating_sub(1);
+        self.sim().release(q);
     }
 
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
-        let (state, count) = self.sim.get_state();
+        self.matching_amplitudes(&BasisPattern::default())
+            .expect("sparse simulator should inspect the state")
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        let (state, count) = self.sim().matching_state(pattern);
         // Because the simulator returns the state indices with opposite endianness from the
         // expected one, we need to reverse the bit order of the indices.
         let mut new_state = state
@@ -205,11 +601,49 @@ impl Backend for SparseSim {
             })
             .collect::<Vec<_>>();
         new_state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-        (new_state, count)
+        Some((new_state, count))
     }
 
     fn qubit_is_zero(&mut self, q: usize) -> bool {
-        self.sim.qubit_is_zero(q)
+        self.sim().qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        // Rotate each qubit so that its basis becomes the Z basis, then rotate it back afterwards.
+        for &(basis, q) in bases {
+            match basis {
+                Pauli::X => self.h(q),
+                Pauli::Y => {
+                    self.sadj(q);
+                    self.h(q);
+                }
+                Pauli::I | Pauli::Z => {}
+            }
+        }
+
+        let ids = bases
+            .iter()
+            .filter(|&&(basis, _)| basis != Pauli::I)
+            .map(|&(_, q)| q)
+            .collect::<Vec<_>>();
+        let probability = if ids.is_empty() {
+            0.0
+        } else {
+            self.sim().joint_probability(&ids)
+        };
+
+        for &(basis, q) in bases.iter().rev() {
+            match basis {
+                Pauli::X => self.h(q),
+                Pauli::Y => {
+                    self.h(q);
+                    self.s(q);
+                }
+                Pauli::I | Pauli::Z => {}
+            }
+        }
+
+        Some(probability)
     }
 
     fn custom_intrinsic(&mut self, name: &str, _arg: Value) -> Option<Result<Value, String>> {
@@ -225,8 +659,8 @@ impl Backend for SparseSim {
 
     fn set_seed(&mut self, seed: Option<u64>) {
         match seed {
-            Some(seed) => self.sim.set_rng_seed(seed),
-            None => self.sim.set_rng_seed(rand::thread_rng().next_u64()),
+            Some(seed) => self.sim().set_rng_seed(seed),
+            None => self.sim().set_rng_seed(rand::thread_rng().next_u64()),
         }
     }
 }
@@ -0,0 +1,328 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! A sparse state vector with amplitudes of either `f32`s or `f64`s. At single precision, each
+//! entry keeps the same index as the sparse simulator at double precision, so it only takes 32
+//! bytes instead of 40. Rounding errors are larger than at double precision and grow with the
+//! number of gates, so the state vector keeps track of how far its norm has drifted from one,
+//! which is the error that measurements reveal. At double precision it replays recorded runs,
+//! since unlike the sparse simulator it can measure a chosen outcome.
+
+#[cfg(test)]
+mod tests;
+
+use super::{BasisPattern, StateVector};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use num_traits::{Float, FloatConst, NumAssign};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+
+/// The floating-point types of the parts of amplitudes.
+pub(super) trait Real: Float + FloatConst + NumAssign + Default {
+    fn from_double(value: f64) -> Self;
+    fn into_double(self) -> f64;
+}
+
+impl Real for f32 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_double(value: f64) -> Self {
+        value as f32
+    }
+
+    fn into_double(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Real for f64 {
+    fn from_double(value: f64) -> Self {
+        value
+    }
+
+    fn into_double(self) -> f64 {
+        self
+    }
+}
+
+/// A state vector with amplitudes of two `f32`s.
+pub(super) type SingleStateVector = SparseStateVector<f32>;
+
+/// The amplitudes of the basis states with a nonzero amplitude. Bit `q` of an index is the value of
+/// the qubit with ID `q`, and the bits of released IDs are always zero.
+pub(super) struct SparseStateVector<F> {
+    state: FxHashMap<BigUint, Complex<F>>,
+    /// Whether each ID is allocated.
+    allocated: Vec<bool>,
+    rng: StdRng,
+    /// The largest difference from one of the norm of the state before a measurement renormalized
+    /// it.
+    norm_error: f64,
+}
+
+impl<F: Real> SparseStateVector<F> {
+    pub(super) fn new() -> Self {
+        let mut state = FxHashMap::default();
+        state.insert(BigUint::default(), Complex::new(F::one(), F::zero()));
+        Self {
+            state,
+            allocated: Vec::new(),
+            rng: StdRng::from_entropy(),
+            norm_error: 0.0,
+        }
+    }
+
+    pub(super) fn norm_error(&self) -> f64 {
+        self.norm_error
+    }
+
+    /// The sum of the squared magnitudes of the amplitudes of the basis states that match.
+    fn probability(&self, matches: impl Fn(&BigUint) -> bool) -> f64 {
+        self.state
+            .iter()
+            .filter(|(index, _)| matches(index))
+            .map(|(_, amplitude)| amplitude.norm_sqr().into_double())
+            .sum()
+    }
+
+    /// Keeps the basis states that match and scales them back to a norm of one, recording how far
+    /// the norm of the whole state had drifted.
+    fn collapse(&mut self, matches: impl Fn(&BigUint) -> bool) {
+        let total = self.probability(|_| true);
+        self.norm_error = self.norm_error.max((total - 1.0).abs());
+        self.state.retain(|index, _| matches(index));
+        let kept = self.probability(|_| true);
+        if kept > 0.0 {
+            let scale = F::from_double(1.0 / kept.sqrt());
+            for amplitude in self.state.values_mut() {
+                *amplitude *= scale;
+            }
+        }
+    }
+
+    /// Maps each basis state where the controls are one to another basis state, multiplying its
+    /// amplitude by the phase of the state it came from.
+    fn permute(&mut self, ctls: &[usize], map: impl Fn(&BigUint) -> (BigUint, Complex<F>)) {
+        self.state = self
+            .state
+            .drain()
+            .map(|(index, amplitude)| {
+                if ctls.iter().all(|&ctl| index.bit(ctl as u64)) {
+                    let (index, phase) = map(&index);
+                    (index, amplitude * phase)
+                } else {
+                    (index, amplitude)
+                }
+            })
+            .collect();
+    }
+
+    /// Multiplies the amplitudes of the basis states where the controls and the qubit are one by
+    /// the phase. The basis states don't change, so the amplitudes are updated in place.
+    fn phase(&mut self, ctls: &[usize], q: usize, phase: Complex<F>) {
+        for (index, amplitude) in &mut self.state {
+            if index.bit(q as u64) && ctls.iter().all(|&ctl| index.bit(ctl as u64)) {
+                *amplitude *= phase;
+            }
+        }
+    }
+
+    /// Applies the single-qubit unitary, given by its rows, to the qubit.
+    fn apply(&mut self, q: usize, matrix: [[Complex<F>; 2]; 2]) {
+        let mut state = FxHashMap::<BigUint, Complex<F>>::default();
+        for (index, amplitude) in self.state.drain() {
+            let bit = usize::from(index.bit(q as u64));
+            let mut zero = index;
+            zero.set_bit(q as u64, false);
+            let mut one = zero.clone();
+            one.set_bit(q as u64, true);
+            *state.entry(zero).or_default() += matrix[0][bit] * amplitude;
+            *state.entry(one).or_default() += matrix[1][bit] * amplitude;
+        }
+        // Amplitudes whose squared magnitude is below the precision are dropped from the state.
+        let negligible = F::epsilon() * F::epsilon();
+        state.retain(|_, amplitude| amplitude.norm_sqr() > negligible);
+        self.state = state;
+    }
+
+    fn rotation(&mut self, q: usize, theta: f64, matrix: impl Fn(F, F) -> [[Complex<F>; 2]; 2]) {
+        let half = F::from_double(theta / 2.0);
+        self.apply(q, matrix(half.cos(), half.sin()));
+    }
+}
+
+fn flipped(index: &BigUint, q: usize) -> BigUint {
+    let mut flipped = index.clone();
+    flipped.set_bit(q as u64, !index.bit(q as u64));
+    flipped
+}
+
+impl<F: Real> StateVector for SparseStateVector<F> {
+    fn allocate(&mut self) -> usize {
+        let id = match self.allocated.iter().position(|&allocated| !allocated) {
+            Some(id) => id,
+            None => {
+                self.allocated.push(false);
+                self.allocated.len() - 1
+            }
+        };
+        self.allocated[id] = true;
+        id
+    }
+
+    fn release(&mut self, q: usize) {
+        // Released qubits are measured and reset, so their bits are zero for the next allocation.
+        if self.measure(q) {
+            self.x(q);
+        }
+        self.allocated[q] = false;
+    }
+
+    fn matching_state(&mut self, pattern: &BasisPattern) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        let ids = (0..self.allocated.len())
+            .filter(|&id| self.allocated[id])
+            .collect::<Vec<_>>();
+        // Only the basis states that match are copied out of the state.
+        let state = self
+            .state
+            .iter()
+            .filter(|(index, _)| {
+                pattern.matches(ids.len(), |position| index.bit(ids[position] as u64))
+            })
+            .map(|(index, amplitude)| {
+                let mut position_index = BigUint::default();
+                for (position, &id) in ids.iter().enumerate() {
+                    if index.bit(id as u64) {
+                        position_index.set_bit(position as u64, true);
+                    }
+                }
+                let amplitude =
+                    Complex::new(amplitude.re.into_double(), amplitude.im.into_double());
+                (position_index, amplitude)
+            })
+            .collect();
+        (state, ids.len())
+    }
+
+    fn h(&mut self, q: usize) {
+        let h = Complex::new(F::FRAC_1_SQRT_2(), F::zero());
+        self.apply(q, [[h, h], [h, -h]]);
+    }
+
+    fn joint_probability(&mut self, ids: &[usize]) -> f64 {
+        let odd = self
+            .probability(|index| ids.iter().filter(|&&id| index.bit(id as u64)).count() % 2 == 1);
+        odd / self.probability(|_| true)
+    }
+
+    fn mcx(&mut self, ctls: &[usize], q: usize) {
+        self.permute(ctls, |index| {
+            (flipped(index, q), Complex::new(F::one(), F::zero()))
+        });
+    }
+
+    fn mcy(&mut self, ctls: &[usize], q: usize) {
+        self.permute(ctls, |index| {
+            let phase = if index.bit(q as u64) {
+                -Complex::i()
+            } else {
+                Complex::i()
+            };
+            (flipped(index, q), phase)
+        });
+    }
+
+    fn mcz(&mut self, ctls: &[usize], q: usize) {
+        self.phase(ctls, q, Complex::new(-F::one(), F::zero()));
+    }
+
+    fn measure(&mut self, q: usize) -> bool {
+        let one = self.probability(|index| index.bit(q as u64)) / self.probability(|_| true);
+        let result = self.rng.gen::<f64>() < one;
+        self.collapse(|index| index.bit(q as u64) == result);
+        result
+    }
+
+    fn measure_as(&mut self, q: usize, one: bool) -> bool {
+        if self.probability(|index| index.bit(q as u64) == one) > 0.0 {
+            self.collapse(|index| index.bit(q as u64) == one);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.state.keys().all(|index| !index.bit(q as u64))
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.rotation(q, theta, |cos, sin| {
+            let cos = Complex::new(cos, F::zero());
+            let sin = Complex::new(F::zero(), -sin);
+            [[cos, sin], [sin, cos]]
+        });
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.rotation(q, theta, |cos, sin| {
+            let (cos, sin) = (Complex::new(cos, F::zero()), Complex::new(sin, F::zero()));
+            [[cos, -sin], [sin, cos]]
+        });
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.rotation(q, theta, |cos, sin| {
+            let zero = Complex::default();
+            [
+                [Complex::new(cos, -sin), zero],
+                [zero, Complex::new(cos, sin)],
+            ]
+        });
+    }
+
+    fn s(&mut self, q: usize) {
+        self.phase(&[], q, Complex::i());
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.phase(&[], q, -Complex::i());
+    }
+
+    fn set_rng_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn swap_qubit_ids(&mut self, q0: usize, q1: usize) {
+        self.permute(&[], |index| {
+            let mut swapped = index.clone();
+            swapped.set_bit(q0 as u64, index.bit(q1 as u64));
+            swapped.set_bit(q1 as u64, index.bit(q0 as u64));
+            (swapped, Complex::new(F::one(), F::zero()))
+        });
+    }
+
+    fn t(&mut self, q: usize) {
+        self.phase(&[], q, Complex::new(F::FRAC_1_SQRT_2(), F::FRAC_1_SQRT_2()));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.phase(
+            &[],
+            q,
+            Complex::new(F::FRAC_1_SQRT_2(), -F::FRAC_1_SQRT_2()),
+        );
+    }
+
+    fn x(&mut self, q: usize) {
+        self.mcx(&[], q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.mcy(&[], q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.mcz(&[], q);
+    }
+}
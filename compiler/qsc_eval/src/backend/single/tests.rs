@@ -0,0 +1,155 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::SingleStateVector;
+use crate::backend::{Backend, BasisPattern, Precision, SparseSim, StateVector};
+use num_bigint::BigUint;
+use num_complex::Complex32;
+use qsc_fir::fir::Pauli;
+
+/// Asserts that the states have the same amplitudes, ignoring the negligible ones that either
+/// simulator may have kept.
+fn assert_same_state(single: &mut SparseSim, double: &mut SparseSim) {
+    let (mut single_state, single_count) = single.capture_quantum_state();
+    let (mut double_state, double_count) = double.capture_quantum_state();
+    single_state.retain(|(_, amplitude)| amplitude.norm() > 1e-6);
+    double_state.retain(|(_, amplitude)| amplitude.norm() > 1e-6);
+    assert_eq!(single_count, double_count);
+    assert_eq!(single_state.len(), double_state.len(), "{single_state:?}");
+    for ((single_index, single), (double_index, double)) in single_state.iter().zip(&double_state) {
+        assert_eq!(single_index, double_index);
+        assert!((single - double).norm() < 1e-6, "{single} != {double}");
+    }
+}
+
+#[test]
+fn gates_match_double_precision() {
+    let mut single = SparseSim::with_precision(Precision::Single);
+    let mut double = SparseSim::new();
+    let mut replay = SparseSim::for_replay(Precision::Double);
+    for sim in [&mut single, &mut double, &mut replay] {
+        let (q0, q1, q2) = (
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+        );
+        sim.h(q0);
+        sim.cx(q0, q1);
+        sim.ry(0.3, q2);
+        sim.t(q2);
+        sim.rxx(1.1, q1, q2);
+        sim.cy(q2, q0);
+        sim.s(q1);
+        sim.rz(-0.7, q0);
+        sim.ccx(q0, q2, q1);
+        sim.cz(q1, q2);
+        sim.swap(q0, q2);
+    }
+    assert_same_state(&mut single, &mut double);
+    assert_same_state(&mut replay, &mut double);
+    assert_eq!(single.precision(), Precision::Single);
+    assert_eq!(double.precision(), Precision::Double);
+}
+
+#[test]
+fn state_lists_qubits_in_allocation_order() {
+    let mut sim = SparseSim::with_precision(Precision::Single);
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.qubit_release(q1);
+    sim.x(q2);
+    let (state, count) = sim.capture_quantum_state();
+    assert_eq!(count, 2);
+    assert_eq!(state.len(), 1);
+    assert_eq!(state[0].0, BigUint::from(0b11u8));
+
+    // A released qubit is reset, so its ID is allocated again in the zero state.
+    assert_eq!(sim.qubit_allocate(), q1);
+    assert!(sim.qubit_is_zero(q1));
+}
+
+#[test]
+fn matching_state_skips_released_qubits() {
+    let mut state = SingleStateVector::new();
+    let q0 = state.allocate();
+    let q1 = state.allocate();
+    let q2 = state.allocate();
+    state.h(q0);
+    state.release(q1);
+    state.x(q2);
+    // The qubit with ID 2 is at position 1 once the qubit with ID 1 is released.
+    let pattern = BasisPattern::default().fix(0, true).fix(1, true);
+    let (matching, count) = state.matching_state(&pattern);
+    assert_eq!(count, 2);
+    assert_eq!(
+        matching
+            .iter()
+            .map(|(index, _)| index.clone())
+            .collect::<Vec<_>>(),
+        [BigUint::from(0b11u8)]
+    );
+}
+
+#[test]
+fn seeded_measurements_are_repeatable() {
+    let run = || {
+        let mut sim = SparseSim::with_precision(Precision::Single);
+        sim.set_seed(Some(7));
+        (0..16)
+            .map(|_| {
+                let q = sim.qubit_allocate();
+                sim.h(q);
+                let result = sim.mresetz(q);
+                sim.qubit_release(q);
+                result
+            })
+            .collect::<Vec<_>>()
+    };
+    let results = run();
+    assert_eq!(results, run());
+    assert!(
+        results.contains(&true) && results.contains(&false),
+        "{results:?}"
+    );
+}
+
+#[test]
+fn measurement_probability_matches_double_precision() {
+    let mut single = SparseSim::with_precision(Precision::Single);
+    let mut double = SparseSim::new();
+    let probabilities = [&mut single, &mut double].map(|sim| {
+        let (q0, q1) = (sim.qubit_allocate(), sim.qubit_allocate());
+        sim.rx(0.9, q0);
+        sim.ry(0.4, q1);
+        sim.measurement_probability(&[(Pauli::X, q0), (Pauli::Z, q1)])
+            .expect("state should be inspected")
+    });
+    assert!(
+        (probabilities[0] - probabilities[1]).abs() < 1e-6,
+        "{probabilities:?}"
+    );
+}
+
+#[test]
+fn measurement_records_norm_error() {
+    let mut state = SingleStateVector::new();
+    let q = state.allocate();
+    state.h(q);
+    assert!(state.norm_error() < 1e-6);
+
+    // A norm that has drifted to 0.81 is renormalized by the measurement.
+    for amplitude in state.state.values_mut() {
+        *amplitude *= 0.9;
+    }
+    state.measure(q);
+    assert!(
+        (state.norm_error() - 0.19).abs() < 1e-6,
+        "{}",
+        state.norm_error()
+    );
+    let norm: f32 = state.state.values().map(Complex32::norm_sqr).sum();
+    assert!((norm - 1.0).abs() < 1e-6);
+    assert!(SparseSim::new().norm_error().abs() < f64::EPSILON);
+}
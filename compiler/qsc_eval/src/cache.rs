@@ -0,0 +1,210 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Caches the gates applied by operations that are called repeatedly with the same arguments, such
+//! as a QFT applied in a loop, so that later calls apply the gates directly instead of evaluating
+//! the operation again.
+//!
+//! Qubits in the arguments are replaced by their position of first appearance, so calls on
+//! different qubits in the same pattern share a cache entry. The first call with given arguments is
+//! only noted, the second is recorded, and the calls after it are replayed. A recording is thrown
+//! away if the operation does anything whose effect could differ between calls: measuring,
+//! allocating qubits, drawing random numbers, producing output or applying a gate to a qubit that
+//! it was not given.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    intrinsic,
+    val::{FunctorApp, Qubit, Value},
+};
+use qsc_fir::fir::{LocalItemId, PackageId, StoreItemId};
+use rustc_hash::FxHashMap;
+use std::{rc::Rc, sync::Arc};
+
+/// The largest number of gates recorded for one call, which bounds the memory used by the cache.
+const MAX_GATES: usize = 1 << 16;
+
+/// A call to an intrinsic whose qubits are positions in the arguments of the cached call.
+pub(crate) struct Gate {
+    pub(crate) name: Arc<str>,
+    pub(crate) arg: Value,
+}
+
+enum Entry {
+    /// The operation was called once with the arguments.
+    Seen,
+    /// The operation can't be cached for the arguments.
+    Uncacheable,
+    Cached(Rc<[Gate]>),
+}
+
+struct Recording {
+    key: (PackageId, LocalItemId, FunctorApp),
+    arg: Value,
+    qubits: Vec<usize>,
+    /// The length of the call stack inside the recorded call.
+    depth: usize,
+    gates: Vec<Gate>,
+    valid: bool,
+}
+
+#[derive(Default)]
+pub struct OperationCache {
+    entries: FxHashMap<(PackageId, LocalItemId, FunctorApp), Vec<(Value, Entry)>>,
+    recordings: Vec<Recording>,
+    hits: u64,
+}
+
+impl OperationCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of calls whose gates were replayed from the cache.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of gate sequences in the cache.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries
+            .values()
+            .flatten()
+            .filter(|(_, entry)| matches!(entry, Entry::Cached(_)))
+            .count()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up a call to the operation, whose body will run at the given depth of the call
+    /// stack. Returns the gates to apply instead of the call and the qubits they apply to, if the
+    /// call is cached. Otherwise, the call is recorded if it was seen before.
+    pub(crate) fn lookup(
+        &mut self,
+        id: StoreItemId,
+        functor: FunctorApp,
+        arg: &Value,
+        depth: usize,
+    ) -> Option<(Rc<[Gate]>, Vec<usize>)> {
+        let key = (id.package, id.item, functor);
+        let mut qubits = Vec::new();
+        let arg = map_qubits(arg, &mut |q| {
+            Some(qubits.iter().position(|&p| p == q).unwrap_or_else(|| {
+                qubits.push(q);
+                qubits.len() - 1
+            }))
+        })
+        .expect("qubits should always be mapped");
+
+        let entries = self.entries.entry(key).or_default();
+        match entries.iter().find(|(entry_arg, _)| *entry_arg == arg) {
+            Some((_, Entry::Cached(gates))) => {
+                self.hits += 1;
+                Some((gates.clone(), qubits))
+            }
+            Some((_, Entry::Uncacheable)) => None,
+            Some((_, Entry::Seen)) => {
+                self.recordings.push(Recording {
+                    key,
+                    arg,
+                    qubits,
+                    depth,
+                    gates: Vec::new(),
+                    valid: true,
+                });
+                None
+            }
+            None => {
+                entries.push((arg, Entry::Seen));
+                None
+            }
+        }
+    }
+
+    /// Adds a call to an intrinsic to the calls being recorded.
+    pub(crate) fn record(&mut self, name: &Arc<str>, arg: &Value) {
+        let replayable = intrinsic::is_replayable(name);
+        let pure = intrinsic::is_pure(name);
+        for recording in self.recordings.iter_mut().filter(|r| r.valid) {
+            if replayable {
+                let qubits = &recording.qubits;
+                match map_qubits(arg, &mut |q| qubits.iter().position(|&p| p == q)) {
+                    Some(arg) if recording.gates.len() < MAX_GATES => recording.gates.push(Gate {
+                        name: name.clone(),
+                        arg,
+                    }),
+                    _ => recording.valid = false,
+                }
+            } else if !pure {
+                recording.valid = false;
+            }
+        }
+    }
+
+    /// Finishes the recording of the call whose body ran at the given depth of the call stack, if
+    /// any.
+    pub(crate) fn leave(&mut self, depth: usize) {
+        if self.recordings.last().map(|r| r.depth) != Some(depth) {
+            return;
+        }
+        let recording = self.recordings.pop().expect("recording should be present");
+        let entry = if recording.valid {
+            Entry::Cached(recording.gates.into())
+        } else {
+            Entry::Uncacheable
+        };
+        if let Some(entries) = self.entries.get_mut(&recording.key) {
+            if let Some((_, slot)) = entries.iter_mut().find(|(arg, _)| *arg == recording.arg) {
+                *slot = entry;
+            }
+        }
+    }
+
+    /// Discards the calls being recorded, which is needed if the evaluation stopped before they
+    /// finished.
+    pub(crate) fn abandon(&mut self) {
+        self.recordings.clear();
+    }
+}
+
+/// Replaces the qubits in the gate's argument with the qubits at their positions.
+pub(crate) fn substitute(arg: &Value, qubits: &[usize]) -> Value {
+    map_qubits(arg, &mut |q| qubits.get(q).copied()).expect("cached qubit should be mapped")
+}
+
+/// Replaces each qubit in the value, or returns `None` if a qubit can't be replaced.
+fn map_qubits(value: &Value, f: &mut impl FnMut(usize) -> Option<usize>) -> Option<Value> {
+    Some(match value {
+        Value::Qubit(q) => Value::Qubit(Qubit(f(q.0)?)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| map_qubits(item, f))
+                .collect::<Option<Vec<_>>>()?
+                .into(),
+        ),
+        Value::Tuple(items) => Value::Tuple(
+            items
+                .iter()
+                .map(|item| map_qubits(item, f))
+                .collect::<Option<Rc<[_]>>>()?,
+        ),
+        Value::Closure(fixed, id, functor) => Value::Closure(
+            fixed
+                .iter()
+                .map(|item| map_qubits(item, f))
+                .collect::<Option<Rc<[_]>>>()?,
+            *id,
+            *functor,
+        ),
+        _ => value.clone(),
+    })
+}
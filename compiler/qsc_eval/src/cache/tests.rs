@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{substitute, OperationCache};
+use crate::val::{FunctorApp, Qubit, Value};
+use qsc_fir::fir::{PackageId, StoreItemId};
+use std::sync::Arc;
+
+fn operation() -> StoreItemId {
+    StoreItemId {
+        package: PackageId::from(2),
+        item: 0.into(),
+    }
+}
+
+fn qubits(ids: &[usize]) -> Value {
+    Value::Tuple(ids.iter().map(|&id| Value::Qubit(Qubit(id))).collect())
+}
+
+#[test]
+fn second_call_is_recorded_and_later_calls_are_replayed() {
+    let mut cache = OperationCache::new();
+    let functor = FunctorApp::default();
+    let h: Arc<str> = "__quantum__qis__h__body".into();
+    let cx: Arc<str> = "__quantum__qis__cx__body".into();
+
+    assert!(cache
+        .lookup(operation(), functor, &qubits(&[0, 1]), 1)
+        .is_none());
+    cache.leave(1);
+    assert!(cache.is_empty());
+
+    assert!(cache
+        .lookup(operation(), functor, &qubits(&[4, 2]), 1)
+        .is_none());
+    cache.record(&h, &Value::Qubit(Qubit(4)));
+    cache.record(&"Length".into(), &Value::Array(Vec::new().into()));
+    cache.record(&cx, &qubits(&[4, 2]));
+    cache.leave(1);
+    assert_eq!(cache.len(), 1);
+
+    let (gates, ids) = cache
+        .lookup(operation(), functor, &qubits(&[7, 3]), 1)
+        .expect("call should be cached");
+    assert_eq!(cache.hits(), 1);
+    let replayed = gates
+        .iter()
+        .map(|gate| (gate.name.to_string(), substitute(&gate.arg, &ids)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        replayed,
+        [
+            (h.to_string(), Value::Qubit(Qubit(7))),
+            (cx.to_string(), qubits(&[7, 3])),
+        ]
+    );
+}
+
+#[test]
+fn different_arguments_are_cached_separately() {
+    let mut cache = OperationCache::new();
+    let functor = FunctorApp::default();
+    let arg = Value::Tuple(vec![Value::Double(0.5), Value::Qubit(Qubit(0))].into());
+    let other = Value::Tuple(vec![Value::Double(0.25), Value::Qubit(Qubit(0))].into());
+    for _ in 0..2 {
+        assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+        cache.leave(1);
+    }
+    assert!(cache.lookup(operation(), functor, &other, 1).is_none());
+    assert!(cache
+        .lookup(
+            operation(),
+            FunctorApp {
+                adjoint: true,
+                controlled: 0
+            },
+            &arg,
+            1
+        )
+        .is_none());
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_some());
+}
+
+#[test]
+fn measurement_makes_call_uncacheable() {
+    let mut cache = OperationCache::new();
+    let functor = FunctorApp::default();
+    let arg = Value::Qubit(Qubit(0));
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    cache.record(&"__quantum__qis__m__body".into(), &arg);
+    cache.leave(1);
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    assert!(cache.is_empty());
+    assert_eq!(cache.hits(), 0);
+}
+
+#[test]
+fn gate_on_other_qubit_makes_call_uncacheable() {
+    let mut cache = OperationCache::new();
+    let functor = FunctorApp::default();
+    let arg = Value::Qubit(Qubit(0));
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    cache.record(&"__quantum__qis__x__body".into(), &Value::Qubit(Qubit(1)));
+    cache.leave(1);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn nested_calls_are_recorded_by_their_callers() {
+    let mut cache = OperationCache::new();
+    let functor = FunctorApp::default();
+    let inner = StoreItemId {
+        package: PackageId::from(2),
+        item: 1.into(),
+    };
+    let arg = Value::Qubit(Qubit(3));
+    let x: Arc<str> = "__quantum__qis__x__body".into();
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    cache.leave(1);
+    assert!(cache.lookup(operation(), functor, &arg, 1).is_none());
+    assert!(cache.lookup(inner, functor, &arg, 2).is_none());
+    cache.record(&x, &arg);
+    cache.leave(2);
+    cache.leave(1);
+    assert_eq!(cache.len(), 1);
+    let (gates, _) = cache
+        .lookup(operation(), functor, &arg, 1)
+        .expect("outer call should be cached");
+    assert_eq!(gates.len(), 1);
+}
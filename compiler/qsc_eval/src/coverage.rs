@@ -0,0 +1,76 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Records which parts of a program an evaluation reached, so that a test run can report how much
+//! of the code under test it exercised. Coverage is kept across evaluations until it is cleared,
+//! so the runs of a whole test suite can be recorded together.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_fir::fir::{BlockId, ExprId, PackageId, StmtId};
+use rustc_hash::FxHashMap;
+
+/// The number of times each statement, branch and specialization ran.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    stmts: FxHashMap<(PackageId, StmtId), u64>,
+    /// The times the `then` and `else` branches of each `if` were taken, keyed by its `then`
+    /// expression.
+    branches: FxHashMap<(PackageId, ExprId), [u64; 2]>,
+    /// The calls to each specialization, keyed by the block that implements it.
+    specs: FxHashMap<(PackageId, BlockId), u64>,
+}
+
+impl Coverage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of times the statement ran.
+    #[must_use]
+    pub fn stmt(&self, package: PackageId, stmt: StmtId) -> u64 {
+        self.stmts
+            .get(&(package, stmt))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of times the `then` and `else` branches of the `if` with the given `then`
+    /// expression were taken. An `if` without an `else` takes its `else` branch when its
+    /// condition is false.
+    #[must_use]
+    pub fn branch(&self, package: PackageId, then_expr: ExprId) -> [u64; 2] {
+        self.branches
+            .get(&(package, then_expr))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of calls to the specialization implemented by the block.
+    #[must_use]
+    pub fn spec(&self, package: PackageId, block: BlockId) -> u64 {
+        self.specs
+            .get(&(package, block))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stmts.is_empty() && self.branches.is_empty() && self.specs.is_empty()
+    }
+
+    pub(crate) fn record_stmt(&mut self, package: PackageId, stmt: StmtId) {
+        *self.stmts.entry((package, stmt)).or_default() += 1;
+    }
+
+    pub(crate) fn record_branch(&mut self, package: PackageId, then_expr: ExprId, taken: bool) {
+        self.branches.entry((package, then_expr)).or_default()[usize::from(!taken)] += 1;
+    }
+
+    pub(crate) fn record_spec(&mut self, package: PackageId, block: BlockId) {
+        *self.specs.entry((package, block)).or_default() += 1;
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Coverage;
+use qsc_fir::fir::PackageId;
+
+#[test]
+fn hits_are_counted_per_package() {
+    let mut coverage = Coverage::new();
+    assert!(coverage.is_empty());
+    let package = PackageId::from(2);
+    coverage.record_stmt(package, 3u32.into());
+    coverage.record_stmt(package, 3u32.into());
+    coverage.record_stmt(PackageId::from(1), 3u32.into());
+    assert_eq!(coverage.stmt(package, 3u32.into()), 2);
+    assert_eq!(coverage.stmt(PackageId::from(1), 3u32.into()), 1);
+    assert_eq!(coverage.stmt(package, 4u32.into()), 0);
+
+    coverage.record_spec(package, 0u32.into());
+    assert_eq!(coverage.spec(package, 0u32.into()), 1);
+    assert!(!coverage.is_empty());
+}
+
+#[test]
+fn branches_count_then_and_else_separately() {
+    let mut coverage = Coverage::new();
+    let package = PackageId::from(2);
+    coverage.record_branch(package, 5u32.into(), true);
+    coverage.record_branch(package, 5u32.into(), false);
+    coverage.record_branch(package, 5u32.into(), false);
+    assert_eq!(coverage.branch(package, 5u32.into()), [1, 2]);
+    assert_eq!(coverage.branch(package, 6u32.into()), [0, 0]);
+}
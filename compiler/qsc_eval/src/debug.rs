@@ -2,8 +2,9 @@
 // Licensed under the MIT License.
 
 use qsc_data_structures::span::Span;
+use std::{rc::Rc, str::FromStr};
 
-use crate::val::FunctorApp;
+use crate::{val::FunctorApp, VariableInfo};
 use qsc_fir::fir;
 use qsc_fir::fir::{PackageId, StoreItemId};
 use qsc_hir::hir;
@@ -37,6 +38,11 @@ impl CallStack {
         self.frames
     }
 
+    #[must_use]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
     pub fn push_frame(&mut self, frame: Frame) {
         self.frames.push(frame);
     }
@@ -46,6 +52,98 @@ impl CallStack {
     }
 }
 
+/// A condition on the number of times a breakpoint has been reached
+/// that must hold for the breakpoint to take effect.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HitCondition {
+    /// The breakpoint takes effect only on the given hit.
+    Equal(u32),
+    /// The breakpoint takes effect on the given hit and every hit after it.
+    AtLeast(u32),
+    /// The breakpoint takes effect on every hit that is a multiple of the given count.
+    Multiple(u32),
+}
+
+impl HitCondition {
+    #[must_use]
+    pub fn is_met(self, hits: u32) -> bool {
+        match self {
+            HitCondition::Equal(count) => hits == count,
+            HitCondition::AtLeast(count) => hits >= count,
+            HitCondition::Multiple(count) => count != 0 && hits % count == 0,
+        }
+    }
+}
+
+impl FromStr for HitCondition {
+    type Err = ();
+
+    /// Parses a hit condition written as `N` or `== N` (exactly the Nth hit),
+    /// `>= N` (the Nth hit and after) or `% N` (every Nth hit).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (ctor, count): (fn(u32) -> Self, &str) = if let Some(count) = s.strip_prefix(">=") {
+            (HitCondition::AtLeast, count)
+        } else if let Some(count) = s.strip_prefix('%') {
+            (HitCondition::Multiple, count)
+        } else if let Some(count) = s.strip_prefix("==") {
+            (HitCondition::Equal, count)
+        } else {
+            (HitCondition::Equal, s)
+        };
+        count.trim().parse().map(ctor).map_err(|_| ())
+    }
+}
+
+/// Additional behavior attached to a breakpoint.
+/// A breakpoint without a condition stops execution every time it is reached.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BreakpointCondition {
+    /// When set, the breakpoint only takes effect when the hit condition holds.
+    pub hit_condition: Option<HitCondition>,
+    /// When set, the breakpoint is a logpoint: instead of stopping, the message
+    /// is emitted with each `{name}` replaced by the value of the local `name`.
+    pub log_message: Option<Rc<str>>,
+}
+
+/// Formats a logpoint message, replacing each `{name}` with the value of the
+/// matching variable as shown by `display`. Use `{{` and `}}` for literal braces.
+/// Names that do not match a variable are left as written.
+#[must_use]
+pub fn format_log_message(
+    message: &str,
+    variables: &[VariableInfo],
+    display: impl Fn(&VariableInfo) -> String,
+) -> String {
+    let mut formatted = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                formatted.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                formatted.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                match variables.iter().rev().find(|var| *var.name == *name.trim()) {
+                    Some(var) => formatted.push_str(&display(var)),
+                    None => {
+                        formatted.push('{');
+                        formatted.push_str(&name);
+                        formatted.push('}');
+                    }
+                }
+            }
+            c => formatted.push(c),
+        }
+    }
+    formatted
+}
+
 #[must_use]
 pub fn map_hir_package_to_fir(package: hir::PackageId) -> fir::PackageId {
     fir::PackageId::from(<hir::PackageId as Into<usize>>::into(package))
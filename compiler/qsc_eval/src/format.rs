@@ -0,0 +1,231 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Displays values for people to read, the same way wherever they are shown: in interpolated
+//! strings, when the REPL echoes the value of a line and in the variables view of the debugger.
+//! Long arrays and deeply nested tuples can be cut short, and hosts can choose how the values of
+//! particular user-defined types are shown.
+
+#[cfg(test)]
+mod tests;
+
+use crate::val::{FunctorApp, Value, DEFAULT_RANGE_STEP};
+use qsc_fir::{
+    fir::{Global, PackageId, PackageStoreLookup, Pauli, Res, StoreItemId},
+    ty::{Ty, Udt},
+};
+use rustc_hash::FxHashMap;
+use std::{
+    fmt::{self, Write},
+    rc::Rc,
+};
+
+/// Displays the values of a user-defined type in place of their underlying tuples.
+pub type UdtFormatter = Rc<dyn Fn(&Value) -> String>;
+
+/// How the items left out of an array or tuple are shown.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Ellipsis {
+    /// As `...`.
+    #[default]
+    Plain,
+    /// As `...` followed by how many items were left out, such as `...7 more`.
+    Counted,
+}
+
+/// How values are displayed. By default, values are displayed in full.
+#[derive(Clone, Default)]
+pub struct ValueFormat {
+    max_depth: Option<usize>,
+    max_items: Option<usize>,
+    ellipsis: Ellipsis,
+    formatters: FxHashMap<Rc<str>, UdtFormatter>,
+}
+
+impl ValueFormat {
+    /// Shows the arrays and tuples that are nested inside `depth` others as `[...]` and `(...)`.
+    #[must_use]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Shows at most `items` items of each array and tuple, followed by an ellipsis for the rest.
+    #[must_use]
+    pub fn with_max_items(mut self, items: usize) -> Self {
+        self.max_items = Some(items);
+        self
+    }
+
+    /// Sets how the items left out of an array or tuple are shown.
+    #[must_use]
+    pub fn with_ellipsis(mut self, ellipsis: Ellipsis) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Shows the values of the user-defined types with the given name using the formatter. Only
+    /// values whose types are known are shown this way; see [`ValueFormat::display_typed`].
+    #[must_use]
+    pub fn with_formatter(
+        mut self,
+        udt: &str,
+        formatter: impl Fn(&Value) -> String + 'static,
+    ) -> Self {
+        self.formatters.insert(udt.into(), Rc::new(formatter));
+        self
+    }
+
+    /// Displays a value whose type isn't known, so that user-defined types are shown as their
+    /// underlying tuples.
+    #[must_use]
+    pub fn display(&self, value: &Value) -> String {
+        let mut out = String::new();
+        self.write(&mut out, value, None, &|_| None, 0)
+            .expect("string should be writable");
+        out
+    }
+
+    /// Displays a value of the given type, where the type is from the given package, such as the
+    /// type of one of its expressions.
+    #[must_use]
+    pub fn display_typed(
+        &self,
+        globals: &impl PackageStoreLookup,
+        package: PackageId,
+        value: &Value,
+        ty: &Ty,
+    ) -> String {
+        let udts = |id: StoreItemId| match globals.get_global(id) {
+            Some(Global::Udt(udt)) => Some(udt),
+            _ => None,
+        };
+        let mut out = String::new();
+        self.write(&mut out, value, Some((package, ty)), &udts, 0)
+            .expect("string should be writable");
+        out
+    }
+
+    pub(crate) fn write<'a>(
+        &self,
+        f: &mut dyn Write,
+        value: &Value,
+        ty: Option<(PackageId, &Ty)>,
+        udts: &dyn Fn(StoreItemId) -> Option<&'a Udt>,
+        depth: usize,
+    ) -> fmt::Result {
+        if let Some((package, Ty::Udt(Res::Item(id)))) = ty {
+            let id = StoreItemId {
+                package: id.package.unwrap_or(package),
+                item: id.item,
+            };
+            if let Some(udt) = udts(id) {
+                if let Some(formatter) = self.formatters.get(&*udt.name) {
+                    return f.write_str(&formatter(value));
+                }
+                let pure = udt.get_pure_ty();
+                return self.write(f, value, Some((id.package, &pure)), udts, depth);
+            }
+        }
+
+        match value {
+            Value::Array(items) => {
+                let item_ty = match ty {
+                    Some((package, Ty::Array(item))) => Some((package, &**item)),
+                    _ => None,
+                };
+                let items = items.iter().map(|item| (item, item_ty));
+                self.write_items(f, ["[", "]"], items, udts, depth)
+            }
+            Value::Tuple(items) => {
+                let item_tys = match ty {
+                    Some((package, Ty::Tuple(tys))) if tys.len() == items.len() => {
+                        Some((package, tys))
+                    }
+                    _ => None,
+                };
+                let items = items.iter().enumerate().map(|(index, item)| {
+                    (item, item_tys.map(|(package, tys)| (package, &tys[index])))
+                });
+                self.write_items(f, ["(", ")"], items, udts, depth)
+            }
+            _ => write_scalar(f, value),
+        }
+    }
+
+    fn write_items<'a, 'b>(
+        &self,
+        f: &mut dyn Write,
+        [open, close]: [&str; 2],
+        items: impl ExactSizeIterator<Item = (&'b Value, Option<(PackageId, &'b Ty)>)>,
+        udts: &dyn Fn(StoreItemId) -> Option<&'a Udt>,
+        depth: usize,
+    ) -> fmt::Result {
+        let len = items.len();
+        f.write_str(open)?;
+        if len > 0 && self.max_depth.is_some_and(|max| depth >= max) {
+            f.write_str("...")?;
+            return f.write_str(close);
+        }
+
+        let shown = self.max_items.map_or(len, |max| max.min(len));
+        for (index, (item, ty)) in items.take(shown).enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            self.write(f, item, ty, udts, depth + 1)?;
+        }
+        if shown < len {
+            if shown > 0 {
+                f.write_str(", ")?;
+            }
+            match self.ellipsis {
+                Ellipsis::Plain => f.write_str("...")?,
+                Ellipsis::Counted => write!(f, "...{} more", len - shown)?,
+            }
+        } else if open == "(" && len == 1 {
+            f.write_str(",")?;
+        }
+        f.write_str(close)
+    }
+}
+
+fn write_scalar(f: &mut dyn Write, value: &Value) -> fmt::Result {
+    match value {
+        Value::Array(_) | Value::Tuple(_) => unreachable!("value should not have items"),
+        Value::BigInt(v) => write!(f, "{v}"),
+        Value::Bool(v) => write!(f, "{v}"),
+        Value::Closure(..) => f.write_str("<closure>"),
+        Value::Double(v) => {
+            if (v.floor() - v.ceil()).abs() < f64::EPSILON {
+                // The value is a whole number, which by convention is displayed with one decimal point
+                // to differentiate it from an integer value.
+                write!(f, "{v:.1}")
+            } else {
+                write!(f, "{v}")
+            }
+        }
+        Value::Global(id, functor) if functor == &FunctorApp::default() => write!(f, "{id}"),
+        Value::Global(id, functor) => write!(f, "{functor} {id}"),
+        Value::Int(v) => write!(f, "{v}"),
+        Value::Pauli(v) => match v {
+            Pauli::I => f.write_str("PauliI"),
+            Pauli::X => f.write_str("PauliX"),
+            Pauli::Z => f.write_str("PauliZ"),
+            Pauli::Y => f.write_str("PauliY"),
+        },
+        Value::Qubit(v) => write!(f, "Qubit{}", (v.0)),
+        &Value::Range(start, step, end) => match (start, step, end) {
+            (Some(start), DEFAULT_RANGE_STEP, Some(end)) => write!(f, "{start}..{end}"),
+            (Some(start), DEFAULT_RANGE_STEP, None) => write!(f, "{start}..."),
+            (Some(start), step, Some(end)) => write!(f, "{start}..{step}..{end}"),
+            (Some(start), step, None) => write!(f, "{start}..{step}..."),
+            (None, DEFAULT_RANGE_STEP, Some(end)) => write!(f, "...{end}"),
+            (None, DEFAULT_RANGE_STEP, None) => f.write_str("..."),
+            (None, step, Some(end)) => write!(f, "...{step}..{end}"),
+            (None, step, None) => write!(f, "...{step}..."),
+        },
+        Value::Result(v) => f.write_str(if v.unwrap_bool() { "One" } else { "Zero" }),
+        Value::String(v) => f.write_str(v),
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Ellipsis, ValueFormat};
+use crate::val::Value;
+use num_bigint::BigInt;
+use std::rc::Rc;
+
+fn array(items: impl IntoIterator<Item = Value>) -> Value {
+    Value::Array(Rc::new(items.into_iter().collect()))
+}
+
+fn tuple(items: impl IntoIterator<Item = Value>) -> Value {
+    Value::Tuple(items.into_iter().collect())
+}
+
+fn ints(range: std::ops::Range<i64>) -> Value {
+    array(range.map(Value::Int))
+}
+
+#[test]
+fn values_are_displayed_in_full_by_default() {
+    let value = tuple([
+        ints(0..5),
+        tuple([Value::Double(1.0)]),
+        Value::BigInt(BigInt::from(10).pow(30)),
+    ]);
+    let expected = "([0, 1, 2, 3, 4], (1.0,), 1000000000000000000000000000000)";
+    assert_eq!(ValueFormat::default().display(&value), expected);
+    assert_eq!(value.to_string(), expected);
+}
+
+#[test]
+fn items_past_the_limit_are_left_out() {
+    let value = tuple([ints(0..10), ints(0..2), tuple([Value::Int(7)])]);
+    let format = ValueFormat::default().with_max_items(2);
+    assert_eq!(format.display(&value), "([0, 1, ...], [0, 1], ...)");
+    let format = format.with_ellipsis(Ellipsis::Counted);
+    assert_eq!(
+        format.display(&value),
+        "([0, 1, ...8 more], [0, 1], ...1 more)"
+    );
+    let format = ValueFormat::default().with_max_items(0);
+    assert_eq!(format.display(&value), "(...)");
+    assert_eq!(format.display(&Value::unit()), "()");
+}
+
+#[test]
+fn nesting_past_the_limit_is_left_out() {
+    let value = array([array([ints(0..2)]), array([]), ints(0..1)]);
+    let format = ValueFormat::default().with_max_depth(1);
+    assert_eq!(format.display(&value), "[[...], [], [...]]");
+    let format = ValueFormat::default().with_max_depth(0);
+    assert_eq!(format.display(&value), "[...]");
+}
@@ -0,0 +1,503 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+use std::{
+    fmt::{self, Display, Formatter},
+    future::Future,
+    pin::pin,
+    rc::Rc,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+/// A host that services the host I/O intrinsics declared in `Microsoft.Quantum.Host`.
+pub trait HostIo {
+    /// Reads the named parameter supplied by the host.
+    /// # Errors
+    /// Returns a message describing the failure if the parameter cannot be read.
+    fn read_parameter(&mut self, name: &str) -> Result<f64, String>;
+
+    /// Fetches a row of the named dataset supplied by the host.
+    /// # Errors
+    /// Returns a message describing the failure if the row cannot be fetched.
+    fn fetch_dataset_row(&mut self, dataset: &str, row: i64) -> Result<Vec<f64>, String>;
+}
+
+/// A host whose calls complete asynchronously, such as one that fetches datasets over the network.
+/// The evaluator runs each call to completion before it continues, by way of [`BlockingHost`].
+pub trait AsyncHostIo {
+    /// Reads the named parameter supplied by the host.
+    /// # Errors
+    /// Returns a message describing the failure if the parameter cannot be read.
+    fn read_parameter(&mut self, name: &str) -> impl Future<Output = Result<f64, String>>;
+
+    /// Fetches a row of the named dataset supplied by the host.
+    /// # Errors
+    /// Returns a message describing the failure if the row cannot be fetched.
+    fn fetch_dataset_row(
+        &mut self,
+        dataset: &str,
+        row: i64,
+    ) -> impl Future<Output = Result<Vec<f64>, String>>;
+}
+
+/// A host that services calls with an [`AsyncHostIo`], parking the evaluating thread until each
+/// call completes. It can be recorded and replayed like any other host.
+pub struct BlockingHost<H>(pub H);
+
+impl<H: AsyncHostIo> HostIo for BlockingHost<H> {
+    fn read_parameter(&mut self, name: &str) -> Result<f64, String> {
+        block_on(self.0.read_parameter(name))
+    }
+
+    fn fetch_dataset_row(&mut self, dataset: &str, row: i64) -> Result<Vec<f64>, String> {
+        block_on(self.0.fetch_dataset_row(dataset, row))
+    }
+}
+
+/// Wakes a thread that is parked waiting for a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// A host I/O call and the value the host returned for it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayEntry {
+    ReadParameter {
+        name: String,
+        value: f64,
+    },
+    FetchDatasetRow {
+        dataset: String,
+        row: i64,
+        values: Vec<f64>,
+    },
+}
+
+/// An ordered log of the host I/O performed during an execution,
+/// which can be replayed to reproduce that execution without the host.
+///
+/// The log is serialized as text, one call per line, with tab-separated fields.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplayLog {
+    pub entries: Vec<ReplayEntry>,
+}
+
+impl Display for ReplayLog {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for entry in &self.entries {
+            match entry {
+                ReplayEntry::ReadParameter { name, value } => {
+                    writeln!(f, "ReadParameter\t{}\t{value}", escape(name))?;
+                }
+                ReplayEntry::FetchDatasetRow {
+                    dataset,
+                    row,
+                    values,
+                } => {
+                    let values = values
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    writeln!(f, "FetchDatasetRow\t{}\t{row}\t{values}", escape(dataset))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ReplayLog {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (index, line) in s.lines().enumerate().filter(|(_, l)| !l.is_empty()) {
+            let invalid = || format!("invalid replay log entry on line {}", index + 1);
+            let fields = line.split('\t').collect::<Vec<_>>();
+            let entry = match fields.as_slice() {
+                ["ReadParameter", name, value] => ReplayEntry::ReadParameter {
+                    name: unescape(name),
+                    value: value.parse().map_err(|_| invalid())?,
+                },
+                ["FetchDatasetRow", dataset, row, values] => ReplayEntry::FetchDatasetRow {
+                    dataset: unescape(dataset),
+                    row: row.parse().map_err(|_| invalid())?,
+                    values: values
+                        .split(',')
+                        .filter(|v| !v.is_empty())
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?,
+                },
+                _ => return Err(invalid()),
+            };
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// A host that forwards calls to another host and records each result in a [`ReplayLog`].
+pub struct Recorder<H> {
+    host: H,
+    log: ReplayLog,
+}
+
+impl<H: HostIo> Recorder<H> {
+    #[must_use]
+    pub fn new(host: H) -> Self {
+        Self {
+            host,
+            log: ReplayLog::default(),
+        }
+    }
+
+    /// Returns the log of the calls recorded so far.
+    #[must_use]
+    pub fn log(&self) -> &ReplayLog {
+        &self.log
+    }
+
+    /// Consumes the recorder and returns the recorded log.
+    #[must_use]
+    pub fn into_log(self) -> ReplayLog {
+        self.log
+    }
+}
+
+impl<H: HostIo> HostIo for Recorder<H> {
+    fn read_parameter(&mut self, name: &str) -> Result<f64, String> {
+        let value = self.host.read_parameter(name)?;
+        self.log.entries.push(ReplayEntry::ReadParameter {
+            name: name.to_string(),
+            value,
+        });
+        Ok(value)
+    }
+
+    fn fetch_dataset_row(&mut self, dataset: &str, row: i64) -> Result<Vec<f64>, String> {
+        let values = self.host.fetch_dataset_row(dataset, row)?;
+        self.log.entries.push(ReplayEntry::FetchDatasetRow {
+            dataset: dataset.to_string(),
+            row,
+            values: values.clone(),
+        });
+        Ok(values)
+    }
+}
+
+/// A host that answers calls from a [`ReplayLog`], failing if the program
+/// makes a call that differs from the one recorded at that point.
+pub struct Replayer {
+    log: ReplayLog,
+    next: usize,
+}
+
+impl Replayer {
+    #[must_use]
+    pub fn new(log: ReplayLog) -> Self {
+        Self { log, next: 0 }
+    }
+
+    fn next_entry(&mut self, call: &str) -> Result<&ReplayEntry, String> {
+        let entry = self
+            .log
+            .entries
+            .get(self.next)
+            .ok_or_else(|| format!("replay log has no entry for call {call}"))?;
+        self.next += 1;
+        Ok(entry)
+    }
+}
+
+impl HostIo for Replayer {
+    fn read_parameter(&mut self, name: &str) -> Result<f64, String> {
+        let call = format!("ReadParameter({name:?})");
+        match self.next_entry(&call)? {
+            ReplayEntry::ReadParameter {
+                name: recorded,
+                value,
+            } if recorded == name => Ok(*value),
+            _ => Err(format!("replay diverged from log at call {call}")),
+        }
+    }
+
+    fn fetch_dataset_row(&mut self, dataset: &str, row: i64) -> Result<Vec<f64>, String> {
+        let call = format!("FetchDatasetRow({dataset:?}, {row})");
+        match self.next_entry(&call)? {
+            ReplayEntry::FetchDatasetRow {
+                dataset: recorded,
+                row: recorded_row,
+                values,
+            } if recorded == dataset && *recorded_row == row => Ok(values.clone()),
+            _ => Err(format!("replay diverged from log at call {call}")),
+        }
+    }
+}
+
+/// The dataset name and row index that `FetchDatasetRow` is called with.
+fn dataset_row(arg: &Value) -> Option<(&str, i64)> {
+    match arg {
+        Value::Tuple(items) => match &**items {
+            [Value::String(dataset), Value::Int(row)] => Some((dataset, *row)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A backend that services host I/O intrinsics with the given host and forwards
+/// everything else to the wrapped backend.
+pub struct HostIoBackend<'a, B, H> {
+    backend: &'a mut B,
+    host: &'a mut H,
+}
+
+impl<'a, B: Backend, H: HostIo> HostIoBackend<'a, B, H> {
+    #[must_use]
+    pub fn new(backend: &'a mut B, host: &'a mut H) -> Self {
+        Self { backend, host }
+    }
+}
+
+impl<'a, B: Backend, H: HostIo> Backend for HostIoBackend<'a, B, H> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.backend.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        self.backend.forced_m(q, one)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            "ReadParameter" => Some(match arg {
+                Value::String(name) => self.host.read_parameter(&name).map(Value::Double),
+                _ => Err("ReadParameter expects a parameter name".to_string()),
+            }),
+            "FetchDatasetRow" => Some(match dataset_row(&arg) {
+                Some((dataset, row)) => self.host.fetch_dataset_row(dataset, row).map(|values| {
+                    Value::Array(Rc::new(values.into_iter().map(Value::Double).collect()))
+                }),
+                None => Err("FetchDatasetRow expects a dataset name and a row index".to_string()),
+            }),
+            _ => self.backend.custom_intrinsic(name, arg),
+        }
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.backend.end_result_branch();
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{
+    AsyncHostIo, BlockingHost, HostIo, HostIoBackend, Recorder, ReplayEntry, ReplayLog, Replayer,
+};
+use crate::{
+    backend::{Backend, SparseSim},
+    val::Value,
+};
+use expect_test::expect;
+use std::{
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+struct TestHost;
+
+impl HostIo for TestHost {
+    fn read_parameter(&mut self, name: &str) -> Result<f64, String> {
+        match name {
+            "theta" => Ok(0.5),
+            _ => Err(format!("unknown parameter {name}")),
+        }
+    }
+
+    fn fetch_dataset_row(&mut self, dataset: &str, _row: i64) -> Result<Vec<f64>, String> {
+        match dataset {
+            "training\tset" => Ok(vec![1.0, -2.5, 3.0]),
+            _ => Err(format!("unknown dataset {dataset}")),
+        }
+    }
+}
+
+/// A future that is pending once, waking itself, before it is ready with its value.
+struct Deferred<T> {
+    value: Option<T>,
+    polled: bool,
+}
+
+impl<T: Unpin> Future for Deferred<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        if self.polled {
+            Poll::Ready(
+                self.value
+                    .take()
+                    .expect("future should not be polled after it is ready"),
+            )
+        } else {
+            self.polled = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn deferred<T>(value: T) -> Deferred<T> {
+    Deferred {
+        value: Some(value),
+        polled: false,
+    }
+}
+
+struct AsyncTestHost;
+
+impl AsyncHostIo for AsyncTestHost {
+    fn read_parameter(&mut self, name: &str) -> impl Future<Output = Result<f64, String>> {
+        deferred(TestHost.read_parameter(name))
+    }
+
+    fn fetch_dataset_row(
+        &mut self,
+        dataset: &str,
+        row: i64,
+    ) -> impl Future<Output = Result<Vec<f64>, String>> {
+        deferred(TestHost.fetch_dataset_row(dataset, row))
+    }
+}
+
+fn recorded_log() -> ReplayLog {
+    recorded_log_of(TestHost)
+}
+
+fn recorded_log_of(host: impl HostIo) -> ReplayLog {
+    let mut recorder = Recorder::new(host);
+    assert_eq!(recorder.read_parameter("theta"), Ok(0.5));
+    assert_eq!(
+        recorder.fetch_dataset_row("training\tset", 3),
+        Ok(vec![1.0, -2.5, 3.0])
+    );
+    assert!(recorder.read_parameter("phi").is_err());
+    recorder.into_log()
+}
+
+#[test]
+fn recorder_logs_successful_calls() {
+    let log = recorded_log();
+    assert_eq!(
+        log.entries,
+        vec![
+            ReplayEntry::ReadParameter {
+                name: "theta".to_string(),
+                value: 0.5
+            },
+            ReplayEntry::FetchDatasetRow {
+                dataset: "training\tset".to_string(),
+                row: 3,
+                values: vec![1.0, -2.5, 3.0]
+            },
+        ]
+    );
+}
+
+#[test]
+fn log_round_trips_through_text() {
+    let log = recorded_log();
+    let text = log.to_string();
+    expect![[r"
+        ReadParameter	theta	0.5
+        FetchDatasetRow	training\tset	3	1,-2.5,3
+    "]]
+    .assert_eq(&text);
+    assert_eq!(text.parse::<ReplayLog>(), Ok(log));
+}
+
+#[test]
+fn invalid_log_is_rejected() {
+    assert_eq!(
+        "ReadParameter\ttheta\tnot a number".parse::<ReplayLog>(),
+        Err("invalid replay log entry on line 1".to_string())
+    );
+}
+
+#[test]
+fn replayer_returns_recorded_values() {
+    let mut replayer = Replayer::new(recorded_log());
+    assert_eq!(replayer.read_parameter("theta"), Ok(0.5));
+    assert_eq!(
+        replayer.fetch_dataset_row("training\tset", 3),
+        Ok(vec![1.0, -2.5, 3.0])
+    );
+    assert_eq!(
+        replayer.read_parameter("theta"),
+        Err("replay log has no entry for call ReadParameter(\"theta\")".to_string())
+    );
+}
+
+#[test]
+fn replayer_fails_on_divergence() {
+    let mut replayer = Replayer::new(recorded_log());
+    assert_eq!(
+        replayer.fetch_dataset_row("training\tset", 3),
+        Err("replay diverged from log at call FetchDatasetRow(\"training\\tset\", 3)".to_string())
+    );
+}
+
+#[test]
+fn async_host_is_recorded_like_a_blocking_one() {
+    assert_eq!(recorded_log_of(BlockingHost(AsyncTestHost)), recorded_log());
+}
+
+#[test]
+fn malformed_arguments_are_runtime_errors() {
+    let mut sim = SparseSim::new();
+    let mut host = TestHost;
+    let mut backend = HostIoBackend::new(&mut sim, &mut host);
+    assert_eq!(
+        backend.custom_intrinsic("ReadParameter", Value::Int(1)),
+        Some(Err("ReadParameter expects a parameter name".to_string()))
+    );
+    assert_eq!(
+        backend.custom_intrinsic(
+            "FetchDatasetRow",
+            Value::Tuple(Rc::from([Value::Int(3), Value::String("training".into())]))
+        ),
+        Some(Err(
+            "FetchDatasetRow expects a dataset name and a row index".to_string()
+        ))
+    );
+}
@@ -44,7 +44,25 @@ pub(crate) fn call(
             Ok(()) => Ok(Value::unit()),
             Err(_) => Err(Error::OutputFail(name_span)),
         },
+        "MessageTo" => {
+            let [channel, msg] = unwrap_tuple(arg);
+            match out.message_to(&channel.unwrap_string(), &msg.unwrap_string()) {
+                Ok(()) => Ok(Value::unit()),
+                Err(_) => Err(Error::OutputFail(name_span)),
+            }
+        }
         "CheckZero" => Ok(Value::Bool(sim.qubit_is_zero(arg.unwrap_qubit().0))),
+        "Barrier" => {
+            let [qubits, label] = unwrap_tuple(arg);
+            let qubits = qubits
+                .unwrap_array()
+                .iter()
+                .map(|qubit| qubit.clone().unwrap_qubit().0)
+                .collect::<Vec<_>>();
+            sim.barrier(&qubits, &label.unwrap_string());
+            Ok(Value::unit())
+        }
+        "AssertMeasurementProbability" => assert_measurement_probability(sim, arg, name_span),
         "ArcCos" => Ok(Value::Double(arg.unwrap_double().acos())),
         "ArcSin" => Ok(Value::Double(arg.unwrap_double().asin())),
         "ArcTan" => Ok(Value::Double(arg.unwrap_double().atan())),
@@ -82,6 +100,9 @@ pub(crate) fn call(
         }
         #[allow(clippy::cast_possible_truncation)]
         "Truncate" => Ok(Value::Int(arg.unwrap_double() as i64)),
+        "AddIChecked" => Ok(checked_int(arg, i64::overflowing_add)),
+        "SubtractIChecked" => Ok(checked_int(arg, i64::overflowing_sub)),
+        "MultiplyIChecked" => Ok(checked_int(arg, i64::overflowing_mul)),
         "__quantum__rt__qubit_allocate" => Ok(Value::Qubit(Qubit(sim.qubit_allocate()))),
         "__quantum__rt__qubit_release" => {
             let qubit = arg.unwrap_qubit().0;
@@ -125,7 +146,9 @@ pub(crate) fn call(
         "__quantum__qis__y__body" => Ok(one_qubit_gate(|q| sim.y(q), arg)),
         "__quantum__qis__z__body" => Ok(one_qubit_gate(|q| sim.z(q), arg)),
         "__quantum__qis__swap__body" => two_qubit_gate(|q0, q1| sim.swap(q0, q1), arg, arg_span),
-        "__quantum__qis__reset__body" => Ok(one_qubit_gate(|q| sim.reset(q), arg)),
+        "__quantum__qis__reset__body" | "ResetAllocatedQubit" => {
+            Ok(one_qubit_gate(|q| sim.reset(q), arg))
+        }
         "__quantum__qis__m__body" => Ok(Value::Result(sim.m(arg.unwrap_qubit().0).into())),
         "__quantum__qis__mresetz__body" => {
             Ok(Value::Result(sim.mresetz(arg.unwrap_qubit().0).into()))
@@ -143,6 +166,94 @@ pub(crate) fn call(
     }
 }
 
+/// Whether calling the intrinsic again with the same arguments has the same effect on the
+/// simulator, which holds for the gates and resets but not for measurements, whose results can
+/// differ.
+pub(crate) fn is_replayable(name: &str) -> bool {
+    name.starts_with("__quantum__qis__")
+        && !matches!(
+            name,
+            "__quantum__qis__m__body" | "__quantum__qis__mresetz__body"
+        )
+}
+
+/// Whether the intrinsic is a classical function that has no effects and always returns the same
+/// value for the same arguments.
+pub(crate) fn is_pure(name: &str) -> bool {
+    matches!(
+        name,
+        "Length"
+            | "IntAsDouble"
+            | "IntAsBigInt"
+            | "ArcCos"
+            | "ArcSin"
+            | "ArcTan"
+            | "ArcTan2"
+            | "Cos"
+            | "Cosh"
+            | "Sin"
+            | "Sinh"
+            | "Tan"
+            | "Tanh"
+            | "Sqrt"
+            | "Log"
+            | "Truncate"
+            | "AddIChecked"
+            | "SubtractIChecked"
+            | "MultiplyIChecked"
+    )
+}
+
+fn assert_measurement_probability(
+    sim: &mut dyn Backend<ResultType = impl Into<val::Result>>,
+    arg: Value,
+    name_span: PackageSpan,
+) -> Result<Value, Error> {
+    let fail = |message: String| {
+        Err(Error::IntrinsicFail(
+            "AssertMeasurementProbability".to_string(),
+            message,
+            name_span,
+        ))
+    };
+
+    let [bases, qubits, result, prob, tolerance, msg] = unwrap_tuple(arg);
+    let (bases, qubits) = (bases.unwrap_array(), qubits.unwrap_array());
+    if bases.len() != qubits.len() {
+        return fail("bases and qubits should have the same length".to_string());
+    }
+
+    let bases = bases
+        .iter()
+        .zip(qubits.iter())
+        .map(|(basis, qubit)| (basis.clone().unwrap_pauli(), qubit.clone().unwrap_qubit().0))
+        .collect::<Vec<_>>();
+    let Some(probability_of_one) = sim.measurement_probability(&bases) else {
+        return Ok(Value::unit());
+    };
+
+    let actual = if result.unwrap_result() {
+        probability_of_one
+    } else {
+        1.0 - probability_of_one
+    };
+    let expected = prob.unwrap_double();
+    if (actual - expected).abs() <= tolerance.unwrap_double() {
+        Ok(Value::unit())
+    } else {
+        fail(format!(
+            "{} (expected probability {expected}, got {actual})",
+            msg.unwrap_string()
+        ))
+    }
+}
+
+fn checked_int(arg: Value, op: impl FnOnce(i64, i64) -> (i64, bool)) -> Value {
+    let [a, b] = unwrap_tuple(arg);
+    let (value, overflowed) = op(a.unwrap_int(), b.unwrap_int());
+    Value::Tuple(vec![Value::Int(value), Value::Bool(overflowed)].into())
+}
+
 fn one_qubit_gate(mut gate: impl FnMut(usize), arg: Value) -> Value {
     gate(arg.unwrap_qubit().0);
     Value::unit()
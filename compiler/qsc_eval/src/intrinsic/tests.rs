@@ -134,6 +134,10 @@ impl Backend for CustomSim {
         self.sim.qubit_is_zero(q)
     }
 
+    fn measurement_probability(&mut self, bases: &[(fir::Pauli, usize)]) -> Option<f64> {
+        self.sim.measurement_probability(bases)
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         match name {
             "Add1" => Some(Ok(Value::Int(arg.unwrap_int() + 1))),
@@ -297,6 +301,17 @@ fn message() {
     );
 }
 
+#[test]
+fn message_to_channel_is_a_message_by_default() {
+    check_intrinsic_output(
+        "",
+        r#"MessageTo("telemetry", "{\"shots\": 1}")"#,
+        &expect![[r#"
+            {"shots": 1}
+        "#]],
+    );
+}
+
 #[test]
 fn check_zero() {
     check_intrinsic_result(
@@ -321,6 +336,47 @@ fn check_zero_false() {
     );
 }
 
+#[test]
+fn assert_measurement_probability() {
+    check_intrinsic_result(
+        indoc! {r#"
+            namespace Test {
+                open Microsoft.Quantum.Diagnostics;
+
+                operation Main() : Bool {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    AssertMeasurementProbability([PauliZ], [q0], One, 0.5, 1e-5, "Z");
+                    AssertMeasurementProbability([PauliX], [q0], Zero, 1.0, 1e-5, "X");
+                    CNOT(q0, q1);
+                    AssertMeasurementProbability([PauliZ, PauliZ], [q0, q1], Zero, 1.0, 1e-5, "ZZ");
+                    AssertMeasurementProbability([PauliY, PauliY], [q0, q1], One, 1.0, 1e-5, "YY");
+                    AssertMeasurementProbability([PauliI, PauliZ], [q0, q1], One, 0.5, 1e-5, "IZ");
+                    CNOT(q0, q1);
+                    H(q0);
+                    CheckZero(q0) and CheckZero(q1)
+                }
+            }
+        "#},
+        "Test.Main()",
+        &expect!["true"],
+    );
+}
+
+#[test]
+fn assert_measurement_probability_fails_outside_tolerance() {
+    check_intrinsic_result(
+        "",
+        indoc! {r#"{
+            use q = Qubit();
+            X(q);
+            Microsoft.Quantum.Diagnostics.AssertMeasurementProbability(
+                [PauliZ], [q], One, 0.5, 0.01, "should be balanced");
+        }"#},
+        &expect!["intrinsic callable `AssertMeasurementProbability` failed: should be balanced (expected probability 0.5, got 1)"],
+    );
+}
+
 #[test]
 fn length() {
     check_intrinsic_value("", "Length([1, 2, 3])", &Value::Int(3));
@@ -442,6 +498,35 @@ fn truncate() {
     check_intrinsic_value("", "Microsoft.Quantum.Math.Truncate(-3.9)", &Value::Int(-3));
 }
 
+#[test]
+fn checked_arithmetic() {
+    check_intrinsic_result(
+        "",
+        "Microsoft.Quantum.Math.AddIChecked(2, 3)",
+        &expect!["(5, false)"],
+    );
+    check_intrinsic_result(
+        "",
+        "Microsoft.Quantum.Math.AddIChecked(9223372036854775807, 1)",
+        &expect!["(-9223372036854775808, true)"],
+    );
+    check_intrinsic_result(
+        "",
+        "Microsoft.Quantum.Math.SubtractIChecked(-9223372036854775807, 2)",
+        &expect!["(9223372036854775807, true)"],
+    );
+    check_intrinsic_result(
+        "",
+        "Microsoft.Quantum.Math.MultiplyIChecked(4611686018427387904, 2)",
+        &expect!["(-9223372036854775808, true)"],
+    );
+    check_intrinsic_result(
+        "",
+        "Microsoft.Quantum.Math.MultiplyIChecked(-4, 5)",
+        &expect!["(-20, false)"],
+    );
+}
+
 #[test]
 fn sqrt() {
     check_intrinsic_value("", "Microsoft.Quantum.Math.Sqrt(0.0)", &Value::Double(0.0));
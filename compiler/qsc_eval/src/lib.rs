@@ -8,35 +8,50 @@
 mod tests;
 
 pub mod backend;
+pub mod cache;
+pub mod coverage;
 pub mod debug;
 mod error;
+pub mod format;
+pub mod host_io;
 mod intrinsic;
 pub mod lower;
+pub mod noise;
 pub mod output;
+pub mod pauli_frame;
+pub mod progress;
+pub mod replay;
+pub mod trace;
 pub mod val;
 
 use crate::val::{FunctorApp, Value};
 use backend::Backend;
-use debug::{map_fir_package_to_hir, CallStack, Frame};
-use error::PackageSpan;
+use cache::OperationCache;
+use coverage::Coverage;
+use debug::{format_log_message, map_fir_package_to_hir, BreakpointCondition, CallStack, Frame};
+pub use error::PackageSpan;
+use format::ValueFormat;
 use miette::Diagnostic;
 use num_bigint::BigInt;
 use output::Receiver;
 use qsc_data_structures::index_map::IndexMap;
+use qsc_data_structures::message::MessageParams;
 use qsc_data_structures::span::Span;
 use qsc_fir::fir::{
-    self, BinOp, BlockId, CallableImpl, Expr, ExprId, ExprKind, Field, Functor, Global, Lit,
-    LocalItemId, LocalVarId, Mutability, PackageId, PackageStoreLookup, PatId, PatKind, PrimField,
-    Res, StmtId, StmtKind, StoreItemId, StringComponent, UnOp,
+    self, BinOp, BlockId, CallableImpl, CallableKind, Expr, ExprId, ExprKind, Field, Functor,
+    Global, Lit, LocalItemId, LocalVarId, Mutability, PackageId, PackageStoreLookup, PatId,
+    PatKind, PrimField, Res, StmtId, StmtKind, StoreItemId, StorePatId, StringComponent, UnOp,
 };
-use qsc_fir::ty::Ty;
+use qsc_fir::ty::{Prim, Ty};
 use rand::{rngs::StdRng, SeedableRng};
 use std::{
     cell::RefCell,
     fmt::{self, Display, Formatter, Write},
     iter,
+    mem::take,
     ops::Neg,
     rc::Rc,
+    sync::Arc,
 };
 use thiserror::Error;
 
@@ -50,6 +65,27 @@ pub enum Error {
     #[diagnostic(code("Qsc.Eval.InvalidArrayLength"))]
     InvalidArrayLength(i64, #[label("cannot be used as a length")] PackageSpan),
 
+    #[error("cannot return from inside a branch on measurement results")]
+    #[diagnostic(help(
+        "code generated for the adaptive profile rejoins the code after the `if` from both sides of a branch on measurement results, so neither side can return"
+    ))]
+    #[diagnostic(code("Qsc.Eval.DynamicBranchReturn"))]
+    DynamicBranchReturn(#[label("cannot return here")] PackageSpan),
+
+    #[error("cannot update a variable from outside a branch on measurement results")]
+    #[diagnostic(help(
+        "code generated for the adaptive profile can't join values from the sides of a branch on measurement results, so variables declared before the `if` can't be updated inside it"
+    ))]
+    #[diagnostic(code("Qsc.Eval.DynamicBranchUpdate"))]
+    DynamicBranchUpdate(#[label("cannot update this variable here")] PackageSpan),
+
+    #[error("branches on measurement results have different values")]
+    #[diagnostic(help(
+        "code generated for the adaptive profile can't join values from the sides of a branch on measurement results, so the `if` can't have a value that depends on the branch taken"
+    ))]
+    #[diagnostic(code("Qsc.Eval.DynamicBranchValue"))]
+    DynamicBranchValue(#[label("branches have different values")] PackageSpan),
+
     #[error("division by zero")]
     #[diagnostic(code("Qsc.Eval.DivZero"))]
     DivZero(#[label("cannot divide by zero")] PackageSpan),
@@ -86,6 +122,27 @@ pub enum Error {
     #[diagnostic(code("Qsc.Eval.OutputFail"))]
     OutputFail(#[label("failed to generate output")] PackageSpan),
 
+    #[error("the value returned by the entry point cannot be recorded in the {0} output format")]
+    #[diagnostic(help(
+        "the tree formats record results, integers computed from results, and tuples and arrays of them; the flat format only records results, and the packed format at most 64 results"
+    ))]
+    #[diagnostic(code("Qsc.Eval.UnrecordableOutput"))]
+    UnrecordableOutput(
+        String,
+        #[label("returns a value that cannot be recorded")] PackageSpan,
+    ),
+
+    #[error("program needs {0} qubits, but the target has {1}")]
+    #[diagnostic(help(
+        "lower the precision or register sizes of the program, or choose a target with more qubits"
+    ))]
+    #[diagnostic(code("Qsc.Eval.QubitBudgetExceeded"))]
+    QubitBudgetExceeded(
+        usize,
+        usize,
+        #[label("the target runs out of qubits here")] PackageSpan,
+    ),
+
     #[error("qubits in gate invocation are not unique")]
     #[diagnostic(code("Qsc.Eval.QubitUniqueness"))]
     QubitUniqueness(#[label] PackageSpan),
@@ -94,6 +151,13 @@ pub enum Error {
     #[diagnostic(code("Qsc.Eval.RangeStepZero"))]
     RangeStepZero(#[label("invalid range")] PackageSpan),
 
+    #[error("cannot compare measurement results")]
+    #[diagnostic(help(
+        "the results of measurements are not known when generating code by tracing the program"
+    ))]
+    #[diagnostic(code("Qsc.Eval.ResultComparisonUnsupported"))]
+    ResultComparisonUnsupported(#[label("cannot compare results here")] PackageSpan),
+
     #[error("Qubit{0} released while not in |0⟩ state")]
     #[diagnostic(help("qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state"))]
     #[diagnostic(code("Qsc.Eval.ReleasedQubitNotZero"))]
@@ -126,6 +190,9 @@ impl Error {
         match self {
             Error::ArrayTooLarge(span)
             | Error::DivZero(span)
+            | Error::DynamicBranchReturn(span)
+            | Error::DynamicBranchUpdate(span)
+            | Error::DynamicBranchValue(span)
             | Error::EmptyRange(span)
             | Error::IndexOutOfRange(_, span)
             | Error::InvalidIndex(_, span)
@@ -134,9 +201,12 @@ impl Error {
             | Error::InvalidRotationAngle(_, span)
             | Error::InvalidNegativeInt(_, span)
             | Error::OutputFail(span)
+            | Error::QubitBudgetExceeded(_, _, span)
+            | Error::UnrecordableOutput(_, span)
             | Error::QubitUniqueness(span)
             | Error::RangeStepZero(span)
             | Error::ReleasedQubitNotZero(_, span)
+            | Error::ResultComparisonUnsupported(span)
             | Error::UnboundName(span)
             | Error::UnknownIntrinsic(_, span)
             | Error::UnsupportedIntrinsicType(_, span)
@@ -146,6 +216,37 @@ impl Error {
     }
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Error::ArrayTooLarge(_)
+            | Error::DivZero(_)
+            | Error::DynamicBranchReturn(_)
+            | Error::DynamicBranchUpdate(_)
+            | Error::DynamicBranchValue(_)
+            | Error::EmptyRange(_)
+            | Error::IntTooLarge(_, _)
+            | Error::OutputFail(_)
+            | Error::QubitUniqueness(_)
+            | Error::RangeStepZero(_)
+            | Error::ResultComparisonUnsupported(_)
+            | Error::UnboundName(_) => Vec::new(),
+            Error::IndexOutOfRange(value, _)
+            | Error::InvalidIndex(value, _)
+            | Error::InvalidNegativeInt(value, _)
+            | Error::InvalidArrayLength(value, _) => vec![value.to_string()],
+            Error::InvalidRotationAngle(angle, _) => vec![angle.to_string()],
+            Error::ReleasedQubitNotZero(qubit, _) => vec![qubit.to_string()],
+            Error::QubitBudgetExceeded(needed, max, _) => vec![needed.to_string(), max.to_string()],
+            Error::IntrinsicFail(name, message, _) => vec![name.clone(), message.clone()],
+            Error::UnrecordableOutput(text, _)
+            | Error::UnknownIntrinsic(text, _)
+            | Error::UnsupportedIntrinsicType(text, _)
+            | Error::UserFail(text, _) => vec![text.clone()],
+        }
+    }
+}
+
 /// A specialization that may be implemented for an operation.
 enum Spec {
     /// The default specialization.
@@ -188,11 +289,14 @@ impl From<StmtId> for EvalId {
     }
 }
 
-/// Evaluates the given code with the given context.
+/// Evaluates the given code with the given context. If an operation cache is given, it is used
+/// for the evaluation and holds the gates recorded by it afterwards. If coverage is given, the
+/// parts of the program that the evaluation reaches are added to it.
 /// # Errors
 /// Returns the first error encountered during execution.
 /// # Panics
 /// On internal error where no result is returned.
+#[allow(clippy::too_many_arguments)]
 pub fn eval(
     package: PackageId,
     seed: Option<u64>,
@@ -201,15 +305,62 @@ pub fn eval(
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
+    mut cache: Option<&mut OperationCache>,
+    mut coverage: Option<&mut Coverage>,
 ) -> Result<Value, (Error, Vec<Frame>)> {
     let mut state = State::new(package, seed);
+    if let Some(cache) = &mut cache {
+        state.set_operation_cache(Some(take(&mut **cache)));
+    }
+    if let Some(coverage) = &mut coverage {
+        state.set_coverage(Some(take(&mut **coverage)));
+    }
     match id {
         EvalId::Expr(expr) => state.push_expr(expr),
         EvalId::Stmt(stmt) => state.push_stmt(stmt),
     }
+    let res = state.eval(globals, env, sim, receiver, &[], StepAction::Continue);
+    if let (Some(cache), Some(used)) = (cache, state.take_operation_cache()) {
+        *cache = used;
+    }
+    if let (Some(coverage), Some(recorded)) = (coverage, state.take_coverage()) {
+        *coverage = recorded;
+    }
+    let StepResult::Return(value) = res? else {
+        panic!("eval should always return a value");
+    };
+    Ok(value)
+}
+
+/// Calls the given callable value with the given argument, which lets a host call the same
+/// callable repeatedly with different arguments without compiling a new call expression each time.
+/// The argument must have the input type of the callable.
+/// # Errors
+/// Returns the first error encountered during execution.
+/// # Panics
+/// If the value is not callable.
+#[allow(clippy::too_many_arguments)]
+pub fn invoke(
+    package: PackageId,
+    seed: Option<u64>,
+    callable: Value,
+    arg: Value,
+    globals: &impl PackageStoreLookup,
+    env: &mut Env,
+    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    receiver: &mut impl Receiver,
+) -> Result<Value, (Error, Vec<Frame>)> {
+    assert!(
+        matches!(callable, Value::Closure(..) | Value::Global(..)),
+        "value should be callable"
+    );
+    let mut state = State::new(package, seed);
+    state.push_val(callable);
+    state.push_val(arg);
+    state.push_action(Action::Call(Span::default(), Span::default()));
     let res = state.eval(globals, env, sim, receiver, &[], StepAction::Continue)?;
     let StepResult::Return(value) = res else {
-        panic!("eval should always return a value");
+        panic!("invoke should always return a value");
     };
     Ok(value)
 }
@@ -256,19 +407,22 @@ impl AsIndex for i64 {
 
 #[derive(Debug, Clone)]
 struct Variable {
-    name: Rc<str>,
+    name: Arc<str>,
     value: Value,
     mutability: Mutability,
     span: Span,
+    pat: StorePatId,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableInfo {
     pub value: Value,
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     pub type_name: String,
     pub mutability: Mutability,
     pub span: Span,
+    /// The pattern that bound the variable, which has its declared type.
+    pub pat: StorePatId,
 }
 
 impl Variable {
@@ -307,38 +461,73 @@ impl Range {
     }
 }
 
-pub struct Env(Vec<Scope>);
+pub struct Env {
+    scopes: Vec<Scope>,
+    format: ValueFormat,
+}
 
 impl Env {
+    /// Sets how values are displayed in interpolated strings and by the hosts of the evaluator.
+    pub fn set_value_format(&mut self, format: ValueFormat) {
+        self.format = format;
+    }
+
+    #[must_use]
+    pub fn value_format(&self) -> &ValueFormat {
+        &self.format
+    }
+
+    /// Displays the value of a variable as its declared type.
+    #[must_use]
+    pub fn display_variable(
+        &self,
+        globals: &impl PackageStoreLookup,
+        variable: &VariableInfo,
+    ) -> String {
+        let ty = &globals.get_pat(variable.pat).ty;
+        self.format
+            .display_typed(globals, variable.pat.package, &variable.value, ty)
+    }
+
     #[must_use]
     fn get(&self, id: LocalVarId) -> Option<&Variable> {
-        self.0.iter().rev().find_map(|scope| scope.bindings.get(id))
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(id))
     }
 
     fn get_mut(&mut self, id: LocalVarId) -> Option<&mut Variable> {
-        self.0
+        self.scopes
             .iter_mut()
             .rev()
             .find_map(|scope| scope.bindings.get_mut(id))
     }
 
+    /// The index of the innermost scope that binds the variable.
+    fn scope_index(&self, id: LocalVarId) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.bindings.contains_key(id))
+    }
+
     fn push_scope(&mut self, frame_id: usize) {
         let scope = Scope {
             frame_id,
             ..Default::default()
         };
-        self.0.push(scope);
+        self.scopes.push(scope);
     }
 
     fn leave_scope(&mut self) {
-        self.0
+        self.scopes
             .pop()
             .expect("scope should be entered first before leaving");
     }
 
     #[must_use]
     pub fn get_variables_in_top_frame(&self) -> Vec<VariableInfo> {
-        if let Some(scope) = self.0.last() {
+        if let Some(scope) = self.scopes.last() {
             self.get_variables_in_frame(scope.frame_id)
         } else {
             vec![]
@@ -348,7 +537,7 @@ impl Env {
     #[must_use]
     pub fn get_variables_in_frame(&self, frame_id: usize) -> Vec<VariableInfo> {
         let candidate_scopes: Vec<_> = self
-            .0
+            .scopes
             .iter()
             .filter(|scope| scope.frame_id == frame_id)
             .map(|scope| scope.bindings.iter())
@@ -364,12 +553,40 @@ impl Env {
                         value: var.value.clone(),
                         mutability: var.mutability,
                         span: var.span,
+                        pat: var.pat,
                     })
                     .collect()
             })
             .collect();
         variables_by_scope.into_iter().flatten().collect::<Vec<_>>()
     }
+
+    /// Updates the value of the innermost variable with the given name in the top frame.
+    /// Returns `false` if no such variable exists.
+    pub fn update_variable_in_top_frame(&mut self, name: &str, value: Value) -> bool {
+        let Some(frame_id) = self.scopes.last().map(|scope| scope.frame_id) else {
+            return false;
+        };
+        let var = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .filter(|scope| scope.frame_id == frame_id)
+            .find_map(|scope| {
+                scope
+                    .bindings
+                    .values_mut()
+                    .filter(|var| &*var.name == name)
+                    .last()
+            });
+        match var {
+            Some(var) => {
+                var.value = value;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -381,7 +598,10 @@ struct Scope {
 impl Default for Env {
     #[must_use]
     fn default() -> Self {
-        Self(vec![Scope::default()])
+        Self {
+            scopes: vec![Scope::default()],
+            format: ValueFormat::default(),
+        }
     }
 }
 
@@ -406,7 +626,11 @@ enum Action {
     Fail(Span),
     Field(Field),
     If(ExprId, Option<ExprId>),
+    IfResult(bool, ExprId, Option<ExprId>, Span),
+    ElseResultBranch(Option<ExprId>),
+    EndResultBranch(Span),
     Index(Span),
+    Interpolate(ExprId),
     Range(bool, bool, bool),
     Return,
     StringConcat(usize),
@@ -427,6 +651,23 @@ pub struct State {
     call_stack: CallStack,
     current_span: Span,
     rng: RefCell<StdRng>,
+    breakpoint_conditions: IndexMap<StmtId, BreakpointCondition>,
+    hit_counts: IndexMap<StmtId, u32>,
+    operation_cache: Option<OperationCache>,
+    coverage: Option<Coverage>,
+    result_branches: Vec<ResultBranch>,
+}
+
+/// A branch on measurement results whose values aren't known, so that both of its sides are
+/// evaluated in turn for the backend to generate code that branches at runtime.
+struct ResultBranch {
+    /// The number of scopes when the branch began. Variables in the scopes below can't be updated
+    /// inside the branch.
+    floor: usize,
+    /// The depth of the call stack when the branch began.
+    frame: usize,
+    /// The value of the `then` side, once it has been evaluated.
+    then_val: Option<Value>,
 }
 
 impl State {
@@ -444,6 +685,85 @@ impl State {
             call_stack: CallStack::default(),
             current_span: Span::default(),
             rng,
+            breakpoint_conditions: IndexMap::new(),
+            hit_counts: IndexMap::new(),
+            operation_cache: None,
+            coverage: None,
+            result_branches: Vec::new(),
+        }
+    }
+
+    /// Sets the cache used to replay the gates of operations that are called repeatedly with the
+    /// same arguments. Without a cache, every call is evaluated.
+    pub fn set_operation_cache(&mut self, cache: Option<OperationCache>) {
+        self.operation_cache = cache;
+    }
+
+    /// Takes the operation cache back from the evaluator, so that it can be reused by another
+    /// evaluation of the same program.
+    pub fn take_operation_cache(&mut self) -> Option<OperationCache> {
+        let mut cache = self.operation_cache.take();
+        if let Some(cache) = &mut cache {
+            cache.abandon();
+        }
+        cache
+    }
+
+    /// Sets the coverage that the statements, branches and specializations reached by the
+    /// evaluation are added to. Without coverage, nothing is recorded.
+    pub fn set_coverage(&mut self, coverage: Option<Coverage>) {
+        self.coverage = coverage;
+    }
+
+    /// Takes the recorded coverage back from the evaluator.
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    /// Attaches a condition to the breakpoint on the given statement, resetting its hit count.
+    pub fn set_breakpoint_condition(&mut self, stmt: StmtId, condition: BreakpointCondition) {
+        self.breakpoint_conditions.insert(stmt, condition);
+        self.hit_counts.remove(stmt);
+    }
+
+    /// Removes all breakpoint conditions and hit counts.
+    pub fn clear_breakpoint_conditions(&mut self) {
+        self.breakpoint_conditions = IndexMap::new();
+        self.hit_counts = IndexMap::new();
+    }
+
+    /// Records a hit of the breakpoint on the given statement and returns whether
+    /// execution should stop there. Logpoints emit their message and never stop.
+    fn check_breakpoint(
+        &mut self,
+        env: &Env,
+        globals: &impl PackageStoreLookup,
+        out: &mut impl Receiver,
+        stmt: StmtId,
+    ) -> Result<bool, Error> {
+        let Some(condition) = self.breakpoint_conditions.get(stmt) else {
+            return Ok(true);
+        };
+
+        let hits = self.hit_counts.get(stmt).copied().unwrap_or_default() + 1;
+        self.hit_counts.insert(stmt, hits);
+        if let Some(hit_condition) = condition.hit_condition {
+            if !hit_condition.is_met(hits) {
+                return Ok(false);
+            }
+        }
+
+        match &condition.log_message {
+            Some(message) => {
+                let variables = env.get_variables_in_top_frame();
+                let message = format_log_message(message, &variables, |variable| {
+                    env.display_variable(globals, variable)
+                });
+                out.message(&message)
+                    .map_err(|_| Error::OutputFail(self.to_global_span(self.current_span)))?;
+                Ok(false)
+            }
+            None => Ok(true),
         }
     }
 
@@ -465,22 +785,30 @@ impl State {
             .extend(exprs.iter().rev().map(|expr| Cont::Expr(*expr)));
     }
 
-    fn push_frame(&mut self, id: StoreItemId, functor: FunctorApp) {
-        self.call_stack.push_frame(Frame {
+    fn new_frame(&self, id: StoreItemId, functor: FunctorApp) -> Frame {
+        Frame {
             span: self.current_span,
             id,
             caller: self.package,
             functor,
-        });
+        }
+    }
+
+    fn push_frame(&mut self, id: StoreItemId, functor: FunctorApp) {
+        self.call_stack.push_frame(self.new_frame(id, functor));
         self.cont_stack.push(Cont::Frame(self.vals.len()));
         self.package = id.package;
     }
 
-    fn leave_frame(&mut self, len: usize) {
+    fn leave_frame(&mut self, sim: &mut impl Backend, len: usize) {
+        if let Some(cache) = &mut self.operation_cache {
+            cache.leave(self.call_stack.len());
+        }
         let frame = self
             .call_stack
             .pop_frame()
             .expect("frame should be present");
+        sim.exit_call(&frame);
         self.package = frame.caller;
         let frame_val = self.pop_val();
         self.vals.drain(len..);
@@ -547,6 +875,7 @@ impl State {
         step: StepAction,
     ) -> Result<StepResult, (Error, Vec<Frame>)> {
         let current_frame = self.call_stack.len();
+        let result_branches = sim.result_branches();
 
         while let Some(cont) = self.pop_cont() {
             let res = match cont {
@@ -557,12 +886,12 @@ impl State {
                     continue;
                 }
                 Cont::Expr(expr) => {
-                    self.cont_expr(env, globals, expr)
+                    self.cont_expr(env, globals, expr, result_branches)
                         .map_err(|e| (e, self.get_stack_frames()))?;
                     continue;
                 }
                 Cont::Frame(len) => {
-                    self.leave_frame(len);
+                    self.leave_frame(sim, len);
                     continue;
                 }
                 Cont::Scope => {
@@ -571,8 +900,14 @@ impl State {
                 }
                 Cont::Stmt(stmt) => {
                     self.cont_stmt(globals, stmt);
-                    if let Some(bp) = breakpoints.iter().find(|&bp| *bp == stmt) {
-                        StepResult::BreakpointHit(*bp)
+                    let bp_hit = match breakpoints.iter().find(|&bp| *bp == stmt) {
+                        Some(bp) => self
+                            .check_breakpoint(env, globals, out, *bp)
+                            .map_err(|e| (e, self.get_stack_frames()))?,
+                        None => false,
+                    };
+                    if bp_hit {
+                        StepResult::BreakpointHit(stmt)
                     } else {
                         if self.current_span == Span::default() {
                             // if there is no span, we are in generated code, so we should skip
@@ -613,6 +948,7 @@ impl State {
         env: &mut Env,
         globals: &impl PackageStoreLookup,
         expr: ExprId,
+        result_branches: bool,
     ) -> Result<(), Error> {
         let expr = globals.get_expr((self.package, expr).into());
         self.current_span = expr.span;
@@ -641,12 +977,12 @@ impl State {
             ExprKind::Field(expr, field) => self.cont_field(*expr, field),
             ExprKind::Hole => panic!("hole expr should be disallowed by passes"),
             ExprKind::If(cond_expr, then_expr, else_expr) => {
-                self.cont_if(*cond_expr, *then_expr, *else_expr);
+                self.cont_if(globals, *cond_expr, *then_expr, *else_expr, result_branches);
             }
             ExprKind::Index(arr, index) => self.cont_index(globals, *arr, *index),
             ExprKind::Lit(lit) => self.push_val(lit_to_val(lit)),
             ExprKind::Range(start, step, end) => self.cont_range(*start, *step, *end),
-            ExprKind::Return(expr) => self.cont_ret(*expr),
+            ExprKind::Return(ret_expr) => self.cont_ret(expr.span, *ret_expr)?,
             ExprKind::String(components) => self.cont_string(components),
             ExprKind::UpdateIndex(lhs, mid, rhs) => self.update_index(globals, *lhs, *mid, *rhs),
             ExprKind::Tuple(tup) => self.cont_tup(tup),
@@ -680,14 +1016,50 @@ impl State {
         self.push_expr(item);
     }
 
-    fn cont_ret(&mut self, expr: ExprId) {
+    fn cont_ret(&mut self, span: Span, expr: ExprId) -> Result<(), Error> {
+        // Returning from the callable that contains a branch on results would skip the code
+        // after the branch on only one of its sides.
+        if self
+            .result_branches
+            .last()
+            .is_some_and(|branch| branch.frame == self.call_stack.len())
+        {
+            return Err(Error::DynamicBranchReturn(self.to_global_span(span)));
+        }
         self.push_action(Action::Return);
         self.push_expr(expr);
+        Ok(())
     }
 
-    fn cont_if(&mut self, cond_expr: ExprId, then_expr: ExprId, else_expr: Option<ExprId>) {
-        self.push_action(Action::If(then_expr, else_expr));
-        self.push_expr(cond_expr);
+    fn cont_if(
+        &mut self,
+        globals: &impl PackageStoreLookup,
+        cond_expr: ExprId,
+        then_expr: ExprId,
+        else_expr: Option<ExprId>,
+        result_branches: bool,
+    ) {
+        let cond = globals.get_expr((self.package, cond_expr).into());
+        match cond.kind {
+            ExprKind::BinOp(op @ (BinOp::Eq | BinOp::Neq), lhs, rhs)
+                if result_branches
+                    && globals.get_expr((self.package, lhs).into()).ty
+                        == Ty::Prim(Prim::Result) =>
+            {
+                self.push_action(Action::IfResult(
+                    op == BinOp::Eq,
+                    then_expr,
+                    else_expr,
+                    cond.span,
+                ));
+                self.push_expr(rhs);
+                self.push_expr(lhs);
+            }
+            _ => {
+                self.push_action(Action::If(then_expr, else_expr));
+                self.push_expr(cond_expr);
+            }
+        }
     }
 
     fn cont_fail(&mut self, span: Span, fail_expr: ExprId) {
@@ -783,15 +1155,18 @@ impl State {
 
     fn cont_string(&mut self, components: &[StringComponent]) {
         if let [StringComponent::Lit(str)] = components {
-            self.push_val(Value::String(Rc::clone(str)));
+            self.push_val(Value::String(Rc::from(&**str)));
             return;
         }
 
         self.push_action(Action::StringConcat(components.len()));
         for component in components.iter().rev() {
             match component {
-                StringComponent::Expr(expr) => self.push_expr(*expr),
-                StringComponent::Lit(lit) => self.push_action(Action::StringLit(lit.clone())),
+                StringComponent::Expr(expr) => {
+                    self.push_action(Action::Interpolate(*expr));
+                    self.push_expr(*expr);
+                }
+                StringComponent::Lit(lit) => self.push_action(Action::StringLit(Rc::from(&**lit))),
             }
         }
     }
@@ -872,6 +1247,9 @@ impl State {
     }
 
     fn cont_stmt(&mut self, globals: &impl PackageStoreLookup, stmt: StmtId) {
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_stmt(self.package, stmt);
+        }
         let stmt = globals.get_stmt((self.package, stmt).into());
         self.current_span = stmt.span;
 
@@ -906,8 +1284,8 @@ impl State {
             }
             Action::ArrayRepeat(span) => self.eval_arr_repeat(span)?,
             Action::Assign(lhs) => self.eval_assign(env, globals, lhs)?,
-            Action::BinOp(op, span, rhs) => self.eval_binop(op, span, rhs)?,
-            Action::Bind(pat, mutability) => self.eval_bind(env, globals, pat, mutability),
+            Action::BinOp(op, span, rhs) => self.eval_binop(sim, op, span, rhs)?,
+            Action::Bind(pat, mutability) => self.eval_bind(env, sim, globals, pat, mutability),
             Action::Call(callable_span, args_span) => {
                 self.eval_call(env, sim, globals, callable_span, args_span, out)?;
             }
@@ -922,11 +1300,17 @@ impl State {
             }
             Action::Field(field) => self.eval_field(field),
             Action::If(then_expr, else_expr) => self.eval_if(then_expr, else_expr),
+            Action::IfResult(eq, then_expr, else_expr, span) => {
+                self.eval_if_result(env, sim, eq, then_expr, else_expr, span);
+            }
+            Action::ElseResultBranch(else_expr) => self.eval_else_result_branch(sim, else_expr),
+            Action::EndResultBranch(span) => self.eval_end_result_branch(sim, span)?,
             Action::Index(span) => self.eval_index(span)?,
             Action::Range(has_start, has_step, has_end) => {
                 self.eval_range(has_start, has_step, has_end);
             }
-            Action::Return => self.eval_ret(env),
+            Action::Return => self.eval_ret(env, sim),
+            Action::Interpolate(expr) => self.eval_interpolate(env, globals, expr),
             Action::StringConcat(len) => self.eval_string_concat(len),
             Action::StringLit(str) => self.push_val(Value::String(str)),
             Action::UpdateIndex(span) => self.eval_update_index(span)?,
@@ -955,15 +1339,18 @@ impl State {
         let lhs = globals.get_expr((self.package, lhs).into());
         let rhs = self.pop_val();
         match (&lhs.kind, rhs) {
-            (&ExprKind::Var(Res::Local(id), _), rhs) => match env.get_mut(id) {
-                Some(var) if var.is_mutable() => {
-                    var.value.append_array(rhs);
-                }
-                Some(_) => {
-                    unreachable!("update of mutable variable should be disallowed by compiler")
+            (&ExprKind::Var(Res::Local(id), _), rhs) => {
+                self.check_result_branch_update(env, id, lhs.span)?;
+                match env.get_mut(id) {
+                    Some(var) if var.is_mutable() => {
+                        var.value.append_array(rhs);
+                    }
+                    Some(_) => {
+                        unreachable!("update of mutable variable should be disallowed by compiler")
+                    }
+                    None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
                 }
-                None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
-            },
+            }
             _ => unreachable!("unassignable array update pattern should be disallowed by compiler"),
         }
         Ok(())
@@ -996,15 +1383,52 @@ impl State {
     fn eval_bind(
         &mut self,
         env: &mut Env,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
         globals: &impl PackageStoreLookup,
         pat: PatId,
         mutability: Mutability,
     ) {
         let val = self.pop_val();
+        // The core library allocates arrays of qubits for the program, so the names it binds them
+        // to aren't the program's.
+        if sim.labels_qubits() && self.package != PackageId::CORE {
+            self.label_qubits(sim, globals, pat, &val);
+        }
         self.bind_value(env, globals, pat, val, mutability);
     }
 
-    fn eval_binop(&mut self, op: BinOp, span: Span, rhs: Option<ExprId>) -> Result<(), Error> {
+    /// Tells the backend the names that the pattern binds qubits in the value to. Names made up by
+    /// the compiler, such as those of qubits allocated in a tuple, are left out.
+    fn label_qubits(
+        &self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        globals: &impl PackageStoreLookup,
+        pat: PatId,
+        val: &Value,
+    ) {
+        let pat = globals.get_pat((self.package, pat).into());
+        match &pat.kind {
+            PatKind::Bind(variable) if !variable.name.starts_with('@') => {
+                label_value(sim, &variable.name, val);
+            }
+            PatKind::Bind(_) | PatKind::Discard => {}
+            PatKind::Tuple(tup) => {
+                if let Value::Tuple(vals) = val {
+                    for (pat, val) in tup.iter().zip(vals.iter()) {
+                        self.label_qubits(sim, globals, *pat, val);
+                    }
+                }
+            }
+        }
+    }
+
+    fn eval_binop(
+        &mut self,
+        sim: &impl Backend,
+        op: BinOp,
+        span: Span,
+        rhs: Option<ExprId>,
+    ) -> Result<(), Error> {
         match op {
             BinOp::Add => self.eval_binop_simple(eval_binop_add),
             BinOp::AndB => self.eval_binop_simple(eval_binop_andb),
@@ -1017,8 +1441,7 @@ impl State {
             }
             BinOp::Div => self.eval_binop_with_error(span, eval_binop_div)?,
             BinOp::Eq => {
-                let rhs_val = self.pop_val();
-                let lhs_val = self.pop_val();
+                let (lhs_val, rhs_val) = self.pop_comparable_vals(sim, span)?;
                 self.push_val(Value::Bool(lhs_val == rhs_val));
             }
             BinOp::Exp => self.eval_binop_with_error(span, eval_binop_exp)?,
@@ -1029,8 +1452,7 @@ impl State {
             BinOp::Mod => self.eval_binop_with_error(span, eval_binop_mod)?,
             BinOp::Mul => self.eval_binop_simple(eval_binop_mul),
             BinOp::Neq => {
-                let rhs_val = self.pop_val();
-                let lhs_val = self.pop_val();
+                let (lhs_val, rhs_val) = self.pop_comparable_vals(sim, span)?;
                 self.push_val(Value::Bool(lhs_val != rhs_val));
             }
             BinOp::OrB => self.eval_binop_simple(eval_binop_orb),
@@ -1049,6 +1471,23 @@ impl State {
         Ok(())
     }
 
+    /// Pops the operands of an equality comparison. Results that are only identified by the
+    /// measurement that produced them can't be compared unless the backend knows their outcomes.
+    fn pop_comparable_vals(
+        &mut self,
+        sim: &impl Backend,
+        span: Span,
+    ) -> Result<(Value, Value), Error> {
+        let rhs_val = resolve_result_ids(self.pop_val(), sim);
+        let lhs_val = resolve_result_ids(self.pop_val(), sim);
+        if any_result_id(&lhs_val) || any_result_id(&rhs_val) {
+            return Err(Error::ResultComparisonUnsupported(
+                self.to_global_span(span),
+            ));
+        }
+        Ok((lhs_val, rhs_val))
+    }
+
     fn eval_binop_simple(&mut self, binop_func: impl FnOnce(Value, Value) -> Value) {
         let rhs_val = self.pop_val();
         let lhs_val = self.pop_val();
@@ -1087,7 +1526,7 @@ impl State {
 
         let callee = match globals.get_global(callee_id) {
             Some(Global::Callable(callable)) => callable,
-            Some(Global::Udt) => {
+            Some(Global::Udt(_)) => {
                 self.push_val(arg);
                 return Ok(());
             }
@@ -1096,21 +1535,40 @@ impl State {
 
         let callee_span = self.to_global_span(callee.span);
 
+        if let (Some(cache), CallableImpl::Spec(_)) =
+            (&mut self.operation_cache, &callee.implementation)
+        {
+            if callee.kind == CallableKind::Operation && callee.output == Ty::UNIT {
+                let key = merge_fixed_args(fixed_args.clone(), arg.clone());
+                let depth = self.call_stack.len() + 1;
+                if let Some((gates, qubits)) = cache.lookup(callee_id, functor, &key, depth) {
+                    let frame = self.new_frame(callee_id, functor);
+                    sim.enter_call(&frame);
+                    for gate in gates.iter() {
+                        let arg = cache::substitute(&gate.arg, &qubits);
+                        self.call_intrinsic(&gate.name, callee_span, arg, arg_span, sim, out)?;
+                    }
+                    sim.exit_call(&frame);
+                    self.push_val(Value::unit());
+                    return Ok(());
+                }
+            }
+        }
+
         let spec = spec_from_functor_app(functor);
         self.push_frame(callee_id, functor);
+        sim.enter_call(
+            self.call_stack
+                .frames()
+                .last()
+                .expect("frame should be present"),
+        );
         self.push_scope(env);
         match &callee.implementation {
             CallableImpl::Intrinsic => {
-                let name = &callee.name.name;
-                let val = intrinsic::call(
-                    name,
-                    callee_span,
-                    arg,
-                    arg_span,
-                    sim,
-                    &mut self.rng.borrow_mut(),
-                    out,
-                )?;
+                sim.set_call_stack(self.call_stack.frames());
+                let val =
+                    self.call_intrinsic(&callee.name.name, callee_span, arg, arg_span, sim, out)?;
                 if val == Value::unit() && callee.output != Ty::UNIT {
                     return Err(Error::UnsupportedIntrinsicType(
                         callee.name.name.to_string(),
@@ -1128,6 +1586,9 @@ impl State {
                     Spec::CtlAdj => specialized_implementation.ctl_adj.as_ref(),
                 }
                 .expect("missing specialization should be a compilation error");
+                if let Some(coverage) = &mut self.coverage {
+                    coverage.record_spec(self.package, spec_decl.block);
+                }
                 self.bind_args_for_spec(
                     env,
                     globals,
@@ -1143,6 +1604,29 @@ impl State {
         }
     }
 
+    fn call_intrinsic(
+        &mut self,
+        name: &Arc<str>,
+        name_span: PackageSpan,
+        arg: Value,
+        arg_span: PackageSpan,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        out: &mut impl Receiver,
+    ) -> Result<Value, Error> {
+        if let Some(cache) = &mut self.operation_cache {
+            cache.record(name, &arg);
+        }
+        intrinsic::call(
+            name,
+            name_span,
+            arg,
+            arg_span,
+            sim,
+            &mut self.rng.borrow_mut(),
+            out,
+        )
+    }
+
     fn eval_field(&mut self, field: Field) {
         let record = self.pop_val();
         let val = match (record, field) {
@@ -1158,7 +1642,11 @@ impl State {
     }
 
     fn eval_if(&mut self, then_expr: ExprId, else_expr: Option<ExprId>) {
-        if self.pop_val().unwrap_bool() {
+        let taken = self.pop_val().unwrap_bool();
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_branch(self.package, then_expr, taken);
+        }
+        if taken {
             self.push_expr(then_expr);
         } else if let Some(else_expr) = else_expr {
             self.push_expr(else_expr);
@@ -1167,6 +1655,72 @@ impl State {
         }
     }
 
+    /// Branches on the comparison of two results, for backends that can branch on results. Results
+    /// whose values are known branch like any other condition, while the backend is asked to branch
+    /// at runtime on the others, in which case both sides are evaluated.
+    fn eval_if_result(
+        &mut self,
+        env: &Env,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        eq: bool,
+        then_expr: ExprId,
+        else_expr: Option<ExprId>,
+        span: Span,
+    ) {
+        let (Value::Result(rhs), Value::Result(lhs)) = (self.pop_val(), self.pop_val()) else {
+            panic!("values should be Result");
+        };
+        if let (val::Result::Val(lhs), val::Result::Val(rhs)) = (lhs, rhs) {
+            self.push_val(Value::Bool((lhs == rhs) == eq));
+            self.eval_if(then_expr, else_expr);
+        } else {
+            sim.begin_result_branch(lhs, rhs, eq);
+            self.result_branches.push(ResultBranch {
+                floor: env.scopes.len(),
+                frame: self.call_stack.len(),
+                then_val: None,
+            });
+            self.push_action(Action::EndResultBranch(span));
+            self.push_action(Action::ElseResultBranch(else_expr));
+            self.push_expr(then_expr);
+        }
+    }
+
+    fn eval_else_result_branch(
+        &mut self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        else_expr: Option<ExprId>,
+    ) {
+        let then_val = self.pop_val();
+        self.result_branches
+            .last_mut()
+            .expect("result branch should have begun")
+            .then_val = Some(then_val);
+        sim.else_result_branch();
+        match else_expr {
+            Some(else_expr) => self.push_expr(else_expr),
+            None => self.push_val(Value::unit()),
+        }
+    }
+
+    fn eval_end_result_branch(
+        &mut self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        span: Span,
+    ) -> Result<(), Error> {
+        let else_val = self.pop_val();
+        let branch = self
+            .result_branches
+            .pop()
+            .expect("result branch should have begun");
+        sim.end_result_branch();
+        if branch.then_val.as_ref() != Some(&else_val) {
+            return Err(Error::DynamicBranchValue(self.to_global_span(span)));
+        }
+        self.push_val(else_val);
+        Ok(())
+    }
+
     fn eval_index(&mut self, span: Span) -> Result<(), Error> {
         let index_val = self.pop_val();
         let arr = self.pop_val().unwrap_array();
@@ -1205,11 +1759,11 @@ impl State {
         self.push_val(Value::Range(start, step, end));
     }
 
-    fn eval_ret(&mut self, env: &mut Env) {
+    fn eval_ret(&mut self, env: &mut Env, sim: &mut impl Backend) {
         while let Some(cont) = self.pop_cont() {
             match cont {
                 Cont::Frame(len) => {
-                    self.leave_frame(len);
+                    self.leave_frame(sim, len);
                     break;
                 }
                 Cont::Scope => env.leave_scope(),
@@ -1221,6 +1775,17 @@ impl State {
         }
     }
 
+    fn eval_interpolate(&mut self, env: &Env, globals: &impl PackageStoreLookup, expr: ExprId) {
+        let value = self.pop_val();
+        if let Value::String(_) = value {
+            self.push_val(value);
+            return;
+        }
+        let ty = &globals.get_expr((self.package, expr).into()).ty;
+        let string = env.format.display_typed(globals, self.package, &value, ty);
+        self.push_val(Value::String(string.into()));
+    }
+
     fn eval_string_concat(&mut self, len: usize) {
         let mut string = String::new();
         for component in self.pop_vals(len) {
@@ -1400,10 +1965,11 @@ impl State {
         val: Value,
         mutability: Mutability,
     ) {
-        let pat = globals.get_pat((self.package, pat).into());
+        let pat_id = (self.package, pat).into();
+        let pat = globals.get_pat(pat_id);
         match &pat.kind {
             PatKind::Bind(variable) => {
-                let scope = env.0.last_mut().expect("binding should have a scope");
+                let scope = env.scopes.last_mut().expect("binding should have a scope");
                 scope.bindings.insert(
                     variable.id,
                     Variable {
@@ -1411,6 +1977,7 @@ impl State {
                         value: val,
                         mutability,
                         span: variable.span,
+                        pat: pat_id,
                     },
                 );
             }
@@ -1424,6 +1991,22 @@ impl State {
         }
     }
 
+    /// Variables declared before a branch on results can't be updated inside it, since their
+    /// values after the branch would depend on the side taken.
+    fn check_result_branch_update(
+        &self,
+        env: &Env,
+        id: LocalVarId,
+        span: Span,
+    ) -> Result<(), Error> {
+        match (self.result_branches.last(), env.scope_index(id)) {
+            (Some(branch), Some(index)) if index < branch.floor => {
+                Err(Error::DynamicBranchUpdate(self.to_global_span(span)))
+            }
+            _ => Ok(()),
+        }
+    }
+
     #[allow(clippy::similar_names)]
     fn update_binding(
         &self,
@@ -1435,15 +2018,18 @@ impl State {
         let lhs = globals.get_expr((self.package, lhs).into());
         match (&lhs.kind, rhs) {
             (ExprKind::Hole, _) => {}
-            (&ExprKind::Var(Res::Local(id), _), rhs) => match env.get_mut(id) {
-                Some(var) if var.is_mutable() => {
-                    var.value = rhs;
-                }
-                Some(_) => {
-                    unreachable!("update of mutable variable should be disallowed by compiler")
+            (&ExprKind::Var(Res::Local(id), _), rhs) => {
+                self.check_result_branch_update(env, id, lhs.span)?;
+                match env.get_mut(id) {
+                    Some(var) if var.is_mutable() => {
+                        var.value = rhs;
+                    }
+                    Some(_) => {
+                        unreachable!("update of mutable variable should be disallowed by compiler")
+                    }
+                    None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
                 }
-                None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
-            },
+            }
             (ExprKind::Tuple(var_tup), Value::Tuple(tup)) => {
                 for (expr, val) in var_tup.iter().zip(tup.iter()) {
                     self.update_binding(env, globals, *expr, val.clone())?;
@@ -1465,17 +2051,25 @@ impl State {
     ) -> Result<(), Error> {
         let lhs = globals.get_expr((self.package, lhs).into());
         match &lhs.kind {
-            &ExprKind::Var(Res::Local(id), _) => match env.get_mut(id) {
-                Some(var) if var.is_mutable() => {
-                    var.value.update_array(index, rhs).map_err(|idx| {
-                        Error::IndexOutOfRange(idx.try_into().expect("index should be valid"), span)
-                    })?;
-                }
-                Some(_) => {
-                    unreachable!("update of immutable variable should be disallowed by compiler")
+            &ExprKind::Var(Res::Local(id), _) => {
+                self.check_result_branch_update(env, id, lhs.span)?;
+                match env.get_mut(id) {
+                    Some(var) if var.is_mutable() => {
+                        var.value.update_array(index, rhs).map_err(|idx| {
+                            Error::IndexOutOfRange(
+                                idx.try_into().expect("index should be valid"),
+                                span,
+                            )
+                        })?;
+                    }
+                    Some(_) => {
+                        unreachable!(
+                            "update of immutable variable should be disallowed by compiler"
+                        )
+                    }
+                    None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
                 }
-                None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
-            },
+            }
             _ => unreachable!("unassignable array update pattern should be disallowed by compiler"),
         }
         Ok(())
@@ -1493,34 +2087,37 @@ impl State {
     ) -> Result<(), Error> {
         let lhs = globals.get_expr((self.package, lhs).into());
         match &lhs.kind {
-            &ExprKind::Var(Res::Local(id), _) => match env.get_mut(id) {
-                Some(var) if var.is_mutable() => {
-                    let rhs = update.unwrap_array();
-                    let Value::Array(arr) = &mut var.value else {
-                        panic!("variable should be an array");
-                    };
-                    let Value::Range(start, step, end) = range else {
-                        unreachable!("range should be a Value::Range");
-                    };
-                    let range = make_range(arr, *start, *step, *end, range_span)?;
-                    for (idx, rhs) in range.into_iter().zip(rhs.iter()) {
-                        if idx < 0 {
-                            return Err(Error::InvalidNegativeInt(idx, range_span));
+            &ExprKind::Var(Res::Local(id), _) => {
+                self.check_result_branch_update(env, id, lhs.span)?;
+                match env.get_mut(id) {
+                    Some(var) if var.is_mutable() => {
+                        let rhs = update.unwrap_array();
+                        let Value::Array(arr) = &mut var.value else {
+                            panic!("variable should be an array");
+                        };
+                        let Value::Range(start, step, end) = range else {
+                            unreachable!("range should be a Value::Range");
+                        };
+                        let range = make_range(arr, *start, *step, *end, range_span)?;
+                        for (idx, rhs) in range.into_iter().zip(rhs.iter()) {
+                            if idx < 0 {
+                                return Err(Error::InvalidNegativeInt(idx, range_span));
+                            }
+                            let i = idx.as_index(range_span)?;
+                            var.value.update_array(i, rhs.clone()).map_err(|idx| {
+                                Error::IndexOutOfRange(
+                                    idx.try_into().expect("index should be valid"),
+                                    range_span,
+                                )
+                            })?;
                         }
-                        let i = idx.as_index(range_span)?;
-                        var.value.update_array(i, rhs.clone()).map_err(|idx| {
-                            Error::IndexOutOfRange(
-                                idx.try_into().expect("index should be valid"),
-                                range_span,
-                            )
-                        })?;
                     }
+                    Some(_) => {
+                        unreachable!("update of mutable variable should be disallowed by compiler")
+                    }
+                    None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
                 }
-                Some(_) => {
-                    unreachable!("update of mutable variable should be disallowed by compiler")
-                }
-                None => return Err(Error::UnboundName(self.to_global_span(lhs.span))),
-            },
+            }
             _ => unreachable!("unassignable array update pattern should be disallowed by compiler"),
         }
         Ok(())
@@ -1647,6 +2244,56 @@ fn resolve_closure(
     Ok(Value::Closure(args.into(), callable, FunctorApp::default()))
 }
 
+/// Replaces the results that are identified by the measurements that returned them with their
+/// outcomes, where the backend knows them.
+fn resolve_result_ids(val: Value, sim: &impl Backend) -> Value {
+    match val {
+        Value::Result(val::Result::Id(id)) => sim
+            .result_outcome(id)
+            .map_or(val, |outcome| Value::Result(val::Result::Val(outcome))),
+        Value::Array(items) if items.iter().any(any_result_id) => Value::Array(Rc::new(
+            items
+                .iter()
+                .map(|item| resolve_result_ids(item.clone(), sim))
+                .collect(),
+        )),
+        Value::Tuple(items) if items.iter().any(any_result_id) => Value::Tuple(
+            items
+                .iter()
+                .map(|item| resolve_result_ids(item.clone(), sim))
+                .collect(),
+        ),
+        _ => val,
+    }
+}
+
+fn any_result_id(val: &Value) -> bool {
+    match val {
+        Value::Result(val::Result::Id(_)) => true,
+        Value::Array(items) => items.iter().any(any_result_id),
+        Value::Tuple(items) => items.iter().any(any_result_id),
+        _ => false,
+    }
+}
+
+/// Labels the qubit, or each qubit of the array, with the name, indexing into arrays as in
+/// `ctls[1]`.
+fn label_value(
+    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    label: &str,
+    val: &Value,
+) {
+    match val {
+        Value::Qubit(q) => sim.qubit_label(q.0, label),
+        Value::Array(items) if matches!(items.first(), Some(Value::Qubit(_) | Value::Array(_))) => {
+            for (index, item) in items.iter().enumerate() {
+                label_value(sim, &format!("{label}[{index}]"), item);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn lit_to_val(lit: &Lit) -> Value {
     match lit {
         Lit::BigInt(v) => Value::BigInt(v.clone()),
@@ -9,7 +9,7 @@ use qsc_fir::{
     ty::{Arrow, InferFunctorId, ParamId, Ty},
 };
 use qsc_hir::hir::{self, SpecBody, SpecGen};
-use std::{clone::Clone, rc::Rc};
+use std::{clone::Clone, sync::Arc};
 
 pub struct Lowerer {
     nodes: IndexMap<hir::NodeId, fir::NodeId>,
@@ -147,7 +147,7 @@ impl Lowerer {
             id: lower_local_item_id(item.id),
             span: item.span,
             parent: item.parent.map(lower_local_item_id),
-            doc: Rc::clone(&item.doc),
+            doc: Arc::clone(&item.doc),
             attrs,
             visibility: lower_visibility(item.visibility),
             kind,
@@ -381,7 +381,7 @@ impl Lowerer {
     fn lower_string_component(&mut self, component: &hir::StringComponent) -> fir::StringComponent {
         match component {
             hir::StringComponent::Expr(expr) => fir::StringComponent::Expr(self.lower_expr(expr)),
-            hir::StringComponent::Lit(str) => fir::StringComponent::Lit(Rc::clone(str)),
+            hir::StringComponent::Lit(str) => fir::StringComponent::Lit(Arc::clone(str)),
         }
     }
 
@@ -0,0 +1,376 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_hash::FxHashMap;
+
+/// A noise model that applies a Pauli error to each qubit a gate acts on, after the gate.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PauliNoise {
+    /// The probability of an X error.
+    pub x: f64,
+    /// The probability of a Y error.
+    pub y: f64,
+    /// The probability of a Z error.
+    pub z: f64,
+}
+
+impl PauliNoise {
+    /// Depolarizing noise, where each of the three errors happens with probability `p / 3`.
+    #[must_use]
+    pub fn depolarizing(p: f64) -> Self {
+        Self {
+            x: p / 3.0,
+            y: p / 3.0,
+            z: p / 3.0,
+        }
+    }
+
+    /// The Pauli twirl of the thermal relaxation a qubit undergoes while idle for `duration`, given
+    /// its relaxation time `t1` and dephasing time `t2`, all in the same unit of time.
+    #[must_use]
+    pub fn relaxation(duration: f64, t1: f64, t2: f64) -> Self {
+        let damping = 1.0 - (-duration / t1).exp();
+        let dephasing = 1.0 - (-duration / t2).exp();
+        Self {
+            x: damping / 4.0,
+            y: damping / 4.0,
+            z: (dephasing / 2.0 - damping / 4.0).max(0.0),
+        }
+    }
+
+    /// The noise of applying this noise and then `other`.
+    #[must_use]
+    pub fn then(self, other: Self) -> Self {
+        let i = 1.0 - self.x - self.y - self.z;
+        let other_i = 1.0 - other.x - other.y - other.z;
+        Self {
+            x: i * other.x + self.x * other_i + self.y * other.z + self.z * other.y,
+            y: i * other.y + self.y * other_i + self.x * other.z + self.z * other.x,
+            z: i * other.z + self.z * other_i + self.x * other.y + self.y * other.x,
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Pauli {
+        let p = rng.gen::<f64>();
+        if p < self.x {
+            Pauli::X
+        } else if p < self.x + self.y {
+            Pauli::Y
+        } else if p < self.x + self.y + self.z {
+            Pauli::Z
+        } else {
+            Pauli::I
+        }
+    }
+}
+
+/// A noise model with its own Pauli noise for each gate and an error rate for reading out
+/// measurements, either of which can be given for particular qubits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoiseModel {
+    /// The noise of the gates that have no noise of their own.
+    pub default: PauliNoise,
+    /// The noise of each gate, keyed by the name of the backend operation, such as `cx` or `sadj`.
+    pub gates: FxHashMap<String, PauliNoise>,
+    /// The noise of gates on particular qubits, keyed by the name of the backend operation and then
+    /// by the qubits the gate acts on, in order, with the noise of each of those qubits. It takes
+    /// precedence over the noise of the gate.
+    pub qubit_gates: FxHashMap<String, FxHashMap<Vec<usize>, Vec<PauliNoise>>>,
+    /// The probability that a measurement reports the opposite of the outcome the qubit collapsed
+    /// to.
+    pub readout: f64,
+    /// The readout error of each qubit, by ID, which takes precedence over `readout`.
+    pub qubit_readout: Vec<f64>,
+}
+
+impl NoiseModel {
+    /// The noise of the given gate.
+    #[must_use]
+    pub fn gate(&self, name: &str) -> PauliNoise {
+        self.gates.get(name).copied().unwrap_or(self.default)
+    }
+
+    /// The noise of the qubit at the position among the qubits that the given gate acts on.
+    #[must_use]
+    pub fn gate_on(&self, name: &str, qubits: &[usize], position: usize) -> PauliNoise {
+        self.qubit_gates
+            .get(name)
+            .and_then(|gates| gates.get(qubits))
+            .and_then(|noise| noise.get(position))
+            .copied()
+            .unwrap_or_else(|| self.gate(name))
+    }
+
+    /// The readout error of the qubit.
+    #[must_use]
+    pub fn readout_of(&self, q: usize) -> f64 {
+        self.qubit_readout.get(q).copied().unwrap_or(self.readout)
+    }
+}
+
+impl From<PauliNoise> for NoiseModel {
+    fn from(noise: PauliNoise) -> Self {
+        Self {
+            default: noise,
+            ..Self::default()
+        }
+    }
+}
+
+/// A backend that applies a noise model after each gate applied by the backend it wraps.
+/// Measurements only suffer readout errors, which flip the reported result but leave the qubit in
+/// the state it collapsed to. Resets and state inspection are passed through without noise.
+pub struct NoisyBackend<'a, B: Backend> {
+    backend: &'a mut B,
+    noise: NoiseModel,
+    rng: StdRng,
+}
+
+impl<'a, B: Backend> NoisyBackend<'a, B> {
+    pub fn new(backend: &'a mut B, noise: impl Into<NoiseModel>, seed: Option<u64>) -> Self {
+        Self {
+            backend,
+            noise: noise.into(),
+            rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+        }
+    }
+
+    fn apply_noise(&mut self, gate: &str, qubits: &[usize]) {
+        for (position, &q) in qubits.iter().enumerate() {
+            match self
+                .noise
+                .gate_on(gate, qubits, position)
+                .sample(&mut self.rng)
+            {
+                Pauli::I => {}
+                Pauli::X => self.backend.x(q),
+                Pauli::Y => self.backend.y(q),
+                Pauli::Z => self.backend.z(q),
+            }
+        }
+    }
+
+    fn readout_error(&mut self, q: usize) -> bool {
+        let readout = self.noise.readout_of(q);
+        readout > 0.0 && self.rng.gen::<f64>() < readout
+    }
+}
+
+impl<'a, B: Backend> Backend for NoisyBackend<'a, B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+        self.apply_noise("ccx", &[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+        self.apply_noise("cx", &[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+        self.apply_noise("cy", &[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+        self.apply_noise("cz", &[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+        self.apply_noise("h", &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        if self.readout_error(q) {
+            // Flipping the qubit around the measurement flips the result without changing the
+            // state the qubit is left in.
+            self.backend.x(q);
+            let result = self.backend.m(q);
+            self.backend.x(q);
+            result
+        } else {
+            self.backend.m(q)
+        }
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        if self.readout_error(q) {
+            self.backend.x(q);
+        }
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+        self.apply_noise("rx", &[q]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+        self.apply_noise("rxx", &[q0, q1]);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+        self.apply_noise("ry", &[q]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+        self.apply_noise("ryy", &[q0, q1]);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+        self.apply_noise("rz", &[q]);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+        self.apply_noise("rzz", &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+        self.apply_noise("sadj", &[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+        self.apply_noise("s", &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+        self.apply_noise("swap", &[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+        self.apply_noise("tadj", &[q]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+        self.apply_noise("t", &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+        self.apply_noise("x", &[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+        self.apply_noise("y", &[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+        self.apply_noise("z", &[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        // The forced outcome already includes any readout error.
+        self.backend.forced_m(q, one)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.backend.end_result_branch();
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{NoiseModel, NoisyBackend, PauliNoise};
+use crate::backend::{Backend, SparseSim};
+
+#[test]
+fn noiseless_model_leaves_gates_unchanged() {
+    let mut sim = SparseSim::new();
+    let mut noisy = NoisyBackend::new(&mut sim, PauliNoise::default(), Some(0));
+    let q = noisy.qubit_allocate();
+    noisy.x(q);
+    assert!(!noisy.qubit_is_zero(q));
+}
+
+#[test]
+fn certain_bit_flip_follows_every_gate() {
+    let mut sim = SparseSim::new();
+    let noise = PauliNoise {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let mut noisy = NoisyBackend::new(&mut sim, noise, Some(0));
+    let q0 = noisy.qubit_allocate();
+    let q1 = noisy.qubit_allocate();
+    noisy.x(q0);
+    assert!(noisy.qubit_is_zero(q0));
+    noisy.cx(q0, q1);
+    assert!(!noisy.qubit_is_zero(q0));
+    assert!(!noisy.qubit_is_zero(q1));
+}
+
+#[test]
+fn measurements_are_not_noisy() {
+    let mut sim = SparseSim::new();
+    let mut noisy = NoisyBackend::new(&mut sim, PauliNoise::depolarizing(1.0), Some(0));
+    let q = noisy.qubit_allocate();
+    noisy.reset(q);
+    assert!(!noisy.m(q));
+    assert!(noisy.qubit_is_zero(q));
+}
+
+#[test]
+fn gates_use_their_own_noise() {
+    let mut sim = SparseSim::new();
+    let mut model = NoiseModel::from(PauliNoise {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    });
+    model.gates.insert("h".to_string(), PauliNoise::default());
+    let mut noisy = NoisyBackend::new(&mut sim, model, Some(0));
+    let q = noisy.qubit_allocate();
+    noisy.h(q);
+    noisy.h(q);
+    assert!(noisy.qubit_is_zero(q));
+    noisy.z(q);
+    assert!(!noisy.qubit_is_zero(q));
+}
+
+#[test]
+fn readout_errors_flip_results_but_not_qubits() {
+    let mut sim = SparseSim::new();
+    let model = NoiseModel {
+        readout: 1.0,
+        ..NoiseModel::default()
+    };
+    let mut noisy = NoisyBackend::new(&mut sim, model, Some(0));
+    let q = noisy.qubit_allocate();
+    assert!(noisy.m(q));
+    assert!(noisy.qubit_is_zero(q));
+    noisy.x(q);
+    assert!(!noisy.mresetz(q));
+    assert!(noisy.qubit_is_zero(q));
+}
+
+#[test]
+fn relaxation_grows_with_duration() {
+    assert_eq!(
+        PauliNoise::relaxation(0.0, 100.0, 50.0),
+        PauliNoise::default()
+    );
+    let short = PauliNoise::relaxation(1.0, 100.0, 50.0);
+    let long = PauliNoise::relaxation(10.0, 100.0, 50.0);
+    assert!(short.x > 0.0 && short.x < long.x);
+    assert!(short.z > 0.0 && short.z < long.z);
+    assert!((short.x - short.y).abs() < f64::EPSILON);
+}
+
+#[test]
+fn composing_flips_cancels_them() {
+    let flip = PauliNoise {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    assert_eq!(flip.then(flip), PauliNoise::default());
+    let phase = PauliNoise {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    assert_eq!(
+        flip.then(phase),
+        PauliNoise {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        }
+    );
+}
+
+#[test]
+fn qubits_use_their_own_noise_and_readout() {
+    let mut sim = SparseSim::new();
+    let flip = PauliNoise {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let mut model = NoiseModel::default();
+    model
+        .qubit_gates
+        .entry("cx".to_string())
+        .or_default()
+        .insert(vec![0, 1], vec![PauliNoise::default(), flip]);
+    model.qubit_readout = vec![0.0, 1.0];
+    let mut noisy = NoisyBackend::new(&mut sim, model, Some(0));
+    let q0 = noisy.qubit_allocate();
+    let q1 = noisy.qubit_allocate();
+    noisy.cx(q0, q1);
+    assert!(noisy.qubit_is_zero(q0));
+    assert!(!noisy.qubit_is_zero(q1));
+    noisy.cx(q1, q0);
+    assert!(!noisy.qubit_is_zero(q0));
+    assert!(!noisy.qubit_is_zero(q1));
+    assert!(noisy.m(q0));
+    assert!(!noisy.m(q1));
+}
@@ -6,6 +6,8 @@ use std::io::{Cursor, Write};
 use num_bigint::BigUint;
 use num_complex::{Complex, Complex64};
 
+use crate::progress::Progress;
+
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Error;
 
@@ -50,6 +52,21 @@ pub trait Receiver {
     /// # Errors
     /// This will return an error if handling the output fails.
     fn message(&mut self, msg: &str) -> Result<(), Error>;
+
+    /// Receive message output sent to a named channel, such as `stderr` or `telemetry`. By
+    /// default the message is received as generic message output.
+    /// # Errors
+    /// This will return an error if handling the output fails.
+    fn message_to(&mut self, _channel: &str, msg: &str) -> Result<(), Error> {
+        self.message(msg)
+    }
+
+    /// Receive the progress of a multi-shot evaluation after each shot
+    /// # Errors
+    /// This will return an error if handling the progress fails, which stops the remaining shots.
+    fn progress(&mut self, _progress: &Progress) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 pub struct GenericReceiver<'a> {
@@ -0,0 +1,303 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+
+/// The Pauli correction that is pending on a qubit, as the X and Z parts of the Pauli.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Correction {
+    x: bool,
+    z: bool,
+}
+
+/// A backend that keeps the Pauli gates applied to the backend it wraps in a classical frame
+/// instead of the state. Clifford gates move the frame through themselves, which only flips bits,
+/// and the frame of a qubit is applied to the state when a non-Clifford gate acts on it, when it is
+/// measured or when the state is inspected. Circuits heavy in Pauli corrections, such as
+/// teleportation and error correction, then apply far fewer gates to the state. The state differs
+/// from the one without the frame by at most a global phase.
+pub struct PauliFrameBackend<'a, B: Backend> {
+    backend: &'a mut B,
+    frame: Vec<Correction>,
+}
+
+impl<'a, B: Backend> PauliFrameBackend<'a, B> {
+    pub fn new(backend: &'a mut B) -> Self {
+        Self {
+            backend,
+            frame: Vec::new(),
+        }
+    }
+
+    fn correction(&mut self, q: usize) -> &mut Correction {
+        if q >= self.frame.len() {
+            self.frame.resize(q + 1, Correction::default());
+        }
+        &mut self.frame[q]
+    }
+
+    /// Applies the pending correction of each qubit to the state.
+    fn flush(&mut self, qubits: &[usize]) {
+        for &q in qubits {
+            let correction = std::mem::take(self.correction(q));
+            if correction.x {
+                self.backend.x(q);
+            }
+            if correction.z {
+                self.backend.z(q);
+            }
+        }
+    }
+
+    fn flush_all(&mut self) {
+        let qubits = (0..self.frame.len()).collect::<Vec<_>>();
+        self.flush(&qubits);
+    }
+
+    /// Moves the frame through a controlled X gate.
+    fn propagate_cx(&mut self, ctl: usize, q: usize) {
+        let control = *self.correction(ctl);
+        let target = *self.correction(q);
+        self.correction(q).x ^= control.x;
+        self.correction(ctl).z ^= target.z;
+    }
+
+    /// Moves the frame through an S or adjoint S gate, which differ only by the phase of the
+    /// correction.
+    fn propagate_s(&mut self, q: usize) {
+        let correction = self.correction(q);
+        correction.z ^= correction.x;
+    }
+}
+
+impl<'a, B: Backend> Backend for PauliFrameBackend<'a, B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.flush(&[ctl0, ctl1, q]);
+        self.backend.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.propagate_cx(ctl, q);
+        self.backend.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        // CY is CX conjugated by S on the target.
+        self.propagate_s(q);
+        self.propagate_cx(ctl, q);
+        self.propagate_s(q);
+        self.backend.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        let control = *self.correction(ctl);
+        let target = *self.correction(q);
+        self.correction(q).z ^= control.x;
+        self.correction(ctl).z ^= target.x;
+        self.backend.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        let correction = self.correction(q);
+        std::mem::swap(&mut correction.x, &mut correction.z);
+        self.backend.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.flush(&[q]);
+        self.backend.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.flush(&[q]);
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        // The qubit ends in the zero state whatever correction was pending on it.
+        *self.correction(q) = Correction::default();
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.flush(&[q]);
+        self.backend.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.flush(&[q0, q1]);
+        self.backend.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.flush(&[q]);
+        self.backend.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.flush(&[q0, q1]);
+        self.backend.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.flush(&[q]);
+        self.backend.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.flush(&[q0, q1]);
+        self.backend.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.propagate_s(q);
+        self.backend.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.propagate_s(q);
+        self.backend.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        let first = *self.correction(q0);
+        let second = *self.correction(q1);
+        *self.correction(q0) = second;
+        *self.correction(q1) = first;
+        self.backend.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.flush(&[q]);
+        self.backend.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.flush(&[q]);
+        self.backend.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.correction(q).x ^= true;
+    }
+
+    fn y(&mut self, q: usize) {
+        let correction = self.correction(q);
+        correction.x ^= true;
+        correction.z ^= true;
+    }
+
+    fn z(&mut self, q: usize) {
+        self.correction(q).z ^= true;
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let q = self.backend.qubit_allocate();
+        *self.correction(q) = Correction::default();
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.flush(&[q]);
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.flush_all();
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.flush(&[q]);
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.flush_all();
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        self.flush(&[q]);
+        self.backend.forced_m(q, one)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.flush_all();
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        // A custom intrinsic may act on any qubit, so it sees the state without pending corrections.
+        self.flush_all();
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        // The frame can't follow both sides of the branch, so it is flushed where they start and
+        // where they join.
+        self.flush_all();
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.flush_all();
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.flush_all();
+        self.backend.end_result_branch();
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::PauliFrameBackend;
+use crate::{
+    backend::{Backend, Precision, SparseSim},
+    progress::CountingBackend,
+};
+
+/// Applies a circuit that mixes Pauli, Clifford and non-Clifford gates.
+fn circuit(sim: &mut impl Backend) -> Vec<usize> {
+    let qubits = (0..3).map(|_| sim.qubit_allocate()).collect::<Vec<_>>();
+    let [q0, q1, q2] = qubits[..] else {
+        unreachable!("three qubits should be allocated")
+    };
+    sim.h(q0);
+    sim.x(q0);
+    sim.z(q1);
+    sim.cx(q0, q1);
+    sim.s(q1);
+    sim.y(q1);
+    sim.cz(q0, q2);
+    sim.y(q2);
+    sim.swap(q0, q2);
+    sim.h(q1);
+    sim.x(q1);
+    sim.cy(q1, q2);
+    sim.sadj(q0);
+    sim.z(q0);
+    sim.t(q0);
+    sim.x(q2);
+    sim.ry(0.3, q2);
+    sim.cx(q2, q0);
+    sim.y(q0);
+    qubits
+}
+
+#[test]
+fn state_matches_simulation_without_frame_up_to_global_phase() {
+    let mut expected_sim = SparseSim::new();
+    circuit(&mut expected_sim);
+    let (expected, count) = expected_sim.capture_quantum_state();
+
+    let mut sim = SparseSim::new();
+    let mut framed = PauliFrameBackend::new(&mut sim);
+    circuit(&mut framed);
+    let (actual, actual_count) = framed.capture_quantum_state();
+
+    assert_eq!(actual_count, count);
+    assert_eq!(
+        actual.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+        expected.iter().map(|(id, _)| id).collect::<Vec<_>>()
+    );
+    let phase = actual[0].1 / expected[0].1;
+    assert!((phase.norm() - 1.0).abs() < 1e-10);
+    for ((_, actual), (_, expected)) in actual.iter().zip(&expected) {
+        assert!(
+            (actual - expected * phase).norm() < 1e-10,
+            "{actual} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn paulis_are_applied_only_when_needed() {
+    let mut sim = SparseSim::new();
+    let mut counter = CountingBackend::new(&mut sim);
+    let mut framed = PauliFrameBackend::new(&mut counter);
+    let q0 = framed.qubit_allocate();
+    let q1 = framed.qubit_allocate();
+    framed.x(q0);
+    framed.z(q1);
+    framed.cx(q0, q1);
+    framed.y(q0);
+    framed.reset(q0);
+    // The correction on the second qubit is now a Y, whose X part flips its measurement.
+    assert!(framed.m(q1));
+    assert!(!framed.m(q0));
+    // Only the controlled X, the reset, the correction of the second qubit and the measurements
+    // reach the simulator.
+    assert_eq!(counter.gates(), 6);
+}
+
+#[test]
+fn forced_measurement_reaches_the_wrapped_backends() {
+    let mut sim = SparseSim::for_replay(Precision::Double);
+    let mut counter = CountingBackend::new(&mut sim);
+    let mut framed = PauliFrameBackend::new(&mut counter);
+    let q = framed.qubit_allocate();
+    framed.x(q);
+    // The pending X is applied before the measurement, so the qubit can only be found in `One`.
+    assert_eq!(framed.forced_m(q, false), None);
+    assert_eq!(framed.forced_m(q, true), Some(true));
+    // The correction and the measurement that was made reach the simulator.
+    assert_eq!(counter.gates(), 2);
+}
@@ -0,0 +1,306 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+use std::{collections::VecDeque, time::Duration};
+
+/// The number of most recent shots whose durations are averaged to estimate the time remaining.
+const ETA_WINDOW: usize = 16;
+
+/// The progress of a multi-shot evaluation, reported after each shot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    pub shots_completed: u32,
+    pub total_shots: u32,
+    /// The number of gates, measurements and resets applied across all completed shots.
+    pub gates_applied: u64,
+    /// The estimated time until the remaining shots complete, if there is enough history to
+    /// estimate it.
+    pub eta: Option<Duration>,
+}
+
+/// Tracks the progress of a multi-shot evaluation, estimating the time remaining from a moving
+/// average of the durations of the most recent shots.
+///
+/// The tracker doesn't read a clock itself, since WASM hosts don't have one that the standard
+/// library can use, so the duration of each shot is measured by the caller.
+#[derive(Debug)]
+pub struct ProgressTracker {
+    total_shots: u32,
+    shots_completed: u32,
+    gates_applied: u64,
+    recent: VecDeque<Duration>,
+}
+
+impl ProgressTracker {
+    #[must_use]
+    pub fn new(total_shots: u32) -> Self {
+        Self {
+            total_shots,
+            shots_completed: 0,
+            gates_applied: 0,
+            recent: VecDeque::with_capacity(ETA_WINDOW),
+        }
+    }
+
+    /// Records a shot that took `elapsed` and applied `gates` gates, returning the progress so far.
+    pub fn shot_finished(&mut self, elapsed: Duration, gates: u64) -> Progress {
+        self.shots_completed += 1;
+        self.gates_applied += gates;
+        if self.recent.len() == ETA_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(elapsed);
+        self.progress()
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        Progress {
+            shots_completed: self.shots_completed,
+            total_shots: self.total_shots,
+            gates_applied: self.gates_applied,
+            eta: self.eta(),
+        }
+    }
+
+    fn eta(&self) -> Option<Duration> {
+        let remaining = self.total_shots.saturating_sub(self.shots_completed);
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let window = u32::try_from(self.recent.len())
+            .ok()
+            .filter(|&window| window > 0)?;
+        let average = self.recent.iter().sum::<Duration>() / window;
+        average.checked_mul(remaining)
+    }
+}
+
+/// A backend that counts the gates, measurements and resets applied by the backend it wraps.
+pub struct CountingBackend<'a, B: Backend> {
+    backend: &'a mut B,
+    gates: u64,
+}
+
+impl<'a, B: Backend> CountingBackend<'a, B> {
+    pub fn new(backend: &'a mut B) -> Self {
+        Self { backend, gates: 0 }
+    }
+
+    /// The number of gates, measurements and resets applied so far.
+    #[must_use]
+    pub fn gates(&self) -> u64 {
+        self.gates
+    }
+}
+
+impl<'a, B: Backend> Backend for CountingBackend<'a, B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.gates += 1;
+        self.backend.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.gates += 1;
+        self.backend.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.gates += 1;
+        self.backend.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.gates += 1;
+        self.backend.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.gates += 1;
+        self.backend.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.gates += 1;
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.gates += 1;
+        self.backend.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gates += 1;
+        self.backend.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.gates += 1;
+        self.backend.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gates += 1;
+        self.backend.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.gates += 1;
+        self.backend.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gates += 1;
+        self.backend.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.gates += 1;
+        self.backend.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.gates += 1;
+        self.backend.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        let result = self.backend.forced_m(q, one)?;
+        self.gates += 1;
+        Some(result)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.backend.end_result_branch();
+    }
+}
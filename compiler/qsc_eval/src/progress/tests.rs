@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{CountingBackend, Progress, ProgressTracker, ETA_WINDOW};
+use crate::backend::{Backend, SparseSim};
+use std::time::Duration;
+
+#[test]
+fn eta_is_unknown_before_the_first_shot() {
+    let tracker = ProgressTracker::new(4);
+    assert_eq!(
+        tracker.progress(),
+        Progress {
+            shots_completed: 0,
+            total_shots: 4,
+            gates_applied: 0,
+            eta: None,
+        }
+    );
+}
+
+#[test]
+fn eta_is_average_shot_duration_times_remaining_shots() {
+    let mut tracker = ProgressTracker::new(4);
+    tracker.shot_finished(Duration::from_millis(10), 3);
+    let progress = tracker.shot_finished(Duration::from_millis(30), 5);
+    assert_eq!(
+        progress,
+        Progress {
+            shots_completed: 2,
+            total_shots: 4,
+            gates_applied: 8,
+            eta: Some(Duration::from_millis(40)),
+        }
+    );
+}
+
+#[test]
+fn eta_only_averages_recent_shots() {
+    let mut tracker = ProgressTracker::new(100);
+    for _ in 0..ETA_WINDOW {
+        tracker.shot_finished(Duration::from_secs(1), 0);
+    }
+    let mut progress = tracker.progress();
+    for _ in 0..ETA_WINDOW {
+        progress = tracker.shot_finished(Duration::from_millis(1), 0);
+    }
+    let window = u32::try_from(ETA_WINDOW).expect("window should fit in u32");
+    let remaining = 100 - 2 * window;
+    assert_eq!(progress.eta, Some(Duration::from_millis(remaining.into())));
+}
+
+#[test]
+fn eta_is_zero_when_all_shots_complete() {
+    let mut tracker = ProgressTracker::new(1);
+    let progress = tracker.shot_finished(Duration::from_secs(1), 1);
+    assert_eq!(progress.eta, Some(Duration::ZERO));
+}
+
+#[test]
+fn counting_backend_counts_gates_but_not_allocations() {
+    let mut sim = SparseSim::new();
+    let mut counter = CountingBackend::new(&mut sim);
+    let q0 = counter.qubit_allocate();
+    let q1 = counter.qubit_allocate();
+    counter.h(q0);
+    counter.cx(q0, q1);
+    counter.rxx(0.5, q0, q1);
+    counter.mresetz(q0);
+    counter.mresetz(q1);
+    counter.qubit_release(q0);
+    counter.qubit_release(q1);
+    assert_eq!(counter.gates(), 5);
+}
@@ -0,0 +1,307 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    str::FromStr,
+};
+
+/// The outcomes of the measurements of a run, in the order they were made, with the seed that the
+/// simulator of the run was given.
+///
+/// Logs are written as `seed:count:outcomes`, where the outcomes are hexadecimal digits that each
+/// hold four outcomes, the first in the highest bit, so that a log fits in a bug report.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MeasurementLog {
+    pub seed: u64,
+    pub outcomes: Vec<bool>,
+}
+
+impl MeasurementLog {
+    /// Whether another run made the same measurements with the same outcomes as the logged one.
+    #[must_use]
+    pub fn matches(&self, outcomes: &[bool]) -> bool {
+        self.outcomes == outcomes
+    }
+}
+
+impl Display for MeasurementLog {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}:", self.seed, self.outcomes.len())?;
+        for chunk in self.outcomes.chunks(4) {
+            let digit = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |digit, (i, &one)| digit | (u32::from(one) << (3 - i)));
+            f.write_char(char::from_digit(digit, 16).expect("digit should be below 16"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MeasurementLog {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let (Some(seed), Some(count), Some(digits), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(());
+        };
+        let seed = seed.parse().map_err(|_| ())?;
+        let count = count.parse::<usize>().map_err(|_| ())?;
+        if digits.len() != count.div_ceil(4) {
+            return Err(());
+        }
+        let mut outcomes = Vec::with_capacity(count);
+        for c in digits.chars() {
+            let digit = c.to_digit(16).ok_or(())?;
+            outcomes.extend((0..4).rev().map(|bit| digit & (1 << bit) != 0));
+        }
+        // The last digit may be padded past the logged outcomes.
+        if outcomes[count..].iter().any(|&one| one) {
+            return Err(());
+        }
+        outcomes.truncate(count);
+        Ok(Self { seed, outcomes })
+    }
+}
+
+/// A backend that records the outcomes of the measurements made on the backend it wraps. When
+/// replaying a log, measurements are forced to have the logged outcomes through
+/// [`Backend::forced_m`]. Measurements past the end of the log, and those whose logged outcome the
+/// backend can't give, are made as usual, and the recorded outcomes no longer match the log.
+pub struct RecordingBackend<'a, B: Backend<ResultType = bool>> {
+    backend: &'a mut B,
+    /// The outcomes to force, in the order of the measurements.
+    log: Vec<bool>,
+    outcomes: Vec<bool>,
+}
+
+impl<'a, B: Backend<ResultType = bool>> RecordingBackend<'a, B> {
+    pub fn new(backend: &'a mut B) -> Self {
+        Self::replaying(backend, Vec::new())
+    }
+
+    /// A backend that forces the measurements to have the outcomes of the log.
+    pub fn replaying(backend: &'a mut B, log: Vec<bool>) -> Self {
+        Self {
+            backend,
+            log,
+            outcomes: Vec::new(),
+        }
+    }
+
+    /// Measures the qubit with its logged outcome, if there is one that the backend can give.
+    fn replay_m(&mut self, q: usize) -> Option<bool> {
+        let &one = self.log.get(self.outcomes.len())?;
+        self.backend.forced_m(q, one)
+    }
+
+    /// The outcomes of the measurements, in the order they were made.
+    #[must_use]
+    pub fn finish(self) -> Vec<bool> {
+        self.outcomes
+    }
+}
+
+impl<'a, B: Backend<ResultType = bool>> Backend for RecordingBackend<'a, B> {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        let outcome = self.replay_m(q).unwrap_or_else(|| self.backend.m(q));
+        self.outcomes.push(outcome);
+        outcome
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let outcome = match self.replay_m(q) {
+            Some(outcome) => {
+                if outcome {
+                    self.backend.x(q);
+                }
+                outcome
+            }
+            None => self.backend.mresetz(q),
+        };
+        self.outcomes.push(outcome);
+        outcome
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        let outcome = self.backend.forced_m(q, one)?;
+        self.outcomes.push(outcome);
+        Some(outcome)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.backend.end_result_branch();
+    }
+}
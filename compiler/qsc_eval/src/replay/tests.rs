@@ -0,0 +1,97 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{MeasurementLog, RecordingBackend};
+use crate::backend::{Backend, Precision, SparseSim};
+
+#[test]
+fn log_round_trips_through_text() {
+    let log = MeasurementLog {
+        seed: 42,
+        outcomes: vec![true, false, true, true, false, true],
+    };
+    assert_eq!(log.to_string(), "42:6:b4");
+    assert_eq!("42:6:b4".parse(), Ok(log));
+}
+
+#[test]
+fn empty_log_round_trips_through_text() {
+    let log = MeasurementLog {
+        seed: 7,
+        outcomes: Vec::new(),
+    };
+    assert_eq!(log.to_string(), "7:0:");
+    assert_eq!("7:0:".parse(), Ok(log));
+}
+
+#[test]
+fn malformed_logs_are_rejected() {
+    for text in [
+        "",
+        "42",
+        "42:6",
+        "42:6:b",
+        "42:6:b5",
+        "42:6:bz",
+        "x:6:b4",
+        "42:6:b4:0",
+    ] {
+        assert_eq!(text.parse::<MeasurementLog>(), Err(()), "{text}");
+    }
+}
+
+#[test]
+fn logs_only_match_runs_with_the_same_outcomes() {
+    let log = MeasurementLog {
+        seed: 0,
+        outcomes: vec![true, false],
+    };
+    assert!(log.matches(&[true, false]));
+    assert!(!log.matches(&[true]));
+    assert!(!log.matches(&[true, false, true]));
+    assert!(!log.matches(&[false, false]));
+}
+
+#[test]
+fn outcomes_are_recorded_in_order() {
+    let mut sim = SparseSim::new();
+    let mut recording = RecordingBackend::new(&mut sim);
+    let q0 = recording.qubit_allocate();
+    let q1 = recording.qubit_allocate();
+    recording.x(q0);
+    assert!(recording.m(q0));
+    assert!(!recording.mresetz(q1));
+    assert!(recording.mresetz(q0));
+    assert_eq!(recording.finish(), vec![true, false, true]);
+}
+
+#[test]
+fn replayed_outcomes_are_forced() {
+    for precision in [Precision::Double, Precision::Single] {
+        let mut sim = SparseSim::for_replay(precision);
+        let mut replaying = RecordingBackend::replaying(&mut sim, vec![true, false, true]);
+        let q0 = replaying.qubit_allocate();
+        let q1 = replaying.qubit_allocate();
+        replaying.h(q0);
+        replaying.cx(q0, q1);
+        assert!(replaying.m(q0));
+        // The state collapsed to |11⟩, so the logged outcome of `q1` is impossible.
+        assert!(replaying.mresetz(q1));
+        replaying.h(q0);
+        assert!(replaying.mresetz(q0));
+        assert!(replaying.qubit_is_zero(q0) && replaying.qubit_is_zero(q1));
+        assert_eq!(replaying.finish(), vec![true, true, true]);
+    }
+}
+
+#[test]
+fn simulator_at_double_precision_cant_force_outcomes() {
+    let mut sim = SparseSim::new();
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    assert_eq!(sim.forced_m(q, true), None);
+    assert_eq!(
+        SparseSim::for_replay(Precision::Double).precision(),
+        Precision::Double
+    );
+}
@@ -1931,6 +1931,84 @@ fn while_false_shortcut_expr() {
     );
 }
 
+#[test]
+fn bounded_while_expr() {
+    check_expr(
+        indoc! {"
+            namespace Test {
+                @MaxIterations(3)
+                function Count(n : Int) : Int {
+                    mutable i = 0;
+                    while i < n {
+                        set i += 1;
+                    }
+                    i
+                }
+            }
+        "},
+        "Test.Count(3)",
+        &expect!["3"],
+    );
+}
+
+#[test]
+fn bounded_while_exceeding_bound_expr() {
+    check_expr(
+        indoc! {"
+            namespace Test {
+                @MaxIterations(3)
+                function Count(n : Int) : Int {
+                    mutable i = 0;
+                    while i < n {
+                        set i += 1;
+                    }
+                    i
+                }
+            }
+        "},
+        "Test.Count(4)",
+        &expect![[r#"
+            (
+                UserFail(
+                    "while loop exceeded its maximum of 3 iterations",
+                    PackageSpan {
+                        package: PackageId(
+                            2,
+                        ),
+                        span: Span {
+                            lo: 120,
+                            hi: 167,
+                        },
+                    },
+                ),
+                [
+                    Frame {
+                        span: Span {
+                            lo: 120,
+                            hi: 167,
+                        },
+                        id: StoreItemId {
+                            package: PackageId(
+                                2,
+                            ),
+                            item: LocalItemId(
+                                1,
+                            ),
+                        },
+                        caller: PackageId(
+                            2,
+                        ),
+                        functor: FunctorApp {
+                            adjoint: false,
+                            controlled: 0,
+                        },
+                    },
+                ],
+            )
+        "#]],
+    );
+}
+
 #[test]
 fn cond_expr() {
     check_expr("", "true ? 1 | 0", &expect!["1"]);
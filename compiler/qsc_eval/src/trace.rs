@@ -0,0 +1,306 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backend::{Backend, BasisPattern},
+    debug::Frame,
+    val::{self, Value},
+};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+
+/// An event recorded while tracing a simulation. Times are logical: each gate, measurement and
+/// reset takes one unit of time, starting at zero, so that traces are reproducible and don't
+/// depend on a clock.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// A call to a callable started.
+    Enter { time: u64, frame: Frame },
+    /// A call to a callable finished.
+    Exit { time: u64, frame: Frame },
+    /// A gate was applied to the qubits.
+    Gate {
+        time: u64,
+        name: &'static str,
+        qubits: Vec<usize>,
+    },
+    /// A qubit was measured, and reset to zero if `reset` is set.
+    Measurement {
+        time: u64,
+        qubit: usize,
+        reset: bool,
+    },
+    /// A qubit was reset to zero.
+    Reset { time: u64, qubit: usize },
+}
+
+/// A backend that records the calls, gates and measurements of the backend it wraps as a trace.
+///
+/// Calls are traced as the evaluator enters and exits them. Calls to intrinsics are traced too, so
+/// each gate is inside the call to its intrinsic.
+pub struct TracingBackend<'a, B: Backend> {
+    backend: &'a mut B,
+    /// The calls in progress, outermost first.
+    stack: Vec<Frame>,
+    time: u64,
+    events: Vec<TraceEvent>,
+}
+
+impl<'a, B: Backend> TracingBackend<'a, B> {
+    pub fn new(backend: &'a mut B) -> Self {
+        Self {
+            backend,
+            stack: Vec::new(),
+            time: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Exits the calls that are still in progress, such as those that an error interrupted, and
+    /// returns the trace.
+    #[must_use]
+    pub fn finish(mut self) -> Vec<TraceEvent> {
+        while let Some(frame) = self.stack.pop() {
+            self.events.push(TraceEvent::Exit {
+                time: self.time,
+                frame,
+            });
+        }
+        self.events
+    }
+
+    fn gate(&mut self, name: &'static str, qubits: &[usize]) {
+        self.events.push(TraceEvent::Gate {
+            time: self.time,
+            name,
+            qubits: qubits.to_vec(),
+        });
+        self.time += 1;
+    }
+
+    fn measurement(&mut self, qubit: usize, reset: bool) {
+        self.events.push(TraceEvent::Measurement {
+            time: self.time,
+            qubit,
+            reset,
+        });
+        self.time += 1;
+    }
+}
+
+impl<'a, B: Backend> Backend for TracingBackend<'a, B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+        self.gate("CCX", &[ctl0, ctl1, q]);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+        self.gate("CX", &[ctl, q]);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+        self.gate("CY", &[ctl, q]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+        self.gate("CZ", &[ctl, q]);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+        self.gate("H", &[q]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q, false);
+        self.backend.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.measurement(q, true);
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+        self.events.push(TraceEvent::Reset {
+            time: self.time,
+            qubit: q,
+        });
+        self.time += 1;
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+        self.gate("Rx", &[q]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+        self.gate("Rxx", &[q0, q1]);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+        self.gate("Ry", &[q]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+        self.gate("Ryy", &[q0, q1]);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+        self.gate("Rz", &[q]);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+        self.gate("Rzz", &[q0, q1]);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+        self.gate("S Adj", &[q]);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+        self.gate("S", &[q]);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+        self.gate("SWAP", &[q0, q1]);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+        self.gate("T Adj", &[q]);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+        self.gate("T", &[q]);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+        self.gate("X", &[q]);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+        self.gate("Y", &[q]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+        self.gate("Z", &[q]);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn measurement_probability(&mut self, bases: &[(Pauli, usize)]) -> Option<f64> {
+        self.backend.measurement_probability(bases)
+    }
+
+    fn forced_m(&mut self, q: usize, one: bool) -> Option<Self::ResultType> {
+        let result = self.backend.forced_m(q, one)?;
+        self.measurement(q, false);
+        Some(result)
+    }
+
+    fn matching_amplitudes(
+        &mut self,
+        pattern: &BasisPattern,
+    ) -> Option<(Vec<(BigUint, Complex<f64>)>, usize)> {
+        self.backend.matching_amplitudes(pattern)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn barrier(&mut self, qubits: &[usize], label: &str) {
+        self.backend.barrier(qubits, label);
+    }
+
+    fn qubit_label(&mut self, q: usize, label: &str) {
+        self.backend.qubit_label(q, label);
+    }
+
+    fn labels_qubits(&self) -> bool {
+        self.backend.labels_qubits()
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn set_call_stack(&mut self, frames: &[Frame]) {
+        self.backend.set_call_stack(frames);
+    }
+
+    fn enter_call(&mut self, frame: &Frame) {
+        self.events.push(TraceEvent::Enter {
+            time: self.time,
+            frame: *frame,
+        });
+        self.stack.push(*frame);
+        self.backend.enter_call(frame);
+    }
+
+    fn exit_call(&mut self, frame: &Frame) {
+        self.stack.pop();
+        self.events.push(TraceEvent::Exit {
+            time: self.time,
+            frame: *frame,
+        });
+        self.backend.exit_call(frame);
+    }
+
+    fn result_branches(&self) -> bool {
+        self.backend.result_branches()
+    }
+
+    fn result_outcome(&self, id: usize) -> Option<bool> {
+        self.backend.result_outcome(id)
+    }
+
+    fn begin_result_branch(&mut self, lhs: val::Result, rhs: val::Result, eq: bool) {
+        self.backend.begin_result_branch(lhs, rhs, eq);
+    }
+
+    fn else_result_branch(&mut self) {
+        self.backend.else_result_branch();
+    }
+
+    fn end_result_branch(&mut self) {
+        self.backend.end_result_branch();
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{TraceEvent, TracingBackend};
+use crate::{
+    backend::{Backend, SparseSim},
+    debug::Frame,
+    val::FunctorApp,
+};
+use qsc_data_structures::span::Span;
+use qsc_fir::fir::{PackageId, StoreItemId};
+
+fn frame(item: usize, lo: u32) -> Frame {
+    Frame {
+        span: Span { lo, hi: lo + 1 },
+        id: StoreItemId {
+            package: PackageId::from(2),
+            item: item.into(),
+        },
+        caller: PackageId::from(2),
+        functor: FunctorApp::default(),
+    }
+}
+
+#[test]
+fn gates_and_measurements_take_one_unit_of_time() {
+    let mut sim = SparseSim::new();
+    let mut tracing = TracingBackend::new(&mut sim);
+    let q0 = tracing.qubit_allocate();
+    let q1 = tracing.qubit_allocate();
+    tracing.h(q0);
+    tracing.cx(q0, q1);
+    tracing.mresetz(q1);
+    tracing.reset(q0);
+    assert_eq!(
+        tracing.finish(),
+        [
+            TraceEvent::Gate {
+                time: 0,
+                name: "H",
+                qubits: vec![q0],
+            },
+            TraceEvent::Gate {
+                time: 1,
+                name: "CX",
+                qubits: vec![q0, q1],
+            },
+            TraceEvent::Measurement {
+                time: 2,
+                qubit: q1,
+                reset: true,
+            },
+            TraceEvent::Reset { time: 3, qubit: q0 },
+        ]
+    );
+}
+
+#[test]
+fn calls_are_entered_and_exited_around_their_gates() {
+    let main = frame(0, 0);
+    let prepare = frame(1, 10);
+    let mut sim = SparseSim::new();
+    let mut tracing = TracingBackend::new(&mut sim);
+    let q = tracing.qubit_allocate();
+    tracing.enter_call(&main);
+    tracing.enter_call(&prepare);
+    tracing.h(q);
+    tracing.exit_call(&prepare);
+    tracing.m(q);
+    assert_eq!(
+        tracing.finish(),
+        [
+            TraceEvent::Enter {
+                time: 0,
+                frame: main,
+            },
+            TraceEvent::Enter {
+                time: 0,
+                frame: prepare,
+            },
+            TraceEvent::Gate {
+                time: 0,
+                name: "H",
+                qubits: vec![q],
+            },
+            TraceEvent::Exit {
+                time: 1,
+                frame: prepare,
+            },
+            TraceEvent::Measurement {
+                time: 1,
+                qubit: q,
+                reset: false,
+            },
+            // Calls still in progress are exited when the trace finishes.
+            TraceEvent::Exit {
+                time: 2,
+                frame: main,
+            },
+        ]
+    );
+}
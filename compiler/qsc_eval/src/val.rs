@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use crate::format::ValueFormat;
 use num_bigint::BigInt;
 use qsc_fir::fir::{Pauli, StoreItemId};
 use std::{
@@ -73,7 +74,7 @@ impl From<usize> for Result {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Qubit(pub usize);
 
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct FunctorApp {
     /// An invocation is either adjoint or not, with each successive use of `Adjoint` functor switching
     /// between the two, so a bool is sufficient to track.
@@ -94,61 +95,7 @@ impl Display for FunctorApp {
 
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self {
-            Value::Array(arr) => {
-                write!(f, "[")?;
-                join(f, arr.iter(), ", ")?;
-                write!(f, "]")
-            }
-            Value::BigInt(v) => write!(f, "{v}"),
-            Value::Bool(v) => write!(f, "{v}"),
-            Value::Closure(..) => f.write_str("<closure>"),
-            Value::Double(v) => {
-                if (v.floor() - v.ceil()).abs() < f64::EPSILON {
-                    // The value is a whole number, which by convention is displayed with one decimal point
-                    // to differentiate it from an integer value.
-                    write!(f, "{v:.1}")
-                } else {
-                    write!(f, "{v}")
-                }
-            }
-            Value::Global(id, functor) if functor == &FunctorApp::default() => id.fmt(f),
-            Value::Global(id, functor) => write!(f, "{functor} {id}"),
-            Value::Int(v) => write!(f, "{v}"),
-            Value::Pauli(v) => match v {
-                Pauli::I => write!(f, "PauliI"),
-                Pauli::X => write!(f, "PauliX"),
-                Pauli::Z => write!(f, "PauliZ"),
-                Pauli::Y => write!(f, "PauliY"),
-            },
-            Value::Qubit(v) => write!(f, "Qubit{}", (v.0)),
-            &Value::Range(start, step, end) => match (start, step, end) {
-                (Some(start), DEFAULT_RANGE_STEP, Some(end)) => write!(f, "{start}..{end}"),
-                (Some(start), DEFAULT_RANGE_STEP, None) => write!(f, "{start}..."),
-                (Some(start), step, Some(end)) => write!(f, "{start}..{step}..{end}"),
-                (Some(start), step, None) => write!(f, "{start}..{step}..."),
-                (None, DEFAULT_RANGE_STEP, Some(end)) => write!(f, "...{end}"),
-                (None, DEFAULT_RANGE_STEP, None) => write!(f, "..."),
-                (None, step, Some(end)) => write!(f, "...{step}..{end}"),
-                (None, step, None) => write!(f, "...{step}..."),
-            },
-            Value::Result(v) => {
-                if v.unwrap_bool() {
-                    write!(f, "One")
-                } else {
-                    write!(f, "Zero")
-                }
-            }
-            Value::String(v) => write!(f, "{v}"),
-            Value::Tuple(tup) => {
-                write!(f, "(")?;
-                join(f, tup.iter(), ", ")?;
-                if tup.len() == 1 {
-                    write!(f, ",")?;
-                }
-                write!(f, ")")
-            }
-        }
+        ValueFormat::default().write(f, self, None, &|_| None, 0)
     }
 }
 
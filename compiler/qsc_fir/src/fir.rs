@@ -19,9 +19,9 @@ use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
     hash::{Hash, Hasher},
-    rc::Rc,
     result,
     str::FromStr,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -319,7 +319,7 @@ pub enum Global<'a> {
     /// A global callable.
     Callable(&'a CallableDecl),
     /// A global user-defined type.
-    Udt,
+    Udt(&'a Udt),
 }
 
 /// A unique identifier for an item within a package store.
@@ -632,7 +632,7 @@ impl PackageLookup for Package {
         match &self.items.get(id)?.kind {
             ItemKind::Callable(callable) => Some(Global::Callable(callable)),
             ItemKind::Namespace(..) => None,
-            ItemKind::Ty(..) => Some(Global::Udt),
+            ItemKind::Ty(_, udt) => Some(Global::Udt(udt)),
         }
     }
 
@@ -659,7 +659,7 @@ pub struct Item {
     /// The parent item.
     pub parent: Option<LocalItemId>,
     /// The documentation.
-    pub doc: Rc<str>,
+    pub doc: Arc<str>,
     /// The attributes.
     pub attrs: Vec<Attr>,
     /// The visibility.
@@ -1335,7 +1335,7 @@ pub enum StringComponent {
     /// An expression.
     Expr(ExprId),
     /// A string literal.
-    Lit(Rc<str>),
+    Lit(Arc<str>),
 }
 
 /// A pattern.
@@ -1464,7 +1464,7 @@ pub struct Ident {
     /// The span.
     pub span: Span,
     /// The identifier name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
 }
 
 impl Display for Ident {
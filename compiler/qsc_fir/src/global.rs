@@ -7,11 +7,11 @@ use crate::{
 };
 use qsc_data_structures::index_map;
 use rustc_hash::FxHashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub struct Global {
-    pub namespace: Rc<str>,
-    pub name: Rc<str>,
+    pub namespace: Arc<str>,
+    pub name: Arc<str>,
     pub visibility: Visibility,
     pub kind: Kind,
 }
@@ -33,8 +33,8 @@ pub struct Term {
 
 #[derive(Default)]
 pub struct Table {
-    tys: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Ty>>,
-    terms: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Term>>,
+    tys: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Ty>>,
+    terms: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Term>>,
 }
 
 impl Table {
@@ -98,8 +98,8 @@ impl PackageIter<'_> {
 
         match (&item.kind, &parent) {
             (ItemKind::Callable(decl), Some(ItemKind::Namespace(namespace, _))) => Some(Global {
-                namespace: Rc::clone(&namespace.name),
-                name: Rc::clone(&decl.name.name),
+                namespace: Arc::clone(&namespace.name),
+                name: Arc::clone(&decl.name.name),
                 visibility: item.visibility,
                 kind: Kind::Term(Term {
                     id,
@@ -109,8 +109,8 @@ impl PackageIter<'_> {
             }),
             (ItemKind::Ty(name, def), Some(ItemKind::Namespace(namespace, _))) => {
                 self.next = Some(Global {
-                    namespace: Rc::clone(&namespace.name),
-                    name: Rc::clone(&name.name),
+                    namespace: Arc::clone(&namespace.name),
+                    name: Arc::clone(&name.name),
                     visibility: item.visibility,
                     kind: Kind::Term(Term {
                         id,
@@ -119,15 +119,15 @@ impl PackageIter<'_> {
                 });
 
                 Some(Global {
-                    namespace: Rc::clone(&namespace.name),
-                    name: Rc::clone(&name.name),
+                    namespace: Arc::clone(&namespace.name),
+                    name: Arc::clone(&name.name),
                     visibility: item.visibility,
                     kind: Kind::Ty(Ty { id }),
                 })
             }
             (ItemKind::Namespace(ident, _), None) => Some(Global {
                 namespace: "".into(),
-                name: Rc::clone(&ident.name),
+                name: Arc::clone(&ident.name),
                 visibility: Visibility::Public,
                 kind: Kind::Namespace,
             }),
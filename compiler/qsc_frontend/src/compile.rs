@@ -4,6 +4,7 @@
 #[cfg(test)]
 mod tests;
 
+pub mod generate;
 pub mod preprocess;
 
 use crate::{
@@ -13,6 +14,7 @@ use crate::{
     typeck::{self, Checker, Table},
 };
 use bitflags::bitflags;
+use generate::SourceGenerator;
 use miette::{Diagnostic, Report};
 use preprocess::TrackedName;
 use qsc_ast::{
@@ -23,7 +25,9 @@ use qsc_ast::{
     visit::Visitor as _,
 };
 use qsc_data_structures::{
+    edition::Edition,
     index_map::{self, IndexMap},
+    message::MessageParams,
     span::Span,
 };
 use qsc_hir::{
@@ -44,12 +48,14 @@ bitflags! {
         const FloatingPointComputations = 0b0000_0100;
         const BackwardsBranching = 0b0000_1000;
         const HigherLevelConstructs = 0b0001_0000;
+        const Recursion = 0b0010_0000;
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ConfigAttr {
     Unrestricted,
+    Adaptive,
     Base,
 }
 
@@ -58,6 +64,7 @@ impl ConfigAttr {
     pub fn to_str(&self) -> &'static str {
         match self {
             Self::Unrestricted => "Unrestricted",
+            Self::Adaptive => "Adaptive",
             Self::Base => "Base",
         }
     }
@@ -74,44 +81,100 @@ impl FromStr for ConfigAttr {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "Unrestricted" => Ok(ConfigAttr::Unrestricted),
+            "Adaptive" => Ok(ConfigAttr::Adaptive),
             "Base" => Ok(ConfigAttr::Base),
             _ => Err(()),
         }
     }
 }
 
+impl RuntimeCapabilityFlags {
+    /// The capabilities of the Base profile, which runs a fixed sequence of gates and measurements.
+    #[must_use]
+    pub const fn base() -> Self {
+        Self::empty()
+    }
+
+    /// The capabilities of the `Adaptive_RI` profile, which can branch on measurement results and
+    /// compute integers from them.
+    #[must_use]
+    pub const fn adaptive_ri() -> Self {
+        Self::ForwardBranching.union(Self::IntegerComputations)
+    }
+
+    /// The capabilities of the `Adaptive_RIF` profile, which can also compute floating-point
+    /// numbers from measurement results.
+    #[must_use]
+    pub const fn adaptive_rif() -> Self {
+        Self::adaptive_ri().union(Self::FloatingPointComputations)
+    }
+
+    /// Every capability, which simulators have.
+    #[must_use]
+    pub const fn unrestricted() -> Self {
+        Self::all()
+    }
+
+    /// The named presets, from the least to the most capable.
+    #[must_use]
+    pub const fn presets() -> [(&'static str, Self); 4] {
+        [
+            ("Base", Self::base()),
+            ("Adaptive_RI", Self::adaptive_ri()),
+            ("Adaptive_RIF", Self::adaptive_rif()),
+            ("Unrestricted", Self::unrestricted()),
+        ]
+    }
+
+    /// The least capable preset that has all of these capabilities, with its name.
+    #[must_use]
+    pub fn minimal_preset(self) -> (&'static str, Self) {
+        Self::presets()
+            .into_iter()
+            .find(|(_, preset)| preset.contains(self))
+            .expect("unrestricted preset should have every capability")
+    }
+}
+
 impl From<ConfigAttr> for RuntimeCapabilityFlags {
     fn from(value: ConfigAttr) -> Self {
         match value {
-            ConfigAttr::Unrestricted => Self::all(),
-            ConfigAttr::Base => Self::empty(),
+            ConfigAttr::Unrestricted => Self::unrestricted(),
+            ConfigAttr::Adaptive => Self::adaptive_ri(),
+            ConfigAttr::Base => Self::base(),
         }
     }
 }
 
 #[allow(clippy::module_name_repetitions)]
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct CompileUnit {
     pub package: hir::Package,
     pub ast: AstPackage,
     pub assigner: HirAssigner,
     pub sources: SourceMap,
     pub errors: Vec<Error>,
+    /// Diagnostics that don't stop the package from compiling, such as uses of deprecated aliases.
+    pub warnings: Vec<Error>,
     pub dropped_names: Vec<TrackedName>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct AstPackage {
     pub package: ast::Package,
     pub tys: Table,
     pub names: Names,
     pub locals: Locals,
+    /// The spans of the items that were left out because their `@Config` attribute didn't match.
+    pub inactive: Vec<Span>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct SourceMap {
     sources: Vec<Source>,
     entry: Option<Source>,
+    features: Vec<Arc<str>>,
+    edition: Edition,
 }
 
 impl SourceMap {
@@ -141,9 +204,41 @@ impl SourceMap {
         Self {
             sources: offset_sources,
             entry: entry_source,
+            features: Vec::new(),
+            edition: Edition::default(),
         }
     }
 
+    /// Enables the feature flags, which include the items marked with `@Config(feature = "name")`
+    /// in the compilation.
+    #[must_use]
+    pub fn with_features<S: Into<Arc<str>>>(
+        mut self,
+        features: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The enabled feature flags.
+    #[must_use]
+    pub fn features(&self) -> &[Arc<str>] {
+        &self.features
+    }
+
+    /// Sets the edition of the language that the sources are written in.
+    #[must_use]
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// The edition of the language that the sources are written in.
+    #[must_use]
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
     pub fn push(&mut self, name: SourceName, contents: SourceContents) -> u32 {
         let offset = next_offset(self.sources.last());
 
@@ -173,6 +268,12 @@ impl SourceMap {
     pub fn iter(&self) -> impl Iterator<Item = &Source> {
         self.sources.iter()
     }
+
+    /// The entry expression source, if there is one.
+    #[must_use]
+    pub fn entry(&self) -> Option<&Source> {
+        self.entry.as_ref()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -202,11 +303,29 @@ pub(super) enum ErrorKind {
     Type(#[from] typeck::Error),
     #[error(transparent)]
     Lower(#[from] lower::Error),
+    #[error(transparent)]
+    Generate(#[from] generate::Error),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match &self.0 {
+            ErrorKind::Parse(error) => error.message_params(),
+            ErrorKind::Resolve(error) => error.message_params(),
+            ErrorKind::Type(error) => error.message_params(),
+            ErrorKind::Lower(error) => error.message_params(),
+            ErrorKind::Generate(error) => error.message_params(),
+        }
+    }
 }
 
+/// The compiled packages, each behind a shared pointer so that taking a snapshot of the store only
+/// copies the pointers. A package is copied the first time it is changed while a snapshot shares it,
+/// so the snapshot keeps the version it was taken with. The pointers are atomically counted, so a
+/// store can be shared across threads.
 pub struct PackageStore {
-    core: global::Table,
-    units: IndexMap<PackageId, CompileUnit>,
+    core: Arc<global::Table>,
+    units: IndexMap<PackageId, Arc<CompileUnit>>,
     next_id: PackageId,
 }
 
@@ -221,9 +340,9 @@ impl PackageStore {
     pub fn new(core: CompileUnit) -> Self {
         let table = global::iter_package(Some(PackageId::CORE), &core.package).collect();
         let mut units = IndexMap::new();
-        units.insert(PackageId::CORE, core);
+        units.insert(PackageId::CORE, Arc::new(core));
         Self {
-            core: table,
+            core: Arc::new(table),
             units,
             next_id: PackageId::CORE.successor(),
         }
@@ -237,13 +356,33 @@ impl PackageStore {
     pub fn insert(&mut self, unit: CompileUnit) -> PackageId {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, unit);
+        self.units.insert(id, Arc::new(unit));
         id
     }
 
     #[must_use]
     pub fn get(&self, id: PackageId) -> Option<&CompileUnit> {
-        self.units.get(id)
+        self.units.get(id).map(Arc::as_ref)
+    }
+
+    /// Removes a package from the store, returning its compilation unit. Packages that depend on
+    /// the removed package should be removed along with it. Snapshots that share the package keep
+    /// it.
+    pub fn remove(&mut self, id: PackageId) -> Option<CompileUnit> {
+        let unit = self.units.get_mut(id).map(std::mem::take);
+        self.units.remove(id);
+        unit.map(|unit| Arc::try_unwrap(unit).unwrap_or_else(|unit| (*unit).clone()))
+    }
+
+    /// A snapshot of the store that shares its packages. Later changes to either store, including
+    /// to its open package, are not seen by the other.
+    #[must_use]
+    pub fn snapshot(&self) -> Self {
+        Self {
+            core: Arc::clone(&self.core),
+            units: self.units.clone(),
+            next_id: self.next_id,
+        }
     }
 
     #[must_use]
@@ -258,7 +397,7 @@ impl PackageStore {
     pub fn open(mut self) -> OpenPackageStore {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, CompileUnit::default());
+        self.units.insert(id, Arc::default());
 
         OpenPackageStore {
             store: self,
@@ -303,10 +442,12 @@ impl OpenPackageStore {
 
         (
             &self.store.core,
-            self.store
-                .units
-                .get_mut(id)
-                .expect("open package id should exist in store"),
+            Arc::make_mut(
+                self.store
+                    .units
+                    .get_mut(id)
+                    .expect("open package id should exist in store"),
+            ),
         )
     }
 
@@ -318,13 +459,13 @@ impl OpenPackageStore {
     }
 }
 
-pub struct Iter<'a>(index_map::Iter<'a, PackageId, CompileUnit>);
+pub struct Iter<'a>(index_map::Iter<'a, PackageId, Arc<CompileUnit>>);
 
 impl<'a> Iterator for Iter<'a> {
     type Item = (PackageId, &'a CompileUnit);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|(id, unit)| (id, Arc::as_ref(unit)))
     }
 }
 
@@ -337,58 +478,180 @@ impl MutVisitor for Offsetter {
     }
 }
 
+#[must_use]
 pub fn compile(
     store: &PackageStore,
     dependencies: &[PackageId],
     sources: SourceMap,
     capabilities: RuntimeCapabilityFlags,
 ) -> CompileUnit {
-    let (mut ast_package, parse_errors) = parse_all(&sources);
+    compile_with_dependencies(
+        &Dependencies::new(store, dependencies),
+        sources,
+        capabilities,
+    )
+}
+
+/// Compiles the sources like [`compile`] against dependencies whose global tables are already
+/// built.
+#[must_use]
+pub fn compile_with_dependencies(
+    dependencies: &Dependencies,
+    sources: SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+) -> CompileUnit {
+    let parsed = parse(&sources, capabilities);
+    let resolved = resolve_all(
+        dependencies.names.clone(),
+        &dependencies.dropped_names,
+        &parsed,
+    );
+    let checked = typeck_all(dependencies.tys.clone(), &parsed, &resolved);
+    lower(sources, parsed, resolved, checked)
+}
 
-    let mut cond_compile = preprocess::Conditional::new(capabilities);
-    cond_compile.visit_package(&mut ast_package);
+/// Compiles the sources like [`compile`], first running the source generators on the items with
+/// their attributes and adding the sources they generate to the package. Generated sources are not
+/// passed to the generators again.
+#[must_use]
+pub fn compile_with_generators(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    mut sources: SourceMap,
+    capabilities: RuntimeCapabilityFlags,
+    generators: &[&dyn SourceGenerator],
+) -> CompileUnit {
+    let parsed = parse(&sources, capabilities);
+    let (generated, errors) = generate::expand(generators, &sources, &parsed.package);
+    let mut parsed = if generated.is_empty() {
+        parsed
+    } else {
+        for (name, contents) in generated {
+            sources.push(name.into(), contents.into());
+        }
+        parse(&sources, capabilities)
+    };
+    generate::StripAttrs(generators).visit_package(&mut parsed.package);
+    parsed
+        .errors
+        .extend(errors.into_iter().map(|e| Error(e.into())));
+
+    let dependencies = Dependencies::new(store, dependencies);
+    let resolved = resolve_all(dependencies.names, &dependencies.dropped_names, &parsed);
+    let checked = typeck_all(dependencies.tys, &parsed, &resolved);
+    lower(sources, parsed, resolved, checked)
+}
+
+/// The AST of a package, after conditional compilation has dropped the items that don't apply to
+/// the target.
+pub struct ParsedPackage {
+    pub package: ast::Package,
+    pub dropped_names: Vec<TrackedName>,
+    /// The spans of the dropped items.
+    pub inactive: Vec<Span>,
+    /// The edition the package was parsed in, which type checking follows too.
+    pub edition: Edition,
+    pub errors: Vec<Error>,
+}
+
+/// The names and locals that the identifiers in a package refer to.
+pub struct ResolvedPackage {
+    pub assigner: HirAssigner,
+    pub names: Names,
+    pub locals: Locals,
+    pub errors: Vec<Error>,
+}
+
+/// The types of the expressions in a package.
+pub struct CheckedPackage {
+    pub tys: Table,
+    pub errors: Vec<Error>,
+}
+
+/// Parses the sources into a package and drops the items that conditional compilation excludes
+/// for the given capabilities and the features enabled in the sources. This is the first phase of
+/// [`compile`].
+#[must_use]
+pub fn parse(sources: &SourceMap, capabilities: RuntimeCapabilityFlags) -> ParsedPackage {
+    let (mut package, errors) = parse_all(sources);
+
+    let mut cond_compile = preprocess::Conditional::new(capabilities, sources.features());
+    cond_compile.visit_package(&mut package);
+    let inactive = cond_compile.inactive().to_vec();
     let dropped_names = cond_compile.into_names();
 
-    let mut ast_assigner = AstAssigner::new();
-    ast_assigner.visit_package(&mut ast_package);
-    AstValidator::default().visit_package(&ast_package);
-    let mut hir_assigner = HirAssigner::new();
-    let (names, locals, name_errors) = resolve_all(
-        store,
-        dependencies,
-        &mut hir_assigner,
-        &ast_package,
-        dropped_names.clone(),
-    );
-    let (tys, ty_errors) = typeck_all(store, dependencies, &ast_package, &names);
+    AstAssigner::new().visit_package(&mut package);
+    AstValidator::default().visit_package(&package);
+    ParsedPackage {
+        package,
+        dropped_names,
+        inactive,
+        edition: sources.edition,
+        errors: errors.into_iter().map(|e| Error(e.into())).collect(),
+    }
+}
+
+/// Resolves the names in a parsed package against the package and its dependencies. This is the
+/// second phase of [`compile`].
+#[must_use]
+pub fn resolve(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    parsed: &ParsedPackage,
+) -> ResolvedPackage {
+    let (globals, dropped_names) = resolve_globals(store, dependencies);
+    resolve_all(globals, &dropped_names, parsed)
+}
+
+/// Infers and checks the types in a resolved package. This is the third phase of [`compile`].
+#[must_use]
+pub fn typeck(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    parsed: &ParsedPackage,
+    resolved: &ResolvedPackage,
+) -> CheckedPackage {
+    typeck_all(typeck_globals(store, dependencies), parsed, resolved)
+}
+
+/// Lowers a type checked package to HIR, collecting the errors of every phase into the compile
+/// unit. This is the last phase of [`compile`].
+#[must_use]
+pub fn lower(
+    sources: SourceMap,
+    parsed: ParsedPackage,
+    mut resolved: ResolvedPackage,
+    checked: CheckedPackage,
+) -> CompileUnit {
     let mut lowerer = Lowerer::new();
     let package = lowerer
-        .with(&mut hir_assigner, &names, &tys)
-        .lower_package(&ast_package);
+        .with(&mut resolved.assigner, &resolved.names, &checked.tys)
+        .lower_package(&parsed.package);
     HirValidator::default().visit_package(&package);
     let lower_errors = lowerer.drain_errors();
 
-    let errors = parse_errors
+    let (warnings, errors) = parsed
+        .errors
         .into_iter()
-        .map(Into::into)
-        .chain(name_errors.into_iter().map(Into::into))
-        .chain(ty_errors.into_iter().map(Into::into))
-        .chain(lower_errors.into_iter().map(Into::into))
-        .map(Error)
-        .collect();
+        .chain(resolved.errors)
+        .chain(checked.errors)
+        .chain(lower_errors.map(|e| Error(e.into())))
+        .partition(is_warning);
 
     CompileUnit {
         package,
         ast: AstPackage {
-            package: ast_package,
-            tys,
-            names,
-            locals,
+            package: parsed.package,
+            tys: checked.tys,
+            names: resolved.names,
+            locals: resolved.locals,
+            inactive: parsed.inactive,
         },
-        assigner: hir_assigner,
+        assigner: resolved.assigner,
         sources,
         errors,
-        dropped_names,
+        warnings,
+        dropped_names: parsed.dropped_names,
     }
 }
 
@@ -400,7 +663,7 @@ pub fn compile(
 #[must_use]
 pub fn core() -> CompileUnit {
     let store = PackageStore {
-        core: global::Table::default(),
+        core: Arc::default(),
         units: IndexMap::new(),
         next_id: PackageId::CORE,
     };
@@ -438,7 +701,8 @@ fn parse_all(sources: &SourceMap) -> (ast::Package, Vec<qsc_parse::Error>) {
     let mut namespaces = Vec::new();
     let mut errors = Vec::new();
     for source in &sources.sources {
-        let (source_namespaces, source_errors) = qsc_parse::namespaces(&source.contents);
+        let (source_namespaces, source_errors) =
+            qsc_parse::namespaces(&source.contents, sources.edition);
         for mut namespace in source_namespaces {
             Offsetter(source.offset).visit_namespace(&mut namespace);
             namespaces.push(TopLevelNode::Namespace(namespace));
@@ -452,7 +716,7 @@ fn parse_all(sources: &SourceMap) -> (ast::Package, Vec<qsc_parse::Error>) {
         .as_ref()
         .filter(|source| !source.contents.is_empty())
         .map(|source| {
-            let (mut entry, entry_errors) = qsc_parse::expr(&source.contents);
+            let (mut entry, entry_errors) = qsc_parse::expr(&source.contents, sources.edition);
             Offsetter(source.offset).visit_expr(&mut entry);
             append_parse_errors(&mut errors, source.offset, entry_errors);
             entry
@@ -467,14 +731,47 @@ fn parse_all(sources: &SourceMap) -> (ast::Package, Vec<qsc_parse::Error>) {
     (package, errors)
 }
 
-fn resolve_all(
+/// The global names and types of the dependencies of a package. Building them walks every item of
+/// every dependency, so they can be built once and shared by many packages compiled against the
+/// same dependencies. Cloning them only copies pointers to the shared tables.
+#[derive(Clone)]
+pub struct Dependencies {
+    ids: Vec<PackageId>,
+    names: resolve::GlobalTable,
+    tys: typeck::GlobalTable,
+    dropped_names: Vec<TrackedName>,
+}
+
+impl Dependencies {
+    /// Builds the global tables of the core library and the given dependencies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dependency is not in the store.
+    #[must_use]
+    pub fn new(store: &PackageStore, dependencies: &[PackageId]) -> Self {
+        let (names, dropped_names) = resolve_globals(store, dependencies);
+        Self {
+            ids: dependencies.to_vec(),
+            names: names.into_shared(),
+            tys: typeck_globals(store, dependencies),
+            dropped_names,
+        }
+    }
+
+    /// The IDs of the dependencies, not including the core library.
+    #[must_use]
+    pub fn ids(&self) -> &[PackageId] {
+        &self.ids
+    }
+}
+
+fn resolve_globals(
     store: &PackageStore,
     dependencies: &[PackageId],
-    assigner: &mut HirAssigner,
-    package: &ast::Package,
-    mut dropped_names: Vec<TrackedName>,
-) -> (Names, Locals, Vec<resolve::Error>) {
+) -> (resolve::GlobalTable, Vec<TrackedName>) {
     let mut globals = resolve::GlobalTable::new();
+    let mut dropped_names = Vec::new();
     if let Some(unit) = store.get(PackageId::CORE) {
         globals.add_external_package(PackageId::CORE, &unit.package);
         dropped_names.extend(unit.dropped_names.iter().cloned());
@@ -488,20 +785,31 @@ fn resolve_all(
         dropped_names.extend(unit.dropped_names.iter().cloned());
     }
 
-    let mut errors = globals.add_local_package(assigner, package);
+    (globals, dropped_names)
+}
+
+fn resolve_all(
+    mut globals: resolve::GlobalTable,
+    dependency_dropped_names: &[TrackedName],
+    parsed: &ParsedPackage,
+) -> ResolvedPackage {
+    let mut assigner = HirAssigner::new();
+    let mut dropped_names = parsed.dropped_names.clone();
+    dropped_names.extend(dependency_dropped_names.iter().cloned());
+    let mut errors = globals.add_local_package(&mut assigner, &parsed.package);
     let mut resolver = Resolver::new(globals, dropped_names);
-    resolver.with(assigner).visit_package(package);
+    resolver.with(&mut assigner).visit_package(&parsed.package);
     let (names, locals, mut resolver_errors) = resolver.into_result();
     errors.append(&mut resolver_errors);
-    (names, locals, errors)
+    ResolvedPackage {
+        assigner,
+        names,
+        locals,
+        errors: errors.into_iter().map(|e| Error(e.into())).collect(),
+    }
 }
 
-fn typeck_all(
-    store: &PackageStore,
-    dependencies: &[PackageId],
-    package: &ast::Package,
-    names: &Names,
-) -> (typeck::Table, Vec<typeck::Error>) {
+fn typeck_globals(store: &PackageStore, dependencies: &[PackageId]) -> typeck::GlobalTable {
     let mut globals = typeck::GlobalTable::new();
     if let Some(unit) = store.get(PackageId::CORE) {
         globals.add_external_package(PackageId::CORE, &unit.package);
@@ -514,9 +822,26 @@ fn typeck_all(
         globals.add_external_package(id, &unit.package);
     }
 
-    let mut checker = Checker::new(globals);
-    checker.check_package(names, package);
-    checker.into_table()
+    globals
+}
+
+fn typeck_all(
+    globals: typeck::GlobalTable,
+    parsed: &ParsedPackage,
+    resolved: &ResolvedPackage,
+) -> CheckedPackage {
+    let mut checker = Checker::new(globals, parsed.edition);
+    checker.check_package(&resolved.names, &parsed.package);
+    let (tys, errors) = checker.into_table();
+    CheckedPackage {
+        tys,
+        errors: errors.into_iter().map(|e| Error(e.into())).collect(),
+    }
+}
+
+/// Whether the diagnostic is a warning, which is kept apart from the errors of a compilation.
+pub(crate) fn is_warning(error: &Error) -> bool {
+    error.severity() == Some(miette::Severity::Warning)
 }
 
 fn append_parse_errors(
@@ -0,0 +1,145 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Source generators registered by the host. Before names are resolved, each item with an attribute
+//! that a generator handles is passed to the generator, and the Q# source it returns is added to
+//! the source map as a new source. The generated source is then compiled with the rest of the
+//! package, so diagnostics and debugging point into it like any other source.
+
+use super::SourceMap;
+use miette::Diagnostic;
+use qsc_ast::{
+    ast::{Attr, ItemKind, Namespace, TopLevelNode},
+    mut_visit::MutVisitor,
+};
+use qsc_data_structures::{message::MessageParams, span::Span};
+use thiserror::Error;
+
+/// Generates Q# source for the items that have its attribute.
+pub trait SourceGenerator {
+    /// The name of the attribute that the generator handles, without the `@`.
+    fn attribute(&self) -> &str;
+
+    /// Generates the source for one item with the attribute. The source is compiled as a separate
+    /// file of the package, so it must be made of namespaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing why the source couldn't be generated.
+    fn generate(&self, request: &Request) -> Result<String, String>;
+}
+
+/// An item that has the attribute of a source generator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Request<'a> {
+    /// The namespace of the item.
+    pub namespace: &'a str,
+    /// The name of the item.
+    pub item: &'a str,
+    /// The argument of the attribute as written in the source, without the surrounding parentheses.
+    pub arg: &'a str,
+}
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("source generator for `@{0}` failed: {1}")]
+    #[diagnostic(code("Qsc.Generate.Failed"))]
+    Failed(String, String, #[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Failed(name, message, _) => vec![name.clone(), message.clone()],
+        }
+    }
+}
+
+/// Runs the generators on the items of the package, returning the generated sources with their
+/// names.
+pub(super) fn expand(
+    generators: &[&dyn SourceGenerator],
+    sources: &SourceMap,
+    package: &qsc_ast::ast::Package,
+) -> (Vec<(String, String)>, Vec<Error>) {
+    let mut generated = Vec::new();
+    let mut errors = Vec::new();
+    for node in &*package.nodes {
+        let TopLevelNode::Namespace(namespace) = node else {
+            continue;
+        };
+        for item in &*namespace.items {
+            let name = match item.kind.as_ref() {
+                ItemKind::Callable(decl) => decl.name.name.as_ref(),
+                ItemKind::Ty(ident, _) => ident.name.as_ref(),
+                ItemKind::Err | ItemKind::Open(..) => continue,
+            };
+            for attr in &*item.attrs {
+                let Some(generator) = find(generators, attr) else {
+                    continue;
+                };
+                let request = Request {
+                    namespace: &namespace.name.name,
+                    item: name,
+                    arg: arg_source(sources, attr),
+                };
+                match generator.generate(&request) {
+                    Ok(source) => generated.push((
+                        format!(
+                            "<generated @{} for {}.{name}>",
+                            attr.name.name, request.namespace
+                        ),
+                        source,
+                    )),
+                    Err(message) => errors.push(Error::Failed(
+                        attr.name.name.to_string(),
+                        message,
+                        attr.span,
+                    )),
+                }
+            }
+        }
+    }
+
+    (generated, errors)
+}
+
+/// Removes the attributes handled by the generators, so that they aren't reported as unknown.
+pub(super) struct StripAttrs<'a>(pub(super) &'a [&'a dyn SourceGenerator]);
+
+impl MutVisitor for StripAttrs<'_> {
+    fn visit_namespace(&mut self, namespace: &mut Namespace) {
+        for item in &mut namespace.items {
+            item.attrs = item
+                .attrs
+                .iter()
+                .filter(|attr| find(self.0, attr).is_none())
+                .cloned()
+                .collect();
+        }
+    }
+}
+
+fn find<'a>(
+    generators: &[&'a dyn SourceGenerator],
+    attr: &Attr,
+) -> Option<&'a dyn SourceGenerator> {
+    generators
+        .iter()
+        .find(|generator| generator.attribute() == attr.name.name.as_ref())
+        .copied()
+}
+
+fn arg_source<'a>(sources: &'a SourceMap, attr: &Attr) -> &'a str {
+    let span = attr.arg.span;
+    let Some(source) = sources.find_by_offset(span.lo) else {
+        return "";
+    };
+    let lo = (span.lo - source.offset) as usize;
+    let hi = (span.hi - source.offset) as usize;
+    let arg = source.contents[lo..hi].trim();
+    arg.strip_prefix('(')
+        .and_then(|arg| arg.strip_suffix(')'))
+        .unwrap_or(arg)
+        .trim()
+}
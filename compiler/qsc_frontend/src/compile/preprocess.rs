@@ -3,35 +3,45 @@
 
 use core::str::FromStr;
 use qsc_ast::{
-    ast::{Attr, ExprKind, ItemKind, Namespace, Stmt, StmtKind},
+    ast::{Attr, Expr, ExprKind, ItemKind, Lit, Namespace, Stmt, StmtKind},
     mut_visit::MutVisitor,
 };
+use qsc_data_structures::span::Span;
 use qsc_hir::hir;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use super::{ConfigAttr, RuntimeCapabilityFlags};
 
 #[derive(PartialEq, Hash, Clone, Debug)]
 pub struct TrackedName {
-    pub name: Rc<str>,
-    pub namespace: Rc<str>,
+    pub name: Arc<str>,
+    pub namespace: Arc<str>,
 }
 
 pub(crate) struct Conditional {
     capabilities: RuntimeCapabilityFlags,
+    features: Vec<Arc<str>>,
     dropped_names: Vec<TrackedName>,
     included_names: Vec<TrackedName>,
+    inactive: Vec<Span>,
 }
 
 impl Conditional {
-    pub(crate) fn new(capabilities: RuntimeCapabilityFlags) -> Self {
+    pub(crate) fn new(capabilities: RuntimeCapabilityFlags, features: &[Arc<str>]) -> Self {
         Self {
             capabilities,
+            features: features.to_vec(),
             dropped_names: Vec::new(),
             included_names: Vec::new(),
+            inactive: Vec::new(),
         }
     }
 
+    /// The spans of the items that were dropped.
+    pub(crate) fn inactive(&self) -> &[Span] {
+        &self.inactive
+    }
+
     pub(crate) fn into_names(self) -> Vec<TrackedName> {
         self.dropped_names
             .into_iter()
@@ -46,7 +56,7 @@ impl MutVisitor for Conditional {
             .items
             .iter()
             .filter_map(|item| {
-                if matches_config(&item.attrs, self.capabilities) {
+                if matches_config(&item.attrs, self.capabilities, &self.features) {
                     match item.kind.as_ref() {
                         ItemKind::Callable(callable) => {
                             self.included_names.push(TrackedName {
@@ -62,6 +72,7 @@ impl MutVisitor for Conditional {
                     }
                     Some(item.clone())
                 } else {
+                    self.inactive.push(item.span);
                     match item.kind.as_ref() {
                         ItemKind::Callable(callable) => {
                             self.dropped_names.push(TrackedName {
@@ -84,31 +95,32 @@ impl MutVisitor for Conditional {
 
     fn visit_stmt(&mut self, stmt: &mut Stmt) {
         if let StmtKind::Item(item) = stmt.kind.as_mut() {
-            if matches_config(&item.attrs, self.capabilities) {
+            if matches_config(&item.attrs, self.capabilities, &self.features) {
                 match item.kind.as_ref() {
                     ItemKind::Callable(callable) => {
                         self.included_names.push(TrackedName {
                             name: callable.name.name.clone(),
-                            namespace: Rc::from(""),
+                            namespace: Arc::from(""),
                         });
                     }
                     ItemKind::Ty(ident, _) => self.included_names.push(TrackedName {
                         name: ident.name.clone(),
-                        namespace: Rc::from(""),
+                        namespace: Arc::from(""),
                     }),
                     _ => {}
                 }
             } else {
+                self.inactive.push(item.span);
                 match item.kind.as_ref() {
                     ItemKind::Callable(callable) => {
                         self.dropped_names.push(TrackedName {
                             name: callable.name.name.clone(),
-                            namespace: Rc::from(""),
+                            namespace: Arc::from(""),
                         });
                     }
                     ItemKind::Ty(ident, _) => self.dropped_names.push(TrackedName {
                         name: ident.name.clone(),
-                        namespace: Rc::from(""),
+                        namespace: Arc::from(""),
                     }),
                     _ => {}
                 }
@@ -118,7 +130,11 @@ impl MutVisitor for Conditional {
     }
 }
 
-fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) -> bool {
+fn matches_config(
+    attrs: &[Box<Attr>],
+    capabilities: RuntimeCapabilityFlags,
+    features: &[Arc<str>],
+) -> bool {
     attrs.iter().all(|attr| {
         if hir::Attr::from_str(attr.name.name.as_ref()) == Ok(hir::Attr::Config) {
             if let ExprKind::Paren(inner) = attr.arg.kind.as_ref() {
@@ -126,11 +142,20 @@ fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) ->
                     // If there is no config attribute, then we assume that the item matches
                     // the target. We can't do membership tests on the capabilities because
                     // Base is not a subset of any capabilities, it is a lack of capabilities.
+                    // Adaptive targets are restricted too, so they keep the Base items and
+                    // add the Adaptive ones.
                     ExprKind::Path(path) => match ConfigAttr::from_str(path.name.name.as_ref()) {
                         Ok(ConfigAttr::Unrestricted) => capabilities.is_all(),
-                        Ok(ConfigAttr::Base) => capabilities.is_empty(),
+                        Ok(ConfigAttr::Adaptive) => capabilities == ConfigAttr::Adaptive.into(),
+                        Ok(ConfigAttr::Base) => {
+                            capabilities.is_empty() || capabilities == ConfigAttr::Adaptive.into()
+                        }
                         _ => true,
                     },
+                    ExprKind::Assign(lhs, rhs) => match feature_name(lhs, rhs) {
+                        Some(name) => features.iter().any(|feature| feature.as_ref() == name),
+                        None => true,
+                    },
                     _ => true, // Unknown config attribute, so we assume it matches
                 }
             } else {
@@ -143,3 +168,18 @@ fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) ->
         }
     })
 }
+
+/// The name of the feature in a `feature = "name"` setting.
+pub(crate) fn feature_name<'a>(lhs: &Expr, rhs: &'a Expr) -> Option<&'a str> {
+    match (lhs.kind.as_ref(), rhs.kind.as_ref()) {
+        (ExprKind::Path(path), ExprKind::Lit(lit))
+            if path.namespace.is_none() && path.name.name.as_ref() == "feature" =>
+        {
+            match lit.as_ref() {
+                Lit::String(name) => Some(name.as_ref()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
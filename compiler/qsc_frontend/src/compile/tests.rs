@@ -5,7 +5,13 @@
 
 use crate::compile::RuntimeCapabilityFlags;
 
-use super::{compile, Error, PackageStore, SourceMap};
+use super::{
+    compile, compile_with_dependencies, compile_with_generators,
+    generate::{Request, SourceGenerator},
+    lower, parse,
+    preprocess::TrackedName,
+    resolve, typeck, CompileUnit, Dependencies, Error, ErrorKind, PackageStore, SourceMap,
+};
 use expect_test::expect;
 use indoc::indoc;
 use miette::Diagnostic;
@@ -13,11 +19,13 @@ use qsc_data_structures::span::Span;
 use qsc_hir::{
     global,
     hir::{
-        Block, Expr, ExprKind, ItemId, ItemKind, Lit, LocalItemId, NodeId, Res, SpecBody, Stmt,
-        StmtKind,
+        Block, Expr, ExprKind, ItemId, ItemKind, Lit, LocalItemId, NodeId, Package, Res, SpecBody,
+        Stmt, StmtKind,
     },
     mut_visit::MutVisitor,
+    renumber::renumber,
     ty::{Prim, Ty},
+    visit::{self, Visitor},
 };
 
 fn error_span(error: &Error) -> Span {
@@ -933,6 +941,62 @@ fn unimplemented_call_from_dependency_produces_error() {
     .assert_debug_eq(&unit.errors);
 }
 
+#[test]
+fn alias_from_dependency_produces_warning() {
+    let lib_sources = SourceMap::new(
+        [(
+            "lib".into(),
+            indoc! {"
+                namespace Foo {
+                    @Alias(\"Old.Bar\")
+                    operation Bar() : Unit {}
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let mut store = PackageStore::new(super::core());
+    let lib = compile(&store, &[], lib_sources, RuntimeCapabilityFlags::all());
+    assert!(lib.errors.is_empty(), "{:#?}", lib.errors);
+    let lib = store.insert(lib);
+
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Test {
+                    open Old;
+                    operation Main() : Unit {
+                        Bar();
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(&store, &[lib], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    expect![[r#"
+        [
+            Error(
+                Resolve(
+                    DeprecatedAlias(
+                        "Bar",
+                        "Foo.Bar",
+                        Span {
+                            lo: 69,
+                            hi: 72,
+                        },
+                    ),
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&unit.warnings);
+}
+
 #[test]
 fn unimplemented_attribute_call_within_unit_error() {
     let sources = SourceMap::new(
@@ -1103,3 +1167,379 @@ fn duplicate_intrinsic_from_dependency() {
     "#]]
     .assert_debug_eq(&unit.errors);
 }
+
+#[test]
+fn phases_report_their_own_errors() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Foo {
+                    function A() : Int { B() }
+                    function C() : Int { 1.0 }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+
+    let store = PackageStore::new(super::core());
+    let parsed = parse(&sources, RuntimeCapabilityFlags::all());
+    assert!(parsed.errors.is_empty(), "{:#?}", parsed.errors);
+    let resolved = resolve(&store, &[], &parsed);
+    assert_eq!(resolved.errors.len(), 1, "{:#?}", resolved.errors);
+    let checked = typeck(&store, &[], &parsed, &resolved);
+    assert!(!checked.errors.is_empty(), "{:#?}", checked.errors);
+
+    let num_errors = resolved.errors.len() + checked.errors.len();
+    let unit = lower(sources, parsed, resolved, checked);
+    assert_eq!(unit.errors.len(), num_errors, "{:#?}", unit.errors);
+}
+
+#[test]
+fn dependencies_are_reused_across_packages() {
+    let mut store = PackageStore::new(super::core());
+    let std = store.insert(super::std(&store, RuntimeCapabilityFlags::all()));
+    let dependencies = Dependencies::new(&store, &[std]);
+    for (name, source) in [
+        (
+            "first",
+            "namespace A { operation Main() : Unit { use q = Qubit(); X(q); } }",
+        ),
+        (
+            "second",
+            "namespace A { function Main() : Int { Length([1, 2]) } }",
+        ),
+    ] {
+        let sources = SourceMap::new([(name.into(), source.into())], None);
+        let unit = compile_with_dependencies(&dependencies, sources, RuntimeCapabilityFlags::all());
+        assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    }
+
+    let sources = SourceMap::new(
+        [(
+            "third".into(),
+            "namespace A { function Main() : Int { 1.0 } }".into(),
+        )],
+        None,
+    );
+    let unit = compile_with_dependencies(&dependencies, sources, RuntimeCapabilityFlags::all());
+    assert_eq!(unit.errors.len(), 1, "{:#?}", unit.errors);
+}
+
+#[test]
+fn snapshot_is_unaffected_by_later_changes() {
+    let mut store = PackageStore::new(super::core());
+    let std = store.insert(super::std(&store, RuntimeCapabilityFlags::all()));
+    let mut open = store.open();
+    let snapshot = open.package_store().snapshot();
+    let id = open.open_package_id();
+
+    let (_, unit) = open.get_open_mut();
+    unit.dropped_names.push(TrackedName {
+        name: "Foo".into(),
+        namespace: "Bar".into(),
+    });
+    let (mut store, _) = open.into_package_store();
+    let inserted = store.insert(CompileUnit::default());
+
+    assert!(std::ptr::eq(
+        store.get(std).expect("std should be in store"),
+        snapshot.get(std).expect("std should be in snapshot"),
+    ));
+    assert_eq!(store.get(id).map(|unit| unit.dropped_names.len()), Some(1));
+    assert_eq!(
+        snapshot.get(id).map(|unit| unit.dropped_names.len()),
+        Some(0)
+    );
+    assert!(snapshot.get(inserted).is_none());
+    assert_eq!(snapshot.iter().count(), 3);
+}
+
+#[test]
+fn package_store_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PackageStore>();
+}
+
+struct Ansatz;
+
+impl SourceGenerator for Ansatz {
+    fn attribute(&self) -> &'static str {
+        "GenerateAnsatz"
+    }
+
+    fn generate(&self, request: &Request) -> Result<String, String> {
+        let layers: usize = request
+            .arg
+            .parse()
+            .map_err(|_| format!("expected a number of layers, found `{}`", request.arg))?;
+        let body = (0..layers)
+            .map(|layer| format!("Ry(thetas[{layer}], q);"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(format!(
+            "namespace {}.Generated {{ open Microsoft.Quantum.Intrinsic; operation {}Ansatz(thetas : Double[], q : Qubit) : Unit {{ {body} }} }}",
+            request.namespace, request.item
+        ))
+    }
+}
+
+fn compile_with_ansatz(source: &str) -> CompileUnit {
+    let mut store = PackageStore::new(super::core());
+    let std = store.insert(super::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    compile_with_generators(
+        &store,
+        &[std],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        &[&Ansatz],
+    )
+}
+
+#[test]
+fn generated_source_is_compiled_with_package() {
+    let unit = compile_with_ansatz(indoc! {"
+        namespace Foo {
+            open Foo.Generated;
+
+            @GenerateAnsatz(2)
+            operation Main() : Unit {
+                use q = Qubit();
+                MainAnsatz([0.1, 0.2], q);
+            }
+        }
+    "});
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    assert!(unit
+        .sources
+        .find_by_name("<generated @GenerateAnsatz for Foo.Main>")
+        .is_some());
+}
+
+struct Broken;
+
+impl SourceGenerator for Broken {
+    fn attribute(&self) -> &'static str {
+        "GenerateBroken"
+    }
+
+    fn generate(&self, _: &Request) -> Result<String, String> {
+        Ok("namespace Broken { function F() : Int { 1.0 } }".to_string())
+    }
+}
+
+#[test]
+fn generated_source_errors_point_into_generated_source() {
+    let mut store = PackageStore::new(super::core());
+    let std = store.insert(super::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            "namespace Foo { @GenerateBroken() function Main() : Unit {} }".into(),
+        )],
+        None,
+    );
+    let unit = compile_with_generators(
+        &store,
+        &[std],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        &[&Ansatz, &Broken],
+    );
+    assert_eq!(unit.errors.len(), 1, "{:#?}", unit.errors);
+    let (name, _) = source_span(&unit.sources, &unit.errors[0]);
+    assert_eq!(name, "<generated @GenerateBroken for Foo.Main>");
+    assert!(matches!(unit.errors[0].0, ErrorKind::Type(_)));
+}
+
+#[test]
+fn failed_generator_reports_error_at_attribute() {
+    let unit = compile_with_ansatz(indoc! {"
+        namespace Foo {
+            @GenerateAnsatz(many)
+            operation Main() : Unit {}
+        }
+    "});
+    assert_eq!(unit.errors.len(), 1, "{:#?}", unit.errors);
+    let (name, span) = source_span(&unit.sources, &unit.errors[0]);
+    assert_eq!(name, "test");
+    assert_eq!(span, Span { lo: 20, hi: 41 });
+    assert!(matches!(unit.errors[0].0, ErrorKind::Generate(_)));
+}
+
+#[test]
+fn feature_flags_select_items() {
+    let source = indoc! {r#"
+        namespace Foo {
+            @Config(feature = "chemistry")
+            function Energy() : Int { 1 }
+            @Config(feature = "optics")
+            function Lens() : Int { 2 }
+            function Main() : Int { Energy() }
+        }
+    "#};
+
+    let sources =
+        SourceMap::new([("test".into(), source.into())], None).with_features(["chemistry"]);
+    let unit = compile(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let inactive = unit
+        .ast
+        .inactive
+        .iter()
+        .map(|span| &source[span.lo as usize..span.hi as usize])
+        .collect::<Vec<_>>();
+    assert_eq!(
+        inactive,
+        ["@Config(feature = \"optics\")\n    function Lens() : Int { 2 }"]
+    );
+
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert_eq!(unit.ast.inactive.len(), 2);
+    assert_eq!(unit.errors.len(), 1, "{:#?}", unit.errors);
+    assert!(matches!(unit.errors[0].0, ErrorKind::Resolve(_)));
+}
+
+/// The names of the items of the package by ID, and the items that its expressions refer to.
+fn item_names_and_uses(package: &Package) -> (Vec<(usize, String)>, Vec<ItemId>) {
+    struct Uses(Vec<ItemId>);
+
+    impl<'a> Visitor<'a> for Uses {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            if let ExprKind::Var(Res::Item(id), _) = &expr.kind {
+                self.0.push(*id);
+            }
+            visit::walk_expr(self, expr);
+        }
+    }
+
+    let names = package
+        .items
+        .iter()
+        .map(|(id, item)| {
+            let name = match &item.kind {
+                ItemKind::Callable(decl) => decl.name.name.to_string(),
+                ItemKind::Namespace(name, _) | ItemKind::Ty(name, _) => name.name.to_string(),
+            };
+            (usize::from(id), name)
+        })
+        .collect();
+    let mut uses = Uses(Vec::new());
+    uses.visit_package(package);
+    (names, uses.0)
+}
+
+#[test]
+fn renumbered_ids_do_not_depend_on_source_order() {
+    let first = indoc! {"
+        namespace Foo {
+            function B() : Unit {
+                A();
+            }
+        }
+    "};
+    let second = indoc! {"
+        namespace Foo {
+            function A() : Unit {}
+        }
+    "};
+
+    let renumbered = [[first, second], [second, first]].map(|sources| {
+        let sources = SourceMap::new(
+            [
+                ("test1".into(), sources[0].into()),
+                ("test2".into(), sources[1].into()),
+            ],
+            None,
+        );
+        let unit = compile(
+            &PackageStore::new(super::core()),
+            &[],
+            sources,
+            RuntimeCapabilityFlags::all(),
+        );
+        assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+        item_names_and_uses(&renumber(&unit.package))
+    });
+
+    let (names, uses) = &renumbered[0];
+    assert_eq!(
+        names,
+        &[
+            (0, "Foo".to_string()),
+            (1, "Foo".to_string()),
+            (2, "A".to_string()),
+            (3, "B".to_string()),
+        ]
+    );
+    assert_eq!(
+        uses,
+        &[ItemId {
+            package: None,
+            item: LocalItemId::from(2),
+        }]
+    );
+    assert_eq!(renumbered[0], renumbered[1]);
+}
+
+#[test]
+fn renumbering_is_idempotent() {
+    let sources = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Foo {
+                    newtype Pair = (Fst : Int, Snd : Int);
+                    function Sum(pair : Pair) : Int {
+                        let add = (x, y) -> x + y;
+                        add(pair::Fst, pair::Snd)
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit = compile(
+        &PackageStore::new(super::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+    );
+    assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+    let renumbered = renumber(&unit.package);
+    assert_eq!(renumber(&renumbered).to_string(), renumbered.to_string());
+}
+
+#[test]
+fn minimal_preset_is_the_least_capable_that_suffices() {
+    let name = |capabilities: RuntimeCapabilityFlags| capabilities.minimal_preset().0;
+    assert_eq!(name(RuntimeCapabilityFlags::empty()), "Base");
+    assert_eq!(
+        name(RuntimeCapabilityFlags::ForwardBranching),
+        "Adaptive_RI"
+    );
+    assert_eq!(
+        name(RuntimeCapabilityFlags::FloatingPointComputations),
+        "Adaptive_RIF"
+    );
+    assert_eq!(name(RuntimeCapabilityFlags::Recursion), "Unrestricted");
+    assert_eq!(
+        RuntimeCapabilityFlags::from(super::ConfigAttr::Adaptive),
+        RuntimeCapabilityFlags::adaptive_ri()
+    );
+}
@@ -6,6 +6,7 @@ mod tests;
 
 use crate::compile::{Source, SourceMap};
 use miette::{Diagnostic, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents};
+use qsc_data_structures::message::MessageParams;
 use std::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
@@ -122,6 +123,12 @@ impl<E: Diagnostic + Send + Sync> Diagnostic for WithSource<E> {
     }
 }
 
+impl<E: MessageParams> MessageParams for WithSource<E> {
+    fn message_params(&self) -> Vec<String> {
+        self.error.message_params()
+    }
+}
+
 impl<E: Diagnostic + Display> Display for WithSource<E> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         std::fmt::Display::fmt(&self.error, f)
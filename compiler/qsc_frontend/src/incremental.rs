@@ -21,6 +21,7 @@ use qsc_ast::{
     validate::Validator as AstValidator,
     visit::Visitor as AstVisitor,
 };
+use qsc_data_structures::edition::Edition;
 use qsc_hir::{
     assigner::Assigner as HirAssigner,
     hir::{self, PackageId},
@@ -52,11 +53,12 @@ pub struct Increment {
 }
 
 impl Compiler {
-    /// Creates a new compiler.
+    /// Creates a new compiler for fragments written in the edition.
     pub fn new(
         store: &PackageStore,
         dependencies: impl IntoIterator<Item = PackageId>,
         capabilities: RuntimeCapabilityFlags,
+        edition: Edition,
     ) -> Self {
         let mut resolve_globals = resolve::GlobalTable::new();
         let mut typeck_globals = typeck::GlobalTable::new();
@@ -79,7 +81,7 @@ impl Compiler {
         Self {
             ast_assigner: AstAssigner::new(),
             resolver: Resolver::with_persistent_local_scope(resolve_globals, dropped_names),
-            checker: Checker::new(typeck_globals),
+            checker: Checker::new(typeck_globals, edition),
             lowerer: Lowerer::new(),
             capabilities,
         }
@@ -126,6 +128,7 @@ impl Compiler {
                 names: self.resolver.names().clone(),
                 locals: self.resolver.locals().clone(),
                 tys: self.checker.table().clone(),
+                inactive: Vec::new(),
             },
             hir,
         })
@@ -166,6 +169,7 @@ impl Compiler {
                 names: self.resolver.names().clone(),
                 locals: self.resolver.locals().clone(),
                 tys: self.checker.table().clone(),
+                inactive: Vec::new(),
             },
             hir,
         })
@@ -191,8 +195,10 @@ impl Compiler {
         unit: &mut CompileUnit,
         ast: &mut ast::Package,
     ) -> (hir::Package, Vec<Error>) {
-        let mut cond_compile = preprocess::Conditional::new(self.capabilities);
+        let mut cond_compile =
+            preprocess::Conditional::new(self.capabilities, unit.sources.features());
         cond_compile.visit_package(ast);
+        unit.ast.inactive.extend_from_slice(cond_compile.inactive());
 
         self.ast_assigner.visit_package(ast);
 
@@ -220,6 +226,12 @@ impl Compiler {
                     .drain_errors()
                     .map(|e| compile::Error(e.into())),
             )
+            .collect::<Vec<_>>();
+        let (warnings, errors): (Vec<_>, Vec<_>) =
+            errors.into_iter().partition(compile::is_warning);
+        unit.warnings.extend(warnings);
+        let errors = errors
+            .into_iter()
             .map(|e| WithSource::from_map(&unit.sources, e))
             .collect::<Vec<_>>();
 
@@ -255,7 +267,7 @@ impl Compiler {
     ) -> (ast::Package, Vec<Error>) {
         let offset = sources.push(source_name.into(), source_contents.into());
 
-        let (expr, errors) = qsc_parse::expr(source_contents);
+        let (expr, errors) = qsc_parse::expr(source_contents, sources.edition());
         let mut stmt = Box::new(Stmt {
             id: ast::NodeId::default(),
             span: expr.span,
@@ -283,7 +295,8 @@ impl Compiler {
     ) -> (ast::Package, Vec<Error>) {
         let offset = sources.push(source_name.into(), source_contents.into());
 
-        let (mut top_level_nodes, errors) = qsc_parse::top_level_nodes(source_contents);
+        let (mut top_level_nodes, errors) =
+            qsc_parse::top_level_nodes(source_contents, sources.edition());
         let mut offsetter = Offsetter(offset);
         for node in &mut top_level_nodes {
             match node {
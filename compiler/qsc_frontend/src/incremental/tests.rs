@@ -9,12 +9,18 @@ use crate::{
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use miette::Diagnostic;
+use qsc_data_structures::edition::Edition;
 use std::fmt::Write;
 
 #[test]
 fn one_callable() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let unit = compiler
         .compile_fragments(
             &mut CompileUnit::default(),
@@ -93,6 +99,7 @@ fn one_callable() {
                         ty_vars: {},
                     },
                 ],
+                used_opens: {},
             }
             hir:
             Package:
@@ -117,7 +124,12 @@ fn one_callable() {
 #[test]
 fn one_statement() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let unit = compiler
         .compile_fragments(
             &mut CompileUnit::default(),
@@ -162,6 +174,7 @@ fn one_statement() {
                         ty_vars: {},
                     },
                 ],
+                used_opens: {},
             }
             hir:
             Package:
@@ -175,7 +188,12 @@ fn one_statement() {
 #[test]
 fn parse_error() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let errors = compiler
         .compile_fragments(&mut CompileUnit::default(), "test_1", "}}", fail_on_error)
         .expect_err("should fail");
@@ -215,7 +233,12 @@ fn parse_error() {
 #[test]
 fn conditional_compilation_not_available() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let errors = compiler
         .compile_fragments(
             &mut CompileUnit::default(),
@@ -240,7 +263,12 @@ fn errors_across_multiple_lines() {
     let mut store = PackageStore::new(compile::core());
     let std = compile::std(&store, RuntimeCapabilityFlags::all());
     let std_id = store.insert(std);
-    let mut compiler = Compiler::new(&store, [std_id], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        [std_id],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let mut unit = CompileUnit::default();
     compiler
         .compile_fragments(
@@ -304,7 +332,12 @@ fn errors_across_multiple_lines() {
 #[test]
 fn continue_after_parse_error() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let mut errors = Vec::new();
 
     compiler
@@ -375,7 +408,12 @@ fn continue_after_parse_error() {
 #[test]
 fn continue_after_lower_error() {
     let store = PackageStore::new(compile::core());
-    let mut compiler = Compiler::new(&store, vec![], RuntimeCapabilityFlags::all());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
     let mut unit = CompileUnit::default();
 
     let mut errors = Vec::new();
@@ -419,6 +457,57 @@ fn continue_after_lower_error() {
     "#]].assert_debug_eq(&errors);
 }
 
+#[test]
+fn reopened_namespace_resolves_items_added_later() {
+    let store = PackageStore::new(compile::core());
+    let mut compiler = Compiler::new(
+        &store,
+        vec![],
+        RuntimeCapabilityFlags::all(),
+        Edition::default(),
+    );
+    let mut unit = CompileUnit::default();
+
+    let increment = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_1",
+            "namespace Foo { function A() : Int { 1 } }",
+            fail_on_error,
+        )
+        .expect("compile_fragments should succeed");
+    compiler.update(&mut unit, increment);
+
+    let increment = compiler
+        .compile_fragments(&mut unit, "test_2", "open Foo; A();", fail_on_error)
+        .expect("compile_fragments should succeed");
+    compiler.update(&mut unit, increment);
+
+    let errors = compiler
+        .compile_fragments(&mut unit, "test_3", "open Foo; B();", fail_on_error)
+        .expect_err("should fail");
+    assert!(
+        errors.iter().any(|error| error
+            .code()
+            .is_some_and(|code| code.to_string() == "Qsc.Resolve.NotFound")),
+        "{errors:?}"
+    );
+
+    let increment = compiler
+        .compile_fragments(
+            &mut unit,
+            "test_4",
+            "namespace Foo { function B() : Int { 2 } }",
+            fail_on_error,
+        )
+        .expect("compile_fragments should succeed");
+    compiler.update(&mut unit, increment);
+
+    compiler
+        .compile_fragments(&mut unit, "test_5", "open Foo; A(); B();", fail_on_error)
+        .expect("compile_fragments should succeed");
+}
+
 fn check_unit(expect: &Expect, actual: &Increment) {
     let ast = format!("ast:\n{}", actual.ast.package);
 
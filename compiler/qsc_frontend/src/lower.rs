@@ -6,31 +6,39 @@ mod tests;
 
 use crate::{
     closure::{self, Lambda, PartialApp},
-    compile::ConfigAttr,
+    compile::{preprocess::feature_name, ConfigAttr},
     resolve::{self, Names},
     typeck::{self, convert},
 };
 use miette::Diagnostic;
 use qsc_ast::ast;
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_data_structures::{index_map::IndexMap, message::MessageParams, span::Span};
 use qsc_hir::{
     assigner::Assigner,
     hir::{self, LocalItemId},
     mut_visit::MutVisitor,
-    ty::{Arrow, FunctorSetValue, Ty},
+    ty::{Arrow, FunctorSetValue, Prim, Ty},
 };
-use std::{clone::Clone, rc::Rc, str::FromStr, vec};
+use std::{clone::Clone, str::FromStr, sync::Arc, vec};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub(super) enum Error {
     #[error("unknown attribute {0}")]
-    #[diagnostic(help("supported attributes are: EntryPoint, Config"))]
+    #[diagnostic(help(
+        "supported attributes are: EntryPoint, Config, Default, MaxIterations, Alias"
+    ))]
     #[diagnostic(code("Qsc.LowerAst.UnknownAttr"))]
     UnknownAttr(String, #[label] Span),
     #[error("invalid attribute arguments: expected {0}")]
     #[diagnostic(code("Qsc.LowerAst.InvalidAttrArgs"))]
     InvalidAttrArgs(&'static str, #[label] Span),
+    #[error("default value is not a constant")]
+    #[diagnostic(help(
+        "default values can use literals, arithmetic and logical operators, arrays and tuples"
+    ))]
+    #[diagnostic(code("Qsc.LowerAst.NonConstantDefault"))]
+    NonConstantDefault(#[label] Span),
     #[error("missing callable body")]
     #[diagnostic(code("Qsc.LowerAst.MissingBody"))]
     MissingBody(#[label] Span),
@@ -45,6 +53,20 @@ pub(super) enum Error {
     InvalidSpecPat(#[label] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::UnknownAttr(name, _) => vec![name.clone()],
+            Self::InvalidAttrArgs(expected, _) => vec![(*expected).to_string()],
+            Self::NonConstantDefault(_)
+            | Self::MissingBody(_)
+            | Self::DuplicateSpec(_)
+            | Self::InvalidElidedPat(_)
+            | Self::InvalidSpecPat(_) => Vec::new(),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 enum ItemScope {
     Global,
@@ -55,6 +77,7 @@ pub(super) struct Lowerer {
     nodes: IndexMap<ast::NodeId, hir::NodeId>,
     locals: IndexMap<hir::NodeId, (hir::Ident, Ty)>,
     parent: Option<LocalItemId>,
+    max_iterations: Option<i64>,
     items: Vec<hir::Item>,
     errors: Vec<Error>,
 }
@@ -65,6 +88,7 @@ impl Lowerer {
             nodes: IndexMap::new(),
             locals: IndexMap::new(),
             parent: None,
+            max_iterations: None,
             items: Vec::new(),
             errors: Vec::new(),
         }
@@ -142,7 +166,7 @@ impl With<'_> {
             id,
             span: namespace.span,
             parent: None,
-            doc: Rc::clone(&namespace.doc),
+            doc: Arc::clone(&namespace.doc),
             attrs: Vec::new(),
             visibility: hir::Visibility::Public,
             kind: hir::ItemKind::Namespace(name, items),
@@ -177,7 +201,17 @@ impl With<'_> {
                 let id = resolve_id(callable.name.id);
                 let grandparent = self.lowerer.parent;
                 self.lowerer.parent = Some(id.item);
+                let max_iterations = item
+                    .attrs
+                    .iter()
+                    .filter(|a| {
+                        hir::Attr::from_str(a.name.name.as_ref()) == Ok(hir::Attr::MaxIterations)
+                    })
+                    .find_map(|a| max_iterations_arg(&a.arg));
+                let outer_max_iterations =
+                    std::mem::replace(&mut self.lowerer.max_iterations, max_iterations);
                 let callable = self.lower_callable_decl(callable);
+                self.lowerer.max_iterations = outer_max_iterations;
                 self.lowerer.parent = grandparent;
                 (id, hir::ItemKind::Callable(callable))
             }
@@ -197,7 +231,7 @@ impl With<'_> {
             id: id.item,
             span: item.span,
             parent: self.lowerer.parent,
-            doc: Rc::clone(&item.doc),
+            doc: Arc::clone(&item.doc),
             attrs,
             visibility,
             kind,
@@ -226,19 +260,38 @@ impl With<'_> {
                     None
                 }
             },
+            Ok(hir::Attr::MaxIterations) => {
+                if max_iterations_arg(&attr.arg).is_some() {
+                    Some(hir::Attr::MaxIterations)
+                } else {
+                    self.lowerer
+                        .errors
+                        .push(Error::InvalidAttrArgs("a positive Int", attr.arg.span));
+                    None
+                }
+            }
             Ok(hir::Attr::Config) => {
-                if !matches!(attr.arg.kind.as_ref(), ast::ExprKind::Paren(inner)
-                    if matches!(inner.kind.as_ref(), ast::ExprKind::Path(path)
-                        if ConfigAttr::from_str(path.name.name.as_ref()).is_ok()))
-                {
+                if !is_config_arg(&attr.arg) {
                     self.lowerer.errors.push(Error::InvalidAttrArgs(
-                        "Unrestricted or Base",
+                        "Unrestricted, Adaptive, Base, or feature = \"name\"",
                         attr.arg.span,
                     ));
                 }
                 None
             }
-            Err(()) => {
+            Err(()) if attr.name.name.as_ref() == "Default" => self.lower_default_attr(attr),
+            Err(()) if attr.name.name.as_ref() == "Alias" => {
+                if let Some(alias) = alias_arg(&attr.arg) {
+                    Some(hir::Attr::Alias(alias))
+                } else {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "a fully qualified name string",
+                        attr.arg.span,
+                    ));
+                    None
+                }
+            }
+            Ok(hir::Attr::Default(..) | hir::Attr::Alias(..)) | Err(()) => {
                 self.lowerer.errors.push(Error::UnknownAttr(
                     attr.name.name.to_string(),
                     attr.name.span,
@@ -248,6 +301,129 @@ impl With<'_> {
         }
     }
 
+    /// Lowers `@Default(name, value)`, evaluating the value at compile time.
+    fn lower_default_attr(&mut self, attr: &ast::Attr) -> Option<hir::Attr> {
+        let default = match attr.arg.kind.as_ref() {
+            ast::ExprKind::Tuple(args) => match &**args {
+                [name, value] => match name.kind.as_ref() {
+                    ast::ExprKind::Path(path) if path.namespace.is_none() => {
+                        Some((&path.name, value))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((name, value)) = default else {
+            self.lowerer.errors.push(Error::InvalidAttrArgs(
+                "a parameter name and a constant value",
+                attr.arg.span,
+            ));
+            return None;
+        };
+
+        if let Some(value) = self.lower_const(value) {
+            Some(hir::Attr::Default(Arc::clone(&name.name), Box::new(value)))
+        } else {
+            self.lowerer
+                .errors
+                .push(Error::NonConstantDefault(value.span));
+            None
+        }
+    }
+
+    /// Evaluates an expression made only of literals, arithmetic and logical operators, arrays and
+    /// tuples, returning `None` if it isn't constant or its evaluation fails.
+    fn lower_const(&mut self, expr: &ast::Expr) -> Option<hir::Expr> {
+        let (kind, ty) = match expr.kind.as_ref() {
+            ast::ExprKind::Lit(lit) => (lower_lit(lit), Ty::Prim(lit_prim(lit))),
+            ast::ExprKind::Paren(inner) => return self.lower_const(inner),
+            ast::ExprKind::UnOp(op, operand) => {
+                let operand = self.lower_const(operand)?;
+                let hir::ExprKind::Lit(lit) = operand.kind else {
+                    return None;
+                };
+                let lit = match (op, lit) {
+                    (ast::UnOp::Neg, hir::Lit::Int(value)) => hir::Lit::Int(value.checked_neg()?),
+                    (ast::UnOp::Neg, hir::Lit::BigInt(value)) => hir::Lit::BigInt(-value),
+                    (ast::UnOp::Neg, hir::Lit::Double(value)) => hir::Lit::Double(-value),
+                    (ast::UnOp::NotL, hir::Lit::Bool(value)) => hir::Lit::Bool(!value),
+                    (ast::UnOp::Pos, lit) => lit,
+                    _ => return None,
+                };
+                (hir::ExprKind::Lit(lit), operand.ty)
+            }
+            ast::ExprKind::BinOp(op, lhs, rhs) => {
+                let lhs = self.lower_const(lhs)?;
+                let rhs = self.lower_const(rhs)?;
+                let (hir::ExprKind::Lit(a), hir::ExprKind::Lit(b)) = (lhs.kind, rhs.kind) else {
+                    return None;
+                };
+                let lit = match (a, b) {
+                    (hir::Lit::Int(a), hir::Lit::Int(b)) => hir::Lit::Int(match op {
+                        ast::BinOp::Add => a.checked_add(b)?,
+                        ast::BinOp::Sub => a.checked_sub(b)?,
+                        ast::BinOp::Mul => a.checked_mul(b)?,
+                        ast::BinOp::Div => a.checked_div(b)?,
+                        ast::BinOp::Mod => a.checked_rem(b)?,
+                        ast::BinOp::Exp => a.checked_pow(u32::try_from(b).ok()?)?,
+                        _ => return None,
+                    }),
+                    (hir::Lit::BigInt(a), hir::Lit::BigInt(b)) => hir::Lit::BigInt(match op {
+                        ast::BinOp::Add => a + b,
+                        ast::BinOp::Sub => a - b,
+                        ast::BinOp::Mul => a * b,
+                        _ => return None,
+                    }),
+                    (hir::Lit::Double(a), hir::Lit::Double(b)) => hir::Lit::Double(match op {
+                        ast::BinOp::Add => a + b,
+                        ast::BinOp::Sub => a - b,
+                        ast::BinOp::Mul => a * b,
+                        ast::BinOp::Div => a / b,
+                        ast::BinOp::Exp => a.powf(b),
+                        _ => return None,
+                    }),
+                    (hir::Lit::Bool(a), hir::Lit::Bool(b)) => hir::Lit::Bool(match op {
+                        ast::BinOp::AndL => a && b,
+                        ast::BinOp::OrL => a || b,
+                        _ => return None,
+                    }),
+                    _ => return None,
+                };
+                (hir::ExprKind::Lit(lit), lhs.ty)
+            }
+            ast::ExprKind::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.lower_const(item))
+                    .collect::<Option<Vec<_>>>()?;
+                // The type of an empty array can't be known from its items.
+                let item_ty = items.first()?.ty.clone();
+                if items.iter().any(|item| item.ty != item_ty) {
+                    return None;
+                }
+                (hir::ExprKind::Array(items), Ty::Array(Box::new(item_ty)))
+            }
+            ast::ExprKind::Tuple(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.lower_const(item))
+                    .collect::<Option<Vec<_>>>()?;
+                let ty = Ty::Tuple(items.iter().map(|item| item.ty.clone()).collect());
+                (hir::ExprKind::Tuple(items), ty)
+            }
+            _ => return None,
+        };
+
+        Some(hir::Expr {
+            id: self.assigner.next_node(),
+            span: expr.span,
+            ty,
+            kind,
+        })
+    }
+
     pub(super) fn lower_callable_decl(&mut self, decl: &ast::CallableDecl) -> hir::CallableDecl {
         let id = self.lower_id(decl.id);
         let kind = lower_callable_kind(decl.kind);
@@ -556,7 +732,12 @@ impl With<'_> {
                 hir::ExprKind::UnOp(lower_unop(*op), Box::new(self.lower_expr(operand)))
             }
             ast::ExprKind::While(cond, body) => {
-                hir::ExprKind::While(Box::new(self.lower_expr(cond)), self.lower_block(body))
+                let cond = self.lower_expr(cond);
+                let body = self.lower_block(body);
+                match self.lowerer.max_iterations {
+                    Some(max) => self.lower_bounded_while(cond, body, max, expr.span),
+                    None => hir::ExprKind::While(Box::new(cond), body),
+                }
             }
         };
 
@@ -568,6 +749,148 @@ impl With<'_> {
         }
     }
 
+    /// Lowers a `while` loop in a callable annotated with `@MaxIterations(max)` into a block that
+    /// counts the iterations of the loop and fails when the bound would be exceeded:
+    ///
+    /// ```qsharp
+    /// {
+    ///     mutable @iterations = 0;
+    ///     while cond {
+    ///         if @iterations >= max { fail "..."; }
+    ///         set @iterations += 1;
+    ///         ...
+    ///     }
+    /// }
+    /// ```
+    fn lower_bounded_while(
+        &mut self,
+        cond: hir::Expr,
+        mut body: hir::Block,
+        max: i64,
+        span: Span,
+    ) -> hir::ExprKind {
+        let counter_ty = Ty::Prim(Prim::Int);
+        let counter_id = self.assigner.next_node();
+        let ident = hir::Ident {
+            id: counter_id,
+            span,
+            name: "@iterations".into(),
+        };
+        self.lowerer
+            .locals
+            .insert(counter_id, (ident.clone(), counter_ty.clone()));
+
+        let mut counter = || hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: counter_ty.clone(),
+            kind: hir::ExprKind::Var(hir::Res::Local(counter_id), Vec::new()),
+        };
+        let counter_for_check = counter();
+        let counter_for_update = counter();
+
+        let fail = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            kind: hir::ExprKind::Fail(Box::new(hir::Expr {
+                id: self.assigner.next_node(),
+                span,
+                ty: Ty::Prim(Prim::String),
+                kind: hir::ExprKind::String(vec![hir::StringComponent::Lit(
+                    format!("while loop exceeded its maximum of {max} iterations").into(),
+                )]),
+            })),
+        };
+        let fail = self.semi(fail);
+        let check = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            kind: hir::ExprKind::If(
+                Box::new(hir::Expr {
+                    id: self.assigner.next_node(),
+                    span,
+                    ty: Ty::Prim(Prim::Bool),
+                    kind: hir::ExprKind::BinOp(
+                        hir::BinOp::Gte,
+                        Box::new(counter_for_check),
+                        Box::new(self.int_lit(max, span)),
+                    ),
+                }),
+                Box::new(hir::Expr {
+                    id: self.assigner.next_node(),
+                    span,
+                    ty: Ty::UNIT,
+                    kind: hir::ExprKind::Block(hir::Block {
+                        id: self.assigner.next_node(),
+                        span,
+                        ty: Ty::UNIT,
+                        stmts: vec![fail],
+                    }),
+                }),
+                None,
+            ),
+        };
+        let update = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            kind: hir::ExprKind::AssignOp(
+                hir::BinOp::Add,
+                Box::new(counter_for_update),
+                Box::new(self.int_lit(1, span)),
+            ),
+        };
+        let check = self.semi(check);
+        let update = self.semi(update);
+        body.stmts.splice(0..0, [check, update]);
+
+        let init = hir::Stmt {
+            id: self.assigner.next_node(),
+            span,
+            kind: hir::StmtKind::Local(
+                hir::Mutability::Mutable,
+                hir::Pat {
+                    id: self.assigner.next_node(),
+                    span,
+                    ty: counter_ty,
+                    kind: hir::PatKind::Bind(ident),
+                },
+                self.int_lit(0, span),
+            ),
+        };
+        let while_loop = hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            kind: hir::ExprKind::While(Box::new(cond), body),
+        };
+        hir::ExprKind::Block(hir::Block {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::UNIT,
+            stmts: vec![init, self.semi(while_loop)],
+        })
+    }
+
+    fn int_lit(&mut self, value: i64, span: Span) -> hir::Expr {
+        hir::Expr {
+            id: self.assigner.next_node(),
+            span,
+            ty: Ty::Prim(Prim::Int),
+            kind: hir::ExprKind::Lit(hir::Lit::Int(value)),
+        }
+    }
+
+    fn semi(&mut self, expr: hir::Expr) -> hir::Stmt {
+        hir::Stmt {
+            id: self.assigner.next_node(),
+            span: expr.span,
+            kind: hir::StmtKind::Semi(expr),
+        }
+    }
+
     fn lower_partial_app(
         &mut self,
         callee: &ast::Expr,
@@ -617,7 +940,13 @@ impl With<'_> {
             span,
             parent: self.lowerer.parent,
             doc: "".into(),
-            attrs: Vec::new(),
+            // Loops in the lambda are bounded by the callable that it was lifted from.
+            attrs: self
+                .lowerer
+                .max_iterations
+                .map(|_| hir::Attr::MaxIterations)
+                .into_iter()
+                .collect(),
             visibility: hir::Visibility::Internal,
             kind: hir::ItemKind::Callable(callable),
         });
@@ -642,7 +971,7 @@ impl With<'_> {
     fn lower_string_component(&mut self, component: &ast::StringComponent) -> hir::StringComponent {
         match component {
             ast::StringComponent::Expr(expr) => hir::StringComponent::Expr(self.lower_expr(expr)),
-            ast::StringComponent::Lit(str) => hir::StringComponent::Lit(Rc::clone(str)),
+            ast::StringComponent::Lit(str) => hir::StringComponent::Lit(Arc::clone(str)),
         }
     }
 
@@ -812,11 +1141,23 @@ fn lower_lit(lit: &ast::Lit) -> hir::ExprKind {
             hir::ExprKind::Lit(hir::Lit::Result(hir::Result::Zero))
         }
         ast::Lit::String(value) => {
-            hir::ExprKind::String(vec![hir::StringComponent::Lit(Rc::clone(value))])
+            hir::ExprKind::String(vec![hir::StringComponent::Lit(Arc::clone(value))])
         }
     }
 }
 
+fn lit_prim(lit: &ast::Lit) -> Prim {
+    match lit {
+        ast::Lit::BigInt(_) => Prim::BigInt,
+        ast::Lit::Bool(_) => Prim::Bool,
+        ast::Lit::Double(_) => Prim::Double,
+        ast::Lit::Int(_) => Prim::Int,
+        ast::Lit::Pauli(_) => Prim::Pauli,
+        ast::Lit::Result(_) => Prim::Result,
+        ast::Lit::String(_) => Prim::String,
+    }
+}
+
 fn lower_functor(functor: ast::Functor) -> hir::Functor {
     match functor {
         ast::Functor::Adj => hir::Functor::Adj,
@@ -832,3 +1173,53 @@ fn is_partial_app(arg: &ast::Expr) -> bool {
         _ => false,
     }
 }
+
+/// Whether the argument of a `@Config` attribute is a known capability, like `(Base)`, or a
+/// feature flag setting, like `(feature = "name")`.
+fn is_config_arg(arg: &ast::Expr) -> bool {
+    match arg.kind.as_ref() {
+        ast::ExprKind::Paren(inner) => match inner.kind.as_ref() {
+            ast::ExprKind::Path(path) => ConfigAttr::from_str(path.name.name.as_ref()).is_ok(),
+            ast::ExprKind::Assign(lhs, rhs) => feature_name(lhs, rhs).is_some(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The old fully qualified name given by the argument of an `@Alias` attribute, which must be a
+/// string literal with a namespace and a name.
+pub(super) fn alias_arg(arg: &ast::Expr) -> Option<Arc<str>> {
+    match arg.kind.as_ref() {
+        ast::ExprKind::Paren(inner) => match inner.kind.as_ref() {
+            ast::ExprKind::Lit(lit) => match lit.as_ref() {
+                ast::Lit::String(name) => match name.rsplit_once('.') {
+                    Some((namespace, item))
+                        if !item.is_empty() && namespace.split('.').all(|p| !p.is_empty()) =>
+                    {
+                        Some(Arc::clone(name))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The bound given by the argument of a `@MaxIterations` attribute, which must be a positive
+/// integer literal.
+fn max_iterations_arg(arg: &ast::Expr) -> Option<i64> {
+    match arg.kind.as_ref() {
+        ast::ExprKind::Paren(inner) => match inner.kind.as_ref() {
+            ast::ExprKind::Lit(lit) => match lit.as_ref() {
+                ast::Lit::Int(max) if *max > 0 => Some(*max),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -86,6 +86,60 @@ fn test_entrypoint_attr_wrong_args() {
     );
 }
 
+#[test]
+fn test_default_attr_non_constant() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @EntryPoint()
+                @Default(n, Foo(1))
+                @Default(m, [])
+                operation Foo(n : Int, m : Int[]) : Int { n }
+            }
+        "},
+        &expect![[r#"
+            [
+                NonConstantDefault(
+                    Span {
+                        lo: 52,
+                        hi: 58,
+                    },
+                ),
+                NonConstantDefault(
+                    Span {
+                        lo: 76,
+                        hi: 78,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_default_attr_wrong_args() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @EntryPoint()
+                @Default(1)
+                operation Foo(n : Int) : Int { n }
+            }
+        "},
+        &expect![[r#"
+            [
+                InvalidAttrArgs(
+                    "a parameter name and a constant value",
+                    Span {
+                        lo: 48,
+                        hi: 51,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn test_target_profile_base_attr_allowed() {
     check_errors(
@@ -134,7 +188,7 @@ fn test_target_profile_attr_wrong_args() {
         &expect![[r#"
             [
                 InvalidAttrArgs(
-                    "Unrestricted or Base",
+                    "Unrestricted, Adaptive, Base, or feature = \"name\"",
                     Span {
                         lo: 29,
                         hi: 34,
@@ -145,6 +199,48 @@ fn test_target_profile_attr_wrong_args() {
     );
 }
 
+#[test]
+fn test_max_iterations_attr_allowed() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @MaxIterations(100)
+                operation Foo() : Unit {
+                    body ... {}
+                }
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn test_max_iterations_attr_wrong_args() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @MaxIterations(0)
+                operation Foo() : Unit {
+                    body ... {}
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                InvalidAttrArgs(
+                    "a positive Int",
+                    Span {
+                        lo: 36,
+                        hi: 39,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn test_unknown_attr() {
     check_errors(
@@ -9,7 +9,7 @@ use qsc_ast::{
     ast::{self, CallableBody, CallableDecl, Ident, NodeId, SpecBody, SpecGen, TopLevelNode},
     visit::{self as ast_visit, walk_attr, Visitor as AstVisitor},
 };
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_data_structures::{index_map::IndexMap, message::MessageParams, span::Span};
 use qsc_hir::{
     assigner::Assigner,
     global,
@@ -17,10 +17,10 @@ use qsc_hir::{
     ty::{ParamId, Prim},
 };
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::{collections::hash_map::Entry, rc::Rc, str::FromStr, vec};
+use std::{rc::Rc, str::FromStr, sync::Arc, vec};
 use thiserror::Error;
 
-use crate::compile::preprocess::TrackedName;
+use crate::{compile::preprocess::TrackedName, lower::alias_arg};
 
 const PRELUDE: &[&str] = &[
     "Microsoft.Quantum.Canon",
@@ -99,13 +99,44 @@ pub(super) enum Error {
     #[diagnostic(help(
         "found a matching item `{1}` that is not available for the current compilation configuration"
     ))]
-    #[diagnostic(code("Qsc.Resolve.NotFound"))]
+    #[diagnostic(code("Qsc.Resolve.NotAvailable"))]
     NotAvailable(String, String, #[label] Span),
 
     #[error("use of unimplemented item `{0}`")]
     #[diagnostic(help("this item is not implemented and cannot be used"))]
     #[diagnostic(code("Qsc.Resolve.Unimplemented"))]
     Unimplemented(String, #[label] Span),
+
+    #[error("`{0}` is a deprecated alias of `{1}`")]
+    #[diagnostic(help("the item has moved; use `{1}` instead"))]
+    #[diagnostic(code("Qsc.Resolve.DeprecatedAlias"), severity(Warning))]
+    DeprecatedAlias(String, String, #[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Ambiguous {
+                name,
+                first_open,
+                second_open,
+                ..
+            } => vec![name.clone(), first_open.clone(), second_open.clone()],
+            Self::AmbiguousPrelude {
+                name,
+                candidate_a,
+                candidate_b,
+                ..
+            } => vec![name.clone(), candidate_a.clone(), candidate_b.clone()],
+            Self::Duplicate(name, namespace, _) => vec![name.clone(), namespace.clone()],
+            Self::DeprecatedAlias(alias, name, _) => vec![alias.clone(), name.clone()],
+            Self::DuplicateBinding(name, _)
+            | Self::DuplicateIntrinsic(name, _)
+            | Self::NotFound(name, _)
+            | Self::NotAvailable(name, _, _)
+            | Self::Unimplemented(name, _) => vec![name.clone()],
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -115,11 +146,11 @@ pub struct Scope {
     span: Span,
     kind: ScopeKind,
     /// Open statements. The key is the namespace name or alias.
-    opens: FxHashMap<Rc<str>, Vec<Open>>,
+    opens: FxHashMap<Arc<str>, Vec<Open>>,
     /// Local newtype declarations.
-    tys: FxHashMap<Rc<str>, ItemId>,
+    tys: FxHashMap<Arc<str>, ItemId>,
     /// Local callable and newtype declarations.
-    terms: FxHashMap<Rc<str>, ItemId>,
+    terms: FxHashMap<Arc<str>, ItemId>,
     /// Local variables, including callable parameters, for loop bindings, etc.
     /// The u32 is the `valid_at` offset - the lowest offset at which the variable name is available.
     /// It's used to determine which variables are visible at a specific offset in the scope.
@@ -127,9 +158,9 @@ pub struct Scope {
     /// Bug: Because we keep track of only one `valid_at` offset per name,
     /// when a variable is later shadowed in the same scope,
     /// it is missed in the list. https://github.com/microsoft/qsharp/issues/897
-    vars: FxHashMap<Rc<str>, (u32, NodeId)>,
+    vars: FxHashMap<Arc<str>, (u32, NodeId)>,
     /// Type parameters.
-    ty_vars: FxHashMap<Rc<str>, ParamId>,
+    ty_vars: FxHashMap<Arc<str>, ParamId>,
 }
 
 impl Scope {
@@ -160,9 +191,18 @@ type ScopeId = usize;
 pub struct Locals {
     // order is ascending by span (outermost -> innermost)
     scopes: Vec<Scope>,
+    /// The spans of the namespace names of the opens that at least one name was resolved through.
+    used_opens: FxHashSet<Span>,
 }
 
 impl Locals {
+    /// Whether at least one name was resolved through the open whose namespace name has the span.
+    /// Opens that repeat an earlier open of the same namespace in the same scope are never used.
+    #[must_use]
+    pub fn is_open_used(&self, namespace_span: Span) -> bool {
+        self.used_opens.contains(&namespace_span)
+    }
+
     fn get_scopes<'a>(&'a self, scope_chain: &'a [ScopeId]) -> impl Iterator<Item = &Scope> + 'a {
         // reverse to go from innermost -> outermost
         scope_chain.iter().rev().map(|id| {
@@ -221,7 +261,7 @@ impl Locals {
 
 #[derive(Debug)]
 pub struct Local {
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     pub kind: LocalKind,
 }
 
@@ -237,10 +277,15 @@ pub enum LocalKind {
 
 #[derive(Debug, Clone, Default)]
 pub struct GlobalScope {
-    tys: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>>,
-    terms: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>>,
-    namespaces: FxHashSet<Rc<str>>,
-    intrinsics: FxHashSet<Rc<str>>,
+    tys: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>>,
+    terms: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>>,
+    namespaces: FxHashSet<Arc<str>>,
+    intrinsics: FxHashSet<Arc<str>>,
+    /// The fully qualified names of the items that have aliases.
+    aliased: FxHashMap<ItemId, Arc<str>>,
+    /// The scope of the dependencies, which is shared by every package compiled against them.
+    /// Names are looked up in this scope first and then in the dependencies.
+    dependencies: Option<Rc<GlobalScope>>,
 }
 
 impl GlobalScope {
@@ -249,13 +294,39 @@ impl GlobalScope {
             NameKind::Ty => &self.tys,
             NameKind::Term => &self.terms,
         };
-        namespaces.get(namespace).and_then(|items| items.get(name))
+        namespaces
+            .get(namespace)
+            .and_then(|items| items.get(name))
+            .or_else(|| self.dependencies.as_ref()?.get(kind, namespace, name))
+    }
+
+    fn has_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.contains(namespace)
+            || self
+                .dependencies
+                .as_ref()
+                .is_some_and(|dependencies| dependencies.has_namespace(namespace))
+    }
+
+    fn is_intrinsic(&self, name: &str) -> bool {
+        self.intrinsics.contains(name)
+            || self
+                .dependencies
+                .as_ref()
+                .is_some_and(|dependencies| dependencies.is_intrinsic(name))
+    }
+
+    /// The fully qualified name of the item with the ID, if it has aliases.
+    fn aliased_name(&self, id: ItemId) -> Option<&Arc<str>> {
+        self.aliased
+            .get(&id)
+            .or_else(|| self.dependencies.as_ref()?.aliased_name(id))
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum ScopeKind {
-    Namespace(Rc<str>),
+    Namespace(Arc<str>),
     Callable,
     Block,
 }
@@ -268,17 +339,75 @@ enum NameKind {
 
 #[derive(Debug, Clone)]
 struct Open {
-    namespace: Rc<str>,
+    namespace: Arc<str>,
     span: Span,
 }
 
+/// Resolutions of names through explicit opens, keyed by the namespaces opened under an alias.
+/// Incremental compilations such as notebook cells tend to open the same namespaces again and
+/// again, so later fragments can reuse the lookups made for earlier ones.
+///
+/// The entries only depend on the global scope, so the cache must be cleared when it changes.
+#[derive(Debug, Default)]
+struct OpenCache {
+    envs: FxHashMap<Vec<Arc<str>>, OpenEnv>,
+}
+
+/// The resolutions for a single set of opens. Each resolution is paired with the index of the
+/// open it came from.
+#[derive(Debug, Default)]
+struct OpenEnv {
+    tys: FxHashMap<Arc<str>, Vec<(Res, usize)>>,
+    terms: FxHashMap<Arc<str>, Vec<(Res, usize)>>,
+}
+
+impl OpenCache {
+    fn clear(&mut self) {
+        self.envs.clear();
+    }
+
+    fn resolve<'a>(
+        &mut self,
+        kind: NameKind,
+        globals: &GlobalScope,
+        opens: &'a [Open],
+        name: &str,
+    ) -> FxHashMap<Res, &'a Open> {
+        let key = opens
+            .iter()
+            .map(|open| Arc::clone(&open.namespace))
+            .collect::<Vec<_>>();
+        let env = self.envs.entry(key).or_default();
+        let names = match kind {
+            NameKind::Ty => &mut env.tys,
+            NameKind::Term => &mut env.terms,
+        };
+
+        if !names.contains_key(name) {
+            let mut resolutions = Vec::new();
+            for (index, open) in opens.iter().enumerate() {
+                if let Some(&res) = globals.get(kind, &open.namespace, name) {
+                    resolutions.push((res, index));
+                }
+            }
+            names.insert(name.into(), resolutions);
+        }
+
+        names[name]
+            .iter()
+            .map(|&(res, index)| (res, &opens[index]))
+            .collect()
+    }
+}
+
 pub(super) struct Resolver {
     names: Names,
     dropped_names: Vec<TrackedName>,
-    curr_params: Option<FxHashSet<Rc<str>>>,
+    curr_params: Option<FxHashSet<Arc<str>>>,
     curr_scope_chain: Vec<ScopeId>,
     globals: GlobalScope,
     locals: Locals,
+    open_cache: Option<OpenCache>,
     errors: Vec<Error>,
 }
 
@@ -291,6 +420,7 @@ impl Resolver {
             globals: globals.scope,
             locals: Locals::default(),
             curr_scope_chain: Vec::new(),
+            open_cache: None,
             errors: Vec::new(),
         }
     }
@@ -314,6 +444,7 @@ impl Resolver {
             globals: globals.scope,
             locals,
             curr_scope_chain: vec![scope_id],
+            open_cache: Some(OpenCache::default()),
             errors: Vec::new(),
         }
     }
@@ -356,6 +487,9 @@ impl Resolver {
                         assigner,
                         &mut self.errors,
                     );
+                    if let Some(open_cache) = &mut self.open_cache {
+                        open_cache.clear();
+                    }
                 }
                 ast::TopLevelNode::Stmt(stmt) => {
                     if let ast::StmtKind::Item(item) = stmt.kind.as_ref() {
@@ -366,9 +500,23 @@ impl Resolver {
         }
     }
 
-    fn check_item_status(&mut self, res: Res, name: String, span: Span) {
-        if let Res::Item(_, ItemStatus::Unimplemented) = res {
-            self.errors.push(Error::Unimplemented(name, span));
+    /// Reports uses of unimplemented items and deprecated aliases, and returns the resolution to
+    /// record for the name. Names resolved through an alias are recorded as the item itself.
+    fn check_item_status(&mut self, res: Res, name: String, span: Span) -> Res {
+        match res {
+            Res::Item(_, ItemStatus::Unimplemented) => {
+                self.errors.push(Error::Unimplemented(name, span));
+                res
+            }
+            Res::Item(id, ItemStatus::Alias) => {
+                let target = self
+                    .globals
+                    .aliased_name(id)
+                    .map_or_else(String::new, ToString::to_string);
+                self.errors.push(Error::DeprecatedAlias(name, target, span));
+                Res::Item(id, ItemStatus::Available)
+            }
+            _ => res,
         }
     }
 
@@ -381,9 +529,11 @@ impl Resolver {
             self.locals.get_scopes(&self.curr_scope_chain),
             name,
             &namespace,
+            self.open_cache.as_mut(),
         ) {
-            Ok(res) => {
-                self.check_item_status(res, name.name.to_string(), name.span);
+            Ok((res, open)) => {
+                self.locals.used_opens.extend(open);
+                let res = self.check_item_status(res, name.name.to_string(), name.span);
                 self.names.insert(name.id, res);
             }
             Err(err) => self.errors.push(err),
@@ -400,9 +550,16 @@ impl Resolver {
             self.locals.get_scopes(&self.curr_scope_chain),
             name,
             namespace,
+            self.open_cache.as_mut(),
         ) {
-            Ok(res) => {
-                self.check_item_status(res, path.name.name.to_string(), path.span);
+            Ok((res, open)) => {
+                self.locals.used_opens.extend(open);
+                let name = if let Res::Item(_, ItemStatus::Alias) = res {
+                    path_name(path)
+                } else {
+                    path.name.name.to_string()
+                };
+                let res = self.check_item_status(res, name, path.span);
                 self.names.insert(path.id, res);
             }
             Err(err) => {
@@ -441,18 +598,18 @@ impl Resolver {
         &mut self,
         pat: &ast::Pat,
         valid_at: u32,
-        bindings: &mut FxHashSet<Rc<str>>,
+        bindings: &mut FxHashSet<Arc<str>>,
     ) {
         match &*pat.kind {
             ast::PatKind::Bind(name, _) => {
-                if !bindings.insert(Rc::clone(&name.name)) {
+                if !bindings.insert(Arc::clone(&name.name)) {
                     self.errors
                         .push(Error::DuplicateBinding(name.name.to_string(), name.span));
                 }
                 self.names.insert(name.id, Res::Local(name.id));
                 self.current_scope_mut()
                     .vars
-                    .insert(Rc::clone(&name.name), (valid_at, name.id));
+                    .insert(Arc::clone(&name.name), (valid_at, name.id));
             }
             ast::PatKind::Discard(_) | ast::PatKind::Elided | ast::PatKind::Err => {}
             ast::PatKind::Paren(pat) => self.bind_pat_recursive(pat, valid_at, bindings),
@@ -463,16 +620,17 @@ impl Resolver {
     }
 
     fn bind_open(&mut self, name: &ast::Ident, alias: &Option<Box<ast::Ident>>) {
-        let alias = alias.as_ref().map_or("".into(), |a| Rc::clone(&a.name));
-        if self.globals.namespaces.contains(&name.name) {
-            self.current_scope_mut()
-                .opens
-                .entry(alias)
-                .or_default()
-                .push(Open {
-                    namespace: Rc::clone(&name.name),
+        let alias = alias.as_ref().map_or("".into(), |a| Arc::clone(&a.name));
+        if self.globals.has_namespace(&name.name) {
+            let opens = self.current_scope_mut().opens.entry(alias).or_default();
+            // Reopening a namespace doesn't change resolution, and keeping the set of opens stable
+            // lets repeated opens reuse cached resolutions.
+            if !opens.iter().any(|open| open.namespace == name.name) {
+                opens.push(Open {
+                    namespace: Arc::clone(&name.name),
                     span: name.span,
                 });
+            }
         } else {
             self.errors
                 .push(Error::NotFound(name.name.to_string(), name.span));
@@ -493,7 +651,7 @@ impl Resolver {
                 );
                 self.current_scope_mut()
                     .terms
-                    .insert(Rc::clone(&decl.name.name), id);
+                    .insert(Arc::clone(&decl.name.name), id);
             }
             ast::ItemKind::Ty(name, _) => {
                 let id = intrapackage(assigner.next_item());
@@ -505,8 +663,8 @@ impl Resolver {
                     ),
                 );
                 let scope = self.current_scope_mut();
-                scope.tys.insert(Rc::clone(&name.name), id);
-                scope.terms.insert(Rc::clone(&name.name), id);
+                scope.tys.insert(Arc::clone(&name.name), id);
+                scope.terms.insert(Arc::clone(&name.name), id);
             }
             ast::ItemKind::Err => {}
         }
@@ -516,7 +674,7 @@ impl Resolver {
         decl.generics.iter().enumerate().for_each(|(ix, ident)| {
             self.current_scope_mut()
                 .ty_vars
-                .insert(Rc::clone(&ident.name), ix.into());
+                .insert(Arc::clone(&ident.name), ix.into());
             self.names.insert(ident.id, Res::Param(ix.into()));
         });
     }
@@ -586,7 +744,7 @@ impl With<'_> {
 
 impl AstVisitor<'_> for With<'_> {
     fn visit_namespace(&mut self, namespace: &ast::Namespace) {
-        let kind = ScopeKind::Namespace(Rc::clone(&namespace.name.name));
+        let kind = ScopeKind::Namespace(Arc::clone(&namespace.name.name));
         self.with_scope(namespace.span, kind, |visitor| {
             for item in &*namespace.items {
                 if let ast::ItemKind::Open(name, alias) = &*item.kind {
@@ -599,17 +757,18 @@ impl AstVisitor<'_> for With<'_> {
     }
 
     fn visit_attr(&mut self, attr: &ast::Attr) {
-        // The Config attribute arguments do not go through name resolution.
-        if hir::Attr::from_str(attr.name.name.as_ref()) != Ok(hir::Attr::Config) {
+        // The Config and Default attribute arguments do not go through name resolution.
+        let name = attr.name.name.as_ref();
+        if hir::Attr::from_str(name) != Ok(hir::Attr::Config) && name != "Default" {
             walk_attr(self, attr);
         }
     }
 
     fn visit_callable_decl(&mut self, decl: &ast::CallableDecl) {
-        fn collect_param_names(pat: &ast::Pat, names: &mut FxHashSet<Rc<str>>) {
+        fn collect_param_names(pat: &ast::Pat, names: &mut FxHashSet<Arc<str>>) {
             match &*pat.kind {
                 ast::PatKind::Bind(name, _) => {
-                    names.insert(Rc::clone(&name.name));
+                    names.insert(Arc::clone(&name.name));
                 }
                 ast::PatKind::Discard(_) | ast::PatKind::Elided | ast::PatKind::Err => {}
                 ast::PatKind::Paren(pat) => collect_param_names(pat, names),
@@ -726,6 +885,7 @@ impl AstVisitor<'_> for With<'_> {
     }
 }
 
+#[derive(Clone)]
 pub(super) struct GlobalTable {
     names: Names,
     scope: GlobalScope,
@@ -733,7 +893,7 @@ pub(super) struct GlobalTable {
 
 impl GlobalTable {
     pub(super) fn new() -> Self {
-        let builtins: [(Rc<str>, Res); 10] = [
+        let builtins: [(Arc<str>, Res); 10] = [
             ("BigInt".into(), Res::PrimTy(Prim::BigInt)),
             ("Bool".into(), Res::PrimTy(Prim::Bool)),
             ("Double".into(), Res::PrimTy(Prim::Double)),
@@ -745,20 +905,30 @@ impl GlobalTable {
             ("String".into(), Res::PrimTy(Prim::String)),
             ("Unit".into(), Res::UnitTy),
         ];
-        let mut core: FxHashMap<Rc<str>, Res> = FxHashMap::default();
+        let mut core: FxHashMap<Arc<str>, Res> = FxHashMap::default();
         for (name, res) in builtins {
             core.insert(name, res);
         }
-        let mut tys: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>> = FxHashMap::default();
+        let mut tys: FxHashMap<Arc<str>, FxHashMap<Arc<str>, Res>> = FxHashMap::default();
         tys.insert("Microsoft.Quantum.Core".into(), core);
 
         Self {
             names: IndexMap::new(),
             scope: GlobalScope {
                 tys,
-                terms: FxHashMap::default(),
-                namespaces: FxHashSet::default(),
-                intrinsics: FxHashSet::default(),
+                ..GlobalScope::default()
+            },
+        }
+    }
+
+    /// Moves the names of the table into a scope that clones of the table share. Names added to
+    /// a clone, such as those of a package compiled against the table, are kept apart from it.
+    pub(super) fn into_shared(self) -> Self {
+        Self {
+            names: self.names,
+            scope: GlobalScope {
+                dependencies: Some(Rc::new(self.scope)),
+                ..GlobalScope::default()
             },
         }
     }
@@ -819,9 +989,76 @@ impl GlobalTable {
                 (_, hir::Visibility::Internal) => {}
             }
         }
+
+        for item in package.items.values() {
+            if item.visibility != hir::Visibility::Public {
+                continue;
+            }
+            let item_id = ItemId {
+                package: Some(id),
+                item: item.id,
+            };
+            for attr in &item.attrs {
+                if let hir::Attr::Alias(alias) = attr {
+                    let (is_ty, name) = match &item.kind {
+                        hir::ItemKind::Callable(decl) => (false, &decl.name.name),
+                        hir::ItemKind::Ty(name, _) => (true, &name.name),
+                        hir::ItemKind::Namespace(..) => continue,
+                    };
+                    let namespace =
+                        item.parent
+                            .and_then(|parent| match &package.items.get(parent)?.kind {
+                                hir::ItemKind::Namespace(namespace, _) => Some(&namespace.name),
+                                _ => None,
+                            });
+                    if let Some(namespace) = namespace {
+                        // An alias that names an item of another package is left to that item.
+                        bind_alias(&mut self.scope, item_id, is_ty, alias, namespace, name);
+                    }
+                }
+            }
+        }
     }
 }
 
+/// Adds the alias as another name of the item, which is also named `name` in `namespace`. Returns
+/// `false` if the alias is invalid or names another item.
+fn bind_alias(
+    scope: &mut GlobalScope,
+    id: ItemId,
+    is_ty: bool,
+    alias: &str,
+    namespace: &str,
+    name: &str,
+) -> bool {
+    let Some((alias_namespace, alias_name)) = alias.rsplit_once('.') else {
+        return false;
+    };
+    let occupied = |kind| scope.get(kind, alias_namespace, alias_name).is_some();
+    if occupied(NameKind::Term) || (is_ty && occupied(NameKind::Ty)) {
+        return false;
+    }
+
+    let res = Res::Item(id, ItemStatus::Alias);
+    scope
+        .terms
+        .entry(alias_namespace.into())
+        .or_default()
+        .insert(alias_name.into(), res);
+    if is_ty {
+        scope
+            .tys
+            .entry(alias_namespace.into())
+            .or_default()
+            .insert(alias_name.into(), res);
+    }
+    scope.namespaces.insert(alias_namespace.into());
+    scope
+        .aliased
+        .insert(id, format!("{namespace}.{name}").into());
+    true
+}
+
 fn bind_global_items(
     names: &mut IndexMap<NodeId, Res>,
     scope: &mut GlobalScope,
@@ -833,7 +1070,7 @@ fn bind_global_items(
         namespace.name.id,
         Res::Item(intrapackage(assigner.next_item()), ItemStatus::Available),
     );
-    scope.namespaces.insert(Rc::clone(&namespace.name.name));
+    scope.namespaces.insert(Arc::clone(&namespace.name.name));
 
     for item in &*namespace.items {
         match bind_global_item(
@@ -852,7 +1089,7 @@ fn bind_global_items(
 /// Tries to extract a field name from an expression in cases where it is syntactically ambiguous
 /// whether the expression is a field name or a variable name. This applies to the index operand in
 /// a ternary update operator.
-pub(super) fn extract_field_name<'a>(names: &Names, expr: &'a ast::Expr) -> Option<&'a Rc<str>> {
+pub(super) fn extract_field_name<'a>(names: &Names, expr: &'a ast::Expr) -> Option<&'a Arc<str>> {
     // Follow the same reasoning as `is_field_update`.
     match &*expr.kind {
         ast::ExprKind::Path(path)
@@ -876,14 +1113,22 @@ fn is_field_update<'a>(
             {
                 let name = &path.name;
                 let namespace = &path.namespace;
-                resolve(NameKind::Term, globals, scopes, name, namespace)
+                resolve(NameKind::Term, globals, scopes, name, namespace, None)
             },
-            Ok(Res::Local(_))
+            Ok((Res::Local(_), _))
         ),
         _ => false,
     }
 }
 
+/// The name as written in the path, including its namespace if it has one.
+fn path_name(path: &ast::Path) -> String {
+    match &path.namespace {
+        Some(namespace) => format!("{}.{}", namespace.name, path.name.name),
+        None => path.name.name.to_string(),
+    }
+}
+
 fn ast_attrs_as_hir_attrs(attrs: &[Box<ast::Attr>]) -> Vec<hir::Attr> {
     attrs
         .iter()
@@ -894,7 +1139,7 @@ fn ast_attrs_as_hir_attrs(attrs: &[Box<ast::Attr>]) -> Vec<hir::Attr> {
 fn bind_global_item(
     names: &mut Names,
     scope: &mut GlobalScope,
-    namespace: &Rc<str>,
+    namespace: &Arc<str>,
     next_id: impl FnOnce() -> ItemId,
     item: &ast::Item,
 ) -> Result<(), Vec<Error>> {
@@ -905,29 +1150,42 @@ fn bind_global_item(
             let res = Res::Item(item_id, status);
             names.insert(decl.name.id, res);
             let mut errors = Vec::new();
-            match scope
-                .terms
-                .entry(Rc::clone(namespace))
-                .or_default()
-                .entry(Rc::clone(&decl.name.name))
+            if scope
+                .get(NameKind::Term, namespace, &decl.name.name)
+                .is_some()
             {
-                Entry::Occupied(_) => errors.push(Error::Duplicate(
+                errors.push(Error::Duplicate(
                     decl.name.name.to_string(),
                     namespace.to_string(),
                     decl.name.span,
-                )),
-                Entry::Vacant(entry) => {
-                    entry.insert(res);
-                }
+                ));
+            } else {
+                scope
+                    .terms
+                    .entry(Arc::clone(namespace))
+                    .or_default()
+                    .insert(Arc::clone(&decl.name.name), res);
             }
 
-            if decl_is_intrinsic(decl) && !scope.intrinsics.insert(Rc::clone(&decl.name.name)) {
+            if decl_is_intrinsic(decl)
+                && (scope.is_intrinsic(&decl.name.name)
+                    || !scope.intrinsics.insert(Arc::clone(&decl.name.name)))
+            {
                 errors.push(Error::DuplicateIntrinsic(
                     decl.name.name.to_string(),
                     decl.name.span,
                 ));
             }
 
+            errors.extend(bind_item_aliases(
+                scope,
+                item_id,
+                false,
+                item,
+                namespace,
+                &decl.name.name,
+            ));
+
             if errors.is_empty() {
                 Ok(())
             } else {
@@ -939,34 +1197,60 @@ fn bind_global_item(
             let status = ItemStatus::from_attrs(&ast_attrs_as_hir_attrs(item.attrs.as_ref()));
             let res = Res::Item(item_id, status);
             names.insert(name.id, res);
-            match (
-                scope
-                    .terms
-                    .entry(Rc::clone(namespace))
-                    .or_default()
-                    .entry(Rc::clone(&name.name)),
-                scope
-                    .tys
-                    .entry(Rc::clone(namespace))
-                    .or_default()
-                    .entry(Rc::clone(&name.name)),
-            ) {
-                (Entry::Occupied(_), _) | (_, Entry::Occupied(_)) => Err(vec![Error::Duplicate(
+            if scope.get(NameKind::Term, namespace, &name.name).is_some()
+                || scope.get(NameKind::Ty, namespace, &name.name).is_some()
+            {
+                return Err(vec![Error::Duplicate(
                     name.name.to_string(),
                     namespace.to_string(),
                     name.span,
-                )]),
-                (Entry::Vacant(term_entry), Entry::Vacant(ty_entry)) => {
-                    term_entry.insert(res);
-                    ty_entry.insert(res);
-                    Ok(())
-                }
+                )]);
+            }
+
+            for items in [&mut scope.terms, &mut scope.tys] {
+                items
+                    .entry(Arc::clone(namespace))
+                    .or_default()
+                    .insert(Arc::clone(&name.name), res);
+            }
+            let errors = bind_item_aliases(scope, item_id, true, item, namespace, &name.name);
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
             }
         }
         ast::ItemKind::Err | ast::ItemKind::Open(..) => Ok(()),
     }
 }
 
+/// Binds the aliases that the `@Alias` attributes of a local item give it.
+fn bind_item_aliases(
+    scope: &mut GlobalScope,
+    id: ItemId,
+    is_ty: bool,
+    item: &ast::Item,
+    namespace: &str,
+    name: &str,
+) -> Vec<Error> {
+    item.attrs
+        .iter()
+        .filter(|attr| attr.name.name.as_ref() == "Alias")
+        .filter_map(|attr| {
+            // Malformed arguments are reported when the attribute is lowered.
+            let alias = alias_arg(&attr.arg)?;
+            let (alias_namespace, alias_name) = alias.rsplit_once('.')?;
+            (!bind_alias(scope, id, is_ty, &alias, namespace, name)).then(|| {
+                Error::Duplicate(
+                    alias_name.to_string(),
+                    alias_namespace.to_string(),
+                    attr.arg.span,
+                )
+            })
+        })
+        .collect()
+}
+
 fn decl_is_intrinsic(decl: &ast::CallableDecl) -> bool {
     if let CallableBody::Specs(specs) = decl.body.as_ref() {
         specs
@@ -983,7 +1267,8 @@ fn resolve<'a>(
     scopes: impl Iterator<Item = &'a Scope>,
     name: &Ident,
     namespace: &Option<Box<Ident>>,
-) -> Result<Res, Error> {
+    mut open_cache: Option<&mut OpenCache>,
+) -> Result<(Res, Option<Span>), Error> {
     let scopes = scopes.collect::<Vec<_>>();
     let mut candidates = FxHashMap::default();
     let mut vars = true;
@@ -993,12 +1278,15 @@ fn resolve<'a>(
         if namespace.is_empty() {
             if let Some(res) = resolve_scope_locals(kind, globals, scope, vars, name_str) {
                 // Local declarations shadow everything.
-                return Ok(res);
+                return Ok((res, None));
             }
         }
 
         if let Some(namespaces) = scope.opens.get(namespace) {
-            candidates = resolve_explicit_opens(kind, globals, namespaces, name_str);
+            candidates = match open_cache.as_deref_mut() {
+                Some(open_cache) => open_cache.resolve(kind, globals, namespaces, name_str),
+                None => resolve_explicit_opens(kind, globals, namespaces, name_str),
+            };
             if !candidates.is_empty() {
                 // Explicit opens shadow prelude and unopened globals.
                 break;
@@ -1034,14 +1322,34 @@ fn resolve<'a>(
             });
         }
         if let Some((res, _)) = single(candidates) {
-            return Ok(res);
+            return Ok((res, None));
         }
     }
 
     if candidates.is_empty() {
         if let Some(&res) = globals.get(kind, namespace, name_str) {
             // An unopened global is the last resort.
-            return Ok(res);
+            return Ok((res, None));
+        }
+    }
+
+    if candidates.len() > 1 {
+        // An item found under both its name and an alias is the same item, so the alias is dropped.
+        let aliases = candidates
+            .keys()
+            .filter_map(|res| match res {
+                Res::Item(id, ItemStatus::Alias) => Some(*id),
+                _ => None,
+            })
+            .filter(|id| {
+                candidates.keys().any(|res| match res {
+                    Res::Item(other, status) => other == id && *status != ItemStatus::Alias,
+                    _ => false,
+                })
+            })
+            .collect::<Vec<_>>();
+        for id in aliases {
+            candidates.remove(&Res::Item(id, ItemStatus::Alias));
         }
     }
 
@@ -1072,7 +1380,8 @@ fn resolve<'a>(
             second_open_span: opens[1].span,
         })
     } else {
-        single(candidates.into_keys())
+        single(candidates)
+            .map(|(res, open)| (res, Some(open.span)))
             .ok_or_else(|| Error::NotFound(name_str.to_string(), name.span))
     }
 }
@@ -17,7 +17,7 @@ use qsc_ast::{
     mut_visit::MutVisitor,
     visit::{self, Visitor},
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{edition::Edition, span::Span};
 use qsc_hir::assigner::Assigner as HirAssigner;
 use std::fmt::Write;
 
@@ -87,7 +87,7 @@ fn resolve_names(input: &str) -> String {
 }
 
 fn compile(input: &str) -> (Package, Names, Locals, Vec<Error>) {
-    let (namespaces, parse_errors) = qsc_parse::namespaces(input);
+    let (namespaces, parse_errors) = qsc_parse::namespaces(input, Edition::default());
     assert!(parse_errors.is_empty(), "parse failed: {parse_errors:#?}");
     let mut package = Package {
         id: NodeId::default(),
@@ -101,7 +101,8 @@ fn compile(input: &str) -> (Package, Names, Locals, Vec<Error>) {
 
     AstAssigner::new().visit_package(&mut package);
 
-    let mut cond_compile = compile::preprocess::Conditional::new(RuntimeCapabilityFlags::all());
+    let mut cond_compile =
+        compile::preprocess::Conditional::new(RuntimeCapabilityFlags::all(), &[]);
     cond_compile.visit_package(&mut package);
     let dropped_names = cond_compile.into_names();
 
@@ -2099,6 +2100,128 @@ fn disallow_duplicate_intrinsic_and_non_intrinsic_collision() {
 }
 
 #[allow(clippy::cast_possible_truncation)]
+#[test]
+fn alias_resolves_with_deprecation_warning() {
+    check(
+        indoc! {"
+            namespace Foo {
+                @Alias(\"Old.A\")
+                function A() : Unit {}
+            }
+            namespace Bar {
+                function B() : Unit {
+                    Old.A();
+                    Foo.A();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                @Alias("Old.A")
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                function item3() : Unit {
+                    item1();
+                    item1();
+                }
+            }
+
+            // DeprecatedAlias("Old.A", "Foo.A", Span { lo: 115, hi: 120 })
+        "#]],
+    );
+}
+
+#[test]
+fn alias_and_name_opened_together_are_not_ambiguous() {
+    check(
+        indoc! {"
+            namespace Foo {
+                @Alias(\"Old.A\")
+                function A() : Unit {}
+            }
+            namespace Bar {
+                open Old;
+                open Foo;
+                function B() : Unit {
+                    A();
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                @Alias("Old.A")
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                open Old;
+                open Foo;
+                function item3() : Unit {
+                    item1();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn alias_of_newtype_resolves_as_type() {
+    check(
+        indoc! {"
+            namespace Foo {
+                @Alias(\"Old.P\")
+                newtype P = Int;
+            }
+            namespace Bar {
+                open Old;
+                function B() : P {
+                    Foo.P(1)
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                @Alias("Old.P")
+                newtype item1 = Int;
+            }
+            namespace item2 {
+                open Old;
+                function item3() : item1 {
+                    item1(1)
+                }
+            }
+
+            // DeprecatedAlias("P", "Foo.P", Span { lo: 108, hi: 109 })
+        "#]],
+    );
+}
+
+#[test]
+fn alias_conflicting_with_item_is_duplicate() {
+    check(
+        indoc! {"
+            namespace Old {
+                function A() : Unit {}
+            }
+            namespace Foo {
+                @Alias(\"Old.A\")
+                function A() : Unit {}
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Unit {}
+            }
+            namespace item2 {
+                @Alias("Old.A")
+                function item3() : Unit {}
+            }
+
+            // Duplicate("A", "Old", Span { lo: 71, hi: 80 })
+        "#]],
+    );
+}
+
 fn check_locals(input: &str, expect: &Expect) {
     let parts = input.split('↘').collect::<Vec<_>>();
     assert_eq!(
@@ -2399,3 +2522,50 @@ fn get_locals_block_scope_boundary_begin() {
         &expect![""],
     );
 }
+
+#[test]
+fn opens_used_by_names_are_recorded() {
+    struct Opens(Vec<Span>);
+
+    impl Visitor<'_> for Opens {
+        fn visit_item(&mut self, item: &qsc_ast::ast::Item) {
+            if let qsc_ast::ast::ItemKind::Open(name, _) = &*item.kind {
+                self.0.push(name.span);
+            }
+            visit::walk_item(self, item);
+        }
+    }
+
+    let input = indoc! {"
+        namespace Foo {
+            function A() : Unit {}
+            newtype T = Int;
+        }
+
+        namespace Bar {
+            function B() : Unit {}
+        }
+
+        namespace Baz {
+            open Foo;
+            open Bar;
+            open Foo as F;
+            open Foo;
+
+            function C(t : T) : Unit {
+                F.A();
+            }
+        }
+    "};
+    let (package, _, locals, errors) = compile(input);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let mut opens = Opens(Vec::new());
+    opens.visit_package(&package);
+    let used = opens
+        .0
+        .iter()
+        .map(|&span| locals.is_open_used(span))
+        .collect::<Vec<_>>();
+    assert_eq!(used, [true, false, true, false]);
+}
@@ -10,26 +10,80 @@ mod tests;
 
 use miette::Diagnostic;
 use qsc_ast::ast::NodeId;
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_data_structures::{
+    edition::{Edition, LanguageFeature},
+    index_map::IndexMap,
+    message::MessageParams,
+    span::Span,
+};
 use qsc_hir::{
     hir::{CallableKind, ItemId},
     ty::{FunctorSet, GenericArg, Ty, Udt},
 };
 use rustc_hash::FxHashMap;
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Index, sync::Arc};
 use thiserror::Error;
 
 pub(super) use check::{Checker, GlobalTable};
 
 #[derive(Debug, Default, Clone)]
 pub struct Table {
-    pub udts: FxHashMap<ItemId, Udt>,
+    pub udts: ItemTable<Udt>,
 
     // AST nodes that get mapped to types are Expr, Block, Pat, and QubitInit nodes
     pub terms: IndexMap<NodeId, Ty>,
     pub generics: IndexMap<NodeId, Vec<GenericArg>>,
 }
 
+/// Global items by ID. The items of the dependencies are shared by every package checked against
+/// them, and the items of the package itself are kept apart from them.
+#[derive(Debug, Clone)]
+pub struct ItemTable<T> {
+    dependencies: Arc<FxHashMap<ItemId, T>>,
+    local: FxHashMap<ItemId, T>,
+}
+
+impl<T> Default for ItemTable<T> {
+    fn default() -> Self {
+        Self {
+            dependencies: Arc::default(),
+            local: FxHashMap::default(),
+        }
+    }
+}
+
+impl<T> ItemTable<T> {
+    fn new(dependencies: Arc<FxHashMap<ItemId, T>>) -> Self {
+        Self {
+            dependencies,
+            local: FxHashMap::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &ItemId) -> Option<&T> {
+        self.local.get(id).or_else(|| self.dependencies.get(id))
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, id: &ItemId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Adds an item of the package itself.
+    pub(crate) fn insert(&mut self, id: ItemId, item: T) -> Option<T> {
+        self.local.insert(id, item)
+    }
+}
+
+impl<T> Index<&ItemId> for ItemTable<T> {
+    type Output = T;
+
+    fn index(&self, id: &ItemId) -> &T {
+        self.get(id).expect("item should be in table")
+    }
+}
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 #[diagnostic(transparent)]
 #[error(transparent)]
@@ -75,6 +129,12 @@ enum ErrorKind {
     ))]
     #[diagnostic(code("Qsc.TypeCk.MissingClassHasIndex"))]
     MissingClassHasIndex(String, String, #[label] Span),
+    #[error("type {0} cannot be indexed by a qubit register")]
+    #[diagnostic(help(
+        "to look up classical data by the value of a qubit register, call `Microsoft.Quantum.Unstable.TableLookup.Lookup` with a target register"
+    ))]
+    #[diagnostic(code("Qsc.TypeCk.IndexByQubits"))]
+    IndexByQubits(String, #[label] Span),
     #[error("type {0} is not an integer")]
     #[diagnostic(help("only BigInt and Int are integers"))]
     #[diagnostic(code("Qsc.TypeCk.MissingClassInteger"))]
@@ -109,4 +169,44 @@ enum ErrorKind {
     #[diagnostic(help("provide a type annotation"))]
     #[diagnostic(code("Qsc.TypeCk.AmbiguousTy"))]
     AmbiguousTy(#[label] Span),
+    #[error("{0} requires edition {1}")]
+    #[diagnostic(help("set `\"edition\": \"{1}\"` in qsharp.json to use it"))]
+    #[diagnostic(code("Qsc.TypeCk.Edition"))]
+    Edition(LanguageFeature, Edition, #[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match &self.0 {
+            ErrorKind::TyMismatch(expected, found, _)
+            | ErrorKind::MissingClassHasField(expected, found, _)
+            | ErrorKind::MissingClassHasIndex(expected, found, _) => {
+                vec![expected.clone(), found.clone()]
+            }
+            ErrorKind::CallableMismatch(expected, found, _) => {
+                vec![expected.to_string(), found.to_string()]
+            }
+            ErrorKind::FunctorMismatch(expected, found, _)
+            | ErrorKind::MissingFunctor(expected, found, _) => {
+                vec![expected.to_string(), found.to_string()]
+            }
+            ErrorKind::MissingClassAdd(ty, _)
+            | ErrorKind::MissingClassAdj(ty, _)
+            | ErrorKind::MissingClassCall(ty, _)
+            | ErrorKind::MissingClassCtl(ty, _)
+            | ErrorKind::MissingClassEq(ty, _)
+            | ErrorKind::MissingClassExp(ty, _)
+            | ErrorKind::IndexByQubits(ty, _)
+            | ErrorKind::MissingClassInteger(ty, _)
+            | ErrorKind::MissingClassIterable(ty, _)
+            | ErrorKind::MissingClassNum(ty, _)
+            | ErrorKind::MissingClassShow(ty, _)
+            | ErrorKind::MissingClassUnwrap(ty, _)
+            | ErrorKind::TyHole(ty, _) => vec![ty.clone()],
+            ErrorKind::Edition(feature, edition, _) => {
+                vec![feature.to_string(), edition.to_string()]
+            }
+            ErrorKind::MissingItemTy(_) | ErrorKind::AmbiguousTy(_) => Vec::new(),
+        }
+    }
 }
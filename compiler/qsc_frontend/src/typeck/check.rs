@@ -4,7 +4,7 @@
 use super::{
     infer::Inferrer,
     rules::{self, SpecImpl},
-    Error, ErrorKind, Table,
+    Error, ErrorKind, ItemTable, Table,
 };
 use crate::{
     resolve::{Names, Res},
@@ -14,30 +14,35 @@ use qsc_ast::{
     ast::{self, NodeId, TopLevelNode},
     visit::{self, Visitor},
 };
-use qsc_data_structures::index_map::IndexMap;
+use qsc_data_structures::{edition::Edition, index_map::IndexMap};
 use qsc_hir::{
     hir::{self, ItemId, PackageId},
     ty::{FunctorSetValue, Scheme, Ty, Udt},
 };
 use rustc_hash::FxHashMap;
-use std::vec;
+use std::{sync::Arc, vec};
 
+/// The types of the items of the dependencies. They are behind shared pointers, so the checkers
+/// of every package compiled against the same dependencies share them.
+#[derive(Clone)]
 pub(crate) struct GlobalTable {
-    udts: FxHashMap<ItemId, Udt>,
-    terms: FxHashMap<ItemId, Scheme>,
+    udts: Arc<FxHashMap<ItemId, Udt>>,
+    terms: Arc<FxHashMap<ItemId, Scheme>>,
     errors: Vec<Error>,
 }
 
 impl GlobalTable {
     pub(crate) fn new() -> Self {
         Self {
-            udts: FxHashMap::default(),
-            terms: FxHashMap::default(),
+            udts: Arc::default(),
+            terms: Arc::default(),
             errors: Vec::new(),
         }
     }
 
     pub(crate) fn add_external_package(&mut self, id: PackageId, package: &hir::Package) {
+        let udts = Arc::make_mut(&mut self.udts);
+        let terms = Arc::make_mut(&mut self.terms);
         for item in package.items.values() {
             let item_id = ItemId {
                 package: Some(id),
@@ -46,13 +51,12 @@ impl GlobalTable {
 
             match &item.kind {
                 hir::ItemKind::Callable(decl) => {
-                    self.terms.insert(item_id, decl.scheme().with_package(id))
+                    terms.insert(item_id, decl.scheme().with_package(id))
                 }
                 hir::ItemKind::Namespace(..) => None,
                 hir::ItemKind::Ty(_, udt) => {
-                    self.udts.insert(item_id, udt.clone());
-                    self.terms
-                        .insert(item_id, udt.cons_scheme(item_id).with_package(id))
+                    udts.insert(item_id, udt.clone());
+                    terms.insert(item_id, udt.cons_scheme(item_id).with_package(id))
                 }
             };
         }
@@ -60,25 +64,28 @@ impl GlobalTable {
 }
 
 pub(crate) struct Checker {
-    globals: FxHashMap<ItemId, Scheme>,
+    globals: ItemTable<Scheme>,
     table: Table,
     inferrer: Inferrer,
     new: Vec<NodeId>,
     errors: Vec<Error>,
+    edition: Edition,
 }
 
 impl Checker {
-    pub(crate) fn new(globals: GlobalTable) -> Self {
+    /// Creates a checker that follows the typing rules of the edition.
+    pub(crate) fn new(globals: GlobalTable, edition: Edition) -> Self {
         Checker {
-            globals: globals.terms,
+            globals: ItemTable::new(globals.terms),
             table: Table {
-                udts: globals.udts,
+                udts: ItemTable::new(globals.udts),
                 terms: IndexMap::new(),
                 generics: IndexMap::new(),
             },
-            inferrer: Inferrer::new(),
+            inferrer: Inferrer::new(edition),
             new: Vec::new(),
             errors: globals.errors,
+            edition,
         }
     }
 
@@ -103,6 +110,7 @@ impl Checker {
                 names,
                 &self.globals,
                 &mut self.table,
+                self.edition,
                 entry,
             ));
         }
@@ -174,6 +182,7 @@ impl Checker {
             names,
             &self.globals,
             &mut self.table,
+            self.edition,
             spec,
         ));
     }
@@ -1,10 +1,14 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use super::{Error, ErrorKind};
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use super::{Error, ErrorKind, ItemTable};
+use qsc_data_structures::{
+    edition::{Edition, LanguageFeature},
+    index_map::IndexMap,
+    span::Span,
+};
 use qsc_hir::{
-    hir::{ItemId, PrimField, Res},
+    hir::{PrimField, Res},
     ty::{
         Arrow, FunctorSet, FunctorSetValue, GenericArg, GenericParam, InferFunctorId, InferTyId,
         Prim, Scheme, Ty, Udt,
@@ -138,7 +142,12 @@ impl Class {
         }
     }
 
-    fn check(self, udts: &FxHashMap<ItemId, Udt>, span: Span) -> (Vec<Constraint>, Vec<Error>) {
+    fn check(
+        self,
+        udts: &ItemTable<Udt>,
+        edition: Edition,
+        span: Span,
+    ) -> (Vec<Constraint>, Vec<Error>) {
         match self {
             Class::Add(ty) if check_add(&ty) => (Vec::new(), Vec::new()),
             Class::Add(ty) => (
@@ -152,7 +161,7 @@ impl Class {
                 output,
             } => check_call(callee, &input, output, span),
             Class::Ctl { op, with_ctls } => check_ctl(op, with_ctls, span),
-            Class::Eq(ty) => check_eq(ty, span),
+            Class::Eq(ty) => check_eq(udts, edition, ty, span),
             Class::Exp { base, power } => check_exp(base, power, span),
             Class::HasField { record, name, item } => {
                 check_has_field(udts, &record, name, item, span)
@@ -173,7 +182,7 @@ impl Class {
                 Vec::new(),
                 vec![Error(ErrorKind::MissingClassNum(ty.display(), span))],
             ),
-            Class::Show(ty) => check_show(ty, span),
+            Class::Show(ty) => check_show(udts, ty, span),
             Class::Unwrap { wrapper, base } => check_unwrap(udts, &wrapper, base, span),
         }
     }
@@ -336,9 +345,10 @@ pub(super) struct Inferrer {
 }
 
 impl Inferrer {
-    pub(super) fn new() -> Self {
+    /// Creates an inferrer that follows the typing rules of the edition.
+    pub(super) fn new(edition: Edition) -> Self {
         Self {
-            solver: Solver::new(),
+            solver: Solver::new(edition),
             constraints: VecDeque::new(),
             next_ty: InferTyId::default(),
             next_functor: InferFunctorId::default(),
@@ -402,7 +412,7 @@ impl Inferrer {
     }
 
     /// Solves for all variables given the accumulated constraints.
-    pub(super) fn solve(&mut self, udts: &FxHashMap<ItemId, Udt>) -> Vec<Error> {
+    pub(super) fn solve(&mut self, udts: &ItemTable<Udt>) -> Vec<Error> {
         while let Some(constraint) = self.constraints.pop_front() {
             for constraint in self.solver.constrain(udts, constraint).into_iter().rev() {
                 self.constraints.push_front(constraint);
@@ -454,23 +464,21 @@ struct Solver {
     pending_tys: FxHashMap<InferTyId, Vec<Class>>,
     pending_functors: FxHashMap<InferFunctorId, FunctorSetValue>,
     errors: Vec<Error>,
+    edition: Edition,
 }
 
 impl Solver {
-    fn new() -> Self {
+    fn new(edition: Edition) -> Self {
         Self {
             solution: Solution::default(),
             pending_tys: FxHashMap::default(),
             pending_functors: FxHashMap::default(),
             errors: Vec::new(),
+            edition,
         }
     }
 
-    fn constrain(
-        &mut self,
-        udts: &FxHashMap<ItemId, Udt>,
-        constraint: Constraint,
-    ) -> Vec<Constraint> {
+    fn constrain(&mut self, udts: &ItemTable<Udt>, constraint: Constraint) -> Vec<Constraint> {
         match constraint {
             Constraint::Class(class, span) => self.class(udts, class, span),
             Constraint::Eq {
@@ -489,12 +497,7 @@ impl Solver {
         }
     }
 
-    fn class(
-        &mut self,
-        udts: &FxHashMap<ItemId, Udt>,
-        class: Class,
-        span: Span,
-    ) -> Vec<Constraint> {
+    fn class(&mut self, udts: &ItemTable<Udt>, class: Class, span: Span) -> Vec<Constraint> {
         let unknown_dependency = class.dependencies().into_iter().any(|ty| {
             if ty == &Ty::Err {
                 true
@@ -514,7 +517,7 @@ impl Solver {
         } else {
             let (constraints, mut errors) = class
                 .map(|ty| substituted_ty(&self.solution, ty))
-                .check(udts, span);
+                .check(udts, self.edition, span);
             self.errors.append(&mut errors);
             constraints
         }
@@ -823,7 +826,12 @@ fn check_ctl(op: Ty, with_ctls: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>)
     )
 }
 
-fn check_eq(ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
+fn check_eq(
+    udts: &ItemTable<Udt>,
+    edition: Edition,
+    ty: Ty,
+    span: Span,
+) -> (Vec<Constraint>, Vec<Error>) {
     match ty {
         Ty::Prim(
             Prim::BigInt
@@ -844,6 +852,23 @@ fn check_eq(ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
                 .collect(),
             Vec::new(),
         ),
+        Ty::Udt(_, Res::Item(id)) if udts.contains_key(&id) => {
+            // User-defined types are equal when the values they wrap are.
+            let feature = LanguageFeature::UdtEquality;
+            if edition.supports(feature) {
+                let udt = &udts[&id];
+                let base = id.package.map_or_else(
+                    || udt.get_pure_ty(),
+                    |package_id| udt.get_pure_ty().with_package(package_id),
+                );
+                (vec![Constraint::Class(Class::Eq(base), span)], Vec::new())
+            } else {
+                (
+                    Vec::new(),
+                    vec![Error(ErrorKind::Edition(feature, feature.edition(), span))],
+                )
+            }
+        }
         _ => (
             Vec::new(),
             vec![Error(ErrorKind::MissingClassEq(ty.display(), span))],
@@ -877,7 +902,7 @@ fn check_exp(base: Ty, power: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
 }
 
 fn check_has_field(
-    udts: &FxHashMap<ItemId, Udt>,
+    udts: &ItemTable<Udt>,
     record: &Ty,
     name: String,
     item: Ty,
@@ -956,6 +981,10 @@ fn check_has_index(
             }],
             Vec::new(),
         ),
+        (container @ Ty::Array(_), Ty::Array(index)) if *index == Ty::Prim(Prim::Qubit) => (
+            Vec::new(),
+            vec![Error(ErrorKind::IndexByQubits(container.display(), span))],
+        ),
         (container, index) => (
             Vec::new(),
             vec![Error(ErrorKind::MissingClassHasIndex(
@@ -1003,8 +1032,16 @@ fn check_num(ty: &Ty) -> bool {
     matches!(ty, Ty::Prim(Prim::BigInt | Prim::Double | Prim::Int))
 }
 
-fn check_show(ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
+fn check_show(udts: &ItemTable<Udt>, ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
     match ty {
+        Ty::Udt(_, Res::Item(id)) if udts.contains_key(&id) => {
+            let udt = &udts[&id];
+            let ty = id.package.map_or_else(
+                || udt.get_pure_ty(),
+                |package_id| udt.get_pure_ty().with_package(package_id),
+            );
+            (vec![Constraint::Class(Class::Show(ty), span)], Vec::new())
+        }
         Ty::Array(item) => (
             vec![Constraint::Class(Class::Show(*item), span)],
             Vec::new(),
@@ -1025,7 +1062,7 @@ fn check_show(ty: Ty, span: Span) -> (Vec<Constraint>, Vec<Error>) {
 }
 
 fn check_unwrap(
-    udts: &FxHashMap<ItemId, Udt>,
+    udts: &ItemTable<Udt>,
     wrapper: &Ty,
     base: Ty,
     span: Span,
@@ -4,19 +4,18 @@
 use super::{
     convert,
     infer::{ArgTy, Class, Inferrer, TySource},
-    Error, Table,
+    Error, ItemTable, Table,
 };
 use crate::resolve::{self, Names, Res};
 use qsc_ast::ast::{
     self, BinOp, Block, Expr, ExprKind, Functor, Lit, NodeId, Pat, PatKind, QubitInit,
     QubitInitKind, Spec, Stmt, StmtKind, StringComponent, TernOp, TyKind, UnOp,
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{edition::Edition, span::Span};
 use qsc_hir::{
-    hir::{self, ItemId},
+    hir,
     ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, Prim, Scheme, Ty},
 };
-use rustc_hash::FxHashMap;
 use std::convert::identity;
 
 /// An inferred partial term has a type, but may be the result of a diverging (non-terminating)
@@ -37,7 +36,7 @@ impl<T> Partial<T> {
 
 struct Context<'a> {
     names: &'a Names,
-    globals: &'a FxHashMap<ItemId, Scheme>,
+    globals: &'a ItemTable<Scheme>,
     table: &'a mut Table,
     return_ty: Option<Ty>,
     typed_holes: Vec<(NodeId, Span)>,
@@ -48,7 +47,7 @@ struct Context<'a> {
 impl<'a> Context<'a> {
     fn new(
         names: &'a Names,
-        globals: &'a FxHashMap<ItemId, Scheme>,
+        globals: &'a ItemTable<Scheme>,
         table: &'a mut Table,
         inferrer: &'a mut Inferrer,
         new: Vec<NodeId>,
@@ -789,11 +788,12 @@ pub(super) struct SpecImpl<'a> {
 
 pub(super) fn spec(
     names: &Names,
-    globals: &FxHashMap<ItemId, Scheme>,
+    globals: &ItemTable<Scheme>,
     table: &mut Table,
+    edition: Edition,
     spec: SpecImpl,
 ) -> Vec<Error> {
-    let mut inferrer = Inferrer::new();
+    let mut inferrer = Inferrer::new(edition);
     let mut context = Context::new(names, globals, table, &mut inferrer, Vec::new());
     context.infer_spec(spec);
     context.solve()
@@ -801,11 +801,12 @@ pub(super) fn spec(
 
 pub(super) fn expr(
     names: &Names,
-    globals: &FxHashMap<ItemId, Scheme>,
+    globals: &ItemTable<Scheme>,
     table: &mut Table,
+    edition: Edition,
     expr: &Expr,
 ) -> Vec<Error> {
-    let mut inferrer = Inferrer::new();
+    let mut inferrer = Inferrer::new(edition);
     let mut context = Context::new(names, globals, table, &mut inferrer, Vec::new());
     context.infer_expr(expr);
     context.solve()
@@ -813,7 +814,7 @@ pub(super) fn expr(
 
 pub(super) fn stmt(
     names: &Names,
-    globals: &FxHashMap<ItemId, Scheme>,
+    globals: &ItemTable<Scheme>,
     table: &mut Table,
     inferrer: &mut Inferrer,
     stmt: &Stmt,
@@ -825,7 +826,7 @@ pub(super) fn stmt(
 
 pub(super) fn solve(
     names: &Names,
-    globals: &FxHashMap<ItemId, Scheme>,
+    globals: &ItemTable<Scheme>,
     table: &mut Table,
     inferrer: &mut Inferrer,
     new_nodes: Vec<NodeId>,
@@ -16,7 +16,7 @@ use qsc_ast::{
     mut_visit::MutVisitor,
     visit::{self, Visitor},
 };
-use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_data_structures::{edition::Edition, index_map::IndexMap, span::Span};
 use qsc_hir::{assigner::Assigner as HirAssigner, ty::Ty};
 use std::fmt::Write;
 
@@ -52,7 +52,7 @@ impl<'a> Visitor<'a> for TyCollector<'a> {
 }
 
 fn check(input: &str, entry_expr: &str, expect: &Expect) {
-    let (package, tys, errors) = compile(input, entry_expr);
+    let (package, tys, errors) = compile(input, entry_expr, Edition::default());
     let mut collector = TyCollector {
         tys: &tys.terms,
         nodes: Vec::new(),
@@ -79,8 +79,12 @@ fn check(input: &str, entry_expr: &str, expect: &Expect) {
     expect.assert_eq(&actual);
 }
 
-fn compile(input: &str, entry_expr: &str) -> (Package, super::Table, Vec<compile::Error>) {
-    let mut package = parse(input, entry_expr);
+fn compile(
+    input: &str,
+    entry_expr: &str,
+    edition: Edition,
+) -> (Package, super::Table, Vec<compile::Error>) {
+    let mut package = parse(input, entry_expr, edition);
     AstAssigner::new().visit_package(&mut package);
     let mut assigner = HirAssigner::new();
 
@@ -91,7 +95,7 @@ fn compile(input: &str, entry_expr: &str) -> (Package, super::Table, Vec<compile
     let (names, _, mut resolve_errors) = resolver.into_result();
     errors.append(&mut resolve_errors);
 
-    let mut checker = Checker::new(super::GlobalTable::new());
+    let mut checker = Checker::new(super::GlobalTable::new(), edition);
     checker.check_package(&names, &package);
     let (tys, ty_errors) = checker.into_table();
 
@@ -105,14 +109,14 @@ fn compile(input: &str, entry_expr: &str) -> (Package, super::Table, Vec<compile
     (package, tys, errors)
 }
 
-fn parse(input: &str, entry_expr: &str) -> Package {
-    let (namespaces, errors) = qsc_parse::namespaces(input);
+fn parse(input: &str, entry_expr: &str, edition: Edition) -> Package {
+    let (namespaces, errors) = qsc_parse::namespaces(input, edition);
     assert!(errors.is_empty(), "parsing input failed: {errors:#?}");
 
     let entry = if entry_expr.is_empty() {
         None
     } else {
-        let (mut entry, errors) = qsc_parse::expr(entry_expr);
+        let (mut entry, errors) = qsc_parse::expr(entry_expr, edition);
         let offset = input
             .len()
             .try_into()
@@ -412,6 +416,38 @@ fn array_index_error() {
     );
 }
 
+#[test]
+fn array_index_by_qubit_register_error() {
+    check(
+        indoc! {"
+            namespace A {
+                operation Foo() : Unit {
+                    use address = Qubit[1];
+                    let entry = [[true], [false]][address];
+                }
+            }
+        "},
+        "",
+        &expect![[r##"
+            #6 31-33 "()" : Unit
+            #10 41-128 "{\n        use address = Qubit[1];\n        let entry = [[true], [false]][address];\n    }" : Unit
+            #12 55-62 "address" : Qubit[]
+            #14 65-73 "Qubit[1]" : Qubit[]
+            #15 71-72 "1" : Int
+            #17 87-92 "entry" : ?2
+            #19 95-121 "[[true], [false]][address]" : ?2
+            #20 95-112 "[[true], [false]]" : Bool[][]
+            #21 96-102 "[true]" : Bool[]
+            #22 97-101 "true" : Bool
+            #23 104-111 "[false]" : Bool[]
+            #24 105-110 "false" : Bool
+            #25 113-120 "address" : Qubit[]
+            Error(Type(Error(IndexByQubits("Bool[][]", Span { lo: 95, hi: 121 }))))
+            Error(Type(Error(AmbiguousTy(Span { lo: 95, hi: 121 }))))
+        "##]],
+    );
+}
+
 #[test]
 fn array_repeat_error() {
     check(
@@ -1951,6 +1987,57 @@ fn interpolate_operation() {
     );
 }
 
+#[test]
+fn interpolate_udt() {
+    check(
+        indoc! {r#"
+            namespace A {
+                newtype Point = (X : Int, Y : Int);
+                function Foo() : String {
+                    let p = Point(1, 2);
+                    $"{p}"
+                }
+            }
+        "#},
+        "",
+        &expect![[r##"
+            #19 70-72 "()" : Unit
+            #23 82-133 "{\n        let p = Point(1, 2);\n        $\"{p}\"\n    }" : String
+            #25 96-97 "p" : UDT<"Point": Item 1>
+            #27 100-111 "Point(1, 2)" : UDT<"Point": Item 1>
+            #28 100-105 "Point" : ((Int, Int) -> UDT<"Point": Item 1>)
+            #31 105-111 "(1, 2)" : (Int, Int)
+            #32 106-107 "1" : Int
+            #33 109-110 "2" : Int
+            #35 121-127 "$\"{p}\"" : String
+            #36 124-125 "p" : UDT<"Point": Item 1>
+        "##]],
+    );
+}
+
+#[test]
+fn interpolate_udt_of_function() {
+    check(
+        indoc! {r#"
+            namespace A {
+                newtype Callback = (Unit -> Unit);
+                function Foo(f : Callback) : String {
+                    $"{f}"
+                }
+            }
+        "#},
+        "",
+        &expect![[r##"
+            #16 69-83 "(f : Callback)" : UDT<"Callback": Item 1>
+            #17 70-82 "f : Callback" : UDT<"Callback": Item 1>
+            #25 93-115 "{\n        $\"{f}\"\n    }" : String
+            #27 103-109 "$\"{f}\"" : String
+            #28 106-107 "f" : UDT<"Callback": Item 1>
+            Error(Type(Error(MissingClassShow("(Unit -> Unit)", Span { lo: 106, hi: 107 }))))
+        "##]],
+    );
+}
+
 #[test]
 fn interpolate_int_array() {
     check(
@@ -3630,3 +3717,36 @@ fn use_range_field_names_in_udt() {
         "#]],
     );
 }
+
+#[test]
+fn newtype_equality_requires_edition() {
+    let source = indoc! {"
+        namespace A {
+            newtype Pair = (Int, Int);
+            newtype Op = (Qubit => Unit);
+            function Same(a : Pair, b : Pair) : Bool { a == b }
+            function SameOp(a : Op, b : Op) : Bool { a == b }
+        }
+    "};
+    let messages = |edition| {
+        compile(source, "", edition)
+            .2
+            .iter()
+            .map(|error| match &error.0 {
+                compile::ErrorKind::Type(error) => error.to_string(),
+                _ => error.to_string(),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        messages(Edition::Q2023),
+        [
+            "comparing user-defined types for equality requires edition 2024",
+            "comparing user-defined types for equality requires edition 2024",
+        ]
+    );
+    assert_eq!(
+        messages(Edition::Q2024),
+        ["type (Qubit => Unit) does not support equality"]
+    );
+}
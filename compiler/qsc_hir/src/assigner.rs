@@ -6,7 +6,7 @@ use crate::{
     mut_visit::{self, MutVisitor},
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Assigner {
     next_node: NodeId,
     next_item: LocalItemId,
@@ -13,9 +13,9 @@ use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
     hash::{Hash, Hasher},
-    rc::Rc,
     result,
     str::FromStr,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -69,6 +69,12 @@ impl Display for NodeId {
     }
 }
 
+impl From<usize> for NodeId {
+    fn from(value: usize) -> Self {
+        Self(u32::try_from(value).expect("node ID should fit in u32"))
+    }
+}
+
 impl From<NodeId> for usize {
     fn from(value: NodeId) -> Self {
         assert!(!value.is_default(), "default node ID should be replaced");
@@ -192,6 +198,8 @@ pub enum ItemStatus {
     Available,
     /// The item is marked as unimplemented and uses are disallowed.
     Unimplemented,
+    /// The item is used through a deprecated alias, which resolves but should be migrated.
+    Alias,
 }
 
 impl ItemStatus {
@@ -282,7 +290,7 @@ pub struct Item {
     /// The parent item.
     pub parent: Option<LocalItemId>,
     /// The documentation.
-    pub doc: Rc<str>,
+    pub doc: Arc<str>,
     /// The attributes.
     pub attrs: Vec<Attr>,
     /// The visibility.
@@ -1002,7 +1010,7 @@ pub enum StringComponent {
     /// An expression.
     Expr(Expr),
     /// A string literal.
-    Lit(Rc<str>),
+    Lit(Arc<str>),
 }
 
 /// A pattern.
@@ -1137,7 +1145,7 @@ pub struct Ident {
     /// The span.
     pub span: Span,
     /// The identifier name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
 }
 
 impl Display for Ident {
@@ -1149,10 +1157,18 @@ impl Display for Ident {
 /// An attribute.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Attr {
+    /// Gives an item an older fully qualified name, which still resolves to the item but is
+    /// deprecated. This lets items move between namespaces without breaking existing code.
+    Alias(Arc<str>),
     /// Provide pre-processing information about when an item should be included in compilation.
     Config,
+    /// Provides the constant default value of a parameter of an entry point, which is passed to the
+    /// entry point when the program is run without an entry expression.
+    Default(Arc<str>, Box<Expr>),
     /// Indicates that a callable is an entry point to a program.
     EntryPoint,
+    /// Bounds the number of iterations of the `while` loops in a callable.
+    MaxIterations,
     /// Indicates that an item does not have an implementation available for use.
     Unimplemented,
 }
@@ -1164,6 +1180,7 @@ impl FromStr for Attr {
         match s {
             "Config" => Ok(Self::Config),
             "EntryPoint" => Ok(Self::EntryPoint),
+            "MaxIterations" => Ok(Self::MaxIterations),
             "Unimplemented" => Ok(Self::Unimplemented),
             _ => Err(()),
         }
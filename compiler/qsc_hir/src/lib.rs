@@ -8,6 +8,7 @@ pub mod assigner;
 pub mod global;
 pub mod hir;
 pub mod mut_visit;
+pub mod renumber;
 pub mod ty;
 pub mod validate;
 pub mod visit;
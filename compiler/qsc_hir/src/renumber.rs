@@ -0,0 +1,214 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Renumbers the items and nodes of a package so that their IDs follow from its contents, not from
+//! the order in which its sources were compiled. Items are ordered by their names, qualified by
+//! the names of the items they are declared in, and nodes are numbered in the order they appear
+//! in the renumbered items. The IDs of other packages are left as they are.
+
+use crate::{
+    hir::{
+        Block, CallableDecl, Expr, ExprKind, Ident, Item, ItemId, ItemKind, LocalItemId, NodeId,
+        Package, Pat, QubitInit, Res, SpecDecl, Stmt, StmtKind,
+    },
+    mut_visit::{self, MutVisitor},
+    ty::{GenericArg, Ty, UdtDef, UdtDefKind},
+};
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+
+/// Returns a copy of the package with stable IDs, for serializing it. Items with the same
+/// qualified name, such as two declarations of a namespace, keep their relative order.
+#[must_use]
+pub fn renumber(package: &Package) -> Package {
+    let mut order = package
+        .items
+        .values()
+        .map(|item| (qualified_name(package, item), item.id))
+        .collect::<Vec<_>>();
+    order.sort();
+
+    let mut renumberer = Renumberer {
+        items: order
+            .iter()
+            .enumerate()
+            .map(|(index, &(_, id))| (id, LocalItemId::from(index)))
+            .collect(),
+        nodes: FxHashMap::default(),
+        next_node: NodeId::FIRST,
+    };
+
+    let mut stable = Package::default();
+    for (_, id) in order {
+        let mut item = package.items.get(id).expect("item should exist").clone();
+        renumberer.visit_item(&mut item);
+        stable.items.insert(item.id, item);
+    }
+    stable.stmts.clone_from(&package.stmts);
+    for stmt in &mut stable.stmts {
+        renumberer.visit_stmt(stmt);
+    }
+    stable.entry.clone_from(&package.entry);
+    if let Some(entry) = &mut stable.entry {
+        renumberer.visit_expr(entry);
+    }
+    stable
+}
+
+/// The name of the item after the names of the items it is declared in, outermost first.
+fn qualified_name(package: &Package, item: &Item) -> Vec<Arc<str>> {
+    let mut names = vec![name(item)];
+    let mut parent = item.parent;
+    while let Some(id) = parent {
+        let item = package.items.get(id).expect("parent item should exist");
+        names.push(name(item));
+        parent = item.parent;
+    }
+    names.reverse();
+    names
+}
+
+fn name(item: &Item) -> Arc<str> {
+    match &item.kind {
+        ItemKind::Callable(decl) => Arc::clone(&decl.name.name),
+        ItemKind::Namespace(name, _) | ItemKind::Ty(name, _) => Arc::clone(&name.name),
+    }
+}
+
+struct Renumberer {
+    items: FxHashMap<LocalItemId, LocalItemId>,
+    nodes: FxHashMap<NodeId, NodeId>,
+    next_node: NodeId,
+}
+
+impl Renumberer {
+    fn item(&self, id: LocalItemId) -> LocalItemId {
+        self.items[&id]
+    }
+
+    /// Returns the new ID of the node, assigning the next one if the node hasn't been seen yet.
+    /// Definitions and uses of a local share an ID, so the first of them to be seen assigns it.
+    fn node(&mut self, id: NodeId) -> NodeId {
+        if id.is_default() {
+            return id;
+        }
+        *self.nodes.entry(id).or_insert_with(|| {
+            let new = self.next_node;
+            self.next_node = new.successor();
+            new
+        })
+    }
+
+    fn res(&mut self, res: &mut Res) {
+        match res {
+            Res::Item(ItemId {
+                package: None,
+                item,
+            }) => *item = self.item(*item),
+            Res::Local(node) => *node = self.node(*node),
+            Res::Err | Res::Item(_) => {}
+        }
+    }
+
+    fn ty(&mut self, ty: &mut Ty) {
+        match ty {
+            Ty::Array(item) => self.ty(item),
+            Ty::Arrow(arrow) => {
+                self.ty(&mut arrow.input);
+                self.ty(&mut arrow.output);
+            }
+            Ty::Tuple(items) => items.iter_mut().for_each(|item| self.ty(item)),
+            Ty::Udt(_, res) => self.res(res),
+            Ty::Infer(_) | Ty::Param(..) | Ty::Prim(_) | Ty::Err => {}
+        }
+    }
+
+    fn udt_def(&mut self, def: &mut UdtDef) {
+        match &mut def.kind {
+            UdtDefKind::Field(field) => self.ty(&mut field.ty),
+            UdtDefKind::Tuple(defs) => defs.iter_mut().for_each(|def| self.udt_def(def)),
+        }
+    }
+}
+
+impl MutVisitor for Renumberer {
+    fn visit_item(&mut self, item: &mut Item) {
+        item.id = self.item(item.id);
+        item.parent = item.parent.map(|parent| self.item(parent));
+        match &mut item.kind {
+            ItemKind::Namespace(_, items) => {
+                for item in items.iter_mut() {
+                    *item = self.item(*item);
+                }
+            }
+            ItemKind::Ty(_, udt) => self.udt_def(&mut udt.definition),
+            ItemKind::Callable(_) => {}
+        }
+        mut_visit::walk_item(self, item);
+    }
+
+    fn visit_callable_decl(&mut self, decl: &mut CallableDecl) {
+        decl.id = self.node(decl.id);
+        self.ty(&mut decl.output);
+        mut_visit::walk_callable_decl(self, decl);
+    }
+
+    fn visit_spec_decl(&mut self, decl: &mut SpecDecl) {
+        decl.id = self.node(decl.id);
+        mut_visit::walk_spec_decl(self, decl);
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        block.id = self.node(block.id);
+        self.ty(&mut block.ty);
+        mut_visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.id = self.node(stmt.id);
+        if let StmtKind::Item(item) = &mut stmt.kind {
+            *item = self.item(*item);
+        }
+        mut_visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        expr.id = self.node(expr.id);
+        self.ty(&mut expr.ty);
+        match &mut expr.kind {
+            ExprKind::Closure(captures, item) => {
+                for capture in captures.iter_mut() {
+                    *capture = self.node(*capture);
+                }
+                *item = self.item(*item);
+            }
+            ExprKind::Var(res, args) => {
+                self.res(res);
+                for arg in args {
+                    if let GenericArg::Ty(ty) = arg {
+                        self.ty(ty);
+                    }
+                }
+            }
+            _ => {}
+        }
+        mut_visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &mut Pat) {
+        pat.id = self.node(pat.id);
+        self.ty(&mut pat.ty);
+        mut_visit::walk_pat(self, pat);
+    }
+
+    fn visit_qubit_init(&mut self, init: &mut QubitInit) {
+        init.id = self.node(init.id);
+        self.ty(&mut init.ty);
+        mut_visit::walk_qubit_init(self, init);
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident) {
+        ident.id = self.node(ident.id);
+        mut_visit::walk_ident(self, ident);
+    }
+}
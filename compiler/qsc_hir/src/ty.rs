@@ -8,7 +8,7 @@ use rustc_hash::FxHashMap;
 use crate::hir::{CallableKind, FieldPath, Functor, ItemId, PackageId, Res};
 use std::{
     fmt::{self, Debug, Display, Formatter, Write},
-    rc::Rc,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -33,13 +33,13 @@ pub enum Ty {
     /// A placeholder type variable used during type inference.
     Infer(InferTyId),
     /// A type parameter.
-    Param(Rc<str>, ParamId),
+    Param(Arc<str>, ParamId),
     /// A primitive type.
     Prim(Prim),
     /// A tuple type.
     Tuple(Vec<Ty>),
     /// A user-defined type.
-    Udt(Rc<str>, Res),
+    Udt(Arc<str>, Res),
     /// An invalid type.
     #[default]
     Err,
@@ -148,6 +148,7 @@ impl Display for Ty {
 }
 
 /// A type scheme.
+#[derive(Clone)]
 pub struct Scheme {
     params: Vec<GenericParam>,
     ty: Box<Arrow>,
@@ -179,6 +180,12 @@ impl Scheme {
         &self.params
     }
 
+    /// The type of the callable, in terms of its generic parameters.
+    #[must_use]
+    pub fn ty(&self) -> &Arrow {
+        &self.ty
+    }
+
     /// Instantiates this type scheme with the given arguments.
     ///
     /// # Errors
@@ -279,7 +286,7 @@ pub struct TypeParamName {
     /// The span.
     pub span: Span,
     /// The name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
 }
 
 impl Display for TypeParamName {
@@ -517,7 +524,7 @@ pub struct Udt {
     /// The span.
     pub span: Span,
     /// The name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     // The definition.
     pub definition: UdtDef,
 }
@@ -693,7 +700,7 @@ pub struct UdtField {
     /// The span of the field name.
     pub name_span: Option<Span>,
     /// The field name.
-    pub name: Option<Rc<str>>,
+    pub name: Option<Arc<str>>,
     // The field type.
     pub ty: Ty,
 }
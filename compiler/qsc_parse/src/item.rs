@@ -21,11 +21,11 @@ use crate::{
     ErrorKind,
 };
 use qsc_ast::ast::{
-    Attr, Block, CallableBody, CallableDecl, CallableKind, Ident, Item, ItemKind, Namespace,
-    NodeId, Pat, PatKind, Path, Spec, SpecBody, SpecDecl, SpecGen, StmtKind, TopLevelNode, Ty,
-    TyDef, TyDefKind, TyKind, Visibility, VisibilityKind,
+    Attr, Block, CallableBody, CallableDecl, CallableKind, Expr, ExprKind, Ident, Item, ItemKind,
+    Namespace, NodeId, Pat, PatKind, Path, Spec, SpecBody, SpecDecl, SpecGen, StmtKind,
+    TopLevelNode, Ty, TyDef, TyDefKind, TyKind, Visibility, VisibilityKind,
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{edition::LanguageFeature, span::Span};
 
 pub(super) fn parse(s: &mut Scanner) -> Result<Box<Item>> {
     let lo = s.peek().span.lo;
@@ -177,7 +177,7 @@ fn parse_attr(s: &mut Scanner) -> Result<Box<Attr>> {
     let lo = s.peek().span.lo;
     token(s, TokenKind::At)?;
     let name = ident(s)?;
-    let arg = expr(s)?;
+    let arg = parse_attr_arg(s)?;
     Ok(Box::new(Attr {
         id: NodeId::default(),
         span: s.span(lo),
@@ -186,6 +186,36 @@ fn parse_attr(s: &mut Scanner) -> Result<Box<Attr>> {
     }))
 }
 
+/// Parses the argument of an attribute, which is an expression or a parenthesized list whose
+/// entries may be settings like `feature = "name"`.
+fn parse_attr_arg(s: &mut Scanner) -> Result<Box<Expr>> {
+    let lo = s.peek().span.lo;
+    if token(s, TokenKind::Open(Delim::Paren)).is_err() {
+        return expr(s);
+    }
+    let (settings, final_sep) = seq(s, parse_attr_setting)?;
+    token(s, TokenKind::Close(Delim::Paren))?;
+    Ok(Box::new(Expr {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(final_sep.reify(settings, ExprKind::Paren, ExprKind::Tuple)),
+    }))
+}
+
+fn parse_attr_setting(s: &mut Scanner) -> Result<Box<Expr>> {
+    let lo = s.peek().span.lo;
+    let lhs = expr(s)?;
+    if token(s, TokenKind::Eq).is_err() {
+        return Ok(lhs);
+    }
+    let rhs = expr(s)?;
+    Ok(Box::new(Expr {
+        id: NodeId::default(),
+        span: s.span(lo),
+        kind: Box::new(ExprKind::Assign(lhs, rhs)),
+    }))
+}
+
 fn parse_visibility(s: &mut Scanner) -> Result<Visibility> {
     let lo = s.peek().span.lo;
     token(s, TokenKind::Keyword(Keyword::Internal))?;
@@ -315,9 +345,7 @@ fn parse_callable_decl(s: &mut Scanner) -> Result<Box<CallableDecl>> {
 
     let input = pat(s)?;
     check_input_parens(&input)?;
-    token(s, TokenKind::Colon)?;
-    throw_away_doc(s);
-    let output = ty(s)?;
+    let output = parse_callable_output(s, &input)?;
     let functors = if token(s, TokenKind::Keyword(Keyword::Is)).is_ok() {
         Some(Box::new(ty::functor_expr(s)?))
     } else {
@@ -339,6 +367,33 @@ fn parse_callable_decl(s: &mut Scanner) -> Result<Box<CallableDecl>> {
     }))
 }
 
+/// Parses the output type of a callable. A callable without one, where the body or the functors
+/// follow the input, returns `Unit`.
+fn parse_callable_output(s: &mut Scanner, input: &Pat) -> Result<Ty> {
+    if token(s, TokenKind::Colon).is_ok() {
+        throw_away_doc(s);
+        return ty(s);
+    }
+    let next = s.peek();
+    if !matches!(
+        next.kind,
+        TokenKind::Open(Delim::Brace) | TokenKind::Keyword(Keyword::Is)
+    ) {
+        return Err(Error(ErrorKind::Token(
+            TokenKind::Colon,
+            next.kind,
+            next.span,
+        )));
+    }
+    let span = s.span(input.span.hi);
+    s.require(LanguageFeature::OmittedOutputTy, span);
+    Ok(Ty {
+        id: NodeId::default(),
+        span,
+        kind: Box::new(TyKind::Tuple(Box::default())),
+    })
+}
+
 fn parse_callable_body(s: &mut Scanner) -> Result<CallableBody> {
     let lo = s.peek().span.lo;
     token(s, TokenKind::Open(Delim::Brace))?;
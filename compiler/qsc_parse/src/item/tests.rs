@@ -2,8 +2,12 @@
 // Licensed under the MIT License.
 
 use super::{parse, parse_attr, parse_namespaces, parse_spec_decl};
-use crate::tests::{check, check_vec};
+use crate::{
+    namespaces,
+    tests::{check, check_vec},
+};
 use expect_test::expect;
+use qsc_data_structures::edition::Edition;
 
 #[test]
 fn body_intrinsic() {
@@ -860,16 +864,43 @@ fn function_missing_output_ty() {
     check(
         parse,
         "function Foo() { body intrinsic; }",
+        &expect![[r#"
+            Item _id_ [0-34]:
+                Callable _id_ [0-34] (Function):
+                    name: Ident _id_ [9-12] "Foo"
+                    input: Pat _id_ [12-14]: Unit
+                    output: Type _id_ [14-14]: Unit
+                    body: Specializations:
+                        SpecDecl _id_ [17-32] (Body): Gen: Intrinsic"#]],
+    );
+}
+
+#[test]
+fn function_missing_output_ty_requires_edition() {
+    let (_, errors) = namespaces(
+        "namespace A { function Foo() { body intrinsic; } }",
+        Edition::Q2023,
+    );
+    let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        ["omitting the output type of a callable requires edition 2024"]
+    );
+}
+
+#[test]
+fn function_missing_output_ty_before_lambda() {
+    check(
+        parse,
+        "function Foo() => ()",
         &expect![[r#"
             Error(
                 Token(
                     Colon,
-                    Open(
-                        Brace,
-                    ),
+                    FatArrow,
                     Span {
                         lo: 15,
-                        hi: 16,
+                        hi: 17,
                     },
                 ),
             )
@@ -978,6 +1009,19 @@ fn attr_two_args() {
     );
 }
 
+#[test]
+fn attr_setting_arg() {
+    check(
+        parse_attr,
+        "@Config(feature = \"chemistry\")",
+        &expect![[r#"
+            Attr _id_ [0-30] (Ident _id_ [1-7] "Config"):
+                Expr _id_ [7-30]: Paren: Expr _id_ [8-29]: Assign:
+                    Expr _id_ [8-15]: Path: Path _id_ [8-15] (Ident _id_ [8-15] "feature")
+                    Expr _id_ [18-29]: Lit: String("chemistry")"#]],
+    );
+}
+
 #[test]
 fn open_attr() {
     check(
@@ -1330,11 +1374,11 @@ fn recover_callable_item() {
         parse_namespaces,
         "namespace A {
             function Foo() : Int { 5 }
-            function Bar() { 10 }
+            function Bar() : { 10 }
             operation Baz() : Double { 2.0 }
         }",
         &expect![[r#"
-            Namespace _id_ [0-141] (Ident _id_ [10-11] "A"):
+            Namespace _id_ [0-143] (Ident _id_ [10-11] "A"):
                 Item _id_ [26-52]:
                     Callable _id_ [26-52] (Function):
                         name: Ident _id_ [35-38] "Foo"
@@ -1342,26 +1386,26 @@ fn recover_callable_item() {
                         output: Type _id_ [43-46]: Path: Path _id_ [43-46] (Ident _id_ [43-46] "Int")
                         body: Block: Block _id_ [47-52]:
                             Stmt _id_ [49-50]: Expr: Expr _id_ [49-50]: Lit: Int(5)
-                Item _id_ [65-86]:
+                Item _id_ [65-88]:
                     Err
-                Item _id_ [99-131]:
-                    Callable _id_ [99-131] (Operation):
-                        name: Ident _id_ [109-112] "Baz"
-                        input: Pat _id_ [112-114]: Unit
-                        output: Type _id_ [117-123]: Path: Path _id_ [117-123] (Ident _id_ [117-123] "Double")
-                        body: Block: Block _id_ [124-131]:
-                            Stmt _id_ [126-129]: Expr: Expr _id_ [126-129]: Lit: Double(2)
+                Item _id_ [101-133]:
+                    Callable _id_ [101-133] (Operation):
+                        name: Ident _id_ [111-114] "Baz"
+                        input: Pat _id_ [114-116]: Unit
+                        output: Type _id_ [119-125]: Path: Path _id_ [119-125] (Ident _id_ [119-125] "Double")
+                        body: Block: Block _id_ [126-133]:
+                            Stmt _id_ [128-131]: Expr: Expr _id_ [128-131]: Lit: Double(2)
 
             [
                 Error(
-                    Token(
-                        Colon,
+                    Rule(
+                        "type",
                         Open(
                             Brace,
                         ),
                         Span {
-                            lo: 80,
-                            hi: 81,
+                            lo: 82,
+                            hi: 83,
                         },
                     ),
                 ),
@@ -21,7 +21,7 @@ use super::{
 use crate::keyword::Keyword;
 use enum_iterator::Sequence;
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use std::{
     fmt::{self, Display, Formatter},
     iter::Peekable,
@@ -68,6 +68,21 @@ impl Error {
     }
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Incomplete(expected, token, actual, _) => {
+                vec![expected.to_string(), token.to_string(), actual.to_string()]
+            }
+            Self::IncompleteEof(expected, token, _) => {
+                vec![expected.to_string(), token.to_string()]
+            }
+            Self::UnterminatedString(_) => Vec::new(),
+            Self::Unknown(c, _) => vec![c.to_string()],
+        }
+    }
+}
+
 /// A token kind.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Sequence)]
 pub(crate) enum TokenKind {
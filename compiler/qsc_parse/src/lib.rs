@@ -19,7 +19,11 @@ mod ty;
 use lex::TokenKind;
 use miette::Diagnostic;
 use qsc_ast::ast::{Expr, Namespace, TopLevelNode};
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{
+    edition::{Edition, LanguageFeature},
+    message::MessageParams,
+    span::Span,
+};
 use scan::Scanner;
 use std::result;
 use thiserror::Error;
@@ -35,6 +39,12 @@ impl Error {
     }
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        self.0.message_params()
+    }
+}
+
 #[derive(Clone, Copy, Debug, Diagnostic, Eq, Error, PartialEq)]
 enum ErrorKind {
     #[error(transparent)]
@@ -73,6 +83,10 @@ enum ErrorKind {
     #[error("missing entry in sequence")]
     #[diagnostic(code("Qsc.Parse.MissingSeqEntry"))]
     MissingSeqEntry(#[label] Span),
+    #[error("{0} requires edition {1}")]
+    #[diagnostic(help("set `\"edition\": \"{1}\"` in qsharp.json to use it"))]
+    #[diagnostic(code("Qsc.Parse.Edition"))]
+    Edition(LanguageFeature, Edition, #[label] Span),
 }
 
 impl ErrorKind {
@@ -90,6 +104,29 @@ impl ErrorKind {
             Self::FloatingAttr(span) => Self::FloatingAttr(span + offset),
             Self::FloatingVisibility(span) => Self::FloatingVisibility(span + offset),
             Self::MissingSeqEntry(span) => Self::MissingSeqEntry(span + offset),
+            Self::Edition(feature, edition, span) => Self::Edition(feature, edition, span + offset),
+        }
+    }
+}
+
+impl MessageParams for ErrorKind {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Lex(error) => error.message_params(),
+            Self::Lit(name, _) => vec![(*name).to_string()],
+            Self::Escape(ch, _) => vec![ch.to_string()],
+            Self::Token(expected, actual, _) => vec![expected.to_string(), actual.to_string()],
+            Self::Rule(name, token, _) => vec![(*name).to_string(), token.to_string()],
+            Self::Convert(expected, actual, _) => {
+                vec![(*expected).to_string(), (*actual).to_string()]
+            }
+            Self::Edition(feature, edition, _) => vec![feature.to_string(), edition.to_string()],
+            Self::FloatingAttr(_)
+            | Self::FloatingDocComment(_)
+            | Self::FloatingVisibility(_)
+            | Self::MissingSemi(_)
+            | Self::MissingParens(_)
+            | Self::MissingSeqEntry(_) => Vec::new(),
         }
     }
 }
@@ -100,8 +137,8 @@ trait Parser<T>: FnMut(&mut Scanner) -> Result<T> {}
 
 impl<T, F: FnMut(&mut Scanner) -> Result<T>> Parser<T> for F {}
 
-pub fn namespaces(input: &str) -> (Vec<Namespace>, Vec<Error>) {
-    let mut scanner = Scanner::new(input);
+pub fn namespaces(input: &str, edition: Edition) -> (Vec<Namespace>, Vec<Error>) {
+    let mut scanner = Scanner::new(input, edition);
     match item::parse_namespaces(&mut scanner) {
         Ok(namespaces) => (namespaces, scanner.into_errors()),
         Err(error) => {
@@ -112,8 +149,8 @@ pub fn namespaces(input: &str) -> (Vec<Namespace>, Vec<Error>) {
     }
 }
 
-pub fn top_level_nodes(input: &str) -> (Vec<TopLevelNode>, Vec<Error>) {
-    let mut scanner = Scanner::new(input);
+pub fn top_level_nodes(input: &str, edition: Edition) -> (Vec<TopLevelNode>, Vec<Error>) {
+    let mut scanner = Scanner::new(input, edition);
     match item::parse_top_level_nodes(&mut scanner) {
         Ok(nodes) => (nodes, scanner.into_errors()),
         Err(error) => {
@@ -124,8 +161,8 @@ pub fn top_level_nodes(input: &str) -> (Vec<TopLevelNode>, Vec<Error>) {
     }
 }
 
-pub fn expr(input: &str) -> (Box<Expr>, Vec<Error>) {
-    let mut scanner = Scanner::new(input);
+pub fn expr(input: &str, edition: Edition) -> (Box<Expr>, Vec<Error>) {
+    let mut scanner = Scanner::new(input, edition);
     match expr::expr_eof(&mut scanner) {
         Ok(expr) => (expr, scanner.into_errors()),
         Err(error) => {
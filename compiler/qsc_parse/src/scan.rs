@@ -6,7 +6,10 @@ use crate::{
     lex::{Lexer, Token, TokenKind},
     ErrorKind,
 };
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{
+    edition::{Edition, LanguageFeature},
+    span::Span,
+};
 
 #[derive(Debug)]
 pub(super) struct NoBarrierError;
@@ -23,10 +26,11 @@ pub(super) struct Scanner<'a> {
     recovered_eof: bool,
     peek: Token,
     offset: u32,
+    edition: Edition,
 }
 
 impl<'a> Scanner<'a> {
-    pub(super) fn new(input: &'a str) -> Self {
+    pub(super) fn new(input: &'a str, edition: Edition) -> Self {
         let mut tokens = Lexer::new(input);
         let (peek, errors) = next_ok(&mut tokens);
         Self {
@@ -40,6 +44,7 @@ impl<'a> Scanner<'a> {
             recovered_eof: false,
             peek: peek.unwrap_or_else(|| eof(input.len())),
             offset: 0,
+            edition,
         }
     }
 
@@ -110,6 +115,13 @@ impl<'a> Scanner<'a> {
         }
     }
 
+    /// Reports an error at the span if the feature isn't part of the edition being parsed.
+    pub(super) fn require(&mut self, feature: LanguageFeature, span: Span) {
+        if !self.edition.supports(feature) {
+            self.push_error(Error(ErrorKind::Edition(feature, feature.edition(), span)));
+        }
+    }
+
     pub(super) fn into_errors(self) -> Vec<Error> {
         self.errors
     }
@@ -4,6 +4,7 @@
 use super::{scan::Scanner, Parser};
 use crate::prim::FinalSep;
 use expect_test::Expect;
+use qsc_data_structures::edition::Edition;
 use std::fmt::Display;
 
 pub(super) fn check<T: Display>(parser: impl Parser<T>, input: &str, expect: &Expect) {
@@ -50,7 +51,7 @@ fn check_map<T>(
     expect: &Expect,
     f: impl FnOnce(&T) -> String,
 ) {
-    let mut scanner = Scanner::new(input);
+    let mut scanner = Scanner::new(input, Edition::LATEST);
     let result = parser(&mut scanner);
     let errors = scanner.into_errors();
     match result {
@@ -5,17 +5,27 @@
 mod tests;
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags};
 use qsc_hir::{
+    global::{self, Kind},
     hir::{
-        BinOp, CallableKind, Expr, ExprKind, Item, ItemKind, Lit, Package, SpecBody, SpecGen,
-        StmtKind,
+        BinOp, CallableKind, Expr, ExprKind, Item, ItemId, ItemKind, Lit, NodeId, Package, Pat,
+        PatKind, Res, SpecBody, SpecGen, StmtKind,
     },
     ty::{Prim, Ty},
-    visit::{walk_expr, walk_item, walk_package, Visitor},
+    visit::{walk_expr, walk_item, walk_package, walk_pat, Visitor},
 };
+use rustc_hash::FxHashSet;
 use thiserror::Error;
 
+/// The callables that code generation skips, which may therefore be passed result literals, such
+/// as the expected result of an assertion.
+const RESULT_LITERAL_CALLABLES: [(&str, &str); 1] = [(
+    "Microsoft.Quantum.Diagnostics",
+    "AssertMeasurementProbability",
+)];
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum Error {
     #[error("cannot compare measurement results")]
@@ -45,13 +55,73 @@ pub enum Error {
     ))]
     #[diagnostic(code("Qsc.BaseProfCk.UnsupportedIntrinsic"))]
     UnsupportedIntrinsic(#[label] Span),
+
+    #[error("loop condition depends on measurement results")]
+    #[diagnostic(help(
+        "loops on measurement results are not supported when performing adaptive profile QIR generation, even when bounded with `@MaxIterations`"
+    ))]
+    #[diagnostic(code("Qsc.BaseProfCk.ResultLoop"))]
+    ResultLoop(#[label] Span),
+
+    #[error("values cannot flow out of a branch on measurement results")]
+    #[diagnostic(help(
+        "an `if` on measurement results can't have a value, update variables declared before it, or return when performing adaptive profile QIR generation"
+    ))]
+    #[diagnostic(code("Qsc.BaseProfCk.ResultBranchOutput"))]
+    ResultBranchOutput(#[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl Error {
+    pub(super) fn span(&self) -> Span {
+        match self {
+            Self::ResultComparison(span)
+            | Self::ResultLiteral(span)
+            | Self::ReturnNonResult(span)
+            | Self::UnsupportedIntrinsic(span)
+            | Self::ResultLoop(span)
+            | Self::ResultBranchOutput(span) => *span,
+        }
+    }
 }
 
+/// Checks that the package only uses what the tracing QIR generator supports. Targets with integer
+/// computations may also return integers, which are computed by the target from measured results.
+/// Targets with forward branching may also branch on a comparison of results, as long as no
+/// values flow out of the branch. The tracing generator can't unroll loops on measurement results,
+/// so those targets get an error of their own for them, whether or not `@MaxIterations` bounds the
+/// loop.
 #[must_use]
-pub fn check_base_profile_compliance(package: &Package) -> Vec<Error> {
-    let mut checker = Checker { errors: Vec::new() };
+pub fn check_base_profile_compliance(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<Error> {
+    check_base_profile_compliance_with(package, capabilities, &FxHashSet::default())
+}
+
+/// Checks the package like [`check_base_profile_compliance`], except that the given callables may
+/// be passed result literals. See [`result_literal_callables`].
+#[must_use]
+pub fn check_base_profile_compliance_with(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+    result_literal_callables: &FxHashSet<ItemId>,
+) -> Vec<Error> {
+    let mut checker = Checker {
+        errors: Vec::new(),
+        result_literal_callables,
+        allow_int_output: capabilities.contains(RuntimeCapabilityFlags::IntegerComputations),
+        allow_result_branches: capabilities.contains(RuntimeCapabilityFlags::ForwardBranching),
+        in_loop_cond: false,
+        branch_locals: Vec::new(),
+    };
     if let Some(entry) = &package.entry {
-        if any_non_result_ty(&entry.ty) {
+        if checker.any_unsupported_output_ty(&entry.ty) {
             checker.errors.push(Error::ReturnNonResult(entry.span));
         }
     }
@@ -60,14 +130,68 @@ pub fn check_base_profile_compliance(package: &Package) -> Vec<Error> {
     checker.errors
 }
 
-struct Checker {
+/// Finds the callables among the packages of the store that may be passed result literals.
+#[must_use]
+pub fn result_literal_callables(store: &PackageStore) -> FxHashSet<ItemId> {
+    store
+        .iter()
+        .flat_map(|(id, unit)| global::iter_package(Some(id), &unit.package))
+        .filter_map(|global| match global.kind {
+            Kind::Term(term)
+                if RESULT_LITERAL_CALLABLES
+                    .contains(&(global.namespace.as_ref(), global.name.as_ref())) =>
+            {
+                Some(term.id)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+struct Checker<'b> {
     errors: Vec<Error>,
+    result_literal_callables: &'b FxHashSet<ItemId>,
+    allow_int_output: bool,
+    allow_result_branches: bool,
+    in_loop_cond: bool,
+    /// The locals declared inside each enclosing branch on results, innermost last.
+    branch_locals: Vec<FxHashSet<NodeId>>,
+}
+
+impl Checker<'_> {
+    fn any_unsupported_output_ty(&self, ty: &Ty) -> bool {
+        match ty {
+            Ty::Array(ty) => self.any_unsupported_output_ty(ty),
+            Ty::Prim(Prim::Result) => false,
+            Ty::Prim(Prim::Int) => !self.allow_int_output,
+            Ty::Tuple(tys) if tys.is_empty() => true,
+            Ty::Tuple(tys) => tys.iter().any(|ty| self.any_unsupported_output_ty(ty)),
+            _ => true,
+        }
+    }
+
+    /// Whether the assignment target is a local declared outside the innermost branch on results.
+    fn updates_outer_local(&self, lhs: &Expr) -> bool {
+        let Some(locals) = self.branch_locals.last() else {
+            return false;
+        };
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(id), _) => !locals.contains(id),
+            ExprKind::Tuple(items) => items.iter().any(|item| self.updates_outer_local(item)),
+            _ => false,
+        }
+    }
+
+    fn takes_result_literals(&self, callee: &Expr) -> bool {
+        matches!(&callee.kind, ExprKind::Var(Res::Item(id), _)
+            if self.result_literal_callables.contains(id))
+    }
 }
 
-impl<'a> Visitor<'a> for Checker {
+impl<'a> Visitor<'a> for Checker<'_> {
     fn visit_package(&mut self, package: &'a Package) {
         if let Some(StmtKind::Expr(expr)) = &package.stmts.last().map(|stmt| &stmt.kind) {
-            if any_non_result_ty(&expr.ty) {
+            if self.any_unsupported_output_ty(&expr.ty) {
                 self.errors.push(Error::ReturnNonResult(expr.span));
             }
         }
@@ -75,6 +199,13 @@ impl<'a> Visitor<'a> for Checker {
         walk_package(self, package);
     }
 
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        if let (PatKind::Bind(ident), Some(locals)) = (&pat.kind, self.branch_locals.last_mut()) {
+            locals.insert(ident.id);
+        }
+        walk_pat(self, pat);
+    }
+
     fn visit_item(&mut self, item: &'a Item) {
         match &item.kind {
             ItemKind::Callable(callable)
@@ -95,19 +226,102 @@ impl<'a> Visitor<'a> for Checker {
 
     fn visit_expr(&mut self, expr: &'a Expr) {
         match &expr.kind {
+            // The loop is reported once, rather than each comparison in its condition.
+            ExprKind::While(cond, block)
+                if self.allow_result_branches && depends_on_results(cond) =>
+            {
+                self.errors.push(Error::ResultLoop(cond.span));
+                let outer_cond = self.in_loop_cond;
+                self.in_loop_cond = true;
+                self.visit_expr(cond);
+                self.in_loop_cond = outer_cond;
+                self.visit_block(block);
+                return;
+            }
+            _ if self.in_loop_cond => {}
+            ExprKind::If(cond, then, otherwise)
+                if self.allow_result_branches && is_result_comparison(cond) =>
+            {
+                if expr.ty != Ty::UNIT {
+                    self.errors.push(Error::ResultBranchOutput(expr.span));
+                }
+                // The results are compared by the target, so either may be a literal.
+                if let ExprKind::BinOp(_, lhs, rhs) = &cond.kind {
+                    for operand in [lhs, rhs] {
+                        if !matches!(operand.kind, ExprKind::Lit(Lit::Result(_))) {
+                            self.visit_expr(operand);
+                        }
+                    }
+                }
+                self.branch_locals.push(FxHashSet::default());
+                self.visit_expr(then);
+                if let Some(otherwise) = otherwise {
+                    self.visit_expr(otherwise);
+                }
+                self.branch_locals.pop();
+                return;
+            }
+            ExprKind::Assign(lhs, _)
+            | ExprKind::AssignOp(_, lhs, _)
+            | ExprKind::AssignField(lhs, ..)
+            | ExprKind::AssignIndex(lhs, ..)
+                if self.updates_outer_local(lhs) =>
+            {
+                self.errors.push(Error::ResultBranchOutput(expr.span));
+            }
+            ExprKind::Return(_) if !self.branch_locals.is_empty() => {
+                self.errors.push(Error::ResultBranchOutput(expr.span));
+            }
             ExprKind::BinOp(BinOp::Eq | BinOp::Neq, lhs, _) if any_result_ty(&lhs.ty) => {
                 self.errors.push(Error::ResultComparison(expr.span));
             }
             ExprKind::Lit(Lit::Result(_)) => {
                 self.errors.push(Error::ResultLiteral(expr.span));
             }
+            // A result literal passed straight to a callable that code generation skips can't reach
+            // the program's output, which lets assertions such as `AssertMeasurementProbability`
+            // take the expected result as a literal.
+            ExprKind::Call(callee, arg) if self.takes_result_literals(callee) => {
+                self.visit_expr(callee);
+                match &arg.kind {
+                    ExprKind::Lit(Lit::Result(_)) => {}
+                    ExprKind::Tuple(items) => items
+                        .iter()
+                        .filter(|item| !matches!(item.kind, ExprKind::Lit(Lit::Result(_))))
+                        .for_each(|item| self.visit_expr(item)),
+                    _ => self.visit_expr(arg),
+                }
+                return;
+            }
             _ => {}
         }
         walk_expr(self, expr);
     }
 }
 
-fn any_result_ty(ty: &Ty) -> bool {
+/// Whether the condition compares two results, which the target can branch on.
+fn is_result_comparison(cond: &Expr) -> bool {
+    matches!(&cond.kind, ExprKind::BinOp(BinOp::Eq | BinOp::Neq, lhs, _)
+        if lhs.ty == Ty::Prim(Prim::Result))
+}
+
+/// Whether any part of the expression is a measurement result.
+fn depends_on_results(expr: &Expr) -> bool {
+    struct Results(bool);
+
+    impl<'a> Visitor<'a> for Results {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            self.0 |= any_result_ty(&expr.ty);
+            walk_expr(self, expr);
+        }
+    }
+
+    let mut results = Results(false);
+    results.visit_expr(expr);
+    results.0
+}
+
+pub(super) fn any_result_ty(ty: &Ty) -> bool {
     match ty {
         Ty::Array(ty) => any_result_ty(ty),
         Ty::Prim(Prim::Result) => true,
@@ -115,13 +329,3 @@ fn any_result_ty(ty: &Ty) -> bool {
         _ => false,
     }
 }
-
-fn any_non_result_ty(ty: &Ty) -> bool {
-    match ty {
-        Ty::Array(ty) => any_non_result_ty(ty),
-        Ty::Prim(Prim::Result) => false,
-        Ty::Tuple(tys) if tys.is_empty() => true,
-        Ty::Tuple(tys) => tys.iter().any(any_non_result_ty),
-        _ => true,
-    }
-}
@@ -5,18 +5,28 @@
 
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
 
-use crate::baseprofck::check_base_profile_compliance;
+use crate::baseprofck::{check_base_profile_compliance_with, result_literal_callables};
 
 fn check(expr: &str, expect: &Expect) {
+    check_with_capabilities(expr, RuntimeCapabilityFlags::empty(), expect);
+}
+
+fn check_with_capabilities(expr: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
     let mut store = PackageStore::new(compile::core());
     let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
     let sources = SourceMap::new([("test".into(), "".into())], Some(expr.into()));
     let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
     assert!(unit.errors.is_empty(), "{:?}", unit.errors);
 
-    let errors = check_base_profile_compliance(&unit.package);
+    let errors = check_base_profile_compliance_with(
+        &unit.package,
+        capabilities,
+        &result_literal_callables(&store),
+    );
     expect.assert_debug_eq(&errors);
 }
 
@@ -105,6 +115,42 @@ fn result_literal_error() {
     );
 }
 
+#[test]
+fn result_literal_argument_to_assertion_is_valid() {
+    check(
+        indoc! {r#"{
+            use q = Qubit();
+            Microsoft.Quantum.Diagnostics.AssertMeasurementProbability([PauliZ], [q], Zero, 1.0, 1e-5, "zero");
+            M(q)
+        }"#},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn result_literal_argument_to_unit_callable_error() {
+    check(
+        indoc! {"{
+            operation Expect(r : Result) : Unit {}
+            use q = Qubit();
+            Expect(One);
+            M(q)
+        }"},
+        &expect![[r#"
+            [
+                ResultLiteral(
+                    Span {
+                        lo: 77,
+                        hi: 80,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn non_result_return_error() {
     check(
@@ -127,6 +173,44 @@ fn non_result_return_error() {
     );
 }
 
+#[test]
+fn int_return_with_integer_computations_is_valid() {
+    check_with_capabilities(
+        indoc! {"{
+            use q = Qubit();
+            H(q);
+            (M(q), 3 + 1)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn double_return_with_integer_computations_error() {
+    check_with_capabilities(
+        indoc! {"{
+            use q = Qubit();
+            H(q);
+            M(q);
+            1.0
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ReturnNonResult(
+                    Span {
+                        lo: 0,
+                        hi: 52,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn unit_return_error() {
     check(
@@ -173,3 +257,153 @@ fn unsupported_intrsinsic_error() {
         "#]],
     );
 }
+
+#[test]
+fn bounded_loop_on_results_with_forward_branching_error() {
+    check_with_capabilities(
+        indoc! {"{
+            @MaxIterations(10)
+            operation Loop(q : Qubit) : Unit {
+                while M(q) != One {
+                    H(q);
+                }
+            }
+            use q = Qubit();
+            Loop(q);
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ResultLoop(
+                    Span {
+                        lo: 78,
+                        hi: 89,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn unbounded_loop_on_results_with_forward_branching_error() {
+    check_with_capabilities(
+        indoc! {"{
+            operation Loop(q : Qubit) : Unit {
+                while M(q) != One {
+                    H(q);
+                }
+            }
+            use q = Qubit();
+            Loop(q);
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ResultLoop(
+                    Span {
+                        lo: 55,
+                        hi: 66,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_comparison_with_forward_branching_is_valid() {
+    check_with_capabilities(
+        indoc! {"{
+            use q = Qubit();
+            if M(q) == One {
+                X(q);
+            } elif One != M(q) {
+                mutable flips = 0;
+                set flips += 1;
+            }
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_with_value_error() {
+    check_with_capabilities(
+        indoc! {"{
+            use q = Qubit();
+            let flipped = if M(q) == One { X(q); true } else { false };
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ResultBranchOutput(
+                    Span {
+                        lo: 41,
+                        hi: 85,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_updating_outer_variable_error() {
+    check_with_capabilities(
+        indoc! {"{
+            use q = Qubit();
+            mutable count = 0;
+            if M(q) == One {
+                set count += 1;
+            }
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ResultBranchOutput(
+                    Span {
+                        lo: 75,
+                        hi: 89,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_returning_error() {
+    check_with_capabilities(
+        indoc! {"{
+            operation Flip(q : Qubit) : Unit {
+                if M(q) == One {
+                    return ();
+                }
+                X(q);
+            }
+            use q = Qubit();
+            Flip(q);
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            [
+                ResultBranchOutput(
+                    Span {
+                        lo: 78,
+                        hi: 87,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
@@ -5,7 +5,7 @@
 mod tests;
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     hir::{Expr, ExprKind, Mutability, NodeId, Pat, PatKind, Res, Stmt, StmtKind},
     visit::{walk_expr, walk_stmt, Visitor},
@@ -32,6 +32,12 @@ pub enum Error {
     Unassignable(#[label("not assignable")] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
 #[derive(Default)]
 pub(super) struct Checker {
     mutable: FxHashSet<NodeId>,
@@ -5,7 +5,7 @@
 mod tests;
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     hir::{CallableDecl, CallableKind, Expr, ExprKind, Package, Stmt, StmtKind},
     ty::{FunctorSetValue, Ty},
@@ -40,6 +40,12 @@ pub enum Error {
     Spec(#[label] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
 #[derive(Default)]
 pub(super) struct CallableLimits {
     pub(super) errors: Vec<Error>,
@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Explains why a package needs more runtime capabilities than its target has, so that tools can
+//! tell users which capabilities, or which profile, the package could target instead.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    baseprofck, checks_capabilities, compute_properties::analyze_compute_properties, recursion,
+};
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::{ConfigAttr, RuntimeCapabilityFlags};
+use qsc_hir::hir::Package;
+use std::mem::discriminant;
+
+/// The runtime capabilities that a package needs beyond those of its target.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapabilityDelta {
+    /// The capabilities that the package needs and the target doesn't have.
+    pub missing: RuntimeCapabilityFlags,
+    /// Each missing capability, one flag at a time, with the source locations that need it.
+    pub sources: Vec<(RuntimeCapabilityFlags, Vec<Span>)>,
+    /// The least capable profile that accepts the package.
+    pub minimum_profile: ConfigAttr,
+}
+
+impl CapabilityDelta {
+    /// Whether the target has every capability the package needs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Computes the capabilities that the package needs beyond the given ones, from the same checks
+/// that [`crate::run_default_passes`] reports errors for.
+#[must_use]
+pub fn capability_delta(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> CapabilityDelta {
+    let uses = capability_uses(package, capabilities);
+    let missing = union(&uses);
+    let sources = missing
+        .iter()
+        .map(|flag| {
+            let spans = uses
+                .iter()
+                .filter(|(capability, _)| *capability == flag)
+                .map(|(_, span)| *span)
+                .collect();
+            (flag, spans)
+        })
+        .collect();
+
+    // The capabilities the package needs at all are the ones it is missing on the base profile.
+    let required = if capabilities.is_empty() {
+        missing
+    } else {
+        union(&capability_uses(package, RuntimeCapabilityFlags::empty()))
+    };
+    let minimum_profile = [ConfigAttr::Base, ConfigAttr::Adaptive]
+        .into_iter()
+        .find(|&profile| RuntimeCapabilityFlags::from(profile).contains(required))
+        .unwrap_or(ConfigAttr::Unrestricted);
+
+    CapabilityDelta {
+        missing,
+        sources,
+        minimum_profile,
+    }
+}
+
+/// The runtime capabilities that the package needs, from the compute properties of its
+/// expressions and the recursion among its callables. The least capable profile that has them is
+/// [`RuntimeCapabilityFlags::minimal_preset`] of the result.
+#[must_use]
+pub fn required_capabilities(package: &Package) -> RuntimeCapabilityFlags {
+    let mut required = analyze_compute_properties(package).runtime_features();
+    if !recursion::check_recursion(package, recursion::DEFAULT_ITERATION_LIMIT).is_empty() {
+        required |= RuntimeCapabilityFlags::Recursion;
+    }
+    required
+}
+
+/// Each source location that the target doesn't support, with the capability it needs.
+fn capability_uses(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<(RuntimeCapabilityFlags, Span)> {
+    let mut uses = Vec::new();
+    if !capabilities.contains(RuntimeCapabilityFlags::Recursion) {
+        uses.extend(
+            recursion::check_recursion(package, recursion::DEFAULT_ITERATION_LIMIT)
+                .into_iter()
+                .map(|recursion::Error::Cycle(_, _, span)| {
+                    (RuntimeCapabilityFlags::Recursion, span)
+                }),
+        );
+    }
+    if !checks_capabilities(capabilities) {
+        return uses;
+    }
+
+    // What the adaptive profile still rejects needs more than its capabilities, and what it
+    // accepts needs only the capabilities it adds to the base profile.
+    let errors = baseprofck::check_base_profile_compliance(package, capabilities);
+    let adaptive = ConfigAttr::Adaptive.into();
+    let adaptive_errors = if capabilities == adaptive {
+        errors.clone()
+    } else {
+        baseprofck::check_base_profile_compliance(package, adaptive)
+    };
+    for error in &errors {
+        let capability = if adaptive_errors.iter().any(|other| same(error, other)) {
+            beyond_adaptive(error)
+        } else {
+            within_adaptive(error)
+        };
+        uses.push((capability, error.span()));
+    }
+    // Loops on results are only reported for targets that can otherwise branch on results.
+    uses.extend(
+        adaptive_errors
+            .iter()
+            .filter(|error| !errors.iter().any(|other| same(error, other)))
+            .map(|error| (beyond_adaptive(error), error.span())),
+    );
+    uses.sort_by_key(|(_, span)| span.lo);
+    uses
+}
+
+fn same(error: &baseprofck::Error, other: &baseprofck::Error) -> bool {
+    discriminant(error) == discriminant(other) && error.span() == other.span()
+}
+
+/// The capability that the adaptive profile adds to the base profile that the error needs.
+fn within_adaptive(error: &baseprofck::Error) -> RuntimeCapabilityFlags {
+    match error {
+        baseprofck::Error::ResultComparison(_) | baseprofck::Error::ResultLiteral(_) => {
+            RuntimeCapabilityFlags::ForwardBranching
+        }
+        baseprofck::Error::ReturnNonResult(_) => RuntimeCapabilityFlags::IntegerComputations,
+        _ => beyond_adaptive(error),
+    }
+}
+
+/// The capability beyond the adaptive profile that the error needs.
+fn beyond_adaptive(error: &baseprofck::Error) -> RuntimeCapabilityFlags {
+    match error {
+        baseprofck::Error::ResultLoop(_) => RuntimeCapabilityFlags::BackwardsBranching,
+        _ => RuntimeCapabilityFlags::HigherLevelConstructs,
+    }
+}
+
+fn union(uses: &[(RuntimeCapabilityFlags, Span)]) -> RuntimeCapabilityFlags {
+    uses.iter()
+        .fold(RuntimeCapabilityFlags::empty(), |flags, (capability, _)| {
+            flags | *capability
+        })
+}
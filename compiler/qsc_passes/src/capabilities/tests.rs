@@ -0,0 +1,222 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::hir::Package;
+use std::fmt::Write;
+
+use super::{capability_delta, required_capabilities};
+
+fn compile_expr(expr: &str) -> Package {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), "".into())], Some(expr.into()));
+    let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    unit.package
+}
+
+fn check(expr: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
+    let delta = capability_delta(&compile_expr(expr), capabilities);
+    assert_eq!(delta.is_empty(), delta.sources.is_empty());
+    let mut actual = format!("minimum profile: {}\n", delta.minimum_profile.to_str());
+    for (flag, spans) in &delta.sources {
+        let (name, _) = flag.iter_names().next().expect("flag should have a name");
+        for span in spans {
+            // The entry expression is the first source, so its spans index into it directly.
+            let _ = writeln!(
+                actual,
+                "{name}: {}",
+                &expr[span.lo as usize..span.hi as usize]
+            );
+        }
+    }
+    expect.assert_eq(&actual);
+}
+
+fn check_required(expr: &str, expect: &Expect) {
+    let required = required_capabilities(&compile_expr(expr));
+    let (preset, _) = required.minimal_preset();
+    let names = required
+        .iter_names()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    expect.assert_eq(&format!("{preset} {names:?}"));
+}
+
+#[test]
+fn supported_program_has_no_delta() {
+    check(
+        indoc! {"{
+            use q = Qubit();
+            H(q);
+            M(q)
+        }"},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Base
+        "#]],
+    );
+}
+
+#[test]
+fn integer_output_needs_adaptive() {
+    check(
+        "{ use q = Qubit(); 1 }",
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Adaptive
+            IntegerComputations: { use q = Qubit(); 1 }
+        "#]],
+    );
+}
+
+#[test]
+fn result_comparison_outside_branch_needs_higher_level_constructs() {
+    check(
+        indoc! {"{
+            use q = Qubit();
+            let isOne = M(q) == One;
+            M(q)
+        }"},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Unrestricted
+            HigherLevelConstructs: M(q) == One
+            HigherLevelConstructs: One
+        "#]],
+    );
+}
+
+#[test]
+fn branch_on_result_comparison_needs_forward_branching() {
+    check(
+        indoc! {"{
+            use q = Qubit();
+            if M(q) == One {
+                X(q);
+            }
+            M(q)
+        }"},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Adaptive
+            ForwardBranching: M(q) == One
+            ForwardBranching: One
+        "#]],
+    );
+}
+
+#[test]
+fn bounded_loop_on_results_needs_backwards_branching() {
+    check(
+        indoc! {"{
+            @MaxIterations(10)
+            operation Loop(q : Qubit) : Unit {
+                while M(q) != One {
+                    H(q);
+                }
+            }
+            use q = Qubit();
+            Loop(q);
+            M(q)
+        }"},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Unrestricted
+            ForwardBranching: M(q) != One
+            ForwardBranching: One
+            BackwardsBranching: M(q) != One
+        "#]],
+    );
+}
+
+#[test]
+fn unbounded_loop_on_results_needs_backwards_branching() {
+    check(
+        indoc! {"{
+            operation Loop(q : Qubit) : Unit {
+                while M(q) != One {
+                    H(q);
+                }
+            }
+            use q = Qubit();
+            Loop(q);
+            M(q)
+        }"},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            minimum profile: Unrestricted
+            ForwardBranching: M(q) != One
+            ForwardBranching: One
+            BackwardsBranching: M(q) != One
+        "#]],
+    );
+}
+
+#[test]
+fn recursion_needs_unrestricted() {
+    check(
+        indoc! {"{
+            operation Remeasure(q : Qubit, r : Result) : Unit {
+                Remeasure(q, M(q));
+            }
+            use q = Qubit();
+            Remeasure(q, M(q));
+            M(q)
+        }"},
+        ConfigAttr::Adaptive.into(),
+        &expect![[r#"
+            minimum profile: Unrestricted
+            Recursion: Remeasure
+        "#]],
+    );
+}
+
+#[test]
+fn unrestricted_target_reports_minimum_profile() {
+    check(
+        "{ use q = Qubit(); 1 }",
+        RuntimeCapabilityFlags::all(),
+        &expect![[r#"
+            minimum profile: Adaptive
+        "#]],
+    );
+}
+
+#[test]
+fn required_capabilities_name_the_minimal_preset() {
+    check_required("{ use q = Qubit(); H(q); M(q) }", &expect![[r#"Base []"#]]);
+    check_required(
+        indoc! {"{
+            use q = Qubit();
+            let r = M(q);
+            if r == One {
+                X(q);
+            }
+            r
+        }"},
+        &expect![[r#"Adaptive_RI ["ForwardBranching"]"#]],
+    );
+    check_required(
+        "{ use q = Qubit(); M(q) == One ? 1.0 | 0.0 }",
+        &expect![[r#"Adaptive_RIF ["ForwardBranching", "FloatingPointComputations"]"#]],
+    );
+    check_required(
+        indoc! {"{
+            operation Remeasure(q : Qubit, r : Result) : Unit {
+                Remeasure(q, M(q));
+            }
+            use q = Qubit();
+            Remeasure(q, M(q));
+            M(q)
+        }"},
+        &expect![[r#"Unrestricted ["HigherLevelConstructs", "Recursion"]"#]],
+    );
+}
@@ -0,0 +1,946 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The compute properties of each expression of a package: whether its value is dynamic, which is
+//! when it depends on measurement results and so is only known when the program runs, and the
+//! runtime capabilities that evaluating it needs. Tools such as the language service use these to
+//! explain which parts of a program a target can't run, one expression at a time, and
+//! [`check_compute_properties`] reports each such part as a diagnostic.
+
+#[cfg(test)]
+mod tests;
+
+use crate::baseprofck::any_result_ty;
+use miette::Diagnostic;
+use qsc_data_structures::{index_map::IndexMap, message::MessageParams, span::Span};
+use qsc_frontend::compile::{ConfigAttr, RuntimeCapabilityFlags};
+use qsc_hir::{
+    hir::{
+        BinOp, Block, CallableDecl, CallableKind, Expr, ExprKind, Item, ItemKind, LocalItemId,
+        NodeId, Package, Pat, PatKind, Res, Stmt, StmtKind,
+    },
+    ty::{Prim, Ty},
+    visit::{walk_callable_decl, walk_expr, walk_item, walk_pat, walk_stmt, Visitor},
+};
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("dynamic Int used under {0}")]
+    #[diagnostic(help(
+        "computing integers from measurement results needs a target with integer computations"
+    ))]
+    #[diagnostic(code("Qsc.ComputeProperties.DynamicInt"))]
+    Int(
+        String,
+        #[label("computed when the program runs")] Span,
+        #[label("derives from this measurement result")] Option<Span>,
+    ),
+
+    #[error("dynamic Double used under {0}")]
+    #[diagnostic(help(
+        "computing doubles from measurement results needs a target with floating-point computations"
+    ))]
+    #[diagnostic(code("Qsc.ComputeProperties.DynamicDouble"))]
+    Double(
+        String,
+        #[label("computed when the program runs")] Span,
+        #[label("derives from this measurement result")] Option<Span>,
+    ),
+
+    #[error("dynamic value of a type other than Result, Bool, Int or Double used under {0}")]
+    #[diagnostic(help(
+        "computing such values from measurement results needs a target with higher-level constructs"
+    ))]
+    #[diagnostic(code("Qsc.ComputeProperties.DynamicValue"))]
+    Value(
+        String,
+        #[label("computed when the program runs")] Span,
+        #[label("derives from this measurement result")] Option<Span>,
+    ),
+
+    #[error("branch on a measurement result used under {0}")]
+    #[diagnostic(help(
+        "choosing what to run from measurement results needs a target with forward branching"
+    ))]
+    #[diagnostic(code("Qsc.ComputeProperties.DynamicBranch"))]
+    Branch(
+        String,
+        #[label("decided when the program runs")] Span,
+        #[label("derives from this measurement result")] Option<Span>,
+    ),
+
+    #[error("loop on a measurement result used under {0}")]
+    #[diagnostic(help(
+        "looping until a measurement result changes needs a target with backwards branching"
+    ))]
+    #[diagnostic(code("Qsc.ComputeProperties.DynamicLoop"))]
+    Loop(
+        String,
+        #[label("repeated until the program decides to stop")] Span,
+        #[label("derives from this measurement result")] Option<Span>,
+    ),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Int(capability, ..)
+            | Self::Double(capability, ..)
+            | Self::Value(capability, ..)
+            | Self::Branch(capability, ..)
+            | Self::Loop(capability, ..) => vec![capability.clone()],
+        }
+    }
+}
+
+/// Where a dynamic value comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DynamismSource {
+    /// The result of a call to an operation that returns measurement results.
+    Measurement(Span),
+    /// A parameter of a callable that takes measurement results.
+    Parameter(Span),
+}
+
+impl DynamismSource {
+    /// The span of the call or parameter.
+    #[must_use]
+    pub fn span(self) -> Span {
+        match self {
+            Self::Measurement(span) | Self::Parameter(span) => span,
+        }
+    }
+}
+
+/// The compute properties of an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputeProperties {
+    runtime_features: RuntimeCapabilityFlags,
+    /// The features that the expression needs and none of the expressions inside it do, which are
+    /// the ones that diagnostics point at it for.
+    introduced_features: RuntimeCapabilityFlags,
+    dynamism_sources: Vec<DynamismSource>,
+}
+
+/// The properties of expressions that the analysis didn't see, which are classical.
+static CLASSICAL: ComputeProperties = ComputeProperties {
+    runtime_features: RuntimeCapabilityFlags::empty(),
+    introduced_features: RuntimeCapabilityFlags::empty(),
+    dynamism_sources: Vec::new(),
+};
+
+impl ComputeProperties {
+    /// Whether the value of the expression depends on measurement results.
+    #[must_use]
+    pub fn is_dynamic(&self) -> bool {
+        !self.dynamism_sources.is_empty()
+    }
+
+    /// The runtime capabilities that evaluating the expression needs, including those of the
+    /// expressions inside it.
+    #[must_use]
+    pub fn runtime_features(&self) -> RuntimeCapabilityFlags {
+        self.runtime_features
+    }
+
+    /// The sources that the value of the expression depends on, in source order.
+    #[must_use]
+    pub fn dynamism_sources(&self) -> &[DynamismSource] {
+        &self.dynamism_sources
+    }
+}
+
+/// How a dynamic value got from its source to an expression: the variables that it went through,
+/// from the one the expression uses back to the one the source was first assigned to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamismChain {
+    pub source: DynamismSource,
+    /// The names of the variables, with the spans of their bindings.
+    pub variables: Vec<(Arc<str>, Span)>,
+}
+
+impl Display for DynamismChain {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (name, _) in &self.variables {
+            write!(f, "`{name}` <- ")?;
+        }
+        let kind = match self.source {
+            DynamismSource::Measurement(_) => "measurement",
+            DynamismSource::Parameter(_) => "parameter",
+        };
+        let span = self.source.span();
+        write!(f, "{kind} at {}-{}", span.lo, span.hi)
+    }
+}
+
+/// The compute properties of the expressions of a package.
+#[derive(Debug, Default)]
+pub struct PackageComputeProperties {
+    exprs: IndexMap<NodeId, ComputeProperties>,
+    locals: IndexMap<NodeId, Vec<DynamismSource>>,
+    flows: IndexMap<NodeId, Vec<Flow>>,
+}
+
+impl PackageComputeProperties {
+    /// The compute properties of the expression. Expressions that aren't in the package are
+    /// classical.
+    #[must_use]
+    pub fn expr_properties(&self, id: NodeId) -> &ComputeProperties {
+        self.exprs.get(id).unwrap_or(&CLASSICAL)
+    }
+
+    /// Explains why the expression of the package is dynamic, with a chain for each source of its
+    /// value that follows the variables the dynamism propagated through back to the source.
+    #[must_use]
+    pub fn explain_dynamism(&self, package: &Package, id: NodeId) -> Vec<DynamismChain> {
+        let mut index = Index::default();
+        index.visit_package(package);
+        let Some(expr) = index.exprs.get(&id) else {
+            return Vec::new();
+        };
+        self.expr_properties(id)
+            .dynamism_sources
+            .iter()
+            .map(|&source| DynamismChain {
+                source,
+                variables: self.chain(&index, expr, source),
+            })
+            .collect()
+    }
+
+    fn chain(&self, index: &Index, expr: &Expr, source: DynamismSource) -> Vec<(Arc<str>, Span)> {
+        let mut variables = Vec::new();
+        let mut visited = FxHashSet::default();
+        let mut next = self.carrier(expr, source);
+        while let Some(local) = next {
+            if !visited.insert(local) {
+                break;
+            }
+            variables.extend(index.names.get(&local).cloned());
+            let flow = self
+                .flows
+                .get(local)
+                .into_iter()
+                .flatten()
+                .find(|flow| self.carries(**flow, source));
+            next = match flow {
+                Some(Flow::Value(id) | Flow::Condition(id)) => index
+                    .exprs
+                    .get(id)
+                    .and_then(|expr| self.carrier(expr, source)),
+                Some(Flow::Capture(captured)) => Some(*captured),
+                None => None,
+            };
+        }
+        variables
+    }
+
+    fn carries(&self, flow: Flow, source: DynamismSource) -> bool {
+        match flow {
+            Flow::Value(id) | Flow::Condition(id) => {
+                self.expr_properties(id).dynamism_sources.contains(&source)
+            }
+            Flow::Capture(local) => self.local_has(local, source),
+        }
+    }
+
+    /// The variable in the expression that the source reaches it through, if the source isn't a
+    /// measurement in the expression itself.
+    fn carrier(&self, expr: &Expr, source: DynamismSource) -> Option<NodeId> {
+        if let DynamismSource::Measurement(span) = source {
+            if expr.span.lo <= span.lo && span.hi <= expr.span.hi {
+                return None;
+            }
+        }
+        let mut carrier = Carrier {
+            properties: self,
+            source,
+            found: None,
+        };
+        carrier.visit_expr(expr);
+        carrier.found
+    }
+
+    fn local_has(&self, local: NodeId, source: DynamismSource) -> bool {
+        self.locals
+            .get(local)
+            .is_some_and(|sources| sources.contains(&source))
+    }
+
+    /// The runtime capabilities that evaluating any of the expressions needs.
+    #[must_use]
+    pub fn runtime_features(&self) -> RuntimeCapabilityFlags {
+        self.exprs
+            .values()
+            .fold(RuntimeCapabilityFlags::empty(), |features, props| {
+                features | props.runtime_features
+            })
+    }
+}
+
+/// Analyzes the compute properties of the expressions of a package. Calls into other packages are
+/// not followed: a call is dynamic if its arguments are, or if it is a call to an operation that
+/// returns measurement results.
+#[must_use]
+pub fn analyze_compute_properties(package: &Package) -> PackageComputeProperties {
+    analyze_with_dynamic_params(package, &FxHashSet::default())
+}
+
+/// Analyzes the compute properties of the expressions of a package as if the given callables were
+/// called with dynamic arguments, so that all of their parameters are dynamic.
+pub(crate) fn analyze_with_dynamic_params(
+    package: &Package,
+    callables: &FxHashSet<LocalItemId>,
+) -> PackageComputeProperties {
+    let mut properties = PackageComputeProperties::default();
+    let mut captures = IndexMap::new();
+    let mut capture_args = IndexMap::new();
+    for level in levels(package) {
+        // The components of a level don't depend on each other, so they are analyzed on the
+        // thread pool. Their results are merged in the order of the components.
+        let analyzers: Vec<_> = level
+            .par_iter()
+            .map(|component| {
+                let mut analyzer = Analyzer::new(callables);
+                for &unit in component {
+                    if let Unit::Item(id) = unit {
+                        if let Some(sources) = captures.get(id) {
+                            analyzer.captures.insert(id, Vec::clone(sources));
+                        }
+                        if let Some(args) = capture_args.get(id) {
+                            analyzer.capture_args.insert(id, Vec::clone(args));
+                        }
+                    }
+                }
+                analyzer.visit_component(package, component);
+                analyzer
+            })
+            .collect();
+        for analyzer in analyzers {
+            properties.exprs.extend(analyzer.exprs);
+            properties.locals.extend(analyzer.locals);
+            properties.flows.extend(analyzer.flows);
+            captures.extend(analyzer.captures);
+            capture_args.extend(analyzer.capture_args);
+        }
+    }
+    properties
+}
+
+/// A part of a package that is analyzed on its own: a callable or another item, or the top-level
+/// statements and entry expression.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Unit {
+    Item(LocalItemId),
+    TopLevel,
+}
+
+/// Groups the units of the package into the components of their dependency graph, and the
+/// components into levels, where each component only depends on those in earlier levels. A unit
+/// depends on the units with the closures that were lifted to it, since it binds what they
+/// capture. Each unit is then visited until its variables settle without revisiting the rest of
+/// the package, and the order only depends on the order of the items, so the results are the same
+/// on every run.
+fn levels(package: &Package) -> Vec<Vec<Vec<Unit>>> {
+    let mut units: Vec<_> = package.items.iter().map(|(id, _)| Unit::Item(id)).collect();
+    units.push(Unit::TopLevel);
+
+    let mut dependents: FxHashMap<Unit, Vec<Unit>> = FxHashMap::default();
+    let mut dependencies: FxHashMap<Unit, usize> = FxHashMap::default();
+    for &unit in &units {
+        let mut closures = Closures::default();
+        match unit {
+            Unit::Item(id) => {
+                closures.visit_item(package.items.get(id).expect("item should exist"));
+            }
+            Unit::TopLevel => {
+                package
+                    .stmts
+                    .iter()
+                    .for_each(|stmt| closures.visit_stmt(stmt));
+                package
+                    .entry
+                    .iter()
+                    .for_each(|expr| closures.visit_expr(expr));
+            }
+        }
+        for item in closures.items {
+            let lifted = Unit::Item(item);
+            if lifted != unit {
+                dependents.entry(unit).or_default().push(lifted);
+                *dependencies.entry(lifted).or_default() += 1;
+            }
+        }
+    }
+
+    let mut ready: Vec<_> = units
+        .iter()
+        .copied()
+        .filter(|unit| !dependencies.contains_key(unit))
+        .collect();
+    let mut levels = Vec::new();
+    while !ready.is_empty() {
+        let mut next = Vec::new();
+        for unit in &ready {
+            for dependent in dependents.remove(unit).unwrap_or_default() {
+                let count = dependencies
+                    .get_mut(&dependent)
+                    .expect("dependent should be counted");
+                *count -= 1;
+                if *count == 0 {
+                    dependencies.remove(&dependent);
+                    next.push(dependent);
+                }
+            }
+        }
+        levels.push(ready.into_iter().map(|unit| vec![unit]).collect());
+        ready = next;
+    }
+
+    // Units in dependency cycles are analyzed together, after everything else.
+    let cyclic: Vec<_> = units
+        .into_iter()
+        .filter(|unit| dependencies.contains_key(unit))
+        .collect();
+    if !cyclic.is_empty() {
+        levels.push(vec![cyclic]);
+    }
+    levels
+}
+
+/// The items that closures were lifted to.
+#[derive(Default)]
+struct Closures {
+    items: Vec<LocalItemId>,
+}
+
+impl<'a> Visitor<'a> for Closures {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Closure(_, item) = &expr.kind {
+            if !self.items.contains(item) {
+                self.items.push(*item);
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Where the value of a variable came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Flow {
+    /// The expression that was bound or assigned to it.
+    Value(NodeId),
+    /// The condition of a branch or loop that it was assigned in.
+    Condition(NodeId),
+    /// The variable that a closure captured, for a parameter of the callable it was lifted to.
+    Capture(NodeId),
+}
+
+/// The expressions of a package by their IDs, and the names of its variables.
+#[derive(Default)]
+struct Index<'a> {
+    exprs: FxHashMap<NodeId, &'a Expr>,
+    names: FxHashMap<NodeId, (Arc<str>, Span)>,
+}
+
+impl<'a> Visitor<'a> for Index<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.exprs.insert(expr.id, expr);
+        walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        if let PatKind::Bind(ident) = &pat.kind {
+            self.names
+                .insert(ident.id, (Arc::clone(&ident.name), ident.span));
+        }
+        walk_pat(self, pat);
+    }
+}
+
+/// Finds the first variable in an expression whose value has the source.
+struct Carrier<'a> {
+    properties: &'a PackageComputeProperties,
+    source: DynamismSource,
+    found: Option<NodeId>,
+}
+
+impl<'a> Visitor<'a> for Carrier<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if self.found.is_some() {
+            return;
+        }
+        let locals = match &expr.kind {
+            ExprKind::Var(Res::Local(id), _) => std::slice::from_ref(id),
+            ExprKind::Closure(captures, _) => captures.as_slice(),
+            _ => &[],
+        };
+        self.found = locals
+            .iter()
+            .copied()
+            .find(|&local| self.properties.local_has(local, self.source));
+        walk_expr(self, expr);
+    }
+}
+
+/// Reports each expression of the package that needs a runtime capability the target doesn't
+/// have, with the measurement result that it derives from.
+#[must_use]
+pub fn check_compute_properties(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<Error> {
+    let target = [ConfigAttr::Base, ConfigAttr::Adaptive]
+        .into_iter()
+        .find(|&profile| RuntimeCapabilityFlags::from(profile) == capabilities)
+        .map_or_else(
+            || "the target capabilities".to_string(),
+            |profile| format!("{} profile", profile.to_str()),
+        );
+    let mut checker = Checker {
+        properties: analyze_compute_properties(package),
+        capabilities,
+        target,
+        errors: Vec::new(),
+    };
+    checker.visit_package(package);
+    checker.errors
+}
+
+struct Checker {
+    properties: PackageComputeProperties,
+    capabilities: RuntimeCapabilityFlags,
+    target: String,
+    errors: Vec<Error>,
+}
+
+impl Checker {
+    /// The measurement result that the expression derives from. Branches and loops derive from
+    /// their conditions.
+    fn origin(&self, expr: &Expr) -> Option<Span> {
+        let source = match &expr.kind {
+            ExprKind::If(cond, ..) | ExprKind::While(cond, _) | ExprKind::Repeat(_, cond, _) => {
+                cond
+            }
+            _ => expr,
+        };
+        self.properties
+            .expr_properties(source.id)
+            .dynamism_sources
+            .first()
+            .map(|source| source.span())
+    }
+}
+
+impl<'a> Visitor<'a> for Checker {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        let missing = self
+            .properties
+            .expr_properties(expr.id)
+            .introduced_features
+            .difference(self.capabilities);
+        let origin = self.origin(expr);
+        for feature in missing.iter() {
+            let target = self.target.clone();
+            let error = if feature == RuntimeCapabilityFlags::IntegerComputations {
+                Error::Int(target, expr.span, origin)
+            } else if feature == RuntimeCapabilityFlags::FloatingPointComputations {
+                Error::Double(target, expr.span, origin)
+            } else if feature == RuntimeCapabilityFlags::ForwardBranching {
+                Error::Branch(target, expr.span, origin)
+            } else if feature == RuntimeCapabilityFlags::BackwardsBranching {
+                Error::Loop(target, expr.span, origin)
+            } else {
+                Error::Value(target, expr.span, origin)
+            };
+            self.errors.push(error);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+struct Analyzer<'a> {
+    exprs: IndexMap<NodeId, ComputeProperties>,
+    /// The sources of the values of the variables, by the IDs of their bindings.
+    locals: IndexMap<NodeId, Vec<DynamismSource>>,
+    /// The sources of the values that closures capture, by the items that the closures were lifted
+    /// to and in the order of the leading parameters that the captured values are passed in.
+    captures: IndexMap<LocalItemId, Vec<Vec<DynamismSource>>>,
+    /// The variables that closures capture, by the items that the closures were lifted to.
+    capture_args: IndexMap<LocalItemId, Vec<NodeId>>,
+    /// Where the values of the variables came from, by the IDs of their bindings.
+    flows: IndexMap<NodeId, Vec<Flow>>,
+    /// The conditions of the branches and loops around the expression, with their sources, which
+    /// variables assigned inside them depend on.
+    conditions: Vec<(NodeId, Vec<DynamismSource>)>,
+    /// The features of the expressions being visited, which take in those of the expressions
+    /// inside them.
+    features: Vec<RuntimeCapabilityFlags>,
+    /// The callables whose parameters are all dynamic, whatever their types.
+    dynamic_params: &'a FxHashSet<LocalItemId>,
+    /// Whether the parameters of the callable being visited are all dynamic.
+    in_dynamic_callable: bool,
+    changed: bool,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(dynamic_params: &'a FxHashSet<LocalItemId>) -> Self {
+        Self {
+            exprs: IndexMap::new(),
+            locals: IndexMap::new(),
+            captures: IndexMap::new(),
+            capture_args: IndexMap::new(),
+            flows: IndexMap::new(),
+            conditions: Vec::new(),
+            features: Vec::new(),
+            dynamic_params,
+            in_dynamic_callable: false,
+            changed: false,
+        }
+    }
+
+    /// Visits the units of the component until their variables settle. Variables can become
+    /// dynamic after they are first used, such as when a loop assigns a measurement result to
+    /// them, so the units are visited again until no more of them do.
+    fn visit_component(&mut self, package: &Package, component: &[Unit]) {
+        loop {
+            self.changed = false;
+            for &unit in component {
+                self.visit_unit(package, unit);
+            }
+            if !self.changed {
+                break;
+            }
+        }
+    }
+
+    fn visit_unit(&mut self, package: &Package, unit: Unit) {
+        match unit {
+            Unit::Item(id) => self.visit_item(package.items.get(id).expect("item should exist")),
+            Unit::TopLevel => {
+                package.stmts.iter().for_each(|stmt| self.visit_stmt(stmt));
+                package.entry.iter().for_each(|expr| self.visit_expr(expr));
+            }
+        }
+    }
+
+    fn sources(&self, expr: &Expr) -> &[DynamismSource] {
+        self.exprs
+            .get(expr.id)
+            .map(|props| props.dynamism_sources.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn block_sources(&self, block: &Block) -> Vec<DynamismSource> {
+        match block.stmts.last().map(|stmt| &stmt.kind) {
+            Some(StmtKind::Expr(expr)) => self.sources(expr).to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn condition_sources(&self) -> Vec<DynamismSource> {
+        self.conditions
+            .iter()
+            .flat_map(|(_, sources)| sources)
+            .copied()
+            .collect()
+    }
+
+    fn bind(&mut self, pat: &Pat, sources: &[DynamismSource], flow: Flow) {
+        match &pat.kind {
+            PatKind::Bind(ident) => {
+                self.add_sources(ident.id, sources);
+                self.add_flow(ident.id, flow);
+            }
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind(pat, sources, flow)),
+            PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    /// Adds the sources, and those of the conditions around the assignment, to those of the
+    /// variables that the assignment target refers to.
+    fn assign_to(&mut self, lhs: &Expr, value: &Expr) {
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(id), _) => {
+                let mut all = self.condition_sources();
+                all.extend_from_slice(self.sources(value));
+                self.add_sources(*id, &all);
+                self.add_flow(*id, Flow::Value(value.id));
+                let conditions = self
+                    .conditions
+                    .iter()
+                    .map(|&(cond, _)| cond)
+                    .collect::<Vec<_>>();
+                for cond in conditions {
+                    self.add_flow(*id, Flow::Condition(cond));
+                }
+            }
+            ExprKind::Tuple(items) => items.iter().for_each(|item| self.assign_to(item, value)),
+            _ => {}
+        }
+    }
+
+    fn add_flow(&mut self, id: NodeId, flow: Flow) {
+        if self.flows.get(id).is_none() {
+            self.flows.insert(id, Vec::new());
+        }
+        let flows = self.flows.get_mut(id).expect("local should be tracked");
+        if !flows.contains(&flow) {
+            flows.push(flow);
+        }
+    }
+
+    fn add_sources(&mut self, id: NodeId, sources: &[DynamismSource]) {
+        if self.locals.get(id).is_none() {
+            self.locals.insert(id, Vec::new());
+        }
+        let local = self.locals.get_mut(id).expect("local should be tracked");
+        for source in sources {
+            if !local.contains(source) {
+                local.push(*source);
+                self.changed = true;
+            }
+        }
+    }
+
+    /// Adds the sources of the captured variables to those of the closure's captures, which the
+    /// lifted callable's leading parameters are bound to.
+    fn capture(&mut self, item: LocalItemId, args: &[NodeId]) {
+        if self.captures.get(item).is_none() {
+            self.captures.insert(item, vec![Vec::new(); args.len()]);
+            self.capture_args.insert(item, args.to_vec());
+        }
+        for (i, &arg) in args.iter().enumerate() {
+            let sources = self.locals.get(arg).cloned().unwrap_or_default();
+            let captured = &mut self.captures.get_mut(item).expect("item should be tracked")[i];
+            for source in sources {
+                if !captured.contains(&source) {
+                    captured.push(source);
+                    self.changed = true;
+                }
+            }
+        }
+    }
+
+    fn bind_captures(&mut self, item: &Item) {
+        let (ItemKind::Callable(decl), Some(captures)) = (&item.kind, self.captures.get(item.id))
+        else {
+            return;
+        };
+        let captures = captures.clone();
+        let args = self.capture_args.get(item.id).cloned().unwrap_or_default();
+        if let PatKind::Tuple(pats) = &decl.input.kind {
+            for ((pat, sources), arg) in pats.iter().zip(captures).zip(args) {
+                self.bind(pat, &sources, Flow::Capture(arg));
+            }
+        }
+    }
+
+    fn visit_in_condition(&mut self, cond: &Expr, visit: impl FnOnce(&mut Self)) {
+        self.conditions.push((cond.id, self.sources(cond).to_vec()));
+        visit(self);
+        self.conditions.pop();
+    }
+
+    fn bind_params(&mut self, pat: &Pat) {
+        match &pat.kind {
+            PatKind::Bind(ident) if self.in_dynamic_callable || any_result_ty(&pat.ty) => {
+                self.add_sources(ident.id, &[DynamismSource::Parameter(pat.span)]);
+            }
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind_params(pat)),
+            PatKind::Bind(_) | PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    /// The sources of the value of the expression, from those of the expressions inside it.
+    fn value_sources(&self, expr: &Expr) -> Vec<DynamismSource> {
+        if expr.ty == Ty::UNIT {
+            return Vec::new();
+        }
+        match &expr.kind {
+            ExprKind::Var(Res::Local(id), _) => self.locals.get(*id).cloned().unwrap_or_default(),
+            ExprKind::Call(callee, arg) => {
+                let mut sources = self.sources(callee).to_vec();
+                sources.extend_from_slice(self.sources(arg));
+                if let Ty::Arrow(arrow) = &callee.ty {
+                    if arrow.kind == CallableKind::Operation && any_result_ty(&arrow.output) {
+                        sources.push(DynamismSource::Measurement(expr.span));
+                    }
+                }
+                sources
+            }
+            ExprKind::Block(block) => self.block_sources(block),
+            ExprKind::Closure(args, _) => args
+                .iter()
+                .filter_map(|&id| self.locals.get(id))
+                .flatten()
+                .copied()
+                .collect(),
+            ExprKind::If(cond, body, otherwise) => {
+                let mut sources = self.sources(cond).to_vec();
+                sources.extend_from_slice(self.sources(body));
+                if let Some(otherwise) = otherwise {
+                    sources.extend_from_slice(self.sources(otherwise));
+                }
+                sources
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => items
+                .iter()
+                .flat_map(|item| self.sources(item))
+                .copied()
+                .collect(),
+            ExprKind::ArrayRepeat(lhs, rhs)
+            | ExprKind::BinOp(_, lhs, rhs)
+            | ExprKind::Index(lhs, rhs)
+            | ExprKind::UpdateField(lhs, _, rhs) => {
+                let mut sources = self.sources(lhs).to_vec();
+                sources.extend_from_slice(self.sources(rhs));
+                sources
+            }
+            ExprKind::UpdateIndex(array, index, value) => [array, index, value]
+                .into_iter()
+                .flat_map(|item| self.sources(item))
+                .copied()
+                .collect(),
+            ExprKind::Field(inner, _) | ExprKind::UnOp(_, inner) => self.sources(inner).to_vec(),
+            ExprKind::Range(start, step, end) => [start, step, end]
+                .into_iter()
+                .flatten()
+                .flat_map(|item| self.sources(item))
+                .copied()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The capabilities that the expression needs on its own.
+    fn own_features(&self, expr: &Expr, sources: &[DynamismSource]) -> RuntimeCapabilityFlags {
+        let mut features = if sources.is_empty() {
+            RuntimeCapabilityFlags::empty()
+        } else {
+            value_features(&expr.ty)
+        };
+        match &expr.kind {
+            ExprKind::BinOp(BinOp::Eq | BinOp::Neq, lhs, _) if any_result_ty(&lhs.ty) => {
+                features |= RuntimeCapabilityFlags::ForwardBranching;
+            }
+            ExprKind::If(cond, ..) if !self.sources(cond).is_empty() => {
+                features |= RuntimeCapabilityFlags::ForwardBranching;
+            }
+            ExprKind::While(cond, _) | ExprKind::Repeat(_, cond, _)
+                if !self.sources(cond).is_empty() =>
+            {
+                features |= RuntimeCapabilityFlags::ForwardBranching
+                    | RuntimeCapabilityFlags::BackwardsBranching;
+            }
+            _ => {}
+        }
+        features
+    }
+}
+
+impl<'a> Visitor<'a> for Analyzer<'_> {
+    fn visit_item(&mut self, item: &'a Item) {
+        let outer_dynamic = self.in_dynamic_callable;
+        if let ItemKind::Callable(_) = &item.kind {
+            self.in_dynamic_callable = self.dynamic_params.contains(&item.id);
+        }
+        self.bind_captures(item);
+        walk_item(self, item);
+        self.in_dynamic_callable = outer_dynamic;
+    }
+
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        self.bind_params(&decl.input);
+        walk_callable_decl(self, decl);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        walk_stmt(self, stmt);
+        if let StmtKind::Local(_, pat, value) = &stmt.kind {
+            let sources = self.sources(value).to_vec();
+            self.bind(pat, &sources, Flow::Value(value.id));
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.features.push(RuntimeCapabilityFlags::empty());
+        match &expr.kind {
+            ExprKind::If(cond, body, otherwise) => {
+                self.visit_expr(cond);
+                self.visit_in_condition(cond, |analyzer| {
+                    analyzer.visit_expr(body);
+                    if let Some(otherwise) = otherwise {
+                        analyzer.visit_expr(otherwise);
+                    }
+                });
+            }
+            ExprKind::While(cond, block) => {
+                self.visit_expr(cond);
+                self.visit_in_condition(cond, |analyzer| analyzer.visit_block(block));
+            }
+            ExprKind::For(pat, iter, block) => {
+                self.visit_expr(iter);
+                let sources = self.sources(iter).to_vec();
+                self.bind(pat, &sources, Flow::Value(iter.id));
+                self.visit_block(block);
+            }
+            ExprKind::Closure(args, item) => self.capture(*item, args),
+            ExprKind::Assign(lhs, rhs) | ExprKind::AssignOp(_, lhs, rhs) => {
+                walk_expr(self, expr);
+                self.assign_to(lhs, rhs);
+            }
+            ExprKind::AssignField(record, _, value) | ExprKind::AssignIndex(record, _, value) => {
+                walk_expr(self, expr);
+                self.assign_to(record, value);
+            }
+            _ => walk_expr(self, expr),
+        }
+
+        let mut sources = self.value_sources(expr);
+        sources.sort_by_key(|source| source.span().lo);
+        sources.dedup();
+        let inner = self
+            .features
+            .pop()
+            .expect("expression should have features");
+        let own = self.own_features(expr, &sources);
+        let features = inner | own;
+        if let Some(outer) = self.features.last_mut() {
+            *outer |= features;
+        }
+        self.exprs.insert(
+            expr.id,
+            ComputeProperties {
+                runtime_features: features,
+                introduced_features: own.difference(inner),
+                dynamism_sources: sources,
+            },
+        );
+    }
+}
+
+/// The capabilities needed to compute a dynamic value of the type. A callable value is dynamic
+/// when it captures dynamic values, which need their capabilities where they are computed, so it
+/// needs none of its own.
+fn value_features(ty: &Ty) -> RuntimeCapabilityFlags {
+    match ty {
+        Ty::Prim(Prim::Result | Prim::Bool) | Ty::Arrow(_) => RuntimeCapabilityFlags::empty(),
+        Ty::Prim(Prim::Int) => RuntimeCapabilityFlags::IntegerComputations,
+        Ty::Prim(Prim::Double) => RuntimeCapabilityFlags::FloatingPointComputations,
+        Ty::Array(item) => value_features(item),
+        Ty::Tuple(items) => items
+            .iter()
+            .fold(RuntimeCapabilityFlags::empty(), |features, item| {
+                features | value_features(item)
+            }),
+        _ => RuntimeCapabilityFlags::HigherLevelConstructs,
+    }
+}
@@ -0,0 +1,447 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use miette::Diagnostic;
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::{
+    hir::{Expr, NodeId, Package},
+    visit::{walk_expr, Visitor},
+};
+use std::fmt::Write;
+
+use super::{
+    analyze_compute_properties, check_compute_properties, ComputeProperties, DynamismSource,
+};
+
+fn compile_source(source: &str) -> Package {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    unit.package
+}
+
+/// The first expression in the source whose text is `text`.
+fn find_expr<'a>(package: &'a Package, source: &str, text: &str) -> &'a Expr {
+    struct Find<'a, 'b> {
+        source: &'b str,
+        text: &'b str,
+        found: Option<&'a Expr>,
+    }
+
+    impl<'a> Visitor<'a> for Find<'a, '_> {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            if self.found.is_none()
+                && &self.source[expr.span.lo as usize..expr.span.hi as usize] == self.text
+            {
+                self.found = Some(expr);
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let mut find = Find {
+        source,
+        text,
+        found: None,
+    };
+    find.visit_package(package);
+    find.found.expect("expression should be in the source")
+}
+
+/// The properties of the first expression in the source whose text is `text`.
+fn properties(source: &str, text: &str) -> ComputeProperties {
+    let package = compile_source(source);
+    let expr = find_expr(&package, source, text);
+    analyze_compute_properties(&package)
+        .expr_properties(expr.id)
+        .clone()
+}
+
+fn span_text<'a>(source: &'a str, source_of: &DynamismSource) -> &'a str {
+    let span = source_of.span();
+    &source[span.lo as usize..span.hi as usize]
+}
+
+fn check_errors(source: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
+    let package = compile_source(source);
+    let mut actual = String::new();
+    for error in check_compute_properties(&package, capabilities) {
+        let _ = writeln!(actual, "{error}");
+        for label in error.labels().into_iter().flatten() {
+            let text = &source[label.offset()..label.offset() + label.len()];
+            let _ = writeln!(actual, "  {}: {text}", label.label().unwrap_or_default());
+        }
+    }
+    expect.assert_eq(&actual);
+}
+
+static MEASURE: &str = indoc! {"
+    namespace Test {
+        operation Main() : Int {
+            use q = Qubit();
+            let r = M(q);
+            let n = 3;
+            mutable count = 0;
+            if r == One {
+                set count += 1;
+            }
+            count + n
+        }
+    }
+"};
+
+#[test]
+fn measurement_results_are_dynamic() {
+    let props = properties(MEASURE, "M(q)");
+    assert!(props.is_dynamic());
+    assert_eq!(props.runtime_features(), RuntimeCapabilityFlags::empty());
+    assert!(matches!(
+        props.dynamism_sources(),
+        [source @ DynamismSource::Measurement(_)] if span_text(MEASURE, source) == "M(q)"
+    ));
+
+    let props = properties(MEASURE, "3");
+    assert!(!props.is_dynamic());
+    assert_eq!(props.runtime_features(), RuntimeCapabilityFlags::empty());
+}
+
+#[test]
+fn comparing_results_needs_branching() {
+    let props = properties(MEASURE, "r == One");
+    assert!(props.is_dynamic());
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::ForwardBranching
+    );
+}
+
+#[test]
+fn variables_assigned_under_dynamic_conditions_are_dynamic() {
+    let props = properties(MEASURE, "count + n");
+    assert!(props.is_dynamic());
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::IntegerComputations
+    );
+    assert!(matches!(
+        props.dynamism_sources(),
+        [source] if span_text(MEASURE, source) == "M(q)"
+    ));
+}
+
+#[test]
+fn dynamism_is_explained_through_the_variables_it_propagated_through() {
+    let package = compile_source(MEASURE);
+    let expr = find_expr(&package, MEASURE, "count + n");
+    let chains = analyze_compute_properties(&package).explain_dynamism(&package, expr.id);
+    let [chain] = chains.as_slice() else {
+        panic!("expression should have one source: {chains:?}");
+    };
+    let names: Vec<_> = chain.variables.iter().map(|(name, _)| &**name).collect();
+    assert_eq!(names, ["count", "r"]);
+    assert_eq!(span_text(MEASURE, &chain.source), "M(q)");
+
+    let expr = find_expr(&package, MEASURE, "M(q)");
+    let chains = analyze_compute_properties(&package).explain_dynamism(&package, expr.id);
+    assert!(matches!(chains.as_slice(), [chain] if chain.variables.is_empty()));
+}
+
+#[test]
+fn dynamic_doubles_need_floating_point_computations() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Double {
+                use q = Qubit();
+                M(q) == One ? 1.0 | 0.0
+            }
+        }
+    "};
+    let props = properties(source, "M(q) == One ? 1.0 | 0.0");
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::ForwardBranching
+            | RuntimeCapabilityFlags::FloatingPointComputations
+    );
+}
+
+#[test]
+fn loops_on_results_need_backwards_branching_even_when_bounded() {
+    let source = indoc! {"
+        namespace Test {
+            operation Unbounded() : Unit {
+                use q = Qubit();
+                mutable done = false;
+                while not done { set done = M(q) == One; }
+            }
+
+            @MaxIterations(10)
+            operation Bounded() : Unit {
+                use q = Qubit();
+                mutable again = true;
+                while again { set again = M(q) == Zero; }
+            }
+        }
+    "};
+    let props = properties(source, "while not done { set done = M(q) == One; }");
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::BackwardsBranching
+    );
+    // The bound adds an iteration counter to the loop, which the target has to compute.
+    let props = properties(source, "while again { set again = M(q) == Zero; }");
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::ForwardBranching
+            | RuntimeCapabilityFlags::BackwardsBranching
+            | RuntimeCapabilityFlags::IntegerComputations
+    );
+}
+
+#[test]
+fn result_parameters_are_dynamic() {
+    let source = indoc! {"
+        namespace Test {
+            function IsOne(r : Result) : Bool {
+                r == One
+            }
+        }
+    "};
+    let props = properties(source, "r == One");
+    assert!(matches!(
+        props.dynamism_sources(),
+        [DynamismSource::Parameter(_)]
+    ));
+}
+
+#[test]
+fn diagnostics_point_at_the_expressions_that_introduce_features() {
+    check_errors(
+        MEASURE,
+        ConfigAttr::Base.into(),
+        &expect![[r"
+            branch on a measurement result used under Base profile
+              decided when the program runs: r == One
+              derives from this measurement result: M(q)
+            dynamic Int used under Base profile
+              computed when the program runs: count
+              derives from this measurement result: M(q)
+            dynamic Int used under Base profile
+              computed when the program runs: count
+              derives from this measurement result: M(q)
+        "]],
+    );
+    check_errors(MEASURE, ConfigAttr::Adaptive.into(), &expect![""]);
+}
+
+#[test]
+fn diagnostics_name_the_missing_feature() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Double {
+                use q = Qubit();
+                M(q) == One ? 1.0 | 0.0
+            }
+        }
+    "};
+    check_errors(
+        source,
+        ConfigAttr::Adaptive.into(),
+        &expect![[r"
+            dynamic Double used under Adaptive profile
+              computed when the program runs: M(q) == One ? 1.0 | 0.0
+              derives from this measurement result: M(q)
+        "]],
+    );
+}
+
+#[test]
+fn diagnostics_for_custom_capabilities_name_the_target() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Unit {
+                use q = Qubit();
+                mutable done = false;
+                while not done { set done = M(q) == One; }
+            }
+        }
+    "};
+    check_errors(
+        source,
+        RuntimeCapabilityFlags::ForwardBranching,
+        &expect![[r"
+            loop on a measurement result used under the target capabilities
+              repeated until the program decides to stop: while not done { set done = M(q) == One; }
+              derives from this measurement result: M(q)
+        "]],
+    );
+}
+
+#[test]
+fn lambdas_carry_the_properties_of_their_captures() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Int {
+                use q = Qubit();
+                let n = M(q) == One ? 1 | 0;
+                let m = 3;
+                let add = x -> x + n;
+                let times = x -> x * m;
+                add(2) + times(2)
+            }
+        }
+    "};
+    let props = properties(source, "x -> x + n");
+    assert!(props.is_dynamic());
+    assert_eq!(props.runtime_features(), RuntimeCapabilityFlags::empty());
+
+    let props = properties(source, "x + n");
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::IntegerComputations
+    );
+    assert!(matches!(
+        props.dynamism_sources(),
+        [source_of] if span_text(source, source_of) == "M(q)"
+    ));
+
+    let props = properties(source, "add(2)");
+    assert!(props.is_dynamic());
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::IntegerComputations
+    );
+
+    assert!(!properties(source, "x -> x * m").is_dynamic());
+    assert!(!properties(source, "x * m").is_dynamic());
+    assert!(!properties(source, "times(2)").is_dynamic());
+}
+
+#[test]
+fn nested_lambdas_carry_the_properties_of_outer_captures() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Int {
+                use q = Qubit();
+                let n = M(q) == One ? 1 | 0;
+                let outer = x -> {
+                    let inner = y -> y + n;
+                    inner(x)
+                };
+                outer(2)
+            }
+        }
+    "};
+    let props = properties(source, "y + n");
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::IntegerComputations
+    );
+    assert!(matches!(
+        props.dynamism_sources(),
+        [source_of] if span_text(source, source_of) == "M(q)"
+    ));
+    assert!(properties(source, "outer(2)").is_dynamic());
+}
+
+#[test]
+fn partial_applications_carry_the_properties_of_their_arguments() {
+    let source = indoc! {"
+        namespace Test {
+            function Scale(factor : Double, x : Double) : Double {
+                factor * x
+            }
+
+            operation Main() : Double {
+                use q = Qubit();
+                let factor = M(q) == One ? 2.0 | 1.0;
+                let scale = Scale(factor, _);
+                let half = Scale(0.5, _);
+                scale(3.0) + half(3.0)
+            }
+        }
+    "};
+    let props = properties(source, "scale(3.0)");
+    assert!(props.is_dynamic());
+    assert_eq!(
+        props.runtime_features(),
+        RuntimeCapabilityFlags::FloatingPointComputations
+    );
+    assert!(matches!(
+        props.dynamism_sources(),
+        [source_of] if span_text(source, source_of) == "M(q)"
+    ));
+
+    assert!(!properties(source, "factor * x").is_dynamic());
+    assert!(!properties(source, "half(3.0)").is_dynamic());
+}
+
+#[test]
+fn analysis_on_the_thread_pool_matches_analysis_on_one_thread() {
+    struct Exprs(Vec<NodeId>);
+
+    impl<'a> Visitor<'a> for Exprs {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            self.0.push(expr.id);
+            walk_expr(self, expr);
+        }
+    }
+
+    let source = indoc! {"
+        namespace Test {
+            operation Flip(q : Qubit) : Int {
+                M(q) == One ? 1 | 0
+            }
+
+            operation Angle() : Double {
+                use q = Qubit();
+                let r = M(q);
+                r == One ? 1.0 | 0.0
+            }
+
+            operation Count() : Int {
+                use q = Qubit();
+                mutable n = 0;
+                while M(q) == Zero {
+                    set n += 1;
+                }
+                n
+            }
+
+            operation Main() : Int {
+                use q = Qubit();
+                let r = M(q);
+                let add = x -> {
+                    let inner = y -> r == One ? x + y | y;
+                    inner(x)
+                };
+                add(Flip(q)) + Count()
+            }
+        }
+    "};
+    let package = compile_source(source);
+    let pooled = analyze_compute_properties(&package);
+    let sequential = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("thread pool should be built")
+        .install(|| analyze_compute_properties(&package));
+
+    let mut exprs = Exprs(Vec::new());
+    exprs.visit_package(&package);
+    for id in exprs.0 {
+        assert_eq!(
+            pooled.expr_properties(id),
+            sequential.expr_properties(id),
+            "expression {id}"
+        );
+    }
+    assert!(pooled
+        .expr_properties(find_expr(&package, source, "r == One ? x + y | y").id)
+        .is_dynamic());
+}
@@ -7,7 +7,7 @@ mod tests;
 use std::mem::take;
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     assigner::Assigner,
     global::Table,
@@ -45,6 +45,15 @@ pub enum Error {
     ReturnForbidden(#[label] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::AdjGen(error) => error.message_params(),
+            Self::ApplyAssign(_) | Self::ReturnForbidden(_) => Vec::new(),
+        }
+    }
+}
+
 /// Generates adjoint inverted blocks for within-blocks across all conjugate expressions,
 /// eliminating the conjugate expression from the compilation unit.
 pub(super) fn invert_conjugate_exprs(
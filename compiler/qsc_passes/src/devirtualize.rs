@@ -0,0 +1,238 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Replaces calls through arrays of callables whose contents are known at compile time with direct
+//! calls. When `ops` is an immutable local bound to an array literal of global callables, a call
+//! such as `ops[i](q)` becomes
+//!
+//! ```qsharp
+//! {
+//!     let @dispatch_index = i;
+//!     let @dispatch_arg = q;
+//!     if @dispatch_index == 0 { A(@dispatch_arg) }
+//!     elif @dispatch_index == 1 { B(@dispatch_arg) }
+//!     else { ops[@dispatch_index](@dispatch_arg) }
+//! }
+//! ```
+//!
+//! so that the callables can be inlined and have their specializations generated like any other
+//! direct call. The last branch is only taken for an index out of range, where it fails as before.
+
+#[cfg(test)]
+mod tests;
+
+use crate::common::{generated_name, IdentTemplate};
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{
+        BinOp, Block, Expr, ExprKind, Functor, Lit, Mutability, NodeId, PatKind, Res, Stmt,
+        StmtKind, UnOp,
+    },
+    mut_visit::{walk_expr, walk_stmt, MutVisitor},
+    ty::{Prim, Ty},
+};
+use rustc_hash::FxHashMap;
+use std::mem::take;
+
+pub(crate) struct Devirtualize<'a> {
+    assigner: &'a mut Assigner,
+    /// The callables in each immutable local bound to an array of global callables.
+    tables: FxHashMap<NodeId, Vec<Expr>>,
+}
+
+impl<'a> Devirtualize<'a> {
+    pub(crate) fn new(assigner: &'a mut Assigner) -> Self {
+        Self {
+            assigner,
+            tables: FxHashMap::default(),
+        }
+    }
+
+    fn table_items(&self, value: &Expr) -> Option<Vec<Expr>> {
+        match &value.kind {
+            ExprKind::Array(items)
+                if !items.is_empty()
+                    && items
+                        .iter()
+                        .all(|item| matches!(item.kind, ExprKind::Var(Res::Item(_), _))) =>
+            {
+                Some(items.clone())
+            }
+            ExprKind::Var(Res::Local(local), _) => self.tables.get(local).cloned(),
+            _ => None,
+        }
+    }
+
+    fn gen_ident(&mut self, label: &str, ty: Ty, span: Span) -> IdentTemplate {
+        let id = self.assigner.next_node();
+        IdentTemplate {
+            id,
+            span,
+            ty,
+            name: generated_name(&format!("{label}_{id}")),
+        }
+    }
+
+    /// Creates the conditional that calls the callable at the index of the table directly.
+    fn dispatch(
+        &mut self,
+        call: &Expr,
+        functors: &[(Functor, Ty)],
+        items: Vec<Expr>,
+        table_index: Expr,
+        arg: Expr,
+    ) -> Expr {
+        let ExprKind::Index(array, index) = table_index.kind else {
+            panic!("callee should index into a table");
+        };
+        let index_id = self.gen_ident("dispatch_index", Ty::Prim(Prim::Int), index.span);
+        let arg_id = self.gen_ident("dispatch_arg", arg.ty.clone(), arg.span);
+        let index_init = index_id.gen_id_init(Mutability::Immutable, *index, self.assigner);
+        let arg_init = arg_id.gen_id_init(Mutability::Immutable, arg, self.assigner);
+
+        let fallback = Expr {
+            kind: ExprKind::Index(array, Box::new(index_id.gen_local_ref(self.assigner))),
+            ..table_index
+        };
+        let fallback = self.gen_call(call, functors, fallback, &arg_id);
+        let mut dispatch = self.gen_block(fallback);
+        for (position, mut item) in items.into_iter().enumerate().rev() {
+            item.id = self.assigner.next_node();
+            item.span = call.span;
+            let direct = self.gen_call(call, functors, item, &arg_id);
+            let cond = Expr {
+                id: self.assigner.next_node(),
+                span: call.span,
+                ty: Ty::Prim(Prim::Bool),
+                kind: ExprKind::BinOp(
+                    BinOp::Eq,
+                    Box::new(index_id.gen_local_ref(self.assigner)),
+                    Box::new(Expr {
+                        id: self.assigner.next_node(),
+                        span: call.span,
+                        ty: Ty::Prim(Prim::Int),
+                        kind: ExprKind::Lit(Lit::Int(
+                            i64::try_from(position).expect("array index should fit in an Int"),
+                        )),
+                    }),
+                ),
+            };
+            dispatch = Expr {
+                id: self.assigner.next_node(),
+                span: call.span,
+                ty: call.ty.clone(),
+                kind: ExprKind::If(
+                    Box::new(cond),
+                    Box::new(self.gen_block(direct)),
+                    Some(Box::new(dispatch)),
+                ),
+            };
+        }
+
+        Expr {
+            id: self.assigner.next_node(),
+            span: call.span,
+            ty: call.ty.clone(),
+            kind: ExprKind::Block(Block {
+                id: self.assigner.next_node(),
+                span: call.span,
+                ty: call.ty.clone(),
+                stmts: vec![
+                    index_init,
+                    arg_init,
+                    Stmt {
+                        id: self.assigner.next_node(),
+                        span: call.span,
+                        kind: StmtKind::Expr(dispatch),
+                    },
+                ],
+            }),
+        }
+    }
+
+    /// Applies the functors to the callee, innermost first, and calls it with the argument.
+    fn gen_call(
+        &mut self,
+        call: &Expr,
+        functors: &[(Functor, Ty)],
+        mut callee: Expr,
+        arg: &IdentTemplate,
+    ) -> Expr {
+        for (functor, ty) in functors.iter().rev() {
+            callee = Expr {
+                id: self.assigner.next_node(),
+                span: call.span,
+                ty: ty.clone(),
+                kind: ExprKind::UnOp(UnOp::Functor(*functor), Box::new(callee)),
+            };
+        }
+
+        Expr {
+            id: self.assigner.next_node(),
+            span: call.span,
+            ty: call.ty.clone(),
+            kind: ExprKind::Call(Box::new(callee), Box::new(arg.gen_local_ref(self.assigner))),
+        }
+    }
+
+    fn gen_block(&mut self, expr: Expr) -> Expr {
+        Expr {
+            id: self.assigner.next_node(),
+            span: expr.span,
+            ty: expr.ty.clone(),
+            kind: ExprKind::Block(Block {
+                id: self.assigner.next_node(),
+                span: expr.span,
+                ty: expr.ty.clone(),
+                stmts: vec![Stmt {
+                    id: self.assigner.next_node(),
+                    span: expr.span,
+                    kind: StmtKind::Expr(expr),
+                }],
+            }),
+        }
+    }
+}
+
+impl MutVisitor for Devirtualize<'_> {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        if let StmtKind::Local(Mutability::Immutable, pat, value) = &stmt.kind {
+            if let PatKind::Bind(ident) = &pat.kind {
+                if let Some(items) = self.table_items(value) {
+                    self.tables.insert(ident.id, items);
+                }
+            }
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+        let ExprKind::Call(callee, arg) = &mut expr.kind else {
+            return;
+        };
+
+        let mut functors = Vec::new();
+        let mut inner: &Expr = callee;
+        while let ExprKind::UnOp(UnOp::Functor(functor), operand) = &inner.kind {
+            functors.push((*functor, inner.ty.clone()));
+            inner = operand;
+        }
+        let ExprKind::Index(array, _) = &inner.kind else {
+            return;
+        };
+        let ExprKind::Var(Res::Local(local), _) = &array.kind else {
+            return;
+        };
+        let Some(items) = self.tables.get(local).cloned() else {
+            return;
+        };
+
+        // The callee is replaced, so its parts can be moved into the dispatch without duplicating
+        // node IDs.
+        let table_index = inner.clone();
+        let arg = take(&mut **arg);
+        *expr = self.dispatch(expr, &functors, items, table_index, arg);
+    }
+}
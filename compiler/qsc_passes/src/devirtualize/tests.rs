@@ -0,0 +1,134 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::devirtualize::Devirtualize;
+use indoc::indoc;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::{
+    hir::{Expr, ExprKind, Functor, ItemKind, Package, Res, UnOp},
+    mut_visit::MutVisitor,
+    validate::Validator,
+    visit::{walk_expr, Visitor},
+};
+
+/// Describes the callee of each call in the package, in the order they appear.
+fn callees(file: &str) -> Vec<String> {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(&store, &[], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    Devirtualize::new(&mut unit.assigner).visit_package(&mut unit.package);
+    Validator::default().visit_package(&unit.package);
+
+    let mut finder = Callees {
+        package: &unit.package,
+        callees: Vec::new(),
+    };
+    finder.visit_package(&unit.package);
+    finder.callees
+}
+
+struct Callees<'a> {
+    package: &'a Package,
+    callees: Vec<String>,
+}
+
+impl Callees<'_> {
+    fn describe(&self, callee: &Expr) -> String {
+        match &callee.kind {
+            ExprKind::UnOp(UnOp::Functor(Functor::Adj), inner) => {
+                format!("Adjoint {}", self.describe(inner))
+            }
+            ExprKind::UnOp(UnOp::Functor(Functor::Ctl), inner) => {
+                format!("Controlled {}", self.describe(inner))
+            }
+            ExprKind::Var(Res::Item(item), _) => match self.package.items.get(item.item) {
+                Some(item) => match &item.kind {
+                    ItemKind::Callable(decl) => decl.name.name.to_string(),
+                    _ => "item".to_string(),
+                },
+                None => "item".to_string(),
+            },
+            _ => "indirect".to_string(),
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Callees<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Call(callee, _) = &expr.kind {
+            let callee = self.describe(callee);
+            self.callees.push(callee);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn call_through_table_is_dispatched_directly() {
+    assert_eq!(
+        callees(indoc! {"
+            namespace Test {
+                operation A(q : Qubit) : Unit {}
+                operation B(q : Qubit) : Unit {}
+                operation Main(q : Qubit, i : Int) : Unit {
+                    let ops = [A, B];
+                    ops[i](q);
+                }
+            }
+        "}),
+        ["A", "B", "indirect"]
+    );
+}
+
+#[test]
+fn functors_are_applied_to_direct_calls() {
+    assert_eq!(
+        callees(indoc! {"
+            namespace Test {
+                operation A(q : Qubit) : Unit is Adj {}
+                operation B(q : Qubit) : Unit is Adj {}
+                operation Main(q : Qubit, i : Int) : Unit {
+                    let ops = [A, B];
+                    Adjoint ops[i](q);
+                }
+            }
+        "}),
+        ["Adjoint A", "Adjoint B", "Adjoint indirect"]
+    );
+}
+
+#[test]
+fn alias_of_table_is_dispatched_directly() {
+    assert_eq!(
+        callees(indoc! {"
+            namespace Test {
+                operation A(q : Qubit) : Unit {}
+                operation B(q : Qubit) : Unit {}
+                operation Main(q : Qubit, i : Int) : Unit {
+                    let ops = [A, B];
+                    let alias = ops;
+                    alias[i](q);
+                }
+            }
+        "}),
+        ["A", "B", "indirect"]
+    );
+}
+
+#[test]
+fn mutable_table_is_not_dispatched() {
+    assert_eq!(
+        callees(indoc! {"
+            namespace Test {
+                operation A(q : Qubit) : Unit {}
+                operation B(q : Qubit) : Unit {}
+                operation Main(q : Qubit, i : Int) : Unit {
+                    mutable ops = [A, B];
+                    ops[i](q);
+                }
+            }
+        "}),
+        ["indirect"]
+    );
+}
@@ -5,17 +5,19 @@
 mod tests;
 
 use super::Error as PassErr;
+use crate::id_update::NodeIdRefresher;
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     assigner::Assigner,
     hir::{
         Attr, CallableDecl, Expr, ExprKind, Item, ItemId, ItemKind, LocalItemId, Package, PatKind,
         Res,
     },
-    ty::Ty,
+    mut_visit::MutVisitor,
     visit::Visitor,
 };
+use rustc_hash::FxHashMap;
 use thiserror::Error;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -25,10 +27,21 @@ pub enum Error {
     #[diagnostic(code("Qsc.EntryPoint.Duplicate"))]
     Duplicate(String, #[label] Span),
 
-    #[error("entry point cannot have parameters")]
+    #[error("entry point cannot have parameters without default values")]
+    #[diagnostic(help(
+        "give each parameter a constant default value with `@Default(name, value)`"
+    ))]
     #[diagnostic(code("Qsc.EntryPoint.Args"))]
     Args(#[label] Span),
 
+    #[error("default value has type {1}, but the parameter has type {0}")]
+    #[diagnostic(code("Qsc.EntryPoint.DefaultTy"))]
+    DefaultTy(String, String, #[label] Span),
+
+    #[error("entry point has no parameter `{0}`")]
+    #[diagnostic(code("Qsc.EntryPoint.UnknownDefault"))]
+    UnknownDefault(String, #[label] Span),
+
     #[error("entry point must have body implementation only")]
     #[diagnostic(code("Qsc.EntryPoint.BodyMissing"))]
     BodyMissing(#[label("cannot have specialization implementation")] Span),
@@ -39,6 +52,16 @@ pub enum Error {
     NotFound,
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Duplicate(name, _) | Self::UnknownDefault(name, _) => vec![name.clone()],
+            Self::DefaultTy(param_ty, default_ty, _) => vec![default_ty.clone(), param_ty.clone()],
+            Self::Args(_) | Self::BodyMissing(_) | Self::NotFound => Vec::new(),
+        }
+    }
+}
+
 // If no entry expression is provided, generate one from the entry point callable.
 // Only one callable should be annotated with the entry point attribute.
 pub(super) fn generate_entry_expr(
@@ -61,53 +84,38 @@ pub(super) fn generate_entry_expr(
 
 fn create_entry_from_callables(
     assigner: &mut Assigner,
-    callables: Vec<(&CallableDecl, LocalItemId)>,
+    callables: Vec<(&CallableDecl, LocalItemId, &[Attr])>,
 ) -> Result<Expr, Vec<super::Error>> {
     if callables.len() == 1 {
-        let ep = callables[0].0;
-        let arg_count = if let PatKind::Tuple(args) = &ep.input.kind {
-            args.len()
+        let (ep, item, attrs) = callables[0];
+        let arg = create_entry_arg(assigner, ep, attrs)?;
+        if ep.adj.is_some() || ep.ctl.is_some() || ep.ctl_adj.is_some() {
+            Err(vec![PassErr::EntryPoint(Error::BodyMissing(ep.span))])
         } else {
-            1
-        };
-        if arg_count == 0 {
-            if ep.adj.is_some() || ep.ctl.is_some() || ep.ctl_adj.is_some() {
-                Err(vec![PassErr::EntryPoint(Error::BodyMissing(ep.span))])
-            } else {
-                match &ep.body.body {
-                    qsc_hir::hir::SpecBody::Gen(_) => {
-                        Err(vec![PassErr::EntryPoint(Error::BodyMissing(ep.span))])
-                    }
-                    qsc_hir::hir::SpecBody::Impl(_, block) => {
-                        let arg = Expr {
-                            id: assigner.next_node(),
-                            span: ep.span,
-                            ty: Ty::UNIT,
-                            kind: ExprKind::Tuple(Vec::new()),
-                        };
-                        let item = callables[0].1;
-                        let item_id = ItemId {
-                            package: None,
-                            item,
-                        };
-                        let callee = Expr {
-                            id: assigner.next_node(),
-                            span: ep.span,
-                            ty: block.ty.clone(),
-                            kind: ExprKind::Var(Res::Item(item_id), Vec::new()),
-                        };
-                        let call = Expr {
-                            id: assigner.next_node(),
-                            span: ep.span,
-                            ty: block.ty.clone(),
-                            kind: ExprKind::Call(Box::new(callee), Box::new(arg)),
-                        };
-                        Ok(call)
-                    }
+            match &ep.body.body {
+                qsc_hir::hir::SpecBody::Gen(_) => {
+                    Err(vec![PassErr::EntryPoint(Error::BodyMissing(ep.span))])
+                }
+                qsc_hir::hir::SpecBody::Impl(_, block) => {
+                    let item_id = ItemId {
+                        package: None,
+                        item,
+                    };
+                    let callee = Expr {
+                        id: assigner.next_node(),
+                        span: ep.span,
+                        ty: block.ty.clone(),
+                        kind: ExprKind::Var(Res::Item(item_id), Vec::new()),
+                    };
+                    let call = Expr {
+                        id: assigner.next_node(),
+                        span: ep.span,
+                        ty: block.ty.clone(),
+                        kind: ExprKind::Call(Box::new(callee), Box::new(arg)),
+                    };
+                    Ok(call)
                 }
             }
-        } else {
-            Err(vec![PassErr::EntryPoint(Error::Args(ep.input.span))])
         }
     } else if callables.is_empty() {
         Err(vec![PassErr::EntryPoint(Error::NotFound)])
@@ -121,7 +129,73 @@ fn create_entry_from_callables(
     }
 }
 
-fn get_callables(package: &Package) -> Vec<(&CallableDecl, LocalItemId)> {
+/// Creates the argument to the entry point from the `@Default` values of its parameters.
+fn create_entry_arg(
+    assigner: &mut Assigner,
+    ep: &CallableDecl,
+    attrs: &[Attr],
+) -> Result<Expr, Vec<super::Error>> {
+    let id = assigner.next_node();
+    let params = if let PatKind::Tuple(params) = &ep.input.kind {
+        params.iter().collect()
+    } else {
+        vec![&ep.input]
+    };
+
+    let mut defaults = attrs
+        .iter()
+        .filter_map(|attr| match attr {
+            Attr::Default(name, value) => Some((name.as_ref(), value.as_ref())),
+            _ => None,
+        })
+        .collect::<FxHashMap<_, _>>();
+    let mut errors = Vec::new();
+    let mut args = Vec::new();
+    for param in params {
+        let default = match &param.kind {
+            PatKind::Bind(name) => defaults.remove(name.name.as_ref()),
+            _ => None,
+        };
+        match default {
+            Some(value) if value.ty == param.ty => {
+                let mut value = value.clone();
+                NodeIdRefresher::new(assigner).visit_expr(&mut value);
+                args.push(value);
+            }
+            Some(value) => errors.push(PassErr::EntryPoint(Error::DefaultTy(
+                param.ty.display(),
+                value.ty.display(),
+                value.span,
+            ))),
+            None => return Err(vec![PassErr::EntryPoint(Error::Args(ep.input.span))]),
+        }
+    }
+    for attr in attrs {
+        if let Attr::Default(name, value) = attr {
+            if defaults.contains_key(name.as_ref()) {
+                errors.push(PassErr::EntryPoint(Error::UnknownDefault(
+                    name.to_string(),
+                    value.span,
+                )));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        Err(errors)
+    } else if let PatKind::Bind(_) = &ep.input.kind {
+        Ok(args.pop().expect("parameter should have an argument"))
+    } else {
+        Ok(Expr {
+            id,
+            span: ep.span,
+            ty: ep.input.ty.clone(),
+            kind: ExprKind::Tuple(args),
+        })
+    }
+}
+
+fn get_callables(package: &Package) -> Vec<(&CallableDecl, LocalItemId, &[Attr])> {
     let mut finder = EntryPointFinder {
         callables: Vec::new(),
     };
@@ -130,14 +204,14 @@ fn get_callables(package: &Package) -> Vec<(&CallableDecl, LocalItemId)> {
 }
 
 struct EntryPointFinder<'a> {
-    callables: Vec<(&'a CallableDecl, LocalItemId)>,
+    callables: Vec<(&'a CallableDecl, LocalItemId, &'a [Attr])>,
 }
 
 impl<'a> Visitor<'a> for EntryPointFinder<'a> {
     fn visit_item(&mut self, item: &'a Item) {
         if let ItemKind::Callable(callable) = &item.kind {
             if item.attrs.iter().any(|a| a == &Attr::EntryPoint) {
-                self.callables.push((callable, item.id));
+                self.callables.push((callable, item.id, &item.attrs));
             }
         }
     }
@@ -102,3 +102,65 @@ fn test_entry_point_attr_multiple() {
         "#]],
     );
 }
+
+#[test]
+fn test_entry_point_param_without_default() {
+    check(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                @Default(n, 1)
+                operation Main(n : Int, m : Int) : Int { n + m }
+            }"},
+        "",
+        &expect![[r#"
+            [
+                EntryPoint(
+                    Args(
+                        Span {
+                            lo: 73,
+                            hi: 91,
+                        },
+                    ),
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_entry_point_default_errors() {
+    check(
+        indoc! {"
+            namespace Test {
+                @EntryPoint()
+                @Default(n, 1.5)
+                @Default(k, 2)
+                operation Main(n : Int) : Int { n }
+            }"},
+        "",
+        &expect![[r#"
+            [
+                EntryPoint(
+                    DefaultTy(
+                        "Int",
+                        "Double",
+                        Span {
+                            lo: 52,
+                            hi: 55,
+                        },
+                    ),
+                ),
+                EntryPoint(
+                    UnknownDefault(
+                        "k",
+                        Span {
+                            lo: 73,
+                            hi: 74,
+                        },
+                    ),
+                ),
+            ]
+        "#]],
+    );
+}
@@ -28,6 +28,18 @@ impl<'a> NodeIdRefresher<'a> {
         }
     }
 
+    /// Creates a refresher that also replaces references to the nodes with the given IDs, which
+    /// are outside the nodes being refreshed.
+    pub(crate) fn with_replacements(
+        assigner: &'a mut Assigner,
+        replacements: FxHashMap<NodeId, NodeId>,
+    ) -> Self {
+        Self {
+            assigner,
+            replacements,
+        }
+    }
+
     fn freshen_id(&mut self, id: NodeId) -> NodeId {
         if id.is_default() {
             return self.assigner.next_node();
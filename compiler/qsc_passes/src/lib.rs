@@ -7,32 +7,55 @@
 mod baseprofck;
 mod borrowck;
 mod callable_limits;
+mod capabilities;
 mod common;
+mod compute_properties;
 mod conjugate_invert;
+mod devirtualize;
 mod entry_point;
 mod id_update;
 mod invert_block;
 mod logic_sep;
 mod loop_unification;
+mod qubit_budget;
+mod recursion;
 mod replace_qubit_allocation;
 mod spec_gen;
+mod substitution;
+mod suggestions;
 
 use callable_limits::CallableLimits;
+use devirtualize::Devirtualize;
 use entry_point::generate_entry_expr;
 use loop_unification::LoopUni;
 use miette::Diagnostic;
-use qsc_frontend::compile::{CompileUnit, RuntimeCapabilityFlags};
+use qsc_data_structures::message::MessageParams;
+use qsc_frontend::compile::{CompileUnit, ConfigAttr, RuntimeCapabilityFlags};
 use qsc_hir::{
     assigner::Assigner,
     global::{self, Table},
-    hir::Package,
+    hir::{ItemId, Package},
     mut_visit::MutVisitor,
     validate::Validator,
     visit::Visitor,
 };
 use replace_qubit_allocation::ReplaceQubitAllocation;
+use rustc_hash::{FxHashMap, FxHashSet};
+use spec_gen::SpecCache;
+use std::str::FromStr;
+use substitution::Substitute;
 use thiserror::Error;
 
+pub use baseprofck::result_literal_callables;
+pub use capabilities::{capability_delta, required_capabilities, CapabilityDelta};
+pub use compute_properties::{
+    analyze_compute_properties, ComputeProperties, DynamismSource, PackageComputeProperties,
+};
+pub use qubit_budget::qubit_sized_callables;
+pub use recursion::{analyze_recursion, Recursion};
+pub use substitution::{ArithmeticBudget, Substitution, SubstitutionTable};
+pub use suggestions::{suggest_fix, Suggestion};
+
 #[derive(Clone, Debug, Diagnostic, Error)]
 #[diagnostic(transparent)]
 #[error(transparent)]
@@ -40,9 +63,30 @@ pub enum Error {
     BaseProfCk(baseprofck::Error),
     BorrowCk(borrowck::Error),
     CallableLimits(callable_limits::Error),
+    ComputeProperties(compute_properties::Error),
     ConjInvert(conjugate_invert::Error),
     EntryPoint(entry_point::Error),
+    QubitBudget(qubit_budget::Error),
+    Recursion(recursion::Error),
     SpecGen(spec_gen::Error),
+    Substitution(substitution::Error),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::BaseProfCk(error) => error.message_params(),
+            Self::BorrowCk(error) => error.message_params(),
+            Self::CallableLimits(error) => error.message_params(),
+            Self::ComputeProperties(error) => error.message_params(),
+            Self::ConjInvert(error) => error.message_params(),
+            Self::EntryPoint(error) => error.message_params(),
+            Self::QubitBudget(error) => error.message_params(),
+            Self::Recursion(error) => error.message_params(),
+            Self::SpecGen(error) => error.message_params(),
+            Self::Substitution(error) => error.message_params(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -51,9 +95,56 @@ pub enum PackageType {
     Lib,
 }
 
+/// The state of freshly allocated qubits on a target.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum QubitInitialization {
+    /// Allocated qubits are guaranteed to be in the |0⟩ state.
+    #[default]
+    Zero,
+    /// Allocated qubits may be in any state, so each allocation is followed by a reset.
+    Arbitrary,
+}
+
+impl QubitInitialization {
+    fn allocate_qubit(self) -> &'static str {
+        match self {
+            Self::Zero => "__quantum__rt__qubit_allocate",
+            Self::Arbitrary => "AllocateQubitAndReset",
+        }
+    }
+
+    fn allocate_qubit_array(self) -> &'static str {
+        match self {
+            Self::Zero => "AllocateQubitArray",
+            Self::Arbitrary => "AllocateQubitArrayAndReset",
+        }
+    }
+}
+
+impl FromStr for QubitInitialization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zero" => Ok(Self::Zero),
+            "arbitrary" => Ok(Self::Arbitrary),
+            _ => Err(format!(
+                "unknown qubit initialization `{s}`, expected one of: zero, arbitrary"
+            )),
+        }
+    }
+}
+
 pub struct PassContext {
     capabilities: RuntimeCapabilityFlags,
+    qubit_initialization: QubitInitialization,
+    substitutions: FxHashMap<ItemId, ItemId>,
+    result_literal_callables: FxHashSet<ItemId>,
+    max_qubits: Option<usize>,
+    qubit_sized_callables: FxHashMap<ItemId, &'static [usize]>,
     borrow_check: borrowck::Checker,
+    spec_cache: SpecCache,
+    recursion_iteration_limit: usize,
 }
 
 impl PassContext {
@@ -61,10 +152,66 @@ impl PassContext {
     pub fn new(capabilities: RuntimeCapabilityFlags) -> Self {
         Self {
             capabilities,
+            qubit_initialization: QubitInitialization::default(),
+            substitutions: FxHashMap::default(),
+            result_literal_callables: FxHashSet::default(),
+            max_qubits: None,
+            qubit_sized_callables: FxHashMap::default(),
             borrow_check: borrowck::Checker::default(),
+            spec_cache: SpecCache::default(),
+            recursion_iteration_limit: recursion::DEFAULT_ITERATION_LIMIT,
         }
     }
 
+    /// Sets the state that the target leaves freshly allocated qubits in.
+    #[must_use]
+    pub fn with_qubit_initialization(mut self, qubit_initialization: QubitInitialization) -> Self {
+        self.qubit_initialization = qubit_initialization;
+        self
+    }
+
+    /// Sets the callables to use in place of others, as resolved from a [`SubstitutionTable`].
+    #[must_use]
+    pub fn with_substitutions(mut self, substitutions: FxHashMap<ItemId, ItemId>) -> Self {
+        self.substitutions = substitutions;
+        self
+    }
+
+    /// Sets the callables that may be passed result literals on targets that don't support them,
+    /// as found by [`result_literal_callables`].
+    #[must_use]
+    pub fn with_result_literal_callables(mut self, callables: FxHashSet<ItemId>) -> Self {
+        self.result_literal_callables = callables;
+        self
+    }
+
+    /// Sets the most qubits the target has, which calls whose precision is known at compile time
+    /// are checked against.
+    #[must_use]
+    pub fn with_max_qubits(mut self, max_qubits: usize) -> Self {
+        self.max_qubits = Some(max_qubits);
+        self
+    }
+
+    /// Sets the callables that allocate as many qubits as some of their parameters say, as found
+    /// by [`qubit_sized_callables`].
+    #[must_use]
+    pub fn with_qubit_sized_callables(
+        mut self,
+        callables: FxHashMap<ItemId, &'static [usize]>,
+    ) -> Self {
+        self.qubit_sized_callables = callables;
+        self
+    }
+
+    /// Sets the most rounds of propagating dynamic arguments through recursive callables, after
+    /// which they are all assumed to get dynamic arguments and need recursion on the target.
+    #[must_use]
+    pub fn with_recursion_iteration_limit(mut self, limit: usize) -> Self {
+        self.recursion_iteration_limit = limit;
+        self
+    }
+
     /// Run the default set of passes required for evaluation.
     pub fn run_default_passes(
         &mut self,
@@ -73,6 +220,17 @@ impl PassContext {
         core: &Table,
         package_type: PackageType,
     ) -> Vec<Error> {
+        if !self.substitutions.is_empty() {
+            Substitute {
+                substitutions: &self.substitutions,
+            }
+            .visit_package(package);
+        }
+
+        let budget_errors = self.max_qubits.map_or_else(Vec::new, |max_qubits| {
+            qubit_budget::check_qubit_budget(package, max_qubits, &self.qubit_sized_callables)
+        });
+
         let mut call_limits = CallableLimits::default();
         call_limits.visit_package(package);
         let callable_errors = call_limits.errors;
@@ -80,7 +238,10 @@ impl PassContext {
         self.borrow_check.visit_package(package);
         let borrow_errors = &mut self.borrow_check.errors;
 
-        let spec_errors = spec_gen::generate_specs(core, package, assigner);
+        Devirtualize::new(assigner).visit_package(package);
+        Validator::default().visit_package(package);
+
+        let spec_errors = spec_gen::generate_specs(core, package, assigner, &mut self.spec_cache);
         Validator::default().visit_package(package);
 
         let conjugate_errors = conjugate_invert::invert_conjugate_exprs(core, package, assigner);
@@ -94,25 +255,41 @@ impl PassContext {
             Vec::new()
         };
 
+        let recursion_errors = if self
+            .capabilities
+            .contains(RuntimeCapabilityFlags::Recursion)
+        {
+            Vec::new()
+        } else {
+            recursion::check_recursion(package, self.recursion_iteration_limit)
+        };
+
         LoopUni { core, assigner }.visit_package(package);
         Validator::default().visit_package(package);
 
-        ReplaceQubitAllocation::new(core, assigner).visit_package(package);
+        ReplaceQubitAllocation::new(core, assigner, self.qubit_initialization)
+            .visit_package(package);
         Validator::default().visit_package(package);
 
-        let base_prof_errors = if self.capabilities == RuntimeCapabilityFlags::empty() {
-            baseprofck::check_base_profile_compliance(package)
+        let base_prof_errors = if checks_capabilities(self.capabilities) {
+            baseprofck::check_base_profile_compliance_with(
+                package,
+                self.capabilities,
+                &self.result_literal_callables,
+            )
         } else {
             Vec::new()
         };
 
-        callable_errors
+        budget_errors
             .into_iter()
-            .map(Error::CallableLimits)
+            .map(Error::QubitBudget)
+            .chain(callable_errors.into_iter().map(Error::CallableLimits))
             .chain(borrow_errors.drain(..).map(Error::BorrowCk))
             .chain(spec_errors.into_iter().map(Error::SpecGen))
             .chain(conjugate_errors.into_iter().map(Error::ConjInvert))
             .chain(entry_point_errors)
+            .chain(recursion_errors.into_iter().map(Error::Recursion))
             .chain(base_prof_errors.into_iter().map(Error::BaseProfCk))
             .collect()
     }
@@ -133,6 +310,41 @@ pub fn run_default_passes(
     )
 }
 
+/// Checks that the package only uses the given runtime capabilities. This is the analysis that
+/// [`run_default_passes`] runs for the base and adaptive profiles, exposed so that it can be run
+/// and measured on its own.
+#[must_use]
+pub fn check_runtime_capabilities(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<Error> {
+    baseprofck::check_base_profile_compliance(package, capabilities)
+        .into_iter()
+        .map(Error::BaseProfCk)
+        .collect()
+}
+
+/// Reports each expression of the package that needs a runtime capability beyond the given ones,
+/// naming the capability and the measurement result that the expression derives from. This is
+/// the per-expression explanation of the same [`analyze_compute_properties`] results, for tools
+/// that point users at exactly what their target can't run.
+#[must_use]
+pub fn check_compute_properties(
+    package: &Package,
+    capabilities: RuntimeCapabilityFlags,
+) -> Vec<Error> {
+    compute_properties::check_compute_properties(package, capabilities)
+        .into_iter()
+        .map(Error::ComputeProperties)
+        .collect()
+}
+
+/// Whether the passes check the package against the capabilities, which they only do for the
+/// base and adaptive profiles.
+fn checks_capabilities(capabilities: RuntimeCapabilityFlags) -> bool {
+    capabilities == RuntimeCapabilityFlags::empty() || capabilities == ConfigAttr::Adaptive.into()
+}
+
 pub fn run_core_passes(core: &mut CompileUnit) -> Vec<Error> {
     let mut borrow_check = borrowck::Checker::default();
     borrow_check.visit_package(&core.package);
@@ -146,10 +358,12 @@ pub fn run_core_passes(core: &mut CompileUnit) -> Vec<Error> {
     .visit_package(&mut core.package);
     Validator::default().visit_package(&core.package);
 
-    ReplaceQubitAllocation::new(&table, &mut core.assigner).visit_package(&mut core.package);
+    ReplaceQubitAllocation::new(&table, &mut core.assigner, QubitInitialization::Zero)
+        .visit_package(&mut core.package);
     Validator::default().visit_package(&core.package);
 
-    let base_prof_errors = baseprofck::check_base_profile_compliance(&core.package);
+    let base_prof_errors =
+        baseprofck::check_base_profile_compliance(&core.package, RuntimeCapabilityFlags::empty());
 
     borrow_errors
         .into_iter()
@@ -5,7 +5,7 @@
 mod tests;
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     hir::{Block, CallableKind, Expr, ExprKind, NodeId, StmtKind},
     ty::Ty,
@@ -32,6 +32,15 @@ pub enum Error {
     OpCallForbidden(#[label] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::NonUnitBlock(ty, _) => vec![ty.clone()],
+            Self::ExprForbidden(_) | Self::OpCallForbidden(_) => Vec::new(),
+        }
+    }
+}
+
 /// Checks that the given block is separatable, meaning classical statements and quantum statements
 /// across the block and any nested expressions/blocks can be logically separated. On success, returns a `HashSet` of
 /// all quantum statement node ids, based on whether any operation calls are present in that statement.
@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::{message::MessageParams, span::Span};
+use qsc_frontend::compile::PackageStore;
+use qsc_hir::{
+    global::{self, Kind},
+    hir::{Expr, ExprKind, ItemId, Lit, Mutability, NodeId, Package, PatKind, Res, Stmt, StmtKind},
+    visit::{walk_expr, walk_stmt, Visitor},
+};
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+/// The library callables that allocate as many qubits as some of their parameters say, such as
+/// the bits of precision of a phase estimate, with the indices of those parameters. The qubits
+/// of all of them are held at once.
+const QUBIT_SIZED_CALLABLES: [(&str, &str, &[usize]); 2] = [
+    (
+        "Microsoft.Quantum.Unstable.PhaseEstimation",
+        "EstimatePhase",
+        &[0],
+    ),
+    (
+        "Microsoft.Quantum.Unstable.PhaseEstimation",
+        "EstimateAmplitude",
+        &[0, 3],
+    ),
+];
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("this call needs at least {0} qubits, but the target has {1}")]
+    #[diagnostic(help("lower the precision, or generate code for a target with more qubits"))]
+    #[diagnostic(code("Qsc.QubitBudget.PrecisionExceedsBudget"))]
+    PrecisionExceedsBudget(usize, usize, #[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::PrecisionExceedsBudget(needed, max, _) => {
+                vec![needed.to_string(), max.to_string()]
+            }
+        }
+    }
+}
+
+/// Finds the callables among the packages of the store that allocate as many qubits as some of
+/// their parameters say, with the indices of those parameters.
+#[must_use]
+pub fn qubit_sized_callables(store: &PackageStore) -> FxHashMap<ItemId, &'static [usize]> {
+    store
+        .iter()
+        .flat_map(|(id, unit)| global::iter_package(Some(id), &unit.package))
+        .filter_map(|global| match global.kind {
+            Kind::Term(term) => QUBIT_SIZED_CALLABLES
+                .iter()
+                .find(|&&(namespace, name, _)| {
+                    namespace == global.namespace.as_ref() && name == global.name.as_ref()
+                })
+                .map(|&(_, _, params)| (term.id, params)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks the calls to qubit-sized callables whose sizes are known at compile time, because they
+/// are integer literals or immutable locals bound to them, against the most qubits the target
+/// has. Sizes only known when the program runs are checked when code is generated.
+pub(super) fn check_qubit_budget(
+    package: &Package,
+    max_qubits: usize,
+    callables: &FxHashMap<ItemId, &'static [usize]>,
+) -> Vec<Error> {
+    let mut checker = Checker {
+        max_qubits,
+        callables,
+        literals: FxHashMap::default(),
+        errors: Vec::new(),
+    };
+    checker.visit_package(package);
+    checker.errors
+}
+
+struct Checker<'a> {
+    max_qubits: usize,
+    callables: &'a FxHashMap<ItemId, &'static [usize]>,
+    /// The immutable locals bound to integer literals.
+    literals: FxHashMap<NodeId, i64>,
+    errors: Vec<Error>,
+}
+
+impl Checker<'_> {
+    fn literal(&self, expr: &Expr) -> Option<i64> {
+        match &expr.kind {
+            ExprKind::Lit(Lit::Int(value)) => Some(*value),
+            ExprKind::Var(Res::Local(id), _) => self.literals.get(id).copied(),
+            _ => None,
+        }
+    }
+
+    /// The qubits that a call allocates for the parameters that size its registers, counting
+    /// only the sizes known at compile time.
+    fn known_qubits(&self, params: &[usize], args: &Expr) -> usize {
+        let args = match &args.kind {
+            ExprKind::Tuple(args) => args.iter().collect(),
+            _ => vec![args],
+        };
+        params
+            .iter()
+            .filter_map(|&param| args.get(param).and_then(|arg| self.literal(arg)))
+            .filter_map(|size| usize::try_from(size).ok())
+            .sum()
+    }
+}
+
+impl<'a> Visitor<'a> for Checker<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Local(Mutability::Immutable, pat, value) = &stmt.kind {
+            if let (PatKind::Bind(name), Some(literal)) = (&pat.kind, self.literal(value)) {
+                self.literals.insert(name.id, literal);
+            }
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Call(callee, args) = &expr.kind {
+            if let ExprKind::Var(Res::Item(id), _) = &callee.kind {
+                if let Some(params) = self.callables.get(id) {
+                    let needed = self.known_qubits(params, args);
+                    if needed > self.max_qubits {
+                        self.errors.push(Error::PrecisionExceedsBudget(
+                            needed,
+                            self.max_qubits,
+                            expr.span,
+                        ));
+                    }
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+
+use crate::qubit_budget::{check_qubit_budget, qubit_sized_callables};
+
+fn check(file: &str, max_qubits: usize, expect: &Expect) {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let errors = check_qubit_budget(&unit.package, max_qubits, &qubit_sized_callables(&store));
+    expect.assert_debug_eq(&errors);
+}
+
+#[test]
+fn literal_precision_beyond_the_budget_is_an_error() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Unstable.PhaseEstimation;
+                operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {}
+                operation Main() : Result[] {
+                    use target = Qubit();
+                    EstimatePhase(20, RotatePower, [target])
+                }
+            }
+        "},
+        10,
+        &expect![[r#"
+            [
+                PrecisionExceedsBudget(
+                    20,
+                    10,
+                    Span {
+                        lo: 212,
+                        hi: 252,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn precision_bound_to_a_literal_is_checked() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Unstable.PhaseEstimation;
+                operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {}
+                operation Main() : Result[] {
+                    use target = Qubit();
+                    let precision = 20;
+                    EstimatePhase(precision, RotatePower, [target])
+                }
+            }
+        "},
+        10,
+        &expect![[r#"
+            [
+                PrecisionExceedsBudget(
+                    20,
+                    10,
+                    Span {
+                        lo: 240,
+                        hi: 287,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn precision_within_the_budget_is_valid() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Unstable.PhaseEstimation;
+                operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {}
+                operation Main() : Result[] {
+                    use target = Qubit();
+                    EstimatePhase(10, RotatePower, [target])
+                }
+            }
+        "},
+        10,
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn precision_of_a_parameter_is_left_to_code_generation() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Unstable.PhaseEstimation;
+                operation RotatePower(power : Int, qs : Qubit[]) : Unit is Ctl {}
+                operation Estimate(precision : Int, target : Qubit) : Result[] {
+                    EstimatePhase(precision, RotatePower, [target])
+                }
+            }
+        "},
+        10,
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn amplitude_estimation_counts_the_precision_with_the_prepared_register() {
+    check(
+        indoc! {"
+            namespace Test {
+                open Microsoft.Quantum.Unstable.PhaseEstimation;
+                operation Prepare(qs : Qubit[]) : Unit is Adj {}
+                operation Mark(qs : Qubit[]) : Unit is Ctl {}
+                operation Main() : Result[] {
+                    EstimateAmplitude(8, Prepare, Mark, 4)
+                }
+            }
+        "},
+        10,
+        &expect![[r#"
+            [
+                PrecisionExceedsBudget(
+                    12,
+                    10,
+                    Span {
+                        lo: 215,
+                        hi: 253,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
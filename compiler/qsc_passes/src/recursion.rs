@@ -0,0 +1,729 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compute_properties::{analyze_with_dynamic_params, PackageComputeProperties};
+use miette::Diagnostic;
+use qsc_data_structures::{index_map::IndexMap, message::MessageParams, span::Span};
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_hir::{
+    hir::{
+        BinOp, Block, CallableDecl, Expr, ExprKind, Functor, ItemId, ItemKind, Lit, LocalItemId,
+        Mutability, NodeId, Package, Pat, PatKind, Res, SpecBody, Stmt, StmtKind, StringComponent,
+        UnOp,
+    },
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{cmp::min, mem};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("callable `{0}` is recursive")]
+    #[diagnostic(help(
+        "recursion is not supported by the current target; the recursive callables are {1}"
+    ))]
+    #[diagnostic(code("Qsc.Recursion.Cycle"))]
+    Cycle(String, String, #[label] Span),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::Cycle(name, ..) => vec![name.clone()],
+        }
+    }
+}
+
+/// The recursion structure of the callables in a package. Calls into other packages are not
+/// followed, since packages cannot depend on each other cyclically.
+#[derive(Debug, Default)]
+pub struct Recursion {
+    /// The groups of callables that call each other recursively, including callables that call
+    /// themselves. Each group is in declaration order.
+    pub cycles: Vec<Vec<LocalItemId>>,
+    /// The most calls to each cycle's callables that are on the call stack at once while the entry
+    /// expression runs, or `None` if that isn't bounded by the static arguments of the calls.
+    /// Cycles that the entry expression never calls have a depth of zero.
+    pub depths: Vec<Option<usize>>,
+}
+
+impl Recursion {
+    /// The runtime capabilities required by the recursion in the package.
+    #[must_use]
+    pub fn capabilities(&self) -> RuntimeCapabilityFlags {
+        if self.cycles.is_empty() {
+            RuntimeCapabilityFlags::empty()
+        } else {
+            RuntimeCapabilityFlags::Recursion
+        }
+    }
+}
+
+/// Analyzes the recursion structure of the callables in a package.
+#[must_use]
+pub fn analyze_recursion(package: &Package) -> Recursion {
+    let cycles = CallGraph::new(package).cycles();
+    let depths = match &package.entry {
+        Some(entry) => Depths::new(package, &cycles).estimate(entry),
+        None => vec![None; cycles.len()],
+    };
+    Recursion { cycles, depths }
+}
+
+/// The default for the most rounds of propagating dynamic arguments through recursive callables
+/// before they are all assumed to get dynamic arguments.
+pub(super) const DEFAULT_ITERATION_LIMIT: usize = 16;
+
+/// The most calls to a cycle's callables that are followed on the call stack before the depth of
+/// the cycle is assumed not to be statically bounded.
+pub(super) const DEPTH_LIMIT: usize = 64;
+
+/// Reports the recursive callables that are reachable from the entry expression. Cycles of
+/// callables that only ever get static arguments aren't reported, since they are evaluated when
+/// the program is compiled. Whether a callable gets a dynamic argument depends on whether its
+/// callers did, which is found by iterating to a fixed point for at most `iteration_limit` rounds.
+pub(super) fn check_recursion(package: &Package, iteration_limit: usize) -> Vec<Error> {
+    let Some(entry) = &package.entry else {
+        return Vec::new();
+    };
+
+    let graph = CallGraph::new(package);
+    let mut callees = Callees::default();
+    callees.visit_expr(entry);
+    let mut reachable = FxHashSet::default();
+    let mut pending = callees.items;
+    while let Some(callable) = pending.pop() {
+        if graph.calls.contains_key(callable) && reachable.insert(callable) {
+            pending.extend(graph.calls.get(callable).into_iter().flatten().copied());
+        }
+    }
+
+    let cycles = graph.cycles();
+    let recursive: FxHashSet<_> = cycles.iter().flatten().copied().collect();
+    let dynamic = dynamic_callables(package, &recursive, iteration_limit);
+
+    let mut errors = Vec::new();
+    for cycle in cycles {
+        let evaluated = cycle.iter().all(|callable| !dynamic.contains(callable));
+        if !evaluated && cycle.iter().any(|callable| reachable.contains(callable)) {
+            let decl = callable_decl(package, cycle[0]);
+            let names = cycle
+                .iter()
+                .map(|&callable| format!("`{}`", callable_decl(package, callable).name.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            errors.push(Error::Cycle(
+                decl.name.name.to_string(),
+                names,
+                decl.name.span,
+            ));
+        }
+    }
+    errors
+}
+
+/// The recursive callables that can get a dynamic argument, either in a call whose argument is
+/// dynamic or by being passed as a value to be called elsewhere. If they haven't settled after the
+/// limit, all of the callables are assumed to.
+fn dynamic_callables(
+    package: &Package,
+    recursive: &FxHashSet<LocalItemId>,
+    iteration_limit: usize,
+) -> FxHashSet<LocalItemId> {
+    let mut dynamic = FxHashSet::default();
+    if recursive.is_empty() {
+        return dynamic;
+    }
+    for _ in 0..iteration_limit {
+        let properties = analyze_with_dynamic_params(package, &dynamic);
+        let mut calls = DynamicCalls {
+            recursive,
+            properties: &properties,
+            dynamic: dynamic.clone(),
+        };
+        calls.visit_package(package);
+        if calls.dynamic == dynamic {
+            return dynamic;
+        }
+        dynamic = calls.dynamic;
+    }
+    recursive.clone()
+}
+
+fn callable_decl(package: &Package, callable: LocalItemId) -> &CallableDecl {
+    match &package
+        .items
+        .get(callable)
+        .expect("callable should exist")
+        .kind
+    {
+        ItemKind::Callable(decl) => decl,
+        _ => panic!("item should be a callable"),
+    }
+}
+
+/// A value that is known without running the program, as far as the depth estimate follows it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Value {
+    Bool(bool),
+    Int(i64),
+    Tuple(Vec<Value>),
+    Unknown,
+}
+
+/// Estimates the recursion depth of each cycle by following the calls from the entry expression
+/// with the arguments that are known statically. Branches whose condition isn't known are both
+/// followed, so the estimate is the deepest that the recursion can go.
+struct Depths<'a> {
+    package: &'a Package,
+    cycle_indices: IndexMap<LocalItemId, usize>,
+    /// The values of the immutable locals in the current call.
+    locals: FxHashMap<NodeId, Value>,
+    /// The calls on the stack with their arguments.
+    stack: Vec<(LocalItemId, Value)>,
+    /// The number of calls to each cycle's callables that are on the stack.
+    frames: Vec<usize>,
+    /// The most calls to each cycle's callables that are nested in the current call.
+    nested: Vec<usize>,
+    unbounded: Vec<bool>,
+    calls: FxHashMap<(LocalItemId, Value), Vec<usize>>,
+}
+
+impl<'a> Depths<'a> {
+    fn new(package: &'a Package, cycles: &[Vec<LocalItemId>]) -> Self {
+        let mut cycle_indices = IndexMap::new();
+        for (index, cycle) in cycles.iter().enumerate() {
+            for &callable in cycle {
+                cycle_indices.insert(callable, index);
+            }
+        }
+        Self {
+            package,
+            cycle_indices,
+            locals: FxHashMap::default(),
+            stack: Vec::new(),
+            frames: vec![0; cycles.len()],
+            nested: vec![0; cycles.len()],
+            unbounded: vec![false; cycles.len()],
+            calls: FxHashMap::default(),
+        }
+    }
+
+    fn estimate(mut self, entry: &Expr) -> Vec<Option<usize>> {
+        self.eval(entry);
+        self.nested
+            .into_iter()
+            .zip(self.unbounded)
+            .map(|(depth, unbounded)| (!unbounded).then_some(depth))
+            .collect()
+    }
+
+    fn call(&mut self, callable: LocalItemId, arg: Value) {
+        let decl = callable_decl(self.package, callable);
+        let SpecBody::Impl(_, block) = &decl.body.body else {
+            return;
+        };
+        let cycle = self.cycle_indices.get(callable).copied();
+        let key = (callable, arg);
+        let nested = if let Some(nested) = self.calls.get(&key) {
+            nested.clone()
+        } else if let Some(cycle) =
+            cycle.filter(|&cycle| self.frames[cycle] == DEPTH_LIMIT || self.stack.contains(&key))
+        {
+            // Either the recursion doesn't end, or it goes deeper than is worth following.
+            self.unbounded[cycle] = true;
+            return;
+        } else {
+            let locals = mem::take(&mut self.locals);
+            let outer = mem::replace(&mut self.nested, vec![0; self.frames.len()]);
+            self.bind(&decl.input, key.1.clone());
+            self.stack.push(key.clone());
+            if let Some(cycle) = cycle {
+                self.frames[cycle] += 1;
+            }
+            self.eval_block(block);
+            if let Some(cycle) = cycle {
+                self.frames[cycle] -= 1;
+                self.nested[cycle] += 1;
+            }
+            self.stack.pop();
+            self.locals = locals;
+            let nested = mem::replace(&mut self.nested, outer);
+            self.calls.insert(key, nested.clone());
+            nested
+        };
+        for (outer, nested) in self.nested.iter_mut().zip(nested) {
+            *outer = (*outer).max(nested);
+        }
+    }
+
+    fn bind(&mut self, pat: &Pat, value: Value) {
+        match (&pat.kind, value) {
+            (PatKind::Bind(name), value) => {
+                self.locals.insert(name.id, value);
+            }
+            (PatKind::Tuple(pats), Value::Tuple(values)) if pats.len() == values.len() => {
+                for (pat, value) in pats.iter().zip(values) {
+                    self.bind(pat, value);
+                }
+            }
+            (PatKind::Tuple(pats), _) => {
+                for pat in pats {
+                    self.bind(pat, Value::Unknown);
+                }
+            }
+            (PatKind::Discard | PatKind::Err, _) => {}
+        }
+    }
+
+    /// The callable in the same package that a callee expression refers to, along with the number
+    /// of controlled functors applied to it.
+    fn callee(&self, expr: &Expr) -> Option<(LocalItemId, usize)> {
+        match &expr.kind {
+            ExprKind::Var(
+                Res::Item(ItemId {
+                    package: None,
+                    item,
+                }),
+                _,
+            ) => match self.package.items.get(*item).map(|item| &item.kind) {
+                Some(ItemKind::Callable(_)) => Some((*item, 0)),
+                _ => None,
+            },
+            ExprKind::UnOp(UnOp::Functor(functor), callee) => {
+                let (callable, controls) = self.callee(callee)?;
+                Some((callable, controls + usize::from(*functor == Functor::Ctl)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluates the block, returning `None` if it always returns or fails before it ends.
+    fn eval_block(&mut self, block: &Block) -> Option<Value> {
+        let mut value = Value::Tuple(Vec::new());
+        for stmt in &block.stmts {
+            value = self.eval_stmt(stmt)?;
+        }
+        Some(value)
+    }
+
+    fn eval_stmt(&mut self, stmt: &Stmt) -> Option<Value> {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => self.eval(expr),
+            StmtKind::Semi(expr) => {
+                self.eval(expr)?;
+                Some(Value::Tuple(Vec::new()))
+            }
+            StmtKind::Local(mutability, pat, expr) => {
+                let value = self.eval(expr)?;
+                let value = match mutability {
+                    Mutability::Immutable => value,
+                    Mutability::Mutable => Value::Unknown,
+                };
+                self.bind(pat, value);
+                Some(Value::Tuple(Vec::new()))
+            }
+            StmtKind::Qubit(_, pat, _, block) => {
+                self.bind(pat, Value::Unknown);
+                match block {
+                    Some(block) => self.eval_block(block),
+                    None => Some(Value::Tuple(Vec::new())),
+                }
+            }
+            StmtKind::Item(_) => Some(Value::Tuple(Vec::new())),
+        }
+    }
+
+    /// Evaluates the expression as far as its value is known, following the calls that it makes.
+    /// Returns `None` if the expression always returns or fails.
+    fn eval(&mut self, expr: &Expr) -> Option<Value> {
+        match &expr.kind {
+            ExprKind::Lit(Lit::Bool(value)) => Some(Value::Bool(*value)),
+            ExprKind::Lit(Lit::Int(value)) => Some(Value::Int(*value)),
+            ExprKind::Var(Res::Local(id), _) => {
+                Some(self.locals.get(id).cloned().unwrap_or(Value::Unknown))
+            }
+            ExprKind::Var(..) | ExprKind::UnOp(UnOp::Functor(_), _) => {
+                // A recursive callable used as a value may be called any number of times.
+                if let Some((callable, _)) = self.callee(expr) {
+                    self.set_unbounded(callable);
+                }
+                Some(Value::Unknown)
+            }
+            ExprKind::Closure(_, callable) => {
+                self.set_unbounded(*callable);
+                Some(Value::Unknown)
+            }
+            ExprKind::Call(callee, arg) => {
+                let mut arg = self.eval(arg)?;
+                if let Some((callable, controls)) = self.callee(callee) {
+                    for _ in 0..controls {
+                        arg = match arg {
+                            Value::Tuple(mut items) if items.len() == 2 => {
+                                items.pop().expect("tuple should have two items")
+                            }
+                            _ => Value::Unknown,
+                        };
+                    }
+                    self.call(callable, arg);
+                } else {
+                    self.eval(callee)?;
+                }
+                Some(Value::Unknown)
+            }
+            ExprKind::If(cond, body, otherwise) => {
+                let cond = self.eval(cond)?;
+                self.eval_if(&cond, body, otherwise.as_deref())
+            }
+            ExprKind::BinOp(BinOp::AndL, lhs, rhs) => match self.eval(lhs)? {
+                Value::Bool(false) => Some(Value::Bool(false)),
+                lhs => match (lhs, self.eval(rhs)) {
+                    (Value::Bool(true), rhs) => rhs,
+                    _ => Some(Value::Unknown),
+                },
+            },
+            ExprKind::BinOp(BinOp::OrL, lhs, rhs) => match self.eval(lhs)? {
+                Value::Bool(true) => Some(Value::Bool(true)),
+                lhs => match (lhs, self.eval(rhs)) {
+                    (Value::Bool(false), rhs) => rhs,
+                    _ => Some(Value::Unknown),
+                },
+            },
+            ExprKind::BinOp(op, lhs, rhs) => {
+                let lhs = self.eval(lhs)?;
+                let rhs = self.eval(rhs)?;
+                Some(bin_op(*op, &lhs, &rhs))
+            }
+            ExprKind::UnOp(op, operand) => Some(match (op, self.eval(operand)?) {
+                (UnOp::Neg, Value::Int(value)) => {
+                    value.checked_neg().map_or(Value::Unknown, Value::Int)
+                }
+                (UnOp::NotL, Value::Bool(value)) => Value::Bool(!value),
+                (UnOp::Pos, value) => value,
+                _ => Value::Unknown,
+            }),
+            ExprKind::Block(block) => self.eval_block(block),
+            ExprKind::Tuple(items) => items
+                .iter()
+                .map(|item| self.eval(item))
+                .collect::<Option<_>>()
+                .map(Value::Tuple),
+            ExprKind::Fail(expr) | ExprKind::Return(expr) => {
+                self.eval(expr)?;
+                None
+            }
+            _ => self.eval_parts(expr),
+        }
+    }
+
+    /// Follows the branches of a conditional, both of them if the condition isn't known.
+    fn eval_if(&mut self, cond: &Value, body: &Expr, otherwise: Option<&Expr>) -> Option<Value> {
+        let otherwise = |depths: &mut Self| match otherwise {
+            Some(otherwise) => depths.eval(otherwise),
+            None => Some(Value::Tuple(Vec::new())),
+        };
+        match cond {
+            Value::Bool(true) => self.eval(body),
+            Value::Bool(false) => otherwise(self),
+            _ => match (self.eval(body), otherwise(self)) {
+                (Some(body), Some(otherwise)) if body == otherwise => Some(body),
+                (None, None) => None,
+                _ => Some(Value::Unknown),
+            },
+        }
+    }
+
+    /// Follows the calls in the parts of an expression whose value isn't known.
+    fn eval_parts(&mut self, expr: &Expr) -> Option<Value> {
+        match &expr.kind {
+            ExprKind::For(pat, iter, block) => {
+                self.eval(iter)?;
+                self.bind(pat, Value::Unknown);
+                self.eval_block(block);
+                Some(Value::Tuple(Vec::new()))
+            }
+            ExprKind::While(cond, block) => {
+                self.eval(cond)?;
+                self.eval_block(block);
+                Some(Value::Tuple(Vec::new()))
+            }
+            ExprKind::Repeat(body, until, fixup) => {
+                self.eval_block(body)?;
+                self.eval(until)?;
+                if let Some(fixup) = fixup {
+                    self.eval_block(fixup);
+                }
+                Some(Value::Tuple(Vec::new()))
+            }
+            ExprKind::Conjugate(within, apply) => {
+                self.eval_block(within)?;
+                self.eval_block(apply)
+            }
+            ExprKind::Array(items) => {
+                for item in items {
+                    self.eval(item)?;
+                }
+                Some(Value::Unknown)
+            }
+            ExprKind::ArrayRepeat(lhs, rhs)
+            | ExprKind::Assign(lhs, rhs)
+            | ExprKind::AssignOp(_, lhs, rhs)
+            | ExprKind::AssignField(lhs, _, rhs)
+            | ExprKind::Index(lhs, rhs)
+            | ExprKind::UpdateField(lhs, _, rhs) => {
+                self.eval(lhs)?;
+                self.eval(rhs)?;
+                Some(Value::Unknown)
+            }
+            ExprKind::AssignIndex(container, index, value)
+            | ExprKind::UpdateIndex(container, index, value) => {
+                self.eval(container)?;
+                self.eval(index)?;
+                self.eval(value)?;
+                Some(Value::Unknown)
+            }
+            ExprKind::Range(start, step, end) => {
+                for expr in [start, step, end].into_iter().flatten() {
+                    self.eval(expr)?;
+                }
+                Some(Value::Unknown)
+            }
+            ExprKind::Field(expr, _) => {
+                self.eval(expr)?;
+                Some(Value::Unknown)
+            }
+            ExprKind::String(components) => {
+                for component in components {
+                    if let StringComponent::Expr(expr) = component {
+                        self.eval(expr)?;
+                    }
+                }
+                Some(Value::Unknown)
+            }
+            _ => Some(Value::Unknown),
+        }
+    }
+
+    fn set_unbounded(&mut self, callable: LocalItemId) {
+        if let Some(&cycle) = self.cycle_indices.get(callable) {
+            self.unbounded[cycle] = true;
+        }
+    }
+}
+
+fn bin_op(op: BinOp, lhs: &Value, rhs: &Value) -> Value {
+    match (lhs, rhs) {
+        (&Value::Int(lhs), &Value::Int(rhs)) => match op {
+            BinOp::Add => lhs.checked_add(rhs).map_or(Value::Unknown, Value::Int),
+            BinOp::Sub => lhs.checked_sub(rhs).map_or(Value::Unknown, Value::Int),
+            BinOp::Mul => lhs.checked_mul(rhs).map_or(Value::Unknown, Value::Int),
+            BinOp::Div => lhs.checked_div(rhs).map_or(Value::Unknown, Value::Int),
+            BinOp::Mod => lhs.checked_rem(rhs).map_or(Value::Unknown, Value::Int),
+            BinOp::Eq => Value::Bool(lhs == rhs),
+            BinOp::Neq => Value::Bool(lhs != rhs),
+            BinOp::Gt => Value::Bool(lhs > rhs),
+            BinOp::Gte => Value::Bool(lhs >= rhs),
+            BinOp::Lt => Value::Bool(lhs < rhs),
+            BinOp::Lte => Value::Bool(lhs <= rhs),
+            _ => Value::Unknown,
+        },
+        (&Value::Bool(lhs), &Value::Bool(rhs)) => match op {
+            BinOp::Eq => Value::Bool(lhs == rhs),
+            BinOp::Neq => Value::Bool(lhs != rhs),
+            _ => Value::Unknown,
+        },
+        _ => Value::Unknown,
+    }
+}
+
+/// The calls between the callables of a package.
+struct CallGraph {
+    callables: Vec<LocalItemId>,
+    calls: IndexMap<LocalItemId, Vec<LocalItemId>>,
+}
+
+impl CallGraph {
+    fn new(package: &Package) -> Self {
+        let mut callables = Vec::new();
+        let mut calls = IndexMap::new();
+        for item in package.items.values() {
+            if let ItemKind::Callable(decl) = &item.kind {
+                let mut callees = Callees::default();
+                callees.visit_callable_decl(decl);
+                callables.push(item.id);
+                calls.insert(item.id, callees.items);
+            }
+        }
+
+        // Only keep calls to callables, ignoring references to other items such as newtypes.
+        for callees in calls.values_mut() {
+            callees.retain(|callee| {
+                package
+                    .items
+                    .get(*callee)
+                    .is_some_and(|item| matches!(item.kind, ItemKind::Callable(_)))
+            });
+        }
+
+        Self { callables, calls }
+    }
+
+    fn callees(&self, callable: LocalItemId) -> &[LocalItemId] {
+        self.calls.get(callable).map_or(&[], Vec::as_slice)
+    }
+
+    /// Finds the recursive cycles using Tarjan's strongly connected components algorithm.
+    fn cycles(&self) -> Vec<Vec<LocalItemId>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            next_index: 0,
+            indices: IndexMap::new(),
+            lowlinks: IndexMap::new(),
+            stack: Vec::new(),
+            on_stack: FxHashSet::default(),
+            cycles: Vec::new(),
+        };
+        for &callable in &self.callables {
+            if !tarjan.indices.contains_key(callable) {
+                tarjan.connect(callable);
+            }
+        }
+
+        let mut cycles = tarjan.cycles;
+        for cycle in &mut cycles {
+            cycle.sort_unstable();
+        }
+        cycles.sort_unstable();
+        cycles
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    next_index: usize,
+    indices: IndexMap<LocalItemId, usize>,
+    lowlinks: IndexMap<LocalItemId, usize>,
+    stack: Vec<LocalItemId>,
+    on_stack: FxHashSet<LocalItemId>,
+    cycles: Vec<Vec<LocalItemId>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, callable: LocalItemId) {
+        self.indices.insert(callable, self.next_index);
+        self.lowlinks.insert(callable, self.next_index);
+        self.next_index += 1;
+        self.stack.push(callable);
+        self.on_stack.insert(callable);
+
+        let graph = self.graph;
+        for &callee in graph.callees(callable) {
+            if !self.indices.contains_key(callee) {
+                self.connect(callee);
+                let lowlink = min(self.lowlink(callable), self.lowlink(callee));
+                self.lowlinks.insert(callable, lowlink);
+            } else if self.on_stack.contains(&callee) {
+                let index = *self
+                    .indices
+                    .get(callee)
+                    .expect("callee should have an index");
+                let lowlink = min(self.lowlink(callable), index);
+                self.lowlinks.insert(callable, lowlink);
+            }
+        }
+
+        let index = *self
+            .indices
+            .get(callable)
+            .expect("callable should have an index");
+        if self.lowlink(callable) == index {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("stack should contain the callable");
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == callable {
+                    break;
+                }
+            }
+
+            if component.len() > 1 || graph.callees(callable).contains(&callable) {
+                self.cycles.push(component);
+            }
+        }
+    }
+
+    fn lowlink(&self, callable: LocalItemId) -> usize {
+        *self
+            .lowlinks
+            .get(callable)
+            .expect("callable should have a lowlink")
+    }
+}
+
+/// Collects the callables in the same package that are referred to by HIR nodes. Any reference
+/// counts as a potential call, since a callable passed as a value may be called by the receiver.
+#[derive(Default)]
+struct Callees {
+    items: Vec<LocalItemId>,
+}
+
+impl<'a> Visitor<'a> for Callees {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::Var(
+            Res::Item(ItemId {
+                package: None,
+                item,
+            }),
+            _,
+        ) = &expr.kind
+        {
+            self.items.push(*item);
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// Collects the recursive callables that get a dynamic argument.
+struct DynamicCalls<'a> {
+    recursive: &'a FxHashSet<LocalItemId>,
+    properties: &'a PackageComputeProperties,
+    dynamic: FxHashSet<LocalItemId>,
+}
+
+impl DynamicCalls<'_> {
+    fn callable(&self, expr: &Expr) -> Option<LocalItemId> {
+        match &expr.kind {
+            ExprKind::Var(
+                Res::Item(ItemId {
+                    package: None,
+                    item,
+                }),
+                _,
+            ) if self.recursive.contains(item) => Some(*item),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for DynamicCalls<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Call(callee, arg) if self.callable(callee).is_some() => {
+                if self.properties.expr_properties(arg.id).is_dynamic() {
+                    self.dynamic.extend(self.callable(callee));
+                }
+                self.visit_expr(arg);
+            }
+            _ => {
+                // A callable used as a value may be called with anything.
+                self.dynamic.extend(self.callable(expr));
+                visit::walk_expr(self, expr);
+            }
+        }
+    }
+}
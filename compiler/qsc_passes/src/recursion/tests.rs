@@ -0,0 +1,296 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::{ItemKind, LocalItemId, Package};
+use std::fmt::Write;
+
+use super::{analyze_recursion, check_recursion, DEFAULT_ITERATION_LIMIT, DEPTH_LIMIT};
+
+fn name(package: &Package, id: LocalItemId) -> String {
+    match &package.items.get(id).expect("item should exist").kind {
+        ItemKind::Callable(decl) => decl.name.name.to_string(),
+        _ => panic!("item should be a callable"),
+    }
+}
+
+fn compile_package(file: &str, entry: &str) -> Package {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], Some(entry.into()));
+    let unit = compile(&store, &[], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    unit.package
+}
+
+fn check(file: &str, entry: &str, expect: &Expect) {
+    let package = compile_package(file, entry);
+    let recursion = analyze_recursion(&package);
+    let mut actual = String::new();
+    for (cycle, depth) in recursion.cycles.iter().zip(&recursion.depths) {
+        let names = cycle
+            .iter()
+            .map(|&id| name(&package, id))
+            .collect::<Vec<_>>();
+        let depth = depth.map_or("unbounded".to_string(), |depth| depth.to_string());
+        let _ = writeln!(actual, "cycle: {}, depth: {depth}", names.join(", "));
+    }
+    let errors = check_recursion(&package, DEFAULT_ITERATION_LIMIT);
+    let _ = write!(actual, "{errors:#?}");
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn no_recursion() {
+    check(
+        indoc! {"
+            namespace Test {
+                function A() : Int { B() + C() }
+                function B() : Int { C() }
+                function C() : Int { 1 }
+            }
+        "},
+        "Test.A()",
+        &expect![[r#"
+            []"#]],
+    );
+}
+
+#[test]
+fn direct_recursion_with_static_arguments() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Factorial(n : Int) : Int {
+                    if n <= 1 { 1 } else { n * Factorial(n - 1) }
+                }
+                function Main() : Int { Factorial(3) }
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: Factorial, depth: 3
+            []"#]],
+    );
+}
+
+#[test]
+fn mutual_recursion_with_static_arguments() {
+    check(
+        indoc! {"
+            namespace Test {
+                function IsEven(n : Int) : Bool { n == 0 or IsOdd(n - 1) }
+                function IsOdd(n : Int) : Bool { n != 0 and IsEven(n - 1) }
+                function Main() : Bool { IsEven(4) }
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: IsEven, IsOdd, depth: 5
+            []"#]],
+    );
+}
+
+#[test]
+fn direct_recursion_with_dynamic_arguments() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Measure(q : Qubit) : Result { body intrinsic; }
+                function Factorial(n : Int) : Int {
+                    if n <= 1 { 1 } else { n * Factorial(n - 1) }
+                }
+                operation Main() : Int {
+                    use q = Qubit();
+                    Factorial(Measure(q) == One ? 3 | 4)
+                }
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: Factorial, depth: unbounded
+            [
+                Cycle(
+                    "Factorial",
+                    "`Factorial`",
+                    Span {
+                        lo: 104,
+                        hi: 113,
+                    },
+                ),
+            ]"#]],
+    );
+}
+
+#[test]
+fn recursion_is_reported_when_the_iteration_limit_is_reached() {
+    let package = compile_package(
+        indoc! {"
+            namespace Test {
+                function Factorial(n : Int) : Int {
+                    if n <= 1 { 1 } else { n * Factorial(n - 1) }
+                }
+                function Main() : Int { Factorial(3) }
+            }
+        "},
+        "Test.Main()",
+    );
+    assert!(check_recursion(&package, DEFAULT_ITERATION_LIMIT).is_empty());
+    expect![[r#"
+        [
+            Cycle(
+                "Factorial",
+                "`Factorial`",
+                Span {
+                    lo: 42,
+                    hi: 51,
+                },
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&check_recursion(&package, 0));
+}
+
+#[test]
+fn unreachable_recursion_is_not_reported() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Loop() : Unit { Loop(); }
+                function Main() : Unit {}
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: Loop, depth: 0
+            []"#]],
+    );
+}
+
+#[test]
+fn recursion_through_callable_value() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Apply(f : Int -> Int, x : Int) : Int { f(x) }
+                function Down(n : Int) : Int { if n == 0 { 0 } else { Apply(Down, n - 1) } }
+            }
+        "},
+        "Test.Down(2)",
+        &expect![[r#"
+            cycle: Down, depth: unbounded
+            [
+                Cycle(
+                    "Down",
+                    "`Down`",
+                    Span {
+                        lo: 102,
+                        hi: 106,
+                    },
+                ),
+            ]"#]],
+    );
+}
+
+#[test]
+fn recursion_in_operation_with_static_arguments() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation H(q : Qubit) : Unit { body intrinsic; }
+                operation ApplyH(n : Int, q : Qubit) : Unit {
+                    if n > 0 {
+                        H(q);
+                        ApplyH(n - 1, q);
+                    }
+                }
+                operation Main() : Unit {
+                    use q = Qubit();
+                    ApplyH(3, q);
+                }
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: ApplyH, depth: 4
+            []"#]],
+    );
+}
+
+#[test]
+fn recursion_in_operation_with_dynamic_arguments() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation M(q : Qubit) : Result { body intrinsic; }
+                operation Repeat(n : Int, q : Qubit) : Unit {
+                    if n > 0 {
+                        Repeat(M(q) == One ? n - 1 | n - 2, q);
+                    }
+                }
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Repeat(3, q);
+                }
+            }
+        "},
+        "Test.Main()",
+        &expect![[r#"
+            cycle: Repeat, depth: unbounded
+            [
+                Cycle(
+                    "Repeat",
+                    "`Repeat`",
+                    Span {
+                        lo: 99,
+                        hi: 105,
+                    },
+                ),
+            ]"#]],
+    );
+}
+
+#[test]
+fn depth_of_recursion_that_returns_early() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Fib(n : Int) : Int {
+                    if n < 2 {
+                        return n;
+                    }
+                    Fib(n - 1) + Fib(n - 2)
+                }
+            }
+        "},
+        "Test.Fib(10)",
+        &expect![[r#"
+            cycle: Fib, depth: 10
+            []"#]],
+    );
+}
+
+#[test]
+fn depth_of_recursion_beyond_the_limit_is_unbounded() {
+    let package = compile_package(
+        indoc! {"
+            namespace Test {
+                function Down(n : Int) : Int { if n == 0 { 0 } else { Down(n - 1) } }
+            }
+        "},
+        &format!("Test.Down({DEPTH_LIMIT})"),
+    );
+    assert_eq!(analyze_recursion(&package).depths, [None]);
+    let package = compile_package(
+        indoc! {"
+            namespace Test {
+                function Down(n : Int) : Int { if n == 0 { 0 } else { Down(n - 1) } }
+            }
+        "},
+        &format!("Test.Down({})", DEPTH_LIMIT - 1),
+    );
+    assert_eq!(analyze_recursion(&package).depths, [Some(DEPTH_LIMIT)]);
+}
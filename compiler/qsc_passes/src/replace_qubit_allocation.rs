@@ -16,7 +16,10 @@ use qsc_hir::{
 };
 use std::mem::take;
 
-use crate::common::{create_gen_core_ref, generated_name, IdentTemplate};
+use crate::{
+    common::{create_gen_core_ref, generated_name, IdentTemplate},
+    QubitInitialization,
+};
 
 #[derive(Debug, Clone)]
 struct QubitIdent {
@@ -27,16 +30,22 @@ struct QubitIdent {
 pub(crate) struct ReplaceQubitAllocation<'a> {
     assigner: &'a mut Assigner,
     core: &'a Table,
+    qubit_initialization: QubitInitialization,
     qubits_curr_callable: Vec<Vec<QubitIdent>>,
     qubits_curr_block: Vec<QubitIdent>,
     prefix_qubits: Vec<QubitIdent>,
 }
 
 impl<'a> ReplaceQubitAllocation<'a> {
-    pub(crate) fn new(core: &'a Table, assigner: &'a mut Assigner) -> Self {
+    pub(crate) fn new(
+        core: &'a Table,
+        assigner: &'a mut Assigner,
+        qubit_initialization: QubitInitialization,
+    ) -> Self {
         Self {
             assigner,
             core,
+            qubit_initialization,
             qubits_curr_callable: Vec::new(),
             qubits_curr_block: Vec::new(),
             prefix_qubits: Vec::new(),
@@ -241,7 +250,7 @@ impl<'a> ReplaceQubitAllocation<'a> {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "__quantum__rt__qubit_allocate",
+            self.qubit_initialization.allocate_qubit(),
             Vec::new(),
             ident.span,
         );
@@ -253,7 +262,7 @@ impl<'a> ReplaceQubitAllocation<'a> {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "AllocateQubitArray",
+            self.qubit_initialization.allocate_qubit_array(),
             Vec::new(),
             ident.span,
         );
@@ -390,7 +399,13 @@ impl MutVisitor for ReplaceQubitAllocation<'_> {
         // the entirety of a global scope, so only qubit allocations need to be generated.
         match stmt.kind.clone() {
             StmtKind::Qubit(_, pat, qubit_init, None) => {
-                stmt.kind = create_qubit_global_alloc(self.assigner, self.core, pat, qubit_init);
+                stmt.kind = create_qubit_global_alloc(
+                    self.assigner,
+                    self.core,
+                    self.qubit_initialization,
+                    pat,
+                    qubit_init,
+                );
             }
             StmtKind::Qubit(_, pat, qubit_init, Some(block)) => {
                 let (new_ids, new_stmts) =
@@ -408,16 +423,22 @@ impl MutVisitor for ReplaceQubitAllocation<'_> {
 fn create_qubit_global_alloc(
     assigner: &mut Assigner,
     core: &Table,
+    qubit_initialization: QubitInitialization,
     pat: Pat,
     qubit_init: QubitInit,
 ) -> StmtKind {
-    fn qubit_alloc_expr(assigner: &mut Assigner, core: &Table, qubit_init: QubitInit) -> Expr {
+    fn qubit_alloc_expr(
+        assigner: &mut Assigner,
+        core: &Table,
+        qubit_initialization: QubitInitialization,
+        qubit_init: QubitInit,
+    ) -> Expr {
         match qubit_init.kind {
             QubitInitKind::Array(mut expr) => {
                 let mut call_expr = create_gen_core_ref(
                     core,
                     "QIR.Runtime",
-                    "AllocateQubitArray",
+                    qubit_initialization.allocate_qubit_array(),
                     Vec::new(),
                     qubit_init.span,
                 );
@@ -433,7 +454,7 @@ fn create_qubit_global_alloc(
                 let mut call_expr = create_gen_core_ref(
                     core,
                     "QIR.Runtime",
-                    "__quantum__rt__qubit_allocate",
+                    qubit_initialization.allocate_qubit(),
                     Vec::new(),
                     qubit_init.span,
                 );
@@ -446,7 +467,7 @@ fn create_qubit_global_alloc(
                 ty: qubit_init.ty,
                 kind: ExprKind::Tuple(
                     tup.into_iter()
-                        .map(|init| qubit_alloc_expr(assigner, core, init))
+                        .map(|init| qubit_alloc_expr(assigner, core, qubit_initialization, init))
                         .collect(),
                 ),
             },
@@ -457,7 +478,7 @@ fn create_qubit_global_alloc(
     StmtKind::Local(
         Mutability::Immutable,
         pat,
-        qubit_alloc_expr(assigner, core, qubit_init),
+        qubit_alloc_expr(assigner, core, qubit_initialization, qubit_init),
     )
 }
 
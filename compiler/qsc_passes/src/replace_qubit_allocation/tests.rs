@@ -1,18 +1,27 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::replace_qubit_allocation::ReplaceQubitAllocation;
+use crate::{replace_qubit_allocation::ReplaceQubitAllocation, QubitInitialization};
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
 use qsc_hir::{mut_visit::MutVisitor, validate::Validator, visit::Visitor};
 
 fn check(file: &str, expect: &Expect) {
+    check_with_initialization(file, QubitInitialization::Zero, expect);
+}
+
+fn check_with_initialization(
+    file: &str,
+    qubit_initialization: QubitInitialization,
+    expect: &Expect,
+) {
     let store = PackageStore::new(compile::core());
     let sources = SourceMap::new([("test".into(), file.into())], None);
     let mut unit = compile(&store, &[], sources, RuntimeCapabilityFlags::all());
     assert!(unit.errors.is_empty(), "{:?}", unit.errors);
-    ReplaceQubitAllocation::new(store.core(), &mut unit.assigner).visit_package(&mut unit.package);
+    ReplaceQubitAllocation::new(store.core(), &mut unit.assigner, qubit_initialization)
+        .visit_package(&mut unit.package);
     Validator::default().visit_package(&unit.package);
     expect.assert_eq(&unit.package.to_string());
 }
@@ -821,3 +830,48 @@ fn test_unrelated_unaffected() {
                         ctl-adj: <none>"#]],
     );
 }
+
+#[test]
+fn test_arbitrary_initialization_resets_allocated_qubits() {
+    check_with_initialization(
+        indoc! { "namespace input {
+            operation Foo() : Unit {
+                use q = Qubit();
+                use qs = Qubit[2];
+            }
+        }" },
+        QubitInitialization::Arbitrary,
+        &expect![[r#"
+            Package:
+                Item 0 [0-106] (Public):
+                    Namespace (Ident 14 [10-15] "input"): Item 1
+                Item 1 [22-104] (Public):
+                    Parent: 0
+                    Callable 0 [22-104] (operation):
+                        name: Ident 1 [32-35] "Foo"
+                        input: Pat 2 [35-37] [Type Unit]: Unit
+                        output: Unit
+                        functors: empty set
+                        body: SpecDecl 3 [22-104]: Impl:
+                            Block 4 [45-104] [Type Unit]:
+                                Stmt 18 [55-71]: Local (Immutable):
+                                    Pat 19 [55-71] [Type Qubit]: Bind: Ident 7 [55-71] "q"
+                                    Expr 16 [55-71] [Type Qubit]: Call:
+                                        Expr 15 [55-71] [Type (Unit => Qubit)]: Var: Item 9 (Package 0)
+                                        Expr 17 [55-71] [Type Unit]: Unit
+                                Stmt 23 [80-98]: Local (Immutable):
+                                    Pat 24 [80-98] [Type Qubit[]]: Bind: Ident 11 [80-98] "qs"
+                                    Expr 21 [80-98] [Type Qubit]: Call:
+                                        Expr 20 [80-98] [Type (Int => Qubit[])]: Var: Item 10 (Package 0)
+                                        Expr 13 [95-96] [Type Int]: Lit: Int(2)
+                                Stmt 26 [0-0]: Semi: Expr 27 [0-0] [Type Unit]: Call:
+                                    Expr 25 [80-98] [Type (Qubit[] => Unit)]: Var: Item 7 (Package 0)
+                                    Expr 28 [80-98] [Type Qubit[]]: Var: Local 11
+                                Stmt 30 [0-0]: Semi: Expr 31 [0-0] [Type Unit]: Call:
+                                    Expr 29 [55-71] [Type (Qubit => Unit)]: Var: Item 5 (Package 0)
+                                    Expr 32 [55-71] [Type Qubit]: Var: Local 7
+                        adj: <none>
+                        ctl: <none>
+                        ctl-adj: <none>"#]],
+    );
+}
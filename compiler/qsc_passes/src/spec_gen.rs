@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 pub(crate) mod adj_gen;
+mod cache;
 mod ctl_gen;
 
 #[cfg(test)]
@@ -10,8 +11,9 @@ mod tests;
 use crate::{id_update::NodeIdRefresher, invert_block::adj_invert_block};
 
 use self::{adj_gen::AdjDistrib, ctl_gen::CtlDistrib};
+pub use cache::SpecCache;
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     assigner::Assigner,
     global::Table,
@@ -64,14 +66,30 @@ pub enum Error {
     MissingBody(#[label] Span),
 }
 
-/// Generates specializations for the given compile unit, updating it in-place.
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::CtlGen(error) => error.message_params(),
+            Self::AdjGen(error) => error.message_params(),
+            Self::InvalidAdjGen(_)
+            | Self::InvalidBodyGen(_)
+            | Self::InvalidCtlGen(_)
+            | Self::InvalidCtlAdjGen(_)
+            | Self::MissingBody(_) => Vec::new(),
+        }
+    }
+}
+
+/// Generates specializations for the given compile unit, updating it in-place. Specializations
+/// are taken from the cache for callables that it has seen unchanged before.
 pub(super) fn generate_specs(
     core: &Table,
     package: &mut Package,
     assigner: &mut Assigner,
+    cache: &mut SpecCache,
 ) -> Vec<Error> {
     generate_placeholders(package, assigner);
-    generate_spec_impls(core, package, assigner)
+    generate_spec_impls(core, package, assigner, cache)
 }
 
 fn generate_placeholders(package: &mut Package, assigner: &mut Assigner) {
@@ -139,10 +157,16 @@ fn is_self_adjoint(decl: &CallableDecl) -> bool {
     matches!(&decl.adj, Some(s) if matches!(&s.body, SpecBody::Gen(SpecGen::Slf)))
 }
 
-fn generate_spec_impls(core: &Table, package: &mut Package, assigner: &mut Assigner) -> Vec<Error> {
+fn generate_spec_impls(
+    core: &Table,
+    package: &mut Package,
+    assigner: &mut Assigner,
+    cache: &mut SpecCache,
+) -> Vec<Error> {
     let mut pass = SpecImplPass {
         core,
         assigner,
+        cache,
         errors: Vec::new(),
     };
     pass.visit_package(package);
@@ -152,6 +176,7 @@ fn generate_spec_impls(core: &Table, package: &mut Package, assigner: &mut Assig
 struct SpecImplPass<'a> {
     core: &'a Table,
     assigner: &'a mut Assigner,
+    cache: &'a mut SpecCache,
     errors: Vec<Error>,
 }
 
@@ -209,6 +234,26 @@ impl<'a> SpecImplPass<'a> {
 
 impl<'a> MutVisitor for SpecImplPass<'a> {
     fn visit_callable_decl(&mut self, decl: &mut CallableDecl) {
+        let generated = cache::to_generate(decl);
+        if !generated.contains(&true) {
+            self.generate(decl);
+            return;
+        }
+
+        let key = cache::fingerprint(decl);
+        if self.cache.reuse(key, decl, self.assigner) {
+            return;
+        }
+        let errors = self.errors.len();
+        self.generate(decl);
+        if self.errors.len() == errors {
+            self.cache.insert(key, decl, generated);
+        }
+    }
+}
+
+impl SpecImplPass<'_> {
+    fn generate(&mut self, decl: &mut CallableDecl) {
         let body = &decl.body;
 
         match body.body {
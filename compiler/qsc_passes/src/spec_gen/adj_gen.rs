@@ -3,7 +3,7 @@
 
 use crate::logic_sep;
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     hir::{CallableKind, Expr, ExprKind, Functor, NodeId, UnOp},
     mut_visit::{walk_expr, MutVisitor},
@@ -23,6 +23,15 @@ pub enum Error {
     LogicSep(logic_sep::Error),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::MissingAdjFunctor(_) => Vec::new(),
+            Self::LogicSep(error) => error.message_params(),
+        }
+    }
+}
+
 pub(crate) struct AdjDistrib {
     pub(crate) errors: Vec<Error>,
 }
@@ -0,0 +1,281 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Reuses the specializations generated for callables that were seen before. Notebook cells and
+//! REPL lines are compiled one at a time, and running a cell again redefines each of its callables
+//! even if only one of them changed, so the specializations of the others are taken from the
+//! cache instead of being generated again.
+//!
+//! Callables are keyed by a hash of their declarations that leaves out node IDs and measures spans
+//! from the start of the declaration, so an unchanged redefinition hashes the same as the original
+//! wherever it is in the sources. Items are hashed by their IDs, so a callable whose callees were
+//! redefined gets its specializations generated again, since they depend on those of its callees.
+
+use crate::id_update::NodeIdRefresher;
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    assigner::Assigner,
+    hir::{
+        Block, CallableDecl, Expr, ExprKind, Ident, NodeId, Pat, PatKind, QubitInit, QubitInitKind,
+        Res, SpecBody, SpecDecl, Stmt, StmtKind, StringComponent,
+    },
+    mut_visit::MutVisitor,
+    visit::{self, Visitor},
+};
+use rustc_hash::FxHashMap;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    mem::discriminant,
+};
+
+/// The specializations generated for callables, by the hashes of their declarations.
+#[derive(Default)]
+pub struct SpecCache {
+    entries: FxHashMap<u64, Entry>,
+}
+
+struct Entry {
+    /// The span of the declaration the specializations were generated for.
+    span: Span,
+    /// The bindings of the input of that declaration, which the specializations refer to.
+    params: Vec<NodeId>,
+    /// The generated specializations. Specializations that were written out are left as they are,
+    /// so they aren't kept.
+    adj: Option<SpecDecl>,
+    ctl: Option<SpecDecl>,
+    ctl_adj: Option<SpecDecl>,
+}
+
+impl SpecCache {
+    /// Replaces the specializations of the declaration that are still to be generated with the
+    /// cached ones for the same declaration, returning whether it was in the cache.
+    pub(super) fn reuse(&self, key: u64, decl: &mut CallableDecl, assigner: &mut Assigner) -> bool {
+        let Some(entry) = self.entries.get(&key) else {
+            return false;
+        };
+
+        let params = entry.params.iter().copied().zip(bindings(&decl.input));
+        let mut refresher = NodeIdRefresher::with_replacements(assigner, params.collect());
+        let mut shift = ShiftSpans {
+            from: entry.span.lo,
+            to: decl.span.lo,
+        };
+        for (spec, cached) in [
+            (&mut decl.adj, &entry.adj),
+            (&mut decl.ctl, &entry.ctl),
+            (&mut decl.ctl_adj, &entry.ctl_adj),
+        ] {
+            if let Some(cached) = cached {
+                let mut cached = cached.clone();
+                shift.visit_spec_decl(&mut cached);
+                refresher.visit_spec_decl(&mut cached);
+                *spec = Some(cached);
+            }
+        }
+        true
+    }
+
+    /// Keeps the specializations generated for the declaration, which had the given specializations
+    /// still to be generated when it was hashed.
+    pub(super) fn insert(&mut self, key: u64, decl: &CallableDecl, generated: [bool; 3]) {
+        let kept = |spec: &Option<SpecDecl>, generated| spec.clone().filter(|_| generated);
+        self.entries.insert(
+            key,
+            Entry {
+                span: decl.span,
+                params: bindings(&decl.input),
+                adj: kept(&decl.adj, generated[0]),
+                ctl: kept(&decl.ctl, generated[1]),
+                ctl_adj: kept(&decl.ctl_adj, generated[2]),
+            },
+        );
+    }
+}
+
+/// Which of the adjoint, controlled and controlled adjoint specializations of the declaration are
+/// still to be generated.
+pub(super) fn to_generate(decl: &CallableDecl) -> [bool; 3] {
+    [&decl.adj, &decl.ctl, &decl.ctl_adj].map(|spec| {
+        spec.as_ref()
+            .is_some_and(|spec| matches!(spec.body, SpecBody::Gen(_)))
+    })
+}
+
+/// Hashes the declaration without its node IDs and with its spans relative to its start.
+pub(super) fn fingerprint(decl: &CallableDecl) -> u64 {
+    let mut fingerprinter = Fingerprinter {
+        hasher: DefaultHasher::new(),
+        start: decl.span.lo,
+        locals: FxHashMap::default(),
+    };
+    fingerprinter.visit_callable_decl(decl);
+    fingerprinter.hasher.finish()
+}
+
+fn bindings(pat: &Pat) -> Vec<NodeId> {
+    match &pat.kind {
+        PatKind::Bind(ident) => vec![ident.id],
+        PatKind::Tuple(pats) => pats.iter().flat_map(bindings).collect(),
+        PatKind::Discard | PatKind::Err => Vec::new(),
+    }
+}
+
+struct Fingerprinter {
+    hasher: DefaultHasher,
+    start: u32,
+    /// The variables bound so far, numbered in the order they are bound, which stands in for their
+    /// node IDs.
+    locals: FxHashMap<NodeId, usize>,
+}
+
+impl Fingerprinter {
+    fn write(&mut self, value: impl Hash) {
+        value.hash(&mut self.hasher);
+    }
+
+    fn debug(&mut self, value: &impl Debug) {
+        self.write(format!("{value:?}"));
+    }
+
+    fn span(&mut self, span: Span) {
+        self.write(span.lo.wrapping_sub(self.start));
+        self.write(span.hi.wrapping_sub(self.start));
+    }
+
+    fn local(&mut self, id: NodeId) {
+        self.write(self.locals.get(&id).copied());
+    }
+}
+
+impl<'a> Visitor<'a> for Fingerprinter {
+    fn visit_callable_decl(&mut self, decl: &'a CallableDecl) {
+        self.span(decl.span);
+        self.debug(&decl.kind);
+        self.debug(&decl.generics);
+        self.write(decl.output.to_string());
+        self.debug(&decl.functors);
+        self.write([&decl.adj, &decl.ctl, &decl.ctl_adj].map(Option::is_some));
+        visit::walk_callable_decl(self, decl);
+    }
+
+    fn visit_spec_decl(&mut self, decl: &'a SpecDecl) {
+        self.span(decl.span);
+        match &decl.body {
+            SpecBody::Gen(gen) => self.debug(gen),
+            SpecBody::Impl(input, _) => self.write(input.is_some()),
+        }
+        visit::walk_spec_decl(self, decl);
+    }
+
+    fn visit_block(&mut self, block: &'a Block) {
+        self.span(block.span);
+        self.write(block.ty.to_string());
+        self.write(block.stmts.len());
+        visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        self.span(stmt.span);
+        self.write(discriminant(&stmt.kind));
+        match &stmt.kind {
+            StmtKind::Item(item) => self.debug(item),
+            StmtKind::Local(mutability, ..) => self.debug(mutability),
+            StmtKind::Qubit(source, _, _, scope) => {
+                self.debug(source);
+                self.write(scope.is_some());
+            }
+            StmtKind::Expr(_) | StmtKind::Semi(_) => {}
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.span(expr.span);
+        self.write(expr.ty.to_string());
+        self.write(discriminant(&expr.kind));
+        match &expr.kind {
+            ExprKind::Array(items) | ExprKind::Tuple(items) => self.write(items.len()),
+            ExprKind::AssignOp(op, ..) | ExprKind::BinOp(op, ..) => self.debug(op),
+            ExprKind::UnOp(op, _) => self.debug(op),
+            ExprKind::AssignField(_, field, _)
+            | ExprKind::Field(_, field)
+            | ExprKind::UpdateField(_, field, _) => self.debug(field),
+            ExprKind::Closure(captures, item) => {
+                for &id in captures {
+                    self.local(id);
+                }
+                self.debug(item);
+            }
+            ExprKind::If(_, _, otherwise) => self.write(otherwise.is_some()),
+            ExprKind::Lit(lit) => self.debug(lit),
+            ExprKind::Range(start, step, end) => {
+                self.write([start, step, end].map(Option::is_some));
+            }
+            ExprKind::Repeat(_, _, fixup) => self.write(fixup.is_some()),
+            ExprKind::String(components) => {
+                for component in components {
+                    match component {
+                        StringComponent::Expr(_) => self.write(None::<&str>),
+                        StringComponent::Lit(lit) => self.write(Some(&**lit)),
+                    }
+                }
+            }
+            ExprKind::Var(res, args) => {
+                match res {
+                    Res::Local(id) => self.local(*id),
+                    Res::Item(_) | Res::Err => self.debug(res),
+                }
+                self.debug(args);
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        self.span(pat.span);
+        self.write(pat.ty.to_string());
+        self.write(discriminant(&pat.kind));
+        match &pat.kind {
+            PatKind::Bind(ident) => {
+                let index = self.locals.len();
+                self.locals.insert(ident.id, index);
+            }
+            PatKind::Tuple(pats) => self.write(pats.len()),
+            PatKind::Discard | PatKind::Err => {}
+        }
+        visit::walk_pat(self, pat);
+    }
+
+    fn visit_qubit_init(&mut self, init: &'a QubitInit) {
+        self.span(init.span);
+        self.write(discriminant(&init.kind));
+        if let QubitInitKind::Tuple(inits) = &init.kind {
+            self.write(inits.len());
+        }
+        visit::walk_qubit_init(self, init);
+    }
+
+    fn visit_ident(&mut self, ident: &'a Ident) {
+        self.span(ident.span);
+        self.write(&*ident.name);
+    }
+}
+
+/// Moves the spans of a cached specialization from the declaration it was generated for to the
+/// same place in an unchanged redefinition.
+struct ShiftSpans {
+    from: u32,
+    to: u32,
+}
+
+impl MutVisitor for ShiftSpans {
+    fn visit_span(&mut self, span: &mut Span) {
+        if span.lo >= self.from {
+            span.lo = span.lo - self.from + self.to;
+            span.hi = span.hi - self.from + self.to;
+        }
+    }
+}
@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{message::MessageParams, span::Span};
 use qsc_hir::{
     hir::{CallableKind, Expr, ExprKind, Functor, NodeId, Res, UnOp},
     mut_visit::{walk_expr, MutVisitor},
@@ -18,6 +18,12 @@ pub enum Error {
     MissingCtlFunctor(#[label] Span),
 }
 
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
 pub(super) struct CtlDistrib {
     pub(super) ctls: Res,
     pub(super) errors: Vec<Error>,
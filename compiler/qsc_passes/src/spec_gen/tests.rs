@@ -6,10 +6,16 @@
 
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
-use qsc_hir::{validate::Validator, visit::Visitor};
+use qsc_frontend::compile::{
+    self, compile, CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::{
+    hir::{CallableDecl, ItemKind, Package},
+    validate::Validator,
+    visit::Visitor,
+};
 
-use crate::spec_gen::generate_specs;
+use crate::spec_gen::{cache::fingerprint, generate_placeholders, generate_specs, SpecCache};
 
 fn check(file: &str, expect: &Expect) {
     let store = PackageStore::new(compile::core());
@@ -17,7 +23,12 @@ fn check(file: &str, expect: &Expect) {
     let mut unit = compile(&store, &[], sources, RuntimeCapabilityFlags::all());
     assert!(unit.errors.is_empty(), "{:?}", unit.errors);
 
-    let errors = generate_specs(store.core(), &mut unit.package, &mut unit.assigner);
+    let errors = generate_specs(
+        store.core(),
+        &mut unit.package,
+        &mut unit.assigner,
+        &mut SpecCache::default(),
+    );
     Validator::default().visit_package(&unit.package);
     if errors.is_empty() {
         expect.assert_eq(&unit.package.to_string());
@@ -2117,3 +2128,80 @@ fn op_array_unsupported_functors_with_lambdas() {
         "#]],
     );
 }
+
+fn compile_operation(store: &PackageStore, source: &str) -> CompileUnit {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let unit = compile(store, &[], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    unit
+}
+
+fn operation(package: &mut Package) -> &mut CallableDecl {
+    package
+        .items
+        .values_mut()
+        .find_map(|item| match &mut item.kind {
+            ItemKind::Callable(decl) if &*decl.name.name == "Op" => Some(decl),
+            _ => None,
+        })
+        .expect("package should have the operation")
+}
+
+#[test]
+fn unchanged_redefinitions_reuse_cached_specializations() {
+    let source = indoc! {"
+        namespace Test {
+            operation A(q : Qubit) : Unit is Adj + Ctl {
+                body ... {}
+                adjoint ... {}
+                controlled (ctls, ...) {}
+                controlled adjoint (ctls, ...) {}
+            }
+            operation Op(q : Qubit) : Unit is Adj + Ctl {
+                let n = 1;
+                A(q);
+            }
+        }
+    "};
+    let moved = format!("\n\n{source}");
+    let store = PackageStore::new(compile::core());
+    let mut cache = SpecCache::default();
+    let mut first = compile_operation(&store, source);
+    let errors = generate_specs(
+        store.core(),
+        &mut first.package,
+        &mut first.assigner,
+        &mut cache,
+    );
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let mut probe = compile_operation(&store, &moved);
+    generate_placeholders(&mut probe.package, &mut probe.assigner);
+    let decl = operation(&mut probe.package);
+    assert!(cache.reuse(fingerprint(decl), decl, &mut probe.assigner));
+    let mut changed = compile_operation(&store, &source.replace("let n = 1", "let n = 2"));
+    generate_placeholders(&mut changed.package, &mut changed.assigner);
+    let decl = operation(&mut changed.package);
+    assert!(!cache.reuse(fingerprint(decl), decl, &mut changed.assigner));
+
+    // Cached specializations are the same as freshly generated ones, at the new location.
+    let mut cached = compile_operation(&store, &moved);
+    generate_specs(
+        store.core(),
+        &mut cached.package,
+        &mut cached.assigner,
+        &mut cache,
+    );
+    Validator::default().visit_package(&cached.package);
+    let mut fresh = compile_operation(&store, &moved);
+    generate_specs(
+        store.core(),
+        &mut fresh.package,
+        &mut fresh.assigner,
+        &mut SpecCache::default(),
+    );
+    assert_eq!(
+        fingerprint(operation(&mut cached.package)),
+        fingerprint(operation(&mut fresh.package))
+    );
+}
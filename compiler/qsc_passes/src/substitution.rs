@@ -0,0 +1,244 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Substitution of one callable for another depending on the target, such as an exact `Exp` when
+//! simulating and a Trotterized decomposition of it when generating code for hardware. A
+//! [`SubstitutionTable`] names the callables by their fully qualified names for each target
+//! profile. It is resolved against the global names that a package can see, and then references
+//! to each callable in the package are replaced with references to its substitute. Dependencies
+//! are compiled on their own, so their calls aren't substituted.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::message::MessageParams;
+use qsc_frontend::compile::{ConfigAttr, RuntimeCapabilityFlags};
+use qsc_hir::{
+    global::{Table, Term},
+    hir::{Expr, ExprKind, ItemId, Res},
+    mut_visit::{walk_expr, MutVisitor},
+    ty::Arrow,
+};
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("callable `{0}` in the substitution table is not defined")]
+    #[diagnostic(help("substitutions name callables by their fully qualified names"))]
+    #[diagnostic(code("Qsc.Substitution.MissingCallable"))]
+    MissingCallable(String),
+
+    #[error("substitute `{1}` for callable `{0}` is not defined")]
+    #[diagnostic(help("substitutions name callables by their fully qualified names"))]
+    #[diagnostic(code("Qsc.Substitution.MissingSubstitute"))]
+    MissingSubstitute(String, String),
+
+    #[error("`{1}` cannot be substituted for `{0}` because their signatures differ")]
+    #[diagnostic(help(
+        "a substitute must take and return the same types and support the same functors as the callable it replaces, and neither can be generic"
+    ))]
+    #[diagnostic(code("Qsc.Substitution.SignatureMismatch"))]
+    SignatureMismatch(String, String),
+}
+
+impl MessageParams for Error {
+    fn message_params(&self) -> Vec<String> {
+        match self {
+            Self::MissingCallable(callable) => vec![callable.clone()],
+            Self::MissingSubstitute(callable, substitute)
+            | Self::SignatureMismatch(callable, substitute) => {
+                vec![substitute.clone(), callable.clone()]
+            }
+        }
+    }
+}
+
+/// A substitution of one callable for another on a target.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Substitution {
+    /// The target profile that the substitution applies to.
+    pub target: ConfigAttr,
+    /// The fully qualified name of the callable to replace, such as
+    /// `Microsoft.Quantum.Intrinsic.Exp`.
+    pub callable: Arc<str>,
+    /// The fully qualified name of the callable to use instead.
+    pub substitute: Arc<str>,
+}
+
+/// What a target has least of, which decides the implementations that the generic operations of
+/// `Microsoft.Quantum.Unstable.Arithmetic`, such as `IncByLE` and `AddLE`, and the table lookup
+/// `Microsoft.Quantum.Unstable.TableLookup.Lookup` are lowered to there. The resource estimator
+/// counts whichever implementation the program is lowered to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArithmeticBudget {
+    /// Qubits are scarce, so adders work in place without auxiliary qubits, at the cost of about
+    /// twice the T gates of the adders for [`ArithmeticBudget::TGates`].
+    Qubits,
+    /// T gates are scarce, so adders keep their carries in one auxiliary qubit per bit and
+    /// uncompute them with measurements instead of T gates.
+    TGates,
+    /// Time is scarce, so adders compute their carries with a lookahead tree of logarithmic depth,
+    /// at the cost of more T gates and auxiliary qubits.
+    Depth,
+}
+
+impl ArithmeticBudget {
+    /// The generic arithmetic operations with the implementations they are lowered to on a target
+    /// with the budget, by fully qualified name.
+    #[must_use]
+    pub fn implementations(self) -> [(&'static str, &'static str); 2] {
+        let (inc_by_le, add_le) = match self {
+            Self::Qubits => (
+                "Microsoft.Quantum.Unstable.Arithmetic.RippleCarryTTKIncByLE",
+                "Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCGAddLE",
+            ),
+            Self::TGates => (
+                "Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCGIncByLE",
+                "Microsoft.Quantum.Unstable.Arithmetic.RippleCarryCGAddLE",
+            ),
+            Self::Depth => (
+                "Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSIncByLE",
+                "Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSAddLE",
+            ),
+        };
+        [
+            ("Microsoft.Quantum.Unstable.Arithmetic.IncByLE", inc_by_le),
+            ("Microsoft.Quantum.Unstable.Arithmetic.AddLE", add_le),
+        ]
+    }
+
+    /// The generic table lookup with the implementation it is lowered to on a target with the
+    /// budget, by fully qualified name. Where qubits are scarce, lookups use a SELECT network
+    /// without auxiliary registers. Otherwise they use a SELECT-SWAP network with blocks of about
+    /// the square root of the number of entries, which divides the T gates of the lookup by the
+    /// block size at the cost of one auxiliary register per entry of a block.
+    #[must_use]
+    pub fn table_lookup(self) -> (&'static str, &'static str) {
+        let lookup = match self {
+            Self::Qubits => "Microsoft.Quantum.Unstable.TableLookup.Select",
+            Self::TGates | Self::Depth => {
+                "Microsoft.Quantum.Unstable.TableLookup.BalancedSelectSwap"
+            }
+        };
+        ("Microsoft.Quantum.Unstable.TableLookup.Lookup", lookup)
+    }
+}
+
+/// The substitutions of callables for each target profile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubstitutionTable {
+    substitutions: Vec<Substitution>,
+}
+
+impl SubstitutionTable {
+    /// Substitutes `substitute` for `callable` on the target, in place of any earlier substitution
+    /// for the callable on that target.
+    pub fn insert(&mut self, target: ConfigAttr, callable: &str, substitute: &str) {
+        self.substitutions
+            .retain(|other| other.target != target || &*other.callable != callable);
+        self.substitutions.push(Substitution {
+            target,
+            callable: callable.into(),
+            substitute: substitute.into(),
+        });
+    }
+
+    /// Lowers the generic arithmetic operations on the target to the implementations that suit its
+    /// budget.
+    pub fn insert_arithmetic(&mut self, target: ConfigAttr, budget: ArithmeticBudget) {
+        for (callable, substitute) in budget.implementations() {
+            self.insert(target, callable, substitute);
+        }
+    }
+
+    /// Lowers the generic table lookup on the target to the implementation that suits its budget.
+    /// Table lookups are only defined for the unrestricted target. Arrays cannot be indexed by a
+    /// qubit register, so this only lowers explicit calls to `Lookup`, and the resource estimator
+    /// counts the lookup with the rest of the program rather than reporting it separately.
+    pub fn insert_table_lookup(&mut self, target: ConfigAttr, budget: ArithmeticBudget) {
+        let (callable, substitute) = budget.table_lookup();
+        self.insert(target, callable, substitute);
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.substitutions.is_empty()
+    }
+
+    /// The substitutions on the target, in the order they were inserted.
+    pub fn for_target(&self, target: ConfigAttr) -> impl Iterator<Item = &Substitution> {
+        self.substitutions
+            .iter()
+            .filter(move |substitution| substitution.target == target)
+    }
+
+    /// Resolves the substitutions against the global names, keeping those of the target with the
+    /// capabilities. That is the base or adaptive profile when the capabilities are exactly those
+    /// of the profile, and the unrestricted profile otherwise.
+    #[must_use]
+    pub fn resolve(
+        &self,
+        capabilities: RuntimeCapabilityFlags,
+        globals: &Table,
+    ) -> (FxHashMap<ItemId, ItemId>, Vec<Error>) {
+        let target = [ConfigAttr::Base, ConfigAttr::Adaptive]
+            .into_iter()
+            .find(|&profile| RuntimeCapabilityFlags::from(profile) == capabilities)
+            .unwrap_or(ConfigAttr::Unrestricted);
+
+        let mut resolved = FxHashMap::default();
+        let mut errors = Vec::new();
+        for substitution in self.for_target(target) {
+            let callable = substitution.callable.to_string();
+            let substitute = substitution.substitute.to_string();
+            let Some(original) = resolve_term(globals, &callable) else {
+                errors.push(Error::MissingCallable(callable));
+                continue;
+            };
+            let Some(replacement) = resolve_term(globals, &substitute) else {
+                errors.push(Error::MissingSubstitute(callable, substitute));
+                continue;
+            };
+            match (signature(original), signature(replacement)) {
+                (Some(original_ty), Some(replacement_ty)) if original_ty == replacement_ty => {
+                    resolved.insert(original.id, replacement.id);
+                }
+                _ => errors.push(Error::SignatureMismatch(callable, substitute)),
+            }
+        }
+        (resolved, errors)
+    }
+}
+
+fn resolve_term<'a>(globals: &'a Table, name: &str) -> Option<&'a Term> {
+    let (namespace, name) = name.rsplit_once('.')?;
+    globals.resolve_term(namespace, name)
+}
+
+/// The type of the callable, or `None` if it is generic.
+fn signature(term: &Term) -> Option<Arrow> {
+    let scheme = match term.id.package {
+        Some(package) => term.scheme.with_package(package),
+        None => term.scheme.clone(),
+    };
+    scheme.instantiate(&[]).ok()
+}
+
+/// Replaces references to callables with references to their substitutes.
+pub(super) struct Substitute<'a> {
+    pub(super) substitutions: &'a FxHashMap<ItemId, ItemId>,
+}
+
+impl MutVisitor for Substitute<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Var(Res::Item(id), _) = &mut expr.kind {
+            if let Some(&substitute) = self.substitutions.get(id) {
+                *id = substitute;
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
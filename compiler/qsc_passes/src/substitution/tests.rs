@@ -0,0 +1,188 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+use qsc_hir::{
+    global::{self, Table},
+    hir::{Expr, ExprKind, ItemId, Package, PackageId, Res},
+    mut_visit::MutVisitor,
+    visit::{walk_expr, Visitor},
+};
+use std::fmt::Write;
+
+use super::{ArithmeticBudget, Substitute, SubstitutionTable};
+
+static SOURCE: &str = indoc! {"
+    namespace Test {
+        operation TrotterExp(paulis : Pauli[], theta : Double, qubits : Qubit[]) : Unit is Adj + Ctl {
+            for q in qubits {
+                Rz(theta, q);
+            }
+        }
+
+        operation Main() : Unit {
+            use qs = Qubit[2];
+            Exp([PauliZ, PauliZ], 0.5, qs);
+        }
+    }
+"};
+
+fn compile_with_globals() -> (Package, Table) {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), SOURCE.into())], None);
+    let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let globals = [PackageId::CORE, std]
+        .into_iter()
+        .flat_map(|id| {
+            let dependency = store.get(id).expect("package should be in store");
+            global::iter_package(Some(id), &dependency.package)
+        })
+        .chain(global::iter_package(None, &unit.package))
+        .collect();
+    (unit.package, globals)
+}
+
+fn table() -> SubstitutionTable {
+    let mut table = SubstitutionTable::default();
+    table.insert(
+        ConfigAttr::Base,
+        "Microsoft.Quantum.Intrinsic.Exp",
+        "Test.TrotterExp",
+    );
+    table
+}
+
+/// The items that the package refers to.
+fn references(package: &Package) -> Vec<ItemId> {
+    struct Items(Vec<ItemId>);
+
+    impl<'a> Visitor<'a> for Items {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            if let ExprKind::Var(Res::Item(id), _) = &expr.kind {
+                self.0.push(*id);
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let mut items = Items(Vec::new());
+    items.visit_package(package);
+    items.0
+}
+
+fn check_errors(table: &SubstitutionTable, expect: &Expect) {
+    let (_, globals) = compile_with_globals();
+    let (_, errors) = table.resolve(RuntimeCapabilityFlags::empty(), &globals);
+    let mut actual = String::new();
+    for error in errors {
+        let _ = writeln!(actual, "{error}");
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn calls_are_substituted_on_the_target() {
+    let (mut package, globals) = compile_with_globals();
+    let (substitutions, errors) = table().resolve(RuntimeCapabilityFlags::empty(), &globals);
+    assert!(errors.is_empty(), "{errors:?}");
+    let (&exp, &trotter) = substitutions
+        .iter()
+        .next()
+        .expect("substitution should be resolved");
+    assert!(exp.package.is_some());
+    assert_eq!(trotter.package, None);
+
+    assert!(references(&package).contains(&exp));
+    Substitute {
+        substitutions: &substitutions,
+    }
+    .visit_package(&mut package);
+    let references = references(&package);
+    assert!(!references.contains(&exp));
+    assert!(references.contains(&trotter));
+}
+
+#[test]
+fn substitutions_only_apply_on_their_target() {
+    let (_, globals) = compile_with_globals();
+    let (substitutions, errors) = table().resolve(RuntimeCapabilityFlags::all(), &globals);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert!(substitutions.is_empty());
+
+    let (substitutions, _) = table().resolve(ConfigAttr::Adaptive.into(), &globals);
+    assert!(substitutions.is_empty());
+}
+
+#[test]
+fn later_substitutions_replace_earlier_ones() {
+    let mut table = table();
+    table.insert(
+        ConfigAttr::Base,
+        "Microsoft.Quantum.Intrinsic.Exp",
+        "Test.Main",
+    );
+    assert_eq!(table.for_target(ConfigAttr::Base).count(), 1);
+    check_errors(
+        &table,
+        &expect![[r"
+            `Test.Main` cannot be substituted for `Microsoft.Quantum.Intrinsic.Exp` because their signatures differ
+        "]],
+    );
+}
+
+#[test]
+fn missing_mappings_are_reported() {
+    let mut table = SubstitutionTable::default();
+    table.insert(ConfigAttr::Base, "Test.Missing", "Test.TrotterExp");
+    table.insert(
+        ConfigAttr::Base,
+        "Microsoft.Quantum.Intrinsic.Exp",
+        "Test.Nowhere",
+    );
+    table.insert(ConfigAttr::Base, "Exp", "TrotterExp");
+    check_errors(
+        &table,
+        &expect![[r"
+            callable `Test.Missing` in the substitution table is not defined
+            substitute `Test.Nowhere` for callable `Microsoft.Quantum.Intrinsic.Exp` is not defined
+            callable `Exp` in the substitution table is not defined
+        "]],
+    );
+}
+
+#[test]
+fn arithmetic_is_lowered_to_implementations_for_each_budget() {
+    for budget in [
+        ArithmeticBudget::Qubits,
+        ArithmeticBudget::TGates,
+        ArithmeticBudget::Depth,
+    ] {
+        let mut table = SubstitutionTable::default();
+        table.insert_arithmetic(ConfigAttr::Base, budget);
+        assert_eq!(table.for_target(ConfigAttr::Base).count(), 2);
+        check_errors(&table, &expect![""]);
+    }
+}
+
+#[test]
+fn table_lookup_is_lowered_to_implementations_for_each_budget() {
+    let (_, globals) = compile_with_globals();
+    for budget in [
+        ArithmeticBudget::Qubits,
+        ArithmeticBudget::TGates,
+        ArithmeticBudget::Depth,
+    ] {
+        let mut table = SubstitutionTable::default();
+        table.insert_table_lookup(ConfigAttr::Unrestricted, budget);
+        let (substitutions, errors) = table.resolve(RuntimeCapabilityFlags::all(), &globals);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(substitutions.len(), 1);
+    }
+}
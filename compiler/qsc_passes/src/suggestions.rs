@@ -0,0 +1,183 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Suggests rewrites of the code that errors report the target can't run, so that tools can offer
+//! them as fixes. Flipping a qubit when its measurement result is `One` resets it, which any target
+//! can do without branching.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{baseprofck, compute_properties, Error};
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::{PackageStore, SourceMap};
+use qsc_hir::{
+    global::{self, Kind},
+    hir::{self, BinOp, Expr, ExprKind, Lit, Package, Res, Stmt, StmtKind},
+    visit::{self, Visitor},
+};
+
+const INTRINSIC_NAMESPACE: &str = "Microsoft.Quantum.Intrinsic";
+
+/// A rewrite of the sources that avoids a runtime capability the target lacks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Suggestion {
+    /// What the rewrite does.
+    pub title: String,
+    /// The spans of the sources to replace, with the text to put in their place, in source order.
+    pub edits: Vec<(Span, String)>,
+}
+
+/// Suggests a rewrite for an error that reports code the target can't run, if there is one.
+#[must_use]
+pub fn suggest_fix(
+    store: &PackageStore,
+    package: &Package,
+    sources: &SourceMap,
+    error: &Error,
+) -> Option<Suggestion> {
+    match error {
+        Error::BaseProfCk(
+            baseprofck::Error::ResultComparison(span) | baseprofck::Error::ResultLiteral(span),
+        )
+        | Error::ComputeProperties(compute_properties::Error::Branch(_, span, _)) => {
+            suggest_reset(store, package, sources, *span)
+        }
+        _ => None,
+    }
+}
+
+/// Replaces `if M(q) == One { X(q); }` with `Reset(q);`, where the error is about the `if` or its
+/// condition.
+fn suggest_reset(
+    store: &PackageStore,
+    package: &Package,
+    sources: &SourceMap,
+    span: Span,
+) -> Option<Suggestion> {
+    let mut finder = Finder {
+        span,
+        found: None,
+        is_bare_stmt: false,
+    };
+    finder.visit_package(package);
+    let expr = finder.found?;
+    let ExprKind::If(cond, then, None) = &expr.kind else {
+        return None;
+    };
+    let ExprKind::BinOp(BinOp::Eq, lhs, rhs) = &cond.kind else {
+        return None;
+    };
+    let ((ExprKind::Call(measure, measured), ExprKind::Lit(Lit::Result(hir::Result::One)))
+    | (ExprKind::Lit(Lit::Result(hir::Result::One)), ExprKind::Call(measure, measured))) =
+        (&lhs.kind, &rhs.kind)
+    else {
+        return None;
+    };
+    let ExprKind::Block(block) = &then.kind else {
+        return None;
+    };
+    let [Stmt {
+        kind: StmtKind::Expr(flip) | StmtKind::Semi(flip),
+        ..
+    }] = block.stmts.as_slice()
+    else {
+        return None;
+    };
+    let ExprKind::Call(flip, flipped) = &flip.kind else {
+        return None;
+    };
+    if !is_intrinsic(store, measure, "M")
+        || !is_intrinsic(store, flip, "X")
+        || !same_local(measured, flipped)
+    {
+        return None;
+    }
+
+    // The reset is qualified the way the measurement is, so that it resolves wherever `M` does.
+    let (qualifier, _) = split_name(text(sources, measure.span)?);
+    let reset = format!("{qualifier}Reset({})", text(sources, measured.span)?);
+    let semi = if finder.is_bare_stmt { ";" } else { "" };
+    Some(Suggestion {
+        title: format!("Replace with `{reset}`"),
+        edits: vec![(expr.span, format!("{reset}{semi}"))],
+    })
+}
+
+/// Whether the expression refers to the operation with the name in the intrinsic namespace.
+fn is_intrinsic(store: &PackageStore, callee: &Expr, name: &str) -> bool {
+    let ExprKind::Var(Res::Item(id), _) = &callee.kind else {
+        return false;
+    };
+    id.package
+        .and_then(|package_id| store.get(package_id).map(|unit| (package_id, unit)))
+        .is_some_and(|(package_id, unit)| {
+            global::iter_package(Some(package_id), &unit.package).any(|global| {
+                matches!(global.kind, Kind::Term(term) if term.id == *id)
+                    && global.namespace.as_ref() == INTRINSIC_NAMESPACE
+                    && global.name.as_ref() == name
+            })
+        })
+}
+
+/// Whether both expressions are the same local variable.
+fn same_local(lhs: &Expr, rhs: &Expr) -> bool {
+    match (&lhs.kind, &rhs.kind) {
+        (ExprKind::Var(Res::Local(lhs), _), ExprKind::Var(Res::Local(rhs), _)) => lhs == rhs,
+        _ => false,
+    }
+}
+
+/// Finds the `if` expression with the span, or whose condition contains the span.
+struct Finder<'a> {
+    span: Span,
+    found: Option<&'a Expr>,
+    /// Whether the expression found is a statement without a trailing semicolon.
+    is_bare_stmt: bool,
+}
+
+impl Finder<'_> {
+    fn matches(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::If(cond, ..) => {
+                self.found.is_none() && (expr.span == self.span || contains(cond.span, self.span))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Finder<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Expr(expr) = &stmt.kind {
+            if self.matches(expr) {
+                self.is_bare_stmt = true;
+            }
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if self.matches(expr) {
+            self.found = Some(expr);
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.lo <= inner.lo && inner.hi <= outer.hi
+}
+
+fn text(sources: &SourceMap, span: Span) -> Option<&str> {
+    let source = sources.find_by_offset(span.lo)?;
+    source
+        .contents
+        .get((span.lo - source.offset) as usize..(span.hi - source.offset) as usize)
+}
+
+/// Splits a name into its namespace qualifier, including the trailing dot, and the name itself.
+fn split_name(name: &str) -> (&str, &str) {
+    name.rfind('.')
+        .map_or(("", name), |dot| (&name[..=dot], &name[dot + 1..]))
+}
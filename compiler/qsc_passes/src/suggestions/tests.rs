@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_frontend::compile::{
+    self, compile, ConfigAttr, PackageStore, RuntimeCapabilityFlags, SourceMap,
+};
+
+use super::suggest_fix;
+use crate::{baseprofck::check_base_profile_compliance, Error};
+
+fn check(file: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
+    let mut store = PackageStore::new(compile::core());
+    let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
+    let sources = SourceMap::new([("test".into(), file.into())], Some("Test.Main()".into()));
+    let unit = compile(&store, &[std], sources, RuntimeCapabilityFlags::all());
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let errors = check_base_profile_compliance(&unit.package, capabilities);
+    assert!(!errors.is_empty(), "program should need more capabilities");
+    let suggestion = errors.into_iter().find_map(|error| {
+        suggest_fix(
+            &store,
+            &unit.package,
+            &unit.sources,
+            &Error::BaseProfCk(error),
+        )
+    });
+    let offset = unit
+        .sources
+        .find_by_name("test")
+        .expect("test source should exist")
+        .offset;
+    let actual = match suggestion {
+        Some(suggestion) => {
+            let mut fixed = file.to_string();
+            for (span, text) in suggestion.edits.iter().rev() {
+                fixed.replace_range(
+                    (span.lo - offset) as usize..(span.hi - offset) as usize,
+                    text,
+                );
+            }
+            format!("{}\n{fixed}", suggestion.title)
+        }
+        None => "no suggestion".to_string(),
+    };
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn flip_on_one_is_replaced_with_reset() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                    if M(q) == One { X(q); }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            Replace with `Reset(q)`
+            namespace Test {
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                    Reset(q);
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn flip_of_another_qubit_is_not_replaced() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Unit {
+                    use (q1, q2) = (Qubit(), Qubit());
+                    H(q1);
+                    if One == M(q1) { X(q2); }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect!["no suggestion"],
+    );
+}
+
+#[test]
+fn flip_with_other_operations_named_m_and_x_is_not_replaced() {
+    check(
+        indoc! {"
+            namespace Other {
+                operation M(q : Qubit) : Result { Microsoft.Quantum.Intrinsic.M(q) }
+                operation X(q : Qubit) : Unit { Y(q); }
+            }
+            namespace Test {
+                open Other as O;
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                    if O.M(q) == One { O.X(q); }
+                }
+            }
+        "},
+        RuntimeCapabilityFlags::empty(),
+        &expect!["no suggestion"],
+    );
+}
+
+#[test]
+fn unbounded_loop_has_no_suggestion() {
+    check(
+        indoc! {"
+            namespace Test {
+                operation Main() : Result {
+                    use q = Qubit();
+                    while M(q) != One {
+                        H(q);
+                    }
+                    M(q)
+                }
+            }
+        "},
+        ConfigAttr::Adaptive.into(),
+        &expect!["no suggestion"],
+    );
+}
@@ -5,7 +5,7 @@
 //! Only a sync API is provided for now, because our binary targets
 //! are only sync at the time of writing this (qsi and qsc).
 
-use crate::{DirEntry, EntryType, FileSystem};
+use crate::{project::normalize, DirEntry, EntryType, FileSystem};
 use miette::{Context, IntoDiagnostic};
 use std::convert::Infallible;
 use std::fs::DirEntry as StdEntry;
@@ -91,4 +91,20 @@ impl FileSystem for StdFs {
             .collect::<Result<_, _>>()
             .map_err(crate::Error::from)?)
     }
+
+    /// Resolves symbolic links and makes the path absolute. A file that doesn't exist yet, such
+    /// as an unsaved new file, is canonicalized by its folder.
+    fn canonicalize(&self, path: &Path) -> PathBuf {
+        if let Ok(canonical) = std::fs::canonicalize(path) {
+            return canonical;
+        }
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        match (std::fs::canonicalize(dir), path.file_name()) {
+            (Ok(dir), Some(name)) => dir.join(name),
+            _ => normalize(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())),
+        }
+    }
 }
@@ -10,6 +10,8 @@ mod error;
 mod fs;
 mod js;
 mod manifest;
+mod mem;
+mod overlay;
 mod project;
 
 pub use error::Error;
@@ -17,6 +19,8 @@ pub use error::Error;
 pub use fs::StdFs;
 pub use js::{JSFileEntry, ProjectSystemCallbacks};
 pub use manifest::{Manifest, ManifestDescriptor, MANIFEST_FILE_NAME};
+pub use mem::{MemEntry, MemFs};
+pub use overlay::{Overlay, OverlayEntry};
 #[cfg(feature = "async")]
 pub use project::FileSystemAsync;
 pub use project::{DirEntry, EntryType, FileSystem, Project};
@@ -9,7 +9,8 @@ use std::{
     fs::{self, DirEntry, FileType},
 };
 
-use serde::Deserialize;
+use qsc_data_structures::edition::Edition;
+use serde::{de, Deserialize, Deserializer};
 use std::{path::PathBuf, sync::Arc};
 
 pub const MANIFEST_FILE_NAME: &str = "qsharp.json";
@@ -19,6 +20,21 @@ pub const MANIFEST_FILE_NAME: &str = "qsharp.json";
 pub struct Manifest {
     pub author: Option<String>,
     pub license: Option<String>,
+    /// The feature flags that are enabled, which make the items marked with
+    /// `@Config(feature = "name")` part of the compilation.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// The edition of the language that the project is written in, such as `"2024"`. Projects
+    /// without one are compiled in the first edition.
+    #[serde(default, deserialize_with = "deserialize_edition")]
+    pub edition: Edition,
+}
+
+fn deserialize_edition<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Edition, D::Error> {
+    let edition = String::deserialize(deserializer)?;
+    edition
+        .parse()
+        .map_err(|()| de::Error::custom(format!("unknown edition `{edition}`")))
 }
 
 /// Describes the contents and location of a Q# manifest file.
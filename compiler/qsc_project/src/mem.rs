@@ -0,0 +1,132 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! This module contains a project implementation backed by an in-memory map of files.
+//! It lets hosts without an OS filesystem, editors with unsaved buffers, and tests
+//! supply project contents directly.
+
+#[cfg(feature = "async")]
+use crate::FileSystemAsync;
+use crate::{DirEntry, EntryType, FileSystem};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// An in-memory filesystem. Folders are implied by the paths of the files they contain.
+#[derive(Default, Debug, Clone)]
+pub struct MemFs {
+    files: BTreeMap<PathBuf, Arc<str>>,
+}
+
+impl MemFs {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a file, returning its previous contents.
+    pub fn insert(
+        &mut self,
+        path: impl Into<PathBuf>,
+        contents: impl Into<Arc<str>>,
+    ) -> Option<Arc<str>> {
+        self.files.insert(path.into(), contents.into())
+    }
+
+    /// Removes a file, returning its contents.
+    pub fn remove(&mut self, path: &Path) -> Option<Arc<str>> {
+        self.files.remove(path)
+    }
+}
+
+impl<P: Into<PathBuf>, C: Into<Arc<str>>> FromIterator<(P, C)> for MemFs {
+    fn from_iter<I: IntoIterator<Item = (P, C)>>(iter: I) -> Self {
+        Self {
+            files: iter
+                .into_iter()
+                .map(|(path, contents)| (path.into(), contents.into()))
+                .collect(),
+        }
+    }
+}
+
+/// A file or folder in a [MemFs].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) entry_type: EntryType,
+}
+
+impl DirEntry for MemEntry {
+    type Error = Infallible;
+
+    fn entry_type(&self) -> Result<EntryType, Self::Error> {
+        Ok(self.entry_type)
+    }
+
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+impl FileSystem for MemFs {
+    type Entry = MemEntry;
+
+    fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        let contents = self.files.get(path).ok_or_else(|| {
+            miette::ErrReport::msg(format!("could not read source file `{}`", path.display()))
+        })?;
+        Ok((path.to_string_lossy().into(), contents.clone()))
+    }
+
+    fn list_directory(&self, path: &Path) -> miette::Result<Vec<MemEntry>> {
+        let mut entries = Vec::new();
+        let mut folders = BTreeSet::new();
+        for file in self.files.keys() {
+            let Ok(relative) = file.strip_prefix(path) else {
+                continue;
+            };
+            let mut components = relative.components();
+            match (components.next(), components.next()) {
+                (Some(name), None) => entries.push(MemEntry {
+                    path: path.join(name),
+                    entry_type: EntryType::File,
+                }),
+                (Some(name), Some(_)) => {
+                    folders.insert(path.join(name));
+                }
+                (None, _) => {}
+            }
+        }
+
+        if entries.is_empty() && folders.is_empty() {
+            return Err(miette::ErrReport::msg(format!(
+                "could not list directory `{}`",
+                path.display()
+            )));
+        }
+
+        entries.extend(folders.into_iter().map(|path| MemEntry {
+            path,
+            entry_type: EntryType::Folder,
+        }));
+        Ok(entries)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait(?Send)]
+impl FileSystemAsync for MemFs {
+    type Entry = MemEntry;
+
+    async fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        FileSystem::read_file(self, path)
+    }
+
+    async fn list_directory(&self, path: &Path) -> miette::Result<Vec<MemEntry>> {
+        FileSystem::list_directory(self, path)
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! This module contains a project implementation that reads through to another one,
+//! except for the files that are given contents in memory. Editor integrations that
+//! run the command line tools use it to check buffers with unsaved changes, so that
+//! the tools see the same workspace as the language service.
+
+use crate::{DirEntry, EntryType, FileSystem, MemEntry, MemFs};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// A filesystem whose files can be replaced, or added, with contents in memory.
+/// Overlay paths are matched by their canonical paths in the underlying filesystem,
+/// so a file can be overlaid and read through differently spelled paths.
+#[derive(Default, Debug, Clone)]
+pub struct Overlay<F> {
+    fs: F,
+    files: MemFs,
+}
+
+impl<F: FileSystem> Overlay<F> {
+    #[must_use]
+    pub fn new(fs: F) -> Self {
+        Self {
+            fs,
+            files: MemFs::new(),
+        }
+    }
+
+    /// Gives the file at the path the contents, returning its previous overlay contents.
+    pub fn insert(
+        &mut self,
+        path: impl Into<PathBuf>,
+        contents: impl Into<Arc<str>>,
+    ) -> Option<Arc<str>> {
+        let path = self.fs.canonicalize(&path.into());
+        self.files.insert(path, contents)
+    }
+}
+
+/// A file or folder in an [Overlay].
+#[derive(Debug)]
+pub enum OverlayEntry<E> {
+    /// An entry of the underlying filesystem.
+    Base(E),
+    /// An entry that only exists in the overlay.
+    Overlay(MemEntry),
+}
+
+impl<E: DirEntry> DirEntry for OverlayEntry<E> {
+    type Error = E::Error;
+
+    fn entry_type(&self) -> Result<EntryType, Self::Error> {
+        match self {
+            Self::Base(entry) => entry.entry_type(),
+            Self::Overlay(entry) => Ok(entry.entry_type),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        match self {
+            Self::Base(entry) => entry.path(),
+            Self::Overlay(entry) => entry.path(),
+        }
+    }
+}
+
+impl<F: FileSystem> FileSystem for Overlay<F> {
+    type Entry = OverlayEntry<F::Entry>;
+
+    fn read_file(&self, path: &Path) -> miette::Result<(Arc<str>, Arc<str>)> {
+        match self.files.read_file(&self.fs.canonicalize(path)) {
+            // The file keeps the name it was read by.
+            Ok((_, contents)) => Ok((path.to_string_lossy().into(), contents)),
+            Err(_) => self.fs.read_file(path),
+        }
+    }
+
+    fn list_directory(&self, path: &Path) -> miette::Result<Vec<Self::Entry>> {
+        // Overlaid entries are listed under the path as it was given, like the entries of the
+        // underlying filesystem.
+        let overlay = self
+            .files
+            .list_directory(&self.fs.canonicalize(path))
+            .map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|mut entry| {
+                        if let Some(name) = entry.path.file_name() {
+                            entry.path = path.join(name);
+                        }
+                        entry
+                    })
+                    .collect::<Vec<_>>()
+            });
+        let mut entries = match self.fs.list_directory(path) {
+            Ok(entries) => entries.into_iter().map(OverlayEntry::Base).collect(),
+            // A folder that only holds unsaved files doesn't exist in the underlying filesystem.
+            Err(_) if overlay.is_ok() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let base = entries.iter().map(DirEntry::path).collect::<Vec<_>>();
+        entries.extend(
+            overlay
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|entry| !base.contains(&entry.path()))
+                .map(OverlayEntry::Overlay),
+        );
+        Ok(entries)
+    }
+
+    fn canonicalize(&self, path: &Path) -> PathBuf {
+        self.fs.canonicalize(path)
+    }
+}
@@ -1,9 +1,9 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::manifest::ManifestDescriptor;
+use crate::manifest::{ManifestDescriptor, MANIFEST_FILE_NAME};
 use std::{
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
@@ -97,6 +97,21 @@ pub trait FileSystemAsync {
         }
         Ok(files)
     }
+    /// Starting from the given path, search it and its ancestors for a [MANIFEST_FILE_NAME] file.
+    /// Paths that can't be listed as directories, such as the path of a source file, are skipped.
+    async fn find_manifest(&self, start: &Path) -> miette::Result<Option<ManifestDescriptor>> {
+        for dir in start.ancestors() {
+            let Ok(listing) = self.list_directory(dir).await else {
+                continue;
+            };
+            if let Some(entry) = listing.into_iter().find(is_manifest) {
+                let (_, contents) = self.read_file(&entry.path()).await?;
+                return parse_manifest(dir, &contents).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
     /// Given a [ManifestDescriptor], load project sources.
     async fn load_project(&self, manifest: &ManifestDescriptor) -> miette::Result<Project> {
         let project_path = manifest.manifest_dir.clone();
@@ -116,6 +131,18 @@ pub trait FileSystemAsync {
     }
 }
 
+fn is_manifest<Entry: DirEntry>(entry: &Entry) -> bool {
+    matches!(entry.entry_type(), Ok(EntryType::File)) && entry.entry_name() == MANIFEST_FILE_NAME
+}
+
+fn parse_manifest(dir: &Path, contents: &str) -> miette::Result<ManifestDescriptor> {
+    let manifest = serde_json::from_str(contents).map_err(crate::Error::from)?;
+    Ok(ManifestDescriptor {
+        manifest,
+        manifest_dir: dir.to_path_buf(),
+    })
+}
+
 /// Filters out any hidden files (files that start with '.')
 fn filter_hidden_files<Entry: DirEntry>(
     listing: impl Iterator<Item = Entry>,
@@ -123,6 +150,24 @@ fn filter_hidden_files<Entry: DirEntry>(
     listing.filter(|x| !x.entry_name().starts_with('.'))
 }
 
+/// Resolves the `.` and `..` components of the path. A `..` that would leave the root, or the
+/// start of a relative path, is kept.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(normal.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                normal.pop();
+            }
+            component => normal.push(component),
+        }
+    }
+    normal
+}
+
 /// This trait is used to abstract filesystem logic with regards to Q# projects.
 /// A Q# project requires some multi-file structure, but that may not actually be
 /// an OS filesystem. It could be a virtual filesystem on vscode.dev, or perhaps a
@@ -135,6 +180,14 @@ pub trait FileSystem {
 
     /// Given a path, list its directory contents (if any).
     fn list_directory(&self, path: &Path) -> miette::Result<Vec<Self::Entry>>;
+
+    /// Given a path, return the path that the filesystem knows the same file by, so that
+    /// differently spelled paths to a file can be compared. By default, `.` and `..` components
+    /// are resolved without looking at the filesystem.
+    fn canonicalize(&self, path: &Path) -> PathBuf {
+        normalize(path)
+    }
+
     /// Given an initial path, fetch files matching <initial_path>/**/*.qs
     fn collect_project_sources(&self, initial_path: &Path) -> miette::Result<Vec<Self::Entry>> {
         let listing = self.list_directory(initial_path)?;
@@ -170,6 +223,21 @@ pub trait FileSystem {
         Ok(files)
     }
 
+    /// Starting from the given path, search it and its ancestors for a [MANIFEST_FILE_NAME] file.
+    /// Paths that can't be listed as directories, such as the path of a source file, are skipped.
+    fn find_manifest(&self, start: &Path) -> miette::Result<Option<ManifestDescriptor>> {
+        for dir in start.ancestors() {
+            let Ok(listing) = self.list_directory(dir) else {
+                continue;
+            };
+            if let Some(entry) = listing.into_iter().find(is_manifest) {
+                let (_, contents) = self.read_file(&entry.path())?;
+                return parse_manifest(dir, &contents).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
     /// Given a [ManifestDescriptor], load project sources.
     fn load_project(&self, manifest: &ManifestDescriptor) -> miette::Result<Project> {
         let project_path = manifest.manifest_dir.clone();
@@ -9,6 +9,9 @@ mod harness;
 
 use expect_test::expect;
 use harness::check;
+use qsc_data_structures::edition::Edition;
+use qsc_project::{FileSystem, MemFs, Overlay, StdFs};
+use std::path::Path;
 
 #[test]
 fn basic_manifest() {
@@ -35,6 +38,8 @@ fn basic_manifest() {
                         "Microsoft",
                     ),
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -65,6 +70,8 @@ fn circular_imports() {
                         "Microsoft",
                     ),
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -95,6 +102,8 @@ fn different_files_same_manifest() {
                         "Microsoft",
                     ),
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -115,6 +124,8 @@ fn empty_manifest() {
                 manifest: Manifest {
                     author: None,
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -147,6 +158,8 @@ fn folder_structure() {
                 manifest: Manifest {
                     author: None,
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -174,6 +187,8 @@ fn hidden_files() {
                 manifest: Manifest {
                     author: None,
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
@@ -205,7 +220,169 @@ fn peer_file() {
                 manifest: Manifest {
                     author: None,
                     license: None,
+                    features: [],
+                    edition: Q2023,
                 },
             }"#]],
     )
 }
+
+#[test]
+fn in_memory_project() {
+    let fs: MemFs = [
+        ("/proj/qsharp.json", r#"{ "author": "Microsoft" }"#),
+        ("/proj/src/Main.qs", "namespace Main {}"),
+        ("/proj/src/utils/Util.qs", "namespace Util {}"),
+        ("/proj/src/.Hidden.qs", "namespace Hidden {}"),
+        ("/proj/src/notes.txt", "not a source file"),
+        ("/other/src/Other.qs", "namespace Other {}"),
+    ]
+    .into_iter()
+    .collect();
+
+    let manifest = fs
+        .find_manifest(Path::new("/proj/src/utils/Util.qs"))
+        .expect("manifest search should succeed")
+        .expect("manifest should be found");
+    assert_eq!(manifest.manifest_dir, Path::new("/proj"));
+
+    let project = fs.load_project(&manifest).expect("project should load");
+    expect![[r#"
+        Project {
+            sources: [
+                (
+                    "/proj/src/Main.qs",
+                    "namespace Main {}",
+                ),
+                (
+                    "/proj/src/utils/Util.qs",
+                    "namespace Util {}",
+                ),
+            ],
+            manifest: Manifest {
+                author: Some(
+                    "Microsoft",
+                ),
+                license: None,
+                features: [],
+                edition: Q2023,
+            },
+        }"#]]
+    .assert_eq(&format!("{project:#?}"));
+}
+
+#[test]
+fn in_memory_project_without_manifest() {
+    let fs: MemFs = [("/proj/src/Main.qs", "namespace Main {}")]
+        .into_iter()
+        .collect();
+    let manifest = fs
+        .find_manifest(Path::new("/proj/src"))
+        .expect("manifest search should succeed");
+    assert!(manifest.is_none());
+}
+
+#[test]
+fn manifest_edition() {
+    let fs: MemFs = [
+        ("/proj/qsharp.json", r#"{ "edition": "2024" }"#),
+        ("/old/qsharp.json", r#"{ "edition": "2000" }"#),
+    ]
+    .into_iter()
+    .collect();
+    let manifest = fs
+        .find_manifest(Path::new("/proj"))
+        .expect("manifest search should succeed")
+        .expect("manifest should be found");
+    assert_eq!(manifest.manifest.edition, Edition::Q2024);
+
+    let error = fs
+        .find_manifest(Path::new("/old"))
+        .expect_err("unknown edition should be rejected");
+    assert!(
+        error.to_string().contains("unknown edition `2000`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn overlay_replaces_and_adds_files() {
+    let fs: MemFs = [
+        ("/proj/qsharp.json", "{}"),
+        ("/proj/src/Main.qs", "namespace Main {}"),
+        ("/proj/src/Util.qs", "namespace Util {}"),
+    ]
+    .into_iter()
+    .collect();
+    let mut fs = Overlay::new(fs);
+    fs.insert("/proj/src/Main.qs", "namespace Main { /* unsaved */ }");
+    fs.insert("/proj/src/new/New.qs", "namespace New {}");
+
+    let manifest = fs
+        .find_manifest(Path::new("/proj/src"))
+        .expect("manifest search should succeed")
+        .expect("manifest should be found");
+    let project = fs.load_project(&manifest).expect("project should load");
+    expect![[r#"
+        [
+            (
+                "/proj/src/Main.qs",
+                "namespace Main { /* unsaved */ }",
+            ),
+            (
+                "/proj/src/Util.qs",
+                "namespace Util {}",
+            ),
+            (
+                "/proj/src/new/New.qs",
+                "namespace New {}",
+            ),
+        ]"#]]
+    .assert_eq(&format!("{:#?}", project.sources));
+}
+
+#[test]
+fn overlay_matches_differently_spelled_paths() {
+    let fs: MemFs = [("/proj/src/Main.qs", "namespace Main {}")]
+        .into_iter()
+        .collect();
+    let mut fs = Overlay::new(fs);
+    fs.insert(
+        "/proj/src/../src/./Main.qs",
+        "namespace Main { /* unsaved */ }",
+    );
+
+    let (name, contents) = fs
+        .read_file(Path::new("/proj/./src/Main.qs"))
+        .expect("file should be read");
+    assert_eq!(&*name, "/proj/./src/Main.qs");
+    assert_eq!(&*contents, "namespace Main { /* unsaved */ }");
+}
+
+#[test]
+fn overlay_canonicalizes_paths_in_the_filesystem() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/projects/basic_manifest");
+    let main = root.join("src/Main.qs");
+    let mut fs = Overlay::new(StdFs);
+    fs.insert(root.join("src/../src/Main.qs"), "namespace Main {}");
+    fs.insert(root.join("src/./Unsaved.qs"), "namespace Unsaved {}");
+
+    let manifest = fs
+        .find_manifest(&root)
+        .expect("manifest search should succeed")
+        .expect("manifest should be found");
+    let project = fs.load_project(&manifest).expect("project should load");
+    let sources = project
+        .sources
+        .iter()
+        .map(|(name, contents)| (Path::new(&**name).to_path_buf(), &**contents))
+        .collect::<Vec<_>>();
+    assert!(
+        sources.contains(&(main, "namespace Main {}")),
+        "{sources:?}"
+    );
+    assert!(
+        sources.contains(&(root.join("src/Unsaved.qs"), "namespace Unsaved {}")),
+        "{sources:?}"
+    );
+}
@@ -0,0 +1,272 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation::Compilation,
+    completion::{get_first_non_whitespace_in_source, get_indent},
+    protocol::CodeAction,
+    qsc_utils::into_range,
+};
+use miette::Diagnostic;
+use qsc::{
+    ast::{
+        self,
+        visit::{self, Visitor},
+    },
+    compile::{suggest_fix, ErrorKind},
+    hir::{global, Visibility},
+    line_column::{Encoding, Position, Range},
+    Span,
+};
+use rustc_hash::FxHashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+/// Returns the actions that tidy up the `open` statements of every document in the compilation:
+/// one that removes the opens that no name is resolved through, and one that adds an open for
+/// each unresolved name that exactly one namespace defines. Actions that would change nothing
+/// are left out.
+pub(crate) fn get_workspace_open_actions(
+    compilation: &Compilation,
+    position_encoding: Encoding,
+) -> Vec<CodeAction> {
+    let mut finder = OpenFinder {
+        compilation,
+        insert_at: None,
+        unused: Vec::new(),
+        missing: Vec::new(),
+    };
+    finder.visit_package(&compilation.user_unit().ast.package);
+
+    let removals = finder
+        .unused
+        .into_iter()
+        .map(|span| (line_span(compilation, span), String::new()))
+        .collect::<Vec<_>>();
+
+    let candidates = global_namespaces(compilation);
+    let mut opens = BTreeMap::<u32, BTreeSet<Arc<str>>>::new();
+    for (insert_at, key) in finder.missing {
+        if let Some([namespace]) = candidates.get(&key).map(Vec::as_slice) {
+            opens
+                .entry(insert_at)
+                .or_default()
+                .insert(Arc::clone(namespace));
+        }
+    }
+    let insertions = opens
+        .into_iter()
+        .map(|(offset, namespaces)| {
+            let indent = get_indent(compilation, offset);
+            let text = namespaces
+                .iter()
+                .map(|namespace| format!("open {namespace};{indent}"))
+                .collect();
+            (
+                Span {
+                    lo: offset,
+                    hi: offset,
+                },
+                text,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    [
+        ("Remove unused opens", removals),
+        ("Add missing opens", insertions),
+    ]
+    .into_iter()
+    .filter(|(_, edits)| !edits.is_empty())
+    .map(|(title, edits)| CodeAction {
+        title: title.to_string(),
+        edits: group_by_document(compilation, position_encoding, edits),
+    })
+    .collect()
+}
+
+/// Returns the actions that rewrite the code at the position so that the target can run it, for
+/// the errors there that report code needing runtime capabilities the target lacks.
+pub(crate) fn get_capability_fixes(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Vec<CodeAction> {
+    let offset =
+        compilation.source_position_to_package_offset(source_name, position, position_encoding);
+    let unit = compilation.user_unit();
+    let mut suggestions = Vec::new();
+    for error in &compilation.errors {
+        let ErrorKind::Pass(pass_error) = error.error() else {
+            continue;
+        };
+        let at_position = error.labels().into_iter().flatten().any(|label| {
+            let lo = label.offset();
+            (lo..=lo + label.len()).contains(&(offset as usize))
+        });
+        if at_position {
+            if let Some(suggestion) = suggest_fix(
+                &compilation.package_store,
+                &unit.package,
+                &unit.sources,
+                pass_error,
+            ) {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+    }
+
+    suggestions
+        .into_iter()
+        .map(|suggestion| CodeAction {
+            title: suggestion.title,
+            edits: group_by_document(compilation, position_encoding, suggestion.edits),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum NameKind {
+    Term,
+    Ty,
+}
+
+struct OpenFinder<'a> {
+    compilation: &'a Compilation,
+    /// Where to insert opens for the names in the namespace being visited.
+    insert_at: Option<u32>,
+    /// The spans of the opens that no name is resolved through.
+    unused: Vec<Span>,
+    /// The unqualified names that aren't resolved, with where to insert an open for them.
+    missing: Vec<(u32, (NameKind, Arc<str>))>,
+}
+
+impl OpenFinder<'_> {
+    fn visit_name(&mut self, kind: NameKind, path: &ast::Path) {
+        let names = &self.compilation.user_unit().ast.names;
+        if path.namespace.is_none() && names.get(path.id).is_none() {
+            // Names outside of namespaces are in notebook cells, which get opens at the top.
+            let insert_at = self.insert_at.unwrap_or_else(|| {
+                get_first_non_whitespace_in_source(self.compilation, path.span.lo)
+            });
+            self.missing
+                .push((insert_at, (kind, Arc::clone(&path.name.name))));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for OpenFinder<'_> {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        self.insert_at = namespace.items.first().map(|item| item.span.lo);
+        visit::walk_namespace(self, namespace);
+        self.insert_at = None;
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Open(name, _) = &*item.kind {
+            if !self
+                .compilation
+                .user_unit()
+                .ast
+                .locals
+                .is_open_used(name.span)
+            {
+                self.unused.push(item.span);
+            }
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if let ast::ExprKind::Path(path) = &*expr.kind {
+            self.visit_name(NameKind::Term, path);
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_ty(&mut self, ty: &'a ast::Ty) {
+        if let ast::TyKind::Path(path) = &*ty.kind {
+            self.visit_name(NameKind::Ty, path);
+        }
+        visit::walk_ty(self, ty);
+    }
+}
+
+/// The namespaces that define each public name of every package in the compilation.
+fn global_namespaces(compilation: &Compilation) -> FxHashMap<(NameKind, Arc<str>), Vec<Arc<str>>> {
+    let mut namespaces = FxHashMap::<_, Vec<Arc<str>>>::default();
+    for (id, unit) in compilation.package_store.iter() {
+        for global in global::iter_package(Some(id), &unit.package) {
+            let kind = match global.kind {
+                global::Kind::Term(_) => NameKind::Term,
+                global::Kind::Ty(_) => NameKind::Ty,
+                global::Kind::Namespace => continue,
+            };
+            if global.visibility != Visibility::Public {
+                continue;
+            }
+            let defined_in = namespaces.entry((kind, global.name)).or_default();
+            if !defined_in.contains(&global.namespace) {
+                defined_in.push(global.namespace);
+            }
+        }
+    }
+    namespaces
+}
+
+/// Extends the span to the whole lines it is on when nothing else is on them, so that removing
+/// it doesn't leave a blank line behind.
+fn line_span(compilation: &Compilation, span: Span) -> Span {
+    let source = compilation
+        .user_unit()
+        .sources
+        .find_by_offset(span.lo)
+        .expect("source should exist in the user source map");
+    let contents = &source.contents;
+    let lo = (span.lo - source.offset) as usize;
+    let hi = (span.hi - source.offset) as usize;
+    let line_start = contents[..lo].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = contents[hi..]
+        .find('\n')
+        .map_or(contents.len(), |i| hi + i + 1);
+    if contents[line_start..lo].trim().is_empty() && contents[hi..line_end].trim().is_empty() {
+        let offset =
+            |i: usize| source.offset + u32::try_from(i).expect("source length should fit into u32");
+        Span {
+            lo: offset(line_start),
+            hi: offset(line_end),
+        }
+    } else {
+        span
+    }
+}
+
+/// Groups the edits, which are in source order, by the document they apply to.
+fn group_by_document(
+    compilation: &Compilation,
+    position_encoding: Encoding,
+    edits: Vec<(Span, String)>,
+) -> Vec<(String, Vec<(Range, String)>)> {
+    let sources = &compilation.user_unit().sources;
+    let mut documents = Vec::<(String, Vec<(Range, String)>)>::new();
+    for (span, text) in edits {
+        let name = &sources
+            .find_by_offset(span.lo)
+            .expect("source should exist in the user source map")
+            .name;
+        let edit = (into_range(position_encoding, span, sources), text);
+        match documents.last_mut() {
+            Some((uri, edits)) if **uri == **name => edits.push(edit),
+            _ => documents.push((name.to_string(), vec![edit])),
+        }
+    }
+    documents
+}
@@ -0,0 +1,188 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{get_capability_fixes, get_workspace_open_actions};
+use crate::{
+    compilation::Compilation, protocol::CodeAction,
+    test_utils::compile_project_with_fake_stdlib_and_markers_no_cursor, Encoding,
+};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::{edition::Edition, line_column::Position, target::Profile, PackageType};
+use std::fmt::Write;
+
+/// Applies each action to the sources and shows the documents it changes.
+fn render(sources: &[(&str, &str)], actions: Vec<CodeAction>) -> String {
+    let mut actual = String::new();
+    for action in actions {
+        let _ = writeln!(actual, "{}", action.title);
+        for (uri, edits) in action.edits {
+            let (_, source) = sources
+                .iter()
+                .find(|(name, _)| *name == uri)
+                .expect("edited document should be a source");
+            let mut text = (*source).to_string();
+            for (range, new_text) in edits.iter().rev() {
+                let lo = range.start.to_utf8_byte_offset(Encoding::Utf8, source) as usize;
+                let hi = range.end.to_utf8_byte_offset(Encoding::Utf8, source) as usize;
+                text.replace_range(lo..hi, new_text);
+            }
+            let _ = write!(actual, "--- {uri}\n{text}");
+        }
+    }
+    actual
+}
+
+fn check(sources: &[(&str, &str)], expect: &Expect) {
+    let (compilation, _) = compile_project_with_fake_stdlib_and_markers_no_cursor(sources);
+    let actions = get_workspace_open_actions(&compilation, Encoding::Utf8);
+    expect.assert_eq(&render(sources, actions));
+}
+
+/// Compiles the source for the base profile and shows the fixes offered at the position.
+fn check_capability_fixes(source: &str, position: Position, expect: &Expect) {
+    let compilation = Compilation::new(
+        &[("test.qs".into(), source.into())],
+        &[],
+        Edition::default(),
+        PackageType::Exe,
+        Profile::Base,
+    );
+    let actions = get_capability_fixes(&compilation, "test.qs", position, Encoding::Utf8);
+    expect.assert_eq(&render(&[("test.qs", source)], actions));
+}
+
+#[test]
+fn opens_are_tidied_across_documents() {
+    check(
+        &[
+            (
+                "a.qs",
+                indoc! {"
+                    namespace A {
+                        open FakeStdLib;
+                        open B;
+
+                        operation Main() : Unit {
+                            Fake();
+                        }
+                    }
+                "},
+            ),
+            (
+                "b.qs",
+                indoc! {"
+                    namespace B {
+                        open FakeStdLib;
+
+                        operation Helper(udt : Udt) : Unit {
+                            Main();
+                        }
+                    }
+                "},
+            ),
+            (
+                "c.qs",
+                indoc! {"
+                    namespace C {
+                        open A;
+                        open B;
+
+                        function Wrap(udt : Udt) : Udt { udt }
+                    }
+                "},
+            ),
+        ],
+        &expect![[r#"
+            Remove unused opens
+            --- a.qs
+            namespace A {
+                open FakeStdLib;
+
+                operation Main() : Unit {
+                    Fake();
+                }
+            }
+            --- c.qs
+            namespace C {
+
+                function Wrap(udt : Udt) : Udt { udt }
+            }
+            Add missing opens
+            --- b.qs
+            namespace B {
+                open A;
+                open FakeStdLib;
+
+                operation Helper(udt : Udt) : Unit {
+                    Main();
+                }
+            }
+            --- c.qs
+            namespace C {
+                open FakeStdLib;
+                open A;
+                open B;
+
+                function Wrap(udt : Udt) : Udt { udt }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn names_defined_in_more_than_one_namespace_are_left_alone() {
+    check(
+        &[(
+            "a.qs",
+            indoc! {"
+                namespace A {
+                    operation Fake() : Unit {}
+                }
+
+                namespace B {
+                    operation Main() : Unit {
+                        Fake();
+                    }
+                }
+            "},
+        )],
+        &expect![""],
+    );
+}
+
+#[test]
+fn flip_on_one_is_replaced_with_reset_for_base_profile() {
+    let source = indoc! {"
+        namespace Test {
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                if M(q) == One { X(q); }
+                M(q)
+            }
+        }
+    "};
+    check_capability_fixes(
+        source,
+        Position {
+            line: 4,
+            column: 11,
+        },
+        &expect![[r#"
+            Replace with `Reset(q)`
+            --- test.qs
+            namespace Test {
+                operation Main() : Result {
+                    use q = Qubit();
+                    H(q);
+                    Reset(q);
+                    M(q)
+                }
+            }
+        "#]],
+    );
+    check_capability_fixes(source, Position { line: 3, column: 8 }, &expect![""]);
+}
@@ -6,6 +6,8 @@ use qsc::{
     ast,
     compile::{self, Error},
     display::Lookup,
+    edition::Edition,
+    gate_counts::EstimateCache,
     hir::{self, PackageId},
     incremental::Compiler,
     line_column::{Encoding, Position},
@@ -13,7 +15,7 @@ use qsc::{
     target::Profile,
     CompileUnit, PackageStore, PackageType, SourceMap, Span,
 };
-use std::sync::Arc;
+use std::{cell::RefCell, sync::Arc};
 
 /// Represents an immutable compilation state that can be used
 /// to implement language service features.
@@ -26,6 +28,9 @@ pub(crate) struct Compilation {
     pub user_package_id: PackageId,
     pub errors: Vec<Error>,
     pub kind: CompilationKind,
+    /// The cost estimates of the callables shown so far. A compilation is replaced on every edit,
+    /// so they are never out of date.
+    pub cost_estimates: RefCell<EstimateCache>,
 }
 
 #[derive(Debug)]
@@ -41,9 +46,12 @@ pub(crate) enum CompilationKind {
 }
 
 impl Compilation {
-    /// Creates a new `Compilation` by compiling sources.
+    /// Creates a new `Compilation` by compiling sources written in the edition with the given
+    /// features enabled.
     pub(crate) fn new(
         sources: &[(Arc<str>, Arc<str>)],
+        features: &[String],
+        edition: Edition,
         package_type: PackageType,
         target_profile: Profile,
     ) -> Self {
@@ -53,19 +61,22 @@ impl Compilation {
             trace!("compiling package with {} sources", sources.len());
         }
 
-        let source_map = SourceMap::new(sources.iter().map(|(x, y)| (x.clone(), y.clone())), None);
+        let source_map = SourceMap::new(sources.iter().map(|(x, y)| (x.clone(), y.clone())), None)
+            .with_features(features.iter().map(String::as_str))
+            .with_edition(edition);
 
         let mut package_store = PackageStore::new(compile::core());
         let std_package_id =
             package_store.insert(compile::std(&package_store, target_profile.into()));
 
-        let (unit, errors) = compile::compile(
+        let (unit, mut errors) = compile::compile(
             &package_store,
             &[std_package_id],
             source_map,
             package_type,
             target_profile.into(),
         );
+        errors.extend(compile::warnings(&unit));
 
         let package_id = package_store.insert(unit);
 
@@ -74,6 +85,7 @@ impl Compilation {
             user_package_id: package_id,
             errors,
             kind: CompilationKind::OpenProject,
+            cost_estimates: RefCell::default(),
         }
     }
 
@@ -103,6 +115,7 @@ impl Compilation {
 
             compiler.update(increment);
         }
+        errors.extend(compiler.take_warnings());
 
         let (package_store, package_id) = compiler.into_package_store();
 
@@ -111,6 +124,7 @@ impl Compilation {
             user_package_id: package_id,
             errors,
             kind: CompilationKind::Notebook,
+            cost_estimates: RefCell::default(),
         }
     }
 
@@ -173,16 +187,25 @@ impl Compilation {
 
     /// Regenerates the compilation with the same sources but the passed in workspace configuration options.
     pub fn recompile(&mut self, package_type: PackageType, target_profile: Profile) {
-        let sources = self
-            .user_unit()
-            .sources
+        let source_map = &self.user_unit().sources;
+        let features = source_map
+            .features()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let edition = source_map.edition();
+        let sources = source_map
             .iter()
             .map(|source| (source.name.clone(), source.contents.clone()));
 
         let new = match self.kind {
-            CompilationKind::OpenProject => {
-                Self::new(&sources.collect::<Vec<_>>(), package_type, target_profile)
-            }
+            CompilationKind::OpenProject => Self::new(
+                &sources.collect::<Vec<_>>(),
+                &features,
+                edition,
+                package_type,
+                target_profile,
+            ),
             CompilationKind::Notebook => Self::new_notebook(sources, target_profile),
         };
         self.package_store = new.package_store;
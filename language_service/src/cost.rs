@@ -0,0 +1,83 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::compilation::Compilation;
+use qsc::{
+    gate_counts::{Bound, Estimate, GateCounter},
+    hir::{CallableKind, ItemId, ItemKind, SpecBody, SpecGen},
+};
+
+/// Summarizes the static cost estimate of an operation, such as `≈ 1.2k T gates, 14 qubits`.
+/// Returns `None` for items that aren't operations, for intrinsic operations, and for operations
+/// whose source isn't available.
+///
+/// Estimates are computed on demand, only for the callables reachable from the given one, and are
+/// cached with the compilation, so that each callable is only analyzed once between edits.
+pub(crate) fn cost_summary(compilation: &Compilation, item_id: &ItemId) -> Option<String> {
+    let package = item_id.package.unwrap_or(compilation.user_package_id);
+    let item = compilation
+        .package_store
+        .get(package)?
+        .package
+        .items
+        .get(item_id.item)?;
+    match &item.kind {
+        ItemKind::Callable(decl)
+            if decl.kind == CallableKind::Operation
+                && decl.body.body != SpecBody::Gen(SpecGen::Intrinsic) => {}
+        _ => return None,
+    }
+
+    let mut counter = GateCounter::with_cache(
+        &compilation.package_store,
+        compilation.cost_estimates.take(),
+    );
+    let estimate = counter.callable(package, item_id.item);
+    compilation.cost_estimates.replace(counter.into_cache());
+    Some(format_estimate(&estimate?))
+}
+
+fn format_estimate(estimate: &Estimate) -> String {
+    let t_gates = estimate
+        .gates
+        .iter()
+        .filter(|(name, _)| {
+            // Strip any functors applied to the gate.
+            let gate = name.rsplit(' ').next().unwrap_or(name);
+            matches!(gate, "T" | "t__body" | "t__adj")
+        })
+        .fold(Bound::ZERO, |total, (_, &count)| total + count);
+
+    let mut parts = Vec::new();
+    if t_gates != Bound::ZERO {
+        parts.push(format!("{} T gates", format_bound(t_gates)));
+    }
+    parts.push(format!("{} gates", format_bound(estimate.total_gates())));
+    parts.push(format!("{} qubits", format_bound(estimate.qubits)));
+    format!("≈ {}", parts.join(", "))
+}
+
+fn format_bound(bound: Bound) -> String {
+    match bound.max {
+        Some(max) if max == bound.min => format_count(max),
+        Some(max) => format!("{}–{}", format_count(bound.min), format_count(max)),
+        None => format!("{}+", format_count(bound.min)),
+    }
+}
+
+/// Formats a count compactly with one decimal place, such as `1.2k` or `35M`.
+fn format_count(count: u64) -> String {
+    let (unit, suffix) = match count {
+        0..=999 => return count.to_string(),
+        1_000..=999_999 => (1_000, "k"),
+        1_000_000..=999_999_999 => (1_000_000, "M"),
+        _ => (1_000_000_000, "G"),
+    };
+    let whole = count / unit;
+    let tenths = count % unit * 10 / unit;
+    if whole >= 100 || tenths == 0 {
+        format!("{whole}{suffix}")
+    } else {
+        format!("{whole}.{tenths}{suffix}")
+    }
+}
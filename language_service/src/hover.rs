@@ -5,21 +5,23 @@
 mod tests;
 
 use crate::compilation::Compilation;
+use crate::cost::cost_summary;
 use crate::name_locator::{Handler, Locator, LocatorContext};
 use crate::protocol::Hover;
 use crate::qsc_utils::into_range;
 use qsc::ast::visit::Visitor;
 use qsc::display::{parse_doc_for_param, parse_doc_for_summary, CodeDisplay};
 use qsc::line_column::{Encoding, Position, Range};
-use qsc::{ast, hir, Span};
+use qsc::{ast, hir, resolve, Span};
 use std::fmt::Display;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub(crate) fn get_hover(
     compilation: &Compilation,
     source_name: &str,
     position: Position,
     position_encoding: Encoding,
+    cost_annotations: bool,
 ) -> Option<Hover> {
     let offset =
         compilation.source_position_to_package_offset(source_name, position, position_encoding);
@@ -30,6 +32,7 @@ pub(crate) fn get_hover(
         compilation,
         hover: None,
         display: CodeDisplay { compilation },
+        cost_annotations,
     };
 
     let mut locator = Locator::new(&mut hover_visitor, offset, compilation);
@@ -48,6 +51,8 @@ struct HoverGenerator<'a> {
     hover: Option<Hover>,
     display: CodeDisplay<'a>,
     compilation: &'a Compilation,
+    /// Whether to annotate operations with their estimated cost.
+    cost_annotations: bool,
 }
 
 impl<'a> Handler<'a> for HoverGenerator<'a> {
@@ -57,11 +62,16 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
         name: &'a ast::Ident,
         decl: &'a ast::CallableDecl,
     ) {
-        let contents = display_callable(
+        let mut contents = display_callable(
             &context.current_item_doc,
             &context.current_namespace,
             self.display.ast_callable_decl(decl),
         );
+        if let Some(resolve::Res::Item(item_id, _)) =
+            self.compilation.user_unit().ast.names.get(decl.name.id)
+        {
+            self.append_cost(&mut contents, item_id);
+        }
         self.hover = Some(Hover {
             contents,
             span: self.range(name.span),
@@ -71,7 +81,7 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
     fn at_callable_ref(
         &mut self,
         path: &'a ast::Path,
-        _: &'_ hir::ItemId,
+        item_id: &'_ hir::ItemId,
         item: &'a hir::Item,
         package: &'a hir::Package,
         decl: &'a hir::CallableDecl,
@@ -80,14 +90,15 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
             .parent
             .and_then(|parent_id| package.items.get(parent_id))
             .map_or_else(
-                || Rc::from(""),
+                || Arc::from(""),
                 |parent| match &parent.kind {
                     qsc::hir::ItemKind::Namespace(namespace, _) => namespace.name.clone(),
-                    _ => Rc::from(""),
+                    _ => Arc::from(""),
                 },
             );
 
-        let contents = display_callable(&item.doc, &ns, self.display.hir_callable_decl(decl));
+        let mut contents = display_callable(&item.doc, &ns, self.display.hir_callable_decl(decl));
+        self.append_cost(&mut contents, item_id);
 
         self.hover = Some(Hover {
             contents,
@@ -265,6 +276,16 @@ impl<'a> Handler<'a> for HoverGenerator<'a> {
 }
 
 impl HoverGenerator<'_> {
+    fn append_cost(&self, contents: &mut String, item_id: &hir::ItemId) {
+        if self.cost_annotations {
+            if let Some(cost) = cost_summary(self.compilation, item_id) {
+                contents.push_str("---\n");
+                contents.push_str(&cost);
+                contents.push('\n');
+            }
+        }
+    }
+
     fn range(&self, span: Span) -> Range {
         into_range(
             self.position_encoding,
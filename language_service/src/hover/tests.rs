@@ -17,8 +17,30 @@ use qsc::line_column::Encoding;
 fn check(source_with_markers: &str, expect: &Expect) {
     let (compilation, cursor_position, target_spans) =
         compile_with_fake_stdlib_and_markers(source_with_markers);
-    let actual = get_hover(&compilation, "<source>", cursor_position, Encoding::Utf8)
-        .expect("Expected a hover.");
+    let actual = get_hover(
+        &compilation,
+        "<source>",
+        cursor_position,
+        Encoding::Utf8,
+        false,
+    )
+    .expect("Expected a hover.");
+    assert_eq!(&actual.span, &target_spans[0]);
+    expect.assert_eq(&actual.contents);
+}
+
+/// Asserts that the hover text, with cost annotations enabled, matches the expected hover text.
+fn check_with_costs(source_with_markers: &str, expect: &Expect) {
+    let (compilation, cursor_position, target_spans) =
+        compile_with_fake_stdlib_and_markers(source_with_markers);
+    let actual = get_hover(
+        &compilation,
+        "<source>",
+        cursor_position,
+        Encoding::Utf8,
+        true,
+    )
+    .expect("Expected a hover.");
     assert_eq!(&actual.span, &target_spans[0]);
     expect.assert_eq(&actual.contents);
 }
@@ -27,7 +49,13 @@ fn check(source_with_markers: &str, expect: &Expect) {
 fn check_none(source_with_markers: &str) {
     let (compilation, cursor_position, _) =
         compile_with_fake_stdlib_and_markers(source_with_markers);
-    let actual = get_hover(&compilation, "<source>", cursor_position, Encoding::Utf8);
+    let actual = get_hover(
+        &compilation,
+        "<source>",
+        cursor_position,
+        Encoding::Utf8,
+        false,
+    );
     assert!(actual.is_none());
 }
 
@@ -35,8 +63,8 @@ fn check_notebook(cells_with_markers: &[(&str, &str)], expect: &Expect) {
     let (compilation, cell_uri, position, target_spans) =
         compile_notebook_with_fake_stdlib_and_markers(cells_with_markers);
 
-    let actual =
-        get_hover(&compilation, &cell_uri, position, Encoding::Utf8).expect("Expected a hover.");
+    let actual = get_hover(&compilation, &cell_uri, position, Encoding::Utf8, false)
+        .expect("Expected a hover.");
     assert_eq!(&actual.span, &target_spans[0].range);
     expect.assert_eq(&actual.contents);
 }
@@ -45,7 +73,7 @@ fn check_notebook_none(cells_with_markers: &[(&str, &str)]) {
     let (compilation, cell_uri, position, _) =
         compile_notebook_with_fake_stdlib_and_markers(cells_with_markers);
 
-    let actual = get_hover(&compilation, &cell_uri, position, Encoding::Utf8);
+    let actual = get_hover(&compilation, &cell_uri, position, Encoding::Utf8, false);
     assert!(actual.is_none());
 }
 
@@ -1252,3 +1280,81 @@ fn notebook_callable_defined_in_later_cell() {
         ("cell2", "operation Callee() : Unit {}"),
     ]);
 }
+
+#[test]
+fn callable_def_with_cost() {
+    check_with_costs(
+        indoc! {r#"
+        namespace Test {
+            operation T(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            /// Doc comment
+            operation ◉B↘ar◉() : Unit {
+                use qs = Qubit[2];
+                for q in qs {
+                    T(q);
+                }
+            }
+        }
+    "#},
+        &expect![[r#"
+            ```qsharp
+            Test
+            operation Bar() : Unit
+            ```
+            ---
+            Doc comment
+            ---
+            ≈ 2 T gates, 2 gates, 2 qubits
+        "#]],
+    );
+}
+
+#[test]
+fn callable_ref_with_cost() {
+    check_with_costs(
+        indoc! {r#"
+        namespace Test {
+            operation T(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation Bar(q : Qubit) : Unit {
+                T(q);
+                T(q);
+            }
+            operation Main() : Unit {
+                use q = Qubit();
+                ◉B↘ar◉(q);
+            }
+        }
+    "#},
+        &expect![[r#"
+            ```qsharp
+            Test
+            operation Bar(q : Qubit) : Unit
+            ```
+            ---
+            ≈ 2 T gates, 2 gates, 0 qubits
+        "#]],
+    );
+}
+
+#[test]
+fn intrinsic_callable_has_no_cost() {
+    check_with_costs(
+        indoc! {r#"
+        namespace Test {
+            operation ◉T↘◉(q : Qubit) : Unit {
+                body intrinsic;
+            }
+        }
+    "#},
+        &expect![[r#"
+            ```qsharp
+            Test
+            operation T(q : Qubit) : Unit
+            ```
+        "#]],
+    );
+}
@@ -0,0 +1,30 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation::Compilation,
+    qsc_utils::{into_range, span_contains},
+};
+use qsc::line_column::{Encoding, Range};
+
+/// Returns the ranges of the items in the document that conditional compilation left out,
+/// because their `@Config` attribute doesn't match the target profile or the enabled features.
+pub(crate) fn get_inactive_code(
+    compilation: &Compilation,
+    source_name: &str,
+    position_encoding: Encoding,
+) -> Vec<Range> {
+    let user_unit = compilation.user_unit();
+    let source_span = compilation.package_span_of_source(source_name);
+
+    user_unit
+        .ast
+        .inactive
+        .iter()
+        .filter(|span| span_contains(source_span, span.lo))
+        .map(|span| into_range(position_encoding, *span, &user_unit.sources))
+        .collect()
+}
@@ -0,0 +1,56 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::get_inactive_code;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+
+fn check(source_with_markers: &str) {
+    let (compilation, expected_ranges) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    let actual_ranges = get_inactive_code(&compilation, "<source>", Encoding::Utf8);
+    assert_eq!(actual_ranges, expected_ranges);
+}
+
+#[test]
+fn disabled_feature_is_inactive() {
+    check(
+        r#"
+        namespace Test {
+            ◉@Config(feature = "chemistry")
+            operation Energy() : Unit {
+            }◉
+            operation Main() : Unit {
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn no_config_attributes() {
+    check(
+        r#"
+        namespace Test {
+            operation Main() : Unit {
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn other_target_is_inactive() {
+    check(
+        r#"
+        namespace Test {
+            ◉@Config(Base)
+            operation Main() : Unit {
+            }◉
+            operation Main() : Unit {
+            }
+        }
+    "#,
+    );
+}
@@ -0,0 +1,57 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation::Compilation,
+    cost::cost_summary,
+    protocol::InlayHint,
+    qsc_utils::{into_range, span_contains},
+};
+use qsc::{
+    ast::{
+        self,
+        visit::{self, Visitor},
+    },
+    line_column::Encoding,
+    resolve::Res,
+};
+
+/// Annotates each operation declared in the source with its estimated cost, placed after the
+/// return type in its signature.
+pub(crate) fn get_inlay_hints(
+    compilation: &Compilation,
+    source_name: &str,
+    position_encoding: Encoding,
+) -> Vec<InlayHint> {
+    let user_unit = compilation.user_unit();
+    let source_span = compilation.package_span_of_source(source_name);
+
+    let mut decls = CallableDecls(Vec::new());
+    decls.visit_package(&user_unit.ast.package);
+
+    decls
+        .0
+        .into_iter()
+        .filter(|decl| span_contains(source_span, decl.span.lo))
+        .filter_map(|decl| {
+            let Some(Res::Item(item_id, _)) = user_unit.ast.names.get(decl.name.id) else {
+                return None;
+            };
+            let label = cost_summary(compilation, item_id)?;
+            let position = into_range(position_encoding, decl.output.span, &user_unit.sources).end;
+            Some(InlayHint { position, label })
+        })
+        .collect()
+}
+
+struct CallableDecls<'a>(Vec<&'a ast::CallableDecl>);
+
+impl<'a> Visitor<'a> for CallableDecls<'a> {
+    fn visit_callable_decl(&mut self, decl: &'a ast::CallableDecl) {
+        self.0.push(decl);
+        visit::walk_callable_decl(self, decl);
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::get_inlay_hints;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+
+/// Asserts that the inlay hints are placed at the `◉◉` markers in the source and that their
+/// labels match the expected labels.
+fn check(source_with_markers: &str, expect: &Expect) {
+    let (compilation, expected_ranges) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    let hints = get_inlay_hints(&compilation, "<source>", Encoding::Utf8);
+
+    let positions = hints.iter().map(|hint| hint.position).collect::<Vec<_>>();
+    let expected_positions = expected_ranges
+        .iter()
+        .map(|range| range.start)
+        .collect::<Vec<_>>();
+    assert_eq!(positions, expected_positions);
+
+    let labels = hints
+        .iter()
+        .map(|hint| hint.label.as_str())
+        .collect::<Vec<_>>();
+    expect.assert_debug_eq(&labels);
+}
+
+#[test]
+fn operations_are_annotated() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation T(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation H(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation Prepare(q : Qubit) : Unit◉◉ {
+                H(q);
+                for _ in 1..1234 {
+                    T(q);
+                }
+            }
+            operation Main() : Unit◉◉ {
+                use qs = Qubit[14];
+                Prepare(qs[0]);
+            }
+            function Classical() : Int {
+                42
+            }
+        }
+    "#},
+        &expect![[r#"
+            [
+                "≈ 1.2k T gates, 1.2k gates, 0 qubits",
+                "≈ 1.2k T gates, 1.2k gates, 14 qubits",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn dynamic_counts_are_ranges() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            operation X(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation Main(n : Int) : Unit◉◉ {
+                use q = Qubit();
+                if n > 0 {
+                    X(q);
+                }
+                for _ in 1..n {
+                    X(q);
+                }
+            }
+        }
+    "#},
+        &expect![[r#"
+            [
+                "≈ 0+ gates, 1 qubits",
+            ]
+        "#]],
+    );
+}
@@ -4,11 +4,15 @@
 #![warn(clippy::mod_module_files, clippy::pedantic, clippy::unwrap_used)]
 #![allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
+pub mod code_action;
 pub mod code_lens;
 mod compilation;
 pub mod completion;
+mod cost;
 pub mod definition;
 pub mod hover;
+pub mod inactive_code;
+pub mod inlay_hints;
 mod name_locator;
 mod project_system;
 pub mod protocol;
@@ -16,6 +20,7 @@ mod qsc_utils;
 pub mod references;
 pub mod rename;
 pub mod signature_help;
+mod signature_search;
 mod state;
 #[cfg(test)]
 mod test_utils;
@@ -27,8 +32,8 @@ use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
 use futures_util::StreamExt;
 use log::{trace, warn};
 use protocol::{
-    CodeLens, CompletionList, DiagnosticUpdate, Hover, NotebookMetadata, SignatureHelp,
-    WorkspaceConfigurationUpdate,
+    CodeAction, CodeLens, CompletionList, DiagnosticUpdate, Hover, InlayHint, NotebookMetadata,
+    SignatureHelp, SignatureSearchResult, WorkspaceConfigurationUpdate,
 };
 use qsc::{
     line_column::{Encoding, Position, Range},
@@ -224,7 +229,21 @@ impl LanguageService {
     /// LSP: textDocument/hover
     #[must_use]
     pub fn get_hover(&self, uri: &str, position: Position) -> Option<Hover> {
-        self.document_op(hover::get_hover, "get_hover", uri, position)
+        let cost_annotations = self.state.borrow().cost_annotations();
+        self.document_op(
+            |compilation, uri, position, position_encoding| {
+                hover::get_hover(
+                    compilation,
+                    uri,
+                    position,
+                    position_encoding,
+                    cost_annotations,
+                )
+            },
+            "get_hover",
+            uri,
+            position,
+        )
     }
 
     /// LSP textDocument/signatureHelp
@@ -263,6 +282,103 @@ impl LanguageService {
         )
     }
 
+    /// Returns the actions that remove the unused `open` statements and add the missing ones
+    /// across all the documents of the project that the document belongs to.
+    #[must_use]
+    pub fn get_workspace_code_actions(&self, uri: &str) -> Vec<CodeAction> {
+        self.document_op(
+            |compilation, _, (), position_encoding| {
+                code_action::get_workspace_open_actions(compilation, position_encoding)
+            },
+            "get_workspace_code_actions",
+            uri,
+            (),
+        )
+    }
+
+    /// LSP: textDocument/codeAction
+    ///
+    /// Returns the actions that rewrite the code at the position so that the target profile can
+    /// run it, such as replacing a conditional flip with a reset.
+    #[must_use]
+    pub fn get_code_actions(&self, uri: &str, position: Position) -> Vec<CodeAction> {
+        self.document_op(
+            code_action::get_capability_fixes,
+            "get_code_actions",
+            uri,
+            position,
+        )
+    }
+
+    /// LSP: textDocument/inlayHint
+    ///
+    /// Annotates each operation in the document with its estimated cost. Returns no hints
+    /// unless cost annotations are enabled in the workspace configuration.
+    #[must_use]
+    pub fn get_inlay_hints(&self, uri: &str) -> Vec<InlayHint> {
+        if !self.state.borrow().cost_annotations() {
+            return Vec::new();
+        }
+        self.document_op(
+            |compilation, uri, (), position_encoding| {
+                inlay_hints::get_inlay_hints(compilation, uri, position_encoding)
+            },
+            "get_inlay_hints",
+            uri,
+            (),
+        )
+    }
+
+    /// Returns the ranges of the code in the document that conditional compilation leaves out
+    /// for the current target profile and the features enabled in the project manifest.
+    #[must_use]
+    pub fn get_inactive_code(&self, uri: &str) -> Vec<Range> {
+        self.document_op(
+            |compilation, uri, (), position_encoding| {
+                inactive_code::get_inactive_code(compilation, uri, position_encoding)
+            },
+            "get_inactive_code",
+            uri,
+            (),
+        )
+    }
+
+    /// Returns the errors for the document that were left out of the last published
+    /// diagnostics because they exceed the configured maximum.
+    #[must_use]
+    pub fn get_suppressed_diagnostics(&self, uri: &str) -> Vec<qsc::compile::Error> {
+        trace!("get_suppressed_diagnostics: uri: {uri}");
+        self.state.borrow().get_suppressed_diagnostics(uri)
+    }
+
+    /// Finds the callables that code in the document can use by their signature, such as
+    /// `(Qubit[], Double) => Unit is Adj`, with the closest matches first. Returns an error
+    /// message if the query isn't a callable type.
+    pub fn search_signatures(
+        &self,
+        uri: &str,
+        query: &str,
+    ) -> Result<Vec<SignatureSearchResult>, String> {
+        let query = query.parse().map_err(|err| format!("{err}"))?;
+        Ok(self.document_op(
+            |compilation, _, query, position_encoding| {
+                signature_search::search_signatures(compilation, query, position_encoding)
+            },
+            "search_signatures",
+            uri,
+            &query,
+        ))
+    }
+
+    /// Returns the full explanation of a diagnostic code as Markdown, for clients to show when
+    /// the user asks for more than the message of a diagnostic with that code.
+    #[must_use]
+    #[allow(clippy::unused_self)]
+    pub fn get_diagnostic_explanation(&self, code: &str) -> Option<String> {
+        trace!("get_diagnostic_explanation: code: {code}");
+        qsc::explanation::explain(code).map(ToString::to_string)
+    }
+
     /// Executes an operation that takes a document uri, using the current compilation for that document.
     /// All "read" operations should go through this method. This method will borrow the current
     /// compilation state to perform the request.
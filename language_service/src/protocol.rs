@@ -1,14 +1,18 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use qsc::line_column::Range;
-use qsc::{compile::Error, target::Profile, PackageType};
+use qsc::line_column::{Position, Range};
+use qsc::{compile::Error, location::Location, target::Profile, PackageType};
 
 /// A change to the workspace configuration
 #[derive(Clone, Debug, Default, Copy)]
 pub struct WorkspaceConfigurationUpdate {
     pub target_profile: Option<Profile>,
     pub package_type: Option<PackageType>,
+    /// The maximum number of errors to publish for a single document.
+    pub max_diagnostics: Option<usize>,
+    /// Whether to annotate operations with their estimated gate and qubit counts.
+    pub cost_annotations: Option<bool>,
 }
 
 #[derive(Debug)]
@@ -16,6 +20,10 @@ pub struct DiagnosticUpdate {
     pub uri: String,
     pub version: Option<u32>,
     pub errors: Vec<Error>,
+    /// The number of errors in the document that were left out of `errors` because
+    /// they exceed the configured maximum. They can be fetched on demand with
+    /// [`crate::LanguageService::get_suppressed_diagnostics`].
+    pub suppressed: usize,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -133,3 +141,28 @@ pub enum CodeLensCommand {
     Run,
     Estimate,
 }
+
+#[derive(Debug, PartialEq)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+}
+
+/// An action that edits documents across the workspace in one operation.
+#[derive(Debug, PartialEq)]
+pub struct CodeAction {
+    pub title: String,
+    /// The edits to make, grouped by the URI of the document they apply to.
+    pub edits: Vec<(String, Vec<(Range, String)>)>,
+}
+
+/// A callable found by searching for its signature.
+#[derive(Debug, PartialEq)]
+pub struct SignatureSearchResult {
+    /// The fully qualified name of the callable.
+    pub name: String,
+    pub signature: String,
+    pub summary: String,
+    /// Where the callable is declared.
+    pub location: Option<Location>,
+}
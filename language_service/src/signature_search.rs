@@ -0,0 +1,41 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{compilation::Compilation, protocol::SignatureSearchResult, qsc_utils::into_location};
+use qsc::{
+    line_column::Encoding,
+    signature_search::{search, Query},
+};
+
+/// Returns the callables that the user package can use whose signatures match the query, the
+/// closest matches first.
+pub(crate) fn search_signatures(
+    compilation: &Compilation,
+    query: &Query,
+    position_encoding: Encoding,
+) -> Vec<SignatureSearchResult> {
+    search(
+        &compilation.package_store,
+        compilation.user_package_id,
+        query,
+    )
+    .into_iter()
+    .map(|found| {
+        let package = found.id.package.expect("item should have a package");
+        let location = compilation
+            .package_store
+            .get(package)
+            .and_then(|unit| unit.package.items.get(found.id.item))
+            .map(|item| into_location(position_encoding, compilation, item.span, package));
+        SignatureSearchResult {
+            name: found.name,
+            signature: found.signature,
+            summary: found.summary,
+            location,
+        }
+    })
+    .collect()
+}
@@ -0,0 +1,77 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::search_signatures;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+use expect_test::{expect, Expect};
+
+fn check(source_with_markers: &str, query: &str, expect: &Expect) {
+    let (compilation, expected_ranges) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    let query = query.parse().expect("query should parse");
+    let results = search_signatures(&compilation, &query, Encoding::Utf8);
+
+    let in_test = results
+        .iter()
+        .filter(|result| result.name.starts_with("Test."))
+        .filter_map(|result| result.location.as_ref())
+        .map(|location| location.range)
+        .collect::<Vec<_>>();
+    assert_eq!(in_test, expected_ranges);
+
+    let actual = results
+        .iter()
+        .filter(|result| !result.name.starts_with("Microsoft.Quantum.Core."))
+        .map(|result| format!("{}: {}", result.name, result.signature))
+        .collect::<Vec<_>>();
+    expect.assert_debug_eq(&actual);
+}
+
+#[test]
+fn user_and_library_callables_are_found() {
+    check(
+        r#"
+        namespace Test {
+            open FakeStdLib;
+            ◉function Twice(input : Udt) : Udt {
+                input
+            }◉
+            function Other(input : Int) : Udt {
+                Udt(input, input)
+            }
+        }
+    "#,
+        "Udt -> Udt",
+        &expect![[r#"
+            [
+                "FakeStdLib.TakesUdt: Udt -> Udt",
+                "Test.Twice: Udt -> Udt",
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn internal_library_callables_are_not_found() {
+    check(
+        r#"
+        namespace Test {
+            ◉operation Main() : Unit {
+            }◉
+        }
+    "#,
+        "Unit => Unit",
+        &expect![[r#"
+            [
+                "FakeStdLib.Fake: Unit => Unit",
+                "FakeStdLib.FakeCtlAdj: Unit => Unit is Adj + Ctl",
+                "FakeStdLib.RefFake: Unit => Unit",
+                "Microsoft.Quantum.Unstable.UnstableFake: Unit => Unit",
+                "Test.Main: Unit => Unit",
+                "FakeStdLib.FakeWithTypeParam: 'A => 'A",
+            ]
+        "#]],
+    );
+}
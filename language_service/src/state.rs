@@ -10,7 +10,7 @@ use crate::protocol::WorkspaceConfigurationUpdate;
 use log::{error, trace};
 use miette::Diagnostic;
 use qsc::{compile::Error, target::Profile, PackageType};
-use qsc_project::{FileSystemAsync, JSFileEntry};
+use qsc_project::{FileSystemAsync, JSFileEntry, Manifest};
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{cell::RefCell, fmt::Debug, future::Future, mem::take, pin::Pin, rc::Rc, sync::Arc};
 
@@ -45,11 +45,21 @@ pub(super) struct CompilationState {
     /// This map doesn't necessarily contain ALL the documents that
     /// make up a compilation - only the ones that are currently open.
     open_documents: FxHashMap<DocumentUri, OpenDocument>,
+    /// Errors that were left out of the last published diagnostics for a
+    /// document because they exceed the configured maximum.
+    suppressed_diagnostics: FxHashMap<DocumentUri, Vec<Error>>,
+    /// Whether hovers and inlay hints should include static cost estimates.
+    cost_annotations: bool,
 }
 
 type CompilationUri = Arc<str>;
 type DocumentUri = Arc<str>;
 
+/// The default maximum number of errors to publish for a single document.
+/// Pathological inputs can produce tens of thousands of errors, which
+/// overwhelm clients without being of any use to the user.
+const DEFAULT_MAX_DIAGNOSTICS: usize = 1000;
+
 #[derive(Debug)]
 struct OpenDocument {
     /// This version is the document version provided by the client.
@@ -66,6 +76,7 @@ struct OpenDocument {
 struct Configuration {
     pub target_profile: Profile,
     pub package_type: PackageType,
+    pub max_diagnostics: usize,
 }
 
 impl Default for Configuration {
@@ -73,6 +84,7 @@ impl Default for Configuration {
         Self {
             target_profile: Profile::Unrestricted,
             package_type: PackageType::Exe,
+            max_diagnostics: DEFAULT_MAX_DIAGNOSTICS,
         }
     }
 }
@@ -134,11 +146,16 @@ impl<'a> CompilationStateUpdater<'a> {
     /// a recompilation may be triggered, which will result in a new set of diagnostics
     /// being published.
     pub fn update_configuration(&mut self, configuration: WorkspaceConfigurationUpdate) {
+        let need_republish = configuration
+            .max_diagnostics
+            .is_some_and(|max| max != self.configuration.max_diagnostics);
         let need_recompile = self.apply_configuration(configuration);
 
         // Some configuration options require a recompilation as they impact error checking
         if need_recompile {
             self.recompile_all();
+        } else if need_republish {
+            self.publish_diagnostics();
         }
     }
 
@@ -148,9 +165,13 @@ impl<'a> CompilationStateUpdater<'a> {
 
         let project = self.load_manifest(&doc_uri).await;
 
-        let (compilation_uri, sources) = project.unwrap_or_else(|| {
+        let (compilation_uri, sources, manifest) = project.unwrap_or_else(|| {
             // If we are in single file mode, use the file's path as the compilation identifier.
-            (doc_uri.clone(), vec![(doc_uri.clone(), text.clone())])
+            (
+                doc_uri.clone(),
+                vec![(doc_uri.clone(), text.clone())],
+                Manifest::default(),
+            )
         });
 
         let prev_compilation_uri = self.with_state_mut(|state| {
@@ -175,23 +196,23 @@ impl<'a> CompilationStateUpdater<'a> {
             }
         }
 
-        self.insert_buffer_aware_compilation(sources, &compilation_uri);
+        self.insert_buffer_aware_compilation(sources, &manifest, &compilation_uri);
 
         self.publish_diagnostics();
     }
 
     /// Attempts to resolve a manifest for the given document uri.
     /// If a manifest is found, returns the manifest uri along
-    /// with the sources for the project
+    /// with the sources and the manifest of the project
     async fn load_manifest(
         &self,
         doc_uri: &Arc<str>,
-    ) -> Option<(Arc<str>, Vec<(Arc<str>, Arc<str>)>)> {
+    ) -> Option<(Arc<str>, Vec<(Arc<str>, Arc<str>)>, Manifest)> {
         let manifest = (self.get_manifest)(doc_uri.to_string()).await;
         if let Some(ref manifest) = manifest {
             let res = self.load_project(manifest).await;
             match res {
-                Ok(o) => Some((manifest.compilation_uri(), o.sources)),
+                Ok(o) => Some((manifest.compilation_uri(), o.sources, o.manifest)),
                 Err(e) => {
                     error!("failed to load manifest: {e:?}, defaulting to single-file mode");
                     None
@@ -210,6 +231,7 @@ impl<'a> CompilationStateUpdater<'a> {
     fn insert_buffer_aware_compilation(
         &mut self,
         mut sources: Vec<(Arc<str>, Arc<str>)>,
+        manifest: &Manifest,
         compilation_uri: &Arc<str>,
     ) {
         self.with_state_mut(|state| {
@@ -225,6 +247,8 @@ impl<'a> CompilationStateUpdater<'a> {
 
             let compilation = Compilation::new(
                 &sources,
+                &manifest.features,
+                manifest.edition,
                 self.configuration.package_type,
                 self.configuration.target_profile,
             );
@@ -246,7 +270,7 @@ impl<'a> CompilationStateUpdater<'a> {
             // uses the disk contents instead of the open buffer contents
             // for this document
             if let Some(project) = project {
-                self.insert_buffer_aware_compilation(project.1, &project.0);
+                self.insert_buffer_aware_compilation(project.1, &project.2, &project.0);
             }
         }
 
@@ -356,8 +380,9 @@ impl<'a> CompilationStateUpdater<'a> {
     fn publish_diagnostics(&mut self) {
         let last_docs_with_errors = take(&mut self.documents_with_errors);
         let mut docs_with_errors = FxHashSet::default();
+        let mut suppressed_diagnostics = FxHashMap::default();
 
-        self.with_state(|state| {
+        self.with_state_mut(|state| {
             for (compilation_uri, compilation) in &state.compilations {
                 trace!("publishing diagnostics for {compilation_uri}");
                 for (uri, errors) in map_errors_to_docs(compilation_uri, &compilation.0.errors) {
@@ -370,7 +395,10 @@ impl<'a> CompilationStateUpdater<'a> {
                         continue;
                     }
 
-                    self.publish_diagnostics_for_doc(state, &uri, errors);
+                    let suppressed = self.publish_diagnostics_for_doc(state, &uri, errors);
+                    if !suppressed.is_empty() {
+                        suppressed_diagnostics.insert(uri, suppressed);
+                    }
                 }
             }
 
@@ -378,22 +406,39 @@ impl<'a> CompilationStateUpdater<'a> {
             for uri in last_docs_with_errors.difference(&docs_with_errors) {
                 self.publish_diagnostics_for_doc(state, uri, vec![]);
             }
+
+            state.suppressed_diagnostics = suppressed_diagnostics;
         });
 
         self.documents_with_errors = docs_with_errors;
     }
 
-    fn publish_diagnostics_for_doc(&self, state: &CompilationState, uri: &str, errors: Vec<Error>) {
+    /// Publishes the errors for a document, up to the configured maximum.
+    /// Returns the errors that were left out.
+    fn publish_diagnostics_for_doc(
+        &self,
+        state: &CompilationState,
+        uri: &str,
+        mut errors: Vec<Error>,
+    ) -> Vec<Error> {
         let version = state.open_documents.get(uri).map(|d| d.version);
+        let suppressed = if errors.len() > self.configuration.max_diagnostics {
+            errors.split_off(self.configuration.max_diagnostics)
+        } else {
+            Vec::new()
+        };
         trace!(
-            "publishing diagnostics for {uri} {version:?}): {} errors",
-            errors.len()
+            "publishing diagnostics for {uri} {version:?}): {} errors, {} suppressed",
+            errors.len(),
+            suppressed.len()
         );
         (self.diagnostics_receiver)(DiagnosticUpdate {
             uri: uri.into(),
             version,
             errors,
+            suppressed: suppressed.len(),
         });
+        suppressed
     }
 
     fn apply_configuration(&mut self, configuration: WorkspaceConfigurationUpdate) -> bool {
@@ -409,6 +454,14 @@ impl<'a> CompilationStateUpdater<'a> {
             self.configuration.target_profile = target_profile;
         }
 
+        if let Some(max_diagnostics) = configuration.max_diagnostics {
+            self.configuration.max_diagnostics = max_diagnostics;
+        }
+
+        if let Some(cost_annotations) = configuration.cost_annotations {
+            self.with_state_mut(|state| state.cost_annotations = cost_annotations);
+        }
+
         // Possible optimization: some projects will have overrides for these configurations,
         // so workspace updates won't impact them. We could exclude those projects
         // from recompilation, but we don't right now.
@@ -432,23 +485,9 @@ impl<'a> CompilationStateUpdater<'a> {
         self.publish_diagnostics();
     }
 
-    /// Borrows the compilation state immutably and invokes `f`.
-    /// Warning: This function is not reentrant. For dynamic borrow safety,
-    /// don't call `with_state` from within `with_state` or `with_state_mut`.
-    /// Use a direct reference to the state instead.
-    /// This function may also not be async since holding a borrow across
-    /// `await` points will interfere with other borrowers.
-    fn with_state<F, T>(&self, f: F) -> T
-    where
-        F: FnOnce(&CompilationState) -> T,
-    {
-        let state = self.state.borrow();
-        f(&state)
-    }
-
-    /// Borrows the compilation state immutably and invokes `f`.
+    /// Borrows the compilation state mutably and invokes `f`.
     /// Warning: This function is not reentrant.  For dynamic borrow safety,
-    /// don't call `with_state_mut` from within `with_state` or `with_state_mut`.
+    /// don't call `with_state_mut` from within `with_state_mut`.
     /// Use a direct reference to the state instead.
     /// This function may also not be async since holding a borrow across
     /// `await` points will interfere with other borrowers.
@@ -462,6 +501,17 @@ impl<'a> CompilationStateUpdater<'a> {
 }
 
 impl CompilationState {
+    pub(crate) fn get_suppressed_diagnostics(&self, uri: &str) -> Vec<Error> {
+        self.suppressed_diagnostics
+            .get(uri)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn cost_annotations(&self) -> bool {
+        self.cost_annotations
+    }
+
     pub(crate) fn get_compilation(&self, uri: &str) -> Option<&Compilation> {
         let Some(compilation_uri) = &self
             .open_documents
@@ -518,5 +568,6 @@ fn merge_configurations(
         package_type: compilation_overrides
             .package_type
             .unwrap_or(workspace_scope.package_type),
+        max_diagnostics: workspace_scope.max_diagnostics,
     }
 }
@@ -150,6 +150,8 @@ async fn close_last_doc_in_project() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
         &expect![[r#"
@@ -313,6 +315,8 @@ async fn package_type_update_causes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: None,
         package_type: Some(PackageType::Lib),
+        max_diagnostics: None,
+        cost_annotations: None,
     });
 
     updater
@@ -333,6 +337,8 @@ async fn package_type_update_causes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: None,
         package_type: Some(PackageType::Exe),
+        max_diagnostics: None,
+        cost_annotations: None,
     });
 
     expect_errors(
@@ -357,6 +363,58 @@ async fn package_type_update_causes_error() {
     );
 }
 
+#[tokio::test]
+async fn errors_over_maximum_are_suppressed() {
+    let errors = RefCell::new(Vec::new());
+    let mut updater = new_updater(&errors);
+
+    updater.update_configuration(WorkspaceConfigurationUpdate {
+        target_profile: None,
+        package_type: Some(PackageType::Lib),
+        max_diagnostics: Some(1),
+        cost_annotations: None,
+    });
+
+    updater
+        .update_document(
+            "single/foo.qs",
+            1,
+            "namespace Foo { function A() : Int { B() } function C() : Int { D() } }",
+        )
+        .await;
+
+    let suppressed = {
+        let published = errors.borrow();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].2.len(), 1);
+        updater
+            .state
+            .borrow()
+            .get_suppressed_diagnostics("single/foo.qs")
+    };
+    assert!(!suppressed.is_empty());
+    errors.borrow_mut().clear();
+
+    // Raising the maximum republishes all the errors without recompiling.
+    updater.update_configuration(WorkspaceConfigurationUpdate {
+        target_profile: None,
+        package_type: None,
+        max_diagnostics: Some(100),
+        cost_annotations: None,
+    });
+
+    {
+        let published = errors.borrow();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].2.len(), suppressed.len() + 1);
+    }
+    assert!(updater
+        .state
+        .borrow()
+        .get_suppressed_diagnostics("single/foo.qs")
+        .is_empty());
+}
+
 #[tokio::test]
 async fn target_profile_update_fixes_error() {
     let errors = RefCell::new(Vec::new());
@@ -365,6 +423,8 @@ async fn target_profile_update_fixes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Base),
         package_type: Some(PackageType::Lib),
+        max_diagnostics: None,
+        cost_annotations: None,
     });
 
     updater
@@ -424,6 +484,8 @@ async fn target_profile_update_fixes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Unrestricted),
         package_type: None,
+        max_diagnostics: None,
+        cost_annotations: None,
     });
 
     expect_errors(
@@ -463,6 +525,8 @@ async fn target_profile_update_causes_error_in_stdlib() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Base),
         package_type: None,
+        max_diagnostics: None,
+        cost_annotations: None,
     });
 
     expect_errors(
@@ -796,6 +860,8 @@ async fn update_doc_updates_project() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
         &expect![[r#"
@@ -886,6 +952,8 @@ async fn close_doc_prioritizes_fs() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
         &expect![[r#"
@@ -965,6 +1033,8 @@ async fn delete_manifest() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1000,6 +1070,8 @@ async fn delete_manifest() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1044,6 +1116,8 @@ async fn delete_manifest_then_close() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1105,6 +1179,8 @@ async fn doc_switches_project() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1155,6 +1231,8 @@ async fn doc_switches_project() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1204,6 +1282,8 @@ async fn doc_switches_project_on_close() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -1247,6 +1327,8 @@ async fn doc_switches_project_on_close() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
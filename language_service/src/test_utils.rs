@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::sync::Arc;
+use std::{cell::RefCell, sync::Arc};
 
 use crate::compilation::{Compilation, CompilationKind};
 use qsc::{
@@ -85,6 +85,7 @@ fn compile_project_with_fake_stdlib_and_markers_cursor_optional(
             user_package_id: package_id,
             kind: CompilationKind::OpenProject,
             errors,
+            cost_estimates: RefCell::default(),
         },
         cursor_location,
         target_spans,
@@ -148,6 +149,7 @@ where
         user_package_id: package_id,
         errors,
         kind: CompilationKind::Notebook,
+        cost_estimates: RefCell::default(),
     }
 }
 
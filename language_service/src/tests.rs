@@ -53,6 +53,8 @@ async fn single_document() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]]),
     );
@@ -100,6 +102,8 @@ async fn single_document_update() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]]),
     );
@@ -138,6 +142,8 @@ async fn single_document_update() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]]),
     );
@@ -198,6 +204,8 @@ async fn document_in_project() {
                     },
                 ],
                 entry: None,
+                features: [],
+                edition: Q2023,
             }
         "#]],
     );
@@ -265,6 +273,59 @@ async fn completions_requested_after_document_load() {
     );
 }
 
+#[tokio::test]
+async fn deprecated_alias_is_reported() {
+    let received_errors = RefCell::new(Vec::new());
+    let mut ls = LanguageService::new(Encoding::Utf8);
+    let mut worker = create_update_worker(&mut ls, &received_errors);
+
+    ls.update_document(
+        "foo.qs",
+        1,
+        "namespace Foo { @Alias(\"Old.A\") function A() : Unit {} @EntryPoint() function Main() : Unit { Old.A(); } }",
+    );
+
+    worker.apply_pending().await;
+
+    expect![[r#"
+        [
+            (
+                "foo.qs",
+                Some(
+                    1,
+                ),
+                [
+                    Frontend(
+                        Error(
+                            Resolve(
+                                DeprecatedAlias(
+                                    "Old.A",
+                                    "Foo.A",
+                                    Span {
+                                        lo: 94,
+                                        hi: 99,
+                                    },
+                                ),
+                            ),
+                        ),
+                    ),
+                ],
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&received_errors.borrow());
+}
+
+#[test]
+fn diagnostic_codes_are_explained() {
+    let ls = LanguageService::new(Encoding::Utf8);
+    let explanation = ls
+        .get_diagnostic_explanation("Qsc.TypeCk.MissingFunctor")
+        .expect("code should be explained");
+    assert!(explanation.starts_with("# Qsc.TypeCk.MissingFunctor\n"));
+    assert_eq!(ls.get_diagnostic_explanation("Qsc.Unknown.Code"), None);
+}
+
 fn check_errors_and_compilation(
     ls: &LanguageService,
     received_errors: &mut Vec<(String, Option<u32>, Vec<ErrorKind>)>,
@@ -21,6 +21,8 @@ pub const STD_LIB: &[(&str, &str)] = &[
     ("convert.qs", include_str!("../std/convert.qs")),
     ("core.qs", include_str!("../std/core.qs")),
     ("diagnostics.qs", include_str!("../std/diagnostics.qs")),
+    ("errors.qs", include_str!("../std/errors.qs")),
+    ("host.qs", include_str!("../std/host.qs")),
     ("internal.qs", include_str!("../std/internal.qs")),
     ("intrinsic.qs", include_str!("../std/intrinsic.qs")),
     ("logical.qs", include_str!("../std/logical.qs")),
@@ -37,6 +39,10 @@ pub const STD_LIB: &[(&str, &str)] = &[
         "unstable_arithmetic_internal.qs",
         include_str!("../std/unstable_arithmetic_internal.qs"),
     ),
+    (
+        "unstable_phase_estimation.qs",
+        include_str!("../std/unstable_phase_estimation.qs"),
+    ),
     (
         "unstable_state_preparation.qs",
         include_str!("../std/unstable_state_preparation.qs"),
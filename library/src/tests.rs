@@ -9,9 +9,11 @@ mod canon;
 mod convert;
 mod core;
 mod diagnostics;
+mod errors;
 mod logical;
 mod math;
 mod measurement;
+mod phase_estimation;
 mod state_preparation;
 mod table_lookup;
 
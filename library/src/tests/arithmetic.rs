@@ -331,6 +331,50 @@ fn check_fourier_td_inc_by_le_exhaustive_bitwidth_3() {
     );
 }
 
+//
+// LookAheadDKRSIncByLE
+//
+
+#[test]
+fn check_lookahead_dkrs_inc_by_le_exhaustive_bitwidth_1() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check LookAheadDKRSIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSIncByLE, 1)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_lookahead_dkrs_inc_by_le_exhaustive_bitwidth_2() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check LookAheadDKRSIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSIncByLE, 2)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_lookahead_dkrs_inc_by_le_exhaustive_bitwidth_3() {
+    test_expression_with_lib(
+        "Test.TestIncByLE(\"Check LookAheadDKRSIncByLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSIncByLE, 3)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_lookahead_dkrs_inc_by_le_ctl_exhaustive_bitwidth_3() {
+    test_expression_with_lib(
+        "Test.TestIncByLECtl(\"Check LookAheadDKRSIncByLE(Ctl)\",
+            Microsoft.Quantum.Unstable.Arithmetic.LookAheadDKRSIncByLE, 3)",
+        INC_BY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
 //
 // IncByLEUsingAddLE
 //
@@ -848,6 +892,57 @@ fn check_lookahead_dkrs_add_le_general() {
     );
 }
 
+// ============================ Multipliers ============================
+
+const MULTIPLY_LE_TEST_LIB: &str = include_str!("resources/multiply_le.qs");
+
+#[test]
+fn check_multiply_le_exhaustive() {
+    test_expression_with_lib(
+        "Test.TestMultiplyLE(\"Check MultiplyLE\",
+            Microsoft.Quantum.Unstable.Arithmetic.MultiplyLE, 2, 2, 3)",
+        MULTIPLY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_multiply_le_using_inc_by_le_exhaustive() {
+    test_expression_with_lib(
+        "{open Microsoft.Quantum.Unstable.Arithmetic;
+            Test.TestMultiplyLE(\"Check MultiplyLEUsingIncByLE\",
+                MultiplyLEUsingIncByLE(RippleCarryCGIncByLE,_,_,_),
+                3, 2, 4)}",
+        MULTIPLY_LE_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_multiply_le_general() {
+    test_expression(
+        {
+            "{  // General cases for MultiplyLE
+                open Microsoft.Quantum.Measurement;
+                open Microsoft.Quantum.Unstable.Arithmetic;
+
+                use x1 = Qubit[6];
+                use y1 = Qubit[6];
+                use z1 = Qubit[12];
+                ApplyXorInPlace(43, x1);
+                ApplyXorInPlace(58, y1);
+                ApplyXorInPlace(7, z1);
+                MultiplyLE(x1,y1,z1);
+                let i1 = MeasureInteger(z1);
+                ResetAll(x1+y1+z1);
+
+                return i1;
+        }"
+        },
+        &Value::Int(7 + 43 * 58),
+    );
+}
+
 const COMPARE_TEST_LIB: &str = include_str!("resources/compare.qs");
 
 #[test]
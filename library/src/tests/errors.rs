@@ -0,0 +1,95 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::test_expression_with_lib;
+use qsc::interpret::Value;
+
+// Tests for Microsoft.Quantum.Errors namespace
+
+const LIB: &str = "
+    namespace Test {
+        open Microsoft.Quantum.Errors;
+
+        function SafeDivide(a : Int, b : Int) : (Int[], ErrorInfo[]) {
+            if b == 0 {
+                return Failure(1, \"division by zero\");
+            }
+            Success(a / b)
+        }
+
+        function Average(total : Int, count : Int, scale : Int) : (Int[], ErrorInfo[]) {
+            let (mean, errors) = SafeDivide(total, count);
+            if Length(errors) > 0 {
+                return ([], errors);
+            }
+            SafeDivide(mean[0], scale)
+        }
+
+        operation FlipAndCheck(q : Qubit) : (Int[], ErrorInfo[]) {
+            X(q);
+            M(q) == Zero ? Success(1) | Failure(2, \"qubit is one\")
+        }
+    }
+";
+
+#[test]
+fn check_and_then_chains_successes() {
+    test_expression_with_lib(
+        "Microsoft.Quantum.Errors.ValueOr(-1, Microsoft.Quantum.Errors.AndThen(q -> Test.SafeDivide(q, 2), Test.SafeDivide(12, 3)))",
+        LIB,
+        &Value::Int(2),
+    );
+}
+
+#[test]
+fn check_and_then_propagates_errors() {
+    test_expression_with_lib(
+        "Microsoft.Quantum.Errors.ValueOr(-1, Microsoft.Quantum.Errors.AndThen(q -> Test.SafeDivide(q, 2), Test.SafeDivide(12, 0)))",
+        LIB,
+        &Value::Int(-1),
+    );
+}
+
+#[test]
+fn check_early_return_propagates_errors() {
+    test_expression_with_lib(
+        "(Microsoft.Quantum.Errors.ValueOf(Test.Average(12, 3, 2)), Microsoft.Quantum.Errors.ErrorOf(Test.Average(12, 0, 2))::Message)",
+        LIB,
+        &Value::Tuple(vec![Value::Int(2), Value::String("division by zero".into())].into()),
+    );
+}
+
+#[test]
+fn check_error_of() {
+    test_expression_with_lib(
+        "Microsoft.Quantum.Errors.ErrorOf(Test.SafeDivide(1, 0))::Message",
+        LIB,
+        &Value::String("division by zero".into()),
+    );
+}
+
+#[test]
+fn check_map_success() {
+    test_expression_with_lib(
+        "Microsoft.Quantum.Errors.ValueOf(Microsoft.Quantum.Errors.MapSuccess(x -> x * 10, Test.SafeDivide(6, 3)))",
+        LIB,
+        &Value::Int(20),
+    );
+}
+
+#[test]
+fn check_retry_until_success() {
+    test_expression_with_lib(
+        "{
+            open Microsoft.Quantum.Errors;
+            use q = Qubit();
+            let once = IsSuccess(Retry(1, () => Test.FlipAndCheck(q)));
+            Reset(q);
+            let twice = IsSuccess(Retry(2, () => Test.FlipAndCheck(q)));
+            Reset(q);
+            (once, twice)
+        }",
+        LIB,
+        &Value::Tuple(vec![Value::Bool(false), Value::Bool(true)].into()),
+    );
+}
@@ -0,0 +1,51 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::test_expression_with_lib;
+use qsc::interpret::Value;
+
+// Tests for Microsoft.Quantum.Unstable.PhaseEstimation namespace
+
+const PHASE_ESTIMATION_TEST_LIB: &str = include_str!("resources/phase_estimation.qs");
+
+#[test]
+fn check_estimate_phase_exhaustive_precision_1() {
+    test_expression_with_lib(
+        "Test.TestEstimatePhase(1)",
+        PHASE_ESTIMATION_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_estimate_phase_exhaustive_precision_3() {
+    test_expression_with_lib(
+        "Test.TestEstimatePhase(3)",
+        PHASE_ESTIMATION_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_estimate_amplitude() {
+    test_expression_with_lib(
+        "Test.TestEstimateAmplitude()",
+        PHASE_ESTIMATION_TEST_LIB,
+        &Value::Tuple(
+            vec![
+                Value::Int(1),
+                Value::Double((std::f64::consts::PI / 8.0).sin().powi(2)),
+            ]
+            .into(),
+        ),
+    );
+}
+
+#[test]
+fn check_phase_from_estimate() {
+    test_expression_with_lib(
+        "Microsoft.Quantum.Unstable.PhaseEstimation.PhaseFromEstimate(3, 5)",
+        "",
+        &Value::Double(0.625),
+    );
+}
@@ -52,3 +52,48 @@ fn check_select_fuzz() {
         &Value::Tuple(vec![].into()),
     );
 }
+
+#[test]
+fn check_select_swap_one_swap_bit() {
+    test_expression_with_lib(
+        "Test.TestSelectSwap(2, 3, 6)",
+        SELECT_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_select_swap_two_swap_bits() {
+    test_expression_with_lib(
+        "Test.TestSelectSwap(3, 2, 8)",
+        SELECT_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_select_swap_without_budget_is_select() {
+    test_expression_with_lib(
+        "Test.TestSelectSwap(3, 2, 3)",
+        SELECT_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_balanced_select_swap() {
+    test_expression_with_lib(
+        "Test.TestBalancedSelectSwap(4, 2)",
+        SELECT_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
+
+#[test]
+fn check_lookup() {
+    test_expression_with_lib(
+        "Test.TestGenericLookup(3, 2)",
+        SELECT_TEST_LIB,
+        &Value::Tuple(vec![].into()),
+    );
+}
@@ -103,7 +103,9 @@ export async function getProjectLoader(
   loadDirectory: (path: string) => Promise<[string, number][]>,
   getManifest: (path: string) => Promise<{
     manifestDirectory: string;
-  } | null>,
+    features?: string[];
+    edition?: string;
+  ,
 ): Promise<wasm.ProjectLoader> {
   await instantiateWasm();
   return new wasm.ProjectLoader(readFile, loadDirectory, getManifest);
@@ -179,7 +181,9 @@ export async function getLanguageService(
   listDir?: (uri: string) => Promise<[string, number][]>,
   getManifest?: (uri: string) => Promise<{
     manifestDirectory: string;
-  } | null>,
+    features?: string[];
+    edition?: string;
+  ,
 ): Promise<ILanguageService> {
   await instantiateWasm();
   return new QSharpLanguageService(wasm, readFile, listDir, getManifest);
@@ -21,6 +21,8 @@ interface DumpMsg {
 interface MessageMsg {
   type: "Message";
   message: string;
+  // The channel the message was sent to with `MessageTo`, if any.
+  channel?: string;
 }
 
 interface ResultMsg {
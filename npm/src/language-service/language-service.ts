@@ -5,9 +5,11 @@ import type {
   ICodeLens,
   ICompletionList,
   IHover,
+  IInlayHint,
   ILocation,
   INotebookMetadata,
   IPosition,
+  IRange,
   ISignatureHelp,
   ITextEdit,
   IWorkspaceConfiguration,
@@ -77,6 +79,10 @@ export interface ILanguageService {
     position: IPosition,
   ): Promise<ITextEdit | undefined>;
   getCodeLenses(documentUri: string): Promise<ICodeLens[]>;
+  getInlayHints(documentUri: string): Promise<IInlayHint[]>;
+  getInactiveCode(documentUri: string): Promise<IRange[]>;
+  getSuppressedDiagnostics(documentUri: string): Promise<VSDiagnostic[]>;
+  getDiagnosticExplanation(code: string): Promise<string | undefined>;
 
   dispose(): Promise<void>;
 
@@ -112,7 +118,9 @@ export class QSharpLanguageService implements ILanguageService {
       Promise.resolve([]),
     getManifest: (uri: string) => Promise<{
       manifestDirectory: string;
-    } | null> = () => Promise.resolve(null),
+      features?: string[];
+      edition?: string;
+     = () => Promise.resolve(null),
   ) {
     log.info("Constructing a QSharpLanguageService instance");
     this.languageService = new wasm.LanguageService();
@@ -215,6 +223,22 @@ export class QSharpLanguageService implements ILanguageService {
     return this.languageService.get_code_lenses(documentUri);
   }
 
+  async getInlayHints(documentUri: string): Promise<IInlayHint[]> {
+    return this.languageService.get_inlay_hints(documentUri);
+  }
+
+  async getInactiveCode(documentUri: string): Promise<IRange[]> {
+    return this.languageService.get_inactive_code(documentUri);
+  }
+
+  async getSuppressedDiagnostics(documentUri: string): Promise<VSDiagnostic[]> {
+    return this.languageService.get_suppressed_diagnostics(documentUri);
+  }
+
+  async getDiagnosticExplanation(code: string): Promise<string | undefined> {
+    return this.languageService.get_diagnostic_explanation(code);
+  }
+
   async dispose() {
     this.languageService.stop_background_work();
     await this.backgroundWork;
@@ -25,6 +25,10 @@ const requests: MethodMap<ILanguageService> = {
   getRename: "request",
   prepareRename: "request",
   getCodeLenses: "request",
+  getInlayHints: "request",
+  getInactiveCode: "request",
+  getSuppressedDiagnostics: "request",
+  getDiagnosticExplanation: "request",
   dispose: "request",
   addEventListener: "addEventListener",
   removeEventListener: "removeEventListener",
@@ -59,7 +59,9 @@ export function getProjectLoader(
   loadDirectory: (path: string) => Promise<[string, number][]>,
   getManifest: (path: string) => Promise<{
     manifestDirectory: string;
-  } | null>,
+    features?: string[];
+    edition?: string;
+  ,
 ): ProjectLoader {
   if (!wasm) {
     wasm = require("../lib/node/qsc_wasm.cjs") as Wasm;
@@ -118,7 +120,9 @@ export function getLanguageService(
   listDir?: (uri: string) => Promise<[string, number][]>,
   getManifest?: (uri: string) => Promise<{
     manifestDirectory: string;
-  } | null>,
+    features?: string[];
+    edition?: string;
+  ,
 ): ILanguageService {
   if (!wasm) wasm = require("../lib/node/qsc_wasm.cjs") as Wasm;
   return new QSharpLanguageService(wasm, readFile, listDir, getManifest);
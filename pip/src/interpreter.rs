@@ -10,13 +10,14 @@ use num_bigint::BigUint;
 use num_complex::Complex64;
 use pyo3::{
     create_exception,
-    exceptions::PyException,
+    exceptions::{PyException, PyUserWarning},
     prelude::*,
     pyclass::CompareOp,
     types::PyList,
     types::{PyDict, PyString, PyTuple},
 };
 use qsc::{
+    edition::Edition,
     fir,
     interpret::{
         self,
@@ -80,11 +81,19 @@ impl FromPyObject<'_> for PyManifestDescriptor {
                 "missing key `manifest` in manifest descriptor",
             ))?
             .downcast::<PyDict>()?;
+        let edition = match get_dict_opt_string(manifest, "edition")? {
+            Some(edition) => edition.parse().map_err(|()| {
+                PyException::new_err(format!("unknown edition `{edition}` in manifest"))
+            })?,
+            None => Edition::default(),
+        };
 
         Ok(Self(ManifestDescriptor {
             manifest: Manifest {
                 author: get_dict_opt_string(manifest, "author")?,
                 license: get_dict_opt_string(manifest, "license")?,
+                features: get_dict_opt_string_list(manifest, "features")?.unwrap_or_default(),
+                edition,
             },
             manifest_dir: manifest_dir.into(),
         }))
@@ -121,12 +130,17 @@ impl Interpreter {
             .load_project(&manifest_descriptor.0)
             .map_py_err()?;
             SourceMap::new(project.sources, None)
+                .with_features(project.manifest.features)
+                .with_edition(project.manifest.edition)
         } else {
             SourceMap::default()
         };
 
         match interpret::Interpreter::new(true, sources, PackageType::Lib, target.into()) {
-            Ok(interpreter) => Ok(Self { interpreter }),
+            Ok(mut interpreter) => {
+                issue_warnings(py, &mut interpreter)?;
+                Ok(Self { interpreter })
+            }
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
     }
@@ -146,7 +160,9 @@ impl Interpreter {
         callback: Option<PyObject>,
     ) -> PyResult<PyObject> {
         let mut receiver = OptionalCallbackReceiver { callback, py };
-        match self.interpreter.eval_fragments(&mut receiver, input) {
+        let result = self.interpreter.eval_fragments(&mut receiver, input);
+        issue_warnings(py, &mut self.interpreter)?;
+        match result {
             Ok(value) => Ok(ValueWrapper(value).into_py(py)),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
@@ -177,7 +193,9 @@ impl Interpreter {
         callback: Option<PyObject>,
     ) -> PyResult<PyObject> {
         let mut receiver = OptionalCallbackReceiver { callback, py };
-        match self.interpreter.run(&mut receiver, entry_expr) {
+        let result = self.interpreter.run(&mut receiver, entry_expr);
+        issue_warnings(py, &mut self.interpreter)?;
+        match result {
             Ok(result) => match result {
                 Ok(v) => Ok(ValueWrapper(v).into_py(py)),
                 Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
@@ -236,6 +254,20 @@ create_exception!(
     "An error returned from the Q# interpreter."
 );
 
+/// Issues a Python warning for each compiler warning, such as a use of a deprecated alias, that
+/// the interpreter hasn't reported yet.
+fn issue_warnings(py: Python, interpreter: &mut interpret::Interpreter) -> PyResult<()> {
+    for warning in interpreter.take_warnings() {
+        PyErr::warn(
+            py,
+            py.get_type::<PyUserWarning>(),
+            &format_errors(vec![warning]),
+            1,
+        )?;
+    }
+    Ok(())
+}
+
 fn format_errors(errors: Vec<interpret::Error>) -> String {
     errors
         .into_iter()
@@ -535,3 +567,16 @@ fn get_dict_opt_string(dict: &PyDict, key: &str) -> PyResult<Option<String>> {
         None => None,
     })
 }
+
+fn get_dict_opt_string_list(dict: &PyDict, key: &str) -> PyResult<Option<Vec<String>>> {
+    let value = dict.get_item(key)?;
+    Ok(match value {
+        Some(item) => Some(
+            item.downcast::<PyList>()?
+                .iter()
+                .map(|item| Ok(item.downcast::<PyString>()?.to_string_lossy().into()))
+                .collect::<PyResult<_>>()?,
+        ),
+        None => None,
+    })
+}
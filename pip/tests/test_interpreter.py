@@ -49,6 +49,15 @@ def test_dump_output() -> None:
     )
     assert called
 
+def test_deprecated_alias_warns() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        'namespace Current { @Alias("Legacy.Old") function New() : Int { 1 } }'
+    )
+    with pytest.warns(UserWarning, match="deprecated alias"):
+        value = e.interpret("Legacy.Old()")
+    assert value == 1
+
 def test_quantum_seed() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.set_quantum_seed(42)
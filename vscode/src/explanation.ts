@@ -0,0 +1,65 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { qsharpLanguageId } from "./common";
+
+const showExplanationCommand = "qsharp-vscode.showDiagnosticExplanation";
+
+/**
+ * Offers a "Show full explanation" quick fix on each Q# diagnostic whose code has a long-form
+ * explanation, which opens the explanation as Markdown beside the editor.
+ */
+export function startShowingExplanations(
+  languageService: ILanguageService,
+): vscode.Disposable[] {
+  async function showExplanation(code: string) {
+    const explanation = await languageService.getDiagnosticExplanation(code);
+    if (!explanation) {
+      return;
+    }
+    const document = await vscode.workspace.openTextDocument({
+      language: "markdown",
+      content: explanation,
+    });
+    await vscode.commands.executeCommand(
+      "markdown.showPreviewToSide",
+      document.uri,
+    );
+  }
+
+  const provider: vscode.CodeActionProvider = {
+    async provideCodeActions(document, range, context) {
+      const actions = [];
+      for (const diagnostic of context.diagnostics) {
+        const code = diagnostic.code;
+        if (typeof code !== "string") {
+          continue;
+        }
+        if (!(await languageService.getDiagnosticExplanation(code))) {
+          continue;
+        }
+        const action = new vscode.CodeAction(
+          `Show full explanation of ${code}`,
+          vscode.CodeActionKind.QuickFix,
+        );
+        action.diagnostics = [diagnostic];
+        action.command = {
+          command: showExplanationCommand,
+          title: action.title,
+          arguments: [code],
+        };
+        actions.push(action);
+      }
+      return actions;
+    },
+  };
+
+  return [
+    vscode.commands.registerCommand(showExplanationCommand, showExplanation),
+    vscode.languages.registerCodeActionsProvider(qsharpLanguageId, provider, {
+      providedCodeActionKinds: [vscode.CodeActionKind.QuickFix],
+    }),
+  ];
+}
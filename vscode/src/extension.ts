@@ -18,11 +18,14 @@ import {
   qsharpLanguageId,
 } from "./common.js";
 import { createCompletionItemProvider } from "./completion";
-import { getTarget } from "./config";
+import { getCostAnnotations, getTarget } from "./config";
 import { activateDebugger } from "./debugger/activate";
 import { createDefinitionProvider } from "./definition";
 import { startCheckingQSharp } from "./diagnostics";
+import { startShowingExplanations } from "./explanation";
 import { createHoverProvider } from "./hover";
+import { createInlayHintsProvider } from "./inlayHints";
+import { startShowingInactiveCode } from "./inactiveCode";
 import {
   Logging,
   initLogForwarder,
@@ -158,6 +161,12 @@ async function activateLanguageService(extensionUri: vscode.Uri) {
   // diagnostics
   subscriptions.push(...startCheckingQSharp(languageService));
 
+  // inactive code
+  subscriptions.push(...startShowingInactiveCode(languageService));
+
+  // explanations of diagnostics
+  subscriptions.push(...startShowingExplanations(languageService));
+
   // synchronize document contents
   subscriptions.push(...registerDocumentUpdateHandlers(languageService));
 
@@ -228,6 +237,14 @@ async function activateLanguageService(extensionUri: vscode.Uri) {
     ),
   );
 
+  // inlay hints
+  subscriptions.push(
+    vscode.languages.registerInlayHintsProvider(
+      qsharpLanguageId,
+      createInlayHintsProvider(languageService),
+    ),
+  );
+
   // add the language service dispose handler as well
   subscriptions.push(languageService);
 
@@ -251,6 +268,14 @@ async function updateLanguageServiceProfile(languageService: ILanguageService) {
   });
 }
 
+async function updateLanguageServiceCostAnnotations(
+  languageService: ILanguageService,
+) {
+  languageService.updateConfiguration({
+    costAnnotations: getCostAnnotations(),
+  });
+}
+
 async function loadLanguageService(baseUri: vscode.Uri) {
   const start = performance.now();
   const wasmUri = vscode.Uri.joinPath(baseUri, "./wasm/qsc_wasm_bg.wasm");
@@ -262,6 +287,7 @@ async function loadLanguageService(baseUri: vscode.Uri) {
     getManifest,
   );
   await updateLanguageServiceProfile(languageService);
+  await updateLanguageServiceCostAnnotations(languageService);
   const end = performance.now();
   sendTelemetryEvent(
     EventType.LoadLanguageService,
@@ -278,6 +304,9 @@ function registerConfigurationChangeHandlers(
     if (event.affectsConfiguration("Q#.targetProfile")) {
       updateLanguageServiceProfile(languageService);
     }
+    if (event.affectsConfiguration("Q#.costAnnotations")) {
+      updateLanguageServiceCostAnnotations(languageService);
+    }
   });
 }
 
@@ -0,0 +1,49 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { isQsharpDocument, toVscodeRange } from "./common";
+
+/**
+ * Greys out the code that conditional compilation leaves out, such as items whose
+ * `@Config(feature = "...")` attribute names a feature that the project manifest doesn't enable.
+ */
+export function startShowingInactiveCode(
+  languageService: ILanguageService,
+): vscode.Disposable[] {
+  const decoration = vscode.window.createTextEditorDecorationType({
+    opacity: "0.5",
+  });
+
+  async function update(editor: vscode.TextEditor | undefined) {
+    if (!editor || !isQsharpDocument(editor.document)) {
+      return;
+    }
+    const ranges = await languageService.getInactiveCode(
+      editor.document.uri.toString(),
+    );
+    editor.setDecorations(decoration, ranges.map(toVscodeRange));
+  }
+
+  // Diagnostics are published after every compilation, so the inactive code is refreshed then.
+  async function onDiagnostics(evt: { detail: { uri: string } }) {
+    for (const editor of vscode.window.visibleTextEditors) {
+      if (editor.document.uri.toString() === evt.detail.uri) {
+        await update(editor);
+      }
+    }
+  }
+
+  languageService.addEventListener("diagnostics", onDiagnostics);
+
+  return [
+    {
+      dispose: () => {
+        languageService.removeEventListener("diagnostics", onDiagnostics);
+      },
+    },
+    vscode.window.onDidChangeActiveTextEditor(update),
+    decoration,
+  ];
+}
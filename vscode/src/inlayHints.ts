@@ -0,0 +1,37 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { IInlayHint } from "../../npm/lib/web/qsc_wasm";
+
+export function createInlayHintsProvider(languageService: ILanguageService) {
+  return new QSharpInlayHintsProvider(languageService);
+}
+
+class QSharpInlayHintsProvider implements vscode.InlayHintsProvider {
+  constructor(public languageService: ILanguageService) {}
+
+  async provideInlayHints(
+    document: vscode.TextDocument,
+    range: vscode.Range,
+  ): Promise<vscode.InlayHint[]> {
+    const inlayHints = await this.languageService.getInlayHints(
+      document.uri.toString(),
+    );
+
+    return inlayHints
+      .map((hint) => mapInlayHint(hint))
+      .filter((hint) => range.contains(hint.position));
+  }
+}
+
+function mapInlayHint(hint: IInlayHint): vscode.InlayHint {
+  const inlayHint = new vscode.InlayHint(
+    new vscode.Position(hint.position.line, hint.position.character),
+    hint.label,
+  );
+  inlayHint.paddingLeft = true;
+  inlayHint.tooltip = "Static estimate of the gates and qubits used";
+  return inlayHint;
+}
@@ -13,16 +13,22 @@ import { updateQSharpJsonDiagnostics } from "./diagnostics";
  */
 export async function getManifest(uri: string): Promise<{
   manifestDirectory: string;
-} | null> {
+  features?: string[];
+  edition?: string;
+ {
   const manifestDocument = await findManifestDocument(uri);
 
   if (manifestDocument === null) {
     return null;
   }
 
+  let features;
+  let edition;
   try {
     updateQSharpJsonDiagnostics(manifestDocument.uri);
-    JSON.parse(manifestDocument.content);
+    const manifest = JSON.parse(manifestDocument.content);
+    features = manifestFeatures(manifest);
+    edition = manifestEdition(manifest);
   } catch (e) {
     log.warn(
       `failed to parse manifest at ${manifestDocument.uri.toString()}`,
@@ -39,9 +45,25 @@ export async function getManifest(uri: string): Promise<{
 
   return {
     manifestDirectory: manifestDirectory.toString(),
+    features,
+    edition,
   };
 }
 
+/** Returns the feature flags enabled in a parsed manifest, if it lists any. */
+function manifestFeatures(manifest: any): string[] | undefined {
+  const features = manifest?.features;
+  return Array.isArray(features)
+    ? features.filter((feature) => typeof feature === "string")
+    : undefined;
+}
+
+/** Returns the language edition named in a parsed manifest, if it names one. */
+function manifestEdition(manifest: any): string | undefined {
+  const edition = manifest?.edition;
+  return typeof edition === "string" ? edition : undefined;
+}
+
 /** Returns the manifest document if one is found
  * returns null otherwise
  */
@@ -169,12 +191,18 @@ async function readFileUri(
 
 async function getManifestThrowsOnParseFailure(uri: string): Promise<{
   manifestDirectory: string;
-} | null> {
+  features?: string[];
+  edition?: string;
+ {
   const manifestDocument = await findManifestDocument(uri);
 
   if (manifestDocument) {
+    let features;
+    let edition;
     try {
-      JSON.parse(manifestDocument.content); // will throw if invalid
+      const manifest = JSON.parse(manifestDocument.content); // will throw if invalid
+      features = manifestFeatures(manifest);
+      edition = manifestEdition(manifest);
     } catch (e: any) {
       updateQSharpJsonDiagnostics(
         manifestDocument.uri,
@@ -191,6 +219,8 @@ async function getManifestThrowsOnParseFailure(uri: string): Promise<{
 
     return {
       manifestDirectory: manifestDirectory.toString(),
+      features,
+      edition,
     };
   }
   return null;
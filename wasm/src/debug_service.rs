@@ -4,7 +4,7 @@
 use std::str::FromStr;
 
 use qsc::fir::StmtId;
-use qsc::interpret::{Debugger, Error, StepAction, StepResult};
+use qsc::interpret::{BreakpointCondition, Debugger, Error, HitCondition, StepAction, StepResult};
 use qsc::line_column::Encoding;
 use qsc::{fmt_complex, target::Profile};
 
@@ -191,19 +191,71 @@ impl DebugService {
         .into()
     }
 
+    pub fn set_breakpoint_condition(
+        &mut self,
+        id: u32,
+        hit_condition: Option<String>,
+        log_message: Option<String>,
+    ) -> Result<(), JsValue> {
+        let hit_condition = match hit_condition {
+            Some(hit_condition) => {
+                Some(HitCondition::from_str(&hit_condition).map_err(|()| {
+                    JsError::new(&format!("Invalid hit condition: {hit_condition}"))
+                })?)
+            }
+            None => None,
+        };
+        self.debugger_mut().set_breakpoint_condition(
+            StmtId::from(id),
+            BreakpointCondition {
+                hit_condition,
+                log_message: log_message.map(Into::into),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn clear_breakpoint_conditions(&mut self) {
+        self.debugger_mut().clear_breakpoint_conditions();
+    }
+
     pub fn get_locals(&self) -> IVariableList {
-        let locals = self.debugger().get_locals();
-        let variables: Vec<_> = locals
+        let debugger = self.debugger();
+        let variables: Vec<_> = debugger
+            .get_locals()
             .into_iter()
             .map(|local| Variable {
                 name: (*local.name).to_string(),
-                value: local.value.to_string(),
+                value: debugger.display_local(&local),
                 var_type: local.type_name,
             })
             .collect();
         VariableList { variables }.into()
     }
 
+    pub fn eval_in_frame(
+        &mut self,
+        event_cb: &js_sys::Function,
+        expr: &str,
+        allow_mutation: bool,
+    ) -> Result<String, JsValue> {
+        if !event_cb.is_function() {
+            return Err(JsError::new("Events callback function must be provided").into());
+        }
+        let mut out = CallbackReceiver {
+            event_cb: |msg: &str| {
+                let _ = event_cb.call1(&JsValue::null(), &JsValue::from(msg));
+            },
+        };
+        match self
+            .debugger_mut()
+            .eval_in_frame(&mut out, expr, allow_mutation)
+        {
+            Ok(value) => Ok(value.to_string()),
+            Err(e) => Err(JsError::from(&e[0]).into()),
+        }
+    }
+
     fn debugger(&self) -> &Debugger {
         self.debugger
             .as_ref()
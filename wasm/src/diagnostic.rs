@@ -56,6 +56,15 @@ impl VSDiagnostic {
             interpret::Error::Eval(e) => error_labels(e.error()),
             interpret::Error::NoEntryPoint => Vec::new(),
             interpret::Error::UnsupportedRuntimeCapabilities => Vec::new(),
+            interpret::Error::UnsupportedEntryCapabilities(_) => Vec::new(),
+            interpret::Error::NotCallable => Vec::new(),
+            interpret::Error::OperationArgCount(..) | interpret::Error::OperationArgType(..) => {
+                Vec::new()
+            }
+            interpret::Error::Kernel(e) => error_labels(e),
+            interpret::Error::QubitLimit(..) => Vec::new(),
+            interpret::Error::PrecisionLoss(_) => Vec::new(),
+            interpret::Error::TomographyQubits(..) => Vec::new(),
         };
 
         Self::new(labels, source_name, err)
@@ -68,6 +77,22 @@ impl VSDiagnostic {
         Self::new(labels, source_name, err)
     }
 
+    /// Creates an informational [VSDiagnostic] summarizing the errors that were
+    /// left out of a document's diagnostics because they exceed the configured maximum.
+    pub(crate) fn suppressed_summary(count: usize) -> Self {
+        Self {
+            range: qsc::line_column::Range {
+                start: qsc::line_column::Position { line: 0, column: 0 },
+                end: qsc::line_column::Position { line: 0, column: 1 },
+            }
+            .into(),
+            message: format!("suppressed {count} additional errors in this file"),
+            severity: "info".to_string(),
+            code: None,
+            related: Vec::new(),
+        }
+    }
+
     /// Creates a [VSDiagnostic] using the information from a [miette::Diagnostic].
     /// The error message, code and severity are straightforwardly generated,
     /// while mapping label spans is a little trickier.
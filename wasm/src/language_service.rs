@@ -55,11 +55,14 @@ impl LanguageService {
             .clone();
 
         let diagnostics_callback = move |update: DiagnosticUpdate| {
-            let diags = update
+            let mut diags = update
                 .errors
                 .iter()
                 .map(|err| VSDiagnostic::from_compile_error(&update.uri, err))
                 .collect::<Vec<_>>();
+            if update.suppressed > 0 {
+                diags.push(VSDiagnostic::suppressed_summary(update.suppressed));
+            }
             let _ = diagnostics_callback
                 .call3(
                     &JsValue::NULL,
@@ -99,6 +102,8 @@ impl LanguageService {
                     "exe" => PackageType::Exe,
                     _ => panic!("invalid package type"),
                 }),
+                max_diagnostics: config.maxDiagnostics.map(|max| max as usize),
+                cost_annotations: config.costAnnotations,
             })
     }
 
@@ -106,6 +111,20 @@ impl LanguageService {
         self.0.update_document(uri, version, text);
     }
 
+    pub fn get_suppressed_diagnostics(&self, uri: &str) -> JsValue {
+        let diags = self
+            .0
+            .get_suppressed_diagnostics(uri)
+            .iter()
+            .map(|err| VSDiagnostic::from_compile_error(uri, err))
+            .collect::<Vec<_>>();
+        serde_wasm_bindgen::to_value(&diags).expect("conversion to VSDiagnostic should succeed")
+    }
+
+    pub fn get_diagnostic_explanation(&self, code: &str) -> Option<String> {
+        self.0.get_diagnostic_explanation(code)
+    }
+
     pub fn close_document(&mut self, uri: &str) {
         self.0.close_document(uri);
     }
@@ -288,6 +307,72 @@ impl LanguageService {
             })
             .collect()
     }
+
+    pub fn get_code_actions(&self, uri: &str, position: IPosition) -> Vec<ICodeAction> {
+        let position: Position = position.into();
+        let code_actions = self.0.get_code_actions(uri, position.into());
+        code_actions
+            .into_iter()
+            .map(CodeAction::from)
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn get_workspace_code_actions(&self, uri: &str) -> Vec<ICodeAction> {
+        let code_actions = self.0.get_workspace_code_actions(uri);
+        code_actions
+            .into_iter()
+            .map(CodeAction::from)
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn get_inactive_code(&self, uri: &str) -> JsValue {
+        let ranges = self
+            .0
+            .get_inactive_code(uri)
+            .into_iter()
+            .map(Range::from)
+            .collect::<Vec<_>>();
+        serde_wasm_bindgen::to_value(&ranges).expect("conversion to ranges should succeed")
+    }
+
+    pub fn get_inlay_hints(&self, uri: &str) -> Vec<IInlayHint> {
+        let inlay_hints = self.0.get_inlay_hints(uri);
+        inlay_hints
+            .into_iter()
+            .map(|hint| {
+                InlayHint {
+                    position: hint.position.into(),
+                    label: hint.label,
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    pub fn search_signatures(
+        &self,
+        uri: &str,
+        query: &str,
+    ) -> Result<Vec<ISignatureSearchResult>, JsValue> {
+        let results = self
+            .0
+            .search_signatures(uri, query)
+            .map_err(|err| JsValue::from(JsError::new(&err)))?;
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                SignatureSearchResult {
+                    name: result.name,
+                    signature: result.signature,
+                    summary: result.summary,
+                    location: result.location.map(Location::from),
+                }
+                .into()
+            })
+            .collect())
+    }
 }
 
 serializable_type! {
@@ -295,10 +380,14 @@ serializable_type! {
     {
         pub targetProfile: Option<String>,
         pub packageType: Option<String>,
+        pub maxDiagnostics: Option<u32>,
+        pub costAnnotations: Option<bool>,
     },
     r#"export interface IWorkspaceConfiguration {
         targetProfile?: TargetProfile;
         packageType?: "exe" | "lib";
+        maxDiagnostics?: number;
+        costAnnotations?: boolean;
     }"#,
     IWorkspaceConfiguration
 }
@@ -415,6 +504,36 @@ serializable_type! {
     ICodeLens
 }
 
+serializable_type! {
+    InlayHint,
+    {
+        position: Position,
+        label: String,
+    },
+    r#"export interface IInlayHint {
+        position: IPosition;
+        label: string;
+    }"#,
+    IInlayHint
+}
+
+serializable_type! {
+    SignatureSearchResult,
+    {
+        name: String,
+        signature: String,
+        summary: String,
+        location: Option<Location>,
+    },
+    r#"export interface ISignatureSearchResult {
+        name: string;
+        signature: string;
+        summary: string;
+        location?: ILocation;
+    }"#,
+    ISignatureSearchResult
+}
+
 serializable_type! {
     WorkspaceEdit,
     {
@@ -426,6 +545,42 @@ serializable_type! {
     IWorkspaceEdit
 }
 
+serializable_type! {
+    CodeAction,
+    {
+        title: String,
+        edit: WorkspaceEdit,
+    },
+    r#"export interface ICodeAction {
+        title: string;
+        edit: IWorkspaceEdit;
+    }"#,
+    ICodeAction
+}
+
+impl From<qsls::protocol::CodeAction> for CodeAction {
+    fn from(action: qsls::protocol::CodeAction) -> Self {
+        let changes = action
+            .edits
+            .into_iter()
+            .map(|(uri, edits)| {
+                let edits = edits
+                    .into_iter()
+                    .map(|(range, new_text)| TextEdit {
+                        range: range.into(),
+                        newText: new_text,
+                    })
+                    .collect();
+                (uri, edits)
+            })
+            .collect();
+        CodeAction {
+            title: action.title,
+            edit: WorkspaceEdit { changes },
+        }
+    }
+}
+
 serializable_type! {
     Cell,
     {
@@ -202,6 +202,12 @@ where
         (self.event_cb)(&msg_json.to_string());
         Ok(())
     }
+
+    fn message_to(&mut self, channel: &str, msg: &str) -> Result<(), output::Error> {
+        let msg_json = json!({"type": "Message", "message": msg, "channel": channel});
+        (self.event_cb)(&msg_json.to_string());
+        Ok(())
+    }
 }
 
 fn run_internal<F>(sources: SourceMap, event_cb: F, shots: u32) -> Result<(), Box<interpret::Error>>
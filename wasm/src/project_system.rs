@@ -3,6 +3,7 @@
 
 use async_trait::async_trait;
 use js_sys::JsString;
+use qsc::edition::Edition;
 use qsc_project::{EntryType, JSFileEntry, Manifest, ManifestDescriptor, ProjectSystemCallbacks};
 use std::iter::FromIterator;
 use std::{path::PathBuf, sync::Arc};
@@ -23,14 +24,16 @@ extern "C" {
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(typescript_type = "{ manifestDirectory: string }")]
+    #[wasm_bindgen(
+        typescript_type = "{ manifestDirectory: string, features?: string[], edition?: string }"
+    )]
     pub type ManifestDescriptorObject;
 }
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(
-        typescript_type = "(uri: string) => Promise<{ manifestDirectory: string }| null>"
+        typescript_type = "(uri: string) => Promise<{ manifestDirectory: string, features?: string[], edition?: string }| null>"
     )]
     pub type GetManifestCallback;
 }
@@ -171,8 +174,30 @@ pub(crate) fn get_manifest_transformer(js_val: JsValue, _: String) -> Option<Man
 
     let manifest_dir = PathBuf::from(manifest_dir);
 
+    // The enabled features are optional, since the manifest may not declare any.
+    let features = match js_sys::Reflect::get(&js_val, &JsValue::from_str("features")) {
+        Ok(v) if js_sys::Array::is_array(&v) => js_sys::Array::from(&v)
+            .iter()
+            .filter_map(|feature| feature.as_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // The edition is optional too, and projects without a known one use the first edition.
+    let edition = match js_sys::Reflect::get(&js_val, &JsValue::from_str("edition")) {
+        Ok(v) => v.as_string().map_or_else(Edition::default, |edition| {
+            edition.parse().unwrap_or_else(|()| {
+                log::warn!("unknown edition {edition:?} in manifest, using the first edition");
+                Edition::default()
+            })
+        }),
+        Err(_) => Edition::default(),
+    };
+
     Some(ManifestDescriptor {
         manifest: Manifest {
+            features,
+            edition,
             ..Default::default()
         },
         manifest_dir,